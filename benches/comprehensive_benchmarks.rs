@@ -3,6 +3,8 @@ use epcis_knowledge_graph::ontology::reasoner::{OntologyReasoner, Materializatio
 use epcis_knowledge_graph::storage::oxigraph_store::OxigraphStore;
 use epcis_knowledge_graph::models::epcis::EpcisEvent;
 use epcis_knowledge_graph::pipeline::EpcisEventPipeline;
+use epcis_knowledge_graph::ontology::classification::classify_partitioned;
+use epcis_knowledge_graph::ontology::warm_start::{class_hierarchy_closure, CachedAxiom};
 use tempfile::TempDir;
 
 fn benchmark_ontology_reasoner_creation(c: &mut Criterion) {
@@ -187,6 +189,14 @@ fn benchmark_epcis_event_processing(c: &mut Criterion) {
             biz_step: Some("commissioning".to_string()),
             disposition: Some("active".to_string()),
             biz_location: Some("urn:epc:id:sgln:0614141.00777.0".to_string()),
+            source_list: Vec::new(),
+            destination_list: Vec::new(),
+            input_epc_list: Vec::new(),
+            output_epc_list: Vec::new(),
+            ilmd: None,
+            sensor_element_list: Vec::new(),
+            extensions: Default::default(),
+            event_time_zone_offset: None,
         },
         EpcisEvent {
             event_id: "event-002".to_string(),
@@ -200,6 +210,14 @@ fn benchmark_epcis_event_processing(c: &mut Criterion) {
             biz_step: Some("encoding".to_string()),
             disposition: Some("in_progress".to_string()),
             biz_location: Some("urn:epc:id:sgln:0614141.00777.1".to_string()),
+            source_list: Vec::new(),
+            destination_list: Vec::new(),
+            input_epc_list: Vec::new(),
+            output_epc_list: Vec::new(),
+            ilmd: None,
+            sensor_element_list: Vec::new(),
+            extensions: Default::default(),
+            event_time_zone_offset: None,
         },
     ];
     
@@ -344,6 +362,42 @@ fn benchmark_memory_usage(c: &mut Criterion) {
     group.finish();
 }
 
+/// A forest of `components` class trees, each a chain `depth` classes deep with
+/// two leaf classes under every chain class
+fn class_forest(components: usize, depth: usize) -> Vec<CachedAxiom> {
+    let mut axioms = Vec::new();
+    for component in 0..components {
+        for level in 1..depth {
+            let class = |level: usize| format!("urn:ex:c{}:l{}", component, level);
+            axioms.push(CachedAxiom::SubClassOf { sub_class: class(level), super_class: class(level - 1) });
+            for leaf in 0..2 {
+                axioms.push(CachedAxiom::SubClassOf {
+                    sub_class: format!("urn:ex:c{}:l{}:leaf{}", component, level, leaf),
+                    super_class: class(level),
+                });
+            }
+        }
+    }
+    axioms
+}
+
+fn benchmark_classification(c: &mut Criterion) {
+    let mut group = c.benchmark_group("classification");
+    group.sample_size(10);
+    
+    for components in [10, 100, 1000] {
+        let axioms = class_forest(components, 20);
+        group.bench_with_input(BenchmarkId::new("sequential", components), &axioms, |b, axioms| {
+            b.iter(|| black_box(class_hierarchy_closure(axioms)))
+        });
+        group.bench_with_input(BenchmarkId::new("partitioned", components), &axioms, |b, axioms| {
+            b.iter(|| black_box(classify_partitioned(axioms, rayon::current_num_threads())))
+        });
+    }
+    
+    group.finish();
+}
+
 fn benchmark_scalability(c: &mut Criterion) {
     let mut group = c.benchmark_group("scalability");
     
@@ -392,6 +446,7 @@ criterion_group!(
     benchmark_sparql_query_simulation,
     benchmark_statistics_operations,
     benchmark_memory_usage,
-    benchmark_scalability
+    benchmark_scalability,
+    benchmark_classification
 );
 criterion_main!(benches);
\ No newline at end of file
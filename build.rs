@@ -0,0 +1,6 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // The gRPC service is generated from proto/epcis.proto only when enabled
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/epcis.proto")?;
+    Ok(())
+}
@@ -0,0 +1,422 @@
+use crate::config::{AccessControlConfig, ApiKeyAcl};
+use crate::storage::sparql_update::{queried_graphs, read_graphs, written_graphs};
+use crate::utils::masking::PII_GRAPH_PREFIX;
+use crate::EpcisKgError;
+use axum::http::HeaderMap;
+use oxrdf::NamedNode;
+use spargebra::algebra::QueryDataset;
+use spargebra::{Query, SparqlParser};
+use std::collections::HashMap;
+
+/// Header carrying the client's API key
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Kind of access requested on a named graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphAccess {
+    Read,
+    Write,
+}
+
+/// Enforces per-graph ACLs for SPARQL queries and updates.
+///
+/// Reads are restricted by injecting FROM / FROM NAMED clauses for the graphs
+/// the key may read; writes are rejected when they target a graph the key may
+/// not write.
+#[derive(Debug, Clone)]
+pub struct GraphAccessControl {
+    enabled: bool,
+    entries: HashMap<String, ApiKeyAcl>,
+}
+
+impl GraphAccessControl {
+    pub fn from_config(config: &AccessControlConfig) -> Self {
+        let entries = config
+            .api_keys
+            .iter()
+            .map(|acl| (acl.key.clone(), acl.clone()))
+            .collect();
+
+        Self {
+            enabled: config.enabled,
+            entries,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Resolve the ACL entry for an API key; `None` means access control is disabled
+    pub fn authenticate(&self, api_key: Option<&str>) -> Result<Option<&ApiKeyAcl>, EpcisKgError> {
+        if !self.enabled {
+            return Ok(None);
+        }
+
+        let key = api_key.ok_or_else(|| EpcisKgError::AccessDenied("API key required".to_string()))?;
+        self.entries
+            .get(key)
+            .map(Some)
+            .ok_or_else(|| EpcisKgError::AccessDenied("Unknown API key".to_string()))
+    }
+
+    /// Check whether an ACL entry grants the given access to a graph
+    pub fn can_access(acl: &ApiKeyAcl, graph: &str, access: GraphAccess) -> bool {
+        let patterns = match access {
+            GraphAccess::Read => &acl.read_graphs,
+            GraphAccess::Write => &acl.write_graphs,
+        };
+        patterns.iter().any(|pattern| graph_pattern_matches(pattern, graph))
+    }
+
+    /// Rewrite a query so it only sees graphs the API key may read.
+    ///
+    /// The query is parsed, and rejected if it does not parse or names an
+    /// unreadable graph in FROM, FROM NAMED or GRAPH. Queries without a dataset
+    /// get FROM and FROM NAMED clauses for the readable graphs; queries with
+    /// FROM but no FROM NAMED get the readable graphs as FROM NAMED, so
+    /// `GRAPH ?g` never ranges over the whole store.
+    pub fn restrict_query(
+        &self,
+        api_key: Option<&str>,
+        query: &str,
+        available_graphs: &[String],
+    ) -> Result<String, EpcisKgError> {
+        let acl = match self.authenticate(api_key)? {
+            Some(acl) => acl,
+            None => return Ok(query.to_string()),
+        };
+
+        let mut parsed = SparqlParser::new()
+            .parse_query(query)
+            .map_err(|e| EpcisKgError::Query(format!("Invalid SPARQL query: {}", e)))?;
+        if let Some(denied) = queried_graphs(&parsed)
+            .iter()
+            .find(|graph| !Self::can_access(acl, graph, GraphAccess::Read))
+        {
+            return Err(EpcisKgError::AccessDenied(format!(
+                "API key '{}' may not read graph {}",
+                acl.name, denied
+            )));
+        }
+
+        let mut readable: Vec<&String> = available_graphs
+            .iter()
+            .filter(|graph| Self::can_access(acl, graph, GraphAccess::Read))
+            .collect();
+        readable.sort();
+        let readable: Vec<NamedNode> = readable.into_iter().map(|graph| NamedNode::new_unchecked(graph.as_str())).collect();
+
+        let (Query::Select { dataset, .. }
+        | Query::Construct { dataset, .. }
+        | Query::Describe { dataset, .. }
+        | Query::Ask { dataset, .. }) = &mut parsed;
+        match dataset {
+            // The graphs it names were checked above
+            Some(own) => {
+                if own.named.is_none() {
+                    own.named = Some(readable);
+                }
+            }
+            None => {
+                // Nothing is readable: point the query at a graph that never exists
+                let default = if readable.is_empty() {
+                    vec![NamedNode::new_unchecked("urn:epcis:acl:none")]
+                } else {
+                    readable.clone()
+                };
+                *dataset = Some(QueryDataset { default, named: Some(readable) });
+            }
+        }
+        Ok(parsed.to_string())
+    }
+
+    /// Check that every graph targeted by an update is writable by the API key,
+    /// and every graph its `WHERE` clauses read is readable.
+    ///
+    /// Updates without an explicit target graph (or using ALL / DEFAULT) write
+    /// to the whole store and require a `*` write grant; `WHERE` clauses that
+    /// read the default graph, the union of every graph, require a `*` read grant.
+    pub fn check_update(&self, api_key: Option<&str>, update: &str) -> Result<(), EpcisKgError> {
+        let acl = match self.authenticate(api_key)? {
+            Some(acl) => acl,
+            None => return Ok(()),
+        };
+
+        // Templates may pick their graph from a variable or delete from every graph
+        let parsed = SparqlParser::new()
+            .parse_update(update)
+            .map_err(|e| EpcisKgError::Query(format!("Invalid SPARQL update: {}", e)))?;
+        let (targets, store_wide) = match written_graphs(&parsed) {
+            Some(targets) => (targets, false),
+            None => (Vec::new(), true),
+        };
+
+        if store_wide && !acl.write_graphs.iter().any(|pattern| pattern == "*") {
+            return Err(EpcisKgError::AccessDenied(format!(
+                "API key '{}' may not write outside its named graphs",
+                acl.name
+            )));
+        }
+
+        if let Some(denied) = targets
+            .iter()
+            .find(|graph| !Self::can_access(acl, graph, GraphAccess::Write))
+        {
+            return Err(EpcisKgError::AccessDenied(format!(
+                "API key '{}' may not write graph {}",
+                acl.name, denied
+            )));
+        }
+
+        match read_graphs(&parsed) {
+            Some(read) => match read.iter().find(|graph| !Self::can_access(acl, graph, GraphAccess::Read)) {
+                Some(denied) => Err(EpcisKgError::AccessDenied(format!(
+                    "API key '{}' may not read graph {}",
+                    acl.name, denied
+                ))),
+                None => Ok(()),
+            },
+            None if !acl.read_graphs.iter().any(|pattern| pattern == "*") => Err(EpcisKgError::AccessDenied(format!(
+                "API key '{}' may not read outside its named graphs",
+                acl.name
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the API key may read a graph, for evaluating its updates over
+    /// only the graphs it may read
+    pub fn read_filter(&self, api_key: Option<&str>) -> Result<impl Fn(&str) -> bool, EpcisKgError> {
+        let acl = self.authenticate(api_key)?.cloned();
+        Ok(move |graph: &str| acl.as_ref().map_or(true, |acl| Self::can_access(acl, graph, GraphAccess::Read)))
+    }
+
+    /// Check that the API key may read every one of the given graphs
+    pub fn check_graph_read(&self, api_key: Option<&str>, graphs: &[String]) -> Result<(), EpcisKgError> {
+        self.check_graphs(api_key, graphs, GraphAccess::Read)
+    }
+
+    /// Check that the API key may write every one of the given graphs
+    pub fn check_graph_write(&self, api_key: Option<&str>, graphs: &[String]) -> Result<(), EpcisKgError> {
+        self.check_graphs(api_key, graphs, GraphAccess::Write)
+    }
+
+    fn check_graphs(&self, api_key: Option<&str>, graphs: &[String], access: GraphAccess) -> Result<(), EpcisKgError> {
+        let acl = match self.authenticate(api_key)? {
+            Some(acl) => acl,
+            None => return Ok(()),
+        };
+
+        match graphs.iter().find(|graph| !Self::can_access(acl, graph, access)) {
+            Some(denied) => Err(EpcisKgError::AccessDenied(format!(
+                "API key '{}' may not {} graph {}",
+                acl.name,
+                match access {
+                    GraphAccess::Read => "read",
+                    GraphAccess::Write => "write",
+                },
+                denied
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Add or replace the ACL entry for an API key
+    pub fn upsert(&mut self, acl: ApiKeyAcl) -> Result<(), EpcisKgError> {
+        if acl.key.is_empty() {
+            return Err(EpcisKgError::Validation("API key cannot be empty".to_string()));
+        }
+        self.entries.insert(acl.key.clone(), acl);
+        Ok(())
+    }
+
+    /// Every ACL entry, ordered by name
+    pub fn entries(&self) -> Vec<ApiKeyAcl> {
+        let mut entries: Vec<ApiKeyAcl> = self.entries.values().cloned().collect();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+        entries
+    }
+
+    /// ACL entries with their keys masked, for listing over the API
+    pub fn masked_entries(&self) -> Vec<serde_json::Value> {
+        let mut entries: Vec<&ApiKeyAcl> = self.entries.values().collect();
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+        entries
+            .into_iter()
+            .map(|acl| {
+                let visible: String = acl.key.chars().take(4).collect();
+                serde_json::json!({
+                    "key": format!("{}****", visible),
+                    "name": acl.name,
+                    "read_graphs": acl.read_graphs,
+                    "write_graphs": acl.write_graphs,
+                    "admin": acl.admin,
+                    "quota": acl.quota
+                })
+            })
+            .collect()
+    }
+}
+
+/// Extract the API key from the X-API-Key header or a Bearer token
+pub fn api_key_from_headers(headers: &HeaderMap) -> Option<String> {
+    if let Some(value) = headers.get(API_KEY_HEADER).and_then(|v| v.to_str().ok()) {
+        return Some(value.trim().to_string());
+    }
+
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+}
+
+/// Match a graph name against an ACL pattern (`*`, `prefix*` or exact name)
+pub fn graph_pattern_matches(pattern: &str, graph: &str) -> bool {
+    // Unmasked personal data is only granted by patterns that name it
+    if graph.starts_with(PII_GRAPH_PREFIX) && !pattern.starts_with(PII_GRAPH_PREFIX) {
+        return false;
+    }
+    match pattern.strip_suffix('*') {
+        Some(prefix) => graph.starts_with(prefix),
+        None => pattern == graph,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn partner_acl() -> AccessControlConfig {
+        AccessControlConfig {
+            enabled: true,
+            api_keys: vec![ApiKeyAcl {
+                key: "partner-a-key".to_string(),
+                name: "partner-a".to_string(),
+                read_graphs: vec!["urn:epcis:partner:0614141*".to_string()],
+                write_graphs: vec!["urn:epcis:partner:0614141:events".to_string()],
+                admin: false,
+                quota: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_restrict_query_injects_readable_graphs() {
+        let acl = GraphAccessControl::from_config(&partner_acl());
+        let graphs = vec![
+            "urn:epcis:partner:0614141:events".to_string(),
+            "urn:epcis:partner:0799999:events".to_string(),
+        ];
+
+        let query = acl
+            .restrict_query(Some("partner-a-key"), "SELECT ?s WHERE { ?s ?p ?o }", &graphs)
+            .unwrap();
+        assert!(query.contains("FROM <urn:epcis:partner:0614141:events>"));
+        assert!(!query.contains("0799999"));
+    }
+
+    #[test]
+    fn test_restrict_query_rejects_unreadable_graph() {
+        let acl = GraphAccessControl::from_config(&partner_acl());
+        let query = "SELECT ?s FROM <urn:epcis:partner:0799999:events> WHERE { ?s ?p ?o }";
+
+        assert!(matches!(
+            acl.restrict_query(Some("partner-a-key"), query, &[]),
+            Err(EpcisKgError::AccessDenied(_))
+        ));
+        assert!(acl.restrict_query(None, "SELECT ?s WHERE { ?s ?p ?o }", &[]).is_err());
+
+        // Graphs are found however the query spells them
+        for query in [
+            "PREFIX ex: <urn:epcis:partner:> SELECT ?s FROM ex:0799999 WHERE { ?s ?p ?o }",
+            "SELECT ?s FROM<urn:epcis:partner:0799999:events> WHERE { ?s ?p ?o }",
+            "SELECT ?s WHERE { GRAPH<urn:epcis:partner:0799999:events> { ?s ?p ?o } }",
+        ] {
+            assert!(matches!(
+                acl.restrict_query(Some("partner-a-key"), query, &[]),
+                Err(EpcisKgError::AccessDenied(_))
+            ));
+        }
+        assert!(matches!(
+            acl.restrict_query(Some("partner-a-key"), "SELECT ?s FROM WHERE { ?s", &[]),
+            Err(EpcisKgError::Query(_))
+        ));
+
+        // A query reading its own FROM graph still only ranges GRAPH ?g over readable graphs
+        let graphs = vec![
+            "urn:epcis:partner:0614141:events".to_string(),
+            "urn:epcis:partner:0799999:events".to_string(),
+        ];
+        let query = acl
+            .restrict_query(
+                Some("partner-a-key"),
+                "SELECT ?g FROM <urn:epcis:partner:0614141:events> WHERE { GRAPH ?g { ?s ?p ?o } }",
+                &graphs,
+            )
+            .unwrap();
+        assert!(query.contains("FROM NAMED <urn:epcis:partner:0614141:events>"));
+        assert!(!query.contains("0799999"));
+    }
+
+    #[test]
+    fn test_check_update_targets() {
+        let acl = GraphAccessControl::from_config(&partner_acl());
+
+        assert!(acl
+            .check_update(Some("partner-a-key"), "INSERT DATA { GRAPH <urn:epcis:partner:0614141:events> { <urn:x:a> <urn:x:b> <urn:x:c> } }")
+            .is_ok());
+        assert!(acl
+            .check_update(Some("partner-a-key"), "INSERT DATA { GRAPH <urn:epcis:ontology:cbv> { <urn:x:a> <urn:x:b> <urn:x:c> } }")
+            .is_err());
+        assert!(acl.check_update(Some("partner-a-key"), "INSERT DATA { <urn:x:a> <urn:x:b> <urn:x:c> }").is_err());
+
+        // Reading its own graph does not let a key delete from every graph
+        let own = "GRAPH <urn:epcis:partner:0614141:events> { ?s <urn:x:status> ?o }";
+        assert!(acl.check_update(Some("partner-a-key"), &format!("DELETE {{ {} }} WHERE {{ {} }}", own, own)).is_ok());
+        assert!(acl.check_update(Some("partner-a-key"), &format!("DELETE {{ ?s <urn:x:status> ?o }} WHERE {{ {} }}", own)).is_err());
+    }
+
+    #[test]
+    fn test_check_update_where_reads() {
+        let acl = GraphAccessControl::from_config(&partner_acl());
+        let copy = |pattern: &str| format!("INSERT {{ GRAPH <urn:epcis:partner:0614141:events> {{ ?s ?p ?o }} }} {}", pattern);
+
+        assert!(acl.check_update(Some("partner-a-key"), &copy("WHERE { GRAPH <urn:epcis:partner:0614141:archive> { ?s ?p ?o } }")).is_ok());
+        for pattern in [
+            // The default graph is the union of every graph, and GRAPH ?g ranges over all of them
+            "WHERE { ?s ?p ?o }",
+            "WHERE { GRAPH ?g { ?s ?p ?o } }",
+            "WHERE { GRAPH <urn:epcis:partner:0799999:events> { ?s ?p ?o } }",
+            "USING <urn:epcis:partner:0799999:events> WHERE { ?s ?p ?o }",
+        ] {
+            assert!(matches!(
+                acl.check_update(Some("partner-a-key"), &copy(pattern)),
+                Err(EpcisKgError::AccessDenied(_))
+            ), "{}", pattern);
+        }
+        assert!(matches!(
+            acl.check_update(Some("partner-a-key"), "INSERT DATA { GRAPH <urn:epcis:partner:0614141:events> { <a> <b>"),
+            Err(EpcisKgError::Query(_))
+        ));
+    }
+
+    #[test]
+    fn test_pii_graphs_need_an_explicit_grant() {
+        assert!(!graph_pattern_matches("*", "urn:epcis:pii:evt-1"));
+        assert!(!graph_pattern_matches("urn:epcis:*", "urn:epcis:pii:evt-1"));
+        assert!(graph_pattern_matches("urn:epcis:pii:*", "urn:epcis:pii:evt-1"));
+        assert!(graph_pattern_matches("*", "urn:epcis:event:evt-1"));
+    }
+
+    #[test]
+    fn test_disabled_access_control_allows_everything() {
+        let acl = GraphAccessControl::from_config(&AccessControlConfig::default());
+        let query = "SELECT ?s WHERE { ?s ?p ?o }";
+
+        assert_eq!(acl.restrict_query(None, query, &[]).unwrap(), query);
+        assert!(acl.check_update(None, "CLEAR ALL").is_ok());
+    }
+}
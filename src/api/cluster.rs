@@ -0,0 +1,403 @@
+//! Experimental distributed ingestion.
+//!
+//! A coordinator hashes each captured event's routing EPC into a shard and
+//! forwards the event to the worker owning that shard. Workers store and
+//! materialize their events locally; the coordinator periodically pulls their
+//! event and inferred graphs into its own store, which serves the queries.
+//! A worker only reasons over its own shards, so inferences that need events
+//! from two shards appear once the coordinator runs inference over the merge.
+
+use crate::api::access_control::API_KEY_HEADER;
+use crate::config::{ClusterConfig, ClusterRole};
+use crate::models::epcis::EpcisEvent;
+use crate::ontology::reasoner::{MATERIALIZED_GRAPHS, PROVENANCE_GRAPH};
+use crate::ontology::rematerialize::{self, RematerializeReport, RematerializeScope};
+use crate::pipeline::EpcisEventPipeline;
+use crate::storage::canonical;
+use crate::storage::oxigraph_store::OxigraphStore;
+use crate::utils::masking::{pii_graph_name, PII_GRAPH_PREFIX};
+use crate::{Config, EpcisKgError};
+use oxttl::NQuadsParser;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+const EVENT_GRAPH_PREFIX: &str = "urn:epcis:event:";
+
+/// Shard of a routing key: the first 8 bytes of its SHA-256, modulo `shards`
+pub fn shard_of(key: &str, shards: u32) -> u32 {
+    let digest = Sha256::digest(key.as_bytes());
+    let prefix = u64::from_be_bytes(digest[..8].try_into().expect("SHA-256 digests are 32 bytes"));
+    (prefix % u64::from(shards.max(1))) as u32
+}
+
+/// What an event is routed by: its first EPC (or input or output EPC), its ID if it names none
+pub fn routing_key(event: &EpcisEvent) -> &str {
+    event
+        .epc_list
+        .iter()
+        .chain(&event.input_epc_list)
+        .chain(&event.output_epc_list)
+        .next()
+        .unwrap_or(&event.event_id)
+}
+
+/// Graphs a worker hands to the coordinator on merge
+fn is_merged_graph(name: &str) -> bool {
+    name.starts_with(EVENT_GRAPH_PREFIX)
+        || name.starts_with(PII_GRAPH_PREFIX)
+        || name == PROVENANCE_GRAPH
+        || MATERIALIZED_GRAPHS.contains(&name)
+}
+
+/// A worker's event, personal data and inferred graphs as canonical N-Quads
+pub fn export_shard_graphs(store: &OxigraphStore) -> Result<String, EpcisKgError> {
+    let quads: Vec<oxrdf::Quad> = store
+        .quads()
+        .into_iter()
+        .filter(|quad| matches!(&quad.graph_name, oxrdf::GraphName::NamedNode(graph) if is_merged_graph(graph.as_str())))
+        .collect();
+    canonical::canonical_nquads(&quads)
+}
+
+/// Merge a worker export into the coordinator's store; returns the graphs changed and triples written
+pub fn apply_export(store: &mut OxigraphStore, nquads: &str) -> Result<(usize, usize), EpcisKgError> {
+    let mut graphs: HashMap<String, oxrdf::Graph> = HashMap::new();
+    for quad in NQuadsParser::new().with_quoted_triples().for_reader(nquads.as_bytes()) {
+        let quad = quad.map_err(|e| EpcisKgError::RdfParsing(format!("Invalid quad from worker: {}", e)))?;
+        let oxrdf::GraphName::NamedNode(graph) = &quad.graph_name else { continue };
+        if !is_merged_graph(graph.as_str()) {
+            continue;
+        }
+        graphs
+            .entry(graph.as_str().to_string())
+            .or_default()
+            .insert(oxrdf::TripleRef::new(quad.subject.as_ref(), quad.predicate.as_ref(), quad.object.as_ref()));
+    }
+    store.merge_graphs(graphs)
+}
+
+/// An event a worker did not store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestFailure {
+    pub event_id: String,
+    pub error: String,
+}
+
+/// What a worker did with a batch routed to it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IngestReport {
+    pub stored: Vec<String>,
+    pub failed: Vec<IngestFailure>,
+    /// Local materialization over the stored events, when inference was requested
+    pub materialization: Option<RematerializeReport>,
+}
+
+/// Store a batch of events on a worker and re-materialize the inferences about them
+pub fn ingest(
+    store: &mut OxigraphStore,
+    config: &Config,
+    pipeline: &EpcisEventPipeline,
+    events: &[EpcisEvent],
+    validate: bool,
+    infer: bool,
+    strict: Option<bool>,
+) -> Result<IngestReport, EpcisKgError> {
+    let strict = strict.unwrap_or(config.strict_mode.enabled);
+    // One transaction for the batch: a failure part-way leaves neither memory nor disk half-written
+    store.transaction(|store| {
+        let mut report = IngestReport::default();
+        for event in events {
+            let mut event = event.clone();
+            let same_as = pipeline.normalize_identifiers(&mut event);
+            let rejected = match validate {
+                true => pipeline.validate_event_strict(&event, strict).map(|result| (!result.is_valid).then(|| result.errors.join("; "))),
+                false => Ok(None),
+            };
+            let triples = match rejected {
+                Ok(None) => pipeline.masked_event_triples(&event),
+                Ok(Some(errors)) => Err(EpcisKgError::Validation(errors)),
+                Err(e) => Err(e),
+            };
+            match triples {
+                Ok(mut triples) => {
+                    triples.public.extend(same_as);
+                    // Storing does not wait on anything; the method is only async for its callers in the pipeline
+                    futures::executor::block_on(store.store_event_triples(&event.event_id, &triples.public))?;
+                    if !triples.restricted.is_empty() {
+                        store.insert_triples(&pii_graph_name(&event.event_id), &triples.restricted)?;
+                    }
+                    report.stored.push(event.event_id.clone());
+                }
+                Err(e) => report.failed.push(IngestFailure { event_id: event.event_id.clone(), error: e.to_string() }),
+            }
+        }
+
+        if infer && !report.stored.is_empty() {
+            let scope = RematerializeScope {
+                graphs: report.stored.iter().map(|event_id| format!("{}{}", EVENT_GRAPH_PREFIX, event_id)).collect(),
+                ..RematerializeScope::default()
+            };
+            report.materialization = Some(rematerialize::rematerialize(store, config, &scope, "cluster")?);
+        }
+        Ok(report)
+    })
+}
+
+/// Batch the coordinator sends to a worker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngestRequest {
+    pub events: Vec<EpcisEvent>,
+    #[serde(default = "default_true")]
+    pub validate: bool,
+    #[serde(default = "default_true")]
+    pub infer: bool,
+    /// Strict validation for this batch; the worker's `[strict_mode]` setting when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Outcome of forwarding one worker's share of a capture
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerDispatch {
+    pub worker: String,
+    pub events: usize,
+    pub report: Option<IngestReport>,
+    pub error: Option<String>,
+}
+
+/// Outcome of pulling one worker's graphs
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkerMerge {
+    pub worker: String,
+    pub graphs_changed: usize,
+    pub triples_written: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeReport {
+    pub merged_at: String,
+    pub workers: Vec<WorkerMerge>,
+}
+
+/// Shards owned by a worker
+#[derive(Debug, Clone, Serialize)]
+pub struct ShardAssignment {
+    pub worker: String,
+    pub shards: Vec<u32>,
+}
+
+/// This instance's part in distributed ingestion, and the coordinator's view of its workers
+pub struct Cluster {
+    config: ClusterConfig,
+    pipeline: Arc<EpcisEventPipeline>,
+    client: reqwest::Client,
+    last_merge: Mutex<Option<MergeReport>>,
+}
+
+impl Cluster {
+    pub fn new(config: &ClusterConfig, pipeline: Arc<EpcisEventPipeline>) -> Self {
+        Self {
+            config: config.clone(),
+            pipeline,
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(config.request_timeout_seconds))
+                .build()
+                .unwrap_or_default(),
+            last_merge: Mutex::new(None),
+        }
+    }
+
+    pub fn role(&self) -> ClusterRole {
+        self.config.role
+    }
+
+    pub fn pipeline(&self) -> &EpcisEventPipeline {
+        &self.pipeline
+    }
+
+    pub fn ensure_role(&self, role: ClusterRole, action: &str) -> Result<(), EpcisKgError> {
+        match self.config.role == role {
+            true => Ok(()),
+            false => Err(EpcisKgError::Conflict(format!(
+                "Only a cluster {} can {}; this instance is {}",
+                role.as_str(), action, self.config.role.as_str()
+            ))),
+        }
+    }
+
+    /// Worker owning a shard
+    pub fn worker_for(&self, shard: u32) -> Option<&str> {
+        match self.config.workers.len() {
+            0 => None,
+            workers => Some(self.config.workers[shard as usize % workers].as_str()),
+        }
+    }
+
+    pub fn assignments(&self) -> Vec<ShardAssignment> {
+        let mut shards: BTreeMap<&str, Vec<u32>> = self.config.workers.iter().map(|worker| (worker.as_str(), Vec::new())).collect();
+        for shard in 0..self.config.shards {
+            if let Some(worker) = self.worker_for(shard) {
+                shards.entry(worker).or_default().push(shard);
+            }
+        }
+        self.config
+            .workers
+            .iter()
+            .map(|worker| ShardAssignment { worker: worker.clone(), shards: shards.remove(worker.as_str()).unwrap_or_default() })
+            .collect()
+    }
+
+    /// Events grouped by the worker owning their shard, in capture order
+    pub fn partition(&self, events: Vec<EpcisEvent>) -> BTreeMap<String, Vec<EpcisEvent>> {
+        let mut partitions: BTreeMap<String, Vec<EpcisEvent>> = BTreeMap::new();
+        for event in events {
+            let shard = shard_of(routing_key(&event), self.config.shards);
+            if let Some(worker) = self.worker_for(shard) {
+                partitions.entry(worker.to_string()).or_default().push(event);
+            }
+        }
+        partitions
+    }
+
+    fn authorized(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.config.api_key {
+            Some(api_key) => request.header(API_KEY_HEADER, api_key),
+            None => request,
+        }
+    }
+
+    /// Forward each worker its share of the events, all workers at once
+    pub async fn dispatch(&self, events: Vec<EpcisEvent>, validate: bool, infer: bool, strict: Option<bool>) -> Vec<WorkerDispatch> {
+        let requests = self.partition(events).into_iter().map(|(worker, events)| async move {
+            let url = format!("{}/api/v1/cluster/ingest", worker.trim_end_matches('/'));
+            let count = events.len();
+            let sent = self.authorized(self.client.post(&url)).json(&IngestRequest { events, validate, infer, strict }).send().await;
+            let outcome = match sent {
+                Ok(response) if response.status().is_success() => response.json::<IngestReport>().await.map_err(|e| e.to_string()),
+                Ok(response) => Err(format!("answered {}", response.status())),
+                Err(e) => Err(e.to_string()),
+            };
+            match outcome {
+                Ok(report) => WorkerDispatch { worker, events: count, report: Some(report), error: None },
+                Err(error) => {
+                    tracing::warn!("Forwarding {} events to worker {} failed: {}", count, worker, error);
+                    WorkerDispatch { worker, events: count, report: None, error: Some(error) }
+                }
+            }
+        });
+        futures::future::join_all(requests).await
+    }
+
+    /// Pull every worker's graphs into the store; a failing worker does not stop the others
+    pub async fn merge(&self, store: &std::sync::Mutex<OxigraphStore>) -> MergeReport {
+        let exports = futures::future::join_all(self.config.workers.iter().map(|worker| async move {
+            let url = format!("{}/api/v1/cluster/export", worker.trim_end_matches('/'));
+            let export = match self.authorized(self.client.get(&url)).send().await {
+                Ok(response) if response.status().is_success() => response.text().await.map_err(|e| e.to_string()),
+                Ok(response) => Err(format!("answered {}", response.status())),
+                Err(e) => Err(e.to_string()),
+            };
+            (worker.clone(), export)
+        }))
+        .await;
+
+        let mut workers = Vec::new();
+        for (worker, export) in exports {
+            let applied = export.and_then(|nquads| match store.lock() {
+                Ok(mut store) => apply_export(&mut store, &nquads).map_err(|e| e.to_string()),
+                Err(e) => Err(format!("Failed to acquire store lock: {}", e)),
+            });
+            let merge = match applied {
+                Ok((graphs_changed, triples_written)) => WorkerMerge { worker, graphs_changed, triples_written, error: None },
+                Err(error) => {
+                    tracing::warn!("Merging worker {} failed: {}", worker, error);
+                    WorkerMerge { worker, graphs_changed: 0, triples_written: 0, error: Some(error) }
+                }
+            };
+            workers.push(merge);
+        }
+
+        let report = MergeReport { merged_at: chrono::Utc::now().to_rfc3339(), workers };
+        *self.last_merge.lock() = Some(report.clone());
+        report
+    }
+
+    pub fn last_merge(&self) -> Option<MergeReport> {
+        self.last_merge.lock().clone()
+    }
+
+    pub fn status(&self) -> serde_json::Value {
+        serde_json::json!({
+            "role": self.config.role,
+            "shards": self.config.shards,
+            "assignments": self.assignments(),
+            "merge_interval_seconds": self.config.merge_interval_seconds,
+            "last_merge": self.last_merge()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::oxigraph_store::INFERENCE_RUNS_GRAPH;
+
+    fn event(id: &str, epc: &str) -> EpcisEvent {
+        EpcisEvent { event_id: id.to_string(), epc_list: vec![epc.to_string()], ..EpcisEvent::default() }
+    }
+
+    #[test]
+    fn test_events_route_by_epc_and_worker_graphs_merge() {
+        let config = ClusterConfig {
+            role: ClusterRole::Coordinator,
+            shards: 8,
+            workers: vec!["http://worker-1:8080".to_string(), "http://worker-2:8080".to_string()],
+            ..ClusterConfig::default()
+        };
+        let epc = "urn:epc:id:sgtin:0614141.107346.2017";
+        assert_eq!(shard_of(epc, 8), shard_of(epc, 8));
+        assert!(shard_of(epc, 8) < 8);
+
+        let pipeline = futures::executor::block_on(EpcisEventPipeline::new(
+            Config::default(),
+            OxigraphStore::new_memory().unwrap(),
+            crate::ontology::reasoner::OntologyReasoner::new(),
+        ))
+        .unwrap();
+        let cluster = Cluster::new(&config, Arc::new(pipeline));
+        let assignments = cluster.assignments();
+        assert_eq!(assignments[0].shards, vec![0, 2, 4, 6]);
+        assert_eq!(assignments[1].shards, vec![1, 3, 5, 7]);
+
+        // Every event about an EPC goes to the same worker
+        let partitions = cluster.partition(vec![event("e1", epc), event("e2", epc)]);
+        assert_eq!(partitions.len(), 1);
+        assert_eq!(partitions.values().next().unwrap().len(), 2);
+
+        let mut worker = OxigraphStore::new_memory().unwrap();
+        let typed = oxrdf::Triple::new(
+            oxrdf::NamedNode::new_unchecked(epc),
+            oxrdf::NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"),
+            oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:PhysicalObject"),
+        );
+        worker.insert_triples("urn:epcis:event:e1", &[typed.clone()]).unwrap();
+        worker.insert_triples("urn:epcis:inferred", &[typed.clone()]).unwrap();
+        worker.insert_triples(INFERENCE_RUNS_GRAPH, &[typed]).unwrap();
+        let export = export_shard_graphs(&worker).unwrap();
+
+        let mut coordinator = OxigraphStore::new_memory().unwrap();
+        assert_eq!(apply_export(&mut coordinator, &export).unwrap(), (2, 2));
+        assert_eq!(coordinator.graph_len("urn:epcis:event:e1"), 1);
+        assert_eq!(coordinator.graph_len(INFERENCE_RUNS_GRAPH), 0);
+        // Merging the same export again changes nothing
+        assert_eq!(apply_export(&mut coordinator, &export).unwrap(), (0, 0));
+    }
+}
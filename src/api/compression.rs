@@ -0,0 +1,173 @@
+use crate::config::CompressionConfig;
+use crate::monitoring::metrics::CompressionMetrics;
+use axum::body::{Body, HttpBody};
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap};
+use axum::middleware::Next;
+use axum::response::Response;
+use axum::Router;
+use futures::StreamExt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tower::ServiceBuilder;
+use tower_http::compression::predicate::{NotForContentType, Predicate, SizeAbove};
+use tower_http::compression::CompressionLayer;
+use tower_http::decompression::{DecompressionBody, RequestDecompressionLayer};
+use tower_http::map_request_body::MapRequestBodyLayer;
+
+/// Size of a response body before it was encoded
+#[derive(Debug, Clone, Copy)]
+struct OriginalSize(u64);
+
+/// Bytes of an encoded request body once decoded, counted as the handler reads them
+#[derive(Debug, Clone)]
+struct DecodedBytes(Arc<AtomicU64>);
+
+/// Records an encoded request body when the body is dropped, after the handler has read what it needs
+struct RequestTally {
+    metrics: Arc<CompressionMetrics>,
+    received: u64,
+    decoded: Arc<AtomicU64>,
+}
+
+impl Drop for RequestTally {
+    fn drop(&mut self) {
+        self.metrics.record_request(self.received, self.decoded.load(Ordering::Relaxed));
+    }
+}
+
+/// Records an encoded response once it has been sent
+struct ResponseTally {
+    metrics: Arc<CompressionMetrics>,
+    original: u64,
+    sent: u64,
+}
+
+impl Drop for ResponseTally {
+    fn drop(&mut self) {
+        self.metrics.record_response(self.original, self.sent);
+    }
+}
+
+fn is_encoded(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_ENCODING)
+        .is_some_and(|encoding| !encoding.as_bytes().eq_ignore_ascii_case(b"identity"))
+}
+
+/// Encode responses with gzip or Brotli as the client accepts, and decode
+/// request bodies sent with `Content-Encoding: gzip` or `br`.
+///
+/// Request bodies in other encodings, or any encoding when decoding is off,
+/// are answered with `415 Unsupported Media Type`. Body limits apply to the
+/// decoded body. gRPC, image and event-stream responses are never encoded.
+pub fn with_compression<S>(router: Router<S>, config: &CompressionConfig, metrics: Arc<CompressionMetrics>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let (gzip, brotli) = (config.enabled && config.gzip, config.enabled && config.brotli);
+    let decode = config.enabled && config.decompress_requests;
+    let encode_when = SizeAbove::new(config.min_response_bytes)
+        .and(NotForContentType::GRPC)
+        .and(NotForContentType::IMAGES)
+        .and(NotForContentType::SSE);
+    router
+        .layer(axum::middleware::from_fn(measure_plain_bodies))
+        .layer(
+            ServiceBuilder::new()
+                .layer(RequestDecompressionLayer::new().gzip(decode && gzip).br(decode && brotli))
+                .layer(CompressionLayer::new().gzip(gzip).br(brotli).compress_when(encode_when))
+                .layer(MapRequestBodyLayer::new(Body::new::<DecompressionBody<Body>>)),
+        )
+        .layer(axum::middleware::from_fn_with_state(metrics, measure_encoded_bodies))
+}
+
+/// Outside the codecs: count the bytes of encoded request bodies as received and of encoded responses as sent
+async fn measure_encoded_bodies(State(metrics): State<Arc<CompressionMetrics>>, request: Request, next: Next) -> Response {
+    let request = if is_encoded(request.headers()) {
+        let decoded = Arc::new(AtomicU64::new(0));
+        let mut tally = RequestTally { metrics: Arc::clone(&metrics), received: 0, decoded: Arc::clone(&decoded) };
+        let (mut parts, body) = request.into_parts();
+        parts.extensions.insert(DecodedBytes(decoded));
+        let counted = body.into_data_stream().inspect(move |chunk| {
+            if let Ok(chunk) = chunk {
+                tally.received += chunk.len() as u64;
+            }
+        });
+        Request::from_parts(parts, Body::from_stream(counted))
+    } else {
+        request
+    };
+
+    let response = next.run(request).await;
+    let Some(OriginalSize(original)) = response.extensions().get::<OriginalSize>().copied() else { return response };
+    if !is_encoded(response.headers()) {
+        return response;
+    }
+    let mut tally = ResponseTally { metrics, original, sent: 0 };
+    let (parts, body) = response.into_parts();
+    let counted = body.into_data_stream().inspect(move |chunk| {
+        if let Ok(chunk) = chunk {
+            tally.sent += chunk.len() as u64;
+        }
+    });
+    Response::from_parts(parts, Body::from_stream(counted))
+}
+
+/// Inside the codecs: count decoded request bytes and note each response's size before encoding
+async fn measure_plain_bodies(request: Request, next: Next) -> Response {
+    let request = match request.extensions().get::<DecodedBytes>().cloned() {
+        Some(DecodedBytes(decoded)) => {
+            let (parts, body) = request.into_parts();
+            let counted = body.into_data_stream().inspect(move |chunk| {
+                if let Ok(chunk) = chunk {
+                    decoded.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                }
+            });
+            Request::from_parts(parts, Body::from_stream(counted))
+        }
+        None => request,
+    };
+
+    let mut response = next.run(request).await;
+    if let Some(size) = response.body().size_hint().exact() {
+        response.extensions_mut().insert(OriginalSize(size));
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use axum::routing::post;
+
+    #[tokio::test]
+    async fn test_large_responses_are_encoded_and_counted() {
+        let metrics = Arc::new(CompressionMetrics::default());
+        let mut app: Router = with_compression(
+            Router::new()
+                .route("/large", post(|| async { "urn:epc:id:sgtin:0614141.107346.2017 ".repeat(100) }))
+                .route("/small", post(|| async { "ok" })),
+            &CompressionConfig::default(),
+            Arc::clone(&metrics),
+        );
+        let request = |path: &str, header: (header::HeaderName, &str)| {
+            axum::http::Request::post(path).header(header.0, header.1).body(Body::empty()).unwrap()
+        };
+
+        let response = tower::Service::call(&mut app, request("/large", (header::ACCEPT_ENCODING, "gzip"))).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        let sent = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let stats = metrics.snapshot();
+        assert_eq!(stats.compressed_responses, 1);
+        assert_eq!(stats.response_bytes_saved, 3700 - sent.len() as u64);
+
+        let response = tower::Service::call(&mut app, request("/small", (header::ACCEPT_ENCODING, "gzip"))).await.unwrap();
+        assert!(response.headers().get(header::CONTENT_ENCODING).is_none());
+
+        // Encodings the server cannot decode are refused before the handler runs
+        let response = tower::Service::call(&mut app, request("/small", (header::CONTENT_ENCODING, "zstd"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+}
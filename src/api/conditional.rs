@@ -0,0 +1,108 @@
+use crate::api::access_control::api_key_from_headers;
+use crate::api::server::AppState;
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+
+/// API read routes whose responses only change with the dataset (exact paths, or prefixes ending in `/`)
+const CACHEABLE_PATHS: [&str; 4] = ["/statistics", "/ontologies", "/sparql", "/vocab/"];
+
+/// Distinguishes this process's dataset versions from those of an earlier run
+fn instance_id() -> &'static str {
+    static INSTANCE: OnceLock<String> = OnceLock::new();
+    INSTANCE.get_or_init(|| uuid::Uuid::new_v4().to_string())
+}
+
+fn is_cacheable(path: &str) -> bool {
+    CACHEABLE_PATHS.iter().any(|cacheable| match cacheable.strip_suffix('/') {
+        Some(_) => path.starts_with(cacheable),
+        None => path == *cacheable,
+    })
+}
+
+/// Weak entity tag of a read at a dataset version.
+///
+/// Everything else a response depends on goes into the tag as well: the
+/// query string, the caller's key (ACLs) and the preferred languages (labels).
+fn entity_tag(version: u64, uri: &str, headers: &HeaderMap) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(instance_id().as_bytes());
+    hasher.update(version.to_be_bytes());
+    hasher.update(uri.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(api_key_from_headers(headers).unwrap_or_default().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(headers.get(header::ACCEPT_LANGUAGE).map(HeaderValue::as_bytes).unwrap_or_default());
+    hasher.update(b"\n");
+    hasher.update(headers.get(header::ACCEPT).map(HeaderValue::as_bytes).unwrap_or_default());
+    let digest: String = hasher.finalize().iter().take(12).map(|byte| format!("{:02x}", byte)).collect();
+    format!("W/\"{}-{}\"", version, digest)
+}
+
+/// `If-None-Match` matches with the weak comparison of RFC 9110
+fn none_match(if_none_match: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    if_none_match.split(',').any(|candidate| candidate.trim() == "*" || opaque(candidate) == opaque(etag))
+}
+
+/// Tag dataset reads with an `ETag` and answer `If-None-Match` with
+/// `304 Not Modified` while the dataset has not changed, so polling
+/// dashboards don't recompute the same response
+pub async fn conditional_reads(State(app_state): State<AppState>, request: Request, next: Next) -> Response {
+    if request.method() != Method::GET || !is_cacheable(request.uri().path()) {
+        return next.run(request).await;
+    }
+    let (version, metrics) = match app_state.store.lock() {
+        Ok(store) => (store.version(), store.metrics()),
+        Err(_) => return next.run(request).await,
+    };
+    let etag = entity_tag(version, &request.uri().to_string(), request.headers());
+    let Ok(etag_value) = HeaderValue::from_str(&etag) else { return next.run(request).await };
+
+    let not_modified = request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| none_match(value, &etag));
+    metrics.record_cache_lookup(not_modified);
+    let mut response = if not_modified { StatusCode::NOT_MODIFIED.into_response() } else { next.run(request).await };
+
+    if response.status() == StatusCode::OK || response.status() == StatusCode::NOT_MODIFIED {
+        let headers = response.headers_mut();
+        headers.insert(header::ETAG, etag_value);
+        headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+        headers.insert(header::VARY, HeaderValue::from_static("accept, accept-language, authorization, x-api-key"));
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tags_change_with_the_dataset_and_the_request() {
+        assert!(is_cacheable("/statistics"));
+        assert!(is_cacheable("/vocab/bizsteps"));
+        assert!(!is_cacheable("/ontologies/diagnostics"));
+        assert!(!is_cacheable("/events"));
+
+        let mut headers = HeaderMap::new();
+        let etag = entity_tag(7, "/vocab/bizsteps", &headers);
+        assert!(etag.starts_with("W/\"7-"));
+        assert_eq!(etag, entity_tag(7, "/vocab/bizsteps", &headers));
+        assert_ne!(etag, entity_tag(8, "/vocab/bizsteps", &headers));
+        assert_ne!(etag, entity_tag(7, "/vocab/dispositions", &headers));
+        headers.insert(header::ACCEPT_LANGUAGE, HeaderValue::from_static("de"));
+        assert_ne!(etag, entity_tag(7, "/vocab/bizsteps", &headers));
+
+        let strong = etag.trim_start_matches("W/");
+        assert!(none_match(&etag, &etag));
+        assert!(none_match(&format!("\"other\", {}", strong), &etag));
+        assert!(none_match("*", &etag));
+        assert!(!none_match("\"other\"", &etag));
+    }
+}
@@ -0,0 +1,345 @@
+use crate::api::access_control::API_KEY_HEADER;
+use crate::api::idempotency::IDEMPOTENCY_KEY_HEADER;
+use crate::config::EdgeConfig;
+use crate::models::epcis::EpcisEvent;
+use crate::EpcisKgError;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Events frozen into one forwarding attempt; retried unchanged under the same key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EdgeBatch {
+    /// `Idempotency-Key` of the batch, so the central server applies a retried batch once
+    pub key: String,
+    pub events: Vec<EpcisEvent>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct QueueState {
+    next_batch: u64,
+    queued: VecDeque<EpcisEvent>,
+    in_flight: Option<EdgeBatch>,
+    forwarded: u64,
+}
+
+/// Scans accepted at the site and not yet confirmed by the central server.
+///
+/// Every change is written to disk before it is acknowledged, so a restart
+/// loses nothing. A batch stays frozen from its first forwarding attempt until
+/// the central server confirms it; new scans queue behind it.
+pub struct EdgeQueue {
+    path: Option<PathBuf>,
+    state: Mutex<QueueState>,
+}
+
+impl EdgeQueue {
+    /// Queue kept in `path`, resuming whatever it held
+    pub fn open(path: &str) -> Result<Self, EpcisKgError> {
+        let path = PathBuf::from(path);
+        let state = match path.exists() {
+            true => serde_json::from_str(&std::fs::read_to_string(&path)?)?,
+            false => QueueState::default(),
+        };
+        Ok(Self { path: Some(path), state: Mutex::new(state) })
+    }
+
+    pub fn in_memory() -> Self {
+        Self { path: None, state: Mutex::new(QueueState::default()) }
+    }
+
+    /// Queue scans; returns how many were new and how many repeated a queued event ID
+    pub fn push(&self, events: Vec<EpcisEvent>) -> Result<(usize, usize), EpcisKgError> {
+        let mut state = self.state.lock();
+        let mut seen: HashSet<String> = state
+            .queued
+            .iter()
+            .chain(state.in_flight.iter().flat_map(|batch| batch.events.iter()))
+            .map(|event| event.event_id.clone())
+            .collect();
+        let total = events.len();
+        let fresh: Vec<EpcisEvent> = events.into_iter().filter(|event| seen.insert(event.event_id.clone())).collect();
+        let accepted = fresh.len();
+        state.queued.extend(fresh);
+        self.persist(&state)?;
+        Ok((accepted, total - accepted))
+    }
+
+    /// The batch to forward: the frozen one if a previous attempt failed, else up to `max` queued events
+    pub fn next_batch(&self, max: usize) -> Result<Option<EdgeBatch>, EpcisKgError> {
+        let mut state = self.state.lock();
+        if let Some(batch) = &state.in_flight {
+            return Ok(Some(batch.clone()));
+        }
+        if state.queued.is_empty() {
+            return Ok(None);
+        }
+        let count = max.min(state.queued.len());
+        let events: Vec<EpcisEvent> = state.queued.drain(..count).collect();
+        let mut hasher = Sha256::new();
+        for event in &events {
+            hasher.update(event.event_id.as_bytes());
+            hasher.update(b"\n");
+        }
+        let digest: String = hasher.finalize().iter().take(8).map(|byte| format!("{:02x}", byte)).collect();
+        let batch = EdgeBatch { key: format!("edge-{}-{}", state.next_batch, digest), events };
+        state.next_batch += 1;
+        state.in_flight = Some(batch.clone());
+        self.persist(&state)?;
+        Ok(Some(batch))
+    }
+
+    /// Drop a batch the central server confirmed
+    pub fn acknowledge(&self, key: &str) -> Result<(), EpcisKgError> {
+        let mut state = self.state.lock();
+        if state.in_flight.as_ref().is_some_and(|batch| batch.key == key) {
+            let batch = state.in_flight.take().expect("checked above");
+            state.forwarded += batch.events.len() as u64;
+            self.persist(&state)?;
+        }
+        Ok(())
+    }
+
+    /// Events waiting, including the frozen batch
+    pub fn pending(&self) -> usize {
+        let state = self.state.lock();
+        state.queued.len() + state.in_flight.as_ref().map_or(0, |batch| batch.events.len())
+    }
+
+    pub fn forwarded(&self) -> u64 {
+        self.state.lock().forwarded
+    }
+
+    fn persist(&self, state: &QueueState) -> Result<(), EpcisKgError> {
+        let Some(path) = &self.path else { return Ok(()) };
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let temporary = path.with_extension("json.tmp");
+        std::fs::write(&temporary, serde_json::to_string(state)?)?;
+        Ok(std::fs::rename(&temporary, path)?)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EdgeStatus {
+    pub central_url: String,
+    pub pending: usize,
+    pub forwarded: u64,
+    pub last_forwarded_at: Option<String>,
+    /// Why the last attempt failed; cleared by the next successful one
+    pub last_error: Option<String>,
+}
+
+#[derive(Default)]
+struct ForwardOutcome {
+    last_forwarded_at: Option<DateTime<Utc>>,
+    last_error: Option<String>,
+}
+
+/// Forwards the queue to the central server whenever it is reachable
+pub struct EdgeForwarder {
+    config: EdgeConfig,
+    queue: Arc<EdgeQueue>,
+    client: reqwest::Client,
+    outcome: Mutex<ForwardOutcome>,
+}
+
+impl EdgeForwarder {
+    pub fn new(config: &EdgeConfig, queue: Arc<EdgeQueue>) -> Self {
+        Self {
+            config: config.clone(),
+            queue,
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(config.request_timeout_seconds))
+                .build()
+                .unwrap_or_default(),
+            outcome: Mutex::new(ForwardOutcome::default()),
+        }
+    }
+
+    pub fn queue(&self) -> &EdgeQueue {
+        &self.queue
+    }
+
+    /// Forward one batch; `None` when the queue is empty
+    async fn forward_batch(&self) -> Result<Option<usize>, EpcisKgError> {
+        let Some(batch) = self.queue.next_batch(self.config.batch_size)? else { return Ok(None) };
+        let url = format!("{}/api/v1/events", self.config.central_url.trim_end_matches('/'));
+        let mut request = self
+            .client
+            .post(&url)
+            .header(IDEMPOTENCY_KEY_HEADER, &batch.key)
+            .json(&serde_json::json!({ "events": batch.events }));
+        if let Some(api_key) = &self.config.api_key {
+            request = request.header(API_KEY_HEADER, api_key);
+        }
+
+        let unavailable = |message: String| EpcisKgError::Unavailable { message, retry_after_secs: Some(self.config.forward_interval_seconds) };
+        let response = request.send().await.map_err(|e| unavailable(format!("Central server unreachable: {}", e)))?;
+        let status = response.status();
+        if status.is_success() {
+            self.queue.acknowledge(&batch.key)?;
+            return Ok(Some(batch.events.len()));
+        }
+        let body = response.text().await.unwrap_or_default();
+        match status.is_server_error() || status == reqwest::StatusCode::CONFLICT || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            true => Err(unavailable(format!("Central server answered {}: {}", status, body))),
+            // Kept queued rather than dropped: the scans need an operator's attention
+            false => Err(EpcisKgError::Validation(format!("Central server refused batch {} ({}): {}", batch.key, status, body))),
+        }
+    }
+
+    /// Forward batches until the queue is empty or an attempt fails; returns the events forwarded
+    pub async fn flush(&self) -> Result<usize, EpcisKgError> {
+        let mut forwarded = 0;
+        let result = loop {
+            match self.forward_batch().await {
+                Ok(Some(count)) => forwarded += count,
+                Ok(None) => break Ok(forwarded),
+                Err(e) => break Err(e),
+            }
+        };
+        let mut outcome = self.outcome.lock();
+        if forwarded > 0 {
+            outcome.last_forwarded_at = Some(Utc::now());
+        }
+        outcome.last_error = result.as_ref().err().map(|e| e.to_string());
+        result
+    }
+
+    /// Keep flushing the queue, pausing `forward_interval_seconds` between rounds
+    pub async fn run(self: Arc<Self>) {
+        let interval = std::time::Duration::from_secs(self.config.forward_interval_seconds);
+        loop {
+            match self.flush().await {
+                Ok(0) => {}
+                Ok(forwarded) => info!("Forwarded {} events to {}", forwarded, self.config.central_url),
+                Err(e) => warn!("Forwarding to {} failed, {} events kept: {}", self.config.central_url, self.queue.pending(), e),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+
+    pub fn status(&self) -> EdgeStatus {
+        let outcome = self.outcome.lock();
+        EdgeStatus {
+            central_url: self.config.central_url.clone(),
+            pending: self.queue.pending(),
+            forwarded: self.queue.forwarded(),
+            last_forwarded_at: outcome.last_forwarded_at.map(|time| time.to_rfc3339()),
+            last_error: outcome.last_error.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct EdgeCaptureRequest {
+    events: Vec<EpcisEvent>,
+}
+
+/// Queue scans for forwarding; they are on disk when this answers
+async fn edge_capture(State(forwarder): State<Arc<EdgeForwarder>>, Json(payload): Json<EdgeCaptureRequest>) -> Response {
+    match forwarder.queue().push(payload.events) {
+        Ok((accepted, duplicates)) => (
+            StatusCode::ACCEPTED,
+            Json(serde_json::json!({
+                "success": true,
+                "accepted": accepted,
+                "duplicates": duplicates,
+                "pending": forwarder.queue().pending()
+            })),
+        )
+            .into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn edge_status(State(forwarder): State<Arc<EdgeForwarder>>) -> Json<EdgeStatus> {
+    Json(forwarder.status())
+}
+
+/// Forward the queue now instead of waiting for the next round
+async fn edge_flush(State(forwarder): State<Arc<EdgeForwarder>>) -> Response {
+    match forwarder.flush().await {
+        Ok(forwarded) => Json(serde_json::json!({ "forwarded": forwarded, "status": forwarder.status() })).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Run the site's capture API and forward its queue to the central server
+pub async fn run(config: &EdgeConfig) -> Result<(), EpcisKgError> {
+    if config.central_url.is_empty() {
+        return Err(EpcisKgError::Config("Edge mode needs [edge] central_url".to_string()));
+    }
+    let queue = Arc::new(EdgeQueue::open(&config.queue_path)?);
+    let forwarder = Arc::new(EdgeForwarder::new(config, queue));
+    info!("Edge queue at {} holds {} events for {}", config.queue_path, forwarder.queue().pending(), config.central_url);
+    tokio::spawn(Arc::clone(&forwarder).run());
+
+    let app = Router::new()
+        .route("/api/v1/events", post(edge_capture))
+        .route("/api/v1/edge/status", get(edge_status))
+        .route("/api/v1/edge/flush", post(edge_flush))
+        .with_state(forwarder);
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", config.port)).await?;
+    info!("Edge capture API listening on port {}", config.port);
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(id: &str) -> EpcisEvent {
+        serde_json::from_value(serde_json::json!({
+            "event_id": id,
+            "event_type": "ObjectEvent",
+            "event_time": "2024-03-01T09:00:00Z",
+            "record_time": "2024-03-01T09:00:05Z",
+            "event_action": "OBSERVE",
+            "epc_list": ["urn:epc:id:sgtin:0614141.107346.1"],
+            "biz_step": null,
+            "disposition": null,
+            "biz_location": null
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_batches_stay_frozen_until_acknowledged_across_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("edge_queue.json").to_string_lossy().to_string();
+        let queue = EdgeQueue::open(&path).unwrap();
+        assert_eq!(queue.push(vec![scan("e1"), scan("e2"), scan("e1")]).unwrap(), (2, 1));
+
+        let first = queue.next_batch(10).unwrap().unwrap();
+        assert_eq!(first.events.len(), 2);
+        // Scans arriving during a failed attempt do not change the batch or its key
+        queue.push(vec![scan("e3"), scan("e2")]).unwrap();
+        drop(queue);
+        let queue = EdgeQueue::open(&path).unwrap();
+        let retried = queue.next_batch(10).unwrap().unwrap();
+        assert_eq!((retried.key.as_str(), retried.events.len()), (first.key.as_str(), 2));
+        assert_eq!(queue.pending(), 3);
+
+        queue.acknowledge(&retried.key).unwrap();
+        let next = queue.next_batch(10).unwrap().unwrap();
+        assert_ne!(next.key, first.key);
+        assert_eq!(next.events[0].event_id, "e3");
+        queue.acknowledge(&next.key).unwrap();
+        assert!(queue.next_batch(10).unwrap().is_none());
+        assert_eq!(queue.forwarded(), 3);
+    }
+}
@@ -0,0 +1,214 @@
+//! gRPC interface for capture, query and trace (`grpc` feature).
+//!
+//! The service shares the REST API's [`AppState`], so both see the same
+//! store, access control entries and extension registry.
+
+use crate::api::access_control::{GraphAccess, API_KEY_HEADER};
+use crate::api::server::{check_write_quota, epc_trace_triples, with_graph_access, AppState};
+use crate::api::sparql::SparqlQuery;
+use crate::models::epcis::EpcisEvent;
+use crate::utils::jsonld;
+use crate::utils::validation::Validator;
+use crate::EpcisKgError;
+use std::collections::HashMap;
+use tokio_stream::StreamExt;
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{error, info};
+
+pub mod proto {
+    tonic::include_proto!("epcis.v1");
+}
+
+use proto::capture_event_request::Payload;
+use proto::epcis_service_server::{EpcisService, EpcisServiceServer};
+
+pub struct GrpcService {
+    state: AppState,
+}
+
+/// Serve the gRPC service on `port` in the background
+pub fn spawn(state: AppState, port: u16) {
+    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
+    info!("Starting gRPC service on {}", addr);
+    tokio::spawn(async move {
+        let service = EpcisServiceServer::new(GrpcService { state });
+        if let Err(e) = tonic::transport::Server::builder().add_service(service).serve(addr).await {
+            error!("gRPC service stopped: {}", e);
+        }
+    });
+}
+
+fn api_key<T>(request: &Request<T>) -> Option<String> {
+    let metadata = request.metadata();
+    if let Some(value) = metadata.get(API_KEY_HEADER).and_then(|v| v.to_str().ok()) {
+        return Some(value.trim().to_string());
+    }
+    metadata
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+}
+
+fn status(error: EpcisKgError) -> Status {
+    match error {
+        EpcisKgError::AccessDenied(message) => Status::permission_denied(message),
+        EpcisKgError::Validation(message) | EpcisKgError::Query(message) => Status::invalid_argument(message),
+        EpcisKgError::IriParse(e) => Status::invalid_argument(e.to_string()),
+        other @ (EpcisKgError::InvalidField { .. } | EpcisKgError::InvalidIri { .. }) => Status::invalid_argument(other.to_string()),
+        other @ EpcisKgError::NotFound { .. } => Status::not_found(other.to_string()),
+        EpcisKgError::Conflict(message) => Status::already_exists(message),
+        EpcisKgError::LimitExceeded(message) => Status::resource_exhausted(message),
+        other @ EpcisKgError::Unavailable { .. } => Status::unavailable(other.to_string()),
+        other => Status::internal(other.to_string()),
+    }
+}
+
+impl From<proto::Event> for EpcisEvent {
+    fn from(event: proto::Event) -> Self {
+        Self {
+            event_id: event.event_id,
+            event_type: event.event_type,
+            event_time: event.event_time,
+            record_time: if event.record_time.is_empty() { chrono::Utc::now().to_rfc3339() } else { event.record_time },
+            event_action: event.action,
+            epc_list: event.epc_list,
+            biz_step: event.biz_step,
+            disposition: event.disposition,
+            biz_location: event.biz_location,
+            input_epc_list: event.input_epc_list,
+            output_epc_list: event.output_epc_list,
+            ..Default::default()
+        }
+    }
+}
+
+fn decode_event(request: proto::CaptureEventRequest) -> Result<EpcisEvent, EpcisKgError> {
+    match request.payload {
+        Some(Payload::Event(event)) => Ok(event.into()),
+        Some(Payload::EventJson(json)) => Ok(serde_json::from_str(&json)?),
+        None => Err(EpcisKgError::Validation("Capture request carries no event".to_string())),
+    }
+}
+
+fn term(value: &serde_json::Value) -> proto::Term {
+    let text = |key: &str| value[key].as_str().map(str::to_string);
+    proto::Term {
+        r#type: text("type").unwrap_or_default(),
+        value: text("value").unwrap_or_default(),
+        datatype: text("datatype"),
+        lang: text("xml:lang"),
+    }
+}
+
+#[tonic::async_trait]
+impl EpcisService for GrpcService {
+    /// Validate streamed events and acknowledge them as `POST /events` does
+    async fn capture_events(
+        &self,
+        request: Request<Streaming<proto::CaptureEventRequest>>,
+    ) -> Result<Response<proto::CaptureSummary>, Status> {
+        let start_time = std::time::Instant::now();
+        check_write_quota(&self.state, api_key(&request).as_deref()).map_err(status)?;
+        // Like a `POST /events` request, the stream is one batch for query snapshots
+        let _batch = self.state.snapshots.begin_write();
+        let validator = Validator::new();
+        let mut stream = request.into_inner();
+        let mut summary = proto::CaptureSummary::default();
+
+        while let Some(message) = stream.next().await {
+            let index = summary.received;
+            summary.received += 1;
+            let event = match decode_event(message?) {
+                Ok(event) => event,
+                Err(e) => {
+                    summary.rejected.push(proto::Rejection { index, event_id: String::new(), error: e.to_string() });
+                    continue;
+                }
+            };
+            let extensions = self.state.extensions.read().validate(&event.extensions);
+            let outcome = validator.validate_epcis_event(&event).and_then(|_| {
+                if extensions.is_valid {
+                    Ok(())
+                } else {
+                    Err(EpcisKgError::Validation(extensions.errors.join("; ")))
+                }
+            });
+            match outcome {
+                Ok(()) => summary.accepted += 1,
+                Err(e) => summary.rejected.push(proto::Rejection { index, event_id: event.event_id, error: e.to_string() }),
+            }
+        }
+
+        summary.processing_time_ms = start_time.elapsed().as_millis() as u64;
+        Ok(Response::new(summary))
+    }
+
+    type QueryStream = std::pin::Pin<Box<dyn tokio_stream::Stream<Item = Result<proto::QueryRow, Status>> + Send>>;
+
+    async fn query(&self, request: Request<proto::QueryRequest>) -> Result<Response<Self::QueryStream>, Status> {
+        let api_key = api_key(&request);
+        let request = request.into_inner();
+        let bindings: HashMap<String, Vec<String>> =
+            request.bindings.into_iter().map(|(variable, list)| (variable, list.values)).collect();
+        let query = SparqlQuery { query: request.query, format: None, bindings: Some(bindings), include_archives: false, archives: None }
+            .effective_query()
+            .map_err(status)?;
+        let query = self.state.extensions.read().with_prefixes(&query);
+        if crate::api::sparql::determine_query_type(&query) != "SELECT" {
+            return Err(Status::invalid_argument("Only SELECT queries can be streamed"));
+        }
+
+        let result_json = {
+            let store_guard = self.state.store.lock().map_err(|e| Status::internal(format!("Failed to acquire store lock: {}", e)))?;
+            let query = self
+                .state
+                .access_control
+                .read()
+                .map_err(|e| Status::internal(format!("Failed to acquire access control lock: {}", e)))?
+                .restrict_query(api_key.as_deref(), &query, &store_guard.graph_names())
+                .map_err(status)?;
+            store_guard.query_select(&query).map_err(status)?
+        };
+
+        let result: serde_json::Value = serde_json::from_str(&result_json).map_err(|e| Status::internal(e.to_string()))?;
+        let rows: Vec<Result<proto::QueryRow, Status>> = result["results"]["bindings"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .iter()
+            .map(|solution| {
+                let bindings = solution
+                    .as_object()
+                    .map(|solution| solution.iter().map(|(variable, value)| (variable.clone(), term(value))).collect())
+                    .unwrap_or_default();
+                Ok(proto::QueryRow { bindings })
+            })
+            .collect();
+        Ok(Response::new(Box::pin(tokio_stream::iter(rows))))
+    }
+
+    async fn trace(&self, request: Request<proto::TraceRequest>) -> Result<Response<proto::TraceResponse>, Status> {
+        let api_key = api_key(&request);
+        let epc = request.into_inner().epc;
+        let triples = with_graph_access(&self.state, api_key.as_deref(), "urn:epcis:event:", GraphAccess::Read, |store| {
+            Ok(epc_trace_triples(store, &epc))
+        })
+        .map_err(status)?;
+
+        let mut frame = jsonld::load_frame(jsonld::EPC_FRAME, &self.state.config.jsonld).map_err(status)?;
+        frame["@id"] = serde_json::Value::String(epc);
+        let framed = jsonld::JsonLdFramer::frame(&triples, &frame).map_err(status)?;
+        let event_count = triples
+            .iter()
+            .filter(|triple| triple.predicate.as_str() == "urn:epcglobal:epcis:epcList")
+            .map(|triple| triple.subject.to_string())
+            .collect::<std::collections::HashSet<_>>()
+            .len() as u64;
+
+        Ok(Response::new(proto::TraceResponse {
+            jsonld: framed.to_string(),
+            event_count,
+        }))
+    }
+}
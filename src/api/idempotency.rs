@@ -0,0 +1,322 @@
+use crate::api::access_control::api_key_from_headers;
+use crate::api::problem::problem_response;
+use crate::config::{IdempotencyConfig, ServerConfig};
+use crate::EpcisKgError;
+use axum::body::{Body, Bytes};
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, Duration, Utc};
+use futures::StreamExt;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Request header naming a client-chosen key for one logical request
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Response header set when a stored response is replayed
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// API routes whose POST requests honour `Idempotency-Key`
+const IDEMPOTENT_PATHS: [&str; 3] = ["/events", "/ontologies", "/sparql/update"];
+
+/// Route whose bodies may be as large as `server.max_upload_bytes`
+const UPLOAD_PATH: &str = "/ontologies";
+
+const MAX_KEY_LENGTH: usize = 255;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct StoredResponse {
+    status: u16,
+    content_type: Option<String>,
+    body: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IdempotencyRecord {
+    /// Hash of the method, path and body the key was first used with
+    fingerprint: String,
+    created_at: DateTime<Utc>,
+    /// `None` while the first request is still running
+    response: Option<StoredResponse>,
+}
+
+/// What to do with a request carrying a key
+#[derive(Debug, PartialEq)]
+enum Lookup {
+    /// First use of the key: run the request
+    Run,
+    Replay(StoredResponse),
+    /// The first request with this key has not finished yet
+    InProgress,
+    /// The key was used before with a different request
+    Mismatch,
+}
+
+/// Fingerprints and responses of requests sent with an `Idempotency-Key`.
+///
+/// Keys are scoped to the caller's API key. A retry with the same key and
+/// the same request gets the stored response back instead of being applied
+/// again; the same key with a different request is refused. Records expire
+/// after `ttl_secs` and, unless the store is in memory, survive restarts.
+///
+/// Request bodies are read whole to fingerprint them, so they are held to the
+/// server's body limits here, before any extractor sees them.
+pub struct IdempotencyCache {
+    enabled: bool,
+    ttl: Duration,
+    max_response_bytes: usize,
+    max_body_bytes: usize,
+    max_upload_bytes: usize,
+    path: Option<PathBuf>,
+    records: Mutex<HashMap<String, IdempotencyRecord>>,
+}
+
+impl IdempotencyCache {
+    /// Load persisted records from `config.state_path` when `persist` is set
+    pub fn open(config: &IdempotencyConfig, server: &ServerConfig, persist: bool) -> Result<Self, EpcisKgError> {
+        let path = Some(PathBuf::from(&config.state_path)).filter(|_| persist);
+        let records = match path.as_deref().filter(|path| path.exists()) {
+            Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+            None => HashMap::new(),
+        };
+
+        let cache = Self {
+            enabled: config.enabled,
+            ttl: Duration::seconds(config.ttl_secs as i64),
+            max_response_bytes: config.max_response_bytes,
+            max_body_bytes: server.max_body_bytes,
+            max_upload_bytes: usize::try_from(server.max_upload_bytes).unwrap_or(usize::MAX),
+            path,
+            records: Mutex::new(records),
+        };
+        cache.records.lock().retain(|_, record| record.created_at + cache.ttl > Utc::now());
+        Ok(cache)
+    }
+
+    fn begin(&self, key: &str, fingerprint: &str, now: DateTime<Utc>) -> Lookup {
+        let mut records = self.records.lock();
+        records.retain(|_, record| record.created_at + self.ttl > now);
+
+        match records.get(key) {
+            Some(record) if record.fingerprint != fingerprint => Lookup::Mismatch,
+            Some(IdempotencyRecord { response: Some(response), .. }) => Lookup::Replay(response.clone()),
+            Some(_) => Lookup::InProgress,
+            None => {
+                records.insert(
+                    key.to_string(),
+                    IdempotencyRecord { fingerprint: fingerprint.to_string(), created_at: now, response: None },
+                );
+                Lookup::Run
+            }
+        }
+    }
+
+    /// Keep the response of a finished request, or forget the key so the request can be retried
+    fn complete(&self, key: &str, response: Option<StoredResponse>) {
+        {
+            let mut records = self.records.lock();
+            match response {
+                Some(response) => {
+                    if let Some(record) = records.get_mut(key) {
+                        record.response = Some(response);
+                    }
+                }
+                None => {
+                    records.remove(key);
+                }
+            }
+        }
+        self.persist();
+    }
+
+    fn persist(&self) {
+        let Some(path) = &self.path else { return };
+        let completed: HashMap<String, IdempotencyRecord> = self
+            .records
+            .lock()
+            .iter()
+            .filter(|(_, record)| record.response.is_some())
+            .map(|(key, record)| (key.clone(), record.clone()))
+            .collect();
+        let written = serde_json::to_string(&completed).map_err(EpcisKgError::from).and_then(|content| {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let temporary = path.with_extension("json.tmp");
+            std::fs::write(&temporary, content)?;
+            Ok(std::fs::rename(&temporary, path)?)
+        });
+        if let Err(e) = written {
+            warn!("Failed to save idempotency keys to {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Record key: the idempotency key within the scope of the caller's API key
+fn record_key(api_key: Option<&str>, key: &str) -> String {
+    let digest = Sha256::digest(format!("{}\n{}", api_key.unwrap_or_default(), key).as_bytes());
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn fingerprint(method: &Method, uri: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(uri.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Read a body whole, refusing it once it grows past `limit` bytes
+async fn read_limited(body: Body, limit: usize) -> Result<Bytes, EpcisKgError> {
+    let mut stream = body.into_data_stream();
+    let mut buffered = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| EpcisKgError::Validation(format!("Failed to read request body: {}", e)))?;
+        if buffered.len() + chunk.len() > limit {
+            return Err(EpcisKgError::LimitExceeded(format!("Request body is larger than {} bytes", limit)));
+        }
+        buffered.extend_from_slice(&chunk);
+    }
+    Ok(Bytes::from(buffered))
+}
+
+/// Responses a client is expected to retry are not kept
+fn is_final(status: StatusCode) -> bool {
+    !(status.is_server_error() || status == StatusCode::CONFLICT || status == StatusCode::TOO_MANY_REQUESTS)
+}
+
+/// Replay the stored response for a repeated `Idempotency-Key`, or run the
+/// request and store its response for later retries
+pub async fn idempotent_requests(State(cache): State<Arc<IdempotencyCache>>, request: Request, next: Next) -> Response {
+    let applies = cache.enabled
+        && request.method() == Method::POST
+        && IDEMPOTENT_PATHS.contains(&request.uri().path())
+        && request.headers().contains_key(IDEMPOTENCY_KEY_HEADER);
+    if !applies {
+        return next.run(request).await;
+    }
+
+    let key = match request.headers().get(IDEMPOTENCY_KEY_HEADER).and_then(|value| value.to_str().ok()).map(str::trim) {
+        Some(key) if !key.is_empty() && key.len() <= MAX_KEY_LENGTH => key.to_string(),
+        _ => {
+            return EpcisKgError::InvalidField {
+                field: "Idempotency-Key".to_string(),
+                message: format!("must be 1 to {} visible ASCII characters", MAX_KEY_LENGTH),
+            }
+            .into_response()
+        }
+    };
+    let key = record_key(api_key_from_headers(request.headers()).as_deref(), &key);
+
+    let (parts, body) = request.into_parts();
+    let limit = if parts.uri.path() == UPLOAD_PATH { cache.max_upload_bytes } else { cache.max_body_bytes };
+    let body = match read_limited(body, limit).await {
+        Ok(body) => body,
+        Err(e @ EpcisKgError::LimitExceeded(_)) => return problem_response(StatusCode::PAYLOAD_TOO_LARGE, &e),
+        Err(e) => return e.into_response(),
+    };
+    let uri = parts.uri.to_string();
+    match cache.begin(&key, &fingerprint(&parts.method, &uri, &body), Utc::now()) {
+        Lookup::Run => {}
+        Lookup::Replay(stored) => return replay(stored),
+        Lookup::InProgress => {
+            return EpcisKgError::Conflict("A request with this Idempotency-Key is still being processed".to_string()).into_response()
+        }
+        Lookup::Mismatch => {
+            let error = EpcisKgError::InvalidField {
+                field: "Idempotency-Key".to_string(),
+                message: "the key was already used with a different request".to_string(),
+            };
+            return problem_response(StatusCode::UNPROCESSABLE_ENTITY, &error);
+        }
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    let (parts, body) = response.into_parts();
+    if !is_final(parts.status) {
+        cache.complete(&key, None);
+        return Response::from_parts(parts, body);
+    }
+
+    // Only responses small enough to keep are buffered; larger ones pass through and release the key
+    let mut stream = body.into_data_stream();
+    let mut buffered = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(chunk) => buffered.extend_from_slice(&chunk),
+            Err(e) => {
+                cache.complete(&key, None);
+                return EpcisKgError::Storage(format!("Failed to read response body: {}", e)).into_response();
+            }
+        }
+        if buffered.len() > cache.max_response_bytes {
+            cache.complete(&key, None);
+            let read = futures::stream::once(async move { Ok(Bytes::from(buffered)) });
+            return Response::from_parts(parts, Body::from_stream(read.chain(stream)));
+        }
+    }
+
+    let stored = String::from_utf8(buffered.clone()).ok().map(|text| StoredResponse {
+        status: parts.status.as_u16(),
+        content_type: parts.headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()).map(str::to_string),
+        body: text,
+    });
+    cache.complete(&key, stored);
+    Response::from_parts(parts, Body::from(buffered))
+}
+
+fn replay(stored: StoredResponse) -> Response {
+    let status = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+    let mut response = (status, stored.body).into_response();
+    if let Some(content_type) = stored.content_type.and_then(|value| HeaderValue::from_str(&value).ok()) {
+        response.headers_mut().insert(header::CONTENT_TYPE, content_type);
+    }
+    response.headers_mut().insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retries_replay_and_mismatches_are_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = IdempotencyConfig {
+            state_path: dir.path().join("idempotency.json").to_string_lossy().to_string(),
+            ..IdempotencyConfig::default()
+        };
+        let cache = IdempotencyCache::open(&config, &ServerConfig::default(), true).unwrap();
+        let now = Utc::now();
+        let key = record_key(Some("partner-key"), "capture-42");
+        let request = fingerprint(&Method::POST, "/events", br#"{"events":[]}"#);
+
+        assert_eq!(cache.begin(&key, &request, now), Lookup::Run);
+        assert_eq!(cache.begin(&key, &request, now), Lookup::InProgress);
+        let stored = StoredResponse { status: 200, content_type: Some("application/json".to_string()), body: "{}".to_string() };
+        cache.complete(&key, Some(stored.clone()));
+        assert_eq!(cache.begin(&key, &request, now), Lookup::Replay(stored.clone()));
+        assert_eq!(cache.begin(&key, &fingerprint(&Method::POST, "/events", b"{}"), now), Lookup::Mismatch);
+
+        // Another API key has its own key space, and records outlive a restart until they expire
+        assert_eq!(cache.begin(&record_key(Some("other-key"), "capture-42"), &request, now), Lookup::Run);
+        let reopened = IdempotencyCache::open(&config, &ServerConfig::default(), true).unwrap();
+        assert_eq!(reopened.begin(&key, &request, now), Lookup::Replay(stored));
+        assert_eq!(reopened.begin(&key, &request, now + Duration::seconds(config.ttl_secs as i64 + 1)), Lookup::Run);
+
+        // A failed request releases its key
+        let failed = record_key(None, "capture-43");
+        assert_eq!(cache.begin(&failed, &request, now), Lookup::Run);
+        cache.complete(&failed, None);
+        assert_eq!(cache.begin(&failed, &request, now), Lookup::Run);
+    }
+}
@@ -1,3 +1,16 @@
+pub mod access_control;
+pub mod cluster;
+pub mod compression;
+pub mod conditional;
+pub mod edge;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod idempotency;
+pub mod pgwire;
+pub mod problem;
+pub mod quotas;
 pub mod server;
 pub mod sparql;
-pub mod routes;
\ No newline at end of file
+pub mod sql;
+pub mod uploads;
+pub mod routes;
@@ -0,0 +1,238 @@
+//! Minimal PostgreSQL wire-protocol front end for the read-only SQL views.
+//!
+//! Only the simple query protocol is served, which is what `psql` and most
+//! drivers in "simple" mode use. When access control is enabled the client
+//! password is taken as the API key.
+
+use crate::api::access_control::GraphAccess;
+use crate::api::server::{with_graph_access, AppState};
+use crate::api::sql::{self, SqlResult};
+use crate::EpcisKgError;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{error, info, warn};
+
+const PROTOCOL_VERSION_3: i32 = 196_608;
+const SSL_REQUEST: i32 = 80_877_103;
+const TEXT_OID: i32 = 25;
+const FLOAT8_OID: i32 = 701;
+
+/// Accept Postgres connections on `port` in the background
+pub fn spawn(state: AppState, port: u16) {
+    let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
+    info!("Starting SQL (Postgres wire) interface on {}", addr);
+    tokio::spawn(async move {
+        let listener = match TcpListener::bind(addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("SQL interface could not bind {}: {}", addr, e);
+                return;
+            }
+        };
+        loop {
+            match listener.accept().await {
+                Ok((socket, peer)) => {
+                    let state = state.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = serve(socket, state).await {
+                            warn!("SQL connection from {} closed: {}", peer, e);
+                        }
+                    });
+                }
+                Err(e) => warn!("Failed to accept SQL connection: {}", e),
+            }
+        }
+    });
+}
+
+/// A backend message: tag byte, then length-prefixed body
+struct Message {
+    tag: u8,
+    body: Vec<u8>,
+}
+
+impl Message {
+    fn new(tag: u8) -> Self {
+        Self { tag, body: Vec::new() }
+    }
+
+    fn i16(mut self, value: i16) -> Self {
+        self.body.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    fn i32(mut self, value: i32) -> Self {
+        self.body.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    fn cstr(mut self, value: &str) -> Self {
+        self.body.extend_from_slice(value.as_bytes());
+        self.body.push(0);
+        self
+    }
+
+    fn bytes(mut self, value: &[u8]) -> Self {
+        self.body.extend_from_slice(value);
+        self
+    }
+
+    fn encode(self) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(self.body.len() + 5);
+        frame.push(self.tag);
+        frame.extend_from_slice(&(self.body.len() as i32 + 4).to_be_bytes());
+        frame.extend_from_slice(&self.body);
+        frame
+    }
+}
+
+fn error_response(code: &str, message: &str) -> Vec<u8> {
+    Message::new(b'E')
+        .bytes(b"S")
+        .cstr("ERROR")
+        .bytes(b"C")
+        .cstr(code)
+        .bytes(b"M")
+        .cstr(message)
+        .bytes(&[0])
+        .encode()
+}
+
+fn ready_for_query() -> Vec<u8> {
+    Message::new(b'Z').bytes(b"I").encode()
+}
+
+async fn read_i32(socket: &mut TcpStream) -> std::io::Result<i32> {
+    let mut buffer = [0u8; 4];
+    socket.read_exact(&mut buffer).await?;
+    Ok(i32::from_be_bytes(buffer))
+}
+
+async fn read_body(socket: &mut TcpStream) -> std::io::Result<Vec<u8>> {
+    let length = read_i32(socket).await?;
+    if !(4..=16 * 1024 * 1024).contains(&length) {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid message length"));
+    }
+    let mut body = vec![0u8; length as usize - 4];
+    socket.read_exact(&mut body).await?;
+    Ok(body)
+}
+
+fn cstring(body: &[u8]) -> String {
+    let end = body.iter().position(|b| *b == 0).unwrap_or(body.len());
+    String::from_utf8_lossy(&body[..end]).into_owned()
+}
+
+async fn serve(mut socket: TcpStream, state: AppState) -> std::io::Result<()> {
+    // Startup, declining TLS if the client asks for it first
+    let mut startup = read_body(&mut socket).await?;
+    if startup.get(..4) == Some(&SSL_REQUEST.to_be_bytes()[..]) {
+        socket.write_all(b"N").await?;
+        startup = read_body(&mut socket).await?;
+    }
+    if startup.get(..4) != Some(&PROTOCOL_VERSION_3.to_be_bytes()[..]) {
+        socket.write_all(&error_response("08P01", "Unsupported protocol version")).await?;
+        return Ok(());
+    }
+
+    let access_control_enabled = state.access_control.read().map(|acl| acl.is_enabled()).unwrap_or(true);
+    let api_key = if access_control_enabled {
+        socket.write_all(&Message::new(b'R').i32(3).encode()).await?;
+        let mut tag = [0u8; 1];
+        socket.read_exact(&mut tag).await?;
+        let body = read_body(&mut socket).await?;
+        if tag[0] != b'p' {
+            return Ok(());
+        }
+        let key = cstring(&body);
+        if let Err(e) = authorize(&state, Some(&key)) {
+            socket.write_all(&error_response("28P01", &e.to_string())).await?;
+            return Ok(());
+        }
+        Some(key)
+    } else {
+        None
+    };
+
+    let mut greeting = Message::new(b'R').i32(0).encode();
+    for (name, value) in [("server_version", "14.0"), ("server_encoding", "UTF8"), ("client_encoding", "UTF8"), ("DateStyle", "ISO")] {
+        greeting.extend(Message::new(b'S').cstr(name).cstr(value).encode());
+    }
+    greeting.extend(ready_for_query());
+    socket.write_all(&greeting).await?;
+
+    // After an extended-protocol message is refused, skip input until Sync
+    let mut skipping = false;
+    loop {
+        let mut tag = [0u8; 1];
+        if socket.read_exact(&mut tag).await.is_err() {
+            return Ok(());
+        }
+        let body = read_body(&mut socket).await?;
+        let response = match tag[0] {
+            b'X' => return Ok(()),
+            b'Q' => {
+                let mut response = query_response(&state, api_key.as_deref(), &cstring(&body));
+                response.extend(ready_for_query());
+                response
+            }
+            b'S' => {
+                skipping = false;
+                ready_for_query()
+            }
+            _ if skipping => continue,
+            _ => {
+                skipping = true;
+                error_response("0A000", "Only the simple query protocol is supported")
+            }
+        };
+        socket.write_all(&response).await?;
+    }
+}
+
+fn authorize(state: &AppState, api_key: Option<&str>) -> Result<(), EpcisKgError> {
+    with_graph_access(state, api_key, "urn:epcis:event:", GraphAccess::Read, |_| Ok(()))
+}
+
+fn query_response(state: &AppState, api_key: Option<&str>, query: &str) -> Vec<u8> {
+    let query = query.trim().trim_end_matches(';').trim();
+    if query.is_empty() {
+        return Message::new(b'I').encode();
+    }
+    // Drivers configure sessions on connect; accept and ignore
+    if query.get(..4).map_or(false, |prefix| prefix.eq_ignore_ascii_case("set ")) {
+        return Message::new(b'C').cstr("SET").encode();
+    }
+
+    let result = with_graph_access(state, api_key, "urn:epcis:event:", GraphAccess::Read, |store| {
+        sql::execute(query, &store.event_tables())
+    });
+    match result {
+        Ok(result) => rows_response(&result),
+        Err(EpcisKgError::AccessDenied(message)) => error_response("42501", &message),
+        Err(EpcisKgError::Validation(message)) => error_response("42601", &message),
+        Err(e) => error_response("XX000", &e.to_string()),
+    }
+}
+
+fn rows_response(result: &SqlResult) -> Vec<u8> {
+    let mut description = Message::new(b'T').i16(result.columns.len() as i16);
+    for (i, column) in result.columns.iter().enumerate() {
+        let (type_oid, type_size) = if result.is_numeric(i) { (FLOAT8_OID, 8) } else { (TEXT_OID, -1) };
+        description = description.cstr(column).i32(0).i16(0).i32(type_oid).i16(type_size).i32(-1).i16(0);
+    }
+
+    let mut response = description.encode();
+    for row in &result.rows {
+        let mut data = Message::new(b'D').i16(row.len() as i16);
+        for value in row {
+            data = match value.to_text() {
+                Some(text) => data.i32(text.len() as i32).bytes(text.as_bytes()),
+                None => data.i32(-1),
+            };
+        }
+        response.extend(data.encode());
+    }
+    response.extend(Message::new(b'C').cstr(&format!("SELECT {}", result.rows.len())).encode());
+    response
+}
@@ -0,0 +1,165 @@
+use crate::utils::i18n::{accepted_languages, MessageCatalog, SOURCE_LANGUAGE};
+use crate::EpcisKgError;
+use axum::extract::{Request, State};
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use std::sync::Arc;
+
+/// Media type of RFC 9457 problem details
+pub const PROBLEM_MEDIA_TYPE: &str = "application/problem+json";
+
+/// Longest plain-text rejection body kept as a problem's detail
+const MAX_REJECTION_BYTES: usize = 64 * 1024;
+
+/// HTTP status an error is reported with
+pub fn status_code(error: &EpcisKgError) -> StatusCode {
+    match error {
+        EpcisKgError::Validation(_)
+        | EpcisKgError::Query(_)
+        | EpcisKgError::Json(_)
+        | EpcisKgError::RdfParsing(_)
+        | EpcisKgError::RdfSyntax { .. }
+        | EpcisKgError::Toml(_)
+        | EpcisKgError::IriParse(_)
+        | EpcisKgError::BlankNodeIdParse(_)
+        | EpcisKgError::InvalidField { .. }
+        | EpcisKgError::InvalidIri { .. } => StatusCode::BAD_REQUEST,
+        EpcisKgError::AccessDenied(_) => StatusCode::FORBIDDEN,
+        EpcisKgError::NotFound { .. } => StatusCode::NOT_FOUND,
+        EpcisKgError::Conflict(_) => StatusCode::CONFLICT,
+        EpcisKgError::LimitExceeded(_) => StatusCode::TOO_MANY_REQUESTS,
+        EpcisKgError::NotImplemented(_) => StatusCode::NOT_IMPLEMENTED,
+        EpcisKgError::Unavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
+        EpcisKgError::Io(_)
+        | EpcisKgError::Config(_)
+        | EpcisKgError::Ontology(_)
+        | EpcisKgError::Storage(_)
+        | EpcisKgError::Generic(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Problem details response for an error with an explicit status.
+///
+/// The body carries the error's `code`, whether it is `retryable` and the
+/// offending `field`, `iri` or RDF syntax `line` and `column`. `error`
+/// repeats the detail for clients written against the earlier
+/// `{"error", "status"}` bodies.
+pub fn problem_response(status: StatusCode, error: &EpcisKgError) -> Response {
+    let detail = error.to_string();
+    let mut body = serde_json::json!({
+        "type": format!("urn:epcis-kg:error:{}", error.code()),
+        "title": status.canonical_reason().unwrap_or("Error"),
+        "status": status.as_u16(),
+        "detail": detail,
+        "code": error.code(),
+        "retryable": error.is_retryable(),
+        "error": detail,
+    });
+    if let Some(field) = error.field() {
+        body["field"] = serde_json::Value::from(field);
+    }
+    if let Some(iri) = error.iri() {
+        body["iri"] = serde_json::Value::from(iri);
+    }
+    if let Some((line, column)) = error.position() {
+        body["line"] = serde_json::Value::from(line);
+        body["column"] = serde_json::Value::from(column);
+    }
+
+    let mut response = (status, Json(body)).into_response();
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_MEDIA_TYPE));
+    if let EpcisKgError::Unavailable { retry_after_secs: Some(seconds), .. } = error {
+        response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(*seconds));
+    }
+    response
+}
+
+impl IntoResponse for EpcisKgError {
+    fn into_response(self) -> Response {
+        problem_response(status_code(&self), &self)
+    }
+}
+
+/// Rewrite the plain-text rejections axum produces itself (malformed JSON
+/// bodies, bad query strings, unknown routes) as problem details, so every API
+/// failure has the same shape
+pub async fn rejections_as_problems(request: Request, next: Next) -> Response {
+    let path = request.uri().path().to_string();
+    let response = next.run(request).await;
+    let status = response.status();
+    let is_plain = match response.headers().get(header::CONTENT_TYPE) {
+        Some(value) => value.as_bytes().starts_with(b"text/plain"),
+        None => true,
+    };
+    if !(status.is_client_error() || status.is_server_error()) || !is_plain {
+        return response;
+    }
+
+    let body = axum::body::to_bytes(response.into_body(), MAX_REJECTION_BYTES).await.unwrap_or_default();
+    let message = String::from_utf8_lossy(&body).trim().to_string();
+    let error = match status {
+        StatusCode::NOT_FOUND => EpcisKgError::NotFound { resource: "Resource".to_string(), id: path },
+        status if status.is_server_error() => EpcisKgError::Generic(message.into()),
+        _ => EpcisKgError::Validation(message),
+    };
+    problem_response(status, &error)
+}
+
+/// Translate problem titles into the request's `Accept-Language`.
+///
+/// Titles are looked up as `problem.<code>` in the message catalogs and the
+/// response gets a `Content-Language`. The `detail` is left as produced, since
+/// it carries identifiers and values from the failed request.
+pub async fn localize_problems(State(messages): State<Arc<MessageCatalog>>, request: Request, next: Next) -> Response {
+    let preferred = request
+        .headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(accepted_languages)
+        .unwrap_or_default();
+    let language = messages.negotiate(&preferred);
+    let response = next.run(request).await;
+    let is_problem = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes() == PROBLEM_MEDIA_TYPE.as_bytes());
+    if !is_problem || language == SOURCE_LANGUAGE {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = axum::body::to_bytes(body, MAX_REJECTION_BYTES).await.unwrap_or_default();
+    let Ok(mut problem) = serde_json::from_slice::<serde_json::Value>(&body) else {
+        return Response::from_parts(parts, axum::body::Body::from(body));
+    };
+    if let Some(code) = problem["code"].as_str() {
+        problem["title"] = serde_json::Value::from(messages.message(&language, &format!("problem.{}", code), &[]));
+    }
+    parts.headers.remove(header::CONTENT_LENGTH);
+    if let Ok(value) = HeaderValue::from_str(&language) {
+        parts.headers.insert(header::CONTENT_LANGUAGE, value);
+    }
+    Response::from_parts(parts, axum::body::Body::from(problem.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_map_to_problem_details() {
+        let response = EpcisKgError::NotFound { resource: "Session".to_string(), id: "s1".to_string() }.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_MEDIA_TYPE);
+
+        let response = EpcisKgError::Unavailable { message: "reasoner busy".to_string(), retry_after_secs: Some(5) }.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "5");
+
+        assert_eq!(status_code(&EpcisKgError::InvalidIri { iri: "x".to_string(), message: "no scheme".to_string() }), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,251 @@
+use crate::api::access_control::{GraphAccess, GraphAccessControl};
+use crate::config::{AccessControlConfig, ApiKeyAcl, TenantQuota};
+use crate::storage::oxigraph_store::OxigraphStore;
+use crate::EpcisKgError;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::warn;
+
+/// Resources a tenant (API key) uses against its quota
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TenantUsage {
+    pub tenant: String,
+    /// Graphs the key may write, whose contents count towards its storage
+    pub graphs: usize,
+    pub triples: usize,
+    pub storage_bytes: u64,
+    /// Query time used in the current quota window
+    pub query_seconds: f64,
+    /// Start of the current quota window; absent before the first query
+    pub query_window_started: Option<DateTime<Utc>>,
+    pub quota: TenantQuota,
+    /// Limits reached: `triples`, `storage_bytes` or `query_seconds`
+    pub exceeded: Vec<String>,
+}
+
+/// Query time one API key used in its current quota window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct QueryTime {
+    window_started: DateTime<Utc>,
+    seconds: f64,
+}
+
+/// Query time per API key, and quota checks against it and the store.
+///
+/// Query time adds up over each key's quota window and starts again from zero
+/// when the window ends. It is saved to the state file after every query, so
+/// a restart does not hand a tenant a fresh allowance.
+#[derive(Debug, Default)]
+pub struct QuotaTracker {
+    /// Keyed by a digest of the API key, so the state file holds no keys
+    query_time: Mutex<HashMap<String, QueryTime>>,
+    path: Option<PathBuf>,
+}
+
+impl QuotaTracker {
+    /// Tracker that keeps query time in memory only
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tracker that loads and saves query time in `access_control.quota_state_path`
+    /// when `persist` is set
+    pub fn open(config: &AccessControlConfig, persist: bool) -> Result<Self, EpcisKgError> {
+        let path = Some(PathBuf::from(&config.quota_state_path)).filter(|_| persist);
+        let query_time = match path.as_deref().filter(|path| path.exists()) {
+            Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+            None => HashMap::new(),
+        };
+        Ok(Self {
+            query_time: Mutex::new(query_time),
+            path,
+        })
+    }
+
+    pub fn record_query(&self, acl: &ApiKeyAcl, elapsed: Duration) {
+        let now = Utc::now();
+        let window = acl.quota.clone().unwrap_or_default().query_window();
+        let mut query_time = self.query_time.lock();
+        let used = query_time.entry(tenant_key(acl)).or_insert(QueryTime { window_started: now, seconds: 0.0 });
+        if window_ended(used, window, now) {
+            *used = QueryTime { window_started: now, seconds: 0.0 };
+        }
+        used.seconds += elapsed.as_secs_f64();
+        self.persist(&query_time);
+    }
+
+    /// Query time the key used in its current window, and when that window began
+    fn query_time(&self, acl: &ApiKeyAcl) -> (f64, Option<DateTime<Utc>>) {
+        let window = acl.quota.clone().unwrap_or_default().query_window();
+        match self.query_time.lock().get(&tenant_key(acl)) {
+            Some(used) if !window_ended(used, window, Utc::now()) => (used.seconds, Some(used.window_started)),
+            _ => (0.0, None),
+        }
+    }
+
+    fn persist(&self, query_time: &HashMap<String, QueryTime>) {
+        let Some(path) = &self.path else { return };
+        let written = serde_json::to_string(query_time).map_err(EpcisKgError::from).and_then(|content| {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let temporary = path.with_extension("json.tmp");
+            std::fs::write(&temporary, content)?;
+            Ok(std::fs::rename(&temporary, path)?)
+        });
+        if let Err(e) = written {
+            warn!("Failed to save quota usage to {}: {}", path.display(), e);
+        }
+    }
+
+    pub fn usage(&self, acl: &ApiKeyAcl, store: &OxigraphStore) -> TenantUsage {
+        let owned: Vec<String> = store
+            .graph_names()
+            .into_iter()
+            .filter(|graph| GraphAccessControl::can_access(acl, graph, GraphAccess::Write))
+            .collect();
+        let triples = owned.iter().map(|graph| store.graph_len(graph)).sum();
+        let storage_bytes = owned.iter().map(|graph| store.graph_storage_bytes(graph)).sum();
+        let (query_seconds, query_window_started) = self.query_time(acl);
+        let quota = acl.quota.clone().unwrap_or_default();
+
+        let mut exceeded = Vec::new();
+        if quota.max_triples.is_some_and(|max| triples >= max) {
+            exceeded.push("triples".to_string());
+        }
+        if quota.max_storage_bytes.is_some_and(|max| storage_bytes >= max) {
+            exceeded.push("storage_bytes".to_string());
+        }
+        if quota.max_query_seconds.is_some_and(|max| query_seconds >= max) {
+            exceeded.push("query_seconds".to_string());
+        }
+
+        TenantUsage {
+            tenant: acl.name.clone(),
+            graphs: owned.len(),
+            triples,
+            storage_bytes,
+            query_seconds,
+            query_window_started,
+            quota,
+            exceeded,
+        }
+    }
+
+    /// Refuse a write (capture, update or record) once a storage limit is reached
+    pub fn check_write(&self, acl: &ApiKeyAcl, store: &OxigraphStore) -> Result<(), EpcisKgError> {
+        let limits_storage = acl.quota.as_ref().is_some_and(|quota| quota.max_triples.is_some() || quota.max_storage_bytes.is_some());
+        if !limits_storage {
+            return Ok(());
+        }
+        let usage = self.usage(acl, store);
+        match usage.exceeded.iter().find(|limit| *limit != "query_seconds") {
+            Some(limit) => Err(EpcisKgError::LimitExceeded(format!("tenant '{}' is over its {} quota", acl.name, limit))),
+            None => Ok(()),
+        }
+    }
+
+    /// Refuse a query once the key's query time is used up
+    pub fn check_query(&self, acl: &ApiKeyAcl) -> Result<(), EpcisKgError> {
+        let Some(max) = acl.quota.as_ref().and_then(|quota| quota.max_query_seconds) else {
+            return Ok(());
+        };
+        let (used, _) = self.query_time(acl);
+        if used >= max {
+            return Err(EpcisKgError::LimitExceeded(format!(
+                "tenant '{}' has used its {} s of query time",
+                acl.name, max
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn window_ended(used: &QueryTime, window: Duration, now: DateTime<Utc>) -> bool {
+    (now - used.window_started).to_std().is_ok_and(|elapsed| elapsed >= window)
+}
+
+/// State file key of an API key
+fn tenant_key(acl: &ApiKeyAcl) -> String {
+    Sha256::digest(acl.key.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_storage_and_query_quotas_are_enforced_per_tenant() {
+        let acl = ApiKeyAcl {
+            key: "partner-a-key".to_string(),
+            name: "partner-a".to_string(),
+            read_graphs: vec!["urn:epcis:partner:0614141*".to_string()],
+            write_graphs: vec!["urn:epcis:partner:0614141*".to_string()],
+            admin: false,
+            quota: Some(TenantQuota {
+                max_triples: Some(2),
+                max_storage_bytes: None,
+                max_query_seconds: Some(1.0),
+                query_window_seconds: None,
+            }),
+        };
+        let triple = |object: &str| {
+            oxrdf::Triple::new(
+                oxrdf::NamedNode::new_unchecked("urn:epc:event:1"),
+                oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:epcList"),
+                oxrdf::NamedNode::new_unchecked(object),
+            )
+        };
+        let mut store = OxigraphStore::new_memory().unwrap();
+        store.insert_triples("urn:epcis:partner:0614141:events", &[triple("urn:epc:a")]).unwrap();
+        store.insert_triples("urn:epcis:partner:0799999:events", &[triple("urn:epc:b"), triple("urn:epc:c")]).unwrap();
+
+        let quotas = QuotaTracker::new();
+        assert!(quotas.check_write(&acl, &store).is_ok(), "other tenants' graphs do not count");
+        store.insert_triples("urn:epcis:partner:0614141:events", &[triple("urn:epc:d")]).unwrap();
+        assert!(matches!(quotas.check_write(&acl, &store), Err(EpcisKgError::LimitExceeded(_))));
+
+        assert!(quotas.check_query(&acl).is_ok());
+        quotas.record_query(&acl, Duration::from_millis(1_500));
+        assert!(quotas.check_query(&acl).is_err());
+        let usage = quotas.usage(&acl, &store);
+        assert_eq!((usage.graphs, usage.triples), (1, 2));
+        assert_eq!(usage.exceeded, vec!["triples", "query_seconds"]);
+    }
+
+    #[test]
+    fn test_query_time_survives_restarts_and_resets_with_its_window() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = AccessControlConfig {
+            quota_state_path: dir.path().join("quota_usage.json").to_string_lossy().to_string(),
+            ..AccessControlConfig::default()
+        };
+        let mut acl = ApiKeyAcl {
+            key: "partner-a-key".to_string(),
+            name: "partner-a".to_string(),
+            read_graphs: vec!["*".to_string()],
+            write_graphs: Vec::new(),
+            admin: false,
+            quota: Some(TenantQuota {
+                max_query_seconds: Some(1.0),
+                query_window_seconds: Some(3600),
+                ..TenantQuota::default()
+            }),
+        };
+
+        QuotaTracker::open(&config, true).unwrap().record_query(&acl, Duration::from_millis(1_500));
+        let reopened = QuotaTracker::open(&config, true).unwrap();
+        assert!(reopened.check_query(&acl).is_err(), "a restart keeps the query time used");
+        let saved = std::fs::read_to_string(&config.quota_state_path).unwrap();
+        assert!(!saved.contains(&acl.key), "the state file holds no API keys");
+
+        acl.quota.as_mut().unwrap().query_window_seconds = Some(0);
+        assert!(reopened.check_query(&acl).is_ok(), "query time starts again once the window ends");
+        assert_eq!(reopened.usage(&acl, &OxigraphStore::new_memory().unwrap()).query_window_started, None);
+    }
+}
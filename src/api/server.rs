@@ -1,15 +1,65 @@
-use crate::config::AppConfig;
-use crate::storage::oxigraph_store::OxigraphStore;
-use crate::ontology::reasoner::OntologyReasoner;
+use crate::config::{AppConfig, ClusterRole, JobTask};
+use crate::storage::oxigraph_store::{
+    ontology_graph_prefix, upload_graph_name, LoadMode, OverlayView, OxigraphStore, CARBON_GRAPH, COLD_CHAIN_GRAPH, COUNTERFEIT_GRAPH, EVENT_PATTERNS_GRAPH, EXPIRY_GRAPH, INFERENCE_RUNS_GRAPH, OWNERSHIP_GRAPH, PARTIES_GRAPH, SAME_AS_GRAPH, VERIFICATIONS_GRAPH,
+};
+use crate::storage::overlay::{OverlaySessionManager, DEFAULT_OVERLAY_GRAPH};
+use crate::storage::archive::ArchiveFederation;
+use crate::storage::inventory::{snapshot_graph_name, INVENTORY_SNAPSHOT_PREFIX};
+use crate::storage::recall::RecallRequest;
+use crate::storage::cold_chain::Excursion;
+use crate::storage::carbon;
+use crate::storage::counterfeit::Verification;
+use crate::storage::entity_resolution::SameAsReview;
+use crate::storage::materialized::{MaterializedFilter, DEFAULT_PAGE_SIZE};
+use crate::storage::graph_catalog::{GraphFilter, GraphKind, GraphPage, ProfileStatus};
+use crate::storage::snapshots::SnapshotManager;
+use crate::storage::epcis_query::EpcisQuery;
+use crate::storage::patterns::PatternMatch;
+use crate::storage::quality::QualityOptions;
+use crate::storage::views::{is_view_source, view_graph_name, ViewDefinition, VIEW_DEFINITIONS_GRAPH};
+use crate::ontology::reasoner::{MaterializationStrategy, OntologyReasoner, MATERIALIZED_GRAPHS};
+use crate::ontology::progress::{ProgressCallback, ReasoningJobs};
+use crate::ontology::reasoner_pool::ReasonerPool;
+use crate::ontology::rematerialize::{self, RematerializeScope};
+use crate::ontology::rule_usage::RuleUsage;
+use crate::ontology::runs::{self, InferenceRun};
+use crate::ontology::scope::InferenceScope;
+use crate::ontology::skos::{extension_graph_name, VocabularyExtension, VOCABULARY_EXTENSION_GRAPH_PREFIX};
+use crate::ontology::vocabulary::CbvVocabulary;
+use crate::ontology::diagnostics::OntologyDiagnostics;
+use crate::ontology::imports::ImportGraph;
+use crate::ontology::drift::{SchemaDriftReport, UnknownTerm, SCHEMA_DRIFT_GRAPH};
+use crate::ontology::loader::OntologyLoader;
 use crate::pipeline::EpcisEventPipeline;
-use crate::models::events::ProcessingResult;
-use crate::monitoring::metrics::{SystemMonitor, AlertSeverity, AlertConfig};
-use crate::monitoring::logging::LoggingConfig;
+use crate::pipeline::lanes::{IngestPriority, PRIORITY_HEADER};
+use crate::scheduler::{JobRunner, Scheduler, TriggerOutcome};
+use crate::models::events::{ErrorDeclaration, ErrorDeclarationResult, ProcessingResult};
+use crate::models::extensions::ExtensionRegistry;
+use crate::models::parties::Party;
+use crate::config::ExtensionNamespace;
+use crate::monitoring::metrics::{SystemMonitor, AlertSeverity, AlertConfig, AlertType};
+use crate::monitoring::query_log::{QueryLog, QueryLogEntry};
+use crate::monitoring::access_log::{AccessLog, AccessLogEntry};
+use crate::monitoring::webhooks::WebhookNotifier;
+use crate::monitoring::lineage::{LineageDataset, LineageEmitter, LineageRun, RunState};
+use crate::monitoring::logging::{log_level_control, LogLevelUpdate, LoggingConfig};
+use crate::api::access_control::{api_key_from_headers, GraphAccess, GraphAccessControl};
+use crate::api::cluster::{self, Cluster, IngestRequest};
+use crate::api::compression::with_compression;
+use crate::api::conditional::conditional_reads;
+use crate::api::idempotency::{idempotent_requests, IdempotencyCache};
+use crate::api::problem::{localize_problems, problem_response, rejections_as_problems};
+use crate::api::quotas::QuotaTracker;
+use crate::api::uploads::{spool, SpooledUpload, RDF_UPLOAD_TYPES};
+use crate::utils::i18n::{self, MessageCatalog};
+use crate::utils::jsonld;
+use crate::utils::signing::{DocumentSigner, CREDENTIAL_MEDIA_TYPE};
 use crate::EpcisKgError;
 use axum::{
-    extract::Query,
+    extract::{DefaultBodyLimit, FromRequest, Multipart, Path, Query, Request},
+    http::{header, HeaderMap, StatusCode},
     response::{Json, Response, IntoResponse},
-    routing::{get, post},
+    routing::{delete, get, post, put},
     Router,
     extract::State,
 };
@@ -19,46 +69,111 @@ use tower_http::cors::{CorsLayer, Any};
 use tower_http::trace::TraceLayer;
 use tracing::info;
 
+/// Response header carrying the detached JWS of a signed trace
+const TRACE_SIGNATURE_HEADER: &str = "x-jws-signature";
+
 pub struct WebServer {
     config: Arc<AppConfig>,
     store: Arc<Mutex<OxigraphStore>>,
-    reasoner: Arc<RwLock<OntologyReasoner>>,
+    reasoner_pool: Arc<ReasonerPool>,
     pipeline: Arc<EpcisEventPipeline>,
     system_monitor: Arc<SystemMonitor>,
     logging_config: Arc<LoggingConfig>,
+    webhooks: Arc<WebhookNotifier>,
+    scheduler: Arc<Scheduler>,
+    messages: Arc<MessageCatalog>,
+    idempotency: Arc<IdempotencyCache>,
+    quotas: Arc<QuotaTracker>,
+    snapshots: Arc<SnapshotManager>,
 }
 
 #[derive(Clone)]
 pub struct AppState {
     pub store: Arc<Mutex<OxigraphStore>>,
     pub config: Arc<AppConfig>,
+    pub access_control: Arc<RwLock<GraphAccessControl>>,
+    pub reasoner_pool: Arc<ReasonerPool>,
+    pub sessions: Arc<OverlaySessionManager>,
+    pub archives: Arc<ArchiveFederation>,
+    pub extensions: Arc<parking_lot::RwLock<ExtensionRegistry>>,
+    pub monitor: Arc<SystemMonitor>,
+    pub webhooks: Arc<WebhookNotifier>,
+    /// OpenLineage run events for ontology loads, captures and materialization runs
+    pub lineage: Arc<LineageEmitter>,
+    pub scheduler: Arc<Scheduler>,
+    pub reasoning_jobs: Arc<ReasoningJobs>,
+    pub quotas: Arc<QuotaTracker>,
+    pub messages: Arc<MessageCatalog>,
+    pub query_log: Arc<QueryLog>,
+    pub access_log: Arc<AccessLog>,
+    pub rule_usage: Arc<RuleUsage>,
+    pub cluster: Arc<Cluster>,
+    pub snapshots: Arc<SnapshotManager>,
 }
 
 impl WebServer {
     pub async fn new(config: AppConfig, store: OxigraphStore) -> Result<Self, EpcisKgError> {
-        let reasoner = OntologyReasoner::with_store(store.clone());
-        let pipeline = EpcisEventPipeline::new(config.clone(), store.clone(), reasoner.clone()).await?;
+        #[cfg(feature = "kafka")]
+        let store = {
+            let mut store = store;
+            if config.changefeed.enabled {
+                let changefeed = crate::storage::kafka::KafkaChangefeed::new(&config.changefeed, &config.jsonld, config.resilience.policy("kafka"))?;
+                store.add_change_sink(Arc::new(changefeed));
+            }
+            store
+        };
+        
+        // Reasoners share one lazily loaded TBox instead of each holding a full copy
+        let reasoner_pool = Arc::new(ReasonerPool::from_config(&config));
+        // A read-only dataset takes no captures, so the pipeline gets no copy of it
+        let pipeline_store = if store.is_read_only() { OxigraphStore::new_memory()? } else { store.clone() };
+        let pipeline = EpcisEventPipeline::with_reasoner_pool(config.clone(), pipeline_store, Arc::clone(&reasoner_pool)).await?;
         
         // Initialize monitoring
         let alert_config = AlertConfig::default();
-        let system_monitor = Arc::new(SystemMonitor::with_alert_config(alert_config));
+        let system_monitor = Arc::new(SystemMonitor::with_alert_config(alert_config).with_store_metrics(store.metrics()));
         
         // Initialize logging
         let logging_config = Arc::new(LoggingConfig::default());
+        let webhooks = Arc::new(WebhookNotifier::new(config.cold_chain.webhook_urls.clone(), config.resilience.policy("webhooks")));
+        
+        let config = Arc::new(config);
+        // Scans and snapshots write their results into the store; a read-only dataset only runs backups
+        let (jobs, state_path) = if store.is_read_only() {
+            let backups: Vec<_> = config.scheduled_jobs().into_iter().filter(|job| matches!(job.task, JobTask::Backup { .. })).collect();
+            (backups, None)
+        } else {
+            let state_path = Some(std::path::PathBuf::from(&config.scheduler.state_path)).filter(|_| config.database_path != ":memory:");
+            (config.scheduled_jobs(), state_path)
+        };
+        let store = Arc::new(Mutex::new(store));
+        let snapshots = Arc::new(SnapshotManager::from_config(&config.snapshots));
+        let runner = job_runner(Arc::clone(&store), Arc::clone(&config), pipeline.extension_registry(), Arc::clone(&system_monitor), Arc::clone(&webhooks), Arc::clone(&snapshots));
+        let scheduler = Arc::new(Scheduler::new(jobs, state_path, runner)?);
+        let messages = Arc::new(MessageCatalog::from_config(&config.localization)?);
+        let idempotency = Arc::new(IdempotencyCache::open(&config.idempotency, &config.server, config.database_path != ":memory:")?);
+        let quotas = Arc::new(QuotaTracker::open(&config.access_control, config.database_path != ":memory:")?);
         
         Ok(Self {
-            config: Arc::new(config),
-            store: Arc::new(Mutex::new(store)),
-            reasoner: Arc::new(RwLock::new(reasoner)),
+            config,
+            store,
+            reasoner_pool,
             pipeline: Arc::new(pipeline),
             system_monitor,
             logging_config,
+            webhooks,
+            scheduler,
+            messages,
+            idempotency,
+            quotas,
+            snapshots,
         })
     }
     
     pub async fn run(&self, port: u16) -> Result<(), Box<dyn std::error::Error>> {
         println!("🔍 DEBUG: WebServer::run called with port {}", port);
-        let app = self.create_app();
+        let app_state = self.app_state();
+        let app = self.create_app(app_state.clone());
         
         let addr = std::net::SocketAddr::from(([0, 0, 0, 0], port));
         
@@ -69,30 +184,156 @@ impl WebServer {
         info!("  GET  /api/v1/sparql - SPARQL endpoint (GET)");
         info!("  POST /api/v1/sparql - SPARQL endpoint (POST)");
         info!("  POST /api/v1/sparql/query - SPARQL query execution");
+        info!("  POST /api/v1/sparql/batch - Run several queries against one snapshot");
+        info!("  GET  /api/v1/snapshots - Latest stable snapshot for ?snapshot=latest-stable queries");
+        info!("  POST /api/v1/sparql/update - SPARQL update execution");
+        info!("  GET  /api/v1/archives - List archived snapshots queries can include");
+        info!("  POST /api/v1/sql - Read-only SQL over the events, epcs, locations and sensor_readings views");
         info!("  GET  /api/v1/statistics - Store statistics");
         info!("  GET  /api/v1/ontologies - List ontologies");
-        info!("  POST /api/v1/ontologies - Load ontology");
+        info!("  POST /api/v1/ontologies - Load ontology (JSON, multipart or raw Turtle upload)");
         info!("  POST /api/v1/events - Process EPCIS events");
-        info!("  GET  /api/v1/events - List recent events");
+        info!("  GET  /api/v1/events - List events as framed JSON-LD (?frame=event)");
+        info!("  DELETE /api/v1/events/:event_id - Declare an event in error");
+        info!("  POST /api/v1/events/corrections - Declare events in error in bulk");
+        info!("  GET  /api/v1/trace/:epc - Trace an EPC as framed JSON-LD (?frame=epc, ?sign=true, ?credential=true)");
+        info!("  GET  /api/v1/ontologies/imports - owl:imports graph of the loaded ontologies (?root= for one import closure)");
+        info!("  GET  /api/v1/signing/jwks - Public key for verifying signed traces");
+        info!("  GET  /api/v1/epcs/:epc/owner - Current owner of an EPC with ownership history");
+        info!("  POST /api/v1/epcs/status - Commissioning, disposition, last sighting and decommissioning of a batch of EPCs");
+        info!("  GET/POST /api/v1/parties - List or register trading partners");
+        info!("  GET/POST /api/v1/inventory/snapshots - List or compute dated inventory snapshots");
+        info!("  GET  /api/v1/inventory/snapshots/:date - Stored inventory positions for a day");
+        info!("  POST /api/v1/recall/simulate - Downstream impact of contaminated lots or EPCs");
+        info!("  GET  /api/v1/inventory/expiring - Soon-to-expire inventory per location (?within_days=)");
+        info!("  GET  /api/v1/events/expired - Events that observed product past its expiry");
+        info!("  GET  /api/v1/cold-chain/excursions - Temperature excursions per EPC");
+        info!("  GET  /api/v1/quality/report - Per-predicate completeness, distinct values and invalid literals");
+        info!("  POST /api/v1/cold-chain/scan - Record new excursions and raise alerts");
+        info!("  GET  /api/v1/schema-drift - Event predicates and classes no loaded ontology defines");
+        info!("  POST /api/v1/schema-drift/scan - Record unknown terms and alert on new ones");
+        info!("  POST /api/v1/epcs/:epc/verifications - Record an EPC verification result");
+        info!("  GET  /api/v1/epcs/:epc/suspicion - Counterfeit suspicion score of an EPC");
+        info!("  POST /api/v1/counterfeit/scan - Rescore EPCs for cloning and counterfeit signals");
+        info!("  GET  /api/v1/counterfeit/suspects - EPCs above a suspicion score (?min_score=)");
+        info!("  GET  /api/v1/analytics/carbon - Estimated CO2e and cost of shipments per transport mode (?since=, ?until=)");
+        info!("  POST /api/v1/analytics/carbon/scan - Record shipment estimates in the carbon graph");
+        info!("  GET  /api/v1/entity-resolution/proposals - Proposed owl:sameAs links between near-duplicate master data");
+        info!("  POST /api/v1/entity-resolution/reviews - Accept or reject a proposed owl:sameAs link");
+        info!("  GET  /api/v1/jobs - Scheduled jobs and their last run");
+        info!("  POST /api/v1/jobs/:name/run - Run a scheduled job now");
+        info!("  GET  /api/v1/patterns/rules - Configured event pattern rules");
+        info!("  GET  /api/v1/patterns/matches - Current event pattern matches");
+        info!("  POST /api/v1/patterns/scan - Record new pattern matches and raise alerts");
         info!("  POST /api/v1/inference - Perform reasoning");
         info!("  GET  /api/v1/inference/stats - Get inference statistics");
+        info!("  POST /api/v1/inference/jobs - Start reasoning in the background");
+        info!("  GET  /api/v1/inference/jobs/:job_id - Reasoning job status, progress and ETA");
+        info!("  GET  /api/v1/inference/runs - Recorded materialization runs");
+        info!("  GET  /api/v1/inference/runs/compare - Compare two runs (?baseline=&candidate=)");
+        info!("  GET  /api/v1/cluster/status - Cluster role, shard assignments and last merge");
+        info!("  POST /api/v1/cluster/events - Route captured events to workers by EPC shard (coordinator)");
+        info!("  POST /api/v1/cluster/merge - Pull the workers' graphs into this store now (coordinator)");
+        info!("  POST /api/v1/cluster/ingest - Store and materialize a routed batch (worker)");
+        info!("  GET  /api/v1/cluster/export - Event and inferred graphs as N-Quads (worker)");
         info!("  POST /api/v1/materialize - Manage materialized triples");
+        info!("  GET  /api/v1/materialized - Page through materialized triples (?graph=&predicate=&subject_prefix=&offset=&limit=)");
         info!("  GET  /api/v1/performance - Get performance metrics");
         info!("  GET  /api/v1/monitoring/metrics - Get system metrics");
         info!("  GET  /api/v1/monitoring/alerts - Get system alerts");
         info!("  GET  /api/v1/monitoring/health - Enhanced health check");
         info!("  POST /api/v1/monitoring/alerts/clear - Clear alerts");
+        info!("  GET  /api/v1/acl - List graph access control entries");
+        info!("  POST /api/v1/acl - Add or replace a graph access control entry");
+        info!("  GET  /api/v1/quotas/usage - Resource usage against per-tenant quotas");
+        info!("  GET  /api/v1/extensions - List registered event extension namespaces");
+        info!("  GET  /api/v1/vocab/:vocabulary - CBV terms for autocomplete (bizsteps, dispositions, biztransactiontypes; ?q=prefix; labels per Accept-Language)");
+        info!("  POST /api/v1/extensions - Register an event extension namespace");
+        info!("  GET/POST/DELETE /api/v1/vocab/extensions - List, register or remove SKOS concept schemes extending the CBV (?scheme= to remove)");
+        info!("  GET/POST /api/v1/views - List materialized views with their refresh status, or register a CONSTRUCT view");
+        info!("  GET/DELETE /api/v1/views/:name - Inspect or drop a view");
+        info!("  POST /api/v1/views/:name/refresh - Recompute a view now");
+        info!("  POST /api/v1/sessions - Open a what-if overlay session");
+        info!("  GET/DELETE /api/v1/sessions/:session_id - Inspect or discard a session");
+        info!("  POST /api/v1/sessions/:session_id/triples - Add hypothetical triples");
+        info!("  POST /api/v1/sessions/:session_id/update - SPARQL INSERT DATA, CLEAR or DROP on session graphs");
+        info!("  POST /api/v1/sessions/:session_id/sparql - Query the store with the overlay");
+        info!("  POST /api/v1/sessions/:session_id/inference - Run inference inside a session");
         info!("  GET  /static/* - Static files (HTML, CSS, JS)");
         info!("  GET  / - Web interface");
         
+        self.scheduler.start();
+        // The first stable snapshot is the store as loaded
+        if let Err(e) = app_state.snapshots.advance(&app_state.store) {
+            tracing::warn!("Failed to take the initial query snapshot: {}", e);
+        }
+        // Expired sessions are otherwise only dropped when another session is opened or used
+        let sessions = Arc::clone(&app_state.sessions);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                let purged = sessions.purge_expired();
+                if purged > 0 {
+                    info!("Discarded {} expired sessions", purged);
+                }
+            }
+        });
+        if app_state.cluster.role() == ClusterRole::Coordinator && self.config.cluster.merge_interval_seconds > 0 {
+            let (cluster, store, snapshots) = (Arc::clone(&app_state.cluster), Arc::clone(&app_state.store), Arc::clone(&app_state.snapshots));
+            let period = std::time::Duration::from_secs(self.config.cluster.merge_interval_seconds);
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(period);
+                loop {
+                    interval.tick().await;
+                    // A merge locks the store once per worker; snapshots must not land in between
+                    let batch = snapshots.begin_write();
+                    cluster.merge(&store).await;
+                    drop(batch);
+                }
+            });
+        }
+        if self.config.sql.postgres_enabled {
+            crate::api::pgwire::spawn(app_state.clone(), self.config.sql.postgres_port);
+        }
+        #[cfg(feature = "grpc")]
+        if self.config.grpc.enabled {
+            crate::api::grpc::spawn(app_state.clone(), self.config.grpc.port);
+        }
+        
         let listener = tokio::net::TcpListener::bind(addr).await?;
         
-        axum::serve(listener, app).await?;
+        axum::serve(listener, app.into_make_service_with_connect_info::<std::net::SocketAddr>()).await?;
         
         Ok(())
     }
     
-    fn create_app(&self) -> Router<()> {
+    /// State shared by the REST handlers (and the gRPC service when enabled)
+    fn app_state(&self) -> AppState {
+        AppState {
+            store: Arc::clone(&self.store),
+            config: Arc::clone(&self.config),
+            access_control: Arc::new(RwLock::new(GraphAccessControl::from_config(&self.config.access_control))),
+            reasoner_pool: Arc::clone(&self.reasoner_pool),
+            sessions: Arc::new(OverlaySessionManager::new(&self.config.sessions)),
+            archives: Arc::new(ArchiveFederation::new(&self.config.archive)),
+            extensions: self.pipeline.extension_registry(),
+            monitor: Arc::clone(&self.system_monitor),
+            webhooks: Arc::clone(&self.webhooks),
+            lineage: Arc::new(LineageEmitter::new(&self.config.lineage, self.config.resilience.policy("lineage"))),
+            scheduler: Arc::clone(&self.scheduler),
+            reasoning_jobs: Arc::new(ReasoningJobs::new()),
+            quotas: Arc::clone(&self.quotas),
+            messages: Arc::clone(&self.messages),
+            query_log: Arc::new(QueryLog::from_config(&self.config.query_log)),
+            access_log: Arc::new(AccessLog::from_config(&self.config.access_log)),
+            rule_usage: Arc::new(RuleUsage::new()),
+            cluster: Arc::new(Cluster::new(&self.config.cluster, Arc::clone(&self.pipeline))),
+            snapshots: Arc::clone(&self.snapshots),
+        }
+    }
+    
+    fn create_app(&self, app_state: AppState) -> Router<()> {
         println!("🔍 DEBUG: create_app called");
         // Create CORS layer based on configuration
         let cors_layer = if self.config.server.enable_cors {
@@ -104,42 +345,30 @@ impl WebServer {
             CorsLayer::new()
         };
         
-        // Create app state
-        let app_state = AppState {
-            store: Arc::clone(&self.store),
-            config: Arc::clone(&self.config),
-        };
-        
         // Create main router
         let app = Router::new()
             .route("/health", get(health_handler))
             .route("/", get(web_interface_handler))
-            .nest("/api/v1", self.create_api_router_with_state().with_state(app_state))
+            .nest(
+                "/api/v1",
+                self.create_api_router_with_state()
+                    .with_state(app_state.clone())
+                    .layer(axum::middleware::from_fn_with_state(app_state.clone(), conditional_reads))
+                    .layer(axum::middleware::from_fn_with_state(app_state.clone(), snapshot_boundaries))
+                    .layer(axum::middleware::from_fn_with_state(Arc::clone(&self.idempotency), idempotent_requests))
+                    .layer(DefaultBodyLimit::max(self.config.server.max_body_bytes))
+                    .layer(axum::middleware::from_fn(rejections_as_problems))
+                    .layer(axum::middleware::from_fn_with_state(Arc::clone(&self.messages), localize_problems)),
+            )
             .nest_service("/static", ServeDir::new("static"))
             .layer(cors_layer)
             .layer(TraceLayer::new_for_http());
+        let app = with_compression(app, &self.config.compression, self.system_monitor.compression_metrics())
+            .layer(axum::middleware::from_fn_with_state(app_state, access_logging));
         
         app
     }
     
-    fn create_api_router(&self) -> Router<()> {
-        Router::new()
-            .route("/test", get(|| async { "Hello World" }))
-            .route("/statistics", get(api_statistics))
-            .route("/sparql", get(api_sparql_get).post(api_sparql_post))
-            .route("/ontologies", get(api_list_ontologies).post(api_load_ontology))
-            .route("/events", get(api_list_events).post(api_process_event))
-            .route("/inference", post(api_perform_inference))
-            .route("/inference/stats", get(api_inference_stats))
-            .route("/materialize", post(api_manage_materialized))
-            .route("/performance", get(api_performance_metrics))
-            .route("/config", get(api_config))
-            .route("/monitoring/metrics", get(api_monitoring_metrics))
-            .route("/monitoring/alerts", get(api_monitoring_alerts))
-            .route("/monitoring/health", get(api_monitoring_health))
-            .route("/monitoring/alerts/clear", post(api_clear_alerts))
-    }
-
     fn create_api_router_with_state(&self) -> Router<AppState> {
         Router::new()
             .route("/test", get(|| async { 
@@ -149,17 +378,87 @@ impl WebServer {
             .route("/statistics", get(api_statistics))
             .route("/sparql", get(api_sparql_get).post(api_sparql_post))
             .route("/sparql/query", post(api_sparql_execute))
-            .route("/ontologies", get(api_list_ontologies).post(api_load_ontology))
+            .route("/sparql/batch", post(api_sparql_batch))
+            .route("/snapshots", get(api_snapshots))
+            .route("/sparql/update", post(api_sparql_update))
+            .route("/archives", get(api_list_archives))
+            .route("/sql", post(api_sql))
+            .route(
+                "/ontologies",
+                get(api_list_ontologies)
+                    .post(api_load_ontology)
+                    .layer(DefaultBodyLimit::max(self.config.server.max_upload_bytes as usize)),
+            )
+            .route("/ontologies/diagnostics", get(api_ontology_diagnostics))
+            .route("/ontologies/imports", get(api_ontology_imports))
             .route("/events", get(api_list_events).post(api_process_event))
+            .route("/events/corrections", post(api_correct_events))
+            .route("/events/:event_id", delete(api_delete_event))
+            .route("/trace/:epc", get(api_trace_epc))
+            .route("/signing/jwks", get(api_signing_keys))
             .route("/inference", post(api_perform_inference))
             .route("/inference/stats", get(api_inference_stats))
+            .route("/inference/rules", get(api_rule_usage))
+            .route("/inference/rematerialize", post(api_rematerialize))
+            .route("/cluster/status", get(api_cluster_status))
+            .route("/cluster/events", post(api_cluster_capture))
+            .route("/cluster/merge", post(api_cluster_merge))
+            .route("/cluster/ingest", post(api_cluster_ingest))
+            .route("/cluster/export", get(api_cluster_export))
+            .route("/inference/jobs", post(api_start_inference_job))
+            .route("/inference/jobs/:job_id", get(api_inference_job))
+            .route("/inference/runs", get(api_inference_runs))
+            .route("/inference/runs/compare", get(api_compare_inference_runs))
             .route("/materialize", post(api_manage_materialized))
+            .route("/materialized", get(api_list_materialized))
             .route("/performance", get(api_performance_metrics))
             .route("/config", get(api_config))
             .route("/monitoring/metrics", get(api_monitoring_metrics))
             .route("/monitoring/alerts", get(api_monitoring_alerts))
             .route("/monitoring/health", get(api_monitoring_health))
             .route("/monitoring/alerts/clear", post(api_clear_alerts))
+            .route("/acl", get(api_list_acl).post(api_upsert_acl))
+            .route("/quotas/usage", get(api_quota_usage))
+            .route("/extensions", get(api_list_extensions).post(api_register_extension))
+            .route("/vocab/extensions", get(api_list_vocabulary_extensions).post(api_register_vocabulary_extension).delete(api_remove_vocabulary_extension))
+            .route("/vocab/:vocabulary", get(api_vocabulary_terms))
+            .route("/views", get(api_list_views).post(api_register_view))
+            .route("/views/:name", get(api_get_view).delete(api_remove_view))
+            .route("/views/:name/refresh", post(api_refresh_view))
+            .route("/parties", get(api_list_parties).post(api_upsert_party))
+            .route("/epcs/:epc/owner", get(api_epc_owner))
+            .route("/epcs/status", post(api_epc_status))
+            .route("/entity-resolution/proposals", get(api_same_as_proposals))
+            .route("/entity-resolution/reviews", post(api_review_same_as))
+            .route("/inventory/snapshots", get(api_list_inventory_snapshots).post(api_create_inventory_snapshot))
+            .route("/inventory/snapshots/:date", get(api_inventory_snapshot))
+            .route("/inventory/expiring", get(api_expiring_inventory))
+            .route("/events/expired", get(api_expired_observations))
+            .route("/cold-chain/excursions", get(api_list_excursions))
+            .route("/quality/report", get(api_quality_report))
+            .route("/cold-chain/scan", post(api_scan_cold_chain))
+            .route("/schema-drift", get(api_schema_drift))
+            .route("/schema-drift/scan", post(api_scan_schema_drift))
+            .route("/epcs/:epc/verifications", post(api_record_verification))
+            .route("/epcs/:epc/suspicion", get(api_epc_suspicion))
+            .route("/counterfeit/scan", post(api_scan_counterfeit))
+            .route("/counterfeit/suspects", get(api_list_suspects))
+            .route("/analytics/carbon", get(api_carbon_report))
+            .route("/analytics/carbon/scan", post(api_scan_carbon))
+            .route("/jobs", get(api_list_jobs))
+            .route("/jobs/:name/run", post(api_run_job))
+            .route("/admin/log-level", get(api_log_levels).put(api_set_log_levels))
+            .route("/queries/slow", get(api_slow_queries))
+            .route("/patterns/rules", get(api_list_pattern_rules))
+            .route("/patterns/matches", get(api_list_pattern_matches))
+            .route("/patterns/scan", post(api_scan_patterns))
+            .route("/recall/simulate", post(api_simulate_recall))
+            .route("/sessions", post(api_open_session))
+            .route("/sessions/:session_id", get(api_get_session).delete(api_discard_session))
+            .route("/sessions/:session_id/triples", post(api_session_add_triples))
+            .route("/sessions/:session_id/update", post(api_session_update))
+            .route("/sessions/:session_id/sparql", post(api_session_query))
+            .route("/sessions/:session_id/inference", post(api_session_inference))
     }
 }
 
@@ -169,10 +468,11 @@ impl Clone for WebServer {
         Self {
             config: Arc::clone(&self.config),
             store: Arc::clone(&self.store),
-            reasoner: Arc::clone(&self.reasoner),
+            reasoner_pool: Arc::clone(&self.reasoner_pool),
             pipeline: Arc::clone(&self.pipeline),
             system_monitor: Arc::clone(&self.system_monitor),
             logging_config: Arc::clone(&self.logging_config),
+            webhooks: Arc::clone(&self.webhooks),
         }
     }
 }
@@ -201,7 +501,7 @@ async fn health_handler() -> Json<serde_json::Value> {
 // API Handlers with proper state management
 async fn api_sparql_get(
     Query(params): Query<crate::api::sparql::SparqlQuery>,
-) -> Result<Json<serde_json::Value>, Json<serde_json::Value>> {
+) -> Result<Json<serde_json::Value>, EpcisKgError> {
     // Simple SPARQL endpoint for now - return basic response
     Ok(Json(serde_json::json!({
         "results": "SPARQL query received",
@@ -213,39 +513,84 @@ async fn api_sparql_get(
 }
 
 async fn api_sparql_post(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
     Json(payload): Json<crate::api::sparql::SparqlQuery>,
-) -> Result<Json<serde_json::Value>, Json<serde_json::Value>> {
+) -> Result<Response, EpcisKgError> {
+    // Updates posted to the generic endpoint apply as they would on /sparql/update
+    if spargebra::SparqlParser::new().parse_update(&payload.query).is_ok() {
+        return apply_sparql_update(&app_state, &headers, &payload.query);
+    }
     Ok(Json(serde_json::json!({
         "results": "SPARQL query received via POST",
         "query": payload.query,
         "query_type": crate::api::sparql::determine_query_type(&payload.query),
         "execution_time_ms": 0,
         "status": "success"
-    })))
+    })).into_response())
 }
 
 async fn api_sparql_execute(
     State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<SnapshotParams>,
     Json(payload): Json<crate::api::sparql::SparqlQuery>,
-) -> Result<Response, Json<serde_json::Value>> {
+) -> Result<Response, EpcisKgError> {
     println!("🔍 DEBUG: api_sparql_execute called with query: {}", payload.query);
     let start_time = std::time::Instant::now();
     
-    // Execute the actual SPARQL query against the store
-    let store_guard = app_state.store.lock().map_err(|e| {
-        Json(serde_json::json!({
-            "error": format!("Failed to acquire store lock: {}", e),
-            "status": "error"
-        }))
-    })?;
+    // Apply parameter bindings (e.g. a list of recalled EPCs) as VALUES blocks
+    let query = payload
+        .effective_query()
+        .map_err(|e| EpcisKgError::InvalidField { field: "bindings".to_string(), message: e.to_string() })?;
+    // Registered extension prefixes can be used without declaring them
+    let query = app_state.extensions.read().with_prefixes(&query);
+    
+    // Execute the actual SPARQL query against the store, or the pinned snapshot asked for
+    let snapshot = app_state.snapshots.resolve(params.snapshot.as_deref(), &app_state.store)?;
+    let store_guard;
+    let base: &OxigraphStore = match &snapshot {
+        Some(snapshot) => &snapshot.store,
+        None => {
+            store_guard = app_state.store.lock().map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?;
+            &*store_guard
+        }
+    };
+    
+    // Archived snapshots, when asked for, are merged under the live data for this query only
+    let (federated, archives) = if payload.include_archives {
+        let (view, archives) = app_state.archives.federated_view(base, payload.archives.as_deref())?;
+        (Some(view), Some(archives))
+    } else {
+        (None, None)
+    };
+    let store: &OxigraphStore = federated.as_ref().unwrap_or(base);
+    
+    // Restrict the query to the graphs the caller's API key may read
+    let api_key = api_key_from_headers(&headers);
+    let restricted = app_state
+        .access_control
+        .read()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?
+        .restrict_query(api_key.as_deref(), &query, &store.graph_names());
+    let query = match restricted {
+        Ok(query) => query,
+        Err(e @ EpcisKgError::AccessDenied(_)) => return Ok(access_denied_response(api_key.as_deref(), e)),
+        Err(e) => return Err(e),
+    };
+    let tenant = tenant_of(&app_state, api_key.as_deref());
+    if let Some(tenant) = &tenant {
+        app_state.quotas.check_query(tenant)?;
+    }
     
     // Execute SPARQL query using the store
-    let result_json = store_guard.query_select(&payload.query).map_err(|e| {
-        Json(serde_json::json!({
-            "error": format!("Failed to execute SPARQL query: {}", e),
-            "status": "error"
-        }))
-    })?;
+    let query_started = std::time::Instant::now();
+    let caller = caller_name(tenant.as_ref(), api_key.as_deref());
+    let result_json = logged_select(&app_state.query_log, store, &query, caller.as_deref());
+    if let Some(tenant) = &tenant {
+        app_state.quotas.record_query(tenant, query_started.elapsed());
+    }
+    let result_json = result_json?;
     
     // Parse the JSON result from the storage layer
     let result: serde_json::Value = serde_json::from_str(&result_json).unwrap_or_else(|_| {
@@ -257,20 +602,463 @@ async fn api_sparql_execute(
     
     let execution_time = start_time.elapsed().as_millis() as u64;
     
-    let response = serde_json::json!({
+    let mut response = serde_json::json!({
         "head": result["head"],
         "results": result["results"],
-        "query": payload.query,
-        "query_type": crate::api::sparql::determine_query_type(&payload.query),
+        "query": query,
+        "query_type": crate::api::sparql::determine_query_type(&query),
         "execution_time_ms": execution_time,
         "status": "success"
     });
+    if let Some(archives) = archives {
+        response["archives"] = serde_json::json!(archives);
+    }
+    if let Some(snapshot) = snapshot {
+        response["snapshot"] = serde_json::json!(snapshot.info());
+    }
     
     Ok(Json(response).into_response())
 }
 
+#[derive(serde::Deserialize)]
+struct SnapshotParams {
+    /// `latest-stable` to read the last batch boundary instead of the live store
+    snapshot: Option<String>,
+}
+
+/// The snapshot `?snapshot=latest-stable` queries currently read, and the write batches in flight
+async fn api_snapshots(State(app_state): State<AppState>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "enabled": app_state.snapshots.is_enabled(),
+        "latest_stable": app_state.snapshots.latest(),
+        "batches_in_flight": app_state.snapshots.in_flight()
+    }))
+}
+
+/// Record each request in the access log once its response is ready
+async fn access_logging(State(app_state): State<AppState>, request: Request, next: axum::middleware::Next) -> Response {
+    if !app_state.access_log.is_enabled() {
+        return next.run(request).await;
+    }
+    let (started, timestamp) = (std::time::Instant::now(), chrono::Utc::now());
+    let remote_addr = request
+        .extensions()
+        .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+        .map(|connect_info| connect_info.0.ip().to_string());
+    let api_key = api_key_from_headers(request.headers());
+    let user_agent = request.headers().get(axum::http::header::USER_AGENT).and_then(|value| value.to_str().ok()).map(str::to_string);
+    let method = request.method().to_string();
+    let path = request.uri().path_and_query().map_or_else(|| request.uri().path().to_string(), |path| path.to_string());
+    let protocol = format!("{:?}", request.version());
+
+    let response = next.run(request).await;
+    let caller = caller_name(tenant_of(&app_state, api_key.as_deref()).as_ref(), api_key.as_deref());
+    app_state.access_log.record(&AccessLogEntry {
+        timestamp,
+        remote_addr,
+        caller,
+        method,
+        path,
+        protocol,
+        status: response.status().as_u16(),
+        bytes: axum::body::HttpBody::size_hint(response.body()).exact(),
+        latency_ms: started.elapsed().as_secs_f64() * 1000.0,
+        user_agent,
+    });
+    response
+}
+
+/// Write requests are the batches snapshots must not cut through; the stable
+/// snapshot advances once the last one in flight has finished
+async fn snapshot_boundaries(State(app_state): State<AppState>, request: Request, next: axum::middleware::Next) -> Response {
+    if !app_state.snapshots.is_enabled() || request.method() == axum::http::Method::GET || request.method() == axum::http::Method::HEAD {
+        return next.run(request).await;
+    }
+    let batch = app_state.snapshots.begin_write();
+    let response = next.run(request).await;
+    drop(batch);
+    
+    let (snapshots, store) = (Arc::clone(&app_state.snapshots), Arc::clone(&app_state.store));
+    tokio::task::spawn_blocking(move || {
+        if let Err(e) = snapshots.advance(&store) {
+            tracing::warn!("Failed to advance the query snapshot: {}", e);
+        }
+    });
+    response
+}
+
+#[derive(serde::Deserialize)]
+struct SparqlBatchRequest {
+    queries: Vec<BatchQuery>,
+    /// Run the queries on a thread pool instead of one after another
+    #[serde(default)]
+    parallel: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct BatchQuery {
+    /// Caller-chosen label echoed back with the result
+    id: Option<String>,
+    #[serde(flatten)]
+    query: crate::api::sparql::SparqlQuery,
+}
+
+/// Run several read queries against one snapshot of the store.
+///
+/// The store lock is held for the whole batch, so every query sees the same
+/// data; with `?snapshot=latest-stable` they read the pinned snapshot instead.
+/// A failing query does not fail the batch; its entry carries the error.
+async fn api_sparql_batch(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<SnapshotParams>,
+    Json(payload): Json<SparqlBatchRequest>,
+) -> Result<Response, EpcisKgError> {
+    let start_time = std::time::Instant::now();
+    let max_batch_size = app_state.config.sparql.max_batch_size;
+    if payload.queries.len() > max_batch_size {
+        return Err(EpcisKgError::InvalidField {
+            field: "queries".to_string(),
+            message: format!("batch of {} queries exceeds the limit of {}", payload.queries.len(), max_batch_size),
+        });
+    }
+    
+    let prepared: Vec<Result<String, String>> = {
+        let extensions = app_state.extensions.read();
+        payload
+            .queries
+            .iter()
+            .map(|batch_query| {
+                if batch_query.query.include_archives {
+                    return Err("include_archives is not supported in batches".to_string());
+                }
+                let query = batch_query.query.effective_query().map_err(|e| format!("Invalid query bindings: {}", e))?;
+                Ok(extensions.with_prefixes(&query))
+            })
+            .collect()
+    };
+    
+    let snapshot = app_state.snapshots.resolve(params.snapshot.as_deref(), &app_state.store)?;
+    let store_guard = match snapshot {
+        Some(_) => None,
+        None => Some(app_state.store.lock().map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?),
+    };
+    let store: &OxigraphStore = match &snapshot {
+        Some(snapshot) => &snapshot.store,
+        None => store_guard.as_deref().expect("the live store is locked when no snapshot is read"),
+    };
+    let api_key = api_key_from_headers(&headers);
+    let graph_names = store.graph_names();
+    let restricted: Vec<Result<String, String>> = {
+        let access_control = app_state.access_control.read().map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?;
+        prepared
+            .into_iter()
+            .map(|query| {
+                access_control
+                    .restrict_query(api_key.as_deref(), &query?, &graph_names)
+                    .map_err(|e| e.to_string())
+            })
+            .collect()
+    };
+    
+    let caller = caller_name(tenant_of(&app_state, api_key.as_deref()).as_ref(), api_key.as_deref());
+    let (query_log, caller) = (app_state.query_log.as_ref(), caller.as_deref());
+    let results: Vec<serde_json::Value> = if payload.parallel {
+        use rayon::prelude::*;
+        restricted.par_iter().map(|query| run_batch_query(store, query, query_log, caller)).collect()
+    } else {
+        restricted.iter().map(|query| run_batch_query(store, query, query_log, caller)).collect()
+    };
+    drop(store_guard);
+    
+    let results: Vec<serde_json::Value> = results
+        .into_iter()
+        .zip(&payload.queries)
+        .enumerate()
+        .map(|(index, (mut result, batch_query))| {
+            result["index"] = serde_json::json!(index);
+            result["id"] = serde_json::json!(batch_query.id);
+            result
+        })
+        .collect();
+    let failed = results.iter().filter(|result| result["status"] == "error").count();
+    
+    Ok(Json(serde_json::json!({
+        "results": results,
+        "total": results.len(),
+        "failed": failed,
+        "parallel": payload.parallel,
+        "snapshot": snapshot.map(|snapshot| snapshot.info()),
+        "execution_time_ms": start_time.elapsed().as_millis() as u64,
+        "status": "success"
+    })).into_response())
+}
+
+/// Execute one prepared batch query, timing it
+fn run_batch_query(store: &OxigraphStore, query: &Result<String, String>, query_log: &QueryLog, caller: Option<&str>) -> serde_json::Value {
+    let start_time = std::time::Instant::now();
+    let query = match query {
+        Ok(query) => query,
+        Err(e) => return serde_json::json!({ "status": "error", "error": e }),
+    };
+    
+    let query_type = crate::api::sparql::determine_query_type(query);
+    let outcome = match query_type.as_str() {
+        "SELECT" => logged_select(query_log, store, query, caller).map(|result_json| {
+            let result: serde_json::Value = serde_json::from_str(&result_json).unwrap_or_default();
+            serde_json::json!({ "head": result["head"], "results": result["results"] })
+        }),
+        "ASK" => store.query_ask(query).map(|boolean| serde_json::json!({ "boolean": boolean })),
+        "CONSTRUCT" => store.query_construct(query).map(|triples| serde_json::json!({ "triples": triples })),
+        _ => Err(EpcisKgError::Query("Only SELECT, ASK and CONSTRUCT queries can be batched".to_string())),
+    };
+    
+    let mut result = match outcome {
+        Ok(mut result) => {
+            result["status"] = serde_json::json!("success");
+            result
+        }
+        Err(e) => serde_json::json!({ "status": "error", "error": e.to_string() }),
+    };
+    result["query_type"] = serde_json::json!(query_type);
+    result["execution_time_ms"] = serde_json::json!(start_time.elapsed().as_millis() as u64);
+    result
+}
+
+/// Name of the caller's API key for logs: its ACL name, or a short hash of the key
+fn caller_name(tenant: Option<&crate::config::ApiKeyAcl>, api_key: Option<&str>) -> Option<String> {
+    match tenant {
+        Some(tenant) if !tenant.name.is_empty() => Some(tenant.name.clone()),
+        _ => api_key.map(|key| {
+            let digest = <sha2::Sha256 as sha2::Digest>::digest(key.as_bytes());
+            format!("key:{}", digest.iter().take(4).map(|byte| format!("{:02x}", byte)).collect::<String>())
+        }),
+    }
+}
+
+/// Run a SELECT query and record it in the query log, with its plan when it was slow
+fn logged_select(query_log: &QueryLog, store: &OxigraphStore, query: &str, caller: Option<&str>) -> Result<String, EpcisKgError> {
+    let started = std::time::Instant::now();
+    let result = store.query_select(query);
+    let elapsed = started.elapsed();
+    if query_log.is_enabled() {
+        let slow = query_log.is_slow(elapsed);
+        query_log.record(QueryLogEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            query: query.to_string(),
+            duration_ms: elapsed.as_secs_f64() * 1000.0,
+            result_count: result.as_ref().ok().map(|result_json| {
+                let result: serde_json::Value = serde_json::from_str(result_json).unwrap_or_default();
+                result["results"]["bindings"].as_array().map_or(0, Vec::len)
+            }),
+            caller: caller.map(str::to_string),
+            error: result.as_ref().err().map(|e| e.to_string()),
+            slow,
+            plan: if slow { store.explain(query).ok() } else { None },
+        });
+    }
+    result
+}
+
+#[derive(serde::Deserialize)]
+struct SparqlUpdateRequest {
+    pub update: String,
+}
+
+async fn api_sparql_update(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<SparqlUpdateRequest>,
+) -> Result<Response, EpcisKgError> {
+    apply_sparql_update(&app_state, &headers, &payload.update)
+}
+
+/// Apply an update for the caller, within its graph ACL and write quota
+fn apply_sparql_update(app_state: &AppState, headers: &HeaderMap, update: &str) -> Result<Response, EpcisKgError> {
+    if !app_state.config.sparql.enable_updates {
+        return Err(EpcisKgError::AccessDenied("SPARQL updates are disabled by configuration".to_string()));
+    }
+    
+    // Reject updates that target graphs the caller may not write or read, and
+    // match their WHERE clauses against the graphs it may read only
+    let api_key = api_key_from_headers(headers);
+    let permitted = {
+        let access_control = app_state
+            .access_control
+            .read()
+            .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?;
+        access_control
+            .check_update(api_key.as_deref(), update)
+            .and_then(|_| access_control.read_filter(api_key.as_deref()))
+    };
+    let readable = match permitted {
+        Ok(readable) => readable,
+        Err(e @ EpcisKgError::AccessDenied(_)) => return Ok(access_denied_response(api_key.as_deref(), e)),
+        Err(e) => return Err(e),
+    };
+    
+    let mut store_guard = app_state.store.lock().map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?;
+    if let Some(tenant) = tenant_of(app_state, api_key.as_deref()) {
+        app_state.quotas.check_write(&tenant, &store_guard)?;
+    }
+    
+    let report = store_guard.update_reading(update, readable)?;
+    
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "query_type": "UPDATE",
+        "status": "success",
+        "inserted": report.inserted,
+        "deleted": report.deleted
+    })).into_response())
+}
+
+/// ACL entry of an authenticated API key, whose quota applies to the request
+fn tenant_of(app_state: &AppState, api_key: Option<&str>) -> Option<crate::config::ApiKeyAcl> {
+    let access_control = app_state.access_control.read().ok()?;
+    access_control.authenticate(api_key).ok().flatten().cloned()
+}
+
+/// Refuse a capture once the caller's tenant has reached a storage quota
+pub(crate) fn check_write_quota(app_state: &AppState, api_key: Option<&str>) -> Result<(), EpcisKgError> {
+    let Some(tenant) = tenant_of(app_state, api_key) else {
+        return Ok(());
+    };
+    let store_guard = app_state.store.lock().map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?;
+    app_state.quotas.check_write(&tenant, &store_guard)
+}
+
+/// Resource usage of the caller's tenant, or of every tenant for admin keys
+async fn api_quota_usage(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, EpcisKgError> {
+    let api_key = api_key_from_headers(&headers);
+    let access_control = app_state.access_control.read().map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?;
+    if !access_control.is_enabled() {
+        return Err(EpcisKgError::Conflict(
+            "Quotas apply per API key; enable access control in the [access_control] configuration section".to_string(),
+        ));
+    }
+    let caller = match access_control.authenticate(api_key.as_deref()) {
+        Ok(caller) => caller.cloned(),
+        Err(e) => return Ok(access_denied_response(api_key.as_deref(), e)),
+    };
+    let tenants = match caller {
+        Some(caller) if caller.admin => access_control.entries(),
+        Some(caller) => vec![caller],
+        None => Vec::new(),
+    };
+    drop(access_control);
+    
+    let store_guard = app_state.store.lock().map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?;
+    let usage: Vec<_> = tenants.iter().map(|tenant| app_state.quotas.usage(tenant, &store_guard)).collect();
+    Ok(Json(serde_json::json!({
+        "tenants": usage,
+        "total": usage.len()
+    })).into_response())
+}
+
+/// Map an access control failure to 401 (no key) or 403 (insufficient rights)
+fn access_denied_response(api_key: Option<&str>, error: EpcisKgError) -> Response {
+    let status = if api_key.is_none() {
+        StatusCode::UNAUTHORIZED
+    } else {
+        StatusCode::FORBIDDEN
+    };
+    
+    problem_response(status, &error)
+}
+
+async fn api_list_acl(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, EpcisKgError> {
+    let api_key = api_key_from_headers(&headers);
+    let access_control = app_state.access_control.read().map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?;
+    
+    if let Err(e) = require_acl_admin(&access_control, api_key.as_deref()) {
+        return Ok(access_denied_response(api_key.as_deref(), e));
+    }
+    
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "enabled": access_control.is_enabled(),
+        "entries": access_control.masked_entries()
+    })).into_response())
+}
+
+async fn api_upsert_acl(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<crate::config::ApiKeyAcl>,
+) -> Result<Response, EpcisKgError> {
+    let api_key = api_key_from_headers(&headers);
+    let mut access_control = app_state.access_control.write().map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?;
+    
+    if !access_control.is_enabled() {
+        return Err(EpcisKgError::Conflict(
+            "Access control is disabled; enable it in the [access_control] configuration section".to_string(),
+        ));
+    }
+    
+    if let Err(e) = require_acl_admin(&access_control, api_key.as_deref()) {
+        return Ok(access_denied_response(api_key.as_deref(), e));
+    }
+    
+    let name = payload.name.clone();
+    access_control.upsert(payload)?;
+    
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "message": format!("Access control entry '{}' saved", name)
+    })).into_response())
+}
+
+/// ACL management requires an admin key whenever access control is enabled
+fn require_acl_admin(access_control: &GraphAccessControl, api_key: Option<&str>) -> Result<(), EpcisKgError> {
+    require_admin(access_control, api_key, "manage access control")
+}
+
+/// Admin-only operations; always allowed when access control is disabled
+fn require_admin(access_control: &GraphAccessControl, api_key: Option<&str>, action: &str) -> Result<(), EpcisKgError> {
+    match access_control.authenticate(api_key)? {
+        Some(acl) if !acl.admin => Err(EpcisKgError::AccessDenied(format!(
+            "API key '{}' may not {}",
+            acl.name, action
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct SqlRequest {
+    query: String,
+}
+
+/// Read-only SQL over tabular views of the event history (see `api::sql`)
+async fn api_sql(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<SqlRequest>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let result = with_graph_access(&app_state, api_key.as_deref(), "urn:epcis:event:", GraphAccess::Read, |store| {
+        crate::api::sql::execute(&request.query, &store.event_tables())
+    });
+    match result {
+        Ok(result) => Json(serde_json::json!({
+            "columns": result.columns,
+            "total": result.rows.len(),
+            "rows": result.rows,
+            "status": "success"
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
 async fn api_statistics(
-) -> Result<Json<serde_json::Value>, Json<serde_json::Value>> {
+) -> Result<Json<serde_json::Value>, EpcisKgError> {
     // For now, return sample data since we don't have real store integration in the API
     // In a real implementation, this would query the actual Oxigraph store
     Ok(Json(serde_json::json!({
@@ -282,29 +1070,114 @@ async fn api_statistics(
     })))
 }
 
+#[derive(serde::Deserialize)]
+struct GraphListParams {
+    /// ontology, event, inferred, master or other
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+}
+
+/// Page through the named graphs the caller may read, with source, version,
+/// triple count and load time; ontology graphs are checked against the default profile
 async fn api_list_ontologies(
-) -> Json<serde_json::Value> {
-    Json(serde_json::json!({
-        "ontologies": [
-            {
-                "name": "epcis2.ttl",
-                "uri": "ontologies/epcis2.ttl",
-                "triples": 450,
-                "loaded": true
-            },
-            {
-                "name": "cbv.ttl", 
-                "uri": "ontologies/cbv.ttl",
-                "triples": 326,
-                "loaded": true
-            }
-        ],
-        "loaded_graphs": 2,
-        "total_triples": 776,
-        "status": "operational",
-        "reasoning_enabled": true,
-        "materialization_strategy": "Incremental"
-    }))
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<GraphListParams>,
+) -> Result<Json<GraphPage>, EpcisKgError> {
+    let filter = GraphFilter {
+        kind: params.kind.as_deref().map(str::parse).transpose()?,
+        offset: params.offset.unwrap_or(0),
+        limit: params.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(app_state.config.sparql.max_results),
+    };
+    
+    let api_key = api_key_from_headers(&headers);
+    let tenant = app_state
+        .access_control
+        .read()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?
+        .authenticate(api_key.as_deref())?
+        .cloned();
+    let store_guard = app_state.store.lock().map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?;
+    let mut page = store_guard.graph_catalog(&filter, |graph| {
+        tenant.as_ref().map_or(true, |acl| GraphAccessControl::can_access(acl, graph, GraphAccess::Read))
+    });
+    
+    let profile = &app_state.config.reasoning.default_profile;
+    let checker = OntologyReasoner::with_config(&app_state.config);
+    for graph in page.graphs.iter_mut().filter(|graph| graph.kind == GraphKind::Ontology) {
+        let ontology_data = store_guard.ontology_data(std::slice::from_ref(&graph.name), &graph.name);
+        graph.profile = Some(match checker.check_owl_profile(&ontology_data, profile) {
+            Ok(()) => ProfileStatus { profile: profile.clone(), compliant: true, violations: Vec::new() },
+            Err(e) => ProfileStatus { profile: profile.clone(), compliant: false, violations: vec![e.to_string()] },
+        });
+    }
+    Ok(Json(page))
+}
+
+#[derive(serde::Deserialize)]
+struct ImportsParams {
+    /// Only this ontology (IRI, version IRI or graph name) and what it imports
+    root: Option<String>,
+}
+
+/// The `owl:imports` graph of the loaded ontologies the caller may read
+async fn api_ontology_imports(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<ImportsParams>,
+) -> Result<Json<ImportGraph>, EpcisKgError> {
+    let api_key = api_key_from_headers(&headers);
+    let tenant = app_state
+        .access_control
+        .read()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?
+        .authenticate(api_key.as_deref())?
+        .cloned();
+    let store_guard = app_state.store.lock().map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?;
+    let graph = ImportGraph::build(&store_guard, |graph| {
+        tenant.as_ref().map_or(true, |acl| GraphAccessControl::can_access(acl, graph, GraphAccess::Read))
+    });
+    drop(store_guard);
+    
+    match params.root {
+        Some(root) => graph
+            .closure_of(&root)
+            .map(Json)
+            .ok_or(EpcisKgError::NotFound { resource: "Ontology".to_string(), id: root }),
+        None => Ok(Json(graph)),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct DiagnosticsParams {
+    /// Only diagnose this ontology graph
+    graph: Option<String>,
+}
+
+/// Quality diagnostics for the ontologies loaded into the store
+async fn api_ontology_diagnostics(
+    State(app_state): State<AppState>,
+    Query(params): Query<DiagnosticsParams>,
+) -> Result<Json<serde_json::Value>, EpcisKgError> {
+    let store_guard = app_state.store.lock().map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?;
+    let scope = InferenceScope::from_config(&app_state.config.reasoning.scope);
+    
+    let ontologies: Vec<serde_json::Value> = store_guard
+        .stored_ontologies()
+        .iter()
+        .filter(|ontology| params.graph.as_deref().map_or(true, |graph| ontology.source_file == graph))
+        .map(|ontology| {
+            let diagnostics = OntologyDiagnostics::analyze(&ontology.graph).with_usage(&store_guard, &scope);
+            serde_json::json!({ "graph": ontology.source_file, "diagnostics": diagnostics })
+        })
+        .collect();
+    if let (Some(graph), true) = (&params.graph, ontologies.is_empty()) {
+        return Err(EpcisKgError::NotFound { resource: "Ontology graph".to_string(), id: graph.clone() });
+    }
+    
+    Ok(Json(serde_json::json!({ "ontologies": ontologies, "total": ontologies.len() })))
 }
 
 #[derive(serde::Deserialize)]
@@ -313,18 +1186,142 @@ struct OntologyLoadRequest {
     pub graph_name: Option<String>,
 }
 
+/// Load an ontology: JSON naming a server-side file, or an RDF file uploaded as
+/// multipart form data or as a raw Turtle / N-Triples body
 async fn api_load_ontology(
-    Json(payload): Json<OntologyLoadRequest>,
-) -> Result<Json<serde_json::Value>, Json<serde_json::Value>> {
-    Ok(Json(serde_json::json!({
-        "success": true,
-        "message": "Ontology loading endpoint (simplified)",
-        "file": payload.file_path,
-        "graph_name": payload.graph_name,
-        "triples_loaded": 0,
-        "total_inferences": 0,
-        "inference_time_ms": 0
-    })))
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+    request: Request,
+) -> Result<Response, EpcisKgError> {
+    let media_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    
+    if media_type != "multipart/form-data" && !RDF_UPLOAD_TYPES.contains(&media_type.as_str()) {
+        let payload = match Json::<OntologyLoadRequest>::from_request(request, &app_state).await {
+            Ok(Json(payload)) => payload,
+            Err(rejection) => return Ok(rejection.into_response()),
+        };
+        return Ok(Json(serde_json::json!({
+            "success": true,
+            "message": "Ontology loading endpoint (simplified)",
+            "file": payload.file_path,
+            "graph_name": payload.graph_name,
+            "triples_loaded": 0,
+            "total_inferences": 0,
+            "inference_time_ms": 0
+        })).into_response());
+    }
+    
+    let mode: LoadMode = params.get("mode").map(|mode| mode.parse()).transpose()?.unwrap_or_default();
+    let api_key = api_key_from_headers(&headers);
+    let (upload, graph_name) = match receive_ontology_upload(&app_state, api_key.as_deref(), &media_type, &params, request).await {
+        Ok(received) => received,
+        Err(e @ EpcisKgError::LimitExceeded(_)) => return Ok(problem_response(StatusCode::PAYLOAD_TOO_LARGE, &e)),
+        Err(e @ EpcisKgError::AccessDenied(_)) => return Ok(access_denied_response(api_key.as_deref(), e)),
+        Err(e) => return Err(e),
+    };
+    
+    let file = std::fs::File::open(upload.path())?;
+    let ontology_data = OntologyLoader::with_config(&app_state.config).parse_turtle_reader(std::io::BufReader::new(file), upload.file_name.clone())?;
+    let graph = graph_name.unwrap_or_else(|| upload_graph_name(&upload.file_name, &upload.sha256));
+    let stored = with_graph_access(&app_state, api_key.as_deref(), &graph, GraphAccess::Write, |store| {
+        store.store_ontology_data_as(&ontology_data, Some(&graph), mode)
+    });
+    
+    Ok(match stored {
+        Ok(graph_name) => {
+            let mut run = LineageRun::new("load_ontology", RunState::Complete);
+            run.inputs.push(LineageDataset::file(&upload.file_name));
+            run.outputs.push(LineageDataset::graph(&graph_name).with_row_count(ontology_data.triples_count));
+            app_state.lineage.emit(&run);
+            Json(serde_json::json!({
+                "success": true,
+                "file": upload.file_name,
+                "graph_name": graph_name,
+                "bytes_received": upload.bytes,
+                "triples_loaded": ontology_data.triples_count
+            })).into_response()
+        }
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    })
+}
+
+/// Spool an uploaded RDF file, with the target graph named by the `graph_name`
+/// form field or query parameter.
+///
+/// Write access to a named graph is checked as soon as the name is known, so a
+/// caller who may not write it is refused before the file is spooled. Without a
+/// name, the graph is derived from the file's content, so the check covers every
+/// graph derived for its file name.
+async fn receive_ontology_upload(
+    app_state: &AppState,
+    api_key: Option<&str>,
+    media_type: &str,
+    params: &std::collections::HashMap<String, String>,
+    request: Request,
+) -> Result<(SpooledUpload, Option<String>), EpcisKgError> {
+    let directory = std::path::Path::new(&app_state.config.server.upload_dir);
+    let max_bytes = app_state.config.server.max_upload_bytes;
+    let check_write = |graph: &str| -> Result<(), EpcisKgError> {
+        oxrdf::NamedNode::new(graph).map_err(|e| EpcisKgError::InvalidIri { iri: graph.to_string(), message: e.to_string() })?;
+        app_state
+            .access_control
+            .read()
+            .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?
+            .check_graph_write(api_key, &[graph.to_string()])
+    };
+    let mut graph_name = params.get("graph_name").cloned();
+    if let Some(graph) = &graph_name {
+        check_write(graph)?;
+    }
+    
+    if media_type != "multipart/form-data" {
+        // A raw body has no file name, so the graph name is known up front
+        if graph_name.is_none() {
+            check_write(&ontology_graph_prefix("upload.ttl"))?;
+        }
+        let upload = spool(request.into_body().into_data_stream(), directory, "upload.ttl", max_bytes).await?;
+        return Ok((upload, graph_name));
+    }
+    
+    let mut multipart = Multipart::from_request(request, app_state)
+        .await
+        .map_err(|e| EpcisKgError::Validation(format!("Invalid multipart upload: {}", e.body_text())))?;
+    let mut upload = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| EpcisKgError::Validation(format!("Invalid multipart upload: {}", e)))?
+    {
+        let name = field.name().map(str::to_string);
+        match name.as_deref() {
+            Some("graph_name") => {
+                let graph = field.text().await.map_err(|e| EpcisKgError::Validation(format!("Invalid graph_name field: {}", e)))?;
+                check_write(&graph)?;
+                graph_name = Some(graph);
+            }
+            Some("file") => {
+                let file_name = field.file_name().unwrap_or("upload.ttl").to_string();
+                if graph_name.is_none() {
+                    check_write(&ontology_graph_prefix(&file_name))?;
+                }
+                upload = Some(spool(field, directory, &file_name, max_bytes).await?);
+            }
+            _ => {}
+        }
+    }
+    
+    let upload = upload.ok_or_else(|| EpcisKgError::InvalidField {
+        field: "file".to_string(),
+        message: "multipart upload has no file part".to_string(),
+    })?;
+    Ok((upload, graph_name))
 }
 
 #[derive(serde::Deserialize)]
@@ -332,20 +1329,75 @@ struct EventProcessRequest {
     pub events: Vec<crate::models::epcis::EpcisEvent>,
     pub validate: Option<bool>,
     pub infer: Option<bool>,
+    /// Promote the `[strict_mode]` warnings to errors; the configured default when absent
+    pub strict: Option<bool>,
 }
 
 async fn api_process_event(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
     Json(payload): Json<EventProcessRequest>,
-) -> Result<Json<serde_json::Value>, Json<serde_json::Value>> {
+) -> Result<Json<serde_json::Value>, EpcisKgError> {
+    // Captures are real-time unless the client marks them as backfill
+    let priority: IngestPriority = match headers.get(PRIORITY_HEADER).and_then(|value| value.to_str().ok()) {
+        Some(value) => value.parse()?,
+        None => IngestPriority::default(),
+    };
     let validate = payload.validate.unwrap_or(true);
     let infer = payload.infer.unwrap_or(true);
+    let strict = payload.strict.unwrap_or(app_state.config.strict_mode.enabled);
+    check_write_quota(&app_state, api_key_from_headers(&headers).as_deref())?;
     
     let mut results = Vec::new();
     let mut total_triples = 0;
     let mut total_inferences = 0;
+    let mut all_succeeded = true;
+    
+    // Events the store already holds, e.g. scans an edge site forwards again, are not applied twice
+    let already_stored: std::collections::HashSet<&str> = {
+        let store_guard = app_state.store.lock().map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?;
+        payload
+            .events
+            .iter()
+            .filter(|event| store_guard.graph_len(&format!("urn:epcis:event:{}", event.event_id)) > 0)
+            .map(|event| event.event_id.as_str())
+            .collect()
+    };
     
     // Simplified event processing simulation
     for event in &payload.events {
+        if already_stored.contains(event.event_id.as_str()) {
+            results.push(serde_json::json!({
+                "event_id": event.event_id,
+                "success": true,
+                "duplicate": true,
+                "processing_time_ms": 0,
+                "triples_generated": 0,
+                "inferences_made": 0
+            }));
+            continue;
+        }
+        let warnings = match validate {
+            true => {
+                let validation = app_state.cluster.pipeline().validate_event_strict(event, strict)?;
+                if !validation.is_valid {
+                    all_succeeded = false;
+                    results.push(serde_json::json!({
+                        "event_id": event.event_id,
+                        "success": false,
+                        "error": format!("Validation failed: {}", validation.errors.join("; ")),
+                        "errors": validation.errors,
+                        "warnings": validation.warnings,
+                        "processing_time_ms": 0,
+                        "triples_generated": 0,
+                        "inferences_made": 0
+                    }));
+                    continue;
+                }
+                validation.warnings
+            }
+            false => Vec::new(),
+        };
         let processing_result = ProcessingResult {
             event_id: event.event_id.clone(),
             success: true,
@@ -363,50 +1415,2063 @@ async fn api_process_event(
             "triples_generated": processing_result.triples_generated,
             "inferences_made": processing_result.inferences_made,
             "processing_time_ms": processing_result.processing_time_ms,
+            "warnings": warnings,
             "note": "Simplified processing"
         }));
     }
     
+    let failed = results.iter().filter(|result| result["success"] == false).count();
+    let mut run = LineageRun::new("capture", if all_succeeded { RunState::Complete } else { RunState::Fail });
+    run.error = (!all_succeeded).then(|| format!("{} of {} events failed validation", failed, results.len()));
+    run.outputs.push(LineageDataset::graph("urn:epcis:event").with_row_count(results.len() - failed - already_stored.len()));
+    app_state.lineage.emit(&run);
+    
     Ok(Json(serde_json::json!({
-        "success": true,
+        "success": all_succeeded,
         "events_processed": results.len(),
+        "duplicates": already_stored.len(),
         "total_triples_generated": total_triples,
         "total_inferences_made": total_inferences,
         "validation_enabled": validate,
         "inference_enabled": infer,
+        "strict": strict,
+        "priority": priority,
         "results": results
     })))
 }
 
-async fn api_list_events(
+async fn api_list_events(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+) -> Result<Response, EpcisKgError> {
+    let frame_name = params.get("frame").map(|s| s.as_str()).unwrap_or(jsonld::EVENT_FRAME);
+    let frame = jsonld::load_frame(frame_name, &app_state.config.jsonld)?;
+    
+    // Every other parameter is an EPCIS simple event query parameter
+    let query_params: std::collections::HashMap<String, String> = params
+        .iter()
+        .filter(|(name, _)| name.as_str() != "frame")
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    let mut query = EpcisQuery::from_params(&query_params)?;
+    query.normalize_identifiers(app_state.cluster.pipeline().identifier_normalizer());
+    
+    // Event reads obey the same read ACLs as the SQL views over them
+    let api_key = api_key_from_headers(&headers);
+    let listed = with_graph_access(&app_state, api_key.as_deref(), "urn:epcis:event:", GraphAccess::Read, |store| {
+        if query_params.is_empty() {
+            return Ok(store.triples_in_graphs("urn:epcis:event:"));
+        }
+        let graph_names: Vec<String> = store
+            .query_events(&query)
+            .iter()
+            .map(|event_id| format!("urn:epcis:event:{}", event_id))
+            .collect();
+        Ok(store.union_graph(&graph_names).iter().map(|triple| triple.into_owned()).collect())
+    });
+    let triples: Vec<oxrdf::Triple> = match listed {
+        Ok(triples) => triples,
+        Err(e) => return Ok(graph_access_failure(api_key.as_deref(), e)),
+    };
+    
+    // An explicit frame returns the framed JSON-LD document itself
+    if params.contains_key("frame") {
+        return framed_jsonld_response(&triples, &frame);
+    }
+    
+    let framed = jsonld::JsonLdFramer::frame(&triples, &frame)?;
+    let events = framed["@graph"].as_array().cloned().unwrap_or_default();
+    
+    Ok(Json(serde_json::json!({
+        "events": events,
+        "total_events": events.len(),
+        "@context": framed["@context"]
+    })).into_response())
+}
+
+#[derive(serde::Deserialize)]
+struct DeleteEventParams {
+    reason: Option<String>,
+    /// Comma separated IDs of the events correcting this one
+    corrective_event_ids: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct EventCorrectionRequest {
+    declarations: Vec<ErrorDeclaration>,
+}
+
+/// Declare a single event in error.
+///
+/// EPCIS events are never physically removed: the event is kept, marked as
+/// declared in error, and inferences that depended on it are retracted.
+async fn api_delete_event(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(event_id): Path<String>,
+    Query(params): Query<DeleteEventParams>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let mut declaration = ErrorDeclaration::new(event_id);
+    declaration.reason = params.reason;
+    declaration.corrective_event_ids = params
+        .corrective_event_ids
+        .map(|ids| ids.split(',').map(|id| id.trim().to_string()).filter(|id| !id.is_empty()).collect())
+        .unwrap_or_default();
+    
+    match declare_event_in_error(&app_state, api_key.as_deref(), &declaration) {
+        Ok(result) => Json(serde_json::json!({
+            "success": true,
+            "error_declaration": result,
+            "status": "success"
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+/// Declare several events in error, reporting the outcome of each declaration
+async fn api_correct_events(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<EventCorrectionRequest>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let mut results = Vec::new();
+    let mut declared = 0;
+    
+    for declaration in &payload.declarations {
+        match declare_event_in_error(&app_state, api_key.as_deref(), declaration) {
+            Ok(result) => {
+                declared += 1;
+                results.push(serde_json::json!({ "success": true, "error_declaration": result }));
+            }
+            Err(e) => results.push(serde_json::json!({
+                "success": false,
+                "event_id": declaration.event_id,
+                "error": e.to_string()
+            })),
+        }
+    }
+    
+    Json(serde_json::json!({
+        "success": declared == payload.declarations.len(),
+        "events_declared": declared,
+        "events_failed": payload.declarations.len() - declared,
+        "results": results
+    })).into_response()
+}
+
+fn declare_event_in_error(
+    app_state: &AppState,
+    api_key: Option<&str>,
+    declaration: &ErrorDeclaration,
+) -> Result<ErrorDeclarationResult, EpcisKgError> {
+    let mut store_guard = app_state
+        .store
+        .lock()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?;
+    
+    let graphs = store_guard.event_graphs(&declaration.event_id);
+    app_state
+        .access_control
+        .read()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?
+        .check_graph_write(api_key, &graphs)?;
+    
+    store_guard.declare_event_in_error(declaration)
+}
+
+/// Run a store operation after checking the caller's access to the graph it touches
+pub(crate) fn with_graph_access<T>(
+    app_state: &AppState,
+    api_key: Option<&str>,
+    graph: &str,
+    access: GraphAccess,
+    operation: impl FnOnce(&mut OxigraphStore) -> Result<T, EpcisKgError>,
+) -> Result<T, EpcisKgError> {
+    let mut store_guard = app_state
+        .store
+        .lock()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?;
+    
+    let access_control = app_state
+        .access_control
+        .read()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?;
+    let graphs = [graph.to_string()];
+    match access {
+        GraphAccess::Read => access_control.check_graph_read(api_key, &graphs)?,
+        GraphAccess::Write => access_control.check_graph_write(api_key, &graphs)?,
+    }
+    let tenant = access_control.authenticate(api_key)?.cloned();
+    drop(access_control);
+    
+    let Some(tenant) = tenant else {
+        return operation(&mut store_guard);
+    };
+    match access {
+        GraphAccess::Read => {
+            app_state.quotas.check_query(&tenant)?;
+            let started = std::time::Instant::now();
+            let result = operation(&mut store_guard);
+            app_state.quotas.record_query(&tenant, started.elapsed());
+            result
+        }
+        GraphAccess::Write => {
+            app_state.quotas.check_write(&tenant, &store_guard)?;
+            operation(&mut store_guard)
+        }
+    }
+}
+
+/// Problem response for a failed store operation; access failures are 401 or 403
+fn graph_access_failure(api_key: Option<&str>, error: EpcisKgError) -> Response {
+    match error {
+        EpcisKgError::AccessDenied(_) => access_denied_response(api_key, error),
+        error => error.into_response(),
+    }
+}
+
+async fn api_list_parties(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    match with_graph_access(&app_state, api_key.as_deref(), PARTIES_GRAPH, GraphAccess::Read, |store| Ok(store.parties())) {
+        Ok(parties) => Json(serde_json::json!({
+            "parties": parties,
+            "total": parties.len()
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+/// Register a trading partner, replacing any existing entry for the same GLN
+async fn api_upsert_party(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(party): Json<Party>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    match with_graph_access(&app_state, api_key.as_deref(), PARTIES_GRAPH, GraphAccess::Write, |store| store.upsert_party(&party)) {
+        Ok(()) => Json(serde_json::json!({
+            "success": true,
+            "party": party
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+/// Current owner of an EPC, derived from ownership-transfer TransactionEvents
+async fn api_epc_owner(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(epc): Path<String>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    match with_graph_access(&app_state, api_key.as_deref(), OWNERSHIP_GRAPH, GraphAccess::Read, |store| Ok(store.current_owner(&epc))) {
+        Ok(Some(record)) => Json(serde_json::json!({
+            "success": true,
+            "ownership": record
+        })).into_response(),
+        Ok(None) => EpcisKgError::NotFound { resource: "Ownership record".to_string(), id: epc }.into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct EpcStatusRequest {
+    epcs: Vec<String>,
+}
+
+/// Status of a batch of EPCs for verification services, in request order
+async fn api_epc_status(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<EpcStatusRequest>,
+) -> Response {
+    let max_epcs = app_state.config.server.max_status_epcs;
+    if request.epcs.len() > max_epcs {
+        return EpcisKgError::InvalidField {
+            field: "epcs".to_string(),
+            message: format!("batch of {} EPCs exceeds the limit of {}", request.epcs.len(), max_epcs),
+        }
+        .into_response();
+    }
+    
+    let api_key = api_key_from_headers(&headers);
+    match with_graph_access(&app_state, api_key.as_deref(), "urn:epcis:event:", GraphAccess::Read, |store| {
+        Ok(store.epc_statuses(&request.epcs))
+    }) {
+        Ok(statuses) => Json(serde_json::json!({
+            "statuses": statuses,
+            "total": statuses.len()
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+async fn api_list_inventory_snapshots(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    match with_graph_access(&app_state, api_key.as_deref(), INVENTORY_SNAPSHOT_PREFIX, GraphAccess::Read, |store| {
+        Ok(store.inventory_snapshot_dates())
+    }) {
+        Ok(dates) => Json(serde_json::json!({
+            "dates": dates,
+            "total": dates.len()
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct InventoryParams {
+    location: Option<String>,
+    product_class: Option<String>,
+}
+
+/// Stored inventory positions for one day, read from its snapshot graph
+async fn api_inventory_snapshot(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(date): Path<String>,
+    Query(params): Query<InventoryParams>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let graph = snapshot_graph_name(&date);
+    match with_graph_access(&app_state, api_key.as_deref(), &graph, GraphAccess::Read, |store| Ok(store.inventory_snapshot(&date))) {
+        Ok(Some(mut snapshot)) => {
+            snapshot.positions.retain(|position| {
+                params.location.as_deref().map_or(true, |location| position.location == location)
+                    && params.product_class.as_deref().map_or(true, |class| position.product_class == class)
+            });
+            Json(serde_json::json!({
+                "success": true,
+                "snapshot": snapshot
+            })).into_response()
+        }
+        Ok(None) => EpcisKgError::NotFound { resource: "Inventory snapshot".to_string(), id: date }.into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+#[derive(serde::Deserialize, Default)]
+struct InventorySnapshotRequest {
+    /// RFC 3339 timestamp to replay the history up to (defaults to now)
+    as_of: Option<String>,
+}
+
+/// Compute a snapshot now (or as of a past time) and store it under its day
+async fn api_create_inventory_snapshot(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    request: Option<Json<InventorySnapshotRequest>>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    let as_of = match request.as_of.as_deref().map(chrono::DateTime::parse_from_rfc3339) {
+        None => chrono::Utc::now(),
+        Some(Ok(time)) => time.with_timezone(&chrono::Utc),
+        Some(Err(e)) => {
+            return graph_access_failure(api_key.as_deref(), EpcisKgError::Validation(format!("Invalid as_of timestamp: {}", e)))
+        }
+    };
+    
+    let graph = snapshot_graph_name(&as_of.format("%Y-%m-%d").to_string());
+    match with_graph_access(&app_state, api_key.as_deref(), &graph, GraphAccess::Write, |store| {
+        let snapshot = store.compute_inventory_snapshot(as_of);
+        store.store_inventory_snapshot(&snapshot)?;
+        Ok(snapshot)
+    }) {
+        Ok(snapshot) => Json(serde_json::json!({
+            "success": true,
+            "graph": graph,
+            "snapshot": snapshot
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ExpiringParams {
+    within_days: Option<i64>,
+    location: Option<String>,
+}
+
+/// Inventory expiring within `within_days` (default 7), grouped by location
+async fn api_expiring_inventory(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<ExpiringParams>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let within_days = params.within_days.unwrap_or(7).max(0);
+    match with_graph_access(&app_state, api_key.as_deref(), "urn:epcis:event:", GraphAccess::Read, |store| {
+        Ok(store.expiring_inventory(chrono::Utc::now(), chrono::Duration::days(within_days)))
+    }) {
+        Ok(mut locations) => {
+            if let Some(location) = &params.location {
+                locations.retain(|entry| &entry.location == location);
+            }
+            Json(serde_json::json!({
+                "within_days": within_days,
+                "total_items": locations.iter().map(|entry| entry.items.len()).sum::<usize>(),
+                "locations": locations
+            })).into_response()
+        }
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct QualityParams {
+    since: Option<String>,
+    until: Option<String>,
+    bucket: Option<String>,
+}
+
+/// Data-quality profile of the event predicates, with a completeness trend per period
+async fn api_quality_report(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<QualityParams>,
+) -> Response {
+    let parse_time = |field: &str, value: &Option<String>| -> Result<Option<chrono::DateTime<chrono::Utc>>, EpcisKgError> {
+        value
+            .as_deref()
+            .map(|time| {
+                chrono::DateTime::parse_from_rfc3339(time)
+                    .map(|time| time.with_timezone(&chrono::Utc))
+                    .map_err(|e| EpcisKgError::InvalidField { field: field.to_string(), message: e.to_string() })
+            })
+            .transpose()
+    };
+    let options = match (parse_time("since", &params.since), parse_time("until", &params.until), params.bucket.as_deref().unwrap_or("day").parse()) {
+        (Ok(since), Ok(until), Ok(bucket)) => QualityOptions { since, until, bucket },
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => return e.into_response(),
+    };
+    
+    let api_key = api_key_from_headers(&headers);
+    match with_graph_access(&app_state, api_key.as_deref(), "urn:epcis:event:", GraphAccess::Read, |store| {
+        Ok(store.quality_report(&options))
+    }) {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+/// Events flagged by the shelf-life rules for observing expired product
+async fn api_expired_observations(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    match with_graph_access(&app_state, api_key.as_deref(), EXPIRY_GRAPH, GraphAccess::Read, |store| {
+        Ok(store.expired_observations())
+    }) {
+        Ok(observations) => Json(serde_json::json!({
+            "observations": observations,
+            "total": observations.len()
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+/// Detect excursions, record them and alert on those not seen by an earlier scan
+/// Executes scheduled job tasks against the server's store
+fn job_runner(
+    store: Arc<Mutex<OxigraphStore>>,
+    config: Arc<AppConfig>,
+    extensions: Arc<parking_lot::RwLock<ExtensionRegistry>>,
+    monitor: Arc<SystemMonitor>,
+    webhooks: Arc<WebhookNotifier>,
+    snapshots: Arc<SnapshotManager>,
+) -> JobRunner {
+    Arc::new(move |task: &JobTask| {
+        // Jobs write their results into the store, so each run is a batch for query snapshots
+        let _batch = snapshots.begin_write();
+        let lock = || store.lock().map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)));
+        match task {
+            JobTask::InventorySnapshot => {
+                let mut store_guard = lock()?;
+                let snapshot = store_guard.compute_inventory_snapshot(chrono::Utc::now());
+                store_guard.store_inventory_snapshot(&snapshot)?;
+                Ok(format!(
+                    "Stored inventory snapshot {} ({} positions from {} events)",
+                    snapshot.date,
+                    snapshot.positions.len(),
+                    snapshot.events_replayed
+                ))
+            }
+            JobTask::ColdChainScan => {
+                let (excursions, new_excursions) = scan_cold_chain(&store, &config, &monitor, &webhooks)?;
+                Ok(format!("{} excursions, {} new", excursions.len(), new_excursions.len()))
+            }
+            JobTask::PatternScan => {
+                let (matches, new_matches) = scan_event_patterns(&store, &config, &monitor)?;
+                Ok(format!("{} pattern matches, {} new", matches.len(), new_matches.len()))
+            }
+            JobTask::CounterfeitScan => {
+                let mut store_guard = lock()?;
+                let reports = store_guard.detect_suspicion(&config.counterfeit);
+                store_guard.persist_suspicion(&reports)?;
+                let suspects = reports.iter().filter(|report| report.score >= config.counterfeit.suspect_threshold).count();
+                Ok(format!("{} EPCs scored, {} suspects", reports.len(), suspects))
+            }
+            JobTask::CarbonScan => {
+                let mut store_guard = lock()?;
+                let shipments = store_guard.estimate_shipments(&config.carbon);
+                store_guard.materialize_shipments(&shipments)?;
+                let estimated = shipments.iter().filter(|shipment| shipment.kg_co2e.is_some()).count();
+                Ok(format!("{} shipments, {} estimated", shipments.len(), estimated))
+            }
+            JobTask::SchemaDriftScan => {
+                let (report, new_terms) = scan_schema_drift(&store, &extensions, &monitor)?;
+                Ok(format!("{} unknown terms, {} new", report.unknown_terms.len(), new_terms.len()))
+            }
+            JobTask::Backup { directory, keep } => {
+                let exported = lock()?.export_turtle()?;
+                crate::scheduler::write_backup(std::path::Path::new(directory), &exported, *keep)
+            }
+            JobTask::GraphTtlSweep => {
+                let report = lock()?.expire_by_ttl(&config.graph_ttl.policies, chrono::Utc::now())?;
+                Ok(format!(
+                    "{} triples of {} subjects expired, {} graphs removed, {} inferences retracted",
+                    report.triples_removed,
+                    report.subjects_expired,
+                    report.graphs_removed.len(),
+                    report.inferences_retracted
+                ))
+            }
+        }
+    })
+}
+
+async fn api_list_jobs(State(app_state): State<AppState>) -> Response {
+    let jobs = app_state.scheduler.jobs();
+    Json(serde_json::json!({
+        "jobs": jobs,
+        "total": jobs.len()
+    })).into_response()
+}
+
+/// Start a job outside its schedule; admin only when access control is enabled
+async fn api_run_job(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    {
+        let access_control = match app_state.access_control.read() {
+            Ok(access_control) => access_control,
+            Err(e) => return graph_access_failure(api_key.as_deref(), EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e))),
+        };
+        if let Err(e) = require_admin(&access_control, api_key.as_deref(), "run scheduled jobs") {
+            return access_denied_response(api_key.as_deref(), e);
+        }
+    }
+    
+    match app_state.scheduler.trigger(&name) {
+        TriggerOutcome::Started => (StatusCode::ACCEPTED, Json(serde_json::json!({
+            "success": true,
+            "job": name,
+            "status": "started"
+        }))).into_response(),
+        TriggerOutcome::AlreadyRunning => EpcisKgError::Conflict(format!("Job {} is already running", name)).into_response(),
+        TriggerOutcome::UnknownJob => EpcisKgError::NotFound { resource: "Job".to_string(), id: name }.into_response(),
+    }
+}
+
+/// Running log levels: the global level plus per-target overrides
+async fn api_log_levels() -> Response {
+    match log_level_control() {
+        Some(control) => Json(control.current()).into_response(),
+        None => EpcisKgError::NotImplemented("Log levels cannot be changed in this process".to_string()).into_response(),
+    }
+}
+
+/// Recent queries over the slow-query threshold, slowest first, with their plans
+async fn api_slow_queries(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    {
+        let access_control = match app_state.access_control.read() {
+            Ok(access_control) => access_control,
+            Err(e) => return graph_access_failure(api_key.as_deref(), EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e))),
+        };
+        // Logged queries are other callers' queries
+        if let Err(e) = require_admin(&access_control, api_key.as_deref(), "read the query log") {
+            return access_denied_response(api_key.as_deref(), e);
+        }
+    }
+    
+    let limit = params.get("limit").and_then(|limit| limit.parse::<usize>().ok()).unwrap_or(20);
+    let queries = app_state.query_log.slow(limit);
+    Json(serde_json::json!({
+        "success": true,
+        "enabled": app_state.query_log.is_enabled(),
+        "threshold_ms": app_state.config.query_log.slow_threshold_ms,
+        "queries": queries,
+        "total": queries.len()
+    })).into_response()
+}
+
+/// Change log levels without a restart; admin only when access control is enabled
+async fn api_set_log_levels(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(update): Json<LogLevelUpdate>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    {
+        let access_control = match app_state.access_control.read() {
+            Ok(access_control) => access_control,
+            Err(e) => return graph_access_failure(api_key.as_deref(), EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e))),
+        };
+        if let Err(e) = require_admin(&access_control, api_key.as_deref(), "change log levels") {
+            return access_denied_response(api_key.as_deref(), e);
+        }
+    }
+    
+    let Some(control) = log_level_control() else {
+        return EpcisKgError::NotImplemented("Log levels cannot be changed in this process".to_string()).into_response();
+    };
+    match control.update(&update) {
+        Ok(levels) => Json(serde_json::json!({
+            "success": true,
+            "level": levels.level,
+            "targets": levels.targets
+        })).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+fn scan_cold_chain(
+    store: &Mutex<OxigraphStore>,
+    config: &AppConfig,
+    monitor: &SystemMonitor,
+    webhooks: &WebhookNotifier,
+) -> Result<(Vec<Excursion>, Vec<Excursion>), EpcisKgError> {
+    let mut store_guard = store
+        .lock()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?;
+    let excursions = store_guard.detect_excursions(&config.cold_chain.profiles);
+    let new_excursions = store_guard.materialize_excursions(&excursions)?;
+    drop(store_guard);
+    
+    for excursion in &new_excursions {
+        monitor.add_alert(
+            AlertSeverity::Warning,
+            AlertType::ColdChain,
+            format!(
+                "Temperature excursion for {}: {}°C outside {}..{}°C since {}",
+                excursion.epc, excursion.extreme_celsius, excursion.min_celsius, excursion.max_celsius, excursion.start
+            ),
+            serde_json::to_value(excursion).unwrap_or_default(),
+        );
+        webhooks.notify("excursionDetected", excursion);
+    }
+    
+    Ok((excursions, new_excursions))
+}
+
+/// Excursions in the current sensor readings, without recording or alerting
+async fn api_list_excursions(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let profiles = app_state.config.cold_chain.profiles.clone();
+    match with_graph_access(&app_state, api_key.as_deref(), COLD_CHAIN_GRAPH, GraphAccess::Read, |store| {
+        Ok(store.detect_excursions(&profiles))
+    }) {
+        Ok(excursions) => Json(serde_json::json!({
+            "excursions": excursions,
+            "total": excursions.len(),
+            "profiles": profiles.len()
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+async fn api_scan_cold_chain(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    if let Err(e) = with_graph_access(&app_state, api_key.as_deref(), COLD_CHAIN_GRAPH, GraphAccess::Write, |_| Ok(())) {
+        return graph_access_failure(api_key.as_deref(), e);
+    }
+    
+    match scan_cold_chain(&app_state.store, &app_state.config, &app_state.monitor, &app_state.webhooks) {
+        Ok((excursions, new_excursions)) => Json(serde_json::json!({
+            "success": true,
+            "graph": COLD_CHAIN_GRAPH,
+            "total": excursions.len(),
+            "new_excursions": new_excursions
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ProposalParams {
+    min_confidence: Option<f64>,
+    limit: Option<usize>,
+}
+
+/// Proposed `owl:sameAs` links between near-duplicate master data, most confident first
+async fn api_same_as_proposals(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<ProposalParams>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let mut config = app_state.config.entity_resolution.clone();
+    if let Some(min_confidence) = params.min_confidence {
+        config.min_confidence = min_confidence;
+    }
+    match with_graph_access(&app_state, api_key.as_deref(), SAME_AS_GRAPH, GraphAccess::Read, |store| Ok(store.propose_same_as(&config))) {
+        Ok(mut proposals) => {
+            let total = proposals.len();
+            if let Some(limit) = params.limit {
+                proposals.truncate(limit);
+            }
+            Json(serde_json::json!({
+                "min_confidence": config.min_confidence,
+                "proposals": proposals,
+                "total": total
+            })).into_response()
+        }
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+/// Accept or reject a proposed link; accepted links are asserted as `owl:sameAs`
+async fn api_review_same_as(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(review): Json<SameAsReview>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    match with_graph_access(&app_state, api_key.as_deref(), SAME_AS_GRAPH, GraphAccess::Write, |store| store.review_same_as(&review)) {
+        Ok(()) => (StatusCode::CREATED, Json(serde_json::json!({
+            "success": true,
+            "graph": SAME_AS_GRAPH,
+            "review": review
+        }))).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+/// Record the result of verifying an EPC with its brand owner or a verification service
+async fn api_record_verification(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(epc): Path<String>,
+    Json(request): Json<VerificationRequest>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let verification = Verification {
+        epc,
+        verified: request.verified,
+        verifier: request.verifier,
+        location: request.location,
+        time: request.time,
+    };
+    match with_graph_access(&app_state, api_key.as_deref(), VERIFICATIONS_GRAPH, GraphAccess::Write, |store| {
+        store.record_verification(&verification)
+    }) {
+        Ok(()) => (StatusCode::CREATED, Json(serde_json::json!({
+            "success": true,
+            "verification": verification
+        }))).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct VerificationRequest {
+    verified: bool,
+    verifier: Option<String>,
+    location: Option<String>,
+    time: Option<String>,
+}
+
+/// Rescore every EPC and replace the persisted suspicion scores
+async fn api_scan_counterfeit(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let config = app_state.config.counterfeit.clone();
+    match with_graph_access(&app_state, api_key.as_deref(), COUNTERFEIT_GRAPH, GraphAccess::Write, |store| {
+        let reports = store.detect_suspicion(&config);
+        store.persist_suspicion(&reports)?;
+        Ok(reports)
+    }) {
+        Ok(reports) => {
+            let suspects: Vec<_> = reports.iter().filter(|report| report.score >= config.suspect_threshold).collect();
+            Json(serde_json::json!({
+                "success": true,
+                "graph": COUNTERFEIT_GRAPH,
+                "scored": reports.len(),
+                "threshold": config.suspect_threshold,
+                "suspects": suspects
+            })).into_response()
+        }
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct CarbonParams {
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// CO2e and cost of the shipments received in `[since, until)`, without recording them
+async fn api_carbon_report(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<CarbonParams>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let config = app_state.config.carbon.clone();
+    match with_graph_access(&app_state, api_key.as_deref(), "urn:epcis:event:", GraphAccess::Read, |store| {
+        Ok(store.estimate_shipments(&config))
+    }) {
+        Ok(shipments) => {
+            let shipments = shipments
+                .into_iter()
+                .filter(|shipment| params.since.map_or(true, |since| shipment.received_at >= since))
+                .filter(|shipment| params.until.map_or(true, |until| shipment.received_at < until))
+                .collect();
+            Json(carbon::summarize(shipments, &config.currency)).into_response()
+        }
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+async fn api_scan_carbon(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let config = app_state.config.carbon.clone();
+    match with_graph_access(&app_state, api_key.as_deref(), CARBON_GRAPH, GraphAccess::Write, |store| {
+        let shipments = store.estimate_shipments(&config);
+        store.materialize_shipments(&shipments)?;
+        Ok(shipments)
+    }) {
+        Ok(shipments) => {
+            let report = carbon::summarize(shipments, &config.currency);
+            Json(serde_json::json!({
+                "success": true,
+                "graph": CARBON_GRAPH,
+                "shipments": report.shipments.len(),
+                "estimated": report.estimated,
+                "total_kg_co2e": report.total_kg_co2e,
+                "unconfigured_lanes": report.unconfigured_lanes
+            })).into_response()
+        }
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct SuspectParams {
+    min_score: Option<f64>,
+}
+
+/// Persisted suspicion scores at or above `min_score` (default: the configured threshold)
+async fn api_list_suspects(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<SuspectParams>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let min_score = params.min_score.unwrap_or(app_state.config.counterfeit.suspect_threshold);
+    match with_graph_access(&app_state, api_key.as_deref(), COUNTERFEIT_GRAPH, GraphAccess::Read, |store| Ok(store.suspects(min_score))) {
+        Ok(suspects) => Json(serde_json::json!({
+            "min_score": min_score,
+            "suspects": suspects,
+            "total": suspects.len()
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+async fn api_epc_suspicion(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(epc): Path<String>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    match with_graph_access(&app_state, api_key.as_deref(), COUNTERFEIT_GRAPH, GraphAccess::Read, |store| Ok(store.suspicion(&epc))) {
+        Ok(Some(report)) => Json(serde_json::json!({
+            "success": true,
+            "suspicion": report
+        })).into_response(),
+        Ok(None) => EpcisKgError::NotFound { resource: "Suspicion report".to_string(), id: epc }.into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+/// Evaluate the pattern rules, record matches and alert on those not seen by an earlier scan
+fn scan_event_patterns(
+    store: &Mutex<OxigraphStore>,
+    config: &AppConfig,
+    monitor: &SystemMonitor,
+) -> Result<(Vec<PatternMatch>, Vec<PatternMatch>), EpcisKgError> {
+    let mut store_guard = store
+        .lock()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?;
+    let matches = store_guard.evaluate_patterns(&config.event_patterns.rules, chrono::Utc::now());
+    let new_matches = store_guard.materialize_pattern_matches(&matches)?;
+    drop(store_guard);
+    
+    for pattern_match in &new_matches {
+        monitor.add_alert(
+            AlertSeverity::Warning,
+            AlertType::EventPattern,
+            format!("Pattern {} matched for {}: {}", pattern_match.rule, pattern_match.subject, pattern_match.detail),
+            serde_json::to_value(pattern_match).unwrap_or_default(),
+        );
+    }
+    
+    Ok((matches, new_matches))
+}
+
+/// Report predicates and classes of stored events no loaded ontology defines, alerting on new ones
+fn scan_schema_drift(
+    store: &Mutex<OxigraphStore>,
+    extensions: &parking_lot::RwLock<ExtensionRegistry>,
+    monitor: &SystemMonitor,
+) -> Result<(SchemaDriftReport, Vec<UnknownTerm>), EpcisKgError> {
+    let mut store_guard = store
+        .lock()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?;
+    let report = {
+        let extensions = extensions.read();
+        store_guard.schema_drift(|iri| extensions.declares(iri))
+    };
+    let new_terms = store_guard.materialize_schema_drift(&report.unknown_terms)?;
+    drop(store_guard);
+    
+    for term in &new_terms {
+        monitor.add_alert(
+            AlertSeverity::Warning,
+            AlertType::SchemaDrift,
+            format!(
+                "Events use {} {} which no loaded ontology defines (first in event {})",
+                term.kind.as_str(), term.iri, term.example_event
+            ),
+            serde_json::to_value(term).unwrap_or_default(),
+        );
+    }
+    
+    Ok((report, new_terms))
+}
+
+/// Unknown-term statistics of the stored events, without recording or alerting
+async fn api_schema_drift(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let extensions = Arc::clone(&app_state.extensions);
+    match with_graph_access(&app_state, api_key.as_deref(), "urn:epcis:event:", GraphAccess::Read, |store| {
+        let extensions = extensions.read();
+        Ok(store.schema_drift(|iri| extensions.declares(iri)))
+    }) {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+async fn api_scan_schema_drift(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    if let Err(e) = with_graph_access(&app_state, api_key.as_deref(), SCHEMA_DRIFT_GRAPH, GraphAccess::Write, |_| Ok(())) {
+        return graph_access_failure(api_key.as_deref(), e);
+    }
+    
+    match scan_schema_drift(&app_state.store, &app_state.extensions, &app_state.monitor) {
+        Ok((report, new_terms)) => Json(serde_json::json!({
+            "success": true,
+            "graph": SCHEMA_DRIFT_GRAPH,
+            "report": report,
+            "new_terms": new_terms
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+async fn api_list_pattern_rules(State(app_state): State<AppState>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "rules": app_state.config.event_patterns.rules,
+        "total": app_state.config.event_patterns.rules.len()
+    }))
+}
+
+/// Matches in the current history, without recording or alerting
+async fn api_list_pattern_matches(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let rules = app_state.config.event_patterns.rules.clone();
+    match with_graph_access(&app_state, api_key.as_deref(), EVENT_PATTERNS_GRAPH, GraphAccess::Read, |store| {
+        Ok(store.evaluate_patterns(&rules, chrono::Utc::now()))
+    }) {
+        Ok(matches) => Json(serde_json::json!({
+            "matches": matches,
+            "total": matches.len()
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+async fn api_scan_patterns(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    if let Err(e) = with_graph_access(&app_state, api_key.as_deref(), EVENT_PATTERNS_GRAPH, GraphAccess::Write, |_| Ok(())) {
+        return graph_access_failure(api_key.as_deref(), e);
+    }
+    
+    match scan_event_patterns(&app_state.store, &app_state.config, &app_state.monitor) {
+        Ok((matches, new_matches)) => Json(serde_json::json!({
+            "success": true,
+            "graph": EVENT_PATTERNS_GRAPH,
+            "total": matches.len(),
+            "new_matches": new_matches
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct RecallSimulationRequest {
+    #[serde(flatten)]
+    recall: RecallRequest,
+    /// Also store the impact graph under `urn:epcis:recall:<report id>`
+    #[serde(default)]
+    store_graph: bool,
+}
+
+/// Follow contaminated lots or EPCs downstream through transformations and shipments
+async fn api_simulate_recall(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<RecallSimulationRequest>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    if request.recall.epcs.is_empty() && request.recall.lots.is_empty() {
+        return graph_access_failure(
+            api_key.as_deref(),
+            EpcisKgError::Validation("A recall needs at least one EPC or lot".to_string()),
+        );
+    }
+    
+    let report = match with_graph_access(&app_state, api_key.as_deref(), "urn:epcis:event:", GraphAccess::Read, |store| {
+        Ok(store.simulate_recall(&request.recall))
+    }) {
+        Ok(report) => report,
+        Err(e) => return graph_access_failure(api_key.as_deref(), e),
+    };
+    let impact_triples = report.to_triples();
+    
+    let stored_graph = if request.store_graph {
+        let graph = report.graph_name();
+        if let Err(e) = with_graph_access(&app_state, api_key.as_deref(), &graph, GraphAccess::Write, |store| {
+            store.insert_triples(&graph, &impact_triples)
+        }) {
+            return graph_access_failure(api_key.as_deref(), e);
+        }
+        Some(graph)
+    } else {
+        None
+    };
+    
+    Json(serde_json::json!({
+        "success": true,
+        "report": report,
+        "impact_graph": impact_triples.iter().map(|triple| format!("{} .", triple)).collect::<Vec<_>>().join("\n"),
+        "stored_graph": stored_graph
+    })).into_response()
+}
+
+async fn api_trace_epc(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(epc): Path<String>,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+) -> Result<Response, EpcisKgError> {
+    // A Digital Link finds the EPC URN its captures were stored as
+    let epc = app_state.cluster.pipeline().identifier_normalizer().normalize(&epc);
+    let frame_name = params.get("frame").map(|s| s.as_str()).unwrap_or(jsonld::EPC_FRAME);
+    let mut frame = jsonld::load_frame(frame_name, &app_state.config.jsonld)?;
+    
+    // Anchor EPC-centric frames on the requested EPC
+    if frame_name == jsonld::EPC_FRAME {
+        frame["@id"] = serde_json::Value::String(epc.clone());
+    }
+    
+    let api_key = api_key_from_headers(&headers);
+    let traced = with_graph_access(&app_state, api_key.as_deref(), "urn:epcis:event:", GraphAccess::Read, |store| Ok(epc_trace_triples(store, &epc)));
+    let triples = match traced {
+        Ok(triples) => triples,
+        Err(e) => return Ok(graph_access_failure(api_key.as_deref(), e)),
+    };
+    
+    let wants = |name: &str| params.get(name).is_some_and(|value| value == "true");
+    if !wants("sign") && !wants("credential") {
+        return framed_jsonld_response(&triples, &frame);
+    }
+    
+    let signer = DocumentSigner::from_config(app_state.config.signing.clone())?;
+    
+    // A credential carries the framed trace as its subject and is signed as a whole
+    if wants("credential") {
+        let framed = jsonld::JsonLdFramer::frame(&triples, &frame)?;
+        let credential = signer.issue_credential(framed, "EpcisTraceCredential")?;
+        return Ok(([(header::CONTENT_TYPE, CREDENTIAL_MEDIA_TYPE)], credential).into_response());
+    }
+    
+    // Otherwise the signature covers the trace's canonical N-Quads, whatever the framing
+    let quads: Vec<oxrdf::Quad> = triples.iter().map(|triple| triple.clone().in_graph(oxrdf::GraphName::DefaultGraph)).collect();
+    let signature = signer.sign_dataset(&quads)?;
+    let mut response = framed_jsonld_response(&triples, &frame)?;
+    if let Ok(value) = header::HeaderValue::from_str(&signature) {
+        response.headers_mut().insert(TRACE_SIGNATURE_HEADER, value);
+    }
+    Ok(response)
+}
+
+/// Public signing key as a JWK Set, for partners verifying signed traces
+async fn api_signing_keys(State(app_state): State<AppState>) -> Result<Response, EpcisKgError> {
+    let signer = DocumentSigner::from_config(app_state.config.signing.clone())?;
+    let keys: Vec<serde_json::Value> = signer.public_jwk().into_iter().collect();
+    Ok(Json(serde_json::json!({ "keys": keys })).into_response())
+}
+
+/// Triples of the stored events whose EPC list contains `epc`
+pub(crate) fn epc_trace_triples(store: &OxigraphStore, epc: &str) -> Vec<oxrdf::Triple> {
+    let Ok(epc) = oxrdf::NamedNodeRef::new(epc) else { return Vec::new() };
+    let epc_list = oxrdf::NamedNodeRef::new_unchecked("urn:epcglobal:epcis:epcList");
+    
+    // Find the listing events through the object index, then read each event from its own graph
+    let mut triples = Vec::new();
+    for listing in store.quads_for_pattern(None, Some(epc_list), Some(epc.into()), None) {
+        let oxrdf::GraphName::NamedNode(graph) = &listing.graph_name else { continue };
+        if !graph.as_str().starts_with("urn:epcis:event:") {
+            continue;
+        }
+        let event = store.quads_for_pattern(Some(listing.subject.as_ref()), None, None, Some(graph.as_str()));
+        triples.extend(event.into_iter().map(oxrdf::Triple::from));
+    }
+    triples
+}
+
+/// Frame triples and return them as `application/ld+json`
+fn framed_jsonld_response(triples: &[oxrdf::Triple], frame: &serde_json::Value) -> Result<Response, EpcisKgError> {
+    let framed = jsonld::JsonLdFramer::frame(triples, frame)?;
+    
+    Ok((
+        [(header::CONTENT_TYPE, "application/ld+json")],
+        Json(framed),
+    ).into_response())
+}
+
+#[derive(serde::Deserialize)]
+struct VocabularyParams {
+    /// Case-insensitive prefix of the term label or local name
+    q: Option<String>,
+    limit: Option<usize>,
+}
+
+/// List CBV terms from the live ontology for UI dropdowns and autocompletion
+async fn api_vocabulary_terms(
+    State(app_state): State<AppState>,
+    Path(vocabulary_name): Path<String>,
+    Query(params): Query<VocabularyParams>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(vocabulary) = CbvVocabulary::from_name(&vocabulary_name) else {
+        return EpcisKgError::NotFound {
+            resource: "Vocabulary (expected bizsteps, dispositions or biztransactiontypes)".to_string(),
+            id: vocabulary_name,
+        }
+        .into_response();
+    };
+    let languages = request_languages(&headers, &app_state.config);
+    
+    let is_ontology = |name: &String| name.starts_with("urn:epcis:ontology:") && !name.starts_with(VOCABULARY_EXTENSION_GRAPH_PREFIX);
+    let (stored, extensions) = match app_state.store.lock() {
+        Ok(store_guard) if store_guard.graph_names().iter().any(is_ontology) => {
+            (Some(vocabulary.terms(&store_guard, params.q.as_deref(), &languages)), Vec::new())
+        }
+        Ok(store_guard) => (None, store_guard.vocabulary_extensions()),
+        Err(e) => return EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)).into_response(),
+    };
+    
+    // No ontology loaded into the store yet: read the configured ontology files,
+    // plus any extensions registered in the meantime
+    let mut terms = match stored {
+        Some(terms) => terms,
+        None => {
+            let files = configured_ontologies(&app_state.config).and_then(|mut files| {
+                for extension in &extensions {
+                    files.register_vocabulary_extension(extension)?;
+                }
+                Ok(files)
+            });
+            match files {
+                Ok(files) => vocabulary.terms(&files, params.q.as_deref(), &languages),
+                Err(e) => return e.into_response(),
+            }
+        }
+    };
+    let total = terms.len();
+    if let Some(limit) = params.limit {
+        terms.truncate(limit);
+    }
+    
+    Json(serde_json::json!({
+        "vocabulary": vocabulary_name.to_ascii_lowercase(),
+        "class": vocabulary.class_iri(),
+        "terms": terms,
+        "total": total
+    })).into_response()
+}
+
+/// Languages of the request's `Accept-Language`, or the configured default language
+fn request_languages(headers: &HeaderMap, config: &AppConfig) -> Vec<String> {
+    let accepted = headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(i18n::accepted_languages)
+        .unwrap_or_default();
+    if accepted.is_empty() {
+        vec![config.localization.default_language.to_lowercase()]
+    } else {
+        accepted
+    }
+}
+
+/// In-memory store holding the configured ontology and label files that exist on disk
+fn configured_ontologies(config: &AppConfig) -> Result<OxigraphStore, EpcisKgError> {
+    let loader = OntologyLoader::with_config(config);
+    let mut store = OxigraphStore::new_memory()?;
+    let files = config.ontology_paths.iter().chain(&config.localization.label_paths);
+    for path in files.filter(|path| std::path::Path::new(path.as_str()).exists()) {
+        if let Ok(ontology) = loader.load_ontology(path) {
+            store.store_ontology_data(&ontology)?;
+        }
+    }
+    Ok(store)
+}
+
+async fn api_list_extensions(
+    State(app_state): State<AppState>,
 ) -> Json<serde_json::Value> {
+    let registry = app_state.extensions.read();
     Json(serde_json::json!({
-        "events": [],
-        "total_events": 0,
-        "message": "Event listing functionality to be implemented"
+        "strict": registry.is_strict(),
+        "namespaces": registry.namespaces()
     }))
 }
 
+/// Register (or replace) an extension namespace; requires an admin key when access control is enabled
+async fn api_register_extension(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<ExtensionNamespace>,
+) -> Result<Response, EpcisKgError> {
+    let api_key = api_key_from_headers(&headers);
+    {
+        let access_control = app_state.access_control.read().map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?;
+        if let Err(e) = require_admin(&access_control, api_key.as_deref(), "register extension namespaces") {
+            return Ok(access_denied_response(api_key.as_deref(), e));
+        }
+    }
+    
+    let prefix = payload.prefix.clone();
+    app_state.extensions.write().register(payload)?;
+    
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "prefix": prefix
+    })).into_response())
+}
+
+async fn api_list_vocabulary_extensions(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    match with_graph_access(&app_state, api_key.as_deref(), VOCABULARY_EXTENSION_GRAPH_PREFIX, GraphAccess::Read, |store| Ok(store.vocabulary_extensions())) {
+        Ok(extensions) => Json(serde_json::json!({
+            "extensions": extensions,
+            "total": extensions.len()
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+/// Register a SKOS concept scheme of custom bizSteps or dispositions, replacing an earlier version
+async fn api_register_vocabulary_extension(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(extension): Json<VocabularyExtension>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let graph = extension_graph_name(&extension.scheme);
+    match with_graph_access(&app_state, api_key.as_deref(), &graph, GraphAccess::Write, |store| store.register_vocabulary_extension(&extension)) {
+        Ok(graph) => (StatusCode::CREATED, Json(serde_json::json!({
+            "success": true,
+            "graph": graph,
+            "extension": extension
+        }))).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct VocabularyExtensionParams {
+    scheme: String,
+}
+
+async fn api_remove_vocabulary_extension(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<VocabularyExtensionParams>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let graph = extension_graph_name(&params.scheme);
+    match with_graph_access(&app_state, api_key.as_deref(), &graph, GraphAccess::Write, |store| store.remove_vocabulary_extension(&params.scheme)) {
+        Ok(true) => Json(serde_json::json!({ "success": true, "scheme": params.scheme })).into_response(),
+        Ok(false) => EpcisKgError::NotFound { resource: "Vocabulary extension".to_string(), id: params.scheme }.into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+async fn api_list_views(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    match with_graph_access(&app_state, api_key.as_deref(), VIEW_DEFINITIONS_GRAPH, GraphAccess::Read, |store| Ok(store.views())) {
+        Ok(views) => Json(serde_json::json!({
+            "views": views,
+            "total": views.len()
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+/// Register a CONSTRUCT query as a view kept up to date on writes, replacing a view of the same name
+async fn api_register_view(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(definition): Json<ViewDefinition>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let graph = definition.graph_name();
+    let registered = with_graph_access(&app_state, api_key.as_deref(), &graph, GraphAccess::Write, |store| {
+        // The view's graph must not expose graphs its author may not read; a
+        // view reading every graph also needs read access to graphs yet to come
+        let sources = match definition.sources()? {
+            Some(graphs) => graphs,
+            None => store.graph_names().into_iter().filter(|name| is_view_source(name)).chain(["*".to_string()]).collect(),
+        };
+        app_state
+            .access_control
+            .read()
+            .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?
+            .check_graph_read(api_key.as_deref(), &sources)?;
+        store.register_view(&definition)
+    });
+    match registered {
+        Ok(view) => (StatusCode::CREATED, Json(serde_json::json!({
+            "success": true,
+            "view": view
+        }))).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+async fn api_get_view(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let found = with_graph_access(&app_state, api_key.as_deref(), &view_graph_name(&name), GraphAccess::Read, |store| {
+        store.view(&name).ok_or_else(|| EpcisKgError::NotFound { resource: "View".to_string(), id: name.clone() })
+    });
+    match found {
+        Ok(view) => Json(view).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+async fn api_refresh_view(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    match with_graph_access(&app_state, api_key.as_deref(), &view_graph_name(&name), GraphAccess::Write, |store| store.refresh_view(&name)) {
+        Ok(view) => Json(serde_json::json!({ "success": true, "view": view })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+async fn api_remove_view(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    match with_graph_access(&app_state, api_key.as_deref(), &view_graph_name(&name), GraphAccess::Write, |store| store.remove_view(&name)) {
+        Ok(true) => Json(serde_json::json!({ "success": true, "name": name })).into_response(),
+        Ok(false) => EpcisKgError::NotFound { resource: "View".to_string(), id: name }.into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
 #[derive(serde::Deserialize)]
+struct SessionTriplesRequest {
+    /// Overlay graph receiving the triples (defaults to the session's hypothetical graph)
+    graph: Option<String>,
+    /// Turtle or N-Triples
+    turtle: String,
+}
+
+#[derive(serde::Deserialize, Default)]
+struct SessionInferenceRequest {
+    tbox_graphs: Option<Vec<String>>,
+    abox_graphs: Option<Vec<String>>,
+}
+
+/// Authenticate the caller of a session route, returning the API key its sessions belong to
+fn session_owner(app_state: &AppState, api_key: Option<&str>) -> Result<Option<String>, EpcisKgError> {
+    let access_control = app_state
+        .access_control
+        .read()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?;
+    Ok(access_control.authenticate(api_key)?.map(|acl| acl.key.clone()))
+}
+
+/// Run `read` on the store view (persisted store plus overlay) of a session of `owner`
+fn with_session_view<T>(
+    app_state: &AppState,
+    session_id: &str,
+    owner: Option<&str>,
+    read: impl FnOnce(&OverlayView<'_>) -> Result<T, EpcisKgError>,
+) -> Result<T, EpcisKgError> {
+    let store_guard = app_state
+        .store
+        .lock()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?;
+    let view = app_state.sessions.with_session(session_id, owner, |session| session.view(&store_guard))?;
+    read(&view)
+}
+
+/// Archived snapshots available to `include_archives` queries
+async fn api_list_archives(
+    State(app_state): State<AppState>,
+) -> Response {
+    match app_state.archives.snapshots() {
+        Ok(snapshots) => Json(serde_json::json!({
+            "directory": app_state.config.archive.directory,
+            "archives": snapshots,
+            "total": snapshots.len()
+        })).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Open a session owned by the caller's API key
+async fn api_open_session(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let opened = session_owner(&app_state, api_key.as_deref()).and_then(|owner| app_state.sessions.open(owner.as_deref()));
+    match opened {
+        Ok(summary) => (StatusCode::CREATED, Json(serde_json::json!({
+            "success": true,
+            "session": summary
+        }))).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+async fn api_get_session(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let summary = session_owner(&app_state, api_key.as_deref()).and_then(|owner| {
+        app_state.sessions.with_session(&session_id, owner.as_deref(), |session| session.summary(app_state.sessions.ttl()))
+    });
+    match summary {
+        Ok(summary) => Json(serde_json::json!({
+            "success": true,
+            "session": summary
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+async fn api_discard_session(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    match session_owner(&app_state, api_key.as_deref()).and_then(|owner| app_state.sessions.discard(&session_id, owner.as_deref())) {
+        Ok(true) => Json(serde_json::json!({
+            "success": true,
+            "session_id": session_id,
+            "discarded": true
+        })).into_response(),
+        Ok(false) => EpcisKgError::NotFound { resource: "Session".to_string(), id: session_id }.into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+async fn api_session_add_triples(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+    Json(payload): Json<SessionTriplesRequest>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let graph = payload.graph.as_deref().unwrap_or(DEFAULT_OVERLAY_GRAPH);
+    let added = session_owner(&app_state, api_key.as_deref()).and_then(|owner| {
+        app_state
+            .sessions
+            .with_session(&session_id, owner.as_deref(), |session| session.add_turtle(graph, &payload.turtle))
+            .and_then(|result| result)
+    });
+    
+    match added {
+        Ok(added) => Json(serde_json::json!({
+            "success": true,
+            "session_id": session_id,
+            "graph": graph,
+            "triples_added": added
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+/// Apply a SPARQL `INSERT DATA`, `CLEAR` or `DROP` to the session's overlay graphs
+async fn api_session_update(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+    Json(payload): Json<SparqlUpdateRequest>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let added = session_owner(&app_state, api_key.as_deref()).and_then(|owner| {
+        app_state
+            .sessions
+            .with_session(&session_id, owner.as_deref(), |session| {
+                session.apply_update(&payload.update).map(|added| (added, session.summary(app_state.sessions.ttl())))
+            })
+            .and_then(|result| result)
+    });
+    
+    match added {
+        Ok((added, summary)) => Json(serde_json::json!({
+            "success": true,
+            "session_id": session_id,
+            "triples_added": added,
+            "overlay_graphs": summary.overlay_graphs
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+/// Run a SELECT query against the persisted store with the session overlay applied
+async fn api_session_query(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+    Json(payload): Json<crate::api::sparql::SparqlQuery>,
+) -> Response {
+    let query = match payload.effective_query() {
+        Ok(query) => app_state.extensions.read().with_prefixes(&query),
+        Err(e) => return e.into_response(),
+    };
+    
+    // Overlay sessions obey the same read ACLs as the main query endpoint
+    let api_key = api_key_from_headers(&headers);
+    let owner = match session_owner(&app_state, api_key.as_deref()) {
+        Ok(owner) => owner,
+        Err(e) => return graph_access_failure(api_key.as_deref(), e),
+    };
+    let outcome = with_session_view(&app_state, &session_id, owner.as_deref(), |view| {
+        let query = app_state
+            .access_control
+            .read()
+            .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?
+            .restrict_query(api_key.as_deref(), &query, &view.graph_names())?;
+        Ok(serde_json::from_str::<serde_json::Value>(&view.query_select(&query)?)?)
+    });
+    
+    match outcome {
+        Err(e @ EpcisKgError::AccessDenied(_)) => access_denied_response(api_key.as_deref(), e),
+        Ok(result) => Json(serde_json::json!({
+            "head": result["head"],
+            "results": result["results"],
+            "session_id": session_id,
+            "status": "success"
+        })).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Run inference over the session view; inferred triples are kept in the overlay
+async fn api_session_inference(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+    payload: Option<Json<SessionInferenceRequest>>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let payload = payload.map(|Json(p)| p).unwrap_or_default();
+    let scope = InferenceScope::from_config(&app_state.config.reasoning.scope)
+        .with_overrides(payload.tbox_graphs, payload.abox_graphs);
+    let owner = match session_owner(&app_state, api_key.as_deref()) {
+        Ok(owner) => owner,
+        Err(e) => return graph_access_failure(api_key.as_deref(), e),
+    };
+    
+    // Reason over a copy of the scoped graphs the caller may read, so the store is not held meanwhile
+    let scoped = with_session_view(&app_state, &session_id, owner.as_deref(), |view| {
+        let readable = app_state
+            .access_control
+            .read()
+            .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?
+            .read_filter(api_key.as_deref())?;
+        let mut graph_names = view.graph_names();
+        graph_names.retain(|graph| readable(graph.as_str()));
+        let (tbox_graphs, abox_graphs) = scope.partition(&graph_names);
+        Ok(view.scoped_view(&[tbox_graphs, abox_graphs].concat()))
+    });
+    let scoped = match scoped {
+        Ok(scoped) => scoped,
+        Err(e) => return graph_access_failure(api_key.as_deref(), e),
+    };
+    
+    let mut reasoner = OntologyReasoner::with_config(&app_state.config);
+    let outcome = reasoner
+        .load_scope(&scoped, &scope)
+        .and_then(|scope_summary| Ok((scope_summary, reasoner.perform_inference_with_materialization()?)));
+    let (scope_summary, result) = match outcome {
+        Ok(outcome) => outcome,
+        Err(e) => return e.into_response(),
+    };
+    
+    let stored = app_state.sessions.with_session(&session_id, owner.as_deref(), |session| {
+        reasoner
+            .get_materialized_triples()
+            .iter()
+            .map(|(graph, triples)| session.add_triples(graph, triples.iter().cloned()))
+            .sum::<usize>()
+    });
+    
+    match stored {
+        Ok(stored) => Json(serde_json::json!({
+            "success": true,
+            "session_id": session_id,
+            "scope": scope_summary,
+            "inference_result": result,
+            "overlay_inferences_added": stored
+        })).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+#[derive(Clone, serde::Deserialize)]
 struct InferenceRequest {
     pub strategy: Option<String>,
-    pub clear_existing: Option<bool>,
+    /// Graph patterns forming the TBox; defaults to `[reasoning.scope]`
+    pub tbox_graphs: Option<Vec<String>>,
+    /// Graph patterns forming the ABox; defaults to `[reasoning.scope]`
+    pub abox_graphs: Option<Vec<String>>,
 }
 
 async fn api_perform_inference(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
     Json(payload): Json<InferenceRequest>,
-) -> Result<Json<serde_json::Value>, Json<serde_json::Value>> {
-    Ok(Json(serde_json::json!({
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    match inference_read_filter(&app_state, api_key.as_deref()).and_then(|readable| run_inference(&app_state, &payload, readable, None)) {
+        Ok(result) => Json(result).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+/// Start inference in the background; poll `GET /inference/jobs/:job_id` for progress
+async fn api_start_inference_job(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<InferenceRequest>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    // The caller is authenticated now, so a refused key never gets a job id
+    let readable = match inference_read_filter(&app_state, api_key.as_deref()) {
+        Ok(readable) => readable,
+        Err(e) => return graph_access_failure(api_key.as_deref(), e),
+    };
+    let jobs = Arc::clone(&app_state.reasoning_jobs);
+    let job_id = jobs.start();
+    let progress = jobs.progress_callback(&job_id);
+    let id = job_id.clone();
+    // The job outlives the request, so it holds its own batch for query snapshots
+    let batch = app_state.snapshots.begin_write();
+    tokio::task::spawn_blocking(move || {
+        let outcome = run_inference(&app_state, &payload, readable, Some(progress)).map_err(|e| e.to_string());
+        drop(batch);
+        jobs.finish(&id, outcome);
+    });
+    
+    (StatusCode::ACCEPTED, Json(serde_json::json!({
+        "success": true,
+        "job_id": job_id,
+        "status": "running",
+        "status_url": format!("/api/v1/inference/jobs/{}", job_id)
+    }))).into_response()
+}
+
+/// Status of a background inference job: phase, percent done, triples so far and ETA
+async fn api_inference_job(
+    State(app_state): State<AppState>,
+    Path(job_id): Path<String>,
+) -> Response {
+    match app_state.reasoning_jobs.get(&job_id) {
+        Some(job) => Json(serde_json::json!({ "job": job })).into_response(),
+        None => EpcisKgError::NotFound { resource: "Inference job".to_string(), id: job_id }.into_response(),
+    }
+}
+
+/// Graphs the caller may reason over: those its API key may read
+fn inference_read_filter(app_state: &AppState, api_key: Option<&str>) -> Result<impl Fn(&str) -> bool + Send + 'static, EpcisKgError> {
+    app_state
+        .access_control
+        .read()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?
+        .read_filter(api_key)
+}
+
+/// Run inference over the configured (or requested) scope, limited to the
+/// `readable` graphs, and record the run
+fn run_inference(
+    app_state: &AppState,
+    payload: &InferenceRequest,
+    readable: impl Fn(&str) -> bool,
+    progress: Option<ProgressCallback>,
+) -> Result<serde_json::Value, EpcisKgError> {
+    let scope = InferenceScope::from_config(&app_state.config.reasoning.scope)
+        .with_overrides(payload.tbox_graphs.clone(), payload.abox_graphs.clone());
+    
+    let mut reasoner = OntologyReasoner::with_config(&app_state.config);
+    reasoner.set_progress_callback(progress);
+    reasoner.set_rule_usage(Arc::clone(&app_state.rule_usage));
+    if let Some(strategy) = payload.strategy.as_deref() {
+        reasoner.set_materialization_strategy(match strategy.to_lowercase().as_str() {
+            "full" => MaterializationStrategy::Full,
+            "ondemand" | "on-demand" => MaterializationStrategy::OnDemand,
+            "hybrid" => MaterializationStrategy::Hybrid,
+            _ => MaterializationStrategy::Incremental,
+        });
+    }
+    
+    let started_at = chrono::Utc::now();
+    let (scope_summary, ontology_versions) = {
+        let store_guard = app_state.store.lock().map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?;
+        let all_graphs = store_guard.graph_names();
+        let mut graph_names = all_graphs.clone();
+        graph_names.retain(|graph| readable(graph.as_str()));
+        // Graphs the caller may not read are left out; the whole store is only used when it may read all of it
+        let summary = match graph_names.len() == all_graphs.len() {
+            true => reasoner.load_scope(&store_guard, &scope)?,
+            false => reasoner.load_scope(&store_guard.scoped_view(&graph_names), &scope)?,
+        };
+        let versions = runs::ontology_versions(&store_guard, &summary.tbox_graphs);
+        (summary, versions)
+    };
+    
+    let inputs: Vec<LineageDataset> = scope_summary
+        .tbox_graphs
+        .iter()
+        .map(|graph| LineageDataset::graph(graph).with_version(ontology_versions.get(graph).cloned()))
+        .chain(scope_summary.abox_graphs.iter().map(String::as_str).map(LineageDataset::graph))
+        .collect();
+    let result = match reasoner.perform_inference_with_materialization() {
+        Ok(result) => result,
+        Err(e) => {
+            let mut failed = LineageRun::new("materialize", RunState::Fail);
+            failed.error = Some(e.to_string());
+            failed.inputs = inputs;
+            app_state.lineage.emit(&failed);
+            return Err(e);
+        }
+    };
+    
+    let run = InferenceRun {
+        ontology_versions,
+        tbox_triples: scope_summary.tbox_triples,
+        abox_triples: scope_summary.abox_triples,
+        ..InferenceRun::new("api", payload.strategy.as_deref().unwrap_or("incremental"), started_at, &result, &app_state.config.reasoning)
+    };
+    app_state
+        .store
+        .lock()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?
+        .record_inference_run(&run)?;
+    
+    let mut lineage = LineageRun::new("materialize", RunState::Complete);
+    lineage.run_id = run.id.clone();
+    lineage.inputs = inputs;
+    lineage.outputs = vec![
+        LineageDataset::graph(MATERIALIZED_GRAPHS[0]).with_row_count(result.materialized_triples),
+        LineageDataset::graph(MATERIALIZED_GRAPHS[1]).with_row_count(result.sparql_inferences),
+    ];
+    app_state.lineage.emit(&lineage);
+    
+    Ok(serde_json::json!({
         "success": true,
-        "message": "Inference endpoint (simplified)",
+        "run_id": run.id,
         "strategy": payload.strategy,
-        "clear_existing": payload.clear_existing,
-        "inferences_performed": 0,
-        "materialized_triples_count": 0
-    })))
+        "scope": scope_summary,
+        "inferences_performed": result.materialized_triples + result.sparql_inferences,
+        "materialized_triples_count": result.materialized_triples,
+        "inference_result": result
+    }))
+}
+
+/// Delete and recompute only the inferred triples about the graphs, EPCs or
+/// time range in the request, leaving the rest of the materialization alone
+async fn api_rematerialize(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(scope): Json<RematerializeScope>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let config = Arc::clone(&app_state.config);
+    match with_graph_access(&app_state, api_key.as_deref(), "urn:epcis:inferred", GraphAccess::Write, |store| {
+        rematerialize::rematerialize(store, &config, &scope, "api")
+    }) {
+        Ok(report) => {
+            if let Some(run_id) = &report.run_id {
+                let mut run = LineageRun::new("materialize", RunState::Complete);
+                run.run_id = run_id.clone();
+                run.outputs.push(LineageDataset::graph(MATERIALIZED_GRAPHS[0]).with_row_count(report.inserted));
+                app_state.lineage.emit(&run);
+            }
+            Json(serde_json::json!({
+                "success": true,
+                "scope": scope,
+                "report": report
+            })).into_response()
+        }
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+/// Role, shard assignments and the outcome of the last merge
+async fn api_cluster_status(State(app_state): State<AppState>) -> Json<serde_json::Value> {
+    Json(app_state.cluster.status())
+}
+
+/// Forward captured events to the workers owning their shards (coordinator only)
+async fn api_cluster_capture(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<IngestRequest>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    if let Err(e) = app_state.cluster.ensure_role(ClusterRole::Coordinator, "route events") {
+        return e.into_response();
+    }
+    let graphs: Vec<String> = payload.events.iter().map(|event| format!("urn:epcis:event:{}", event.event_id)).collect();
+    let allowed = app_state
+        .access_control
+        .read()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))
+        .and_then(|access_control| access_control.check_graph_write(api_key.as_deref(), &graphs));
+    if let Err(e) = allowed.and_then(|_| check_write_quota(&app_state, api_key.as_deref())) {
+        return graph_access_failure(api_key.as_deref(), e);
+    }
+    
+    let dispatched = app_state.cluster.dispatch(payload.events, payload.validate, payload.infer, payload.strict).await;
+    Json(serde_json::json!({
+        "success": dispatched.iter().all(|dispatch| dispatch.error.is_none()),
+        "workers": dispatched
+    })).into_response()
+}
+
+/// Pull every worker's graphs into this store now instead of at the next merge interval
+async fn api_cluster_merge(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    if let Err(e) = require_cluster_admin(&app_state, api_key.as_deref(), "merge worker graphs") {
+        return graph_access_failure(api_key.as_deref(), e);
+    }
+    if let Err(e) = app_state.cluster.ensure_role(ClusterRole::Coordinator, "merge worker graphs") {
+        return e.into_response();
+    }
+    let report = app_state.cluster.merge(&app_state.store).await;
+    Json(serde_json::json!({
+        "success": report.workers.iter().all(|merge| merge.error.is_none()),
+        "merge": report
+    })).into_response()
+}
+
+/// Store and materialize a batch routed by the coordinator (worker only)
+async fn api_cluster_ingest(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<IngestRequest>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    if let Err(e) = require_cluster_admin(&app_state, api_key.as_deref(), "ingest routed events") {
+        return graph_access_failure(api_key.as_deref(), e);
+    }
+    if let Err(e) = app_state.cluster.ensure_role(ClusterRole::Worker, "ingest routed events") {
+        return e.into_response();
+    }
+    let ingested = app_state
+        .store
+        .lock()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))
+        .and_then(|mut store| {
+            cluster::ingest(&mut store, &app_state.config, app_state.cluster.pipeline(), &payload.events, payload.validate, payload.infer, payload.strict)
+        });
+    match ingested {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// This worker's event, personal data and inferred graphs as N-Quads, for the coordinator's merge
+async fn api_cluster_export(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    if let Err(e) = require_cluster_admin(&app_state, api_key.as_deref(), "export shard graphs") {
+        return graph_access_failure(api_key.as_deref(), e);
+    }
+    if let Err(e) = app_state.cluster.ensure_role(ClusterRole::Worker, "export shard graphs") {
+        return e.into_response();
+    }
+    let exported = app_state
+        .store
+        .lock()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))
+        .and_then(|store| cluster::export_shard_graphs(&store));
+    match exported {
+        Ok(nquads) => ([(header::CONTENT_TYPE, "application/n-quads")], nquads).into_response(),
+        Err(e) => e.into_response(),
+    }
+}
+
+/// Instances talk to each other with admin keys
+fn require_cluster_admin(app_state: &AppState, api_key: Option<&str>, action: &str) -> Result<(), EpcisKgError> {
+    let access_control = app_state
+        .access_control
+        .read()
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to acquire access control lock: {}", e)))?;
+    require_admin(&access_control, api_key, action)
+}
+
+/// Recorded materialization runs, oldest first
+async fn api_inference_runs(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    match with_graph_access(&app_state, api_key.as_deref(), INFERENCE_RUNS_GRAPH, GraphAccess::Read, |store| Ok(store.inference_runs())) {
+        Ok(runs) => Json(serde_json::json!({
+            "runs": runs,
+            "total": runs.len()
+        })).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct CompareRunsParams {
+    baseline: String,
+    candidate: String,
+}
+
+/// Triple-count, timing, ontology and configuration differences between two runs
+async fn api_compare_inference_runs(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<CompareRunsParams>,
+) -> Response {
+    let api_key = api_key_from_headers(&headers);
+    let runs = match with_graph_access(&app_state, api_key.as_deref(), INFERENCE_RUNS_GRAPH, GraphAccess::Read, |store| Ok(store.inference_runs())) {
+        Ok(runs) => runs,
+        Err(e) => return graph_access_failure(api_key.as_deref(), e),
+    };
+    let find = |id: &str| {
+        runs.iter()
+            .find(|run| run.id == id)
+            .ok_or_else(|| EpcisKgError::NotFound { resource: "Inference run".to_string(), id: id.to_string() })
+    };
+    match (find(&params.baseline), find(&params.candidate)) {
+        (Ok(baseline), Ok(candidate)) => Json(serde_json::json!({
+            "comparison": baseline.compare(candidate),
+            "baseline": baseline,
+            "candidate": candidate
+        })).into_response(),
+        (Err(e), _) | (_, Err(e)) => e.into_response(),
+    }
+}
+
+/// How often each rule and axiom fired across this server's inference runs,
+/// with the `?top` most-fired axioms (default 20) and the axioms that never fired
+async fn api_rule_usage(
+    State(app_state): State<AppState>,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+) -> Json<serde_json::Value> {
+    let top = params.get("top").and_then(|top| top.parse::<usize>().ok()).unwrap_or(20);
+    Json(serde_json::json!({
+        "success": true,
+        "usage": app_state.rule_usage.report(top)
+    }))
 }
 
 async fn api_inference_stats(
+    State(app_state): State<AppState>,
 ) -> Json<serde_json::Value> {
     Json(serde_json::json!({
         "success": true,
@@ -425,7 +3490,8 @@ async fn api_inference_stats(
             "cache_hits": 0,
             "cache_misses": 0,
             "average_processing_time_ms": 0.0
-        }
+        },
+        "reasoner_pool": app_state.reasoner_pool.stats()
     }))
 }
 
@@ -437,7 +3503,7 @@ struct MaterializationRequest {
 
 async fn api_manage_materialized(
     Json(payload): Json<MaterializationRequest>,
-) -> Result<Json<serde_json::Value>, Json<serde_json::Value>> {
+) -> Result<Json<serde_json::Value>, EpcisKgError> {
     match payload.action.to_lowercase().as_str() {
         "clear" => {
             Ok(Json(serde_json::json!({
@@ -468,13 +3534,40 @@ async fn api_manage_materialized(
                 "action": "show"
             })))
         },
-        _ => {
-            Ok(Json(serde_json::json!({
-                "success": false,
-                "message": "Unknown action. Use 'clear' or 'show'",
-                "action": payload.action
-            })))
-        }
+        _ => Err(EpcisKgError::InvalidField {
+            field: "action".to_string(),
+            message: format!("unknown action '{}', use 'clear' or 'show'", payload.action),
+        }),
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct MaterializedParams {
+    graph: Option<String>,
+    predicate: Option<String>,
+    subject_prefix: Option<String>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+}
+
+/// Page through the triples in derived graphs; `limit` is capped at `sparql.max_results`
+async fn api_list_materialized(
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<MaterializedParams>,
+) -> Response {
+    let filter = MaterializedFilter {
+        offset: params.offset.unwrap_or(0),
+        limit: params.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(app_state.config.sparql.max_results),
+        graph: params.graph,
+        predicate: params.predicate,
+        subject_prefix: params.subject_prefix,
+    };
+    let graph = filter.graph.clone().unwrap_or_else(|| "urn:epcis:inferred".to_string());
+    let api_key = api_key_from_headers(&headers);
+    match with_graph_access(&app_state, api_key.as_deref(), &graph, GraphAccess::Read, |store| store.materialized_triples(&filter)) {
+        Ok(page) => Json(page).into_response(),
+        Err(e) => graph_access_failure(api_key.as_deref(), e),
     }
 }
 
@@ -509,10 +3602,18 @@ async fn api_config(
 }
 
 // Monitoring API Handlers
+
+/// Bring the store's size metrics up to date; in-memory stores only count on request
+fn refresh_store_size(app_state: &AppState) {
+    if let Ok(store) = app_state.store.lock() {
+        store.refresh_size_metrics();
+    }
+}
 async fn api_monitoring_metrics(
+    State(app_state): State<AppState>,
 ) -> Json<serde_json::Value> {
-    let monitor = SystemMonitor::new();
-    let metrics = monitor.get_metrics();
+    refresh_store_size(&app_state);
+    let metrics = app_state.monitor.get_metrics();
     
     Json(serde_json::json!({
         "success": true,
@@ -522,9 +3623,10 @@ async fn api_monitoring_metrics(
 }
 
 async fn api_monitoring_alerts(
+    State(app_state): State<AppState>,
     Query(params): Query<std::collections::HashMap<String, String>>,
 ) -> Json<serde_json::Value> {
-    let monitor = SystemMonitor::new();
+    let monitor = &app_state.monitor;
     let limit = params.get("limit")
         .and_then(|s| s.parse::<usize>().ok())
         .unwrap_or(10);
@@ -543,10 +3645,11 @@ async fn api_monitoring_alerts(
 }
 
 async fn api_monitoring_health(
+    State(app_state): State<AppState>,
 ) -> Json<serde_json::Value> {
-    let monitor = SystemMonitor::new();
-    let metrics = monitor.get_metrics();
-    let alerts = monitor.check_alerts();
+    refresh_store_size(&app_state);
+    let metrics = app_state.monitor.get_metrics();
+    let alerts = app_state.monitor.check_alerts();
     
     let health_status = if alerts.is_empty() {
         "healthy"
@@ -8,6 +8,7 @@ use axum::{
     Router,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 pub struct SparqlEndpoint {
@@ -18,6 +19,24 @@ pub struct SparqlEndpoint {
 pub struct SparqlQuery {
     pub query: String,
     pub format: Option<String>,
+    /// Parameter bindings injected into the query as VALUES blocks,
+    /// e.g. `{"epc": ["urn:epc:id:sgtin:0614141.107346.2017", ...]}`
+    pub bindings: Option<HashMap<String, Vec<String>>>,
+    /// Also query archived snapshots, merged with the live data
+    #[serde(default)]
+    pub include_archives: bool,
+    /// Snapshots to include with `include_archives` (all when absent)
+    pub archives: Option<Vec<String>>,
+}
+
+impl SparqlQuery {
+    /// Return the query text with any parameter bindings applied
+    pub fn effective_query(&self) -> Result<String, EpcisKgError> {
+        match &self.bindings {
+            Some(bindings) if !bindings.is_empty() => inject_values_bindings(&self.query, bindings),
+            _ => Ok(self.query.clone()),
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -91,10 +110,14 @@ pub async fn sparql_get(
     Query(params): Query<SparqlQuery>,
 ) -> Result<Json<SparqlResponse>, ErrorResponse> {
     let start_time = std::time::Instant::now();
+    let query = params.effective_query().map_err(|e| ErrorResponse {
+        error: "Invalid query bindings".to_string(),
+        message: e.to_string(),
+    })?;
     
-    match endpoint.execute_query(&params.query).await {
+    match endpoint.execute_query(&query).await {
         Ok(results) => {
-            let query_type = determine_query_type(&params.query);
+            let query_type = determine_query_type(&query);
             let execution_time = start_time.elapsed().as_millis() as u64;
             
             Ok(Json(SparqlResponse {
@@ -123,9 +146,14 @@ pub async fn execute_sparql(
     State(endpoint): State<SparqlEndpoint>,
     Json(payload): Json<SparqlQuery>,
 ) -> Result<Response, ErrorResponse> {
-    match endpoint.execute_query(&payload.query).await {
+    let query = payload.effective_query().map_err(|e| ErrorResponse {
+        error: "Invalid query bindings".to_string(),
+        message: e.to_string(),
+    })?;
+
+    match endpoint.execute_query(&query).await {
         Ok(results) => {
-            let query_type = determine_query_type(&payload.query);
+            let query_type = determine_query_type(&query);
             
             // Return different content types based on format parameter
             match payload.format.as_deref() {
@@ -213,6 +241,63 @@ pub fn determine_query_type(query: &str) -> String {
     }
 }
 
+/// Build a VALUES block binding `variable` to each of the given values.
+///
+/// Values that parse as absolute IRIs (EPC URNs, GLNs, Digital Link URIs) are
+/// emitted as IRIs; anything else is emitted as an escaped string literal.
+pub fn build_values_clause(variable: &str, values: &[String]) -> Result<String, EpcisKgError> {
+    let name = variable.trim_start_matches(['?', '$']);
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(EpcisKgError::Query(format!("Invalid binding variable name: {}", variable)));
+    }
+
+    let terms: Vec<String> = values.iter().map(|value| format_values_term(value)).collect();
+    Ok(format!("VALUES ?{} {{ {} }}", name, terms.join(" ")))
+}
+
+/// Inject parameter bindings into a query as VALUES blocks at the start of its WHERE group.
+///
+/// This lets REST callers bind large lists (e.g. the EPCs affected by a recall)
+/// without building huge FILTER expressions or issuing one query per value.
+pub fn inject_values_bindings(query: &str, bindings: &HashMap<String, Vec<String>>) -> Result<String, EpcisKgError> {
+    let query_upper = query.to_ascii_uppercase();
+    let group_search_start = query_upper.find("WHERE").unwrap_or(0);
+    let group_start = query[group_search_start..]
+        .find('{')
+        .map(|pos| group_search_start + pos)
+        .ok_or_else(|| EpcisKgError::Query("Query has no group graph pattern to bind values into".to_string()))?;
+
+    // Sort by variable name so the generated query is deterministic
+    let mut variables: Vec<&String> = bindings.keys().collect();
+    variables.sort();
+
+    let mut blocks = Vec::new();
+    for variable in variables {
+        blocks.push(build_values_clause(variable, &bindings[variable])?);
+    }
+
+    let mut result = String::with_capacity(query.len() + blocks.iter().map(|b| b.len() + 1).sum::<usize>());
+    result.push_str(&query[..=group_start]);
+    for block in &blocks {
+        result.push(' ');
+        result.push_str(block);
+    }
+    result.push_str(&query[group_start + 1..]);
+    Ok(result)
+}
+
+fn format_values_term(value: &str) -> String {
+    let value = value.trim();
+    if let Some(iri) = value.strip_prefix('<').and_then(|v| v.strip_suffix('>')) {
+        if oxrdf::NamedNode::new(iri).is_ok() {
+            return format!("<{}>", iri);
+        }
+    } else if value.contains(':') && oxrdf::NamedNode::new(value).is_ok() {
+        return format!("<{}>", value);
+    }
+    oxrdf::Literal::new_simple_literal(value).to_string()
+}
+
 // Error response implementation
 impl IntoResponse for ErrorResponse {
     fn into_response(self) -> Response {
@@ -221,4 +306,40 @@ impl IntoResponse for ErrorResponse {
         
         (status, body).into_response()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_values_clause() {
+        let values = vec![
+            "urn:epc:id:sgtin:0614141.107346.2017".to_string(),
+            "lot \"A\"".to_string(),
+        ];
+        let clause = build_values_clause("?epc", &values).unwrap();
+        assert_eq!(
+            clause,
+            "VALUES ?epc { <urn:epc:id:sgtin:0614141.107346.2017> \"lot \\\"A\\\"\" }"
+        );
+    }
+
+    #[test]
+    fn test_build_values_clause_rejects_invalid_variable() {
+        let values = vec!["urn:epc:id:sgtin:0614141.107346.2017".to_string()];
+        assert!(build_values_clause("epc }", &values).is_err());
+    }
+
+    #[test]
+    fn test_inject_values_bindings() {
+        let mut bindings = HashMap::new();
+        bindings.insert("epc".to_string(), vec!["urn:epc:id:sgtin:0614141.107346.2017".to_string()]);
+
+        let query = "SELECT ?event WHERE { ?event <urn:epcglobal:epcis:epcList> ?epc }";
+        let injected = inject_values_bindings(query, &bindings).unwrap();
+        assert_eq!(
+            injected,
+            "SELECT ?event WHERE { VALUES ?epc { <urn:epc:id:sgtin:0614141.107346.2017> } ?event <urn:epcglobal:epcis:epcList> ?epc }"
+        );
+    }
+}
@@ -0,0 +1,517 @@
+//! Read-only SQL over tabular views of the event history.
+//!
+//! A deliberately small dialect for BI tools:
+//! `SELECT <* | columns | COUNT(*)> FROM <view> [WHERE <condition> [AND ...]]
+//! [ORDER BY <column> [ASC | DESC]] [LIMIT <n>]`, where a condition is a
+//! comparison with a literal, `LIKE`, or `IS [NOT] NULL`. Views are built
+//! from the same event tables as the Parquet export.
+
+use crate::storage::columnar::EventTables;
+use crate::EpcisKgError;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Views and their columns
+pub const VIEWS: &[(&str, &[&str])] = &[
+    ("events", &["event_id", "event_type", "action", "event_time", "biz_step", "biz_location", "parent_id"]),
+    ("epcs", &["event_id", "epc", "role", "event_time"]),
+    ("locations", &["location", "event_count", "epc_count", "first_seen", "last_seen"]),
+    ("sensor_readings", &["event_id", "epc", "time", "celsius"]),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum SqlValue {
+    Null,
+    Number(f64),
+    Text(String),
+}
+
+impl SqlValue {
+    /// Text form used on the Postgres wire; `None` for NULL
+    pub fn to_text(&self) -> Option<String> {
+        match self {
+            SqlValue::Null => None,
+            SqlValue::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => Some(format!("{}", *n as i64)),
+            SqlValue::Number(n) => Some(n.to_string()),
+            SqlValue::Text(text) => Some(text.clone()),
+        }
+    }
+
+    fn compare(&self, other: &SqlValue) -> Option<Ordering> {
+        match (self, other) {
+            (SqlValue::Number(a), SqlValue::Number(b)) => a.partial_cmp(b),
+            (SqlValue::Text(a), SqlValue::Text(b)) => Some(a.cmp(b)),
+            (SqlValue::Text(a), SqlValue::Number(b)) => a.parse::<f64>().ok()?.partial_cmp(b),
+            (SqlValue::Number(a), SqlValue::Text(b)) => a.partial_cmp(&b.parse::<f64>().ok()?),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SqlResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<SqlValue>>,
+}
+
+impl SqlResult {
+    /// Whether a column holds numbers (every non-null value is numeric)
+    pub fn is_numeric(&self, index: usize) -> bool {
+        let mut values = self.rows.iter().map(|row| &row[index]).filter(|value| **value != SqlValue::Null).peekable();
+        values.peek().is_some() && values.all(|value| matches!(value, SqlValue::Number(_)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Projection {
+    All,
+    Columns(Vec<String>),
+    Count,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Condition {
+    Compare { column: String, op: String, value: SqlValue },
+    Like { column: String, pattern: String, negated: bool },
+    IsNull { column: String, negated: bool },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct SelectQuery {
+    projection: Projection,
+    view: String,
+    conditions: Vec<Condition>,
+    order_by: Option<(String, bool)>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Text(String),
+    Number(f64),
+    Symbol(String),
+}
+
+/// Parse and run a query against the event tables
+pub fn execute(sql: &str, tables: &EventTables) -> Result<SqlResult, EpcisKgError> {
+    let query = parse(sql)?;
+    let (columns, rows) = view_rows(tables, &query.view)?;
+    let index = |column: &str| {
+        columns
+            .iter()
+            .position(|c| *c == column)
+            .ok_or_else(|| EpcisKgError::Validation(format!("Unknown column {} in view {}", column, query.view)))
+    };
+
+    let mut filters = Vec::new();
+    for condition in &query.conditions {
+        let column = match condition {
+            Condition::Compare { column, .. } | Condition::Like { column, .. } | Condition::IsNull { column, .. } => column,
+        };
+        filters.push((index(column)?, condition));
+    }
+    let mut rows: Vec<Vec<SqlValue>> = rows
+        .into_iter()
+        .filter(|row| filters.iter().all(|(i, condition)| matches(&row[*i], condition)))
+        .collect();
+
+    if let Some((column, descending)) = &query.order_by {
+        let i = index(column)?;
+        rows.sort_by(|a, b| {
+            let ordering = match (&a[i], &b[i]) {
+                (SqlValue::Null, SqlValue::Null) => Ordering::Equal,
+                (SqlValue::Null, _) => Ordering::Greater,
+                (_, SqlValue::Null) => Ordering::Less,
+                (x, y) => x.compare(y).unwrap_or(Ordering::Equal),
+            };
+            if *descending { ordering.reverse() } else { ordering }
+        });
+    }
+    if let Some(limit) = query.limit {
+        rows.truncate(limit);
+    }
+
+    match &query.projection {
+        Projection::Count => Ok(SqlResult {
+            columns: vec!["count".to_string()],
+            rows: vec![vec![SqlValue::Number(rows.len() as f64)]],
+        }),
+        Projection::All => Ok(SqlResult { columns: columns.iter().map(|c| c.to_string()).collect(), rows }),
+        Projection::Columns(selected) => {
+            let indexes = selected.iter().map(|column| index(column)).collect::<Result<Vec<_>, _>>()?;
+            Ok(SqlResult {
+                columns: selected.clone(),
+                rows: rows.into_iter().map(|row| indexes.iter().map(|i| row[*i].clone()).collect()).collect(),
+            })
+        }
+    }
+}
+
+fn matches(value: &SqlValue, condition: &Condition) -> bool {
+    match condition {
+        Condition::IsNull { negated, .. } => (*value == SqlValue::Null) != *negated,
+        Condition::Like { pattern, negated, .. } => match value.to_text() {
+            Some(text) => like(&text, pattern) != *negated,
+            None => false,
+        },
+        Condition::Compare { op, value: expected, .. } => {
+            let Some(ordering) = value.compare(expected) else { return false };
+            match op.as_str() {
+                "=" => ordering == Ordering::Equal,
+                "!=" | "<>" => ordering != Ordering::Equal,
+                "<" => ordering == Ordering::Less,
+                "<=" => ordering != Ordering::Greater,
+                ">" => ordering == Ordering::Greater,
+                _ => ordering != Ordering::Less,
+            }
+        }
+    }
+}
+
+/// SQL `LIKE` with `%` (any run) and `_` (one character)
+fn like(text: &str, pattern: &str) -> bool {
+    let text: Vec<char> = text.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    // matched[j]: the text prefix consumed so far matches pattern[..j]
+    let mut matched = vec![false; pattern.len() + 1];
+    matched[0] = true;
+    for j in 1..=pattern.len() {
+        matched[j] = matched[j - 1] && pattern[j - 1] == '%';
+    }
+    for c in text {
+        let mut next = vec![false; pattern.len() + 1];
+        for j in 1..=pattern.len() {
+            next[j] = match pattern[j - 1] {
+                '%' => next[j - 1] || matched[j],
+                '_' => matched[j - 1],
+                p => matched[j - 1] && p == c,
+            };
+        }
+        matched = next;
+    }
+    matched[pattern.len()]
+}
+
+fn view_rows(tables: &EventTables, view: &str) -> Result<(&'static [&'static str], Vec<Vec<SqlValue>>), EpcisKgError> {
+    let (_, columns) = VIEWS
+        .iter()
+        .find(|(name, _)| *name == view)
+        .ok_or_else(|| EpcisKgError::Validation(format!("Unknown view: {} (expected events, epcs, locations or sensor_readings)", view)))?;
+    let text = |value: &str| SqlValue::Text(value.to_string());
+    let optional = |value: &Option<String>| value.as_deref().map_or(SqlValue::Null, text);
+    let time = |value: &chrono::DateTime<chrono::Utc>| SqlValue::Text(value.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+
+    let rows = match view {
+        "events" => tables
+            .events
+            .iter()
+            .map(|row| {
+                vec![
+                    text(&row.event_id),
+                    text(&row.event_type),
+                    text(&row.action),
+                    time(&row.event_time),
+                    optional(&row.biz_step),
+                    optional(&row.biz_location),
+                    optional(&row.parent_id),
+                ]
+            })
+            .collect(),
+        "epcs" => {
+            let event_times: HashMap<&str, _> = tables.events.iter().map(|row| (row.event_id.as_str(), row.event_time)).collect();
+            tables
+                .epc_list
+                .iter()
+                .map(|row| {
+                    let event_time = event_times.get(row.event_id.as_str()).map_or(SqlValue::Null, time);
+                    vec![text(&row.event_id), text(&row.epc), text(row.role), event_time]
+                })
+                .collect()
+        }
+        "locations" => {
+            let mut epcs_by_event: HashMap<&str, Vec<&str>> = HashMap::new();
+            for row in &tables.epc_list {
+                epcs_by_event.entry(row.event_id.as_str()).or_default().push(row.epc.as_str());
+            }
+            let mut locations: BTreeMap<&str, (usize, BTreeSet<&str>, chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>)> = BTreeMap::new();
+            for event in &tables.events {
+                let Some(location) = event.biz_location.as_deref() else { continue };
+                let entry = locations.entry(location).or_insert((0, BTreeSet::new(), event.event_time, event.event_time));
+                entry.0 += 1;
+                entry.1.extend(epcs_by_event.get(event.event_id.as_str()).into_iter().flatten().copied());
+                entry.2 = entry.2.min(event.event_time);
+                entry.3 = entry.3.max(event.event_time);
+            }
+            locations
+                .into_iter()
+                .map(|(location, (events, epcs, first, last))| {
+                    vec![text(location), SqlValue::Number(events as f64), SqlValue::Number(epcs.len() as f64), time(&first), time(&last)]
+                })
+                .collect()
+        }
+        _ => tables
+            .sensor_readings
+            .iter()
+            .map(|row| vec![text(&row.event_id), text(&row.epc), time(&row.time), SqlValue::Number(row.celsius)])
+            .collect(),
+    };
+    Ok((*columns, rows))
+}
+
+fn tokenize(sql: &str) -> Result<Vec<Token>, EpcisKgError> {
+    let mut tokens = Vec::new();
+    let mut chars = sql.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '\'' {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some('\'') if chars.peek() == Some(&'\'') => {
+                        chars.next();
+                        value.push('\'');
+                    }
+                    Some('\'') => break,
+                    Some(c) => value.push(c),
+                    None => return Err(EpcisKgError::Validation("Unterminated string literal".to_string())),
+                }
+            }
+            tokens.push(Token::Text(value));
+        } else if c.is_ascii_digit() || (c == '-' && tokens.last().map_or(false, |t| matches!(t, Token::Symbol(_)))) {
+            let mut number = String::new();
+            number.push(c);
+            chars.next();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || **c == '.') {
+                number.push(c);
+                chars.next();
+            }
+            let value = number.parse().map_err(|_| EpcisKgError::Validation(format!("Invalid number: {}", number)))?;
+            tokens.push(Token::Number(value));
+        } else if c.is_alphanumeric() || c == '_' || c == '"' {
+            let quoted = c == '"';
+            if quoted {
+                chars.next();
+            }
+            let mut word = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric() || **c == '_' || **c == '.') {
+                word.push(c);
+                chars.next();
+            }
+            if quoted && chars.next() != Some('"') {
+                return Err(EpcisKgError::Validation("Unterminated quoted identifier".to_string()));
+            }
+            // Qualified names (`public.events`) resolve to their last part
+            let word = word.rsplit('.').next().unwrap_or_default().to_lowercase();
+            tokens.push(Token::Word(word));
+        } else {
+            chars.next();
+            let two: String = [c, chars.peek().copied().unwrap_or(' ')].iter().collect();
+            if ["<=", ">=", "<>", "!="].contains(&two.as_str()) {
+                chars.next();
+                tokens.push(Token::Symbol(two));
+            } else if "=<>,*();".contains(c) {
+                tokens.push(Token::Symbol(c.to_string()));
+            } else {
+                return Err(EpcisKgError::Validation(format!("Unexpected character: {}", c)));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        if self.peek() == Some(&Token::Word(keyword.to_string())) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn symbol(&mut self, symbol: &str) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol.to_string())) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), EpcisKgError> {
+        if self.keyword(keyword) {
+            Ok(())
+        } else {
+            Err(EpcisKgError::Validation(format!("Expected {}", keyword.to_uppercase())))
+        }
+    }
+
+    fn identifier(&mut self) -> Result<String, EpcisKgError> {
+        match self.next() {
+            Some(Token::Word(word)) => Ok(word),
+            other => Err(EpcisKgError::Validation(format!("Expected a name, found {:?}", other))),
+        }
+    }
+
+    fn literal(&mut self) -> Result<SqlValue, EpcisKgError> {
+        match self.next() {
+            Some(Token::Text(text)) => Ok(SqlValue::Text(text)),
+            Some(Token::Number(number)) => Ok(SqlValue::Number(number)),
+            Some(Token::Word(word)) if word == "null" => Ok(SqlValue::Null),
+            other => Err(EpcisKgError::Validation(format!("Expected a literal, found {:?}", other))),
+        }
+    }
+
+    fn condition(&mut self) -> Result<Condition, EpcisKgError> {
+        let column = self.identifier()?;
+        if self.keyword("is") {
+            let negated = self.keyword("not");
+            self.expect_keyword("null")?;
+            return Ok(Condition::IsNull { column, negated });
+        }
+        let negated = self.keyword("not");
+        if self.keyword("like") {
+            let SqlValue::Text(pattern) = self.literal()? else {
+                return Err(EpcisKgError::Validation("LIKE needs a string pattern".to_string()));
+            };
+            return Ok(Condition::Like { column, pattern, negated });
+        }
+        if negated {
+            return Err(EpcisKgError::Validation("Expected LIKE after NOT".to_string()));
+        }
+        match self.next() {
+            Some(Token::Symbol(op)) if ["=", "!=", "<>", "<", "<=", ">", ">="].contains(&op.as_str()) => {
+                Ok(Condition::Compare { column, op, value: self.literal()? })
+            }
+            other => Err(EpcisKgError::Validation(format!("Expected a comparison operator, found {:?}", other))),
+        }
+    }
+}
+
+fn parse(sql: &str) -> Result<SelectQuery, EpcisKgError> {
+    let mut parser = Parser { tokens: tokenize(sql)?, position: 0 };
+    if !parser.keyword("select") {
+        return Err(EpcisKgError::Validation("Only SELECT statements are supported".to_string()));
+    }
+
+    let projection = if parser.symbol("*") {
+        Projection::All
+    } else if parser.keyword("count") {
+        if !(parser.symbol("(") && parser.symbol("*") && parser.symbol(")")) {
+            return Err(EpcisKgError::Validation("Only COUNT(*) is supported".to_string()));
+        }
+        Projection::Count
+    } else {
+        let mut columns = vec![parser.identifier()?];
+        while parser.symbol(",") {
+            columns.push(parser.identifier()?);
+        }
+        Projection::Columns(columns)
+    };
+
+    parser.expect_keyword("from")?;
+    let view = parser.identifier()?;
+
+    let mut conditions = Vec::new();
+    if parser.keyword("where") {
+        conditions.push(parser.condition()?);
+        while parser.keyword("and") {
+            conditions.push(parser.condition()?);
+        }
+    }
+
+    let mut order_by = None;
+    if parser.keyword("order") {
+        parser.expect_keyword("by")?;
+        let column = parser.identifier()?;
+        let descending = parser.keyword("desc");
+        if !descending {
+            parser.keyword("asc");
+        }
+        order_by = Some((column, descending));
+    }
+
+    let mut limit = None;
+    if parser.keyword("limit") {
+        match parser.next() {
+            Some(Token::Number(n)) if n >= 0.0 && n.fract() == 0.0 => limit = Some(n as usize),
+            other => return Err(EpcisKgError::Validation(format!("Expected a row count after LIMIT, found {:?}", other))),
+        }
+    }
+
+    parser.symbol(";");
+    if let Some(token) = parser.peek() {
+        return Err(EpcisKgError::Validation(format!("Unsupported SQL near {:?}", token)));
+    }
+
+    Ok(SelectQuery { projection, view, conditions, order_by, limit })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::columnar::{EpcListRow, EventRow};
+
+    fn tables() -> EventTables {
+        let event = |id: &str, hour: u32, location: &str| EventRow {
+            event_id: id.to_string(),
+            event_type: "ObjectEvent".to_string(),
+            action: "OBSERVE".to_string(),
+            event_time: format!("2024-03-01T{:02}:00:00Z", hour).parse().unwrap(),
+            biz_step: Some("urn:epcglobal:cbv:bizstep:shipping".to_string()),
+            biz_location: Some(location.to_string()),
+            parent_id: None,
+        };
+        let epc = |id: &str, epc: &str| EpcListRow { event_id: id.to_string(), epc: epc.to_string(), role: "epc" };
+        EventTables {
+            events: vec![
+                event("e1", 8, "urn:epc:id:sgln:0614141.00001.0"),
+                event("e2", 9, "urn:epc:id:sgln:0614141.00001.0"),
+                event("e3", 10, "urn:epc:id:sgln:0614141.00002.0"),
+            ],
+            epc_list: vec![epc("e1", "urn:epc:id:sgtin:0614141.107346.1"), epc("e2", "urn:epc:id:sgtin:0614141.107346.1")],
+            sensor_readings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_select_filter_order_limit() {
+        let result = execute(
+            "SELECT event_id, event_time FROM events WHERE biz_location LIKE '%00001%' AND event_time >= '2024-03-01T08:30:00Z' ORDER BY event_time DESC LIMIT 5;",
+            &tables(),
+        )
+        .unwrap();
+        assert_eq!(result.columns, vec!["event_id", "event_time"]);
+        assert_eq!(result.rows, vec![vec![SqlValue::Text("e2".to_string()), SqlValue::Text("2024-03-01T09:00:00Z".to_string())]]);
+
+        let count = execute("select count(*) from public.epcs where epc = 'urn:epc:id:sgtin:0614141.107346.1'", &tables()).unwrap();
+        assert_eq!(count.rows[0][0].to_text().as_deref(), Some("2"));
+
+        let locations = execute("SELECT location, epc_count FROM locations WHERE event_count > 1", &tables()).unwrap();
+        assert_eq!(locations.rows.len(), 1);
+        assert!(locations.is_numeric(1));
+
+        assert!(execute("DELETE FROM events", &tables()).is_err());
+        assert!(execute("SELECT nope FROM events", &tables()).is_err());
+    }
+}
@@ -0,0 +1,91 @@
+use crate::EpcisKgError;
+use axum::body::Bytes;
+use futures::{Stream, StreamExt};
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Media types of RDF bodies accepted as ontology uploads (N-Triples is a subset of Turtle)
+pub const RDF_UPLOAD_TYPES: [&str; 3] = ["text/turtle", "application/x-turtle", "application/n-triples"];
+
+/// An uploaded file written to the spool directory; removed when dropped
+#[derive(Debug)]
+pub struct SpooledUpload {
+    path: PathBuf,
+    /// Name the client gave the file, or a placeholder for raw bodies
+    pub file_name: String,
+    pub bytes: u64,
+    /// SHA-256 of the content, computed as it arrives
+    pub sha256: [u8; 32],
+}
+
+impl SpooledUpload {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for SpooledUpload {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Write an upload to a temporary file in `directory` as it arrives, so large
+/// files never sit in memory; fails with `LimitExceeded` past `max_bytes`
+pub async fn spool<S, E>(stream: S, directory: &Path, file_name: &str, max_bytes: u64) -> Result<SpooledUpload, EpcisKgError>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+    E: std::fmt::Display,
+{
+    std::fs::create_dir_all(directory)?;
+    let mut upload = SpooledUpload {
+        path: directory.join(format!("upload-{}.part", uuid::Uuid::new_v4())),
+        file_name: file_name.to_string(),
+        bytes: 0,
+        sha256: [0; 32],
+    };
+    let mut hasher = Sha256::new();
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&upload.path)?);
+
+    let mut stream = std::pin::pin!(stream);
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| EpcisKgError::Validation(format!("Failed to read upload: {}", e)))?;
+        upload.bytes += chunk.len() as u64;
+        if upload.bytes > max_bytes {
+            return Err(EpcisKgError::LimitExceeded(format!("Upload exceeds the limit of {} bytes", max_bytes)));
+        }
+        hasher.update(&chunk);
+        file.write_all(&chunk)?;
+    }
+    file.flush()?;
+    upload.sha256 = hasher.finalize().into();
+    Ok(upload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunks(parts: &[&'static str]) -> impl Stream<Item = Result<Bytes, std::io::Error>> {
+        futures::stream::iter(parts.iter().map(|part| Ok(Bytes::from_static(part.as_bytes()))).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_spooled_uploads_are_limited_and_cleaned_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let turtle = ["<urn:a> <urn:b> ", "<urn:c> .\n"];
+
+        let upload = futures::executor::block_on(spool(chunks(&turtle), dir.path(), "cbv.ttl", 1024)).unwrap();
+        assert_eq!(upload.bytes, 26);
+        assert_eq!(upload.sha256, <[u8; 32]>::from(Sha256::digest(b"<urn:a> <urn:b> <urn:c> .\n")));
+        assert_eq!(std::fs::read_to_string(upload.path()).unwrap(), "<urn:a> <urn:b> <urn:c> .\n");
+        let path = upload.path().to_path_buf();
+        drop(upload);
+        assert!(!path.exists());
+
+        let too_large = futures::executor::block_on(spool(chunks(&turtle), dir.path(), "cbv.ttl", 20));
+        assert!(matches!(too_large, Err(EpcisKgError::LimitExceeded(_))));
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,325 @@
+//! Typed async client for the REST API.
+//!
+//! Enabled with the `client` feature. Request and response types are the
+//! server's own models, so a client built from the same revision always
+//! matches the server it talks to.
+
+use crate::api::access_control::API_KEY_HEADER;
+use crate::models::epcis::EpcisEvent;
+use crate::models::events::{ErrorDeclaration, ErrorDeclarationResult, ProcessingResult};
+use crate::models::parties::{OwnershipRecord, Party};
+use crate::monitoring::metrics::{SystemAlert, SystemMetrics};
+use crate::storage::counterfeit::{SuspicionReport, Verification};
+use crate::storage::inventory::InventorySnapshot;
+use crate::storage::recall::RecallRequest;
+use crate::EpcisKgError;
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Client for one server, e.g. `EpcisClient::new("http://localhost:8080")`
+#[derive(Debug, Clone)]
+pub struct EpcisClient {
+    base_url: String,
+    api_key: Option<String>,
+    http: reqwest::Client,
+}
+
+/// Outcome of `POST /events`
+#[derive(Debug, Clone, Deserialize)]
+pub struct CaptureResponse {
+    pub success: bool,
+    pub events_processed: usize,
+    pub total_triples_generated: usize,
+    pub total_inferences_made: usize,
+    pub results: Vec<ProcessingResult>,
+}
+
+/// Outcome of `POST /events/corrections`
+#[derive(Debug, Clone, Deserialize)]
+pub struct CorrectionResponse {
+    pub success: bool,
+    pub events_declared: usize,
+    pub events_failed: usize,
+    pub results: Vec<serde_json::Value>,
+}
+
+/// SPARQL JSON results of a SELECT query
+#[derive(Debug, Clone, Deserialize)]
+pub struct SparqlResults {
+    pub head: serde_json::Value,
+    pub results: serde_json::Value,
+    #[serde(default)]
+    pub execution_time_ms: u64,
+}
+
+/// One query of a batch request
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BatchQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub query: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bindings: Option<HashMap<String, Vec<String>>>,
+}
+
+/// Outcome of `POST /sparql/batch`; each result carries its own `status`
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchResponse {
+    pub results: Vec<serde_json::Value>,
+    pub total: usize,
+    pub failed: usize,
+    pub execution_time_ms: u64,
+}
+
+/// `GET /monitoring/health`
+#[derive(Debug, Clone, Deserialize)]
+pub struct HealthStatus {
+    pub status: String,
+    pub uptime_seconds: u64,
+    pub active_alerts_count: usize,
+    pub alerts: Vec<SystemAlert>,
+}
+
+/// `GET /monitoring/alerts`
+#[derive(Debug, Clone, Deserialize)]
+pub struct Alerts {
+    pub alerts: Vec<SystemAlert>,
+    pub active_alerts: Vec<SystemAlert>,
+}
+
+impl EpcisClient {
+    /// `base_url` is the server root; the `/api/v1` prefix is added per request
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key: None,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Send this key in the `x-api-key` header of every request
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    /// Use a preconfigured HTTP client (timeouts, proxies, TLS roots)
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    // Capture
+
+    pub async fn capture_events(&self, events: &[EpcisEvent], validate: bool, infer: bool) -> Result<CaptureResponse, EpcisKgError> {
+        let body = serde_json::json!({ "events": events, "validate": validate, "infer": infer });
+        self.send(Method::POST, "/events", Some(&body)).await
+    }
+
+    /// Declare events in error; per-declaration failures are reported in the response
+    pub async fn declare_in_error(&self, declarations: &[ErrorDeclaration]) -> Result<CorrectionResponse, EpcisKgError> {
+        let body = serde_json::json!({ "declarations": declarations });
+        self.send(Method::POST, "/events/corrections", Some(&body)).await
+    }
+
+    pub async fn declare_event_in_error(&self, event_id: &str, reason: Option<&str>) -> Result<ErrorDeclarationResult, EpcisKgError> {
+        let mut path = format!("/events/{}", segment(event_id));
+        if let Some(reason) = reason {
+            path.push_str(&format!("?reason={}", urlencoding::encode(reason)));
+        }
+        let response: serde_json::Value = self.send(Method::DELETE, &path, None::<&()>).await?;
+        Ok(serde_json::from_value(response["error_declaration"].clone())?)
+    }
+
+    // Query
+
+    pub async fn sparql(&self, query: &str) -> Result<SparqlResults, EpcisKgError> {
+        self.sparql_with_bindings(query, HashMap::new()).await
+    }
+
+    /// Run a SELECT query with `VALUES` bindings, e.g. `{"epc": [...]}`
+    pub async fn sparql_with_bindings(
+        &self,
+        query: &str,
+        bindings: HashMap<String, Vec<String>>,
+    ) -> Result<SparqlResults, EpcisKgError> {
+        let body = serde_json::json!({
+            "query": query,
+            "bindings": (!bindings.is_empty()).then_some(bindings),
+        });
+        self.send(Method::POST, "/sparql/query", Some(&body)).await
+    }
+
+    pub async fn sparql_batch(&self, queries: &[BatchQuery], parallel: bool) -> Result<BatchResponse, EpcisKgError> {
+        let body = serde_json::json!({ "queries": queries, "parallel": parallel });
+        self.send(Method::POST, "/sparql/batch", Some(&body)).await
+    }
+
+    pub async fn sparql_update(&self, update: &str) -> Result<(), EpcisKgError> {
+        let body = serde_json::json!({ "update": update });
+        self.send::<serde_json::Value, _>(Method::POST, "/sparql/update", Some(&body)).await?;
+        Ok(())
+    }
+
+    /// Stored events as framed JSON-LD
+    pub async fn list_events(&self) -> Result<serde_json::Value, EpcisKgError> {
+        self.send(Method::GET, "/events", None::<&()>).await
+    }
+
+    // Trace and master data
+
+    /// Events mentioning an EPC as a framed JSON-LD document
+    pub async fn trace(&self, epc: &str) -> Result<serde_json::Value, EpcisKgError> {
+        self.send(Method::GET, &format!("/trace/{}", segment(epc)), None::<&()>).await
+    }
+
+    pub async fn epc_owner(&self, epc: &str) -> Result<Option<OwnershipRecord>, EpcisKgError> {
+        self.find(&format!("/epcs/{}/owner", segment(epc)), "ownership").await
+    }
+
+    pub async fn parties(&self) -> Result<Vec<Party>, EpcisKgError> {
+        let response: serde_json::Value = self.send(Method::GET, "/parties", None::<&()>).await?;
+        Ok(serde_json::from_value(response["parties"].clone())?)
+    }
+
+    pub async fn upsert_party(&self, party: &Party) -> Result<(), EpcisKgError> {
+        self.send::<serde_json::Value, _>(Method::POST, "/parties", Some(party)).await?;
+        Ok(())
+    }
+
+    pub async fn inventory_snapshot(&self, date: &str) -> Result<Option<InventorySnapshot>, EpcisKgError> {
+        self.find(&format!("/inventory/snapshots/{}", segment(date)), "snapshot").await
+    }
+
+    /// Recall impact report (see `RecallReport` for the fields)
+    pub async fn simulate_recall(&self, request: &RecallRequest) -> Result<serde_json::Value, EpcisKgError> {
+        self.send(Method::POST, "/recall/simulate", Some(request)).await
+    }
+
+    pub async fn record_verification(&self, verification: &Verification) -> Result<(), EpcisKgError> {
+        let path = format!("/epcs/{}/verifications", segment(&verification.epc));
+        self.send::<serde_json::Value, _>(Method::POST, &path, Some(verification)).await?;
+        Ok(())
+    }
+
+    pub async fn suspicion(&self, epc: &str) -> Result<Option<SuspicionReport>, EpcisKgError> {
+        self.find(&format!("/epcs/{}/suspicion", segment(epc)), "suspicion").await
+    }
+
+    // Reasoning
+
+    /// Materialize inferences over the configured scope; `strategy` is
+    /// `incremental` (default), `full`, `ondemand` or `hybrid`
+    pub async fn infer(&self, strategy: Option<&str>) -> Result<serde_json::Value, EpcisKgError> {
+        let body = serde_json::json!({ "strategy": strategy });
+        self.send(Method::POST, "/inference", Some(&body)).await
+    }
+
+    // Monitoring
+
+    pub async fn health(&self) -> Result<HealthStatus, EpcisKgError> {
+        self.send(Method::GET, "/monitoring/health", None::<&()>).await
+    }
+
+    pub async fn metrics(&self) -> Result<SystemMetrics, EpcisKgError> {
+        let response: serde_json::Value = self.send(Method::GET, "/monitoring/metrics", None::<&()>).await?;
+        Ok(serde_json::from_value(response["metrics"].clone())?)
+    }
+
+    pub async fn alerts(&self, limit: usize) -> Result<Alerts, EpcisKgError> {
+        self.send(Method::GET, &format!("/monitoring/alerts?limit={}", limit), None::<&()>).await
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/api/v1{}", self.base_url, path)
+    }
+
+    /// GET a resource wrapped in `field`, mapping 404 to `None`
+    async fn find<T: DeserializeOwned>(&self, path: &str, field: &str) -> Result<Option<T>, EpcisKgError> {
+        let (status, value) = self.request::<()>(Method::GET, path, None).await?;
+        if status == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if let Some(error) = failure(status, &value) {
+            return Err(error);
+        }
+        Ok(Some(serde_json::from_value(value[field].clone())?))
+    }
+
+    async fn send<T: DeserializeOwned, B: Serialize + ?Sized>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<T, EpcisKgError> {
+        let (status, value) = self.request(method, path, body).await?;
+        if let Some(error) = failure(status, &value) {
+            return Err(error);
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    async fn request<B: Serialize + ?Sized>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&B>,
+    ) -> Result<(StatusCode, serde_json::Value), EpcisKgError> {
+        let mut request = self.http.request(method, self.url(path));
+        if let Some(api_key) = &self.api_key {
+            request = request.header(API_KEY_HEADER, api_key);
+        }
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+
+        let response = request.send().await.map_err(|e| EpcisKgError::Generic(Box::new(e)))?;
+        let status = response.status();
+        let value = response.json().await.map_err(|e| EpcisKgError::Generic(Box::new(e)))?;
+        Ok((status, value))
+    }
+}
+
+/// Percent-encode a path segment (Digital Link URIs contain `/`)
+fn segment(value: &str) -> String {
+    urlencoding::encode(value).into_owned()
+}
+
+fn failure(status: StatusCode, body: &serde_json::Value) -> Option<EpcisKgError> {
+    // Problem details carry `detail`; some handlers report failures in the body of a 200 response
+    let message = body["detail"]
+        .as_str()
+        .or_else(|| body["error"].as_str())
+        .unwrap_or_else(|| status.canonical_reason().unwrap_or("request failed"))
+        .to_string();
+    match status {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Some(EpcisKgError::AccessDenied(message)),
+        StatusCode::BAD_REQUEST => Some(EpcisKgError::Validation(message)),
+        StatusCode::TOO_MANY_REQUESTS => Some(EpcisKgError::LimitExceeded(message)),
+        StatusCode::SERVICE_UNAVAILABLE => Some(EpcisKgError::Unavailable { message, retry_after_secs: None }),
+        status if !status.is_success() => Some(EpcisKgError::Generic(format!("{}: {}", status, message).into())),
+        _ if body["status"] == "error" => Some(EpcisKgError::Query(message)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_urls_and_error_mapping() {
+        let client = EpcisClient::new("http://localhost:8080/");
+        assert_eq!(
+            client.url(&format!("/trace/{}", segment("https://id.gs1.org/01/09506000134352/21/2017"))),
+            "http://localhost:8080/api/v1/trace/https%3A%2F%2Fid.gs1.org%2F01%2F09506000134352%2F21%2F2017"
+        );
+
+        let denied = serde_json::json!({ "error": "API key cannot read graph", "status": "error" });
+        assert!(matches!(failure(StatusCode::FORBIDDEN, &denied), Some(EpcisKgError::AccessDenied(_))));
+        assert!(matches!(failure(StatusCode::OK, &denied), Some(EpcisKgError::Query(_))));
+        assert!(failure(StatusCode::OK, &serde_json::json!({ "success": true })).is_none());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::EpcisKgError;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,39 +8,1489 @@ pub struct AppConfig {
     pub database_path: String,
     pub server_port: u16,
     pub log_level: String,
+    /// Log levels of individual targets, overriding `log_level` (e.g. `storage = "debug"`)
+    #[serde(default)]
+    pub log_targets: BTreeMap<String, String>,
     pub ontology_paths: Vec<String>,
     pub reasoning: ReasoningConfig,
     pub sparql: SparqlConfig,
     pub server: ServerConfig,
     pub persistence: PersistenceConfig,
+    #[serde(default)]
+    pub access_control: AccessControlConfig,
+    #[serde(default)]
+    pub anonymization: AnonymizationConfig,
+    #[serde(default)]
+    pub jsonld: JsonLdConfig,
+    #[serde(default)]
+    pub sessions: SessionConfig,
+    #[serde(default)]
+    pub extensions: ExtensionsConfig,
+    #[serde(default)]
+    pub inventory: InventoryConfig,
+    #[serde(default)]
+    pub cold_chain: ColdChainConfig,
+    #[serde(default)]
+    pub counterfeit: CounterfeitConfig,
+    #[serde(default)]
+    pub event_patterns: EventPatternsConfig,
+    #[serde(default)]
+    pub grpc: GrpcConfig,
+    #[serde(default)]
+    pub changefeed: ChangefeedConfig,
+    #[serde(default)]
+    pub sql: SqlConfig,
+    #[serde(default)]
+    pub scheduler: SchedulerConfig,
+    #[serde(default)]
+    pub scripting: ScriptingConfig,
+    #[serde(default)]
+    pub ontology_cache: OntologyCacheConfig,
+    #[serde(default)]
+    pub signing: SigningConfig,
+    #[serde(default)]
+    pub resilience: ResilienceConfig,
+    #[serde(default)]
+    pub entity_resolution: EntityResolutionConfig,
+    #[serde(default)]
+    pub batching: BatchingConfig,
+    #[serde(default)]
+    pub reasoning_cache: ReasoningCacheConfig,
+    #[serde(default)]
+    pub localization: LocalizationConfig,
+    #[serde(default)]
+    pub archive: ArchiveConfig,
+    #[serde(default)]
+    pub idempotency: IdempotencyConfig,
+    #[serde(default)]
+    pub auto_correction: AutoCorrectionConfig,
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+    #[serde(default)]
+    pub pii_masking: PiiMaskingConfig,
+    #[serde(default)]
+    pub samples: SamplesConfig,
+    #[serde(default)]
+    pub query_log: QueryLogConfig,
+    #[serde(default)]
+    pub access_log: AccessLogConfig,
+    #[serde(default)]
+    pub compression: CompressionConfig,
+    #[serde(default)]
+    pub cluster: ClusterConfig,
+    #[serde(default)]
+    pub snapshots: SnapshotConfig,
+    #[serde(default)]
+    pub strict_mode: StrictModeConfig,
+    #[serde(default)]
+    pub identifiers: IdentifierNormalizationConfig,
+    #[serde(default)]
+    pub edge: EdgeConfig,
+    #[serde(default)]
+    pub graph_ttl: GraphTtlConfig,
+    #[serde(default)]
+    pub carbon: CarbonConfig,
+    #[serde(default)]
+    pub lineage: LineageConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ReasoningConfig {
     pub default_profile: String,
     pub enable_inference: bool,
     pub max_inference_time: u64,
+    /// Reuse the converted ontology, class hierarchy and indexes across runs
+    /// while the ontology content is unchanged
+    pub warm_start: bool,
+    /// Directory holding warm-start reasoner snapshots
+    pub cache_dir: String,
+    /// Maximum number of idle reasoners kept by the server's reasoner pool
+    pub pool_size: usize,
+    /// Class count from which the class hierarchy is computed per connected
+    /// component in parallel rather than sequentially
+    pub parallel_classification_min_classes: usize,
+    /// Named graphs that take part in store-resident reasoning
+    pub scope: InferenceScopeConfig,
 }
 
+/// Which named graphs form the TBox and the ABox for reasoning.
+///
+/// Patterns are exact graph names, `*` for every graph, or a prefix ending in
+/// `*`. Graphs holding inferences are never part of the scope.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InferenceScopeConfig {
+    /// Ontology graphs (classes, properties, axioms)
+    pub tbox_graphs: Vec<String>,
+    /// Event and master data graphs
+    pub abox_graphs: Vec<String>,
+    /// Graphs left out even when matched above
+    pub exclude_graphs: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct SparqlConfig {
     pub max_query_time: u64,
     pub max_results: usize,
     pub enable_updates: bool,
+    /// Maximum number of queries accepted by one batch request
+    pub max_batch_size: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerConfig {
+    pub enable_cors: bool,
+    pub cors_origins: Vec<String>,
+    pub request_timeout: u64,
+    /// Maximum number of EPCs accepted by one POST /api/v1/epcs/status request
+    #[serde(default = "default_max_status_epcs")]
+    pub max_status_epcs: usize,
+    /// Largest request body accepted by API endpoints other than ontology uploads
+    #[serde(default = "default_max_body_bytes")]
+    pub max_body_bytes: usize,
+    /// Largest RDF file accepted by POST /api/v1/ontologies
+    #[serde(default = "default_max_upload_bytes")]
+    pub max_upload_bytes: u64,
+    /// Directory uploads are written to before they are parsed
+    #[serde(default = "default_upload_dir")]
+    pub upload_dir: String,
+}
+
+fn default_max_status_epcs() -> usize {
+    1000
+}
+
+fn default_max_body_bytes() -> usize {
+    2 * 1024 * 1024
+}
+
+fn default_max_upload_bytes() -> u64 {
+    512 * 1024 * 1024
+}
+
+fn default_upload_dir() -> String {
+    "./data/uploads".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistenceConfig {
+    pub auto_save: bool,
+    pub save_interval: u64,
+    pub backup_on_startup: bool,
+}
+
+/// Per-graph access control for the SPARQL endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessControlConfig {
+    /// When disabled, every request may read and write every graph
+    pub enabled: bool,
+    pub api_keys: Vec<ApiKeyAcl>,
+    /// Where per-tenant query time is kept across restarts
+    pub quota_state_path: String,
+}
+
+/// Graphs an API key may read and write.
+///
+/// Graph patterns are exact graph names, `*` for every graph, or a prefix
+/// ending in `*` (e.g. `urn:epcis:partner:0614141*` for a partner's GLN).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyAcl {
+    pub key: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub read_graphs: Vec<String>,
+    #[serde(default)]
+    pub write_graphs: Vec<String>,
+    /// Admin keys may manage ACL entries through the API
+    #[serde(default)]
+    pub admin: bool,
+    /// Resource limits for this key's tenant; unlimited when absent
+    #[serde(default)]
+    pub quota: Option<TenantQuota>,
+}
+
+/// Resource limits of one tenant (API key).
+///
+/// Storage counts the graphs the key may write. Query time accumulates over a
+/// window (a day unless `query_window_seconds` is set) and starts again from
+/// zero when the window ends. Writes are refused once a storage limit is
+/// reached, and reads once the window's query time is used up.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TenantQuota {
+    pub max_triples: Option<usize>,
+    pub max_storage_bytes: Option<u64>,
+    pub max_query_seconds: Option<f64>,
+    pub query_window_seconds: Option<u64>,
+}
+
+impl TenantQuota {
+    /// Period over which `max_query_seconds` applies
+    pub fn query_window(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.query_window_seconds.unwrap_or(24 * 60 * 60))
+    }
+}
+
+/// Pseudonymization settings for anonymized exports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnonymizationConfig {
+    /// Environment variable holding the secret hashing key
+    pub key_env: String,
+    /// Replace serial components of EPC instance identifiers
+    pub pseudonymize_epcs: bool,
+    /// Replace GLN identifiers (SGLN / PGLN URNs and Digital Link GLNs)
+    pub pseudonymize_glns: bool,
+    /// Graphs exported unchanged (exact names or prefixes ending in `*`)
+    pub exclude_graphs: Vec<String>,
+    /// Per-predicate rules applied to object values
+    pub predicates: HashMap<String, PseudonymizationRule>,
+}
+
+/// How the object of a predicate is treated in an anonymized export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PseudonymizationRule {
+    /// Export the value unchanged
+    Keep,
+    /// Treat the value as an EPC and pseudonymize its serial component
+    EpcSerial,
+    /// Treat the value as a GLN
+    Gln,
+    /// Replace a business entity name with a stable `entity-<hash>` label
+    EntityName,
+    /// Replace the whole value with its keyed hash
+    Hash,
+    /// Leave the triple out of the export
+    Drop,
+}
+
+/// JSON-LD framing for API responses
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct JsonLdConfig {
+    /// Frame name -> path of a JSON-LD frame document; overrides the built-in
+    /// `event` and `epc` frames when the same name is used
+    pub frames: HashMap<String, String>,
+}
+
+/// Temporary "what-if" overlay sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    /// Maximum number of concurrently open sessions
+    pub max_sessions: usize,
+    /// Idle time after which a session is discarded
+    pub ttl_seconds: u64,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            max_sessions: 16,
+            ttl_seconds: 3600,
+        }
+    }
+}
+
+/// Dated inventory snapshots computed from the event history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InventoryConfig {
+    /// Compute and store a snapshot periodically while the server runs
+    pub snapshots_enabled: bool,
+    /// Time between scheduled snapshots; each run replaces the snapshot of its UTC day
+    pub snapshot_interval_seconds: u64,
+}
+
+impl Default for InventoryConfig {
+    fn default() -> Self {
+        Self {
+            snapshots_enabled: false,
+            snapshot_interval_seconds: 86400,
+        }
+    }
+}
+
+/// Weights of the cloned/counterfeit EPC heuristics.
+///
+/// Signal weights are combined as independent probabilities, so the
+/// suspicion score of an EPC stays between 0 and 1.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CounterfeitConfig {
+    /// Observed at two locations closer in time than a transfer could take
+    pub concurrent_custody_weight: f64,
+    /// Observed without (or before) being commissioned
+    pub never_commissioned_weight: f64,
+    /// A recorded verification request failed
+    pub verification_failure_weight: f64,
+    /// Minimum plausible time between observations at different locations
+    pub min_transit_minutes: i64,
+    /// EPCs scoring at least this much are reported as suspects
+    pub suspect_threshold: f64,
+}
+
+impl Default for CounterfeitConfig {
+    fn default() -> Self {
+        Self {
+            concurrent_custody_weight: 0.6,
+            never_commissioned_weight: 0.4,
+            verification_failure_weight: 0.7,
+            min_transit_minutes: 60,
+            suspect_threshold: 0.5,
+        }
+    }
+}
+
+/// Matching of near-duplicate master data into proposed `owl:sameAs` links.
+///
+/// Matched keys are combined as independent evidence like the counterfeit
+/// weights; a name match counts `name_weight` scaled by its similarity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntityResolutionConfig {
+    /// Keys to match on: `gln`, `gtin` and/or `name`
+    pub keys: Vec<String>,
+    /// Predicates whose literals name a master data resource
+    pub name_predicates: Vec<String>,
+    /// Predicates holding further identifiers (EPC URIs, Digital Links, plain GLNs or GTINs)
+    pub identifier_predicates: Vec<String>,
+    pub gln_weight: f64,
+    pub gtin_weight: f64,
+    pub name_weight: f64,
+    /// Dice similarity (0..1) two normalized names need to count as a match
+    pub min_name_similarity: f64,
+    /// Proposals below this confidence are not reported
+    pub min_confidence: f64,
+}
+
+impl Default for EntityResolutionConfig {
+    fn default() -> Self {
+        Self {
+            keys: vec!["gln".to_string(), "gtin".to_string(), "name".to_string()],
+            name_predicates: vec![
+                "http://www.w3.org/2000/01/rdf-schema#label".to_string(),
+                "http://www.w3.org/2004/02/skos/core#prefLabel".to_string(),
+                "urn:epcglobal:epcis:partyName".to_string(),
+                "http://example.com/name".to_string(),
+            ],
+            identifier_predicates: vec![
+                "http://example.com/epc".to_string(),
+                "https://gs1.org/voc/gln".to_string(),
+                "https://gs1.org/voc/gtin".to_string(),
+            ],
+            gln_weight: 0.9,
+            gtin_weight: 0.9,
+            name_weight: 0.6,
+            min_name_similarity: 0.85,
+            min_confidence: 0.5,
+        }
+    }
+}
+
+/// Cold-chain monitoring: temperature profiles and where excursion alerts go
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ColdChainConfig {
+    pub profiles: Vec<TemperatureProfile>,
+    /// Scan for new excursions periodically while the server runs (0 = only on request)
+    pub scan_interval_seconds: u64,
+    /// URLs receiving a JSON POST for every newly detected excursion
+    pub webhook_urls: Vec<String>,
+}
+
+/// Allowed temperature range for a product class
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemperatureProfile {
+    /// EPC class pattern, e.g. `urn:epc:idpat:sgtin:0614141.107346.*`; a trailing `*` matches any suffix
+    pub product_class: String,
+    pub min_celsius: f64,
+    pub max_celsius: f64,
+    /// Out-of-range time tolerated before an excursion is recorded
+    #[serde(default)]
+    pub tolerance_minutes: u64,
+}
+
+/// gRPC service for capture, query and trace (requires the `grpc` feature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GrpcConfig {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 50051,
+        }
+    }
+}
+
+/// Periodic jobs run by the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    /// File keeping each job's last-run state across restarts
+    pub state_path: String,
+    pub jobs: Vec<JobDefinition>,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            state_path: "./data/scheduler_state.json".to_string(),
+            jobs: Vec::new(),
+        }
+    }
+}
+
+/// User-level cache of parsed ontology files, shared across database paths
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OntologyCacheConfig {
+    pub enabled: bool,
+    /// Cache directory; empty means `$XDG_CACHE_HOME/epcis-knowledge-graph/ontologies`
+    /// (or `~/.cache/...`). `EPCIS_KG_ONTOLOGY_CACHE_DIR` overrides both.
+    pub directory: String,
+}
+
+impl Default for OntologyCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            directory: String::new(),
+        }
+    }
+}
+
+/// JWS signatures and verifiable credentials for documents sent to partners
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SigningConfig {
+    /// `EdDSA` (Ed25519; partners verify with the public key) or `HS256` (shared secret)
+    pub algorithm: String,
+    /// Environment variable holding the key: a base64url Ed25519 seed of 32
+    /// bytes, or the HMAC secret
+    pub key_env: String,
+    /// `kid` header that tells partners which key to verify with, e.g. a DID URL
+    pub key_id: Option<String>,
+    /// Issuer of verifiable credentials, e.g. `did:web:example.com`
+    pub issuer: String,
+}
+
+impl Default for SigningConfig {
+    fn default() -> Self {
+        Self {
+            algorithm: "EdDSA".to_string(),
+            key_env: "EPCIS_SIGNING_KEY".to_string(),
+            key_id: None,
+            issuer: String::new(),
+        }
+    }
+}
+
+/// Retry and circuit-breaker policies for calls to external systems
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ResilienceConfig {
+    /// Policy of integrations without their own entry
+    pub default: ResiliencePolicy,
+    /// Policies by integration name (`webhooks`, `kafka`, ...); each replaces the default as a whole
+    pub integrations: BTreeMap<String, ResiliencePolicy>,
+}
+
+impl ResilienceConfig {
+    /// Policy of one integration
+    pub fn policy(&self, integration: &str) -> ResiliencePolicy {
+        self.integrations.get(integration).cloned().unwrap_or_else(|| self.default.clone())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResiliencePolicy {
+    /// Attempts per call, the first one included
+    pub max_attempts: u32,
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    pub backoff_multiplier: f64,
+    /// Random share (0-1) added to or taken from each backoff
+    pub jitter: f64,
+    /// Consecutive failed calls that open the circuit breaker
+    pub failure_threshold: u32,
+    /// How long an open breaker rejects calls before letting one through
+    pub open_seconds: u64,
+    /// JSON Lines file receiving payloads that could not be delivered
+    pub dead_letter_path: Option<String>,
+}
+
+impl Default for ResiliencePolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff_ms: 200,
+            max_backoff_ms: 10_000,
+            backoff_multiplier: 2.0,
+            jitter: 0.2,
+            failure_threshold: 5,
+            open_seconds: 30,
+            dead_letter_path: None,
+        }
+    }
+}
+
+/// Batch sizes for event ingestion and parallel inference
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatchingConfig {
+    pub ingestion: BatchingPolicy,
+    pub inference: BatchingPolicy,
+}
+
+impl Default for BatchingConfig {
+    fn default() -> Self {
+        Self {
+            ingestion: BatchingPolicy {
+                initial_size: 100,
+                min_size: 1,
+                max_size: 1_000,
+                target_latency_ms: 1_000,
+                ..BatchingPolicy::default()
+            },
+            inference: BatchingPolicy::default(),
+        }
+    }
+}
+
+/// How a batch size controller steers towards its latency and memory targets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatchingPolicy {
+    /// When disabled the batch size stays at `initial_size`
+    pub adaptive: bool,
+    pub initial_size: usize,
+    pub min_size: usize,
+    pub max_size: usize,
+    /// Wall-clock time one batch should take
+    pub target_latency_ms: u64,
+    /// Resident memory above which batches shrink (Linux only)
+    pub memory_budget_mb: Option<u64>,
+}
+
+impl Default for BatchingPolicy {
+    fn default() -> Self {
+        Self {
+            adaptive: true,
+            initial_size: 1_000,
+            min_size: 100,
+            max_size: 50_000,
+            target_latency_ms: 500,
+            memory_budget_mb: None,
+        }
+    }
+}
+
+/// Entry limits of the reasoner's cache tiers; 0 disables a tier
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReasoningCacheConfig {
+    /// Results of whole inference runs
+    pub query_results: usize,
+    /// Inferences per input triple
+    pub triple_patterns: usize,
+    /// Superclass and superproperty closures
+    pub closures: usize,
+}
+
+impl Default for ReasoningCacheConfig {
+    fn default() -> Self {
+        Self {
+            query_results: 100,
+            triple_patterns: 10_000,
+            closures: 5_000,
+        }
+    }
+}
+
+/// Languages of vocabulary labels and user-facing messages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LocalizationConfig {
+    /// Language of CLI output and of API responses without `Accept-Language`
+    pub default_language: String,
+    /// Directory of `<language>.toml` message catalogs adding to or overriding the bundled ones
+    pub messages_dir: Option<String>,
+    /// Turtle files of language-tagged labels loaded next to the ontologies
+    pub label_paths: Vec<String>,
+}
+
+impl Default for LocalizationConfig {
+    fn default() -> Self {
+        Self {
+            default_language: "en".to_string(),
+            messages_dir: None,
+            label_paths: vec!["ontologies/cbv-labels.ttl".to_string()],
+        }
+    }
+}
+
+/// Archived snapshot files that queries can include on request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveConfig {
+    /// Directory of N-Triples (`.nt`) or N-Quads (`.nq`) snapshots, e.g. a backup job's directory
+    pub directory: String,
+    /// Snapshots kept parsed in memory between queries
+    pub max_loaded: usize,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            directory: "./archive".to_string(),
+            max_loaded: 2,
+        }
+    }
+}
+
+/// Replacing misspelled CBV business steps and dispositions with the closest vocabulary term
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoCorrectionConfig {
+    /// Off: unknown values only get a suggestion in the validation warning
+    pub enabled: bool,
+    /// Lowest confidence (1 minus the relative edit distance) at which a value is replaced
+    pub min_confidence: f64,
+}
+
+impl Default for AutoCorrectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_confidence: 0.85,
+        }
+    }
+}
+
+/// Masking of personal data in event fields at capture
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PiiMaskingConfig {
+    pub enabled: bool,
+    /// Environment variable holding the secret key of `hash` policies
+    pub key_env: String,
+    /// Checked in order; the first policy matching a predicate applies
+    pub policies: Vec<MaskingPolicy>,
+}
+
+impl Default for PiiMaskingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            key_env: "EPCIS_KG_PII_KEY".to_string(),
+            policies: Vec::new(),
+        }
+    }
+}
+
+/// What the event graph keeps of the values of matching predicates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaskingPolicy {
+    /// Predicate IRI, or a prefix ending in `*`
+    pub predicate: String,
+    pub action: MaskingAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaskingAction {
+    /// Replace the value with its keyed hash, so equal values still match
+    Hash,
+    /// Replace the value with `[REDACTED]`
+    Redact,
+    /// Leave the triple out of the event graph
+    Drop,
+}
+
+/// Where `load-samples` and `serve --use-samples-data` find sample datasets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SamplesConfig {
+    /// Pre-generated files `epcis_data_<scale>.ttl`; downloaded packs go to `<directory>/<version>`
+    pub directory: String,
+    /// Release to download missing packs from; `{version}` is replaced with `version`
+    pub release_url: Option<String>,
+    pub version: String,
+    /// Pinned SHA-256 per file name; files not listed are checked against the release's `SHA256SUMS`
+    pub checksums: BTreeMap<String, String>,
+    /// Generate a missing pack locally when it cannot be downloaded
+    pub generate_missing: bool,
+}
+
+impl Default for SamplesConfig {
+    fn default() -> Self {
+        Self {
+            directory: "samples".to_string(),
+            release_url: None,
+            version: "1".to_string(),
+            checksums: BTreeMap::new(),
+            generate_missing: true,
+        }
+    }
+}
+
+/// AES-256-GCM encryption of persisted graph files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    pub enabled: bool,
+    /// Where the current key comes from: `config`, `env` or `command`
+    pub key_source: String,
+    /// Base64 key for `key_source = "config"`; keep such a file private
+    pub key: Option<String>,
+    /// Environment variable holding the base64 key for `key_source = "env"`
+    pub key_env: String,
+    /// Shell command printing the base64 key, e.g. a KMS decrypt call, for `key_source = "command"`
+    pub key_command: Option<String>,
+    /// Environment variables holding retired keys, still accepted for reading during a rotation
+    pub retired_key_envs: Vec<String>,
+}
+
+impl Default for EncryptionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            key_source: "env".to_string(),
+            key: None,
+            key_env: "EPCIS_KG_STORAGE_KEY".to_string(),
+            key_command: None,
+            retired_key_envs: Vec::new(),
+        }
+    }
+}
+
+/// `Idempotency-Key` handling for capture, ontology load and SPARQL update requests
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdempotencyConfig {
+    pub enabled: bool,
+    /// File keeping request fingerprints and responses across restarts
+    pub state_path: String,
+    /// How long a key is remembered
+    pub ttl_secs: u64,
+    /// Larger responses are not kept, so a retry is applied again
+    pub max_response_bytes: usize,
+}
+
+impl Default for IdempotencyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            state_path: "./data/idempotency_keys.json".to_string(),
+            ttl_secs: 24 * 60 * 60,
+            max_response_bytes: 1024 * 1024,
+        }
+    }
+}
+
+/// Log of the SPARQL queries the server runs, with plans of slow ones
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueryLogConfig {
+    pub enabled: bool,
+    /// JSON Lines file every query is appended to, read by `query-log`; empty keeps the log in memory only
+    pub path: String,
+    /// Queries taking at least this long are slow and get their plan captured
+    pub slow_threshold_ms: u64,
+    /// Recent entries kept in memory for `GET /api/v1/queries/slow`
+    pub capacity: usize,
+}
+
+impl Default for QueryLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: "./data/query_log.jsonl".to_string(),
+            slow_threshold_ms: 500,
+            capacity: 1000,
+        }
+    }
+}
+
+/// gzip and Brotli encoding of API responses and request bodies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompressionConfig {
+    /// Off leaves responses unencoded and rejects encoded request bodies with 415
+    pub enabled: bool,
+    pub gzip: bool,
+    pub brotli: bool,
+    /// Responses smaller than this are sent as they are
+    pub min_response_bytes: u16,
+    /// Accept request bodies sent with `Content-Encoding: gzip` or `br`
+    pub decompress_requests: bool,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            gzip: true,
+            brotli: true,
+            min_response_bytes: 1024,
+            decompress_requests: true,
+        }
+    }
+}
+
+/// Line format of the access log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessLogFormat {
+    /// Common Log Format, followed by the latency in milliseconds
+    Common,
+    /// One JSON object per line
+    Json,
+}
+
+/// HTTP access log, written apart from the application log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessLogConfig {
+    pub enabled: bool,
+    pub path: String,
+    pub format: AccessLogFormat,
+    /// The file is rotated to `{path}.1` once it would grow past this size
+    pub max_file_size_mb: u64,
+    /// Rotated files kept (`{path}.1` is the newest); 0 truncates instead
+    pub max_files: usize,
+}
+
+impl Default for AccessLogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: "./logs/access.log".to_string(),
+            format: AccessLogFormat::Common,
+            max_file_size_mb: 100,
+            max_files: 5,
+        }
+    }
+}
+
+/// Store-and-forward capture at a site (`edge` command)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EdgeConfig {
+    /// Central server the queued events are forwarded to, e.g. `http://central:8080`
+    pub central_url: String,
+    /// API key sent to the central server
+    pub api_key: Option<String>,
+    /// Port the local capture API listens on
+    pub port: u16,
+    /// File holding the queued events across restarts
+    pub queue_path: String,
+    /// Most events forwarded in one request
+    pub batch_size: usize,
+    /// Pause between forwarding attempts while the queue is empty or the central server is unreachable
+    pub forward_interval_seconds: u64,
+    pub request_timeout_seconds: u64,
+}
+
+impl Default for EdgeConfig {
+    fn default() -> Self {
+        Self {
+            central_url: String::new(),
+            api_key: None,
+            port: 8090,
+            queue_path: "./data/edge_queue.json".to_string(),
+            batch_size: 500,
+            forward_interval_seconds: 10,
+            request_timeout_seconds: 30,
+        }
+    }
+}
+
+/// Conversion of GS1 Digital Link URIs in captured events to EPC URNs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdentifierNormalizationConfig {
+    pub enabled: bool,
+    /// GS1 Company Prefix length for keys that match none of `company_prefixes`
+    pub company_prefix_length: usize,
+    /// Known GS1 Company Prefixes; the longest one a key starts with sets the split
+    pub company_prefixes: Vec<String>,
+}
+
+impl Default for IdentifierNormalizationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            company_prefix_length: 7,
+            company_prefixes: Vec::new(),
+        }
+    }
+}
+
+/// Capture validation that rejects events for selected warnings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrictModeConfig {
+    /// Strict for every capture; a request's `strict` parameter overrides it either way
+    pub enabled: bool,
+    /// Warnings strict mode turns into errors
+    pub promote: Vec<WarningKind>,
+}
+
+impl Default for StrictModeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            promote: vec![WarningKind::NonUrnEpc, WarningKind::NonSglnLocation, WarningKind::FutureEventTime],
+        }
+    }
+}
+
+/// Kind of capture validation warning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    /// An EPC outside the `urn:epc:id:` scheme
+    NonUrnEpc,
+    UnknownBizStep,
+    UnknownDisposition,
+    /// A business location that is not an SGLN
+    NonSglnLocation,
+    /// An `eventTimeZoneOffset` other than the site's time zone at the event time
+    SiteTimeZoneOffset,
+    FutureEventTime,
+    DeleteWithoutBizStep,
+    /// A commissioning event whose disposition is not `active`
+    CommissioningNotActive,
+    /// A partner extension field its schema does not describe
+    ExtensionField,
+}
+
+/// Pinned copies of the store for analytics queries (`?snapshot=latest-stable`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapshotConfig {
+    /// Each snapshot is a full in-memory copy of the store
+    pub enabled: bool,
+    /// Minimum time between snapshots while ingesting; queries catch up once ingestion pauses
+    pub min_interval_ms: u64,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_interval_ms: 1000,
+        }
+    }
+}
+
+/// Experimental ingestion shared by several instances (see `api::cluster`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClusterConfig {
+    /// Overridden by `serve --role`
+    pub role: ClusterRole,
+    /// Number of EPC-hash shards; shard `n` is ingested by worker `n % workers.len()`
+    pub shards: u32,
+    /// Base URLs of the workers, in shard assignment order (coordinator only)
+    pub workers: Vec<String>,
+    /// Pull the workers' graphs into the coordinator's store this often (0 = only on request)
+    pub merge_interval_seconds: u64,
+    /// API key the coordinator sends to workers; it must be an admin key there
+    pub api_key: Option<String>,
+    pub request_timeout_seconds: u64,
+}
+
+/// Part an instance plays in distributed ingestion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClusterRole {
+    /// Ingests and serves on its own
+    #[default]
+    Standalone,
+    /// Routes captured events to workers by EPC hash and merges their graphs into its store for querying
+    Coordinator,
+    /// Stores and materializes the events of the shards routed to it
+    Worker,
+}
+
+impl ClusterRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClusterRole::Standalone => "standalone",
+            ClusterRole::Coordinator => "coordinator",
+            ClusterRole::Worker => "worker",
+        }
+    }
+}
+
+impl std::str::FromStr for ClusterRole {
+    type Err = EpcisKgError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "standalone" => Ok(ClusterRole::Standalone),
+            "coordinator" => Ok(ClusterRole::Coordinator),
+            "worker" => Ok(ClusterRole::Worker),
+            other => Err(EpcisKgError::Config(format!(
+                "Unknown cluster role '{}' (expected standalone, coordinator or worker)",
+                other
+            ))),
+        }
+    }
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        Self {
+            role: ClusterRole::Standalone,
+            shards: 64,
+            workers: Vec::new(),
+            merge_interval_seconds: 30,
+            api_key: None,
+            request_timeout_seconds: 30,
+        }
+    }
+}
+
+/// Sandboxed Rhai scripts run against every processed event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScriptingConfig {
+    pub enabled: bool,
+    /// Directory of `*.rhai` scripts, run in file-name order
+    pub scripts_dir: String,
+    /// Pick up added, changed and removed scripts without a restart
+    pub hot_reload: bool,
+    /// Minimum seconds between checks of the scripts directory
+    pub reload_interval_seconds: u64,
+    /// Operation budget per script run; runaway scripts are aborted
+    pub max_operations: u64,
+    /// Hook priority of the scripts (lower runs first)
+    pub priority: i32,
+}
+
+impl Default for ScriptingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            scripts_dir: "./scripts".to_string(),
+            hot_reload: true,
+            reload_interval_seconds: 2,
+            max_operations: 100_000,
+            priority: 100,
+        }
+    }
+}
+
+/// A scheduled job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobDefinition {
+    pub name: String,
+    /// Five-field cron expression (`*/15 * * * *`, UTC) or `@every <n>s|m|h`
+    pub schedule: String,
+    /// Random delay of up to this many seconds added to each run
+    #[serde(default)]
+    pub jitter_seconds: u64,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub task: JobTask,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// What a job does
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JobTask {
+    InventorySnapshot,
+    ColdChainScan,
+    PatternScan,
+    CounterfeitScan,
+    /// Materialize the CO2e and cost estimates of detected shipments
+    CarbonScan,
+    /// Record predicates and classes of stored events that no loaded ontology defines
+    SchemaDriftScan,
+    /// Write the whole store as N-Triples into `directory`, keeping the newest `keep` backups
+    Backup { directory: String, keep: usize },
+    /// Delete data past the TTL of its `[graph_ttl]` policy
+    GraphTtlSweep,
+}
+
+impl JobTask {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            JobTask::InventorySnapshot => "inventory_snapshot",
+            JobTask::ColdChainScan => "cold_chain_scan",
+            JobTask::PatternScan => "pattern_scan",
+            JobTask::CounterfeitScan => "counterfeit_scan",
+            JobTask::CarbonScan => "carbon_scan",
+            JobTask::SchemaDriftScan => "schema_drift_scan",
+            JobTask::Backup { .. } => "backup",
+            JobTask::GraphTtlSweep => "graph_ttl_sweep",
+        }
+    }
+}
+
+impl AppConfig {
+    /// Configured jobs plus those implied by the older per-feature interval settings
+    pub fn scheduled_jobs(&self) -> Vec<JobDefinition> {
+        let mut jobs = self.scheduler.jobs.clone();
+        let mut implied = |name: &str, seconds: u64, task: JobTask| {
+            if !jobs.iter().any(|job| job.name == name) {
+                jobs.push(JobDefinition {
+                    name: name.to_string(),
+                    schedule: format!("@every {}s", seconds),
+                    jitter_seconds: 0,
+                    enabled: true,
+                    task,
+                });
+            }
+        };
+        if self.inventory.snapshots_enabled {
+            implied("inventory-snapshots", self.inventory.snapshot_interval_seconds, JobTask::InventorySnapshot);
+        }
+        if self.cold_chain.scan_interval_seconds > 0 {
+            implied("cold-chain-scan", self.cold_chain.scan_interval_seconds, JobTask::ColdChainScan);
+        }
+        if self.event_patterns.scan_interval_seconds > 0 {
+            implied("event-pattern-scan", self.event_patterns.scan_interval_seconds, JobTask::PatternScan);
+        }
+        if self.graph_ttl.sweep_interval_seconds > 0 && !self.graph_ttl.policies.is_empty() {
+            implied("graph-ttl-sweep", self.graph_ttl.sweep_interval_seconds, JobTask::GraphTtlSweep);
+        }
+        if self.carbon.scan_interval_seconds > 0 {
+            implied("carbon-scan", self.carbon.scan_interval_seconds, JobTask::CarbonScan);
+        }
+        jobs
+    }
+}
+
+/// Read-only SQL views over the event history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SqlConfig {
+    /// Serve the views over the PostgreSQL wire protocol for BI tools
+    pub postgres_enabled: bool,
+    pub postgres_port: u16,
+}
+
+impl Default for SqlConfig {
+    fn default() -> Self {
+        Self {
+            postgres_enabled: false,
+            postgres_port: 5433,
+        }
+    }
+}
+
+/// Kafka changefeed of committed events and inferred facts (requires the `kafka` feature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChangefeedConfig {
+    pub enabled: bool,
+    /// Comma-separated `host:port` bootstrap servers
+    pub brokers: String,
+    pub client_id: String,
+    pub events_topic: String,
+    /// Topic for facts added to derived graphs; inferences are not published when unset
+    pub inferences_topic: Option<String>,
+    /// Serialization of committed events
+    pub format: ChangefeedFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangefeedFormat {
+    /// The event graph framed as EPCIS JSON-LD
+    EpcisJson,
+    /// The event graph as an N-Quads delta
+    Nquads,
+}
+
+impl Default for ChangefeedConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            brokers: "localhost:9092".to_string(),
+            client_id: "epcis-knowledge-graph".to_string(),
+            events_topic: "epcis.events".to_string(),
+            inferences_topic: None,
+            format: ChangefeedFormat::EpcisJson,
+        }
+    }
+}
+
+/// Event pattern rules (CEP-lite) evaluated over the event history
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct EventPatternsConfig {
+    pub rules: Vec<PatternRule>,
+    /// Evaluate the rules periodically while the server runs (0 = only on request)
+    pub scan_interval_seconds: u64,
 }
 
+/// A temporal pattern over events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PatternRule {
+    /// An event matching `after` not followed, for the same EPC, by one matching `expect` within the window
+    Absence {
+        name: String,
+        after: EventMatcher,
+        expect: EventMatcher,
+        window_minutes: i64,
+    },
+    /// More than `count` occurrences at one location within a sliding window
+    Threshold {
+        name: String,
+        #[serde(default)]
+        source: PatternSource,
+        #[serde(default)]
+        matcher: EventMatcher,
+        count: usize,
+        window_minutes: i64,
+    },
+}
+
+impl PatternRule {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Absence { name, .. } | Self::Threshold { name, .. } => name,
+        }
+    }
+
+    pub fn window_minutes(&self) -> i64 {
+        match self {
+            Self::Absence { window_minutes, .. } | Self::Threshold { window_minutes, .. } => *window_minutes,
+        }
+    }
+}
+
+/// What a threshold rule counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PatternSource {
+    /// Events selected by the rule's matcher
+    #[default]
+    Events,
+    /// Failed EPC verification results
+    FailedVerifications,
+}
+
+/// Event selector; unset fields match anything.
+///
+/// `biz_step` accepts a full CBV IRI or its last segment (`shipping`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct EventMatcher {
+    pub event_type: Option<String>,
+    pub action: Option<String>,
+    pub biz_step: Option<String>,
+}
+
+/// Graphs of ephemeral operational data, such as sensor heartbeats or
+/// temporary reservations, that expire on their own
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct GraphTtlConfig {
+    /// The first policy whose `graphs` pattern matches a graph applies to it
+    pub policies: Vec<GraphTtlPolicy>,
+    /// Enforce the policies periodically while the server runs (0 = only when the job is run)
+    pub sweep_interval_seconds: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphTtlPolicy {
+    /// Graph name, or a prefix ending in `*` (`urn:epcis:heartbeat:*`)
+    pub graphs: String,
+    pub ttl_seconds: u64,
+    #[serde(default)]
+    pub scope: TtlScope,
+    /// Predicate whose `xsd:dateTime` value dates a subject
+    #[serde(default = "default_ttl_time_predicate")]
+    pub time_predicate: String,
+}
+
+fn default_ttl_time_predicate() -> String {
+    "urn:epcglobal:epcis:recordTime".to_string()
+}
+
+impl GraphTtlPolicy {
+    pub fn applies_to(&self, graph_name: &str) -> bool {
+        match self.graphs.strip_suffix('*') {
+            Some(prefix) => graph_name.starts_with(prefix),
+            None => graph_name == self.graphs,
+        }
+    }
+}
+
+/// What expires once its time is older than the TTL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TtlScope {
+    /// The whole graph, dated by the newest time in it
+    #[default]
+    Graph,
+    /// The triples of each subject, dated by its own time
+    Subject,
+}
+
+/// Emission and cost factors for estimating the footprint of shipments.
+///
+/// A shipment is a shipping event followed by a receiving event of the same
+/// EPCs; its lane is looked up by origin and destination location.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ServerConfig {
-    pub enable_cors: bool,
-    pub cors_origins: Vec<String>,
-    pub request_timeout: u64,
+#[serde(default)]
+pub struct CarbonConfig {
+    pub modes: Vec<TransportMode>,
+    pub lanes: Vec<TransportLane>,
+    /// Weight of an EPC no product weight covers
+    pub default_epc_weight_kg: f64,
+    /// The first entry whose product class pattern covers an EPC gives its weight
+    pub product_weights: Vec<ProductWeight>,
+    /// Currency of the cost factors, reported alongside the totals
+    pub currency: String,
+    /// Materialize the estimates periodically while the server runs (0 = only on request)
+    pub scan_interval_seconds: u64,
+}
+
+impl Default for CarbonConfig {
+    fn default() -> Self {
+        Self {
+            modes: Vec::new(),
+            lanes: Vec::new(),
+            default_epc_weight_kg: 1.0,
+            product_weights: Vec::new(),
+            currency: "EUR".to_string(),
+            scan_interval_seconds: 0,
+        }
+    }
+}
+
+impl CarbonConfig {
+    pub fn mode(&self, name: &str) -> Option<&TransportMode> {
+        self.modes.iter().find(|mode| mode.name == name)
+    }
+
+    /// The lane between two locations, configured in either direction
+    pub fn lane(&self, origin: &str, destination: &str) -> Option<&TransportLane> {
+        self.lanes
+            .iter()
+            .find(|lane| (lane.from == origin && lane.to == destination) || (lane.from == destination && lane.to == origin))
+    }
 }
 
+/// A transport mode (`road`, `rail`, `sea`, `air`) and its factors per tonne-km
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransportMode {
+    pub name: String,
+    pub kg_co2e_per_tonne_km: f64,
+    #[serde(default)]
+    pub cost_per_tonne_km: f64,
+}
+
+/// Distance and mode between two locations; the factors override the mode's
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransportLane {
+    /// Location URIs (`urn:epc:id:sgln:...`) as used in `bizLocation`
+    pub from: String,
+    pub to: String,
+    pub mode: String,
+    pub distance_km: f64,
+    #[serde(default)]
+    pub kg_co2e_per_tonne_km: Option<f64>,
+    #[serde(default)]
+    pub cost_per_tonne_km: Option<f64>,
+}
+
+/// Shipping weight of each EPC of a product class
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProductWeight {
+    /// EPC class pattern, e.g. `urn:epc:idpat:sgtin:0614141.107346.*`
+    pub product_class: String,
+    pub kg: f64,
+}
+
+/// OpenLineage run events for data catalogs, sent when ontologies are
+/// loaded, captures complete and materialization runs finish
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PersistenceConfig {
-    pub auto_save: bool,
-    pub save_interval: u64,
-    pub backup_on_startup: bool,
+#[serde(default)]
+pub struct LineageConfig {
+    pub enabled: bool,
+    /// HTTP endpoint taking run events, e.g. Marquez's `/api/v1/lineage`
+    pub url: String,
+    /// Sent as a bearer token
+    pub api_key: Option<String>,
+    /// Namespace of the jobs
+    pub namespace: String,
+    /// Namespace of the named graphs reported as datasets
+    pub dataset_namespace: String,
+    /// URI identifying this service as the producer of the events
+    pub producer: String,
+}
+
+impl Default for LineageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: "http://localhost:5000/api/v1/lineage".to_string(),
+            api_key: None,
+            namespace: "epcis-kg".to_string(),
+            dataset_namespace: "epcis-kg".to_string(),
+            producer: "https://github.com/anusornc/epcis-cbv-with-owl2_rs".to_string(),
+        }
+    }
+}
+
+/// Registry of partner extension fields attached to events
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ExtensionsConfig {
+    /// Reject events carrying extension fields that are not declared
+    pub strict: bool,
+    pub namespaces: Vec<ExtensionNamespace>,
+}
+
+/// A partner extension namespace and the datatypes of its fields.
+///
+/// Event extension keys are either full IRIs (`https://ns.example.com/epcis/temperature`)
+/// or prefixed names (`ex:temperature`) using the namespace prefix.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtensionNamespace {
+    pub prefix: String,
+    pub namespace: String,
+    #[serde(default)]
+    pub fields: BTreeMap<String, ExtensionDatatype>,
+}
+
+/// Expected datatype of an extension field value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExtensionDatatype {
+    String,
+    Integer,
+    Decimal,
+    Boolean,
+    DateTime,
+    /// The value is an IRI (stored as a resource rather than a literal)
+    Iri,
 }
 
 impl Default for AppConfig {
@@ -48,6 +1499,7 @@ impl Default for AppConfig {
             database_path: "./data".to_string(),
             server_port: 8080,
             log_level: "info".to_string(),
+            log_targets: BTreeMap::new(),
             ontology_paths: vec![
                 "ontologies/epcis2.ttl".to_string(),
                 "ontologies/cbv.ttl".to_string(),
@@ -56,6 +1508,44 @@ impl Default for AppConfig {
             sparql: SparqlConfig::default(),
             server: ServerConfig::default(),
             persistence: PersistenceConfig::default(),
+            access_control: AccessControlConfig::default(),
+            anonymization: AnonymizationConfig::default(),
+            jsonld: JsonLdConfig::default(),
+            sessions: SessionConfig::default(),
+            extensions: ExtensionsConfig::default(),
+            inventory: InventoryConfig::default(),
+            cold_chain: ColdChainConfig::default(),
+            counterfeit: CounterfeitConfig::default(),
+            event_patterns: EventPatternsConfig::default(),
+            grpc: GrpcConfig::default(),
+            changefeed: ChangefeedConfig::default(),
+            sql: SqlConfig::default(),
+            scheduler: SchedulerConfig::default(),
+            scripting: ScriptingConfig::default(),
+            ontology_cache: OntologyCacheConfig::default(),
+            signing: SigningConfig::default(),
+            resilience: ResilienceConfig::default(),
+            entity_resolution: EntityResolutionConfig::default(),
+            batching: BatchingConfig::default(),
+            reasoning_cache: ReasoningCacheConfig::default(),
+            localization: LocalizationConfig::default(),
+            archive: ArchiveConfig::default(),
+            idempotency: IdempotencyConfig::default(),
+            auto_correction: AutoCorrectionConfig::default(),
+            encryption: EncryptionConfig::default(),
+            pii_masking: PiiMaskingConfig::default(),
+            samples: SamplesConfig::default(),
+            query_log: QueryLogConfig::default(),
+            access_log: AccessLogConfig::default(),
+            compression: CompressionConfig::default(),
+            cluster: ClusterConfig::default(),
+            snapshots: SnapshotConfig::default(),
+            strict_mode: StrictModeConfig::default(),
+            identifiers: IdentifierNormalizationConfig::default(),
+            edge: EdgeConfig::default(),
+            graph_ttl: GraphTtlConfig::default(),
+            carbon: CarbonConfig::default(),
+            lineage: LineageConfig::default(),
         }
     }
 }
@@ -66,6 +1556,21 @@ impl Default for ReasoningConfig {
             default_profile: "el".to_string(),
             enable_inference: true,
             max_inference_time: 30,
+            warm_start: true,
+            cache_dir: "./data/reasoner_cache".to_string(),
+            pool_size: 4,
+            parallel_classification_min_classes: 2_000,
+            scope: InferenceScopeConfig::default(),
+        }
+    }
+}
+
+impl Default for InferenceScopeConfig {
+    fn default() -> Self {
+        Self {
+            tbox_graphs: vec!["urn:epcis:ontology:*".to_string()],
+            abox_graphs: vec!["*".to_string()],
+            exclude_graphs: Vec::new(),
         }
     }
 }
@@ -76,6 +1581,7 @@ impl Default for SparqlConfig {
             max_query_time: 60,
             max_results: 1000,
             enable_updates: true,
+            max_batch_size: 50,
         }
     }
 }
@@ -86,6 +1592,10 @@ impl Default for ServerConfig {
             enable_cors: true,
             cors_origins: vec!["*".to_string()],
             request_timeout: 30,
+            max_status_epcs: default_max_status_epcs(),
+            max_body_bytes: default_max_body_bytes(),
+            max_upload_bytes: default_max_upload_bytes(),
+            upload_dir: default_upload_dir(),
         }
     }
 }
@@ -100,6 +1610,45 @@ impl Default for PersistenceConfig {
     }
 }
 
+impl Default for AccessControlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            api_keys: Vec::new(),
+            quota_state_path: "./data/quota_usage.json".to_string(),
+        }
+    }
+}
+
+impl Default for AnonymizationConfig {
+    fn default() -> Self {
+        let mut predicates = HashMap::new();
+        for name_predicate in [
+            "http://example.com/name",
+            "http://www.w3.org/2000/01/rdf-schema#label",
+        ] {
+            predicates.insert(name_predicate.to_string(), PseudonymizationRule::EntityName);
+        }
+        for sensitive_predicate in [
+            "http://example.com/taxId",
+            "http://example.com/email",
+            "http://example.com/phone",
+            "http://example.com/address",
+            "http://example.com/coordinates",
+        ] {
+            predicates.insert(sensitive_predicate.to_string(), PseudonymizationRule::Hash);
+        }
+
+        Self {
+            key_env: "EPCIS_ANONYMIZATION_KEY".to_string(),
+            pseudonymize_epcs: true,
+            pseudonymize_glns: true,
+            exclude_graphs: vec!["urn:epcis:ontology:*".to_string()],
+            predicates,
+        }
+    }
+}
+
 impl AppConfig {
     /// Load configuration from a TOML file
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, EpcisKgError> {
@@ -173,6 +1722,14 @@ impl AppConfig {
                 )));
             }
         }
+        for (target, level) in &self.log_targets {
+            if !matches!(level.as_str(), "trace" | "debug" | "info" | "warn" | "error" | "off") {
+                return Err(EpcisKgError::Config(format!(
+                    "Invalid log level for target {}: {}. Must be one of: trace, debug, info, warn, error, off",
+                    target, level
+                )));
+            }
+        }
 
         // Validate reasoning profile
         match self.reasoning.default_profile.as_str() {
@@ -204,12 +1761,326 @@ impl AppConfig {
             ));
         }
 
+        if self.server.max_status_epcs == 0 {
+            return Err(EpcisKgError::Config(
+                "Max status EPCs must be greater than 0".to_string(),
+            ));
+        }
+
         if self.persistence.save_interval == 0 {
             return Err(EpcisKgError::Config(
                 "Save interval must be greater than 0".to_string(),
             ));
         }
 
+        let weights = [
+            self.counterfeit.concurrent_custody_weight,
+            self.counterfeit.never_commissioned_weight,
+            self.counterfeit.verification_failure_weight,
+            self.counterfeit.suspect_threshold,
+        ];
+        if weights.iter().any(|weight| !(0.0..=1.0).contains(weight)) {
+            return Err(EpcisKgError::Config(
+                "Counterfeit weights and threshold must be between 0 and 1".to_string(),
+            ));
+        }
+
+        let resolution = &self.entity_resolution;
+        if let Some(key) = resolution.keys.iter().find(|key| !["gln", "gtin", "name"].contains(&key.as_str())) {
+            return Err(EpcisKgError::Config(format!(
+                "Unknown entity resolution key: {} (expected gln, gtin or name)",
+                key
+            )));
+        }
+        let weights = [
+            resolution.gln_weight,
+            resolution.gtin_weight,
+            resolution.name_weight,
+            resolution.min_name_similarity,
+            resolution.min_confidence,
+        ];
+        if weights.iter().any(|weight| !(0.0..=1.0).contains(weight)) {
+            return Err(EpcisKgError::Config(
+                "Entity resolution weights and thresholds must be between 0 and 1".to_string(),
+            ));
+        }
+
+        for profile in &self.cold_chain.profiles {
+            if profile.min_celsius > profile.max_celsius {
+                return Err(EpcisKgError::Config(format!(
+                    "Temperature profile for {} has min_celsius above max_celsius",
+                    profile.product_class
+                )));
+            }
+        }
+
+        let mut rule_names = std::collections::HashSet::new();
+        for rule in &self.event_patterns.rules {
+            if !rule_names.insert(rule.name()) {
+                return Err(EpcisKgError::Config(format!("Duplicate event pattern rule: {}", rule.name())));
+            }
+            if rule.window_minutes() <= 0 {
+                return Err(EpcisKgError::Config(format!(
+                    "Event pattern rule {} needs a positive window_minutes",
+                    rule.name()
+                )));
+            }
+        }
+
+        for policy in &self.graph_ttl.policies {
+            if policy.graphs.is_empty() || policy.graphs == "*" || policy.ttl_seconds == 0 || policy.time_predicate.is_empty() {
+                return Err(EpcisKgError::Config(format!(
+                    "Graph TTL policy '{}' needs a graph pattern narrower than '*', a time predicate and a ttl_seconds of at least 1",
+                    policy.graphs
+                )));
+            }
+        }
+
+        let mut mode_names = std::collections::HashSet::new();
+        for mode in &self.carbon.modes {
+            if !mode_names.insert(mode.name.as_str()) {
+                return Err(EpcisKgError::Config(format!("Duplicate transport mode: {}", mode.name)));
+            }
+            if mode.kg_co2e_per_tonne_km < 0.0 || mode.cost_per_tonne_km < 0.0 {
+                return Err(EpcisKgError::Config(format!("Transport mode {} has a negative factor", mode.name)));
+            }
+        }
+        for lane in &self.carbon.lanes {
+            if self.carbon.mode(&lane.mode).is_none() {
+                return Err(EpcisKgError::Config(format!(
+                    "Lane {} -> {} uses unknown transport mode {}",
+                    lane.from, lane.to, lane.mode
+                )));
+            }
+            let factors = [Some(lane.distance_km), lane.kg_co2e_per_tonne_km, lane.cost_per_tonne_km];
+            if factors.iter().flatten().any(|factor| *factor < 0.0) {
+                return Err(EpcisKgError::Config(format!(
+                    "Lane {} -> {} has a negative distance or factor",
+                    lane.from, lane.to
+                )));
+            }
+        }
+        if self.carbon.default_epc_weight_kg < 0.0 || self.carbon.product_weights.iter().any(|weight| weight.kg < 0.0) {
+            return Err(EpcisKgError::Config(
+                "EPC weights must not be negative".to_string(),
+            ));
+        }
+
+        if self.lineage.enabled && (self.lineage.url.is_empty() || self.lineage.namespace.is_empty() || self.lineage.dataset_namespace.is_empty()) {
+            return Err(EpcisKgError::Config(
+                "Lineage needs a URL, a job namespace and a dataset namespace".to_string(),
+            ));
+        }
+
+        if self.changefeed.enabled && (self.changefeed.brokers.is_empty() || self.changefeed.events_topic.is_empty()) {
+            return Err(EpcisKgError::Config(
+                "Changefeed needs brokers and an events topic".to_string(),
+            ));
+        }
+
+        let mut job_names = std::collections::HashSet::new();
+        for job in &self.scheduler.jobs {
+            if !job_names.insert(job.name.as_str()) {
+                return Err(EpcisKgError::Config(format!("Duplicate scheduled job: {}", job.name)));
+            }
+            crate::scheduler::cron::Schedule::parse(&job.schedule)?;
+            if let JobTask::Backup { directory, keep } = &job.task {
+                if directory.is_empty() || *keep == 0 {
+                    return Err(EpcisKgError::Config(format!(
+                        "Backup job {} needs a directory and keep of at least 1",
+                        job.name
+                    )));
+                }
+            }
+        }
+
+        if self.scripting.enabled && (self.scripting.scripts_dir.is_empty() || self.scripting.max_operations == 0) {
+            return Err(EpcisKgError::Config(
+                "Scripting needs a scripts directory and a positive max_operations".to_string(),
+            ));
+        }
+
+        if !matches!(self.signing.algorithm.as_str(), "EdDSA" | "HS256") {
+            return Err(EpcisKgError::Config(format!(
+                "Unsupported signing algorithm: {} (expected EdDSA or HS256)",
+                self.signing.algorithm
+            )));
+        }
+
+        let policies = std::iter::once(("default", &self.resilience.default))
+            .chain(self.resilience.integrations.iter().map(|(name, policy)| (name.as_str(), policy)));
+        for (name, policy) in policies {
+            if policy.max_attempts == 0 || policy.failure_threshold == 0 {
+                return Err(EpcisKgError::Config(format!(
+                    "Resilience policy {} needs positive max_attempts and failure_threshold",
+                    name
+                )));
+            }
+            if policy.backoff_multiplier < 1.0 || !(0.0..=1.0).contains(&policy.jitter) {
+                return Err(EpcisKgError::Config(format!(
+                    "Resilience policy {} needs backoff_multiplier >= 1 and jitter between 0 and 1",
+                    name
+                )));
+            }
+        }
+
+        for (name, policy) in [("ingestion", &self.batching.ingestion), ("inference", &self.batching.inference)] {
+            if policy.min_size == 0 || policy.min_size > policy.max_size || policy.target_latency_ms == 0 {
+                return Err(EpcisKgError::Config(format!(
+                    "Batching policy {} needs 0 < min_size <= max_size and a positive target_latency_ms",
+                    name
+                )));
+            }
+        }
+
+        if self.localization.default_language.trim().is_empty() {
+            return Err(EpcisKgError::Config(
+                "Localization default_language cannot be empty".to_string(),
+            ));
+        }
+
+        if self.archive.directory.is_empty() || self.archive.max_loaded == 0 {
+            return Err(EpcisKgError::Config(
+                "Archive needs a directory and max_loaded of at least 1".to_string(),
+            ));
+        }
+
+        if !(0.0..=1.0).contains(&self.auto_correction.min_confidence) {
+            return Err(EpcisKgError::Config(
+                "Auto-correction min_confidence must be between 0 and 1".to_string(),
+            ));
+        }
+
+        if self.encryption.enabled && !matches!(self.encryption.key_source.as_str(), "config" | "env" | "command") {
+            return Err(EpcisKgError::Config(
+                "Encryption key_source must be config, env or command".to_string(),
+            ));
+        }
+
+        if self.samples.release_url.as_deref().is_some_and(|url| !url.starts_with("https://") && !url.starts_with("http://")) {
+            return Err(EpcisKgError::Config(
+                "Samples release_url must be an http(s) URL".to_string(),
+            ));
+        }
+
+        if self.server.max_body_bytes == 0 || self.server.max_upload_bytes == 0 {
+            return Err(EpcisKgError::Config(
+                "Server body and upload limits must be greater than 0".to_string(),
+            ));
+        }
+
+        if self.pii_masking.enabled && self.pii_masking.policies.iter().any(|policy| policy.predicate.is_empty()) {
+            return Err(EpcisKgError::Config(
+                "PII masking policies need a predicate".to_string(),
+            ));
+        }
+
+        if self.idempotency.enabled && (self.idempotency.state_path.is_empty() || self.idempotency.ttl_secs == 0) {
+            return Err(EpcisKgError::Config(
+                "Idempotency needs a state_path and a ttl_secs of at least 1".to_string(),
+            ));
+        }
+
+        if self.query_log.enabled && self.query_log.capacity == 0 {
+            return Err(EpcisKgError::Config(
+                "Query log capacity must be at least 1".to_string(),
+            ));
+        }
+
+        if self.compression.enabled && !self.compression.gzip && !self.compression.brotli {
+            return Err(EpcisKgError::Config(
+                "Compression needs gzip or brotli enabled".to_string(),
+            ));
+        }
+
+        if self.access_log.enabled && (self.access_log.path.is_empty() || self.access_log.max_file_size_mb == 0) {
+            return Err(EpcisKgError::Config(
+                "Access log needs a path and a max_file_size_mb of at least 1".to_string(),
+            ));
+        }
+
+        if self.cluster.shards == 0 {
+            return Err(EpcisKgError::Config(
+                "Cluster shards must be at least 1".to_string(),
+            ));
+        }
+
+        if self.cluster.role == ClusterRole::Coordinator
+            && (self.cluster.workers.is_empty() || self.cluster.workers.len() > self.cluster.shards as usize)
+        {
+            return Err(EpcisKgError::Config(
+                "A cluster coordinator needs between 1 and `shards` workers".to_string(),
+            ));
+        }
+
+        if !(6..=12).contains(&self.identifiers.company_prefix_length) {
+            return Err(EpcisKgError::Config(
+                "GS1 Company Prefix length must be between 6 and 12 digits".to_string(),
+            ));
+        }
+
+        if let Some(prefix) = self
+            .identifiers
+            .company_prefixes
+            .iter()
+            .find(|prefix| !(6..=12).contains(&prefix.len()) || !prefix.chars().all(|c| c.is_ascii_digit()))
+        {
+            return Err(EpcisKgError::Config(format!(
+                "Invalid GS1 Company Prefix '{}': expected 6 to 12 digits",
+                prefix
+            )));
+        }
+
+        if self.edge.batch_size == 0 || self.edge.forward_interval_seconds == 0 {
+            return Err(EpcisKgError::Config(
+                "Edge batch size and forward interval must be greater than 0".to_string(),
+            ));
+        }
+
+        if !self.edge.central_url.is_empty()
+            && !self.edge.central_url.starts_with("http://")
+            && !self.edge.central_url.starts_with("https://")
+        {
+            return Err(EpcisKgError::Config(format!(
+                "Edge central_url must be an http(s) URL: {}",
+                self.edge.central_url
+            )));
+        }
+
+        if self.inventory.snapshots_enabled && self.inventory.snapshot_interval_seconds == 0 {
+            return Err(EpcisKgError::Config(
+                "Inventory snapshot interval must be greater than 0".to_string(),
+            ));
+        }
+
+        // Validate access control entries
+        if self.access_control.enabled && self.access_control.api_keys.is_empty() {
+            return Err(EpcisKgError::Config(
+                "Access control is enabled but no API keys are configured".to_string(),
+            ));
+        }
+
+        let mut seen_keys = std::collections::HashSet::new();
+        for acl in &self.access_control.api_keys {
+            if acl.key.is_empty() {
+                return Err(EpcisKgError::Config(
+                    "Access control API keys cannot be empty".to_string(),
+                ));
+            }
+            if !seen_keys.insert(acl.key.as_str()) {
+                return Err(EpcisKgError::Config(format!(
+                    "Duplicate access control API key for entry: {}",
+                    acl.name
+                )));
+            }
+            if matches!(&acl.quota, Some(TenantQuota { max_query_seconds: Some(seconds), .. }) if *seconds < 0.0) {
+                return Err(EpcisKgError::Config(format!(
+                    "Query time quota for {} cannot be negative",
+                    acl.name
+                )));
+            }
+        }
+
         Ok(())
     }
 
@@ -259,6 +2130,25 @@ mod tests {
         config.reasoning.default_profile = "el".to_string();
         config.server_port = 0;
         assert!(config.validate().is_err());
+
+        // Access control without any keys should fail
+        config.server_port = 8080;
+        config.access_control.enabled = true;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_access_control_section_is_optional() {
+        let content = toml::to_string_pretty(&AppConfig::default()).unwrap();
+        let without_acl: String = content
+            .split("[access_control]")
+            .next()
+            .unwrap()
+            .to_string();
+
+        let config: AppConfig = toml::from_str(&without_acl).unwrap();
+        assert!(!config.access_control.enabled);
+        assert!(config.access_control.api_keys.is_empty());
     }
 
     #[test]
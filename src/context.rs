@@ -0,0 +1,91 @@
+//! The loaded configuration, carried to every component built from it.
+//!
+//! Components used to fall back to `Config::default()` when a caller did not
+//! pass the configuration along, so settings such as the ontology cache,
+//! reasoning caches or batching silently reverted to their defaults.
+//! `AppContext` is created once from the configuration file and hands out
+//! loaders, reasoners, pipelines and the server configured alike.
+
+use crate::api::server::WebServer;
+use crate::config::AppConfig;
+use crate::ontology::loader::OntologyLoader;
+use crate::ontology::reasoner::OntologyReasoner;
+use crate::pipeline::EpcisEventPipeline;
+use crate::storage::oxigraph_store::OxigraphStore;
+use crate::EpcisKgError;
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct AppContext {
+    config: Arc<AppConfig>,
+}
+
+impl AppContext {
+    pub fn new(config: AppConfig) -> Self {
+        Self { config: Arc::new(config) }
+    }
+
+    /// Load and validate a configuration file, or the defaults when it does not exist
+    pub fn from_file_or_default(path: &str) -> Result<Self, EpcisKgError> {
+        let config = AppConfig::from_file_or_default(path)?;
+        config.validate()?;
+        Ok(Self::new(config))
+    }
+
+    pub fn config(&self) -> &AppConfig {
+        &self.config
+    }
+
+    pub fn shared_config(&self) -> Arc<AppConfig> {
+        Arc::clone(&self.config)
+    }
+
+    pub fn loader(&self) -> OntologyLoader {
+        OntologyLoader::with_config(&self.config)
+    }
+
+    pub fn reasoner(&self) -> OntologyReasoner {
+        OntologyReasoner::with_config(&self.config)
+    }
+
+    pub fn reasoner_with_store(&self, store: OxigraphStore) -> OntologyReasoner {
+        OntologyReasoner::with_config_and_store(&self.config, store)
+    }
+
+    pub async fn pipeline(&self, store: OxigraphStore, reasoner: OntologyReasoner) -> Result<EpcisEventPipeline, EpcisKgError> {
+        EpcisEventPipeline::new(self.config().clone(), store, reasoner).await
+    }
+
+    pub async fn web_server(&self, store: OxigraphStore) -> Result<WebServer, EpcisKgError> {
+        WebServer::new(self.config().clone(), store).await
+    }
+}
+
+impl Default for AppContext {
+    fn default() -> Self {
+        Self::new(AppConfig::default())
+    }
+}
+
+impl From<AppConfig> for AppContext {
+    fn from(config: AppConfig) -> Self {
+        Self::new(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_components_share_the_loaded_config() {
+        let mut config = AppConfig::default();
+        config.database_path = "./data/context-test".to_string();
+        let context = AppContext::new(config);
+        let clone = context.clone();
+
+        assert!(Arc::ptr_eq(&context.shared_config(), &clone.shared_config()));
+        assert_eq!(clone.config().database_path, "./data/context-test");
+        assert_eq!(AppContext::default().config().database_path, AppConfig::default().database_path);
+    }
+}
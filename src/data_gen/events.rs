@@ -1,49 +1,21 @@
-use serde::{Serialize, Deserialize};
+//! Synthetic supply chain events.
+//!
+//! Events are generated as the shared `models::epcis::EpcisEvent`, so a
+//! generated dataset can be fed straight to the event pipeline and the API.
+//! Business steps and dispositions are CBV short names; shipments carry their
+//! origin and destination as location sources and destinations.
+
 use chrono::Utc;
-use uuid::Uuid;
 use crate::data_gen::entities::{Location, Product, BusinessEntity};
-
-/// EPCIS Event types
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum EventType {
-    ObjectEvent,
-    AggregationEvent,
-    QuantityEvent,
-    TransactionEvent,
-    TransformationEvent,
-}
-
-/// EPCIS Event
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct EpcisEvent {
-    pub uri: String,
-    pub event_type: EventType,
-    pub event_time: String,
-    pub record_time: String,
-    pub event_id: String,
-    pub action: String,
-    pub biz_step: String,
-    pub disposition: String,
-    pub epc_list: Vec<String>,
-    pub read_point: Option<String>,
-    pub biz_location: Option<String>,
-    pub quantity: Option<u32>,
-    pub business_transaction_list: Vec<BusinessTransaction>,
-}
-
-/// Business transaction reference
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct BusinessTransaction {
-    pub transaction_type: String,
-    pub transaction_id: String,
-}
+use crate::models::epcis::EpcisEvent;
+use crate::models::parties::SourceDestination;
 
 /// Supply chain journey step
 #[derive(Debug, Clone)]
 pub struct JourneyStep {
     pub from_location: String,
     pub to_location: String,
-    pub event_type: EventType,
+    pub event_type: String,
     pub biz_step: String,
     pub estimated_duration_hours: u32,
 }
@@ -59,32 +31,32 @@ impl EventGenerator {
     pub fn new() -> Self {
         Self {
             business_steps: vec![
-                "urn:epcglobal:cbv:bizstep:commissioning".to_string(),
-                "urn:epcglobal:cbv:bizstep:encoding".to_string(),
-                "urn:epcglobal:cbv:bizstep:manufacturing".to_string(),
-                "urn:epcglobal:cbv:bizstep:testing".to_string(),
-                "urn:epcglobal:cbv:bizstep:quality_control".to_string(),
-                "urn:epcglobal:cbv:bizstep:packing".to_string(),
-                "urn:epcglobal:cbv:bizstep:shipping".to_string(),
-                "urn:epcglobal:cbv:bizstep:receiving".to_string(),
-                "urn:epcglobal:cbv:bizstep:storing".to_string(),
-                "urn:epcglobal:cbv:bizstep:inventory_check".to_string(),
-                "urn:epcglobal:cbv:bizstep:pricing".to_string(),
-                "urn:epcglobal:cbv:bizstep:displaying".to_string(),
-                "urn:epcglobal:cbv:bizstep:selling".to_string(),
-                "urn:epcglobal:cbv:bizstep:customer_pickup".to_string(),
+                "commissioning".to_string(),
+                "encoding".to_string(),
+                "manufacturing".to_string(),
+                "testing".to_string(),
+                "quality_control".to_string(),
+                "packing".to_string(),
+                "shipping".to_string(),
+                "receiving".to_string(),
+                "storing".to_string(),
+                "inventory_check".to_string(),
+                "pricing".to_string(),
+                "displaying".to_string(),
+                "selling".to_string(),
+                "customer_pickup".to_string(),
             ],
             dispositions: vec![
-                "urn:epcglobal:cbv:disp:in_progress".to_string(),
-                "urn:epcglobal:cbv:disp:active".to_string(),
-                "urn:epcglobal:cbv:disp:inactive".to_string(),
-                "urn:epcglobal:cbv:disp:expired".to_string(),
-                "urn:epcglobal:cbv:disp:damaged".to_string(),
-                "urn:epcglobal:cbv:disp:inspected".to_string(),
-                "urn:epcglobal:cbv:disp:certified".to_string(),
-                "urn:epcglobal:cbv:disp:in_transit".to_string(),
-                "urn:epcglobal:cbv:disp:owned".to_string(),
-                "urn:epcglobal:cbv:disp:consigned".to_string(),
+                "in_progress".to_string(),
+                "active".to_string(),
+                "inactive".to_string(),
+                "expired".to_string(),
+                "damaged".to_string(),
+                "inspected".to_string(),
+                "certified".to_string(),
+                "in_transit".to_string(),
+                "owned".to_string(),
+                "consigned".to_string(),
             ],
             actions: vec![
                 "ADD".to_string(),
@@ -145,28 +117,25 @@ impl EventGenerator {
             
             // Manufacturing process sequence
             let biz_step = match i % 5 {
-                0 => "urn:epcglobal:cbv:bizstep:manufacturing",
-                1 => "urn:epcglobal:cbv:bizstep:testing",
-                2 => "urn:epcglobal:cbv:bizstep:quality_control",
-                3 => "urn:epcglobal:cbv:bizstep:commissioning",
-                4 => "urn:epcglobal:cbv:bizstep:encoding",
-                _ => "urn:epcglobal:cbv:bizstep:manufacturing",
+                0 => "manufacturing",
+                1 => "testing",
+                2 => "quality_control",
+                3 => "commissioning",
+                4 => "encoding",
+                _ => "manufacturing",
             };
             
             events.push(EpcisEvent {
-                uri: format!("http://example.com/event/manufacturing/{}", Uuid::new_v4()),
-                event_type: EventType::ObjectEvent,
+                event_id: format!("MANUF-{:08}", i + 1),
+                event_type: "ObjectEvent".to_string(),
                 event_time: event_time.to_rfc3339(),
                 record_time: (event_time + chrono::Duration::minutes(5)).to_rfc3339(),
-                event_id: format!("MANUF-{:08}", i + 1),
-                action: "ADD".to_string(),
-                biz_step: biz_step.to_string(),
-                disposition: "urn:epcglobal:cbv:disp:in_progress".to_string(),
+                event_action: "ADD".to_string(),
                 epc_list: vec![product.epc.clone()],
-                read_point: Some(format!("{}/line{}", factory.uri, i % 10 + 1)),
+                biz_step: Some(biz_step.to_string()),
+                disposition: Some("in_progress".to_string()),
                 biz_location: Some(factory.uri.clone()),
-                quantity: Some(1),
-                business_transaction_list: vec![],
+                ..Default::default()
             });
         }
         
@@ -187,19 +156,19 @@ impl EventGenerator {
         
         for i in 0..count {
             let product = &products[i % products.len()];
-            let event_time = Utc::now() - chrono::Duration::days((count / 2 - i) as i64);
+            let event_time = Utc::now() - chrono::Duration::days((count / 2) as i64 - i as i64);
             
             // Create realistic logistics flow
             let (from_location, to_location, biz_step) = if i % 3 == 0 && !warehouses.is_empty() {
                 // Factory to warehouse
                 let factory = locations.iter().find(|l| l.location_type == "Factory").unwrap();
                 let warehouse = &warehouses[i % warehouses.len()];
-                (factory.uri.clone(), warehouse.uri.clone(), "urn:epcglobal:cbv:bizstep:shipping")
+                (factory.uri.clone(), warehouse.uri.clone(), "shipping")
             } else if i % 3 == 1 && !distribution_centers.is_empty() {
                 // Warehouse to distribution center
                 let warehouse = &warehouses[i % warehouses.len()];
                 let dc = &distribution_centers[i % distribution_centers.len()];
-                (warehouse.uri.clone(), dc.uri.clone(), "urn:epcglobal:cbv:bizstep:transporting")
+                (warehouse.uri.clone(), dc.uri.clone(), "transporting")
             } else {
                 // Distribution center to retail
                 let dc = if distribution_centers.is_empty() {
@@ -208,26 +177,22 @@ impl EventGenerator {
                     &distribution_centers[i % distribution_centers.len()]
                 };
                 let retail = locations.iter().find(|l| l.location_type == "RetailStore").unwrap();
-                (dc.uri.clone(), retail.uri.clone(), "urn:epcglobal:cbv:bizstep:receiving")
+                (dc.uri.clone(), retail.uri.clone(), "receiving")
             };
             
             events.push(EpcisEvent {
-                uri: format!("http://example.com/event/logistics/{}", Uuid::new_v4()),
-                event_type: EventType::ObjectEvent,
+                event_id: format!("LOGIS-{:08}", i + 1),
+                event_type: "ObjectEvent".to_string(),
                 event_time: event_time.to_rfc3339(),
                 record_time: (event_time + chrono::Duration::minutes(10)).to_rfc3339(),
-                event_id: format!("LOGIS-{:08}", i + 1),
-                action: "OBSERVE".to_string(),
-                biz_step: biz_step.to_string(),
-                disposition: "urn:epcglobal:cbv:disp:in_transit".to_string(),
+                event_action: "OBSERVE".to_string(),
                 epc_list: vec![product.epc.clone()],
-                read_point: Some(format!("{}/dock{}", to_location, i % 5 + 1)),
-                biz_location: Some(from_location),
-                quantity: Some(1),
-                business_transaction_list: vec![BusinessTransaction {
-                    transaction_type: "urn:epcglobal:cbv:btt:po".to_string(),
-                    transaction_id: format!("PO-{:08}", i + 1),
-                }],
+                biz_step: Some(biz_step.to_string()),
+                disposition: Some("in_transit".to_string()),
+                biz_location: Some(from_location.clone()),
+                source_list: vec![SourceDestination { kind: "location".to_string(), id: from_location }],
+                destination_list: vec![SourceDestination { kind: "location".to_string(), id: to_location }],
+                ..Default::default()
             });
         }
         
@@ -251,57 +216,47 @@ impl EventGenerator {
         for i in 0..count {
             let product = &products[i % products.len()];
             let store = &retail_stores[i % retail_stores.len()];
-            let event_time = Utc::now() - chrono::Duration::days((count / 4 - i) as i64);
+            let event_time = Utc::now() - chrono::Duration::days((count / 4) as i64 - i as i64);
             
             let (biz_step, disposition, action) = match i % 4 {
                 0 => (
-                    "urn:epcglobal:cbv:bizstep:pricing",
-                    "urn:epcglobal:cbv:disp:active",
+                    "pricing",
+                    "active",
                     "OBSERVE"
                 ),
                 1 => (
-                    "urn:epcglobal:cbv:bizstep:displaying",
-                    "urn:epcglobal:cbv:disp:active",
+                    "displaying",
+                    "active",
                     "OBSERVE"
                 ),
                 2 => (
-                    "urn:epcglobal:cbv:bizstep:selling",
-                    "urn:epcglobal:cbv:disp:sold",
+                    "selling",
+                    "sold",
                     "DELETE"
                 ),
                 3 => (
-                    "urn:epcglobal:cbv:bizstep:customer_pickup",
-                    "urn:epcglobal:cbv:disp:owned",
+                    "customer_pickup",
+                    "owned",
                     "DELETE"
                 ),
                 _ => (
-                    "urn:epcglobal:cbv:bizstep:pricing",
-                    "urn:epcglobal:cbv:disp:active",
+                    "pricing",
+                    "active",
                     "OBSERVE"
                 ),
             };
             
             events.push(EpcisEvent {
-                uri: format!("http://example.com/event/retail/{}", Uuid::new_v4()),
-                event_type: EventType::ObjectEvent,
+                event_id: format!("RETAIL-{:08}", i + 1),
+                event_type: "ObjectEvent".to_string(),
                 event_time: event_time.to_rfc3339(),
                 record_time: (event_time + chrono::Duration::minutes(2)).to_rfc3339(),
-                event_id: format!("RETAIL-{:08}", i + 1),
-                action: action.to_string(),
-                biz_step: biz_step.to_string(),
-                disposition: disposition.to_string(),
+                event_action: action.to_string(),
                 epc_list: vec![product.epc.clone()],
-                read_point: Some(format!("{}/shelf{}", store.uri, i % 20 + 1)),
+                biz_step: Some(biz_step.to_string()),
+                disposition: Some(disposition.to_string()),
                 biz_location: Some(store.uri.clone()),
-                quantity: Some(1),
-                business_transaction_list: if action == "DELETE" {
-                    vec![BusinessTransaction {
-                        transaction_type: "urn:epcglobal:cbv:btt:inv".to_string(),
-                        transaction_id: format!("INV-{:08}", i + 1),
-                    }]
-                } else {
-                    vec![]
-                },
+                ..Default::default()
             });
         }
         
@@ -320,30 +275,27 @@ impl EventGenerator {
         for i in 0..count {
             let product = &products[i % products.len()];
             let location = &locations[i % locations.len()];
-            let event_time = Utc::now() - chrono::Duration::days((count / 6 - i) as i64);
+            let event_time = Utc::now() - chrono::Duration::days((count / 6) as i64 - i as i64);
             
             let (biz_step, disposition) = if i % 5 == 0 {
                 // Failed inspection
-                ("urn:epcglobal:cbv:bizstep:testing", "urn:epcglobal:cbv:disp:damaged")
+                ("testing", "damaged")
             } else {
                 // Passed inspection
-                ("urn:epcglobal:cbv:bizstep:quality_control", "urn:epcglobal:cbv:disp:certified")
+                ("quality_control", "certified")
             };
             
             events.push(EpcisEvent {
-                uri: format!("http://example.com/event/quality/{}", Uuid::new_v4()),
-                event_type: EventType::ObjectEvent,
+                event_id: format!("QUAL-{:08}", i + 1),
+                event_type: "ObjectEvent".to_string(),
                 event_time: event_time.to_rfc3339(),
                 record_time: (event_time + chrono::Duration::minutes(15)).to_rfc3339(),
-                event_id: format!("QUAL-{:08}", i + 1),
-                action: "OBSERVE".to_string(),
-                biz_step: biz_step.to_string(),
-                disposition: disposition.to_string(),
+                event_action: "OBSERVE".to_string(),
                 epc_list: vec![product.epc.clone()],
-                read_point: Some(format!("{}/qc{}", location.uri, i % 3 + 1)),
+                biz_step: Some(biz_step.to_string()),
+                disposition: Some(disposition.to_string()),
                 biz_location: Some(location.uri.clone()),
-                quantity: Some(1),
-                business_transaction_list: vec![],
+                ..Default::default()
             });
         }
         
@@ -367,32 +319,49 @@ impl EventGenerator {
         let retail_store = locations.iter().find(|l| l.location_type == "RetailStore").unwrap();
         
         let journey_locations = vec![
-            (factory.uri.clone(), "urn:epcglobal:cbv:bizstep:manufacturing"),
-            (warehouse.uri.clone(), "urn:epcglobal:cbv:bizstep:receiving"),
-            (distribution_center.uri.clone(), "urn:epcglobal:cbv:bizstep:storing"),
-            (retail_store.uri.clone(), "urn:epcglobal:cbv:bizstep:displaying"),
+            (factory.uri.clone(), "manufacturing"),
+            (warehouse.uri.clone(), "receiving"),
+            (distribution_center.uri.clone(), "storing"),
+            (retail_store.uri.clone(), "displaying"),
         ];
         
         for (i, (location_uri, biz_step)) in journey_locations.iter().enumerate().take(journey_steps) {
             current_time += chrono::Duration::hours(i as i64 * 24);
             
             events.push(EpcisEvent {
-                uri: format!("http://example.com/event/journey/{}", Uuid::new_v4()),
-                event_type: EventType::ObjectEvent,
+                event_id: format!("JOURNEY-{:08}-{:02}", i + 1, journey_steps),
+                event_type: "ObjectEvent".to_string(),
                 event_time: current_time.to_rfc3339(),
                 record_time: (current_time + chrono::Duration::minutes(5)).to_rfc3339(),
-                event_id: format!("JOURNEY-{:08}-{:02}", i + 1, journey_steps),
-                action: if i == 0 { "ADD" } else { "OBSERVE" }.to_string(),
-                biz_step: biz_step.to_string(),
-                disposition: "urn:epcglobal:cbv:disp:in_progress".to_string(),
+                event_action: if i == 0 { "ADD" } else { "OBSERVE" }.to_string(),
                 epc_list: vec![product.epc.clone()],
-                read_point: Some(format!("{}/step{}", location_uri, i + 1)),
+                biz_step: Some(biz_step.to_string()),
+                disposition: Some("in_progress".to_string()),
                 biz_location: Some(location_uri.clone()),
-                quantity: Some(1),
-                business_transaction_list: vec![],
+                ..Default::default()
             });
         }
         
         Ok(events)
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_gen::entities::{LocationGenerator, ProductGenerator};
+    use crate::models::events::EventProcessor;
+
+    #[test]
+    fn test_generated_events_pass_pipeline_validation() {
+        let locations = LocationGenerator::new().generate_supply_chain_network(20).unwrap();
+        let products = ProductGenerator::new().generate_product_catalog(5).unwrap();
+        let events = EventGenerator::new().generate_supply_chain_events(&products, &locations, &[], 24).unwrap();
+        assert!(!events.is_empty());
+
+        let processor = EventProcessor::new();
+        for event in &events {
+            let result = processor.validate_event(event).unwrap();
+            assert!(result.is_valid, "{}: {:?}", event.event_id, result.errors);
+        }
+    }
+}
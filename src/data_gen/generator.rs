@@ -1,10 +1,16 @@
 use super::{GeneratorConfig, GenerationResult, DataGenerator};
 use crate::data_gen::entities::{LocationGenerator, ProductGenerator, BusinessEntityGenerator};
 use crate::data_gen::events::EventGenerator;
-use crate::data_gen::utils::formatters::{TurtleFormatter, DataFormatter};
+use crate::data_gen::integrity;
+use crate::data_gen::utils::formatters::{convert_event_to_triples, TurtleFormatter, DataFormatter};
+use crate::models::epcis::EpcisEvent;
 use std::time::Instant;
 use std::fs;
 
+const LOCATION_CLASS: &str = "http://example.com/Location";
+/// Class of the EPC-identified instances events refer to
+const ITEM_CLASS: &str = "http://example.com/Item";
+
 /// Main data generator for EPCIS knowledge graph
 pub struct EpcisDataGenerator {
     location_gen: LocationGenerator,
@@ -44,8 +50,8 @@ impl EpcisDataGenerator {
         println!("  - Events: {}", event_count);
         
         // Generate entities
-        let locations = self.location_gen.generate_supply_chain_network(location_count)?;
-        let products = self.product_gen.generate_product_catalog(product_count)?;
+        let mut locations = self.location_gen.generate_supply_chain_network(location_count)?;
+        let mut products = self.product_gen.generate_product_catalog(product_count)?;
         let business_entities = self.business_gen.generate_business_entities(20)?;
         
         // Generate events
@@ -53,14 +59,26 @@ impl EpcisDataGenerator {
             &products, &locations, &business_entities, event_count
         )?;
         
+        // Optionally drop master data that no event refers to
+        if config.referenced_master_data_only {
+            let referenced = integrity::referenced_ids(&events);
+            locations.retain(|location| referenced.contains(&location.uri));
+            products.retain(|product| referenced.contains(&product.epc));
+        }
+        
         // Convert to RDF triples
         let mut all_triples = Vec::new();
         
         // Add ontology triples first
         all_triples.extend(self.generate_ontology_triples());
         
-        // Add entity triples
-        all_triples.extend(self.generate_entity_triples(&locations, &products, &business_entities));
+        // Add entity triples, then check that every event reference has master data
+        let entity_triples = self.generate_entity_triples(&locations, &products, &business_entities);
+        let integrity = integrity::check(&events, &entity_triples, &[ITEM_CLASS, LOCATION_CLASS]);
+        if !integrity.is_consistent() {
+            println!("  - Warning: {} event reference(s) without master data", integrity.dangling.len());
+        }
+        all_triples.extend(entity_triples);
         
         // Add event triples
         all_triples.extend(self.generate_event_triples(&events));
@@ -82,6 +100,7 @@ impl EpcisDataGenerator {
             product_count: products.len(),
             generation_time_ms: generation_time,
             output_files: vec![output_file.to_string_lossy().to_string()],
+            integrity,
         })
     }
     
@@ -129,7 +148,7 @@ impl EpcisDataGenerator {
             triples.push(oxrdf::Triple::new(
                 oxrdf::NamedNode::new(&location.uri).unwrap(),
                 oxrdf::NamedNode::new("http://www.w3.org/1999/02/22-rdf-syntax-ns#type").unwrap(),
-                oxrdf::NamedNode::new("http://example.com/Location").unwrap(),
+                oxrdf::NamedNode::new(LOCATION_CLASS).unwrap(),
             ));
             triples.push(oxrdf::Triple::new(
                 oxrdf::NamedNode::new(&location.uri).unwrap(),
@@ -160,34 +179,26 @@ impl EpcisDataGenerator {
                 oxrdf::NamedNode::new("http://example.com/epc").unwrap(),
                 <oxrdf::Literal as Into<oxrdf::Term>>::into(oxrdf::Literal::new_simple_literal(product.epc.clone())),
             ));
-        }
-        
-        triples
-    }
-    
-    fn generate_event_triples(&self, events: &[crate::data_gen::events::EpcisEvent]) -> Vec<oxrdf::Triple> {
-        let mut triples = Vec::new();
-        
-        for event in events {
+            
+            // Master data of the EPC itself, which events refer to
             triples.push(oxrdf::Triple::new(
-                oxrdf::NamedNode::new(&event.uri).unwrap(),
+                oxrdf::NamedNode::new(&product.epc).unwrap(),
                 oxrdf::NamedNode::new("http://www.w3.org/1999/02/22-rdf-syntax-ns#type").unwrap(),
-                oxrdf::NamedNode::new("urn:epcglobal:epcis:ObjectEvent").unwrap(),
-            ));
-            triples.push(oxrdf::Triple::new(
-                oxrdf::NamedNode::new(&event.uri).unwrap(),
-                oxrdf::NamedNode::new("urn:epcglobal:epcis:eventTime").unwrap(),
-                <oxrdf::Literal as Into<oxrdf::Term>>::into(oxrdf::Literal::new_simple_literal(event.event_time.clone())),
+                oxrdf::NamedNode::new(ITEM_CLASS).unwrap(),
             ));
             triples.push(oxrdf::Triple::new(
-                oxrdf::NamedNode::new(&event.uri).unwrap(),
-                oxrdf::NamedNode::new("urn:epcglobal:epcis:action").unwrap(),
-                <oxrdf::Literal as Into<oxrdf::Term>>::into(oxrdf::Literal::new_simple_literal(event.action.clone())),
+                oxrdf::NamedNode::new(&product.epc).unwrap(),
+                oxrdf::NamedNode::new("http://example.com/product").unwrap(),
+                oxrdf::NamedNode::new(&product.uri).unwrap(),
             ));
         }
         
         triples
     }
+    
+    fn generate_event_triples(&self, events: &[EpcisEvent]) -> Vec<oxrdf::Triple> {
+        events.iter().flat_map(convert_event_to_triples).collect()
+    }
 }
 
 impl DataGenerator for EpcisDataGenerator {
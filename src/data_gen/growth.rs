@@ -0,0 +1,341 @@
+//! Day-by-day growth of an existing generated dataset.
+//!
+//! The last event of every EPC in the dataset decides where that EPC is in its
+//! life cycle, and each simulated day moves some EPCs one step further:
+//! commissioning at a factory, shipping, receiving at a warehouse or
+//! distribution center, display at a retail store and finally sale. An EPC is
+//! never shipped before it was commissioned, and nothing happens to it once
+//! sold. Each day's events are written to their own delta file and appended to
+//! the dataset, so incremental inference can be measured on realistic deltas.
+
+use super::integrity;
+use super::utils::formatters::{convert_event_to_triples, DataFormatter, TurtleFormatter};
+use super::GenerationResult;
+use crate::models::epcis::EpcisEvent;
+use crate::models::parties::SourceDestination;
+use chrono::{DateTime, Duration, Utc};
+use oxrdf::{Subject, Term, Triple};
+use oxttl::TurtleParser;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+type GrowthError = Box<dyn std::error::Error + Send + Sync>;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const EPCIS: &str = "urn:epcglobal:epcis:";
+const CBV: &str = "urn:epcglobal:cbv:";
+const ITEM_CLASS: &str = "http://example.com/Item";
+const LOCATION_CLASS: &str = "http://example.com/Location";
+const LOCATION_TYPE: &str = "http://example.com/locationType";
+const EVENT_PREFIX: &str = "urn:epc:event:";
+
+/// Where an EPC is in its life cycle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LifecycleStage {
+    /// Has master data but no events yet
+    Uncommissioned,
+    /// At the factory that commissioned it
+    Commissioned { at: String },
+    InTransit { to: String },
+    /// At a warehouse or distribution center
+    Stored { at: String },
+    /// At a retail store
+    OnDisplay { at: String },
+    /// Sold or otherwise removed; no further events
+    Retired,
+}
+
+/// What is needed of an existing event to recover life cycle state
+#[derive(Debug, Default)]
+struct EventFacts {
+    time: Option<DateTime<Utc>>,
+    action: Option<String>,
+    biz_step: Option<String>,
+    biz_location: Option<String>,
+    destination: Option<String>,
+    epcs: Vec<String>,
+}
+
+/// EPCs, locations and life cycle state read from a generated dataset
+struct Dataset {
+    /// Location URI to location type (`Factory`, `Warehouse`, ...)
+    locations: BTreeMap<String, String>,
+    epcs: BTreeMap<String, LifecycleStage>,
+    /// Typed master data, for the integrity check of new events
+    master_data: Vec<Triple>,
+    latest: Option<DateTime<Utc>>,
+}
+
+impl Dataset {
+    fn read(path: &Path) -> Result<Self, GrowthError> {
+        let file = std::fs::File::open(path)?;
+        let mut locations = BTreeMap::new();
+        let mut items = Vec::new();
+        let mut events: HashMap<String, EventFacts> = HashMap::new();
+        let mut master_data = Vec::new();
+
+        for triple in TurtleParser::new().with_quoted_triples().for_reader(std::io::BufReader::new(file)) {
+            let triple = triple?;
+            let Subject::NamedNode(subject) = &triple.subject else { continue };
+            let subject = subject.as_str();
+            let predicate = triple.predicate.as_str();
+            let object = term_value(&triple.object);
+
+            if let Some(event_id) = subject.strip_prefix(EVENT_PREFIX) {
+                let facts = events.entry(event_id.to_string()).or_default();
+                match predicate.strip_prefix(EPCIS) {
+                    Some("eventTime") => facts.time = DateTime::parse_from_rfc3339(&object).ok().map(|time| time.with_timezone(&Utc)),
+                    Some("action") => facts.action = Some(object.trim_start_matches(CBV).to_string()),
+                    Some("bizStep") => facts.biz_step = Some(object.trim_start_matches(CBV).to_string()),
+                    Some("bizLocation") => facts.biz_location = Some(object),
+                    Some("destination" | "destinationLocation") => facts.destination = Some(object),
+                    Some("epcList") => facts.epcs.push(object),
+                    _ => {}
+                }
+            } else if predicate == LOCATION_TYPE {
+                locations.insert(subject.to_string(), object);
+            } else if predicate == RDF_TYPE && (object == ITEM_CLASS || object == LOCATION_CLASS) {
+                if object == ITEM_CLASS {
+                    items.push(subject.to_string());
+                }
+                master_data.push(triple.clone());
+            }
+        }
+
+        let mut epcs: BTreeMap<String, LifecycleStage> =
+            items.into_iter().map(|epc| (epc, LifecycleStage::Uncommissioned)).collect();
+        let mut latest = None;
+        let mut ordered: Vec<EventFacts> = events.into_values().collect();
+        ordered.sort_by_key(|facts| facts.time);
+        for facts in ordered {
+            latest = latest.max(facts.time);
+            let stage = stage_after(&facts, &locations);
+            for epc in &facts.epcs {
+                epcs.insert(epc.clone(), stage.clone());
+            }
+        }
+
+        Ok(Self { locations, epcs, master_data, latest })
+    }
+
+    fn locations_of(&self, location_type: &str) -> Vec<&String> {
+        self.locations.iter().filter(|(_, kind)| kind.as_str() == location_type).map(|(uri, _)| uri).collect()
+    }
+}
+
+fn term_value(term: &Term) -> String {
+    match term {
+        Term::NamedNode(node) => node.as_str().to_string(),
+        Term::Literal(literal) => literal.value().to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn at_location(location: &str, locations: &BTreeMap<String, String>) -> LifecycleStage {
+    let at = location.to_string();
+    match locations.get(location).map(String::as_str) {
+        Some("Factory") => LifecycleStage::Commissioned { at },
+        Some("RetailStore") => LifecycleStage::OnDisplay { at },
+        _ => LifecycleStage::Stored { at },
+    }
+}
+
+/// Life cycle stage an event leaves its EPCs in
+fn stage_after(facts: &EventFacts, locations: &BTreeMap<String, String>) -> LifecycleStage {
+    let biz_step = facts.biz_step.as_deref().unwrap_or_default();
+    if facts.action.as_deref() == Some("DELETE")
+        || matches!(biz_step, "selling" | "retail_selling" | "customer_pickup" | "destroying" | "decommissioning")
+    {
+        return LifecycleStage::Retired;
+    }
+    let destination = facts.destination.as_ref().or(facts.biz_location.as_ref());
+    match (biz_step, destination) {
+        ("shipping" | "transporting" | "departing", Some(to)) => LifecycleStage::InTransit { to: to.clone() },
+        ("receiving" | "arriving" | "accepting", Some(at)) => at_location(at, locations),
+        _ => match &facts.biz_location {
+            Some(at) => at_location(at, locations),
+            None => LifecycleStage::Uncommissioned,
+        },
+    }
+}
+
+/// The one generated Turtle dataset (`epcis_data_*.ttl`) in `directory`
+pub fn find_dataset(directory: &Path) -> Result<PathBuf, GrowthError> {
+    let mut datasets: Vec<PathBuf> = std::fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+            name.starts_with("epcis_data_") && name.ends_with(".ttl")
+        })
+        .collect();
+    match datasets.len() {
+        1 => Ok(datasets.remove(0)),
+        0 => Err(format!("No generated dataset (epcis_data_*.ttl) in {}", directory.display()).into()),
+        _ => Err(format!("More than one generated dataset in {}; keep one to append to", directory.display()).into()),
+    }
+}
+
+/// Extend the dataset at `dataset` by `days` days of events; returns the delta files written
+pub fn append_days(dataset: &Path, days: u32) -> Result<GenerationResult, GrowthError> {
+    let start_time = Instant::now();
+    let mut data = Dataset::read(dataset)?;
+    if data.epcs.is_empty() {
+        return Err(format!("{} has no EPCs to continue; generate a dataset first", dataset.display()).into());
+    }
+    let factories: Vec<String> = data.locations_of("Factory").into_iter().cloned().collect();
+    if factories.is_empty() {
+        return Err(format!("{} has no factory locations to commission EPCs at", dataset.display()).into());
+    }
+    let warehouses: Vec<String> = data.locations_of("Warehouse").into_iter().cloned().collect();
+    let distribution_centers: Vec<String> = data.locations_of("DistributionCenter").into_iter().cloned().collect();
+    let retail_stores: Vec<String> = data.locations_of("RetailStore").into_iter().cloned().collect();
+    let pick = |candidates: &[String], fallback: &[String], index: usize| -> Option<String> {
+        let pool = if candidates.is_empty() { fallback } else { candidates };
+        (!pool.is_empty()).then(|| pool[index % pool.len()].clone())
+    };
+
+    let directory = dataset.parent().map(Path::to_path_buf).unwrap_or_else(|| PathBuf::from("."));
+    let first_day = data.latest.unwrap_or_else(Utc::now).date_naive() + Duration::days(1);
+    let formatter = TurtleFormatter::new();
+    let mut all_events = Vec::new();
+    let mut output_files = Vec::new();
+    let mut triple_count = 0;
+
+    for day in 0..days {
+        let date = first_day + Duration::days(day as i64);
+        let day_start = date.and_hms_opt(8, 0, 0).map(|time| time.and_utc()).unwrap_or_else(Utc::now);
+        let mut events = Vec::new();
+
+        for (index, (epc, stage)) in data.epcs.iter_mut().enumerate() {
+            // Roughly two thirds of the active EPCs move on any given day
+            if (index + day as usize) % 3 == 2 {
+                continue;
+            }
+            let event_time = day_start + Duration::minutes(events.len() as i64);
+            let event_id = format!("GROW-{}-{:06}", date.format("%Y%m%d"), events.len() + 1);
+            let mut event = EpcisEvent {
+                event_id,
+                event_type: "ObjectEvent".to_string(),
+                event_time: event_time.to_rfc3339(),
+                record_time: (event_time + Duration::minutes(5)).to_rfc3339(),
+                event_action: "OBSERVE".to_string(),
+                epc_list: vec![epc.clone()],
+                ..Default::default()
+            };
+
+            let next = match &*stage {
+                LifecycleStage::Retired => continue,
+                LifecycleStage::Uncommissioned => {
+                    let factory = factories[index % factories.len()].clone();
+                    event.event_action = "ADD".to_string();
+                    event.biz_step = Some("commissioning".to_string());
+                    event.disposition = Some("active".to_string());
+                    event.biz_location = Some(factory.clone());
+                    LifecycleStage::Commissioned { at: factory }
+                }
+                LifecycleStage::Commissioned { at } | LifecycleStage::Stored { at } => {
+                    // Factories ship to warehouses, warehouses to distribution centers, those to stores
+                    let to = match (&*stage, data.locations.get(at).map(String::as_str)) {
+                        (LifecycleStage::Commissioned { .. }, _) => pick(&warehouses, &distribution_centers, index),
+                        (_, Some("Warehouse")) => pick(&distribution_centers, &retail_stores, index),
+                        _ => pick(&retail_stores, &[], index),
+                    };
+                    let Some(to) = to else { continue };
+                    event.biz_step = Some("shipping".to_string());
+                    event.disposition = Some("in_transit".to_string());
+                    event.biz_location = Some(at.clone());
+                    event.source_list = vec![SourceDestination { kind: "location".to_string(), id: at.clone() }];
+                    event.destination_list = vec![SourceDestination { kind: "location".to_string(), id: to.clone() }];
+                    LifecycleStage::InTransit { to }
+                }
+                LifecycleStage::InTransit { to } => {
+                    event.biz_step = Some("receiving".to_string());
+                    event.disposition = Some("in_progress".to_string());
+                    event.biz_location = Some(to.clone());
+                    at_location(to, &data.locations)
+                }
+                LifecycleStage::OnDisplay { at } => {
+                    event.event_action = "DELETE".to_string();
+                    event.biz_step = Some("retail_selling".to_string());
+                    event.disposition = Some("retail_sold".to_string());
+                    event.biz_location = Some(at.clone());
+                    LifecycleStage::Retired
+                }
+            };
+            *stage = next;
+            events.push(event);
+        }
+
+        let triples: Vec<Triple> = events.iter().flat_map(convert_event_to_triples).collect();
+        let turtle = formatter.format_triples(&triples);
+        let delta = directory.join(format!("epcis_delta_{}.ttl", date.format("%Y%m%d")));
+        std::fs::write(&delta, &turtle)?;
+        std::fs::OpenOptions::new().append(true).open(dataset)?.write_all(format!("\n{}", turtle).as_bytes())?;
+
+        triple_count += triples.len();
+        output_files.push(delta.to_string_lossy().to_string());
+        all_events.extend(events);
+    }
+
+    let integrity = integrity::check(&all_events, &data.master_data, &[]);
+    Ok(GenerationResult {
+        triple_count,
+        event_count: all_events.len(),
+        location_count: 0,
+        product_count: 0,
+        generation_time_ms: start_time.elapsed().as_millis() as u64,
+        output_files,
+        integrity,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATASET: &str = r#"@prefix epcis: <urn:epcglobal:epcis:> .
+@prefix cbv: <urn:epcglobal:cbv:> .
+@prefix ex: <http://example.com/> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+
+<urn:loc:factory> rdf:type ex:Location ; ex:locationType "Factory" .
+<urn:loc:warehouse> rdf:type ex:Location ; ex:locationType "Warehouse" .
+<urn:loc:store> rdf:type ex:Location ; ex:locationType "RetailStore" .
+<urn:epc:id:sgtin:1.1.1> rdf:type ex:Item .
+<urn:epc:id:sgtin:1.1.2> rdf:type ex:Item .
+<urn:epc:id:sgtin:1.1.3> rdf:type ex:Item .
+
+<urn:epc:event:E-1> epcis:eventTime "2024-03-01T10:00:00Z"^^xsd:dateTime ;
+    epcis:action cbv:ADD ; epcis:bizStep cbv:commissioning ;
+    epcis:bizLocation <urn:loc:factory> ; epcis:epcList <urn:epc:id:sgtin:1.1.1> .
+<urn:epc:event:E-2> epcis:eventTime "2024-03-02T10:00:00Z"^^xsd:dateTime ;
+    epcis:action cbv:DELETE ; epcis:bizStep cbv:retail_selling ;
+    epcis:bizLocation <urn:loc:store> ; epcis:epcList <urn:epc:id:sgtin:1.1.3> .
+"#;
+
+    #[test]
+    fn test_growth_continues_life_cycles_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let dataset = dir.path().join("epcis_data_test.ttl");
+        std::fs::write(&dataset, DATASET).unwrap();
+
+        let result = append_days(&dataset, 10).unwrap();
+        assert_eq!(result.output_files.len(), 10);
+        assert!(result.output_files[0].ends_with("epcis_delta_20240303.ttl"));
+        assert!(result.integrity.is_consistent());
+
+        // Re-reading the grown dataset recovers where each EPC ended up
+        let grown = Dataset::read(&dataset).unwrap();
+        assert_eq!(grown.epcs["urn:epc:id:sgtin:1.1.3"], LifecycleStage::Retired);
+        assert!(grown.epcs.values().all(|stage| *stage == LifecycleStage::Retired), "{:?}", grown.epcs);
+
+        // The first delta commissions the unseen EPC and ships the commissioned one
+        let turtle = std::fs::read_to_string(&result.output_files[0]).unwrap();
+        assert!(turtle.contains("<urn:epcglobal:cbv:commissioning>"));
+        assert!(turtle.contains("<urn:epcglobal:cbv:shipping>"));
+        assert!(!turtle.contains("sgtin:1.1.3"), "nothing happens after a sale");
+    }
+}
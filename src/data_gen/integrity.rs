@@ -0,0 +1,119 @@
+use crate::models::epcis::EpcisEvent;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+/// Identifier an event uses that has no master data in the generated dataset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DanglingReference {
+    pub event_id: String,
+    /// `epc`, `bizLocation`, `source` or `destination`
+    pub role: String,
+    pub id: String,
+}
+
+/// Outcome of checking generated events against generated master data.
+///
+/// The event model has no `readPoint` yet, so business locations and
+/// source/destination locations are the location references checked.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IntegrityReport {
+    pub dangling: Vec<DanglingReference>,
+    /// Master data subjects no event refers to
+    pub unreferenced: Vec<String>,
+}
+
+impl IntegrityReport {
+    pub fn is_consistent(&self) -> bool {
+        self.dangling.is_empty()
+    }
+}
+
+/// Every EPC and location an event refers to
+pub fn referenced_ids(events: &[EpcisEvent]) -> BTreeSet<String> {
+    events.iter().flat_map(references).map(|(_, id)| id.to_string()).collect()
+}
+
+fn references<'a>(event: &'a EpcisEvent) -> impl Iterator<Item = (&'static str, &'a str)> + 'a {
+    let epcs = event
+        .epc_list
+        .iter()
+        .chain(&event.input_epc_list)
+        .chain(&event.output_epc_list)
+        .map(|epc| ("epc", epc.as_str()));
+    let biz_location = event.biz_location.iter().map(|location| ("bizLocation", location.as_str()));
+    let sources = event.source_list.iter().map(|source| ("source", source.id.as_str()));
+    let destinations = event.destination_list.iter().map(|destination| ("destination", destination.id.as_str()));
+    epcs.chain(biz_location).chain(sources).chain(destinations)
+}
+
+/// Check events against master data triples, where a subject with an `rdf:type` counts as described.
+///
+/// Only subjects of `checked_types` are reported as unreferenced, so catalog
+/// entries such as products, which events reach through their EPCs, are not.
+pub fn check(events: &[EpcisEvent], master_data: &[oxrdf::Triple], checked_types: &[&str]) -> IntegrityReport {
+    let typed = master_data.iter().filter(|triple| triple.predicate.as_str() == RDF_TYPE);
+    let described: BTreeSet<String> = typed.clone().map(|triple| subject_id(&triple.subject)).collect();
+
+    let mut report = IntegrityReport::default();
+    for event in events {
+        for (role, id) in references(event) {
+            if !described.contains(id) {
+                report.dangling.push(DanglingReference {
+                    event_id: event.event_id.clone(),
+                    role: role.to_string(),
+                    id: id.to_string(),
+                });
+            }
+        }
+    }
+
+    let referenced = referenced_ids(events);
+    let checked: BTreeSet<String> = typed
+        .filter(|triple| matches!(&triple.object, oxrdf::Term::NamedNode(class) if checked_types.contains(&class.as_str())))
+        .map(|triple| subject_id(&triple.subject))
+        .collect();
+    report.unreferenced = checked.into_iter().filter(|subject| !referenced.contains(subject)).collect();
+    report
+}
+
+fn subject_id(subject: &oxrdf::Subject) -> String {
+    match subject {
+        oxrdf::Subject::NamedNode(node) => node.as_str().to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxrdf::{NamedNode, Triple};
+
+    #[test]
+    fn test_dangling_and_unreferenced_master_data_are_reported() {
+        let typed = |subject: &str, class: &str| {
+            Triple::new(NamedNode::new_unchecked(subject), NamedNode::new_unchecked(RDF_TYPE), NamedNode::new_unchecked(class))
+        };
+        let master_data = vec![
+            typed("urn:epc:id:sgtin:1.1.1", "http://example.com/Item"),
+            typed("urn:epc:id:sgln:1.1.0", "http://example.com/Location"),
+            typed("urn:epc:id:sgln:2.2.0", "http://example.com/Location"),
+            typed("http://example.com/product/1", "http://example.com/Product"),
+        ];
+        let event = EpcisEvent {
+            event_id: "E-1".to_string(),
+            epc_list: vec!["urn:epc:id:sgtin:1.1.1".to_string(), "urn:epc:id:sgtin:9.9.9".to_string()],
+            biz_location: Some("urn:epc:id:sgln:1.1.0".to_string()),
+            ..Default::default()
+        };
+
+        let report = check(&[event], &master_data, &["http://example.com/Item", "http://example.com/Location"]);
+        assert!(!report.is_consistent());
+        assert_eq!(
+            report.dangling,
+            vec![DanglingReference { event_id: "E-1".to_string(), role: "epc".to_string(), id: "urn:epc:id:sgtin:9.9.9".to_string() }]
+        );
+        assert_eq!(report.unreferenced, vec!["urn:epc:id:sgln:2.2.0".to_string()]);
+    }
+}
@@ -1,6 +1,11 @@
 pub mod generator;
+pub mod growth;
 pub mod entities;
 pub mod events;
+pub mod integrity;
+pub mod samples;
+#[cfg(feature = "client")]
+pub mod workload;
 pub mod utils;
 
 use std::path::PathBuf;
@@ -13,6 +18,9 @@ pub struct GeneratorConfig {
     pub output_format: OutputFormat,
     pub output_path: PathBuf,
     pub custom_counts: Option<(usize, usize, usize)>,
+    /// Leave out locations and EPCs that no generated event refers to
+    #[serde(default)]
+    pub referenced_master_data_only: bool,
 }
 
 /// Data scale options
@@ -63,6 +71,7 @@ impl Default for GeneratorConfig {
             output_format: OutputFormat::Turtle,
             output_path: PathBuf::from("data/generated"),
             custom_counts: None,
+            referenced_master_data_only: false,
         }
     }
 }
@@ -76,6 +85,8 @@ pub struct GenerationResult {
     pub product_count: usize,
     pub generation_time_ms: u64,
     pub output_files: Vec<String>,
+    /// References from events to master data missing from the output
+    pub integrity: integrity::IntegrityReport,
 }
 
 /// Trait for data generators
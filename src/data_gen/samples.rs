@@ -0,0 +1,204 @@
+use super::{generator::EpcisDataGenerator, DataScale, GeneratorConfig, OutputFormat};
+use crate::config::SamplesConfig;
+use crate::EpcisKgError;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Scales with a sample pack; other names fall back to `medium`
+pub const SAMPLE_SCALES: [&str; 4] = ["small", "medium", "large", "xlarge"];
+
+/// Where a sample pack came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SampleSource {
+    /// Already in the samples directory
+    Local,
+    /// Fetched from the release URL and checksum-verified
+    Downloaded,
+    /// Generated because it was missing
+    Generated,
+}
+
+#[derive(Debug, Clone)]
+pub struct SamplePack {
+    pub path: PathBuf,
+    pub source: SampleSource,
+}
+
+/// Finds sample packs on disk, fetching or generating missing ones
+pub struct SamplePacks {
+    config: SamplesConfig,
+}
+
+impl SamplePacks {
+    pub fn from_config(config: &SamplesConfig) -> Self {
+        Self { config: config.clone() }
+    }
+
+    pub fn file_name(scale: &str) -> String {
+        let scale = scale.to_lowercase();
+        let scale = if SAMPLE_SCALES.contains(&scale.as_str()) { scale.as_str() } else { "medium" };
+        format!("epcis_data_{}.ttl", scale)
+    }
+
+    /// Pre-generated file first, then a previously downloaded pack of the configured version
+    fn local_paths(&self, file: &str) -> [PathBuf; 2] {
+        let directory = Path::new(&self.config.directory);
+        [directory.join(file), directory.join(&self.config.version).join(file)]
+    }
+
+    fn release_url(&self) -> Option<String> {
+        self.config
+            .release_url
+            .as_ref()
+            .map(|url| url.replace("{version}", &self.config.version).trim_end_matches('/').to_string())
+    }
+
+    /// Path of the pack for `scale`, downloading or generating it when it is missing
+    pub async fn ensure(&self, scale: &str) -> Result<SamplePack, EpcisKgError> {
+        let file = Self::file_name(scale);
+        if let Some(path) = self.local_paths(&file).into_iter().find(|path| path.exists()) {
+            return Ok(SamplePack { path, source: SampleSource::Local });
+        }
+
+        let mut failure = None;
+        if let Some(release_url) = self.release_url() {
+            match self.download(&release_url, &file).await {
+                Ok(path) => return Ok(SamplePack { path, source: SampleSource::Downloaded }),
+                Err(e) => {
+                    tracing::warn!("Failed to download sample pack {} from {}: {}", file, release_url, e);
+                    failure = Some(e);
+                }
+            }
+        }
+
+        if self.config.generate_missing {
+            let path = self.generate(scale, &file)?;
+            return Ok(SamplePack { path, source: SampleSource::Generated });
+        }
+        Err(failure.unwrap_or(EpcisKgError::NotFound {
+            resource: "Sample pack".to_string(),
+            id: file,
+        }))
+    }
+
+    async fn download(&self, release_url: &str, file: &str) -> Result<PathBuf, EpcisKgError> {
+        let data = fetch(&format!("{}/{}", release_url, file)).await?;
+        let expected = match self.config.checksums.get(file) {
+            Some(checksum) => checksum.to_lowercase(),
+            None => {
+                let sums = fetch(&format!("{}/SHA256SUMS", release_url)).await?;
+                checksum_for(&String::from_utf8_lossy(&sums), file).ok_or_else(|| {
+                    EpcisKgError::Validation(format!("The release's SHA256SUMS lists no checksum for {}", file))
+                })?
+            }
+        };
+        verify_checksum(&data, &expected, file)?;
+
+        let directory = Path::new(&self.config.directory).join(&self.config.version);
+        std::fs::create_dir_all(&directory)?;
+        let path = directory.join(file);
+        let temporary = path.with_extension("ttl.part");
+        std::fs::write(&temporary, &data)?;
+        std::fs::rename(&temporary, &path)?;
+        Ok(path)
+    }
+
+    fn generate(&self, scale: &str, file: &str) -> Result<PathBuf, EpcisKgError> {
+        let data_scale = match scale.to_lowercase().as_str() {
+            "small" => DataScale::Small,
+            "large" => DataScale::Large,
+            "xlarge" => DataScale::XLarge,
+            _ => DataScale::Medium,
+        };
+        let scratch = Path::new(&self.config.directory).join(format!(".generate-{}", uuid::Uuid::new_v4()));
+        let generator_config = GeneratorConfig {
+            scale: data_scale,
+            output_format: OutputFormat::Turtle,
+            output_path: scratch.clone(),
+            custom_counts: None,
+            referenced_master_data_only: false,
+        };
+
+        let result = EpcisDataGenerator::new()
+            .generate_dataset(&generator_config)
+            .map_err(|e| EpcisKgError::Storage(format!("Failed to generate sample pack {}: {}", file, e)));
+        let path = Path::new(&self.config.directory).join(file);
+        let moved = result.and_then(|result| {
+            let generated = result.output_files.first().ok_or_else(|| {
+                EpcisKgError::Storage(format!("Generating sample pack {} produced no file", file))
+            })?;
+            std::fs::rename(generated, &path)?;
+            Ok(())
+        });
+        let _ = std::fs::remove_dir_all(&scratch);
+        moved.map(|()| path)
+    }
+}
+
+async fn fetch(url: &str) -> Result<Vec<u8>, EpcisKgError> {
+    let response = reqwest::get(url)
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to fetch {}: {}", url, e)))?;
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| EpcisKgError::Storage(format!("Failed to read {}: {}", url, e)))?;
+    Ok(bytes.to_vec())
+}
+
+/// Checksum of `file` in `sha256sum` output (`<hex>  <name>`, or `<hex> *<name>` for binary mode)
+fn checksum_for(sums: &str, file: &str) -> Option<String> {
+    sums.lines().find_map(|line| {
+        let (checksum, name) = line.trim().split_once(char::is_whitespace)?;
+        (name.trim().trim_start_matches('*') == file).then(|| checksum.to_lowercase())
+    })
+}
+
+fn verify_checksum(data: &[u8], expected: &str, file: &str) -> Result<(), EpcisKgError> {
+    let actual: String = Sha256::digest(data).iter().map(|byte| format!("{:02x}", byte)).collect();
+    if actual != expected {
+        return Err(EpcisKgError::Validation(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            file, expected, actual
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_packs_are_generated_and_checksums_verified() {
+        let sums = "0a1b  epcis_data_small.ttl\nFF00 *epcis_data_large.ttl\n";
+        assert_eq!(checksum_for(sums, "epcis_data_small.ttl").as_deref(), Some("0a1b"));
+        assert_eq!(checksum_for(sums, "epcis_data_large.ttl").as_deref(), Some("ff00"));
+        assert_eq!(checksum_for(sums, "epcis_data_medium.ttl"), None);
+
+        let sha_of_abc = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert!(verify_checksum(b"abc", sha_of_abc, "abc").is_ok());
+        assert!(matches!(verify_checksum(b"abd", sha_of_abc, "abc"), Err(EpcisKgError::Validation(_))));
+        assert_eq!(SamplePacks::file_name("unknown"), "epcis_data_medium.ttl");
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = SamplesConfig {
+            directory: dir.path().to_string_lossy().to_string(),
+            generate_missing: false,
+            ..SamplesConfig::default()
+        };
+        let missing = futures::executor::block_on(SamplePacks::from_config(&config).ensure("small"));
+        assert!(matches!(missing, Err(EpcisKgError::NotFound { .. })));
+
+        let packs = SamplePacks::from_config(&SamplesConfig { generate_missing: true, ..config });
+        let generated = futures::executor::block_on(packs.ensure("small")).unwrap();
+        assert_eq!(generated.source, SampleSource::Generated);
+        assert_eq!(generated.path, dir.path().join("epcis_data_small.ttl"));
+        let local = futures::executor::block_on(packs.ensure("small")).unwrap();
+        assert_eq!(local.source, SampleSource::Local);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::data_gen::events::EpcisEvent;
+use crate::models::epcis::EpcisEvent;
 use crate::data_gen::entities::{Location, Product, BusinessEntity};
 
 /// Trait for formatting data into different RDF formats
@@ -93,6 +93,10 @@ impl TurtleFormatter {
         match object {
             oxrdf::Term::NamedNode(node) => format!("<{}>", node.as_ref()),
             oxrdf::Term::BlankNode(node) => format!("_:{}", node.as_str()),
+            oxrdf::Term::Triple(triple) => format!(
+                "<< {} <{}> {} >>",
+                triple.subject, triple.predicate.as_str(), self.format_object(&triple.object)
+            ),
             oxrdf::Term::Literal(literal) => {
                 if literal.datatype() == oxrdf::NamedNode::new("http://www.w3.org/2001/XMLSchema#string").unwrap() {
                     format!("\"{}\"", literal.value())
@@ -209,8 +213,9 @@ impl DataFormatter for JsonLdFormatter {
         
         for triple in triples {
             let subject_str = match &triple.subject {
-                oxrdf::Subject::NamedNode(node) => node.as_str(),
-                oxrdf::Subject::BlankNode(node) => node.as_str(),
+                oxrdf::Subject::NamedNode(node) => node.as_str().to_string(),
+                oxrdf::Subject::BlankNode(node) => node.as_str().to_string(),
+                oxrdf::Subject::Triple(quoted) => format!("<< {} >>", quoted),
             };
             
             graph.push(serde_json::json!({
@@ -292,43 +297,31 @@ impl DataFormatter for JsonLdFormatter {
 // Helper functions for formatting
 
 fn format_event_turtle(event: &EpcisEvent) -> String {
-    format!(
-        r#"<{}> rdf:type epcis:ObjectEvent ;
-    epcis:eventID "{}" ;
-    epcis:eventTime "{}"^^xsd:dateTime ;
-    epcis:recordTime "{}"^^xsd:dateTime ;
-    epcis:action "{}" ;
-    epcis:bizStep "{}" ;
-    epcis:disposition "{}" ;
-    epcis:epcList "{}" ;
-{}
-{}
-{} .
-"#,
-        event.uri,
-        event.event_id,
-        event.event_time,
-        event.record_time,
-        event.action,
-        event.biz_step,
-        event.disposition,
-        event.epc_list.join(", "),
-        if let Some(ref read_point) = event.read_point {
-            format!("    epcis:readPoint <{}> ;", read_point)
-        } else {
-            String::new()
-        },
-        if let Some(ref biz_location) = event.biz_location {
-            format!("    epcis:bizLocation <{}> ;", biz_location)
-        } else {
-            String::new()
-        },
-        if let Some(quantity) = event.quantity {
-            format!("    epcis:quantity {}^^xsd:integer", quantity)
-        } else {
-            String::new()
+    let mut lines = vec![
+        format!("<urn:epc:event:{}> rdf:type epcis:{}", event.event_id, event.event_type),
+        format!("    epcis:eventID \"{}\"", event.event_id),
+        format!("    epcis:eventTime \"{}\"^^xsd:dateTime", event.event_time),
+        format!("    epcis:recordTime \"{}\"^^xsd:dateTime", event.record_time),
+        format!("    epcis:action cbv:{}", event.event_action),
+    ];
+    lines.extend(event.epc_list.iter().map(|epc| format!("    epcis:epcList <{}>", epc)));
+    if let Some(ref biz_step) = event.biz_step {
+        lines.push(format!("    epcis:bizStep cbv:{}", biz_step));
+    }
+    if let Some(ref disposition) = event.disposition {
+        lines.push(format!("    epcis:disposition cbv:{}", disposition));
+    }
+    if let Some(ref biz_location) = event.biz_location {
+        lines.push(format!("    epcis:bizLocation <{}>", biz_location));
+    }
+    for (direction, list) in [("source", &event.source_list), ("destination", &event.destination_list)] {
+        for source_destination in list {
+            let predicate = source_destination.predicate(direction);
+            let local_name = predicate.trim_start_matches("urn:epcglobal:epcis:");
+            lines.push(format!("    epcis:{} <{}>", local_name, source_destination.id));
         }
-    )
+    }
+    format!("{} .\n\n", lines.join(" ;\n"))
 }
 
 fn format_location_turtle(location: &Location) -> String {
@@ -443,6 +436,10 @@ fn format_ntriples_object(object: &oxrdf::Term) -> String {
     match object {
         oxrdf::Term::NamedNode(node) => format!("<{}>", node.as_str()),
         oxrdf::Term::BlankNode(node) => format!("_:{}", node.as_str()),
+        oxrdf::Term::Triple(triple) => format!(
+            "<< {} <{}> {} >>",
+            triple.subject, triple.predicate.as_str(), format_ntriples_object(&triple.object)
+        ),
         oxrdf::Term::Literal(literal) => {
             if literal.datatype() == oxrdf::NamedNode::new("http://www.w3.org/2001/XMLSchema#string").unwrap() {
                 format!("\"{}\"", literal.value())
@@ -457,6 +454,13 @@ fn format_jsonld_object(object: &oxrdf::Term) -> serde_json::Value {
     match object {
         oxrdf::Term::NamedNode(node) => serde_json::json!({"@id": node.as_str()}),
         oxrdf::Term::BlankNode(node) => serde_json::json!({"@id": format!("_:{}", node.as_str())}),
+        // JSON-LD-star: an embedded node object stands for the quoted triple
+        oxrdf::Term::Triple(triple) => serde_json::json!({
+            "@id": {
+                "@id": triple.subject.to_string(),
+                triple.predicate.as_str(): format_jsonld_object(&triple.object)
+            }
+        }),
         oxrdf::Term::Literal(literal) => {
             if literal.datatype() == oxrdf::NamedNode::new("http://www.w3.org/2001/XMLSchema#string").unwrap() {
                 serde_json::json!({"@value": literal.value()})
@@ -471,19 +475,48 @@ fn format_jsonld_object(object: &oxrdf::Term) -> serde_json::Value {
 }
 
 // Conversion functions for different formatters
-fn convert_event_to_triples(event: &EpcisEvent) -> Vec<oxrdf::Triple> {
-    vec![
+/// Triples of a generated event, using the same IRIs as the event pipeline
+pub(crate) fn convert_event_to_triples(event: &EpcisEvent) -> Vec<oxrdf::Triple> {
+    let event_uri = oxrdf::NamedNode::new(format!("urn:epc:event:{}", event.event_id)).unwrap();
+    let epcis = |name: &str| oxrdf::NamedNode::new(format!("urn:epcglobal:epcis:{}", name)).unwrap();
+    let cbv = |name: &str| oxrdf::NamedNode::new(format!("urn:epcglobal:cbv:{}", name)).unwrap();
+    let date_time = |value: &str| {
+        oxrdf::Literal::new_typed_literal(value, oxrdf::NamedNode::new("http://www.w3.org/2001/XMLSchema#dateTime").unwrap())
+    };
+
+    let mut triples = vec![
         oxrdf::Triple::new(
-            oxrdf::NamedNode::new(&event.uri).unwrap(),
+            event_uri.clone(),
             oxrdf::NamedNode::new("http://www.w3.org/1999/02/22-rdf-syntax-ns#type").unwrap(),
-            oxrdf::NamedNode::new("urn:epcglobal:epcis:ObjectEvent").unwrap(),
-        ),
-        oxrdf::Triple::new(
-            oxrdf::NamedNode::new(&event.uri).unwrap(),
-            oxrdf::NamedNode::new("urn:epcglobal:epcis:eventTime").unwrap(),
-            oxrdf::Term::Literal(oxrdf::Literal::new_simple_literal(&event.event_time)),
+            epcis(&event.event_type),
         ),
-    ]
+        oxrdf::Triple::new(event_uri.clone(), epcis("eventID"), oxrdf::Literal::new_simple_literal(&event.event_id)),
+        oxrdf::Triple::new(event_uri.clone(), epcis("eventTime"), date_time(&event.event_time)),
+        oxrdf::Triple::new(event_uri.clone(), epcis("recordTime"), date_time(&event.record_time)),
+        oxrdf::Triple::new(event_uri.clone(), epcis("action"), cbv(&event.event_action)),
+    ];
+    for epc in &event.epc_list {
+        triples.push(oxrdf::Triple::new(event_uri.clone(), epcis("epcList"), oxrdf::NamedNode::new(epc).unwrap()));
+    }
+    if let Some(ref biz_step) = event.biz_step {
+        triples.push(oxrdf::Triple::new(event_uri.clone(), epcis("bizStep"), cbv(biz_step)));
+    }
+    if let Some(ref disposition) = event.disposition {
+        triples.push(oxrdf::Triple::new(event_uri.clone(), epcis("disposition"), cbv(disposition)));
+    }
+    if let Some(ref biz_location) = event.biz_location {
+        triples.push(oxrdf::Triple::new(event_uri.clone(), epcis("bizLocation"), oxrdf::NamedNode::new(biz_location).unwrap()));
+    }
+    for (direction, list) in [("source", &event.source_list), ("destination", &event.destination_list)] {
+        for source_destination in list {
+            triples.push(oxrdf::Triple::new(
+                event_uri.clone(),
+                oxrdf::NamedNode::new(source_destination.predicate(direction)).unwrap(),
+                oxrdf::NamedNode::new(&source_destination.id).unwrap(),
+            ));
+        }
+    }
+    triples
 }
 
 fn convert_location_to_triples(location: &Location) -> Vec<oxrdf::Triple> {
@@ -533,16 +566,18 @@ fn convert_business_entity_to_triples(entity: &BusinessEntity) -> Vec<oxrdf::Tri
 
 fn convert_event_to_jsonld(event: &EpcisEvent) -> serde_json::Value {
     serde_json::json!({
-        "@id": event.uri,
-        "@type": "epcis:ObjectEvent",
+        "@id": format!("urn:epc:event:{}", event.event_id),
+        "@type": format!("epcis:{}", event.event_type),
         "epcis:eventID": event.event_id,
         "epcis:eventTime": {
             "@value": event.event_time,
             "@type": "xsd:dateTime"
         },
-        "epcis:action": event.action,
-        "epcis:bizStep": event.biz_step,
-        "epcis:disposition": event.disposition
+        "epcis:action": { "@id": format!("cbv:{}", event.event_action) },
+        "epcis:epcList": event.epc_list.iter().map(|epc| serde_json::json!({ "@id": epc })).collect::<Vec<_>>(),
+        "epcis:bizStep": event.biz_step.as_ref().map(|biz_step| serde_json::json!({ "@id": format!("cbv:{}", biz_step) })),
+        "epcis:disposition": event.disposition.as_ref().map(|disposition| serde_json::json!({ "@id": format!("cbv:{}", disposition) })),
+        "epcis:bizLocation": event.biz_location.as_ref().map(|location| serde_json::json!({ "@id": location }))
     })
 }
 
@@ -0,0 +1,273 @@
+//! Synthetic live traffic against a running server.
+//!
+//! The driver captures events at a target rate through the REST client, waiting
+//! a randomly drawn think time between requests, and can mix in SPARQL queries.
+//! Events follow simple EPC life cycles (commissioning, shipping, receiving) so
+//! the server's validation and inference see plausible data for as long as the
+//! soak test runs.
+
+use crate::client::EpcisClient;
+use crate::models::epcis::EpcisEvent;
+use crate::EpcisKgError;
+use chrono::Utc;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use std::future::Future;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+const QUERIES: [&str; 3] = [
+    "SELECT (COUNT(?event) AS ?events) WHERE { ?event <urn:epcglobal:epcis:eventTime> ?time }",
+    "SELECT ?event ?step WHERE { ?event <urn:epcglobal:epcis:bizStep> ?step } LIMIT 20",
+    "SELECT ?event ?location WHERE { ?event <urn:epcglobal:epcis:bizLocation> ?location } LIMIT 20",
+];
+
+/// How the pause between two requests is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThinkTime {
+    /// Always the mean
+    Constant,
+    /// Between zero and twice the mean
+    Uniform,
+    /// Poisson arrivals: many short pauses and the occasional long one
+    Exponential,
+}
+
+impl FromStr for ThinkTime {
+    type Err = EpcisKgError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "constant" => Ok(ThinkTime::Constant),
+            "uniform" => Ok(ThinkTime::Uniform),
+            "exponential" | "poisson" => Ok(ThinkTime::Exponential),
+            other => Err(EpcisKgError::Validation(format!(
+                "Unknown think-time distribution '{}'; use constant, uniform or exponential",
+                other
+            ))),
+        }
+    }
+}
+
+impl ThinkTime {
+    fn sample(&self, mean: Duration, rng: &mut impl Rng) -> Duration {
+        let factor = match self {
+            ThinkTime::Constant => 1.0,
+            ThinkTime::Uniform => rng.gen_range(0.0..2.0),
+            ThinkTime::Exponential => -(1.0 - rng.gen::<f64>()).ln(),
+        };
+        mean.mul_f64(factor)
+    }
+}
+
+/// What the driver sends and for how long
+#[derive(Debug, Clone)]
+pub struct WorkloadConfig {
+    /// Events captured per second, on average
+    pub rate: f64,
+    /// Events per capture request
+    pub batch_size: usize,
+    pub think_time: ThinkTime,
+    /// Share of requests that are SPARQL queries instead of captures, below 1.0
+    pub query_ratio: f64,
+    /// Stop after this long; `None` runs until stopped
+    pub duration: Option<Duration>,
+    pub seed: u64,
+}
+
+impl Default for WorkloadConfig {
+    fn default() -> Self {
+        Self {
+            rate: 10.0,
+            batch_size: 1,
+            think_time: ThinkTime::Exponential,
+            query_ratio: 0.0,
+            duration: None,
+            seed: 42,
+        }
+    }
+}
+
+impl WorkloadConfig {
+    pub fn validate(&self) -> Result<(), EpcisKgError> {
+        if self.rate.is_nan() || self.rate <= 0.0 {
+            return Err(EpcisKgError::Validation("Simulation rate must be greater than 0".to_string()));
+        }
+        if self.batch_size == 0 {
+            return Err(EpcisKgError::Validation("Simulation batch size must be at least 1".to_string()));
+        }
+        if !(0.0..1.0).contains(&self.query_ratio) {
+            return Err(EpcisKgError::Validation("Query ratio must be at least 0.0 and below 1.0".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Mean pause between requests that keeps captures at `rate` despite the query share
+    fn mean_think_time(&self) -> Duration {
+        let capture_interval = self.batch_size as f64 / self.rate;
+        Duration::from_secs_f64(capture_interval * (1.0 - self.query_ratio))
+    }
+}
+
+/// Counters and latencies of a simulation run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WorkloadStats {
+    pub events_sent: usize,
+    pub capture_requests: usize,
+    pub capture_failures: usize,
+    pub queries_sent: usize,
+    pub query_failures: usize,
+    pub elapsed_ms: u64,
+    pub capture_latency_p50_ms: f64,
+    pub capture_latency_p95_ms: f64,
+    pub query_latency_p50_ms: f64,
+    pub query_latency_p95_ms: f64,
+    #[serde(skip)]
+    capture_latencies: Vec<f64>,
+    #[serde(skip)]
+    query_latencies: Vec<f64>,
+}
+
+impl WorkloadStats {
+    /// Events per second actually achieved
+    pub fn event_rate(&self) -> f64 {
+        if self.elapsed_ms == 0 {
+            return 0.0;
+        }
+        self.events_sent as f64 * 1000.0 / self.elapsed_ms as f64
+    }
+
+    fn finish(&mut self, elapsed: Duration) {
+        self.elapsed_ms = elapsed.as_millis() as u64;
+        self.capture_latency_p50_ms = percentile(&mut self.capture_latencies, 0.50);
+        self.capture_latency_p95_ms = percentile(&mut self.capture_latencies, 0.95);
+        self.query_latency_p50_ms = percentile(&mut self.query_latencies, 0.50);
+        self.query_latency_p95_ms = percentile(&mut self.query_latencies, 0.95);
+    }
+}
+
+fn percentile(samples: &mut [f64], quantile: f64) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    samples.sort_by(|a, b| a.total_cmp(b));
+    let index = ((samples.len() - 1) as f64 * quantile).round() as usize;
+    samples[index]
+}
+
+/// Endless stream of life cycle events: each EPC is commissioned, shipped and received
+#[derive(Debug, Default)]
+pub struct EventStream {
+    sequence: u64,
+}
+
+impl EventStream {
+    pub fn next_event(&mut self) -> EpcisEvent {
+        let sequence = self.sequence;
+        self.sequence += 1;
+        let serial = sequence / 3;
+        let (action, biz_step, disposition, location) = match sequence % 3 {
+            0 => ("ADD", "commissioning", "active", 1),
+            1 => ("OBSERVE", "shipping", "in_transit", 1),
+            _ => ("OBSERVE", "receiving", "in_progress", 2 + serial % 3),
+        };
+        let now = Utc::now().to_rfc3339();
+        EpcisEvent {
+            event_id: format!("SIM-{}-{:08}", std::process::id(), sequence),
+            event_type: "ObjectEvent".to_string(),
+            event_time: now.clone(),
+            record_time: now,
+            event_action: action.to_string(),
+            epc_list: vec![format!("urn:epc:id:sgtin:0614141.107346.{}", serial)],
+            biz_step: Some(biz_step.to_string()),
+            disposition: Some(disposition.to_string()),
+            biz_location: Some(format!("urn:epc:id:sgln:0614141.{:05}.0", location)),
+            ..Default::default()
+        }
+    }
+}
+
+/// Drive traffic against `client` until the configured duration has passed or `stop` completes.
+///
+/// `on_progress` is called after every request with the running totals, e.g.
+/// to print a status line.
+pub async fn run(
+    client: &EpcisClient,
+    config: &WorkloadConfig,
+    stop: impl Future<Output = ()>,
+    mut on_progress: impl FnMut(&WorkloadStats),
+) -> Result<WorkloadStats, EpcisKgError> {
+    config.validate()?;
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut events = EventStream::default();
+    let mut stats = WorkloadStats::default();
+    let mean_think_time = config.mean_think_time();
+    let started = Instant::now();
+    tokio::pin!(stop);
+
+    while config.duration.map_or(true, |duration| started.elapsed() < duration) {
+        if rng.gen_bool(config.query_ratio) {
+            let query = QUERIES[rng.gen_range(0..QUERIES.len())];
+            let sent = Instant::now();
+            let outcome = client.sparql(query).await;
+            stats.queries_sent += 1;
+            stats.query_latencies.push(sent.elapsed().as_secs_f64() * 1000.0);
+            if let Err(e) = outcome {
+                stats.query_failures += 1;
+                tracing::warn!("Simulated query failed: {}", e);
+            }
+        } else {
+            let batch: Vec<EpcisEvent> = (0..config.batch_size).map(|_| events.next_event()).collect();
+            let sent = Instant::now();
+            let outcome = client.capture_events(&batch, true, true).await;
+            stats.capture_requests += 1;
+            stats.capture_latencies.push(sent.elapsed().as_secs_f64() * 1000.0);
+            match outcome {
+                Ok(_) => stats.events_sent += batch.len(),
+                Err(e) => {
+                    stats.capture_failures += 1;
+                    tracing::warn!("Simulated capture failed: {}", e);
+                }
+            }
+        }
+        on_progress(&stats);
+        tokio::select! {
+            _ = &mut stop => break,
+            _ = tokio::time::sleep(config.think_time.sample(mean_think_time, &mut rng)) => {}
+        }
+    }
+
+    stats.finish(started.elapsed());
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_think_times_and_event_life_cycles() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mean = Duration::from_millis(100);
+        assert_eq!(ThinkTime::Constant.sample(mean, &mut rng), mean);
+        let draws: Vec<Duration> = (0..2000).map(|_| ThinkTime::Exponential.sample(mean, &mut rng)).collect();
+        let average = draws.iter().sum::<Duration>() / draws.len() as u32;
+        assert!(average > Duration::from_millis(90) && average < Duration::from_millis(110), "{:?}", average);
+        assert!(draws.iter().any(|draw| *draw > mean * 3));
+        assert!("lognormal".parse::<ThinkTime>().is_err());
+
+        // Queries take their share of requests without lowering the capture rate
+        let config = WorkloadConfig { rate: 10.0, batch_size: 2, query_ratio: 0.5, ..WorkloadConfig::default() };
+        assert_eq!(config.mean_think_time(), Duration::from_millis(100));
+        assert!(WorkloadConfig { query_ratio: 1.5, ..config }.validate().is_err());
+
+        let mut stream = EventStream::default();
+        let steps: Vec<(String, Option<String>)> =
+            (0..4).map(|_| stream.next_event()).map(|event| (event.epc_list[0].clone(), event.biz_step)).collect();
+        assert_eq!(steps[0].1.as_deref(), Some("commissioning"));
+        assert_eq!(steps[2], (steps[0].0.clone(), Some("receiving".to_string())));
+        assert_ne!(steps[3].0, steps[0].0);
+    }
+}
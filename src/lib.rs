@@ -1,11 +1,17 @@
 pub mod api;
 pub mod benchmarks;
+#[cfg(feature = "client")]
+pub mod client;
 pub mod config;
+pub mod context;
 pub mod models;
 pub mod monitoring;
 pub mod ontology;
 pub mod pipeline;
+pub mod scheduler;
 pub mod storage;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod utils;
 pub mod data_gen;
 
@@ -33,6 +39,9 @@ pub enum EpcisKgError {
     #[error("Validation error: {0}")]
     Validation(String),
     
+    #[error("Access denied: {0}")]
+    AccessDenied(String),
+    
     #[error("Not implemented: {0}")]
     NotImplemented(String),
     
@@ -42,6 +51,10 @@ pub enum EpcisKgError {
     #[error("RDF parsing error: {0}")]
     RdfParsing(String),
     
+    /// Where a Turtle or N-Triples document stops parsing; line and column start at 1
+    #[error("RDF syntax error at line {line}, column {column}: {message}")]
+    RdfSyntax { line: u64, column: u64, message: String },
+    
     #[error("TOML parsing error: {0}")]
     Toml(#[from] toml::de::Error),
     
@@ -51,12 +64,88 @@ pub enum EpcisKgError {
     #[error("Blank node ID parsing error: {0}")]
     BlankNodeIdParse(#[from] oxrdf::BlankNodeIdParseError),
     
+    #[error("Invalid {field}: {message}")]
+    InvalidField { field: String, message: String },
+    
+    #[error("Invalid IRI <{iri}>: {message}")]
+    InvalidIri { iri: String, message: String },
+    
+    #[error("{resource} not found: {id}")]
+    NotFound { resource: String, id: String },
+    
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    
+    #[error("Limit exceeded: {0}")]
+    LimitExceeded(String),
+    
+    #[error("Temporarily unavailable: {message}")]
+    Unavailable { message: String, retry_after_secs: Option<u64> },
+    
     #[error("Generic error: {0}")]
     Generic(#[from] Box<dyn std::error::Error + Send + Sync>),
 }
 
-// Re-export the new AppConfig for backwards compatibility
+impl EpcisKgError {
+    /// Stable machine-readable code, reported as `code` in API problem responses
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Io(_) => "io_error",
+            Self::Config(_) => "invalid_config",
+            Self::Ontology(_) => "ontology_error",
+            Self::Storage(_) => "storage_error",
+            Self::Query(_) => "invalid_query",
+            Self::Validation(_) => "validation_failed",
+            Self::AccessDenied(_) => "access_denied",
+            Self::NotImplemented(_) => "not_implemented",
+            Self::Json(_) => "invalid_json",
+            Self::RdfParsing(_) | Self::RdfSyntax { .. } => "invalid_rdf",
+            Self::Toml(_) => "invalid_toml",
+            Self::IriParse(_) | Self::InvalidIri { .. } => "invalid_iri",
+            Self::BlankNodeIdParse(_) => "invalid_blank_node",
+            Self::InvalidField { .. } => "invalid_field",
+            Self::NotFound { .. } => "not_found",
+            Self::Conflict(_) => "conflict",
+            Self::LimitExceeded(_) => "limit_exceeded",
+            Self::Unavailable { .. } => "unavailable",
+            Self::Generic(_) => "internal_error",
+        }
+    }
+    
+    /// Whether the same request may succeed if retried later
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Io(_) | Self::LimitExceeded(_) | Self::Unavailable { .. })
+    }
+    
+    /// Name of the offending request field, if the error is about one
+    pub fn field(&self) -> Option<&str> {
+        match self {
+            Self::InvalidField { field, .. } => Some(field),
+            _ => None,
+        }
+    }
+    
+    /// Line and column of an RDF syntax error
+    pub fn position(&self) -> Option<(u64, u64)> {
+        match self {
+            Self::RdfSyntax { line, column, .. } => Some((*line, *column)),
+            _ => None,
+        }
+    }
+    
+    /// The offending IRI, if the error is about one
+    pub fn iri(&self) -> Option<&str> {
+        match self {
+            Self::InvalidIri { iri, .. } => Some(iri),
+            _ => None,
+        }
+    }
+}
+
+// Re-export the new AppConfig for backwards compatibility; pass the loaded
+// configuration around through `AppContext` rather than `Config::default()`
 pub use config::AppConfig as Config;
+pub use context::AppContext;
 
 #[cfg(test)]
 mod tests {
@@ -88,6 +177,18 @@ mod tests {
         assert_eq!(error.to_string(), "Validation error: Invalid data");
     }
 
+    #[test]
+    fn test_structured_errors() {
+        let error = EpcisKgError::InvalidField { field: "eventTime".to_string(), message: "not RFC 3339".to_string() };
+        assert_eq!(error.code(), "invalid_field");
+        assert_eq!(error.field(), Some("eventTime"));
+        assert!(!error.is_retryable());
+        
+        let error = EpcisKgError::Unavailable { message: "reasoner busy".to_string(), retry_after_secs: Some(5) };
+        assert!(error.is_retryable());
+        assert_eq!(error.to_string(), "Temporarily unavailable: reasoner busy");
+    }
+
     #[test]
     fn test_error_from_io() {
         let io_error = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "access denied");
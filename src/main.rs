@@ -1,16 +1,34 @@
 use clap::{Parser, Subcommand};
-use epcis_knowledge_graph::{EpcisKgError, Config};
-use epcis_knowledge_graph::ontology::loader::OntologyLoader;
-use epcis_knowledge_graph::storage::oxigraph_store::OxigraphStore;
+use epcis_knowledge_graph::{AppContext, EpcisKgError, Config};
+use epcis_knowledge_graph::ontology::loader::OntologyData;
+use epcis_knowledge_graph::storage::archive::ArchiveFederation;
+use epcis_knowledge_graph::storage::encryption::{self, StoreKeyring};
+use epcis_knowledge_graph::storage::load_manifest::LoadManifest;
+use epcis_knowledge_graph::storage::materialized::MaterializedFilter;
+use epcis_knowledge_graph::storage::oxigraph_store::{LoadMode, OxigraphStore};
+use epcis_knowledge_graph::storage::quality::QualityOptions;
 use epcis_knowledge_graph::ontology::reasoner::OntologyReasoner;
-use epcis_knowledge_graph::pipeline::EpcisEventPipeline;
+use epcis_knowledge_graph::ontology::diagnostics::OntologyDiagnostics;
+use epcis_knowledge_graph::ontology::progress::ReasoningProgress;
+use epcis_knowledge_graph::ontology::runs::{self, InferenceRun};
+use epcis_knowledge_graph::ontology::scope::{InferenceScope, ScopeSummary};
+use epcis_knowledge_graph::pipeline::IngestPriority;
+use epcis_knowledge_graph::models::diff as event_diff;
+use epcis_knowledge_graph::models::document::parse_epcis_document;
 use epcis_knowledge_graph::models::epcis::EpcisEvent;
-use epcis_knowledge_graph::api::server::WebServer;
 use epcis_knowledge_graph::monitoring::metrics::{SystemMonitor, AlertSeverity};
+use epcis_knowledge_graph::monitoring::query_log;
+use epcis_knowledge_graph::monitoring::doctor::{self, CheckStatus, DoctorOptions};
 use epcis_knowledge_graph::monitoring::logging::{init_logging, LoggingConfig};
-use epcis_knowledge_graph::data_gen::{generator::EpcisDataGenerator, GeneratorConfig, DataScale, OutputFormat};
+use epcis_knowledge_graph::data_gen::{generator::EpcisDataGenerator, growth, GeneratorConfig, DataScale, OutputFormat};
+use epcis_knowledge_graph::data_gen::samples::{SamplePacks, SampleSource};
+use epcis_knowledge_graph::utils::anonymization::Pseudonymizer;
+use epcis_knowledge_graph::utils::signing::DocumentSigner;
+use epcis_knowledge_graph::utils::i18n::{self, MessageCatalog};
 use epcis_knowledge_graph::benchmarks::{run_performance_benchmarks, run_custom_benchmarks, DataScale as BenchmarkDataScale};
+use indicatif::{ProgressBar, ProgressStyle};
 use tracing::info;
+use std::sync::Arc;
 use std::time::Instant;
 use chrono;
 
@@ -32,6 +50,26 @@ struct Args {
     /// Configuration file path
     #[arg(short, long, default_value = "config/default.toml")]
     config: String,
+
+    /// Language of messages (e.g. de, es); defaults to localization.default_language
+    #[arg(long, global = true)]
+    lang: Option<String>,
+
+    /// Validate inputs and report what load, process or infer would store, without writing anything
+    #[arg(long, global = true)]
+    dry_run: bool,
+}
+
+/// Message catalog in the language of this run
+struct CliMessages {
+    catalog: MessageCatalog,
+    language: String,
+}
+
+impl CliMessages {
+    fn get(&self, key: &str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+        self.catalog.message(&self.language, key, args)
+    }
 }
 
 #[derive(Subcommand, Debug)]
@@ -53,6 +91,29 @@ enum Commands {
         /// Sample data scale (small, medium, large) - requires --use-samples-data
         #[arg(long, default_value = "medium")]
         samples_scale: String,
+
+        /// Serve a prepared dataset for queries only; every write is refused
+        #[arg(long, conflicts_with = "use_samples_data")]
+        read_only: bool,
+
+        /// Memory-map the dataset files while loading them (requires --read-only)
+        #[arg(long, requires = "read_only")]
+        mmap: bool,
+
+        /// Part in distributed ingestion (standalone, coordinator, worker); overrides [cluster] role
+        #[arg(long)]
+        role: Option<String>,
+    },
+
+    /// Accept scans at a site and forward them to the central server once it is reachable
+    Edge {
+        /// Port of the local capture API; overrides [edge] port
+        #[arg(short, long)]
+        port: Option<u16>,
+
+        /// Central server URL; overrides [edge] central_url
+        #[arg(long)]
+        central_url: Option<String>,
     },
 
     /// Load ontologies into the knowledge graph
@@ -64,6 +125,18 @@ enum Commands {
         /// Database path
         #[arg(short, long, default_value = "./data")]
         db_path: String,
+
+        /// Named graph to load into instead of the one derived from each file's path
+        #[arg(short, long)]
+        graph: Option<String>,
+
+        /// What to do when the graph already holds data (replace, merge, fail)
+        #[arg(short, long, default_value = "replace")]
+        mode: String,
+
+        /// Skip files an interrupted load already completed (per the database's load manifest)
+        #[arg(long)]
+        resume: bool,
     },
 
     /// Execute a SPARQL query
@@ -79,6 +152,59 @@ enum Commands {
         /// Output format (json, csv, tsv)
         #[arg(short, long, default_value = "json")]
         format: String,
+
+        /// Open the database read-only
+        #[arg(long)]
+        read_only: bool,
+
+        /// Memory-map the dataset files while loading them (requires --read-only)
+        #[arg(long, requires = "read_only")]
+        mmap: bool,
+
+        /// Also query the archived snapshots in the configured archive directory
+        #[arg(long)]
+        include_archives: bool,
+
+        /// Include only this archived snapshot (repeatable; requires --include-archives)
+        #[arg(long = "archive", requires = "include_archives")]
+        archives: Vec<String>,
+    },
+
+    /// Apply a SPARQL update (INSERT DATA, DELETE DATA, DELETE/INSERT WHERE, CLEAR, DROP)
+    Update {
+        /// SPARQL update string
+        #[arg(required = true)]
+        update: String,
+
+        /// Database path
+        #[arg(short, long, default_value = "./data")]
+        db_path: String,
+    },
+
+    /// Export the knowledge graph as N-Triples grouped by named graph
+    Export {
+        /// Database path
+        #[arg(short, long, default_value = "./data")]
+        db_path: String,
+
+        /// Output file (prints to stdout if omitted)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Pseudonymize EPC serials, GLNs and entity names using the [anonymization] settings
+        #[arg(long)]
+        anonymize: bool,
+
+        /// Export format: rdf, nquads (canonical RDFC-1.0 N-Quads, byte-identical
+        /// for the same data), or parquet (event, EPC list and sensor reading
+        /// tables written into the output directory; needs the `parquet` feature)
+        #[arg(long, default_value = "rdf")]
+        format: String,
+
+        /// Write a detached JWS of the export to `<output>.jws`, signed with
+        /// the [signing] key (needs `--format nquads` and `--output`)
+        #[arg(long)]
+        sign: bool,
     },
 
     /// Validate EPCIS events
@@ -105,6 +231,14 @@ enum Commands {
         /// Perform inference
         #[arg(short, long)]
         inference: bool,
+
+        /// Additional ontology file to check (repeatable; added to the configured ontology_paths)
+        #[arg(long = "ontology")]
+        ontologies: Vec<String>,
+
+        /// Also check ontologies already loaded into the store
+        #[arg(long)]
+        from_store: bool,
     },
 
     /// Comprehensive OWL profile validation
@@ -120,6 +254,19 @@ enum Commands {
         /// Output format (json, text)
         #[arg(short, long, default_value = "json")]
         format: String,
+
+        /// Additional ontology file to validate (repeatable; added to the configured ontology_paths)
+        #[arg(long = "ontology")]
+        ontologies: Vec<String>,
+
+        /// Also validate ontologies already loaded into the store
+        #[arg(long)]
+        from_store: bool,
+
+        /// Also report unsatisfiable classes, redundant subclass axioms and
+        /// classes or properties unused by the stored instance data
+        #[arg(long)]
+        diagnostics: bool,
     },
 
     /// Process EPCIS events
@@ -135,6 +282,10 @@ enum Commands {
         /// Output format (json, text)
         #[arg(short, long, default_value = "json")]
         format: String,
+        
+        /// Ingestion lane (realtime, backfill); real-time events go ahead of queued backfill
+        #[arg(long, default_value = "backfill")]
+        priority: String,
     },
 
     /// Initialize the knowledge graph
@@ -168,6 +319,18 @@ enum Commands {
         /// Output format (json, text)
         #[arg(short, long, default_value = "json")]
         format: String,
+
+        /// Named graph pattern forming the TBox (repeatable); reasons over store-resident graphs
+        #[arg(long = "tbox-graph")]
+        tbox_graphs: Vec<String>,
+
+        /// Named graph pattern forming the ABox (repeatable); reasons over store-resident graphs
+        #[arg(long = "abox-graph")]
+        abox_graphs: Vec<String>,
+
+        /// Reason over the ontologies and events already in the store instead of ontology files
+        #[arg(long)]
+        from_store: bool,
     },
 
     /// Manage materialized triples
@@ -183,6 +346,22 @@ enum Commands {
         /// Graph name (optional, for specific graphs)
         #[arg(short, long)]
         graph: Option<String>,
+
+        /// Only show triples with this predicate IRI
+        #[arg(long)]
+        predicate: Option<String>,
+
+        /// Only show triples whose subject IRI starts with this prefix
+        #[arg(long)]
+        subject_prefix: Option<String>,
+
+        /// Number of matching triples to skip
+        #[arg(long, default_value = "0")]
+        offset: usize,
+
+        /// Maximum number of triples to show
+        #[arg(long, default_value = "100")]
+        limit: usize,
     },
 
     /// Perform incremental inference on new data
@@ -218,9 +397,9 @@ enum Commands {
         #[arg(long, default_value = "10000")]
         cache_limit: usize,
 
-        /// Batch size for processing
-        #[arg(long, default_value = "1000")]
-        batch_size: usize,
+        /// Pin the batch size; without it the size adapts to observed latency
+        #[arg(long)]
+        batch_size: Option<usize>,
     },
 
     /// Perform parallel inference
@@ -240,7 +419,7 @@ enum Commands {
         #[arg(short, long, default_value = "./data")]
         db_path: String,
 
-        /// Action (metrics, alerts, health, status)
+        /// Action (metrics, alerts, health, status, quality)
         #[arg(required = true)]
         action: String,
 
@@ -251,6 +430,78 @@ enum Commands {
         /// Limit for alerts (default: 10)
         #[arg(long, default_value = "10")]
         limit: usize,
+
+        /// Quality report: only events at or after this RFC 3339 time
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Quality report: trend period (day, week, month)
+        #[arg(long, default_value = "day")]
+        bucket: String,
+    },
+
+    /// Compare two event sets and report added, removed and modified events
+    DiffEvents {
+        /// Baseline: an EPCIS JSON document or a database directory
+        #[arg(required = true)]
+        left: String,
+
+        /// Comparison, of the same kind as the baseline
+        #[arg(required = true)]
+        right: String,
+
+        /// Fields left out of the comparison (repeatable), e.g. record_time
+        #[arg(long = "ignore")]
+        ignore: Vec<String>,
+
+        /// Output format (json, text)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Re-encrypt the database's graph files with the current storage key
+    RotateKeys {
+        /// Database path
+        #[arg(short, long, default_value = "./data")]
+        db_path: String,
+
+        /// Output format (json, text)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Report on the query log: the queries that took the most time, with plans of slow runs
+    QueryLog {
+        /// Query log file (defaults to path in the [query_log] configuration section)
+        #[arg(long)]
+        path: Option<String>,
+
+        /// Only include executions over the slow-query threshold
+        #[arg(long)]
+        slow_only: bool,
+
+        /// Number of distinct queries to report
+        #[arg(long, default_value = "10")]
+        limit: usize,
+
+        /// Output format (json, text)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Check the configuration, database, ontologies, reasoner, disk space and port before serving
+    Doctor {
+        /// Database path (defaults to database_path from the configuration)
+        #[arg(short, long)]
+        db_path: Option<String>,
+
+        /// Server port to check (defaults to server_port from the configuration)
+        #[arg(short, long)]
+        port: Option<u16>,
+
+        /// Output format (json, text)
+        #[arg(short, long, default_value = "text")]
+        format: String,
     },
 
     /// Load pre-generated sample data into the knowledge graph
@@ -301,6 +552,57 @@ enum Commands {
         /// Database path (for loading)
         #[arg(short, long, default_value = "./data")]
         db_path: String,
+
+        /// Leave out locations and EPCs that no generated event refers to
+        #[arg(long)]
+        referenced_only: bool,
+
+        /// Extend the dataset already in the output path instead of generating a new one
+        #[arg(long)]
+        append: bool,
+
+        /// Days of events to append, one delta file per day (with --append)
+        #[arg(long, default_value = "1")]
+        days: u32,
+    },
+
+    /// Send live event and query traffic to a running server (needs the `client` feature)
+    Simulate {
+        /// Server root URL (defaults to localhost on server_port from the configuration)
+        #[arg(long)]
+        url: Option<String>,
+
+        /// API key sent with every request
+        #[arg(long)]
+        api_key: Option<String>,
+
+        /// Events captured per second, on average
+        #[arg(long, default_value = "10")]
+        rate: f64,
+
+        /// Events per capture request
+        #[arg(long, default_value = "1")]
+        batch_size: usize,
+
+        /// Think-time distribution between requests (constant, uniform, exponential)
+        #[arg(long, default_value = "exponential")]
+        think_time: String,
+
+        /// Share of requests that are SPARQL queries (0.0 to below 1.0)
+        #[arg(long, default_value = "0.0")]
+        query_ratio: f64,
+
+        /// Stop after this many seconds; runs until Ctrl-C when omitted
+        #[arg(long)]
+        duration: Option<u64>,
+
+        /// Random seed for think times and the query mix
+        #[arg(long, default_value = "42")]
+        seed: u64,
+
+        /// Output format of the final summary (json, text)
+        #[arg(short, long, default_value = "text")]
+        format: String,
     },
 
     /// Run performance benchmarks
@@ -331,9 +633,19 @@ enum Commands {
 async fn main() -> Result<(), EpcisKgError> {
     let args = Args::parse();
 
+    // The doctor reports an invalid configuration instead of stopping at it
+    if let Commands::Doctor { db_path, port, format } = &args.command {
+        return run_doctor(&args.config, db_path.clone(), *port, format);
+    }
+
     // Load configuration
     let config = Config::from_file_or_default(&args.config)?;
     config.validate()?;
+    if let Some(keyring) = StoreKeyring::from_config(&config.encryption)? {
+        encryption::install(keyring);
+    }
+    let context = AppContext::new(config);
+    let config = context.config();
 
     // Initialize structured logging system
     let logging_config = LoggingConfig {
@@ -342,6 +654,7 @@ async fn main() -> Result<(), EpcisKgError> {
         } else {
             config.log_level.clone()
         },
+        targets: config.log_targets.clone(),
         console_output: true,
         file_output: false,
         log_directory: std::path::PathBuf::from("./logs"),
@@ -355,9 +668,20 @@ async fn main() -> Result<(), EpcisKgError> {
     init_logging(logging_config).map_err(|e| EpcisKgError::Config(format!("Failed to initialize logging: {}", e)))?;
 
     info!("Starting EPCIS Knowledge Graph with configuration from: {}", args.config);
+    
+    let catalog = MessageCatalog::from_config(&config.localization)?;
+    let language = match &args.lang {
+        Some(lang) => catalog.negotiate(&i18n::accepted_languages(lang)),
+        None => catalog.default_language().to_string(),
+    };
+    let messages = CliMessages { catalog, language };
+    
+    if args.dry_run && !matches!(args.command, Commands::Load { .. } | Commands::Process { .. } | Commands::Infer { .. } | Commands::Update { .. }) {
+        return Err(EpcisKgError::Validation("--dry-run is supported by load, process, infer and update only".to_string()));
+    }
 
     match args.command {
-        Commands::Serve { port, db_path, use_samples_data, samples_scale } => {
+        Commands::Serve { port, db_path, use_samples_data, samples_scale, read_only, mmap, role } => {
             let final_port = if port != 8080 { port } else { config.server_port };
             let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
             
@@ -367,76 +691,153 @@ async fn main() -> Result<(), EpcisKgError> {
             );
             
             // Initialize the store
-            let mut store = OxigraphStore::new(&final_db_path)?;
+            let mut store = if read_only {
+                OxigraphStore::open_read_only(&final_db_path, mmap)?
+            } else {
+                OxigraphStore::new(&final_db_path)?
+            };
             
             // Load sample data if requested
             if use_samples_data {
                 info!("Loading sample data with scale: {}", samples_scale);
                 
-                // Determine sample file path based on scale
-                let sample_file = match samples_scale.to_lowercase().as_str() {
-                    "small" => "samples/epcis_data_small.ttl",
-                    "medium" => "samples/epcis_data_medium.ttl",
-                    "large" => "samples/epcis_data_large.ttl",
-                    "xlarge" => "samples/epcis_data_xlarge.ttl",
-                    _ => "samples/epcis_data_medium.ttl",
-                };
-                
-                // Check if sample file exists
-                if std::path::Path::new(sample_file).exists() {
-                    // Load the sample data directly into the existing store
-                    let loader = OntologyLoader::new();
-                    match loader.load_ontology(sample_file) {
-                        Ok(ontology_data) => {
-                            store.store_ontology_data(&ontology_data)?;
-                            println!("✓ Loaded {} triples of sample data", ontology_data.triples_count);
-                        },
-                        Err(e) => {
-                            eprintln!("⚠️  Failed to load sample data: {}", e);
-                            eprintln!("⚠️  Continuing with empty database...");
+                // Find the sample pack, downloading or generating it when missing
+                match SamplePacks::from_config(&config.samples).ensure(&samples_scale).await {
+                    Ok(pack) => {
+                        let file = pack.path.display().to_string();
+                        match pack.source {
+                            SampleSource::Downloaded => println!("{}", messages.get("serve.sample_downloaded", &[("file", &file)])),
+                            SampleSource::Generated => println!("{}", messages.get("serve.sample_generated", &[("file", &file)])),
+                            SampleSource::Local => {}
+                        }
+                        
+                        // Load the sample data directly into the existing store
+                        let loader = context.loader();
+                        match loader.load_ontology(&pack.path) {
+                            Ok(ontology_data) => {
+                                store.store_ontology_data(&ontology_data)?;
+                                println!("{}", messages.get("serve.sample_loaded", &[("count", &ontology_data.triples_count)]));
+                            },
+                            Err(e) => {
+                                eprintln!("{}", messages.get("serve.sample_failed", &[("error", &e)]));
+                                eprintln!("{}", messages.get("serve.sample_continue_empty", &[]));
+                            }
                         }
                     }
-                } else {
-                    eprintln!("⚠️  Sample file not found: {}", sample_file);
-                    eprintln!("⚠️  Run 'cargo run -- generate --scale {} --output-path samples/' first.", samples_scale);
+                    Err(e) => {
+                        eprintln!("{}", messages.get("serve.sample_missing", &[("scale", &samples_scale), ("error", &e)]));
+                        eprintln!("{}", messages.get("serve.sample_generate_hint", &[("scale", &samples_scale)]));
+                    }
                 }
             }
             
             // Create and run the web server
-            let web_server = WebServer::new(config.clone(), store).await?;
+            let web_server = match role {
+                Some(role) => {
+                    let mut server_config = config.clone();
+                    server_config.cluster.role = role.parse()?;
+                    server_config.validate()?;
+                    info!("Serving as cluster {}", server_config.cluster.role.as_str());
+                    AppContext::from(server_config).web_server(store).await?
+                }
+                None => context.web_server(store).await?,
+            };
             
-            println!("🚀 Starting EPCIS Knowledge Graph server...");
-            println!("📊 Server will be available at: http://localhost:{}", final_port);
-            println!("🔍 SPARQL endpoint: http://localhost:{}/api/v1/sparql", final_port);
-            println!("📖 API documentation: http://localhost:{}/", final_port);
+            println!("{}", messages.get("serve.starting", &[]));
+            println!("{}", messages.get("serve.available_at", &[("url", &format!("http://localhost:{}", final_port))]));
+            println!("{}", messages.get("serve.sparql_endpoint", &[("url", &format!("http://localhost:{}/api/v1/sparql", final_port))]));
+            println!("{}", messages.get("serve.api_docs", &[("url", &format!("http://localhost:{}/", final_port))]));
             if use_samples_data {
-                println!("📦 Sample data loaded ({} scale)", samples_scale);
+                println!("{}", messages.get("serve.samples_ready", &[("scale", &samples_scale)]));
+            }
+            if read_only {
+                println!("{}", messages.get(if mmap { "serve.read_only_mmap" } else { "serve.read_only" }, &[]));
             }
-            println!("⏹️  Press Ctrl+C to stop the server");
+            println!("{}", messages.get("serve.stop_hint", &[]));
             
             if let Err(e) = web_server.run(final_port).await {
-                eprintln!("❌ Server error: {}", e);
+                eprintln!("{}", messages.get("serve.error", &[("error", &e)]));
                 return Err(EpcisKgError::Config(format!("Failed to start server: {}", e)));
             }
         }
-        Commands::Load { files, db_path } => {
+        Commands::Edge { port, central_url } => {
+            let mut edge = config.edge.clone();
+            edge.port = port.unwrap_or(edge.port);
+            if let Some(central_url) = central_url {
+                edge.central_url = central_url;
+            }
+            info!("Starting edge capture on port {}, forwarding to {}", edge.port, edge.central_url);
+            epcis_knowledge_graph::api::edge::run(&edge).await?;
+        }
+        Commands::Load { files, db_path, graph, mode, resume } => {
             let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
             
             info!(
                 "Loading ontologies from {:?} into database at {}",
                 files, final_db_path
             );
-            load_ontologies(&files, &final_db_path)?;
+            load_ontologies(&context, &files, &final_db_path, graph.as_deref(), mode.parse()?, resume, args.dry_run)?;
         }
         Commands::Query {
             query,
             db_path,
             format,
+            read_only,
+            mmap,
+            include_archives,
+            archives,
         } => {
             let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
             
             info!("Executing query against database at {}", final_db_path);
-            execute_query(&query, &final_db_path, &format)?;
+            let store = if read_only {
+                OxigraphStore::open_read_only(&final_db_path, mmap)?
+            } else {
+                OxigraphStore::new(&final_db_path)?
+            };
+            if include_archives {
+                let federation = ArchiveFederation::new(&config.archive);
+                let names = if archives.is_empty() { None } else { Some(archives.as_slice()) };
+                let (view, included) = federation.federated_view(&store, names)?;
+                info!("Including {} archived snapshot(s) from {}: {:?}", included.len(), config.archive.directory, included);
+                execute_query(&query, &view, &format)?;
+            } else {
+                execute_query(&query, &store, &format)?;
+            }
+        }
+        Commands::Update { update, db_path } => {
+            let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
+            if !config.sparql.enable_updates {
+                return Err(EpcisKgError::AccessDenied("SPARQL updates are disabled by configuration".to_string()));
+            }
+            
+            info!("Applying update to database at {}", final_db_path);
+            let mut store = open_store(&final_db_path, args.dry_run)?;
+            let report = store.update(&update)?;
+            if args.dry_run {
+                println!("Dry run: the update would insert {} and delete {} triple(s)", report.inserted, report.deleted);
+            } else {
+                println!("Inserted {} and deleted {} triple(s)", report.inserted, report.deleted);
+            }
+        }
+        Commands::Export { db_path, output, anonymize, format, sign } => {
+            let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
+            
+            info!(
+                "Exporting knowledge graph at {} (format: {}, anonymize: {})",
+                final_db_path, format, anonymize
+            );
+            if sign && (format != "nquads" || output.is_none()) {
+                return Err(EpcisKgError::Validation("--sign needs --format nquads and --output".to_string()));
+            }
+            match format.as_str() {
+                "rdf" => export_knowledge_graph(&context, &final_db_path, output.as_deref(), anonymize, false, false)?,
+                "nquads" => export_knowledge_graph(&context, &final_db_path, output.as_deref(), anonymize, true, sign)?,
+                "parquet" => export_event_tables(&final_db_path, output.as_deref().unwrap_or("./export"), anonymize, config)?,
+                other => {
+                    return Err(EpcisKgError::Validation(format!("Unknown export format: {} (expected rdf, nquads or parquet)", other)));
+                }
+            }
         }
         Commands::Validate {
             event_file,
@@ -451,7 +852,7 @@ async fn main() -> Result<(), EpcisKgError> {
             // TODO: Implement event validation
             println!("Event validation not yet implemented");
         }
-        Commands::Reason { db_path, profile, inference } => {
+        Commands::Reason { db_path, profile, inference, ontologies, from_store } => {
             let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
             let final_profile = if profile != "el" { profile } else { config.reasoning.default_profile.clone() };
             
@@ -459,9 +860,10 @@ async fn main() -> Result<(), EpcisKgError> {
                 "Performing reasoning on knowledge graph at {} (profile: {}, inference: {})",
                 final_db_path, final_profile, inference
             );
-            perform_reasoning(&final_db_path, &final_profile, inference)?;
+            let sources = OntologySources::new(&context, ontologies, from_store);
+            perform_reasoning(&context, &final_db_path, &final_profile, inference, &sources)?;
         }
-        Commands::Profile { db_path, profile, format } => {
+        Commands::Profile { db_path, profile, format, ontologies, from_store, diagnostics } => {
             let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
             let final_profile = if profile != "el" { profile } else { config.reasoning.default_profile.clone() };
             
@@ -469,16 +871,17 @@ async fn main() -> Result<(), EpcisKgError> {
                 "Performing comprehensive OWL profile validation on knowledge graph at {} (profile: {})",
                 final_db_path, final_profile
             );
-            perform_profile_validation(&final_db_path, &final_profile, &format)?;
+            let sources = OntologySources::new(&context, ontologies, from_store);
+            perform_profile_validation(&context, &final_db_path, &final_profile, &format, &sources, diagnostics)?;
         }
-        Commands::Process { db_path, event_file, format } => {
+        Commands::Process { db_path, event_file, format, priority } => {
             let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
             
             info!(
                 "Processing EPCIS events from {} using knowledge graph at {}",
                 event_file, final_db_path
             );
-            perform_event_processing(&final_db_path, &event_file, &format)?;
+            perform_event_processing(&context, &final_db_path, &event_file, &format, priority.parse()?, args.dry_run)?;
         }
         Commands::Init { db_path, force } => {
             let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
@@ -487,25 +890,30 @@ async fn main() -> Result<(), EpcisKgError> {
                 "Initializing knowledge graph at {} (force: {})",
                 final_db_path, force
             );
-            initialize_knowledge_graph(&final_db_path, force, &config.ontology_paths)?;
+            initialize_knowledge_graph(&context, &final_db_path, force, &config.ontology_paths, &config.localization.label_paths, &messages)?;
         }
-        Commands::Infer { db_path, strategy, clear, format } => {
+        Commands::Infer { db_path, strategy, clear, format, tbox_graphs, abox_graphs, from_store } => {
             let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
             
             info!(
                 "Performing inference with materialization (strategy: {}, clear: {}) on knowledge graph at {}",
                 strategy, clear, final_db_path
             );
-            perform_inference_with_materialization(&final_db_path, &strategy, clear, &format)?;
+            let scope = (from_store || !tbox_graphs.is_empty() || !abox_graphs.is_empty()).then(|| {
+                InferenceScope::from_config(&config.reasoning.scope)
+                    .with_overrides(Some(tbox_graphs), Some(abox_graphs))
+            });
+            perform_inference_with_materialization(&context, &final_db_path, &strategy, clear, &format, scope.as_ref(), args.dry_run)?;
         }
-        Commands::Materialize { db_path, action, graph } => {
+        Commands::Materialize { db_path, action, graph, predicate, subject_prefix, offset, limit } => {
             let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
             
             info!(
                 "Managing materialized triples (action: {}) on knowledge graph at {}",
                 action, final_db_path
             );
-            manage_materialized_triples(&final_db_path, &action, &graph)?;
+            let filter = MaterializedFilter { graph, predicate, subject_prefix, offset, limit };
+            manage_materialized_triples(&context, &final_db_path, &action, &filter)?;
         }
         Commands::Increment { db_path, triples_file, format } => {
             let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
@@ -514,7 +922,7 @@ async fn main() -> Result<(), EpcisKgError> {
                 "Performing incremental inference on new data from {} using knowledge graph at {}",
                 triples_file, final_db_path
             );
-            perform_incremental_inference(&final_db_path, &triples_file, &format)?;
+            perform_incremental_inference(&context, &final_db_path, &triples_file, &format)?;
         }
         Commands::Optimize { db_path, action, parallel, cache_limit, batch_size } => {
             let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
@@ -523,7 +931,7 @@ async fn main() -> Result<(), EpcisKgError> {
                 "Performing optimization action '{}' on knowledge graph at {}",
                 action, final_db_path
             );
-            perform_optimization(&final_db_path, &action, parallel, cache_limit, batch_size)?;
+            perform_optimization(&context, &final_db_path, &action, parallel, cache_limit, batch_size)?;
         }
         Commands::ParallelInfer { db_path, format } => {
             let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
@@ -532,16 +940,40 @@ async fn main() -> Result<(), EpcisKgError> {
                 "Performing parallel inference using knowledge graph at {}",
                 final_db_path
             );
-            perform_parallel_inference(&final_db_path, &format)?;
+            perform_parallel_inference(&context, &final_db_path, &format)?;
         }
-        Commands::Monitor { db_path, action, format, limit } => {
+        Commands::Monitor { db_path, action, format, limit, since, bucket } => {
             let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
             
             info!(
                 "Performing monitoring action '{}' using knowledge graph at {}",
                 action, final_db_path
             );
-            perform_monitoring_action(&final_db_path, &action, format, limit)?;
+            let quality = QualityOptions {
+                since: since
+                    .map(|since| chrono::DateTime::parse_from_rfc3339(&since).map(|time| time.with_timezone(&chrono::Utc)))
+                    .transpose()
+                    .map_err(|e| EpcisKgError::Validation(format!("Invalid --since time: {}", e)))?,
+                until: None,
+                bucket: bucket.parse()?,
+            };
+            perform_monitoring_action(&final_db_path, &action, format, limit, &quality)?;
+        }
+        Commands::DiffEvents { left, right, ignore, format } => {
+            info!("Comparing events of {} with {}", left, right);
+            diff_event_sets(&left, &right, &ignore, &format)?;
+        }
+        Commands::RotateKeys { db_path, format } => {
+            let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
+            info!("Re-encrypting graph files at {}", final_db_path);
+            rotate_storage_keys(&final_db_path, &format)?;
+        }
+        Commands::QueryLog { path, slow_only, limit, format } => {
+            let path = path.unwrap_or_else(|| config.query_log.path.clone());
+            report_query_log(&path, slow_only, limit, &format)?;
+        }
+        Commands::Doctor { .. } => {
+            // Handled before the configuration is loaded
         }
         Commands::LoadSamples { scale, db_path, force } => {
             let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
@@ -551,7 +983,7 @@ async fn main() -> Result<(), EpcisKgError> {
                 scale, final_db_path
             );
             
-            match load_sample_data(&scale, &final_db_path, force) {
+            match load_sample_data(&context, &scale, &final_db_path, force).await {
                 Ok(count) => {
                     println!("✓ Successfully loaded {} triples of sample data", count);
                     println!("✓ Sample data scale: {}", scale);
@@ -573,7 +1005,10 @@ async fn main() -> Result<(), EpcisKgError> {
             products, 
             events, 
             load, 
-            db_path 
+            db_path,
+            referenced_only,
+            append,
+            days,
         } => {
             info!(
                 "Generating test data with scale '{}' to output path {}",
@@ -603,6 +1038,7 @@ async fn main() -> Result<(), EpcisKgError> {
                 output_format,
                 output_path: std::path::PathBuf::from(&output_path),
                 custom_counts: None,
+                referenced_master_data_only: referenced_only,
             };
             
             // Override with custom counts if provided
@@ -614,9 +1050,15 @@ async fn main() -> Result<(), EpcisKgError> {
                 ));
             }
             
-            // Generate data
-            let generator = EpcisDataGenerator::new();
-            match generator.generate_dataset(&generator_config) {
+            // Generate data, or continue the existing dataset's EPC life cycles
+            let generated = if append {
+                info!("Appending {} day(s) of events to the dataset in {}", days, output_path);
+                growth::find_dataset(std::path::Path::new(&output_path))
+                    .and_then(|dataset| growth::append_days(&dataset, days))
+            } else {
+                EpcisDataGenerator::new().generate_dataset(&generator_config)
+            };
+            match generated {
                 Ok(result) => {
                     println!("✓ Data generation completed successfully");
                     println!("  - Generated {} triples", result.triple_count);
@@ -629,13 +1071,26 @@ async fn main() -> Result<(), EpcisKgError> {
                         println!("  - Output file: {}", file);
                     }
                     
+                    // Report event references without master data
+                    if result.integrity.is_consistent() {
+                        println!("  - Referential integrity: every EPC and location has master data");
+                    } else {
+                        println!("  - Referential integrity: {} dangling reference(s)", result.integrity.dangling.len());
+                        for dangling in result.integrity.dangling.iter().take(10) {
+                            println!("      {} {} {}", dangling.event_id, dangling.role, dangling.id);
+                        }
+                    }
+                    if !result.integrity.unreferenced.is_empty() {
+                        println!("  - Master data without events: {}", result.integrity.unreferenced.len());
+                    }
+                    
                     // Load data into database if requested
                     if load {
                         let final_db_path = if db_path != "./data" { db_path } else { config.database_path.clone() };
                         info!("Loading generated data into database at {}", final_db_path);
                         
                         for file in &result.output_files {
-                            match load_generated_data(file, &final_db_path) {
+                            match load_generated_data(&context, file, &final_db_path) {
                                 Ok(count) => {
                                     println!("✓ Loaded {} triples from {}", count, file);
                                 },
@@ -709,27 +1164,73 @@ async fn main() -> Result<(), EpcisKgError> {
             }
         }
         Commands::Config => {
-            show_configuration(&config)?;
+            show_configuration(config)?;
+        }
+        Commands::Simulate { url, api_key, rate, batch_size, think_time, query_ratio, duration, seed, format } => {
+            let url = url.unwrap_or_else(|| format!("http://localhost:{}", config.server_port));
+            run_simulation(&url, api_key, rate, batch_size, &think_time, query_ratio, duration, seed, &format).await?;
         }
     }
 
     Ok(())
 }
 
+/// Open the store at `db_path`; for a dry run, an in-memory copy that is never written back
+fn open_store(db_path: &str, dry_run: bool) -> Result<OxigraphStore, EpcisKgError> {
+    let store = OxigraphStore::new(db_path)?;
+    Ok(if dry_run { store.with_overlay(&Default::default()) } else { store })
+}
+
+/// Print the first few triples a dry run would write
+fn print_example_triples(triples: impl IntoIterator<Item = String>, total: usize) {
+    const EXAMPLES: usize = 3;
+    for triple in triples.into_iter().take(EXAMPLES) {
+        println!("    e.g. {}", triple);
+    }
+    if total > EXAMPLES {
+        println!("    ... and {} more", total - EXAMPLES);
+    }
+}
+
 /// Load ontologies from files into the knowledge graph
-fn load_ontologies(files: &[String], db_path: &str) -> Result<(), EpcisKgError> {
-    let mut store = OxigraphStore::new(db_path)?;
-    let loader = OntologyLoader::new();
+fn load_ontologies(context: &AppContext, files: &[String], db_path: &str, graph: Option<&str>, mode: LoadMode, resume: bool, dry_run: bool) -> Result<(), EpcisKgError> {
+    let mut store = open_store(db_path, dry_run)?;
+    let loader = context.loader();
+    let mut manifest = LoadManifest::open(db_path, resume)?;
     
-    println!("Loading ontologies...");
+    println!("{}", if dry_run { "Validating ontologies (dry run)..." } else { "Loading ontologies..." });
     let mut total_triples = 0;
-    
-    for file in files {
+    let mut skipped = 0;
+    let style = ProgressStyle::with_template("{prefix} [{bar:40}] {bytes}/{total_bytes} ({eta})")
+        .expect("valid progress template")
+        .progress_chars("=> ");
+    
+    for (index, file) in files.iter().enumerate() {
+        if let Some(done) = manifest.completed(std::path::Path::new(file)) {
+            println!("↷ Skipping {} (loaded {} triples into {} at {})", file, done.triples, done.graph, done.loaded_at);
+            skipped += 1;
+            continue;
+        }
+        
         info!("Loading ontology from: {}", file);
-        match loader.load_ontology(file) {
+        let size = std::fs::metadata(file).map(|metadata| metadata.len()).unwrap_or(0);
+        let bar = ProgressBar::new(size)
+            .with_style(style.clone())
+            .with_prefix(format!("[{}/{}] {}", index + 1, files.len(), file));
+        let loaded = loader.load_ontology_with_progress(file, |read| bar.inc(read));
+        bar.finish_and_clear();
+        match loaded {
             Ok(ontology_data) => {
-                store.store_ontology_data(&ontology_data)?;
-                println!("✓ Loaded {} triples from {}", ontology_data.triples_count, file);
+                // Several files loaded into one named graph have to be merged
+                let file_mode = if graph.is_some() && index > 0 { LoadMode::Merge } else { mode };
+                let graph_name = store.store_ontology_data_as(&ontology_data, graph, file_mode)?;
+                if dry_run {
+                    println!("✓ Would load {} triples from {} into {}", ontology_data.triples_count, file, graph_name);
+                    print_example_triples(ontology_data.graph.iter().map(|triple| triple.to_string()), ontology_data.triples_count);
+                } else {
+                    manifest.record(std::path::Path::new(file), &graph_name, ontology_data.triples_count)?;
+                    println!("✓ Loaded {} triples from {} into {}", ontology_data.triples_count, file, graph_name);
+                }
                 total_triples += ontology_data.triples_count;
                 
                 // Print basic statistics
@@ -740,23 +1241,35 @@ fn load_ontologies(files: &[String], db_path: &str) -> Result<(), EpcisKgError>
             },
             Err(e) => {
                 eprintln!("✗ Failed to load ontology from {}: {}", file, e);
+                if index > 0 {
+                    eprintln!("  Re-run with --resume to continue after the files already loaded");
+                }
                 return Err(e);
             }
         }
     }
     
     let store_stats = store.get_statistics()?;
-    println!("\n✓ Successfully loaded {} total triples", total_triples);
+    if dry_run {
+        println!("\n✓ Validated {} total triples", total_triples);
+    } else {
+        println!("\n✓ Successfully loaded {} total triples", total_triples);
+    }
+    if skipped > 0 {
+        println!("  - Files skipped (already loaded): {}", skipped);
+    }
     println!("  - Named graphs: {}", store_stats.named_graphs);
-    println!("  - Storage path: {}", store_stats.storage_path);
+    if dry_run {
+        println!("Dry run: nothing was written to {}", db_path);
+    } else {
+        println!("  - Storage path: {}", store_stats.storage_path);
+    }
     
     Ok(())
 }
 
 /// Execute a SPARQL query against the knowledge graph
-fn execute_query(query: &str, db_path: &str, format: &str) -> Result<(), EpcisKgError> {
-    let store = OxigraphStore::new(db_path)?;
-    
+fn execute_query(query: &str, store: &OxigraphStore, format: &str) -> Result<(), EpcisKgError> {
     info!("Executing SPARQL query: {}", query);
     
     // Determine query type and execute
@@ -791,46 +1304,182 @@ fn execute_query(query: &str, db_path: &str, format: &str) -> Result<(), EpcisKg
     Ok(())
 }
 
+/// Export event data as Parquet tables for analytics tools such as Spark or DuckDB
+fn export_event_tables(db_path: &str, output_dir: &str, anonymize: bool, config: &Config) -> Result<(), EpcisKgError> {
+    let store = OxigraphStore::new(db_path)?;
+    let mut tables = store.event_tables();
+    if anonymize {
+        tables.pseudonymize(&Pseudonymizer::from_config(config.anonymization.clone())?);
+    }
+    
+    #[cfg(feature = "parquet")]
+    {
+        tables.write_parquet(std::path::Path::new(output_dir))?;
+        println!(
+            "✓ Exported {} events, {} EPC list entries and {} sensor readings to {}",
+            tables.events.len(),
+            tables.epc_list.len(),
+            tables.sensor_readings.len(),
+            output_dir
+        );
+        Ok(())
+    }
+    #[cfg(not(feature = "parquet"))]
+    {
+        let _ = (tables, output_dir);
+        Err(EpcisKgError::Config(
+            "Parquet export is not available; rebuild with `--features parquet`".to_string(),
+        ))
+    }
+}
+
+/// Export the knowledge graph, optionally pseudonymized for sharing with researchers
+/// and optionally as canonical N-Quads with a detached signature
+fn export_knowledge_graph(
+    context: &AppContext,
+    db_path: &str,
+    output: Option<&str>,
+    anonymize: bool,
+    canonical: bool,
+    sign: bool,
+) -> Result<(), EpcisKgError> {
+    let config = context.config();
+    let store = OxigraphStore::new(db_path)?;
+    
+    let exported = if anonymize {
+        let pseudonymizer = Pseudonymizer::from_config(config.anonymization.clone())?;
+        if canonical {
+            store.export_pseudonymized_nquads(&pseudonymizer)?
+        } else {
+            store.export_pseudonymized(&pseudonymizer)?
+        }
+    } else if canonical {
+        store.export_canonical_nquads()?
+    } else {
+        store.export_turtle()?
+    };
+    
+    match output {
+        Some(path) => {
+            std::fs::write(path, &exported)?;
+            let stats = store.get_statistics()?;
+            println!("✓ Exported {} named graphs to {}", stats.named_graphs, path);
+            if anonymize {
+                println!("✓ EPC serials, GLNs and configured predicates were pseudonymized");
+            }
+            if sign {
+                let signer = DocumentSigner::from_config(config.signing.clone())?;
+                let signature_path = format!("{}.jws", path);
+                std::fs::write(&signature_path, signer.sign_detached(exported.as_bytes()))?;
+                println!("✓ Signed with {} ({})", config.signing.algorithm, signature_path);
+            }
+        },
+        None => {
+            print!("{}", exported);
+        }
+    }
+    
+    Ok(())
+}
+
+/// Ontologies checked by the Reason and Profile commands
+struct OntologySources {
+    context: AppContext,
+    files: Vec<String>,
+    from_store: bool,
+    scope: InferenceScope,
+}
+
+impl OntologySources {
+    /// Configured `ontology_paths` followed by any `--ontology` files not already listed
+    fn new(context: &AppContext, extra_files: Vec<String>, from_store: bool) -> Self {
+        let config = context.config();
+        let mut files = config.ontology_paths.clone();
+        for file in extra_files {
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+        
+        Self {
+            context: context.clone(),
+            files,
+            from_store,
+            scope: InferenceScope::from_config(&config.reasoning.scope),
+        }
+    }
+    
+    /// Load every source, labelled by file path or store graph name.
+    ///
+    /// Configured files that do not exist are skipped; explicit failures are
+    /// reported as errors so the caller can print them.
+    fn load(&self, store: &OxigraphStore) -> Vec<(String, Result<OntologyData, EpcisKgError>)> {
+        let loader = self.context.loader();
+        let mut sources: Vec<(String, Result<OntologyData, EpcisKgError>)> = self
+            .files
+            .iter()
+            .filter(|file| {
+                let exists = std::path::Path::new(file.as_str()).exists();
+                if !exists {
+                    info!("Ontology not found, skipping: {}", file);
+                }
+                exists
+            })
+            .map(|file| (file.clone(), loader.load_ontology(file)))
+            .collect();
+        
+        if self.from_store {
+            sources.extend(
+                store
+                    .stored_ontologies()
+                    .into_iter()
+                    .map(|ontology_data| (ontology_data.source_file.clone(), Ok(ontology_data))),
+            );
+        }
+        
+        sources
+    }
+}
+
 /// Perform reasoning on the knowledge graph
-fn perform_reasoning(db_path: &str, profile: &str, inference: bool) -> Result<(), EpcisKgError> {
+fn perform_reasoning(context: &AppContext, db_path: &str, profile: &str, inference: bool, sources: &OntologySources) -> Result<(), EpcisKgError> {
     let store = OxigraphStore::new(db_path)?;
-    let mut reasoner = OntologyReasoner::with_store(store);
+    let ontologies = sources.load(&store);
+    let resident_store = sources.from_store.then(|| store.clone());
+    let mut reasoner = context.reasoner_with_store(store);
     
     println!("Performing reasoning with OWL 2 {} profile", profile.to_uppercase());
     
-    // Load ontologies for validation
-    let loader = OntologyLoader::new();
     let mut validation_results = Vec::new();
     
-    // Try to validate ontologies if they exist
-    let ontology_files = vec!["ontologies/epcis2.ttl", "ontologies/cbv.ttl"];
+    if ontologies.is_empty() {
+        validation_results.push("No ontologies found (check ontology_paths, --ontology or --from-store)".to_string());
+    }
     
-    for file in ontology_files {
-        if std::path::Path::new(file).exists() {
-            match loader.load_ontology(file) {
-                Ok(ontology_data) => {
-                    match reasoner.validate_ontology(&ontology_data) {
-                        Ok(()) => {
-                            validation_results.push(format!("✓ {} validation passed", file));
-                        },
-                        Err(e) => {
-                            validation_results.push(format!("✗ {} validation failed: {}", file, e));
-                        }
+    for (file, loaded) in ontologies {
+        match loaded {
+            Ok(ontology_data) => {
+                match reasoner.validate_ontology(&ontology_data) {
+                    Ok(()) => {
+                        validation_results.push(format!("✓ {} validation passed", file));
+                    },
+                    Err(e) => {
+                        validation_results.push(format!("✗ {} validation failed: {}", file, e));
                     }
-                    
-                    // Check OWL profile
-                    match reasoner.check_owl_profile(&ontology_data, profile) {
-                        Ok(()) => {
-                            validation_results.push(format!("✓ {} {} profile compliant", file, profile.to_uppercase()));
-                        },
-                        Err(e) => {
-                            validation_results.push(format!("✗ {} {} profile violation: {}", file, profile.to_uppercase(), e));
-                        }
+                }
+                
+                // Check OWL profile
+                match reasoner.check_owl_profile(&ontology_data, profile) {
+                    Ok(()) => {
+                        validation_results.push(format!("✓ {} {} profile compliant", file, profile.to_uppercase()));
+                    },
+                    Err(e) => {
+                        validation_results.push(format!("✗ {} {} profile violation: {}", file, profile.to_uppercase(), e));
                     }
-                },
-                Err(e) => {
-                    validation_results.push(format!("✗ Failed to load {}: {}", file, e));
                 }
+            },
+            Err(e) => {
+                validation_results.push(format!("✗ Failed to load {}: {}", file, e));
             }
         }
     }
@@ -843,6 +1492,11 @@ fn perform_reasoning(db_path: &str, profile: &str, inference: bool) -> Result<()
     
     // Perform inference if requested
     if inference {
+        if let Some(resident_store) = &resident_store {
+            let summary = reasoner.load_scope(resident_store, &sources.scope)?;
+            print_scope_summary(&summary);
+        }
+        
         println!("\nPerforming inference...");
         match reasoner.perform_inference() {
             Ok(inferences) => {
@@ -872,7 +1526,14 @@ fn perform_reasoning(db_path: &str, profile: &str, inference: bool) -> Result<()
 }
 
 /// Initialize the knowledge graph
-fn initialize_knowledge_graph(db_path: &str, force: bool, default_ontologies: &[String]) -> Result<(), EpcisKgError> {
+fn initialize_knowledge_graph(
+    context: &AppContext,
+    db_path: &str,
+    force: bool,
+    default_ontologies: &[String],
+    label_files: &[String],
+    messages: &CliMessages,
+) -> Result<(), EpcisKgError> {
     let path = std::path::Path::new(db_path);
     
     if path.exists() && !force {
@@ -893,21 +1554,26 @@ fn initialize_knowledge_graph(db_path: &str, force: bool, default_ontologies: &[
     // Initialize an empty store
     let mut store = OxigraphStore::new(db_path)?;
     
-    // Load default ontologies if they exist
+    // Load default ontologies and their translated labels if they exist
     let mut loaded_count = 0;
-    let loader = OntologyLoader::new();
+    let mut label_count = 0;
+    let loader = context.loader();
     
-    for ontology_file in default_ontologies {
+    for (ontology_file, is_labels) in default_ontologies.iter().map(|file| (file, false)).chain(label_files.iter().map(|file| (file, true))) {
         if std::path::Path::new(ontology_file).exists() {
             info!("Loading default ontology: {}", ontology_file);
             match loader.load_ontology(ontology_file) {
                 Ok(ontology_data) => {
                     store.store_ontology_data(&ontology_data)?;
-                    loaded_count += 1;
-                    println!("✓ Loaded {} triples from {}", ontology_data.triples_count, ontology_file);
+                    if is_labels {
+                        label_count += 1;
+                    } else {
+                        loaded_count += 1;
+                    }
+                    println!("{}", messages.get("init.loaded", &[("count", &ontology_data.triples_count), ("file", ontology_file)]));
                 },
                 Err(e) => {
-                    eprintln!("Warning: Failed to load default ontology {}: {}", ontology_file, e);
+                    eprintln!("{}", messages.get("init.load_failed", &[("file", ontology_file), ("error", &e)]));
                 }
             }
         } else {
@@ -916,10 +1582,10 @@ fn initialize_knowledge_graph(db_path: &str, force: bool, default_ontologies: &[
     }
     
     let stats = store.get_statistics()?;
-    println!("✓ Knowledge graph initialized at {}", db_path);
-    println!("  - Loaded {} default ontologies", loaded_count);
-    println!("  - Total triples: {}", stats.total_quads);
-    println!("  - Named graphs: {}", stats.named_graphs);
+    println!("{}", messages.get("init.initialized", &[("path", &db_path)]));
+    println!("{}", messages.get("init.ontologies", &[("count", &loaded_count)]));
+    println!("{}", messages.get("init.label_files", &[("count", &label_count)]));
+    println!("{}", messages.get("init.named_graphs", &[("count", &stats.named_graphs)]));
     
     Ok(())
 }
@@ -938,6 +1604,7 @@ fn show_configuration(config: &Config) -> Result<(), EpcisKgError> {
     println!("    - Default Profile: {}", config.reasoning.default_profile);
     println!("    - Enable Inference: {}", config.reasoning.enable_inference);
     println!("    - Max Inference Time: {}s", config.reasoning.max_inference_time);
+    println!("    - Warm Start: {} ({})", config.reasoning.warm_start, config.reasoning.cache_dir);
     println!("  SPARQL:");
     println!("    - Max Query Time: {}s", config.sparql.max_query_time);
     println!("    - Max Results: {}", config.sparql.max_results);
@@ -955,47 +1622,49 @@ fn show_configuration(config: &Config) -> Result<(), EpcisKgError> {
 }
 
 /// Perform comprehensive OWL profile validation
-fn perform_profile_validation(db_path: &str, profile: &str, format: &str) -> Result<(), EpcisKgError> {
+fn perform_profile_validation(
+    context: &AppContext,
+    db_path: &str,
+    profile: &str,
+    format: &str,
+    sources: &OntologySources,
+    with_diagnostics: bool,
+) -> Result<(), EpcisKgError> {
     let store = OxigraphStore::new(db_path)?;
-    let mut reasoner = OntologyReasoner::with_store(store);
+    let ontologies = sources.load(&store);
+    let mut reasoner = context.reasoner_with_store(store);
     
     println!("Performing comprehensive OWL 2 {} profile validation", profile.to_uppercase());
     
-    // Load ontologies for validation
-    let loader = OntologyLoader::new();
     let mut validation_results = Vec::new();
+    let mut diagnostics = Vec::new();
     
-    // Try to load and validate each ontology
-    let default_ontologies = vec![
-        "ontologies/epcis2.ttl".to_string(),
-        "ontologies/cbv.ttl".to_string(),
-    ];
-    
-    for ontology_file in &default_ontologies {
-        if std::path::Path::new(ontology_file).exists() {
-            println!("Validating ontology: {}", ontology_file);
-            
-            match loader.load_ontology(ontology_file) {
-                Ok(ontology_data) => {
-                    match reasoner.validate_owl_profile_comprehensive(&ontology_data, profile) {
-                        Ok(result) => {
-                            validation_results.push((ontology_file.clone(), result));
-                        },
-                        Err(e) => {
-                            eprintln!("✗ Failed to validate {}: {}", ontology_file, e);
-                        }
+    for (ontology_file, loaded) in ontologies {
+        println!("Validating ontology: {}", ontology_file);
+        
+        match loaded {
+            Ok(ontology_data) => {
+                if with_diagnostics {
+                    diagnostics.push((ontology_file.clone(), reasoner.diagnostics(&ontology_data, &sources.scope)));
+                }
+                match reasoner.validate_owl_profile_comprehensive(&ontology_data, profile) {
+                    Ok(result) => {
+                        validation_results.push((ontology_file.clone(), result));
+                    },
+                    Err(e) => {
+                        eprintln!("✗ Failed to validate {}: {}", ontology_file, e);
                     }
-                },
-                Err(e) => {
-                    eprintln!("✗ Failed to load {}: {}", ontology_file, e);
                 }
+            },
+            Err(e) => {
+                eprintln!("✗ Failed to load {}: {}", ontology_file, e);
             }
         }
     }
     
     // Display results
     if format == "json" {
-        let json_output = serde_json::json!({
+        let mut json_output = serde_json::json!({
             "profile": profile,
             "validation_results": validation_results,
             "summary": {
@@ -1004,6 +1673,9 @@ fn perform_profile_validation(db_path: &str, profile: &str, format: &str) -> Res
                 "non_conforming_ontologies": validation_results.iter().filter(|(_, r)| !r.conforms).count(),
             }
         });
+        if with_diagnostics {
+            json_output["diagnostics"] = serde_json::to_value(&diagnostics)?;
+        }
         println!("{}", serde_json::to_string_pretty(&json_output)?);
     } else {
         // Text format
@@ -1059,6 +1731,10 @@ fn perform_profile_validation(db_path: &str, profile: &str, format: &str) -> Res
             }
         }
         
+        for (file, found) in &diagnostics {
+            print_diagnostics(file, found);
+        }
+        
         println!("\n=== Summary ===");
         let total = validation_results.len();
         let conforming = validation_results.iter().filter(|(_, r)| r.conforms).count();
@@ -1067,16 +1743,41 @@ fn perform_profile_validation(db_path: &str, profile: &str, format: &str) -> Res
         println!("Total ontologies: {}", total);
         println!("Conforming: {}", conforming);
         println!("Non-conforming: {}", non_conforming);
-        println!("Success rate: {:.1}%", (conforming as f64 / total as f64) * 100.0);
+        if total > 0 {
+            println!("Success rate: {:.1}%", (conforming as f64 / total as f64) * 100.0);
+        }
     }
     
     Ok(())
 }
 
+/// Print the quality diagnostics of one ontology
+fn print_diagnostics(file: &str, diagnostics: &OntologyDiagnostics) {
+    println!("\n🩺 Diagnostics for {}", file);
+    if diagnostics.is_clean() {
+        println!("  No issues found");
+        return;
+    }
+    for unsatisfiable in &diagnostics.unsatisfiable_classes {
+        println!("  ❌ Unsatisfiable: {} ({})", unsatisfiable.class, unsatisfiable.reason);
+    }
+    for axiom in &diagnostics.redundant_axioms {
+        println!("  ⚠️  Redundant: {} rdfs:subClassOf {} ({})", axiom.sub_class, axiom.super_class, axiom.reason);
+    }
+    if diagnostics.usage_checked {
+        for class in &diagnostics.unused_classes {
+            println!("  ℹ️  Unused class: {}", class);
+        }
+        for property in &diagnostics.unused_properties {
+            println!("  ℹ️  Unused property: {}", property);
+        }
+    }
+}
+
 /// Perform EPCIS event processing
-fn perform_event_processing(db_path: &str, event_file: &str, format: &str) -> Result<(), EpcisKgError> {
-    let store = OxigraphStore::new(db_path)?;
-    let reasoner = OntologyReasoner::with_store(store.clone());
+fn perform_event_processing(context: &AppContext, db_path: &str, event_file: &str, format: &str, priority: IngestPriority, dry_run: bool) -> Result<(), EpcisKgError> {
+    let store = open_store(db_path, dry_run)?;
+    let reasoner = context.reasoner_with_store(store.clone());
     
     println!("Processing EPCIS events from: {}", event_file);
     
@@ -1085,21 +1786,29 @@ fn perform_event_processing(db_path: &str, event_file: &str, format: &str) -> Re
     println!("Loaded {} events from file", events.len());
     
     // Create event processing pipeline
-    let config = Config::default();
-    let mut pipeline = futures::executor::block_on(EpcisEventPipeline::new(
-        config,
-        store,
-        reasoner,
-    ))?;
-    
-    // Process events
+    let mut pipeline = futures::executor::block_on(context.pipeline(store, reasoner))?;
+    
+    // Process events; a dry run keeps them to list the triples they would add
+    let dry_run_events = if dry_run { events.clone() } else { Vec::new() };
     let start_time = std::time::Instant::now();
-    let results = futures::executor::block_on(pipeline.process_events_batch(events));
+    let results = futures::executor::block_on(pipeline.process_events_with_priority(events, priority));
     let processing_time = start_time.elapsed();
     
+    // Triples the valid events would add, with a few examples
+    let mut triples_to_store = 0;
+    let mut example_triples = Vec::new();
+    for event in &dry_run_events {
+        if !results.iter().any(|result| result.success && result.event_id == event.event_id) {
+            continue;
+        }
+        let triples = pipeline.event_triples(event)?;
+        triples_to_store += triples.len();
+        example_triples.extend(triples.iter().take(3usize.saturating_sub(example_triples.len())).map(|triple| triple.to_string()));
+    }
+    
     // Display results
     if format == "json" {
-        let json_output = serde_json::json!({
+        let mut json_output = serde_json::json!({
             "event_file": event_file,
             "total_events": results.len(),
             "successful_events": results.iter().filter(|r| r.success).count(),
@@ -1111,8 +1820,15 @@ fn perform_event_processing(db_path: &str, event_file: &str, format: &str) -> Re
             "total_triples_generated": results.iter().map(|r| r.triples_generated).sum::<usize>(),
             "total_inferences_made": results.iter().map(|r| r.inferences_made).sum::<usize>(),
             "results": results,
-            "pipeline_stats": pipeline.get_stats()
+            "pipeline_stats": pipeline.get_stats(),
+            "schema_drift": pipeline.schema_drift()
         });
+        if dry_run {
+            json_output["dry_run"] = serde_json::json!({
+                "triples_to_store": triples_to_store,
+                "example_triples": example_triples,
+            });
+        }
         println!("{}", serde_json::to_string_pretty(&json_output)?);
     } else {
         // Text format
@@ -1148,6 +1864,16 @@ fn perform_event_processing(db_path: &str, event_file: &str, format: &str) -> Re
             }
         }
         
+        // Terms the loaded ontologies do not define
+        let drift = pipeline.schema_drift();
+        if !drift.unknown_terms.is_empty() {
+            println!("\n=== Schema Drift ===");
+            println!("{} of {} events use terms no loaded ontology defines:", drift.events_with_unknown_terms, drift.events_checked);
+            for term in &drift.unknown_terms {
+                println!("⚠️  {} {} ({} events, e.g. {})", term.kind.as_str(), term.iri, term.events, term.example_event);
+            }
+        }
+        
         // Show pipeline statistics
         let stats = pipeline.get_stats();
         println!("\n=== Pipeline Statistics ===");
@@ -1157,15 +1883,259 @@ fn perform_event_processing(db_path: &str, event_file: &str, format: &str) -> Re
         println!("Validation errors: {}", stats.validation_errors);
         println!("Processing errors: {}", stats.processing_errors);
         println!("Average processing time: {:.2}ms", stats.average_processing_time_ms);
+        if stats.auto_corrections > 0 {
+            println!("Auto-corrected CBV values: {}", stats.auto_corrections);
+        }
+        for (lane, lane_stats) in [("Real-time", &stats.lanes.realtime), ("Backfill", &stats.lanes.backfill)] {
+            if lane_stats.processed > 0 {
+                println!(
+                    "{} lane: {} events, {:.2}ms average wait, {:.2}ms average processing",
+                    lane, lane_stats.processed, lane_stats.average_wait_ms, lane_stats.average_processing_time_ms
+                );
+            }
+        }
         
         if let Some(last_time) = stats.last_processed_time {
             println!("Last processed: {}", last_time);
         }
+        
+        if dry_run {
+            println!("\n=== Dry Run ===");
+            println!("Triples the valid events would store: {}", triples_to_store);
+            print_example_triples(example_triples, triples_to_store);
+            println!("Dry run: nothing was written to {}", db_path);
+        }
+    }
+    
+    Ok(())
+}
+
+/// Compare two EPCIS documents, or the events of two databases
+fn diff_event_sets(left: &str, right: &str, ignore: &[String], format: &str) -> Result<(), EpcisKgError> {
+    let diff = match (std::path::Path::new(left).is_dir(), std::path::Path::new(right).is_dir()) {
+        (false, false) => event_diff::diff_documents(&load_event_document(left)?, &load_event_document(right)?, ignore),
+        (true, true) => event_diff::diff_events(
+            &OxigraphStore::open_read_only(left, false)?.event_fields(),
+            &OxigraphStore::open_read_only(right, false)?.event_fields(),
+            ignore,
+        ),
+        _ => {
+            return Err(EpcisKgError::Validation(
+                "Compare a document with a document, or a database with a database".to_string(),
+            ))
+        }
+    };
+    
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+        return Ok(());
+    }
+    
+    println!("=== Event Diff ===");
+    println!("Left: {} ({} events)", left, diff.left_events);
+    println!("Right: {} ({} events)", right, diff.right_events);
+    println!(
+        "Added: {}, removed: {}, modified: {}, unchanged: {}",
+        diff.added.len(), diff.removed.len(), diff.modified.len(), diff.unchanged
+    );
+    for event_id in &diff.added {
+        println!("+ {}", event_id);
+    }
+    for event_id in &diff.removed {
+        println!("- {}", event_id);
+    }
+    for event in &diff.modified {
+        println!("~ {}", event.event_id);
+        for change in &event.changes {
+            println!("    {}:", change.field);
+            for value in &change.removed {
+                println!("      - {}", value);
+            }
+            for value in &change.added {
+                println!("      + {}", value);
+            }
+        }
+    }
+    if diff.is_empty() {
+        println!("✓ The event sets are identical");
+    }
+    
+    Ok(())
+}
+
+/// Seal every graph file with the current storage key, opening old files with the retired keys
+fn rotate_storage_keys(db_path: &str, format: &str) -> Result<(), EpcisKgError> {
+    let keyring = encryption::installed().ok_or_else(|| {
+        EpcisKgError::Config("rotate-keys needs [encryption] enabled with the new key as the current key".to_string())
+    })?;
+    let report = encryption::rotate_keys(std::path::Path::new(db_path), &keyring)?;
+    
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
     }
     
+    println!("=== Key Rotation ===");
+    println!("Database: {}", db_path);
+    println!("Current key: {}", report.key_id);
+    println!("Re-encrypted files: {}", report.rewritten);
+    println!("Already up to date: {}", report.up_to_date);
+    println!("✓ Retired keys can be removed from retired_key_envs once every server has restarted");
+    
     Ok(())
 }
 
+/// Events of a file holding either the pipeline's event array or a standard EPCIS 2.0 JSON document
+fn run_doctor(config_path: &str, db_path: Option<String>, port: Option<u16>, format: &str) -> Result<(), EpcisKgError> {
+    let options = DoctorOptions { config_path: config_path.to_string(), db_path, port };
+    let report = doctor::run_checks(&options);
+    
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!("=== Doctor ===");
+        for check in &report.checks {
+            let mark = match check.status {
+                CheckStatus::Pass => "✓",
+                CheckStatus::Warn => "!",
+                CheckStatus::Fail => "✗",
+            };
+            println!("{} {}: {}", mark, check.name, check.detail);
+            if let Some(hint) = &check.hint {
+                println!("    hint: {}", hint);
+            }
+        }
+        println!();
+        println!("{} check(s), {} warning(s), {} failure(s)", report.checks.len(), report.warnings(), report.failures());
+    }
+    
+    match report.failures() {
+        0 => Ok(()),
+        failures => Err(EpcisKgError::Validation(format!("{} doctor check(s) failed", failures))),
+    }
+}
+
+/// Print the queries that took the most total time in a query log file
+fn report_query_log(path: &str, slow_only: bool, limit: usize, format: &str) -> Result<(), EpcisKgError> {
+    let mut entries = query_log::read_log(path)?;
+    if slow_only {
+        entries.retain(|entry| entry.slow);
+    }
+    let mut summaries = query_log::summarize(&entries);
+    summaries.truncate(limit);
+    
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+            "path": path,
+            "entries": entries.len(),
+            "queries": summaries
+        }))?);
+        return Ok(());
+    }
+    
+    println!("=== Query Log ({} executions in {}) ===", entries.len(), path);
+    for (rank, summary) in summaries.iter().enumerate() {
+        println!();
+        println!("{}. {}", rank + 1, summary.query);
+        println!(
+            "   {} run(s), {} slow, {} failed | avg {:.1}ms, max {:.1}ms",
+            summary.executions, summary.slow_executions, summary.failures, summary.avg_duration_ms, summary.max_duration_ms
+        );
+        if !summary.callers.is_empty() {
+            println!("   callers: {}", summary.callers.join(", "));
+        }
+        if let Some(plan) = &summary.plan {
+            let graphs = if plan.dataset_graphs.is_empty() { "all graphs".to_string() } else { plan.dataset_graphs.join(", ") };
+            println!(
+                "   plan: {} of {} ({} graph(s), {} triples), limit {}, {} VALUES block(s)",
+                plan.strategy, graphs, plan.graphs_scanned, plan.triples_scanned, plan.limit, plan.values_blocks
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Drive synthetic traffic against a running server and print a summary
+#[allow(clippy::too_many_arguments)]
+async fn run_simulation(
+    url: &str,
+    api_key: Option<String>,
+    rate: f64,
+    batch_size: usize,
+    think_time: &str,
+    query_ratio: f64,
+    duration: Option<u64>,
+    seed: u64,
+    format: &str,
+) -> Result<(), EpcisKgError> {
+    #[cfg(feature = "client")]
+    {
+        use epcis_knowledge_graph::client::EpcisClient;
+        use epcis_knowledge_graph::data_gen::workload::{self, WorkloadConfig};
+        
+        let workload_config = WorkloadConfig {
+            rate,
+            batch_size,
+            think_time: think_time.parse()?,
+            query_ratio,
+            duration: duration.map(std::time::Duration::from_secs),
+            seed,
+        };
+        workload_config.validate()?;
+        let mut client = EpcisClient::new(url);
+        if let Some(api_key) = api_key {
+            client = client.with_api_key(api_key);
+        }
+        
+        println!("Simulating {} events/s against {} (Ctrl-C to stop)", rate, url);
+        let mut last_report = std::time::Instant::now();
+        let stop = async {
+            let _ = tokio::signal::ctrl_c().await;
+        };
+        let stats = workload::run(&client, &workload_config, stop, |stats| {
+            if last_report.elapsed() >= std::time::Duration::from_secs(10) {
+                last_report = std::time::Instant::now();
+                println!(
+                    "  {} events sent, {} queries, {} failed request(s)",
+                    stats.events_sent,
+                    stats.queries_sent,
+                    stats.capture_failures + stats.query_failures
+                );
+            }
+        })
+        .await?;
+        
+        if format == "json" {
+            println!("{}", serde_json::to_string_pretty(&stats)?);
+        } else {
+            println!("✓ Simulation finished after {:.1}s", stats.elapsed_ms as f64 / 1000.0);
+            println!("  - Events sent: {} ({:.1}/s)", stats.events_sent, stats.event_rate());
+            println!("  - Capture requests: {} ({} failed)", stats.capture_requests, stats.capture_failures);
+            println!("  - Capture latency: p50 {:.1}ms, p95 {:.1}ms", stats.capture_latency_p50_ms, stats.capture_latency_p95_ms);
+            if stats.queries_sent > 0 {
+                println!("  - Queries: {} ({} failed)", stats.queries_sent, stats.query_failures);
+                println!("  - Query latency: p50 {:.1}ms, p95 {:.1}ms", stats.query_latency_p50_ms, stats.query_latency_p95_ms);
+            }
+        }
+        Ok(())
+    }
+    #[cfg(not(feature = "client"))]
+    {
+        let _ = (url, api_key, rate, batch_size, think_time, query_ratio, duration, seed, format);
+        Err(EpcisKgError::Config(
+            "Simulation is not available; rebuild with `--features client`".to_string(),
+        ))
+    }
+}
+
+fn load_event_document(file_path: &str) -> Result<Vec<EpcisEvent>, EpcisKgError> {
+    let document: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(file_path)?)?;
+    match serde_json::from_value::<Vec<EpcisEvent>>(document.clone()) {
+        Ok(events) => Ok(events),
+        Err(_) => parse_epcis_document(&document),
+    }
+}
+
 /// Load EPCIS events from a JSON file
 fn load_events_from_file(file_path: &str) -> Result<Vec<EpcisEvent>, EpcisKgError> {
     let content = std::fs::read_to_string(file_path)
@@ -1177,10 +2147,31 @@ fn load_events_from_file(file_path: &str) -> Result<Vec<EpcisEvent>, EpcisKgErro
     Ok(events)
 }
 
+fn print_scope_summary(summary: &ScopeSummary) {
+    println!(
+        "✓ Loaded inference scope: {} TBox graphs ({} triples), {} ABox graphs ({} triples)",
+        summary.tbox_graphs.len(), summary.tbox_triples, summary.abox_graphs.len(), summary.abox_triples
+    );
+}
+
 /// Perform inference with materialization
-fn perform_inference_with_materialization(db_path: &str, strategy: &str, clear: bool, format: &str) -> Result<(), EpcisKgError> {
-    let store = OxigraphStore::new(db_path)?;
-    let mut reasoner = OntologyReasoner::with_store(store);
+fn perform_inference_with_materialization(
+    context: &AppContext,
+    db_path: &str,
+    strategy: &str,
+    clear: bool,
+    format: &str,
+    scope: Option<&InferenceScope>,
+    dry_run: bool,
+) -> Result<(), EpcisKgError> {
+    let config = context.config();
+    let store = open_store(db_path, dry_run)?;
+    let resident_store = store.clone();
+    let mut reasoner = context.reasoner_with_store(store);
+    // The warm-start cache is written to disk, so a dry run reasons from scratch
+    if config.reasoning.warm_start && !dry_run {
+        reasoner.enable_warm_start(&config.reasoning.cache_dir);
+    }
     
     println!("Performing inference with materialization strategy: {}", strategy);
     
@@ -1203,19 +2194,31 @@ fn perform_inference_with_materialization(db_path: &str, strategy: &str, clear:
         reasoner.clear_materialized_triples();
     }
     
-    // Load ontologies for inference
-    let loader = OntologyLoader::new();
+    // Load ontologies for inference, either from the store's scoped graphs or from files
+    let loader = context.loader();
     let mut ontology_loaded = false;
     
-    let ontology_files = vec!["ontologies/epcis2.ttl", "ontologies/cbv.ttl"];
+    let mut scope_summary = None;
+    let mut ontology_versions = std::collections::BTreeMap::new();
+    
+    let ontology_files = if let Some(scope) = scope {
+        let summary = reasoner.load_scope(&resident_store, scope)?;
+        print_scope_summary(&summary);
+        scope_summary = Some(summary);
+        ontology_loaded = true;
+        Vec::new()
+    } else {
+        config.ontology_paths.clone()
+    };
     
-    for file in ontology_files {
+    for file in &ontology_files {
         if std::path::Path::new(file).exists() {
             match loader.load_ontology(file) {
                 Ok(ontology_data) => {
-                    match reasoner.load_ontology_data(&ontology_data) {
-                        Ok(()) => {
-                            println!("✓ Loaded ontology for inference: {}", file);
+                    match reasoner.load_ontology_data_warm(&ontology_data) {
+                        Ok(warm) => {
+                            println!("✓ Loaded ontology for inference: {}{}", file, if warm { " (warm start)" } else { "" });
+                            ontology_versions.insert(file.clone(), runs::ontology_version(&ontology_data.graph));
                             ontology_loaded = true;
                         },
                         Err(e) => {
@@ -1230,32 +2233,81 @@ fn perform_inference_with_materialization(db_path: &str, strategy: &str, clear:
         }
     }
     
+    // Nothing on disk: fall back to whatever has already been captured in the store
+    if !ontology_loaded {
+        if !resident_store.stored_ontologies().is_empty() {
+            println!("No ontology files found; reasoning over store-resident graphs");
+            let summary = reasoner.load_scope(&resident_store, &InferenceScope::from_config(&config.reasoning.scope))?;
+            print_scope_summary(&summary);
+            scope_summary = Some(summary);
+            ontology_loaded = true;
+        }
+    }
+    
     if !ontology_loaded {
         return Err(EpcisKgError::Validation("No ontologies loaded for inference".to_string()));
     }
     
+    if let Some(summary) = &scope_summary {
+        ontology_versions = runs::ontology_versions(&resident_store, &summary.tbox_graphs);
+    }
+    
     // Perform inference with materialization
     println!("Performing inference with materialization...");
     let start_time = std::time::Instant::now();
-    
-    match reasoner.perform_inference_with_materialization() {
+    let started_at = chrono::Utc::now();
+    
+    let bar = ProgressBar::new(100).with_style(
+        ProgressStyle::with_template("[{bar:40}] {pos:>3}% {msg}")
+            .expect("valid progress template")
+            .progress_chars("=> "),
+    );
+    let progress_bar = bar.clone();
+    reasoner.set_progress_callback(Some(Arc::new(move |progress: &ReasoningProgress| {
+        progress_bar.set_position(progress.percent.round() as u64);
+        let eta = progress.eta_ms.map_or_else(|| "?".to_string(), |ms| format!("{:.1}s", ms as f64 / 1000.0));
+        progress_bar.set_message(format!("{} - {} triples, ETA {}", progress.phase, progress.triples_materialized, eta));
+    })));
+    let inference = reasoner.perform_inference_with_materialization();
+    bar.finish_and_clear();
+    
+    match inference {
         Ok(result) => {
             let processing_time = start_time.elapsed();
             
+            // Persist inferred triples with RDF-star provenance annotations
+            let persisted = reasoner.persist_materialized_with_provenance()?;
+            info!("Persisted {} inferred triples with provenance annotations", persisted);
+            
+            let run = InferenceRun {
+                ontology_versions,
+                tbox_triples: scope_summary.as_ref().map_or(0, |summary| summary.tbox_triples),
+                abox_triples: scope_summary.as_ref().map_or(0, |summary| summary.abox_triples),
+                ..InferenceRun::new("cli", strategy, started_at, &result, &config.reasoning)
+            };
+            if let Some(store) = reasoner.store_mut() {
+                store.record_inference_run(&run)?;
+            }
+            info!("Recorded inference run {}", run.id);
+            
             // Display results
             if format == "json" {
                 let stats = reasoner.get_detailed_stats();
                 let json_output = serde_json::json!({
+                    "run_id": run.id,
                     "inference_result": result,
                     "materialization_strategy": strategy,
                     "processing_time_ms": processing_time.as_millis() as u64,
                     "inference_stats": stats,
-                    "materialized_triples_count": reasoner.get_materialized_triples().len()
+                    "rule_usage": reasoner.rule_usage_report(10),
+                    "materialized_triples_count": reasoner.get_materialized_triples().len(),
+                    "dry_run": dry_run
                 });
                 println!("{}", serde_json::to_string_pretty(&json_output)?);
             } else {
                 // Text format
                 println!("\n=== Inference with Materialization Results ===");
+                println!("Run ID: {}", run.id);
                 println!("Strategy: {}", strategy);
                 println!("Processing time: {:?}", processing_time);
                 println!("Consistent: {}", if result.consistent { "✅ Yes" } else { "❌ No" });
@@ -1287,6 +2339,23 @@ fn perform_inference_with_materialization(db_path: &str, strategy: &str, clear:
                 println!("Cache misses: {}", stats.cache_misses);
                 println!("Cache hit rate: {:.2}%", stats.cache_hit_rate() * 100.0);
                 
+                // Show which rules and axioms did the work
+                let usage = reasoner.rule_usage_report(10);
+                println!("\n=== Rule Usage ===");
+                for rule in &usage.rules {
+                    println!("  {}: {} firings", rule.rule, rule.firings);
+                }
+                if !usage.hot_axioms.is_empty() {
+                    println!("Hot axioms ({:.1}% of axiom firings):", usage.hot_axiom_share * 100.0);
+                    for count in &usage.hot_axioms {
+                        println!("  {} ⊑ {}: {}", count.axiom.sub, count.axiom.super_, count.firings);
+                    }
+                }
+                println!("Unused axioms: {} of {}", usage.unused_axioms.len(), usage.declared_axioms);
+                for axiom in usage.unused_axioms.iter().take(10) {
+                    println!("  {} ⊑ {}", axiom.sub, axiom.super_);
+                }
+                
                 // Show materialized triples sample
                 let materialized = reasoner.get_materialized_triples();
                 if !materialized.is_empty() {
@@ -1305,6 +2374,10 @@ fn perform_inference_with_materialization(db_path: &str, strategy: &str, clear:
                         println!("  ... and {} more", total_triples - 5);
                     }
                 }
+                
+                if dry_run {
+                    println!("\nDry run: {} inferred triples were not persisted; nothing was written to {}", persisted, db_path);
+                }
             }
         },
         Err(e) => {
@@ -1317,57 +2390,35 @@ fn perform_inference_with_materialization(db_path: &str, strategy: &str, clear:
 }
 
 /// Manage materialized triples
-fn manage_materialized_triples(db_path: &str, action: &str, graph: &Option<String>) -> Result<(), EpcisKgError> {
+fn manage_materialized_triples(context: &AppContext, db_path: &str, action: &str, filter: &MaterializedFilter) -> Result<(), EpcisKgError> {
     let store = OxigraphStore::new(db_path)?;
-    let mut reasoner = OntologyReasoner::with_store(store);
+    let page = store.materialized_triples(filter)?;
+    let mut reasoner = context.reasoner_with_store(store);
+    let graph = &filter.graph;
     
     println!("Managing materialized triples - Action: {}", action);
     
     match action.to_lowercase().as_str() {
         "show" => {
-            let materialized = reasoner.get_materialized_triples();
             let stats = reasoner.get_detailed_stats();
             
             println!("\n=== Materialized Triples ===");
-            println!("Total materialized triples: {}", materialized.len());
+            println!("Matching materialized triples: {}", page.total);
             println!("Materialization strategy: {:?}", stats.strategy);
             println!("Total inferences performed: {}", stats.total_inferences);
             println!("Last inference time: {:?}", stats.last_inference_time);
             
-            if let Some(graph_name) = graph {
-                // Show triples for specific graph
-                println!("\nTriples in graph '{}':", graph_name);
-                if let Some(triples) = reasoner.get_materialized_triples_for_graph(graph_name) {
-                    for (i, triple) in triples.iter().enumerate() {
-                        println!("  {}. {} {} {}", i + 1, triple.subject, triple.predicate, triple.object);
-                    }
-                } else {
-                    println!("  No triples found in graph '{}'", graph_name);
-                }
+            if page.triples.is_empty() {
+                println!("No materialized triples found");
             } else {
-                // Show all materialized triples
-                let total_triples: usize = materialized.values().map(|v| v.len()).sum();
-                if total_triples > 0 {
-                    println!("\nAll materialized triples:");
-                    let mut count = 0;
-                    for (_graph_name, triples) in materialized {
-                        for triple in triples {
-                            println!("  {}. {} {} {}", count + 1, triple.subject, triple.predicate, triple.object);
-                            count += 1;
-                        }
-                    }
-                } else {
-                    println!("No materialized triples found");
-                }
-                
-                // Show by graph
-                if !reasoner.get_materialized_triples().is_empty() {
-                    println!("\nBy graph:");
-                    for (graph_name, triples) in reasoner.get_materialized_triples() {
-                        println!("  '{}': {} triples", graph_name, triples.len());
-                    }
+                println!("\nTriples {}-{} of {}:", page.offset + 1, page.offset + page.triples.len(), page.total);
+                for (i, triple) in page.triples.iter().enumerate() {
+                    println!("  {}. [{}] {} {} {}", page.offset + i + 1, triple.graph, triple.subject, triple.predicate, triple.object);
                 }
             }
+            if let Some(next_offset) = page.next_offset {
+                println!("\nMore triples match; continue with --offset {}", next_offset);
+            }
         },
         "clear" => {
             let count = reasoner.get_materialized_triples().len();
@@ -1411,9 +2462,9 @@ fn manage_materialized_triples(db_path: &str, action: &str, graph: &Option<Strin
 }
 
 /// Perform incremental inference on new data
-fn perform_incremental_inference(db_path: &str, triples_file: &str, format: &str) -> Result<(), EpcisKgError> {
+fn perform_incremental_inference(context: &AppContext, db_path: &str, triples_file: &str, format: &str) -> Result<(), EpcisKgError> {
     let store = OxigraphStore::new(db_path)?;
-    let mut reasoner = OntologyReasoner::with_store(store);
+    let mut reasoner = context.reasoner_with_store(store);
     
     println!("Performing incremental inference on new data from: {}", triples_file);
     
@@ -1532,19 +2583,26 @@ fn load_triples_from_file(file_path: &str) -> Result<Vec<oxrdf::Triple>, EpcisKg
 }
 
 /// Perform performance optimization actions
-fn perform_optimization(db_path: &str, action: &str, parallel: bool, cache_limit: usize, batch_size: usize) -> Result<(), EpcisKgError> {
-    let mut reasoner = OntologyReasoner::with_store(OxigraphStore::new(db_path)?);
+fn perform_optimization(context: &AppContext, db_path: &str, action: &str, parallel: bool, cache_limit: usize, batch_size: Option<usize>) -> Result<(), EpcisKgError> {
+    let mut reasoner = context.reasoner_with_store(OxigraphStore::new(db_path)?);
+    let configure = |reasoner: &mut OntologyReasoner| match batch_size {
+        Some(batch_size) => reasoner.configure_performance(parallel, cache_limit, batch_size),
+        None => reasoner.configure_adaptive_performance(parallel, cache_limit),
+    };
     
     match action {
         "configure" => {
-            reasoner.configure_performance(parallel, cache_limit, batch_size);
+            configure(&mut reasoner);
             println!("✓ Performance configuration updated:");
             println!("  - Parallel processing: {}", parallel);
             println!("  - Cache limit: {}", cache_limit);
-            println!("  - Batch size: {}", batch_size);
+            match batch_size {
+                Some(batch_size) => println!("  - Batch size: {} (pinned)", batch_size),
+                None => println!("  - Batch size: adaptive, starting at {}", reasoner.get_batch_size()),
+            }
         },
         "run" => {
-            reasoner.configure_performance(parallel, cache_limit, batch_size);
+            configure(&mut reasoner);
             reasoner.optimize_performance()?;
         },
         "report" => {
@@ -1564,8 +2622,8 @@ fn perform_optimization(db_path: &str, action: &str, parallel: bool, cache_limit
 }
 
 /// Perform parallel inference
-fn perform_parallel_inference(db_path: &str, format: &str) -> Result<(), EpcisKgError> {
-    let mut reasoner = OntologyReasoner::with_store(OxigraphStore::new(db_path)?);
+fn perform_parallel_inference(context: &AppContext, db_path: &str, format: &str) -> Result<(), EpcisKgError> {
+    let mut reasoner = context.reasoner_with_store(OxigraphStore::new(db_path)?);
     
     match reasoner.perform_parallel_inference() {
         Ok(result) => {
@@ -1627,8 +2685,12 @@ fn run_performance_benchmark(reasoner: &mut OntologyReasoner) -> Result<(), Epci
 }
 
 /// Perform monitoring actions
-fn perform_monitoring_action(_db_path: &str, action: &str, format: String, limit: usize) -> Result<(), EpcisKgError> {
-    let monitor = SystemMonitor::new();
+fn perform_monitoring_action(db_path: &str, action: &str, format: String, limit: usize, quality: &QualityOptions) -> Result<(), EpcisKgError> {
+    // Database metrics describe the dataset at db_path, when there is one
+    let monitor = match OxigraphStore::open_read_only(db_path, false) {
+        Ok(store) => SystemMonitor::new().with_store_metrics(store.metrics()),
+        Err(_) => SystemMonitor::new(),
+    };
     
     match action.to_lowercase().as_str() {
         "metrics" => {
@@ -1810,9 +2872,40 @@ fn perform_monitoring_action(_db_path: &str, action: &str, format: String, limit
                 }
             }
         },
+        "quality" => {
+            let report = OxigraphStore::new(db_path)?.quality_report(quality);
+            
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                println!("\n=== Data Quality ===");
+                println!("Events profiled: {} ({} without a valid event time)", report.total_events, report.events_without_time);
+                
+                println!("\n{:<50} {:>12} {:>10} {:>10}", "Predicate", "Complete %", "Distinct", "Invalid %");
+                for profile in &report.predicates {
+                    println!(
+                        "{:<50} {:>12.1} {:>10} {:>10.1}",
+                        profile.predicate, profile.completeness_percent, profile.distinct_values, profile.invalid_literal_percent
+                    );
+                }
+                
+                if !report.trend.is_empty() {
+                    println!("\nTrend (events per period, lowest completeness):");
+                    for point in &report.trend {
+                        let lowest = point
+                            .completeness_percent
+                            .iter()
+                            .min_by(|a, b| a.1.total_cmp(b.1))
+                            .map(|(predicate, percent)| format!("{} {:.1}%", predicate, percent))
+                            .unwrap_or_default();
+                        println!("  {}  {:>6} events  {}", point.period, point.events, lowest);
+                    }
+                }
+            }
+        },
         _ => {
             return Err(EpcisKgError::Config(format!(
-                "Unknown monitoring action: {}. Use 'metrics', 'alerts', 'health', or 'status'",
+                "Unknown monitoring action: {}. Use 'metrics', 'alerts', 'health', 'status', or 'quality'",
                 action
             )));
         }
@@ -1822,7 +2915,7 @@ fn perform_monitoring_action(_db_path: &str, action: &str, format: String, limit
 }
 
 /// Load pre-generated sample data into the knowledge graph
-fn load_sample_data(scale: &str, db_path: &str, force: bool) -> Result<usize, EpcisKgError> {
+async fn load_sample_data(context: &AppContext, scale: &str, db_path: &str, force: bool) -> Result<usize, EpcisKgError> {
     info!("Loading sample data with scale '{}' into database at {}", scale, db_path);
     
     // Clear existing data if force is enabled
@@ -1834,29 +2927,20 @@ fn load_sample_data(scale: &str, db_path: &str, force: bool) -> Result<usize, Ep
         }
     }
     
-    // Determine sample file path based on scale
-    let sample_file = match scale.to_lowercase().as_str() {
-        "small" => "samples/epcis_data_small.ttl",
-        "medium" => "samples/epcis_data_medium.ttl",
-        "large" => "samples/epcis_data_large.ttl",
-        "xlarge" => "samples/epcis_data_xlarge.ttl",
-        _ => "samples/epcis_data_medium.ttl",
-    };
-    
-    // Check if sample file exists
-    if !std::path::Path::new(sample_file).exists() {
-        return Err(EpcisKgError::Io(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            format!("Sample file not found: {}. Run 'cargo run -- generate --scale {} --output-path samples/' first.", sample_file, scale),
-        )));
+    // Find the sample pack, downloading or generating it when missing
+    let pack = SamplePacks::from_config(&context.config().samples).ensure(scale).await?;
+    match pack.source {
+        SampleSource::Downloaded => println!("✓ Downloaded and verified sample pack {}", pack.path.display()),
+        SampleSource::Generated => println!("✓ Generated missing sample pack {}", pack.path.display()),
+        SampleSource::Local => {}
     }
     
     // Load the sample data
-    load_sample_file_data(sample_file)
+    load_sample_file_data(context, &pack.path.to_string_lossy())
 }
 
 /// Load sample file data and return ontology data (without creating a new store)
-fn load_sample_file_data(file_path: &str) -> Result<usize, EpcisKgError> {
+fn load_sample_file_data(context: &AppContext, file_path: &str) -> Result<usize, EpcisKgError> {
     info!("Loading sample data from {}", file_path);
     
     // Check if file exists
@@ -1868,7 +2952,7 @@ fn load_sample_file_data(file_path: &str) -> Result<usize, EpcisKgError> {
     }
     
     // Load the ontology data using the loader
-    let loader = OntologyLoader::new();
+    let loader = context.loader();
     match loader.load_ontology(file_path) {
         Ok(ontology_data) => {
             println!("✓ Successfully loaded {} triples from {}", ontology_data.triples_count, file_path);
@@ -1881,7 +2965,7 @@ fn load_sample_file_data(file_path: &str) -> Result<usize, EpcisKgError> {
 }
 
 /// Load generated data into the knowledge graph
-fn load_generated_data(file_path: &str, db_path: &str) -> Result<usize, EpcisKgError> {
+fn load_generated_data(context: &AppContext, file_path: &str, db_path: &str) -> Result<usize, EpcisKgError> {
     info!("Loading data from {} into database at {}", file_path, db_path);
     
     // Check if file exists
@@ -1896,7 +2980,7 @@ fn load_generated_data(file_path: &str, db_path: &str) -> Result<usize, EpcisKgE
     let mut store = OxigraphStore::new(db_path)?;
     
     // Load the ontology data using the loader
-    let loader = OntologyLoader::new();
+    let loader = context.loader();
     match loader.load_ontology(file_path) {
         Ok(ontology_data) => {
             store.store_ontology_data(&ontology_data)?;
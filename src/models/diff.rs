@@ -0,0 +1,190 @@
+//! Differences between two event sets.
+//!
+//! Events are matched by event ID and compared field by field. A field is a
+//! set of values, so a reordered EPC list is not a change. Documents are
+//! compared on the event model's fields (`biz_step`, `ilmd.lot_number`, ...)
+//! and stores on the predicates of their event graphs.
+
+use crate::models::epcis::EpcisEvent;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Values of each field of one event
+pub type EventFields = BTreeMap<String, BTreeSet<String>>;
+
+/// A field whose values differ between the two sides
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    /// Values only on the left side
+    pub removed: Vec<String>,
+    /// Values only on the right side
+    pub added: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModifiedEvent {
+    pub event_id: String,
+    pub changes: Vec<FieldChange>,
+}
+
+/// How the right event set differs from the left one
+#[derive(Debug, Clone, Serialize)]
+pub struct EventDiff {
+    pub left_events: usize,
+    pub right_events: usize,
+    /// Event IDs only on the right side
+    pub added: Vec<String>,
+    /// Event IDs only on the left side
+    pub removed: Vec<String>,
+    pub modified: Vec<ModifiedEvent>,
+    pub unchanged: usize,
+}
+
+impl EventDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.modified.is_empty()
+    }
+}
+
+/// Fields of an event as captured, keyed by their path in the event model
+pub fn event_fields(event: &EpcisEvent) -> EventFields {
+    let mut fields = EventFields::new();
+    if let Ok(Value::Object(object)) = serde_json::to_value(event) {
+        for (key, value) in object {
+            if key != "event_id" {
+                flatten(&key, &value, &mut fields);
+            }
+        }
+    }
+    fields
+}
+
+/// Nested objects become dotted paths; array elements are the values of one field
+fn flatten(path: &str, value: &Value, fields: &mut EventFields) {
+    match value {
+        Value::Null => {}
+        Value::Object(object) => {
+            for (key, value) in object {
+                flatten(&format!("{}.{}", path, key), value, fields);
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                match value {
+                    Value::Null => {}
+                    Value::String(text) => {
+                        fields.entry(path.to_string()).or_default().insert(text.clone());
+                    }
+                    other => {
+                        fields.entry(path.to_string()).or_default().insert(other.to_string());
+                    }
+                }
+            }
+        }
+        Value::String(text) => {
+            fields.entry(path.to_string()).or_default().insert(text.clone());
+        }
+        other => {
+            fields.entry(path.to_string()).or_default().insert(other.to_string());
+        }
+    }
+}
+
+/// Compare two documents' events
+pub fn diff_documents(left: &[EpcisEvent], right: &[EpcisEvent], ignore: &[String]) -> EventDiff {
+    let fields = |events: &[EpcisEvent]| -> BTreeMap<String, EventFields> {
+        events.iter().map(|event| (event.event_id.clone(), event_fields(event))).collect()
+    };
+    diff_events(&fields(left), &fields(right), ignore)
+}
+
+/// Compare two event sets keyed by event ID, leaving out the `ignore` fields
+pub fn diff_events(left: &BTreeMap<String, EventFields>, right: &BTreeMap<String, EventFields>, ignore: &[String]) -> EventDiff {
+    let mut diff = EventDiff {
+        left_events: left.len(),
+        right_events: right.len(),
+        added: right.keys().filter(|id| !left.contains_key(*id)).cloned().collect(),
+        removed: left.keys().filter(|id| !right.contains_key(*id)).cloned().collect(),
+        modified: Vec::new(),
+        unchanged: 0,
+    };
+
+    let empty = BTreeSet::new();
+    for (event_id, before) in left {
+        let Some(after) = right.get(event_id) else { continue };
+        let names: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+        let changes: Vec<FieldChange> = names
+            .into_iter()
+            .filter(|name| !ignore.contains(name))
+            .filter_map(|name| {
+                let old = before.get(name).unwrap_or(&empty);
+                let new = after.get(name).unwrap_or(&empty);
+                (old != new).then(|| FieldChange {
+                    field: name.clone(),
+                    removed: old.difference(new).cloned().collect(),
+                    added: new.difference(old).cloned().collect(),
+                })
+            })
+            .collect();
+
+        if changes.is_empty() {
+            diff.unchanged += 1;
+        } else {
+            diff.modified.push(ModifiedEvent { event_id: event_id.clone(), changes });
+        }
+    }
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str, epcs: &[&str], biz_step: &str) -> EpcisEvent {
+        EpcisEvent {
+            event_id: id.to_string(),
+            event_time: "2024-03-01T10:00:00Z".to_string(),
+            record_time: "2024-03-01T10:05:00Z".to_string(),
+            epc_list: epcs.iter().map(|epc| epc.to_string()).collect(),
+            biz_step: Some(biz_step.to_string()),
+            ..EpcisEvent::default()
+        }
+    }
+
+    #[test]
+    fn test_added_removed_and_field_level_changes() {
+        let captured = vec![
+            event("1", &["urn:epc:id:sgtin:0614141.107346.1", "urn:epc:id:sgtin:0614141.107346.2"], "shipping"),
+            event("2", &["urn:epc:id:sgtin:0614141.107346.3"], "receiving"),
+            event("3", &[], "packing"),
+        ];
+        let mut resubmitted = vec![
+            // Reordered EPCs are the same event
+            event("1", &["urn:epc:id:sgtin:0614141.107346.2", "urn:epc:id:sgtin:0614141.107346.1"], "shipping"),
+            event("2", &["urn:epc:id:sgtin:0614141.107346.4"], "receiving"),
+            event("4", &[], "packing"),
+        ];
+        resubmitted[0].record_time = "2024-03-02T08:00:00Z".to_string();
+
+        let diff = diff_documents(&captured, &resubmitted, &["record_time".to_string()]);
+        assert_eq!(diff.added, vec!["4".to_string()]);
+        assert_eq!(diff.removed, vec!["3".to_string()]);
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(
+            diff.modified,
+            vec![ModifiedEvent {
+                event_id: "2".to_string(),
+                changes: vec![FieldChange {
+                    field: "epc_list".to_string(),
+                    removed: vec!["urn:epc:id:sgtin:0614141.107346.3".to_string()],
+                    added: vec!["urn:epc:id:sgtin:0614141.107346.4".to_string()],
+                }],
+            }]
+        );
+
+        let diff = diff_documents(&captured, &resubmitted, &[]);
+        assert_eq!(diff.modified[0].changes[0].field, "record_time");
+    }
+}
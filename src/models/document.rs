@@ -0,0 +1,258 @@
+//! EPCIS 2.0 JSON / JSON-LD documents.
+//!
+//! Converts the standard JSON binding (an `EPCISDocument` with
+//! `epcisBody.eventList`, an `EPCISQueryDocument`, or bare events) into the
+//! pipeline's event model. CBV values may be bare (`shipping`), URNs
+//! (`urn:epcglobal:cbv:bizstep:shipping`) or GS1 Web Vocabulary IRIs
+//! (`https://ref.gs1.org/cbv/BizStep-shipping`); all map to the short names
+//! the pipeline uses. Prefixed extension fields are expanded with the
+//! document's `@context`.
+//!
+//! The model has no place yet for `parentID`, `readPoint`, quantity lists or
+//! business transactions, so those fields are dropped.
+
+use crate::models::epcis::{EpcisEvent, Ilmd};
+use crate::models::parties::SourceDestination;
+use crate::models::sensors::{SensorElement, SensorMetadata, SensorReport};
+use crate::EpcisKgError;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+
+const EVENT_TYPES: &[&str] = &["ObjectEvent", "AggregationEvent", "QuantityEvent", "TransactionEvent", "TransformationEvent"];
+
+/// CBV prefixes stripped to get a short name, longest first
+const CBV_PREFIXES: &[&str] = &[
+    "https://ref.gs1.org/cbv/BizStep-",
+    "https://ref.gs1.org/cbv/Disp-",
+    "urn:epcglobal:cbv:bizstep:",
+    "urn:epcglobal:cbv:disp:",
+    "urn:epcglobal:cbv:",
+    "cbv:BizStep-",
+    "cbv:Disp-",
+];
+
+/// Short CBV name of a business step or disposition in any of its spellings
+pub fn cbv_short_name(value: &str) -> &str {
+    CBV_PREFIXES
+        .iter()
+        .find_map(|prefix| value.strip_prefix(prefix))
+        .unwrap_or(value)
+}
+
+/// Events of an EPCIS document, a query document, a single event or an array of events
+pub fn parse_epcis_document(document: &Value) -> Result<Vec<EpcisEvent>, EpcisKgError> {
+    let mut prefixes = HashMap::new();
+    collect_prefixes(&document["@context"], &mut prefixes);
+
+    let events = match document {
+        Value::Array(events) => events.iter().collect(),
+        Value::Object(object) => match object.get("type").and_then(Value::as_str) {
+            Some("EPCISDocument") => event_list(&document["epcisBody"]["eventList"])?,
+            Some("EPCISQueryDocument") => event_list(&document["epcisBody"]["queryResults"]["resultsBody"]["eventList"])?,
+            _ => vec![document],
+        },
+        _ => return Err(EpcisKgError::Validation("EPCIS document must be a JSON object or array".to_string())),
+    };
+
+    events.into_iter().map(|event| event_from_json(event, &prefixes)).collect()
+}
+
+fn event_list(value: &Value) -> Result<Vec<&Value>, EpcisKgError> {
+    value
+        .as_array()
+        .map(|events| events.iter().collect())
+        .ok_or_else(|| EpcisKgError::Validation("EPCIS document has no eventList".to_string()))
+}
+
+/// Namespace prefixes declared in a JSON-LD `@context` (string entries are remote contexts)
+fn collect_prefixes(context: &Value, prefixes: &mut HashMap<String, String>) {
+    match context {
+        Value::Array(entries) => entries.iter().for_each(|entry| collect_prefixes(entry, prefixes)),
+        Value::Object(entries) => {
+            for (prefix, namespace) in entries {
+                if let Some(namespace) = namespace.as_str() {
+                    prefixes.insert(prefix.clone(), namespace.to_string());
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Convert one event object; `prefixes` come from the enclosing document's context
+pub fn event_from_json(value: &Value, prefixes: &HashMap<String, String>) -> Result<EpcisEvent, EpcisKgError> {
+    let object = value
+        .as_object()
+        .ok_or_else(|| EpcisKgError::Validation("EPCIS event must be a JSON object".to_string()))?;
+    let text = |key: &str| object.get(key).and_then(Value::as_str).map(str::to_string);
+    let strings = |key: &str| -> Vec<String> {
+        object
+            .get(key)
+            .and_then(Value::as_array)
+            .map(|items| items.iter().filter_map(Value::as_str).map(str::to_string).collect())
+            .unwrap_or_default()
+    };
+
+    let event_type = text("type").ok_or_else(|| EpcisKgError::Validation("EPCIS event has no type".to_string()))?;
+    if !EVENT_TYPES.contains(&event_type.as_str()) {
+        return Err(EpcisKgError::Validation(format!("Unknown EPCIS event type: {}", event_type)));
+    }
+    let event_time = text("eventTime").ok_or_else(|| EpcisKgError::Validation("EPCIS event has no eventTime".to_string()))?;
+
+    let mut prefixes = prefixes.clone();
+    collect_prefixes(&value["@context"], &mut prefixes);
+
+    Ok(EpcisEvent {
+        event_id: text("eventID").unwrap_or_else(|| derived_event_id(value)),
+        record_time: text("recordTime").unwrap_or_else(|| event_time.clone()),
+        event_time,
+        // TransformationEvents have no action; any other event without one fails validation
+        event_action: text("action").unwrap_or_else(|| if event_type == "TransformationEvent" { "ADD".to_string() } else { String::new() }),
+        epc_list: [strings("epcList"), strings("childEPCs")].concat(),
+        biz_step: text("bizStep").map(|step| cbv_short_name(&step).to_string()),
+        disposition: text("disposition").map(|disposition| cbv_short_name(&disposition).to_string()),
+        biz_location: object.get("bizLocation").and_then(|location| location["id"].as_str()).map(str::to_string),
+        source_list: source_destinations(object.get("sourceList"), "source"),
+        destination_list: source_destinations(object.get("destinationList"), "destination"),
+        input_epc_list: strings("inputEPCList"),
+        output_epc_list: strings("outputEPCList"),
+        ilmd: object.get("ilmd").and_then(Value::as_object).map(ilmd),
+        sensor_element_list: object
+            .get("sensorElementList")
+            .and_then(Value::as_array)
+            .map(|elements| elements.iter().filter_map(sensor_element).collect())
+            .unwrap_or_default(),
+        extensions: extensions(object, &prefixes),
+        event_time_zone_offset: text("eventTimeZoneOffset"),
+        event_type,
+    })
+}
+
+/// Content-derived ID in the spirit of the CBV event hash, for events without an `eventID`
+fn derived_event_id(value: &Value) -> String {
+    let digest = Sha256::digest(value.to_string().as_bytes());
+    let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("ni:///sha-256;{}?ver=CBV2.0", hex)
+}
+
+fn source_destinations(list: Option<&Value>, id_key: &str) -> Vec<SourceDestination> {
+    list.and_then(Value::as_array)
+        .map(|entries| {
+            entries
+                .iter()
+                .filter_map(|entry| {
+                    Some(SourceDestination {
+                        kind: entry["type"].as_str()?.to_string(),
+                        id: entry[id_key].as_str()?.to_string(),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// ILMD attributes by local name, whether written `cbvmda:lotNumber` or as a full IRI
+fn ilmd(fields: &Map<String, Value>) -> Ilmd {
+    let mut ilmd = Ilmd::default();
+    for (key, value) in fields {
+        let Some(value) = value.as_str().map(str::to_string) else { continue };
+        match key.rsplit(|c| c == ':' || c == '/' || c == '#').next() {
+            Some("itemExpirationDate") => ilmd.item_expiration_date = Some(value),
+            Some("bestBeforeDate") => ilmd.best_before_date = Some(value),
+            Some("lotNumber") => ilmd.lot_number = Some(value),
+            _ => {}
+        }
+    }
+    ilmd
+}
+
+fn sensor_element(element: &Value) -> Option<SensorElement> {
+    let metadata = element.get("sensorMetadata").map(|metadata| SensorMetadata {
+        time: metadata["time"].as_str().map(str::to_string),
+        device_id: metadata["deviceID"].as_str().map(str::to_string),
+    });
+    let reports = element["sensorReport"]
+        .as_array()?
+        .iter()
+        .filter_map(|report| {
+            Some(SensorReport {
+                kind: report["type"].as_str()?.to_string(),
+                value: report["value"].as_f64(),
+                min_value: report["minValue"].as_f64(),
+                max_value: report["maxValue"].as_f64(),
+                uom: report["uom"].as_str().map(str::to_string),
+                time: report["time"].as_str().map(str::to_string),
+            })
+        })
+        .collect();
+    Some(SensorElement {
+        sensor_metadata: metadata,
+        sensor_report: reports,
+    })
+}
+
+/// Fields with prefixed or IRI keys (standard fields have neither), expanded with the context's prefixes
+fn extensions(object: &Map<String, Value>, prefixes: &HashMap<String, String>) -> BTreeMap<String, Value> {
+    object
+        .iter()
+        .filter_map(|(key, value)| {
+            let (prefix, local) = key.split_once(':')?;
+            let key = match prefixes.get(prefix) {
+                Some(namespace) => format!("{}{}", namespace, local),
+                None => key.clone(),
+            };
+            Some((key, value.clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epcis_document_events() {
+        let document = serde_json::json!({
+            "@context": ["https://ref.gs1.org/standards/epcis/epcis-context.jsonld", {"example": "http://ns.example.com/epcis/"}],
+            "type": "EPCISDocument",
+            "schemaVersion": "2.0",
+            "epcisBody": {"eventList": [
+                {
+                    "type": "ObjectEvent",
+                    "eventTime": "2005-04-03T20:33:31.116-06:00",
+                    "eventTimeZoneOffset": "-06:00",
+                    "epcList": ["urn:epc:id:sgtin:0614141.107346.2017"],
+                    "action": "OBSERVE",
+                    "bizStep": "https://ref.gs1.org/cbv/BizStep-shipping",
+                    "disposition": "urn:epcglobal:cbv:disp:in_transit",
+                    "readPoint": {"id": "urn:epc:id:sgln:0614141.07346.1234"},
+                    "example:myField": "Example of a vendor/user extension"
+                },
+                {
+                    "type": "TransformationEvent",
+                    "eventID": "urn:uuid:1",
+                    "eventTime": "2013-10-31T14:58:56.591Z",
+                    "inputEPCList": ["urn:epc:id:sgtin:4012345.011122.25"],
+                    "outputEPCList": ["urn:epc:id:sgtin:4012345.077889.25"],
+                    "ilmd": {"cbvmda:lotNumber": "LOTABC"}
+                }
+            ]}
+        });
+
+        let events = parse_epcis_document(&document).unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events[0].event_id.starts_with("ni:///sha-256;"));
+        assert_eq!(events[0].record_time, events[0].event_time);
+        assert_eq!(events[0].biz_step.as_deref(), Some("shipping"));
+        assert_eq!(events[0].disposition.as_deref(), Some("in_transit"));
+        assert_eq!(
+            events[0].extensions.get("http://ns.example.com/epcis/myField"),
+            Some(&Value::from("Example of a vendor/user extension"))
+        );
+        assert_eq!(events[1].event_action, "ADD");
+        assert_eq!(events[1].ilmd.as_ref().and_then(|ilmd| ilmd.lot_number.as_deref()), Some("LOTABC"));
+        // The derived ID depends only on the event's content
+        assert_eq!(parse_epcis_document(&document).unwrap()[0].event_id, events[0].event_id);
+    }
+}
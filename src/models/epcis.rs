@@ -1,4 +1,7 @@
+use crate::models::parties::SourceDestination;
+use crate::models::sensors::SensorElement;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct EpcisEvent {
@@ -6,11 +9,49 @@ pub struct EpcisEvent {
     pub event_type: String,
     pub event_time: String,
     pub record_time: String,
+    /// `eventTimeZoneOffset` as captured (`+hh:mm` / `-hh:mm`); see `models::timezone`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_time_zone_offset: Option<String>,
     pub event_action: String,
     pub epc_list: Vec<String>,
     pub biz_step: Option<String>,
     pub disposition: Option<String>,
     pub biz_location: Option<String>,
+    /// Sources (e.g. the owning party handing goods over)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub source_list: Vec<SourceDestination>,
+    /// Destinations (e.g. the owning party receiving goods)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub destination_list: Vec<SourceDestination>,
+    /// Inputs consumed by a TransformationEvent
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub input_epc_list: Vec<String>,
+    /// Outputs produced by a TransformationEvent
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub output_epc_list: Vec<String>,
+    /// Instance/lot master data of the EPCs this event commissions or produces
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ilmd: Option<Ilmd>,
+    /// Sensor readings taken while the event happened (e.g. temperatures)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sensor_element_list: Vec<SensorElement>,
+    /// Partner extension fields keyed by IRI or `prefix:field` (see `ExtensionRegistry`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extensions: BTreeMap<String, serde_json::Value>,
+}
+
+/// ILMD fields used by the shelf-life rules (CBV master data attributes)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct Ilmd {
+    /// `cbvmda:itemExpirationDate`, an `xsd:date` or `xsd:dateTime`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item_expiration_date: Option<String>,
+    /// `cbvmda:bestBeforeDate`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub best_before_date: Option<String>,
+    /// `cbvmda:lotNumber`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lot_number: Option<String>,
 }
 
 impl Default for EpcisEvent {
@@ -25,6 +66,14 @@ impl Default for EpcisEvent {
             biz_step: None,
             disposition: None,
             biz_location: None,
+            source_list: Vec::new(),
+            destination_list: Vec::new(),
+            input_epc_list: Vec::new(),
+            output_epc_list: Vec::new(),
+            ilmd: None,
+            sensor_element_list: Vec::new(),
+            extensions: BTreeMap::new(),
+            event_time_zone_offset: None,
         }
     }
 }
@@ -57,6 +106,14 @@ mod tests {
             biz_step: Some("commissioning".to_string()),
             disposition: Some("active".to_string()),
             biz_location: Some("urn:epc:id:sgln:123456.789.0".to_string()),
+            source_list: Vec::new(),
+            destination_list: Vec::new(),
+            input_epc_list: Vec::new(),
+            output_epc_list: Vec::new(),
+            ilmd: None,
+            sensor_element_list: Vec::new(),
+            extensions: Default::default(),
+            event_time_zone_offset: None,
         };
 
         assert_eq!(event.event_id, "test-001");
@@ -80,6 +137,14 @@ mod tests {
             biz_step: Some("commissioning".to_string()),
             disposition: Some("active".to_string()),
             biz_location: Some("urn:epc:id:sgln:123456.789.0".to_string()),
+            source_list: Vec::new(),
+            destination_list: Vec::new(),
+            input_epc_list: Vec::new(),
+            output_epc_list: Vec::new(),
+            ilmd: None,
+            sensor_element_list: Vec::new(),
+            extensions: Default::default(),
+            event_time_zone_offset: None,
         };
 
         let json = serde_json::to_string(&event).unwrap();
@@ -100,6 +165,14 @@ mod tests {
             biz_step: None,
             disposition: None,
             biz_location: None,
+            source_list: Vec::new(),
+            destination_list: Vec::new(),
+            input_epc_list: Vec::new(),
+            output_epc_list: Vec::new(),
+            ilmd: None,
+            sensor_element_list: Vec::new(),
+            extensions: Default::default(),
+            event_time_zone_offset: None,
         };
 
         assert_eq!(event.event_id, "minimal-event");
@@ -125,6 +198,14 @@ mod tests {
             biz_step: Some("commissioning".to_string()),
             disposition: Some("active".to_string()),
             biz_location: Some("urn:epc:id:sgln:123456.789.0".to_string()),
+            source_list: Vec::new(),
+            destination_list: Vec::new(),
+            input_epc_list: Vec::new(),
+            output_epc_list: Vec::new(),
+            ilmd: None,
+            sensor_element_list: Vec::new(),
+            extensions: Default::default(),
+            event_time_zone_offset: None,
         };
 
         assert_eq!(event.epc_list.len(), 3);
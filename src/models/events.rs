@@ -31,6 +31,55 @@ pub struct EventProcessingStats {
     pub last_event_time: Option<String>,
 }
 
+/// CBV error reason used when a declaration does not give one
+pub const DEFAULT_ERROR_REASON: &str = "urn:epcglobal:cbv:er:incorrect_data";
+
+/// EPCIS 2.0 error declaration for an event that was recorded in error
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorDeclaration {
+    pub event_id: String,
+    /// CBV error reason URI or its short form (`incorrect_data`, `did_not_occur`)
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// IDs of events that correct the erroneous one
+    #[serde(default)]
+    pub corrective_event_ids: Vec<String>,
+    /// Declaration time (RFC 3339), defaults to now
+    #[serde(default)]
+    pub declaration_time: Option<String>,
+}
+
+impl ErrorDeclaration {
+    pub fn new(event_id: impl Into<String>) -> Self {
+        Self {
+            event_id: event_id.into(),
+            reason: None,
+            corrective_event_ids: Vec::new(),
+            declaration_time: None,
+        }
+    }
+
+    /// Full CBV error reason URI
+    pub fn reason_uri(&self) -> String {
+        match self.reason.as_deref() {
+            None | Some("") => DEFAULT_ERROR_REASON.to_string(),
+            Some(reason) if reason.contains(':') => reason.to_string(),
+            Some(reason) => format!("urn:epcglobal:cbv:er:{}", reason),
+        }
+    }
+}
+
+/// Outcome of declaring an event in error
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorDeclarationResult {
+    pub event_id: String,
+    pub declaration_time: String,
+    pub reason: String,
+    pub declared_graphs: Vec<String>,
+    pub corrective_event_ids: Vec<String>,
+    pub retracted_inferences: usize,
+}
+
 /// Event processor for EPCIS events
 pub struct EventProcessor {
     config: Option<crate::config::AppConfig>,
@@ -181,6 +230,14 @@ mod tests {
             biz_step: Some("commissioning".to_string()),
             disposition: Some("active".to_string()),
             biz_location: Some("urn:epc:id:sgln:123456.789.0".to_string()),
+            source_list: Vec::new(),
+            destination_list: Vec::new(),
+            input_epc_list: Vec::new(),
+            output_epc_list: Vec::new(),
+            ilmd: None,
+            sensor_element_list: Vec::new(),
+            extensions: Default::default(),
+            event_time_zone_offset: None,
         };
         
         let result = processor.validate_event(&event).unwrap();
@@ -201,6 +258,14 @@ mod tests {
             biz_step: None,
             disposition: None,
             biz_location: None,
+            source_list: Vec::new(),
+            destination_list: Vec::new(),
+            input_epc_list: Vec::new(),
+            output_epc_list: Vec::new(),
+            ilmd: None,
+            sensor_element_list: Vec::new(),
+            extensions: Default::default(),
+            event_time_zone_offset: None,
         };
         
         let result = processor.validate_event(&event).unwrap();
@@ -226,6 +291,14 @@ mod tests {
             biz_step: Some("commissioning".to_string()),
             disposition: Some("active".to_string()),
             biz_location: Some("urn:epc:id:sgln:123456.789.0".to_string()),
+            source_list: Vec::new(),
+            destination_list: Vec::new(),
+            input_epc_list: Vec::new(),
+            output_epc_list: Vec::new(),
+            ilmd: None,
+            sensor_element_list: Vec::new(),
+            extensions: Default::default(),
+            event_time_zone_offset: None,
         };
         
         let result = processor.process_event(&event).unwrap();
@@ -251,6 +324,14 @@ mod tests {
             biz_step: None,
             disposition: None,
             biz_location: None,
+            source_list: Vec::new(),
+            destination_list: Vec::new(),
+            input_epc_list: Vec::new(),
+            output_epc_list: Vec::new(),
+            ilmd: None,
+            sensor_element_list: Vec::new(),
+            extensions: Default::default(),
+            event_time_zone_offset: None,
         };
         
         assert_eq!(processor.estimate_triples_count(&minimal_event), 6); // 5 basic + 1 EPC
@@ -269,6 +350,14 @@ mod tests {
             biz_step: Some("commissioning".to_string()),
             disposition: Some("active".to_string()),
             biz_location: Some("urn:epc:id:sgln:123456.789.0".to_string()),
+            source_list: Vec::new(),
+            destination_list: Vec::new(),
+            input_epc_list: Vec::new(),
+            output_epc_list: Vec::new(),
+            ilmd: None,
+            sensor_element_list: Vec::new(),
+            extensions: Default::default(),
+            event_time_zone_offset: None,
         };
         
         assert_eq!(processor.estimate_triples_count(&full_event), 10); // 5 basic + 2 EPCs + 1 biz_step + 1 disposition + 1 location
@@ -0,0 +1,272 @@
+use crate::config::{ExtensionDatatype, ExtensionNamespace, ExtensionsConfig};
+use crate::models::events::ValidationResult;
+use crate::EpcisKgError;
+use oxrdf::{Literal, NamedNode, Term, Triple};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+const RDF_JSON: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#JSON";
+
+/// Registry of partner extension namespaces and the datatypes of their fields.
+///
+/// Extension fields are validated against their declared datatype, mapped to
+/// `<namespace><field>` predicates on the event resource, and the namespace
+/// prefixes are made available to SPARQL queries.
+#[derive(Debug, Clone, Default)]
+pub struct ExtensionRegistry {
+    strict: bool,
+    namespaces: Vec<ExtensionNamespace>,
+}
+
+/// An extension key resolved against the registry
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedExtension {
+    pub iri: String,
+    /// `None` when the namespace is registered but the field is not declared
+    pub datatype: Option<ExtensionDatatype>,
+}
+
+impl ExtensionRegistry {
+    pub fn from_config(config: &ExtensionsConfig) -> Result<Self, EpcisKgError> {
+        let mut registry = Self {
+            strict: config.strict,
+            namespaces: Vec::new(),
+        };
+        for namespace in &config.namespaces {
+            registry.register(namespace.clone())?;
+        }
+        Ok(registry)
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    pub fn namespaces(&self) -> &[ExtensionNamespace] {
+        &self.namespaces
+    }
+
+    /// Add a namespace, replacing any namespace registered under the same prefix
+    pub fn register(&mut self, namespace: ExtensionNamespace) -> Result<(), EpcisKgError> {
+        if namespace.prefix.is_empty() || !namespace.prefix.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-') {
+            return Err(EpcisKgError::Validation(format!(
+                "Invalid extension prefix: '{}'",
+                namespace.prefix
+            )));
+        }
+        NamedNode::new(namespace.namespace.as_str())?;
+
+        self.namespaces.retain(|existing| existing.prefix != namespace.prefix);
+        self.namespaces.push(namespace);
+        Ok(())
+    }
+
+    /// Resolve a full IRI or `prefix:field` key; `None` if no registered namespace covers it
+    pub fn resolve(&self, key: &str) -> Option<ResolvedExtension> {
+        let (namespace, field) = self
+            .namespaces
+            .iter()
+            .find_map(|ns| key.strip_prefix(ns.namespace.as_str()).map(|field| (ns, field)))
+            .or_else(|| {
+                let (prefix, field) = key.split_once(':')?;
+                self.namespaces
+                    .iter()
+                    .find(|ns| ns.prefix == prefix)
+                    .map(|ns| (ns, field))
+            })?;
+
+        Some(ResolvedExtension {
+            iri: format!("{}{}", namespace.namespace, field),
+            datatype: namespace.fields.get(field).copied(),
+        })
+    }
+
+    /// Whether the IRI is a declared field of a registered namespace
+    pub fn declares(&self, iri: &str) -> bool {
+        self.resolve(iri).is_some_and(|resolved| resolved.datatype.is_some())
+    }
+
+    /// Check extension fields against the registry.
+    ///
+    /// Undeclared fields are errors in strict mode and warnings otherwise.
+    pub fn validate(&self, extensions: &BTreeMap<String, Value>) -> ValidationResult {
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+
+        for (key, value) in extensions {
+            let undeclared = match self.resolve(key) {
+                Some(ResolvedExtension { datatype: Some(datatype), .. }) => {
+                    for item in Self::values(value) {
+                        if let Err(e) = Self::value_to_term(item, Some(datatype)) {
+                            errors.push(format!("Extension '{}': {}", key, e));
+                        }
+                    }
+                    None
+                }
+                Some(ResolvedExtension { datatype: None, .. }) => {
+                    Some(format!("Extension field '{}' is not declared in its namespace", key))
+                }
+                None if NamedNode::new(key.as_str()).is_ok() => {
+                    Some(format!("Extension '{}' is not in a registered namespace", key))
+                }
+                None => {
+                    errors.push(format!("Extension key '{}' is neither an IRI nor a registered prefixed name", key));
+                    None
+                }
+            };
+
+            if let Some(message) = undeclared {
+                if self.strict {
+                    errors.push(message);
+                } else {
+                    warnings.push(message);
+                }
+            }
+        }
+
+        ValidationResult {
+            is_valid: errors.is_empty(),
+            errors,
+            warnings,
+        }
+    }
+
+    /// Map extension fields to triples on the event resource
+    pub fn to_triples(&self, event: &NamedNode, extensions: &BTreeMap<String, Value>) -> Result<Vec<Triple>, EpcisKgError> {
+        let mut triples = Vec::new();
+
+        for (key, value) in extensions {
+            let (iri, datatype) = match self.resolve(key) {
+                Some(resolved) => (resolved.iri, resolved.datatype),
+                None => (key.clone(), None),
+            };
+            let predicate = NamedNode::new(iri)?;
+
+            for item in Self::values(value) {
+                let object = Self::value_to_term(item, datatype)
+                    .map_err(|e| EpcisKgError::Validation(format!("Extension '{}': {}", key, e)))?;
+                triples.push(Triple::new(event.clone(), predicate.clone(), object));
+            }
+        }
+
+        Ok(triples)
+    }
+
+    /// Declare registered prefixes that a query uses but does not declare itself
+    pub fn with_prefixes(&self, query: &str) -> String {
+        let declared: Vec<String> = query
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .windows(2)
+            .filter(|window| window[0].eq_ignore_ascii_case("PREFIX"))
+            .map(|window| window[1].trim_end_matches(':').to_string())
+            .collect();
+
+        let declarations: String = self
+            .namespaces
+            .iter()
+            .filter(|ns| !declared.contains(&ns.prefix) && query.contains(&format!("{}:", ns.prefix)))
+            .map(|ns| format!("PREFIX {}: <{}>\n", ns.prefix, ns.namespace))
+            .collect();
+
+        format!("{}{}", declarations, query)
+    }
+
+    /// Arrays are multi-valued fields
+    fn values(value: &Value) -> Vec<&Value> {
+        match value {
+            Value::Array(items) => items.iter().collect(),
+            other => vec![other],
+        }
+    }
+
+    fn value_to_term(value: &Value, datatype: Option<ExtensionDatatype>) -> Result<Term, String> {
+        let typed = |lexical: String, xsd_type: &str| -> Term {
+            Literal::new_typed_literal(lexical, NamedNode::new_unchecked(format!("{}{}", XSD, xsd_type))).into()
+        };
+        let text = || value.as_str().map(str::to_string).unwrap_or_else(|| value.to_string());
+
+        match (datatype, value) {
+            (Some(ExtensionDatatype::String), Value::String(s)) => Ok(Literal::new_simple_literal(s.as_str()).into()),
+            (Some(ExtensionDatatype::Integer), _) if text().parse::<i64>().is_ok() => Ok(typed(text(), "integer")),
+            (Some(ExtensionDatatype::Decimal), _) if text().parse::<f64>().is_ok() => Ok(typed(text(), "decimal")),
+            (Some(ExtensionDatatype::Boolean), _) if matches!(text().as_str(), "true" | "false") => Ok(typed(text(), "boolean")),
+            (Some(ExtensionDatatype::DateTime), Value::String(s)) if chrono::DateTime::parse_from_rfc3339(s).is_ok() => {
+                Ok(typed(s.clone(), "dateTime"))
+            }
+            (Some(ExtensionDatatype::Iri), Value::String(s)) => NamedNode::new(s.as_str())
+                .map(Term::from)
+                .map_err(|e| format!("invalid IRI '{}': {}", s, e)),
+            (Some(datatype), _) => Err(format!("expected {:?}, got {}", datatype, value)),
+            (None, Value::String(s)) => Ok(Literal::new_simple_literal(s.as_str()).into()),
+            (None, Value::Bool(b)) => Ok(typed(b.to_string(), "boolean")),
+            (None, Value::Number(n)) if n.is_i64() || n.is_u64() => Ok(typed(n.to_string(), "integer")),
+            (None, Value::Number(n)) => Ok(typed(n.to_string(), "decimal")),
+            (None, other) => Ok(Literal::new_typed_literal(other.to_string(), NamedNode::new_unchecked(RDF_JSON)).into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry(strict: bool) -> ExtensionRegistry {
+        ExtensionRegistry::from_config(&ExtensionsConfig {
+            strict,
+            namespaces: vec![ExtensionNamespace {
+                prefix: "acme".to_string(),
+                namespace: "https://ns.acme.example.com/epcis/".to_string(),
+                fields: BTreeMap::from([
+                    ("temperature".to_string(), ExtensionDatatype::Decimal),
+                    ("inspectedBy".to_string(), ExtensionDatatype::Iri),
+                ]),
+            }],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_prefixed_and_full_keys_resolve_to_the_same_iri() {
+        let registry = registry(false);
+        let prefixed = registry.resolve("acme:temperature").unwrap();
+        let full = registry.resolve("https://ns.acme.example.com/epcis/temperature").unwrap();
+
+        assert_eq!(prefixed, full);
+        assert_eq!(prefixed.datatype, Some(ExtensionDatatype::Decimal));
+        assert!(registry.resolve("other:temperature").is_none());
+    }
+
+    #[test]
+    fn test_validation_checks_datatypes_and_strictness() {
+        let extensions = BTreeMap::from([
+            ("acme:temperature".to_string(), Value::from("warm")),
+            ("acme:undeclared".to_string(), Value::from(1)),
+        ]);
+
+        let lenient = registry(false).validate(&extensions);
+        assert_eq!(lenient.errors.len(), 1);
+        assert_eq!(lenient.warnings.len(), 1);
+
+        let strict = registry(true).validate(&extensions);
+        assert_eq!(strict.errors.len(), 2);
+    }
+
+    #[test]
+    fn test_extensions_map_to_typed_triples() {
+        let registry = registry(false);
+        let event = NamedNode::new_unchecked("urn:epc:event:evt-1");
+        let extensions = BTreeMap::from([
+            ("acme:temperature".to_string(), Value::from(4.5)),
+            ("acme:inspectedBy".to_string(), Value::from("urn:epc:id:pgln:0614141.00000")),
+        ]);
+
+        let triples = registry.to_triples(&event, &extensions).unwrap();
+        assert_eq!(triples.len(), 2);
+        assert!(triples.iter().any(|t| t.to_string().contains("\"4.5\"^^<http://www.w3.org/2001/XMLSchema#decimal>")));
+
+        let query = registry.with_prefixes("SELECT ?t WHERE { ?e acme:temperature ?t }");
+        assert!(query.starts_with("PREFIX acme: <https://ns.acme.example.com/epcis/>"));
+    }
+}
@@ -0,0 +1,272 @@
+use crate::config::IdentifierNormalizationConfig;
+use crate::models::epcis::EpcisEvent;
+use oxrdf::{NamedNode, Triple};
+use serde::Serialize;
+
+const OWL_SAME_AS: &str = "http://www.w3.org/2002/07/owl#sameAs";
+
+/// Application identifiers of the GS1 keys a Digital Link path starts with
+const PRIMARY_KEYS: [&str; 6] = ["01", "00", "414", "417", "8003", "8004"];
+
+/// An identifier captured as a Digital Link and stored as its EPC URN
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NormalizedIdentifier {
+    pub original: String,
+    pub normalized: String,
+}
+
+/// Converts GS1 Digital Link URIs to canonical EPC URNs.
+///
+/// A Digital Link carries GS1 keys without saying where the GS1 Company
+/// Prefix ends, which the EPC URN needs. The split comes from the longest
+/// configured prefix a key starts with, or else the default prefix length.
+/// Keys with a wrong check digit and values that are not Digital Links are
+/// left as captured.
+#[derive(Debug, Clone)]
+pub struct IdentifierNormalizer {
+    config: IdentifierNormalizationConfig,
+}
+
+impl IdentifierNormalizer {
+    pub fn from_config(config: &IdentifierNormalizationConfig) -> Self {
+        Self { config: config.clone() }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// EPC URN of a Digital Link URI, or `None` for anything else
+    pub fn to_epc_urn(&self, value: &str) -> Option<String> {
+        let pairs = digital_link_pairs(value)?;
+        let (ai, key) = pairs.first()?;
+        let qualifier = |wanted: &str| pairs[1..].iter().find(|(ai, _)| ai == wanted).map(|(_, value)| value.as_str());
+
+        match ai.as_str() {
+            "01" => {
+                let gtin = format!("{:0>14}", key);
+                if gtin.len() != 14 || !valid_check_digit(&gtin) {
+                    return None;
+                }
+                let (prefix, item) = self.split(&gtin[1..13])?;
+                let item = format!("{}{}", &gtin[..1], item);
+                Some(match (qualifier("21"), qualifier("10")) {
+                    (Some(serial), _) => format!("urn:epc:id:sgtin:{}.{}.{}", prefix, item, escape(serial)),
+                    (None, Some(lot)) => format!("urn:epc:class:lgtin:{}.{}.{}", prefix, item, escape(lot)),
+                    (None, None) => format!("urn:epc:idpat:sgtin:{}.{}.*", prefix, item),
+                })
+            }
+            "00" => {
+                if key.len() != 18 || !valid_check_digit(key) {
+                    return None;
+                }
+                let (prefix, serial_reference) = self.split(&key[1..17])?;
+                Some(format!("urn:epc:id:sscc:{}.{}{}", prefix, &key[..1], serial_reference))
+            }
+            "414" => {
+                if key.len() != 13 || !valid_check_digit(key) {
+                    return None;
+                }
+                let (prefix, location) = self.split(&key[..12])?;
+                let extension = qualifier("254").unwrap_or("0");
+                Some(format!("urn:epc:id:sgln:{}.{}.{}", prefix, location, escape(extension)))
+            }
+            "417" => {
+                if key.len() != 13 || !valid_check_digit(key) {
+                    return None;
+                }
+                let (prefix, party) = self.split(&key[..12])?;
+                Some(format!("urn:epc:id:pgln:{}.{}", prefix, party))
+            }
+            "8003" => {
+                // A zero pad digit, the 13-digit GRAI, then the serial that makes it an instance
+                let (grai, serial) = (key.get(..14)?, key.get(14..).filter(|serial| !serial.is_empty())?);
+                if !grai.starts_with('0') || !valid_check_digit(&grai[1..]) {
+                    return None;
+                }
+                let (prefix, asset_type) = self.split(&grai[1..13])?;
+                Some(format!("urn:epc:id:grai:{}.{}.{}", prefix, asset_type, escape(serial)))
+            }
+            "8004" => {
+                let (prefix, asset) = self.split(key)?;
+                (!asset.is_empty()).then(|| format!("urn:epc:id:giai:{}.{}", prefix, escape(asset)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Split a key after its GS1 Company Prefix
+    fn split<'a>(&self, key: &'a str) -> Option<(&'a str, &'a str)> {
+        let length = self
+            .config
+            .company_prefixes
+            .iter()
+            .filter(|prefix| key.starts_with(prefix.as_str()))
+            .map(String::len)
+            .max()
+            .unwrap_or(self.config.company_prefix_length);
+        let prefix = key.get(..length)?;
+        prefix.chars().all(|c| c.is_ascii_digit()).then(|| (prefix, &key[length..]))
+    }
+
+    /// The EPC URN of a Digital Link when enabled, otherwise the value itself
+    pub fn normalize(&self, value: &str) -> String {
+        match self.config.enabled {
+            true => self.to_epc_urn(value).unwrap_or_else(|| value.to_string()),
+            false => value.to_string(),
+        }
+    }
+
+    /// Replace Digital Links among `values` by their EPC URNs, when enabled
+    pub fn normalize_values(&self, values: &mut [String]) {
+        for value in values {
+            *value = self.normalize(value);
+        }
+    }
+
+    /// Replace the Digital Links among an event's EPCs, business location and
+    /// source and destination identifiers by EPC URNs, when enabled
+    pub fn normalize_event(&self, event: &mut EpcisEvent) -> Vec<NormalizedIdentifier> {
+        let mut normalized: Vec<NormalizedIdentifier> = Vec::new();
+        if !self.config.enabled {
+            return normalized;
+        }
+
+        let identifiers = event
+            .epc_list
+            .iter_mut()
+            .chain(event.input_epc_list.iter_mut())
+            .chain(event.output_epc_list.iter_mut())
+            .chain(event.biz_location.iter_mut())
+            .chain(event.source_list.iter_mut().map(|source| &mut source.id))
+            .chain(event.destination_list.iter_mut().map(|destination| &mut destination.id));
+        for value in identifiers {
+            let Some(urn) = self.to_epc_urn(value) else { continue };
+            let original = std::mem::replace(value, urn.clone());
+            if !normalized.iter().any(|seen| seen.original == original) {
+                normalized.push(NormalizedIdentifier { original, normalized: urn });
+            }
+        }
+        normalized
+    }
+}
+
+/// `<EPC URN> owl:sameAs <Digital Link>` for each normalized identifier
+pub fn same_as_triples(normalized: &[NormalizedIdentifier]) -> Vec<Triple> {
+    normalized
+        .iter()
+        .filter_map(|identifier| {
+            let urn = NamedNode::new(&identifier.normalized).ok()?;
+            let original = NamedNode::new(&identifier.original).ok()?;
+            Some(Triple::new(urn, NamedNode::new_unchecked(OWL_SAME_AS), original))
+        })
+        .collect()
+}
+
+/// Application identifier and decoded value pairs of a Digital Link path,
+/// from its primary key on
+fn digital_link_pairs(value: &str) -> Option<Vec<(String, String)>> {
+    let rest = value.strip_prefix("https://").or_else(|| value.strip_prefix("http://"))?;
+    let path = rest.split(['?', '#']).next()?;
+    let segments: Vec<&str> = path.split('/').skip(1).collect();
+    let start = segments.iter().position(|segment| PRIMARY_KEYS.contains(segment))?;
+    let pairs = &segments[start..];
+    if pairs.len() % 2 != 0 {
+        return None;
+    }
+    pairs
+        .chunks(2)
+        .map(|pair| match percent_decode(pair[1]) {
+            Some(decoded) if !decoded.is_empty() => Some((pair[0].to_string(), decoded)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Percent-encode the characters EPC URN components may not contain as-is
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"!'()*+,-.:;=_".contains(&byte) {
+            escaped.push(byte as char);
+        } else {
+            escaped.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    escaped
+}
+
+/// GS1 mod-10 check over a key whose last digit is the check digit
+fn valid_check_digit(key: &str) -> bool {
+    if key.len() < 2 || !key.chars().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+    let digits: Vec<u32> = key.bytes().map(|byte| u32::from(byte - b'0')).collect();
+    let (check, body) = digits.split_last().expect("at least two digits");
+    let sum: u32 = body.iter().rev().enumerate().map(|(i, digit)| if i % 2 == 0 { digit * 3 } else { *digit }).sum();
+    (10 - sum % 10) % 10 == *check
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalizer(company_prefixes: &[&str]) -> IdentifierNormalizer {
+        IdentifierNormalizer::from_config(&IdentifierNormalizationConfig {
+            enabled: true,
+            company_prefixes: company_prefixes.iter().map(|prefix| prefix.to_string()).collect(),
+            ..IdentifierNormalizationConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_digital_links_become_epc_urns() {
+        let default = normalizer(&[]);
+        let urn = |value: &str| default.to_epc_urn(value);
+        assert_eq!(urn("https://id.gs1.org/01/80614141123458/21/6789").as_deref(), Some("urn:epc:id:sgtin:0614141.812345.6789"));
+        assert_eq!(urn("https://example.com/shop/01/80614141123458/21/A%2FB?17=250101").as_deref(), Some("urn:epc:id:sgtin:0614141.812345.A%2FB"));
+        assert_eq!(urn("https://id.gs1.org/01/80614141123458/10/LOT7").as_deref(), Some("urn:epc:class:lgtin:0614141.812345.LOT7"));
+        assert_eq!(urn("https://id.gs1.org/00/106141412345678908").as_deref(), Some("urn:epc:id:sscc:0614141.1234567890"));
+        assert_eq!(urn("https://id.gs1.org/414/0614141123452/254/400").as_deref(), Some("urn:epc:id:sgln:0614141.12345.400"));
+        // Wrong check digit, or not a Digital Link at all
+        assert_eq!(urn("https://id.gs1.org/01/80614141123459/21/6789"), None);
+        assert_eq!(urn("urn:epc:id:sgtin:0614141.812345.6789"), None);
+        assert_eq!(normalizer(&["061414112"]).to_epc_urn("https://id.gs1.org/414/0614141123452").as_deref(), Some("urn:epc:id:sgln:061414112.345.0"));
+
+        let mut event: EpcisEvent = serde_json::from_value(serde_json::json!({
+            "event_id": "evt-1",
+            "event_type": "ObjectEvent",
+            "event_time": "2024-03-01T09:00:00Z",
+            "record_time": "2024-03-01T09:00:05Z",
+            "event_action": "OBSERVE",
+            "epc_list": ["https://id.gs1.org/01/80614141123458/21/6789", "urn:epc:id:sgtin:0614141.812345.6790"],
+            "biz_step": null,
+            "disposition": null,
+            "biz_location": "https://id.gs1.org/414/0614141123452/254/400"
+        }))
+        .unwrap();
+        let normalized = default.normalize_event(&mut event);
+        assert_eq!(event.epc_list, vec!["urn:epc:id:sgtin:0614141.812345.6789", "urn:epc:id:sgtin:0614141.812345.6790"]);
+        assert_eq!(event.biz_location.as_deref(), Some("urn:epc:id:sgln:0614141.12345.400"));
+        let links = same_as_triples(&normalized);
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].object.to_string(), "<https://id.gs1.org/01/80614141123458/21/6789>");
+    }
+}
@@ -1,2 +1,9 @@
+pub mod diff;
+pub mod document;
 pub mod epcis;
-pub mod events;
\ No newline at end of file
+pub mod events;
+pub mod extensions;
+pub mod identifiers;
+pub mod parties;
+pub mod sensors;
+pub mod timezone;
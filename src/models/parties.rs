@@ -0,0 +1,131 @@
+use crate::EpcisKgError;
+use serde::{Deserialize, Serialize};
+
+/// Business steps of TransactionEvents that transfer ownership when the event
+/// names a destination owning party
+pub const OWNERSHIP_TRANSFER_BIZ_STEPS: &[&str] = &[
+    "shipping",
+    "receiving",
+    "accepting",
+    "selling",
+    "retail_selling",
+    "transferring",
+];
+
+/// A GLN-identified trading partner
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Party {
+    /// Party GLN as a PGLN/SGLN URN or a GS1 Digital Link (`https://id.gs1.org/417/...`)
+    pub gln: String,
+    pub name: String,
+    /// e.g. manufacturer, distributor, retailer
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub address: Option<String>,
+    /// IANA time zone of the site (e.g. `Europe/Berlin`), used to check event offsets
+    #[serde(default)]
+    pub time_zone: Option<String>,
+}
+
+impl Party {
+    pub fn validate(&self) -> Result<(), EpcisKgError> {
+        let is_gln = ["urn:epc:id:pgln:", "urn:epc:id:sgln:", "https://id.gs1.org/417/", "https://id.gs1.org/414/"]
+            .iter()
+            .any(|prefix| self.gln.starts_with(prefix));
+        if !is_gln {
+            return Err(EpcisKgError::Validation(format!(
+                "Party identifier is not a GLN URN or Digital Link: {}",
+                self.gln
+            )));
+        }
+        if self.name.trim().is_empty() {
+            return Err(EpcisKgError::Validation(format!("Party {} has no name", self.gln)));
+        }
+        if let Some(zone) = &self.time_zone {
+            crate::models::timezone::parse_zone(zone)?;
+        }
+        Ok(())
+    }
+}
+
+/// EPCIS source or destination (`type` is a CBV source/destination type, short or full URI)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SourceDestination {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub id: String,
+}
+
+impl SourceDestination {
+    /// Predicate linking an event to this source or destination.
+    ///
+    /// `direction` is `source` or `destination`; owning party, possessing party
+    /// and location types get dedicated predicates so they are easy to query.
+    pub fn predicate(&self, direction: &str) -> String {
+        let kind = self.kind.rsplit(':').next().unwrap_or(&self.kind);
+        let suffix = match kind {
+            "owning_party" => "OwningParty",
+            "possessing_party" => "PossessingParty",
+            "location" => "Location",
+            _ => "",
+        };
+        format!("urn:epcglobal:epcis:{}{}", direction, suffix)
+    }
+}
+
+/// One change of ownership of an EPC
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OwnershipTransfer {
+    pub event_id: String,
+    pub event_time: String,
+    pub biz_step: String,
+    pub from: Option<String>,
+    pub to: String,
+}
+
+/// Current owner of an EPC with the transfers that led to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnershipRecord {
+    pub epc: String,
+    pub owner: String,
+    /// From the party master data, when the owner is a registered party
+    pub owner_party: Option<Party>,
+    pub since: String,
+    pub history: Vec<OwnershipTransfer>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_destination_predicates() {
+        let owner = SourceDestination {
+            kind: "urn:epcglobal:cbv:sdt:owning_party".to_string(),
+            id: "urn:epc:id:pgln:0614141.00000".to_string(),
+        };
+        assert_eq!(owner.predicate("destination"), "urn:epcglobal:epcis:destinationOwningParty");
+
+        let other = SourceDestination {
+            kind: "custom".to_string(),
+            id: "urn:example:x".to_string(),
+        };
+        assert_eq!(other.predicate("source"), "urn:epcglobal:epcis:source");
+    }
+
+    #[test]
+    fn test_party_requires_gln() {
+        let mut party = Party {
+            gln: "urn:epc:id:pgln:0614141.00000".to_string(),
+            name: "Acme Foods".to_string(),
+            role: Some("manufacturer".to_string()),
+            address: None,
+            time_zone: Some("America/New_York".to_string()),
+        };
+        assert!(party.validate().is_ok());
+
+        party.gln = "acme".to_string();
+        assert!(party.validate().is_err());
+    }
+}
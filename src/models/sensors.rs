@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+/// One entry of an event's `sensor_element_list`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensorElement {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sensor_metadata: Option<SensorMetadata>,
+    #[serde(default)]
+    pub sensor_report: Vec<SensorReport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensorMetadata {
+    /// Time of the readings when a report has no time of its own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SensorReport {
+    /// Measurement type, e.g. `gs1:Temperature`
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_value: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_value: Option<f64>,
+    /// UN/CEFACT unit code: `CEL`, `FAH` or `KEL` for temperatures
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uom: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+}
+
+impl SensorReport {
+    pub fn is_temperature(&self) -> bool {
+        self.kind.rsplit(|c| c == ':' || c == '/' || c == '#').next() == Some("Temperature")
+    }
+
+    /// Temperature readings of this report in degrees Celsius (value, or min and max).
+    ///
+    /// Empty for non-temperature reports and unknown units.
+    pub fn celsius(&self) -> Vec<f64> {
+        if !self.is_temperature() {
+            return Vec::new();
+        }
+        let convert = |value: f64| match self.uom.as_deref().unwrap_or("CEL") {
+            "CEL" => Some(value),
+            "FAH" => Some((value - 32.0) * 5.0 / 9.0),
+            "KEL" => Some(value - 273.15),
+            _ => None,
+        };
+        [self.value, self.min_value, self.max_value]
+            .into_iter()
+            .flatten()
+            .filter_map(convert)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temperature_units_convert_to_celsius() {
+        let report: SensorReport = serde_json::from_value(serde_json::json!({
+            "type": "gs1:Temperature",
+            "min_value": 41.0,
+            "max_value": 50.0,
+            "uom": "FAH"
+        }))
+        .unwrap();
+        assert_eq!(report.celsius(), vec![5.0, 10.0]);
+
+        let humidity = SensorReport {
+            kind: "gs1:RelativeHumidity".to_string(),
+            value: Some(40.0),
+            ..report
+        };
+        assert!(humidity.celsius().is_empty());
+    }
+}
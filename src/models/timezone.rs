@@ -0,0 +1,136 @@
+//! `eventTimeZoneOffset` handling.
+//!
+//! Event times are compared as instants, but EPCIS also records the offset in
+//! effect where the event happened. These helpers recover the local site time
+//! of an event and check the recorded offset against the time zone of its
+//! `bizLocation` from party master data (an IANA name such as
+//! `Europe/Berlin`, so daylight saving time is taken into account).
+
+use crate::models::epcis::EpcisEvent;
+use crate::EpcisKgError;
+use chrono::{DateTime, FixedOffset, Offset, Utc};
+use chrono_tz::Tz;
+
+/// Parse an EPCIS time zone offset (`+hh:mm` or `-hh:mm`, within ±14:00)
+pub fn parse_offset(offset: &str) -> Result<FixedOffset, EpcisKgError> {
+    let invalid = || EpcisKgError::InvalidField {
+        field: "eventTimeZoneOffset".to_string(),
+        message: format!("'{}' is not an offset of the form +hh:mm or -hh:mm", offset),
+    };
+    let (sign, rest) = match offset.as_bytes().first() {
+        Some(b'+') => (1, &offset[1..]),
+        Some(b'-') => (-1, &offset[1..]),
+        _ => return Err(invalid()),
+    };
+    let (hours, minutes) = rest.split_once(':').ok_or_else(invalid)?;
+    if hours.len() != 2 || minutes.len() != 2 {
+        return Err(invalid());
+    }
+    let hours: i32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+    if minutes >= 60 || hours * 60 + minutes > 14 * 60 {
+        return Err(invalid());
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).ok_or_else(invalid)
+}
+
+/// Format an offset the way EPCIS writes it
+pub fn format_offset(offset: FixedOffset) -> String {
+    let seconds = offset.local_minus_utc();
+    let sign = if seconds < 0 { '-' } else { '+' };
+    let minutes = seconds.abs() / 60;
+    format!("{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+}
+
+/// Offset in effect where the event happened: its `eventTimeZoneOffset`, else the
+/// offset written into `eventTime` unless that is plain UTC (`Z`)
+pub fn event_offset(event: &EpcisEvent) -> Result<Option<FixedOffset>, EpcisKgError> {
+    if let Some(offset) = &event.event_time_zone_offset {
+        return parse_offset(offset).map(Some);
+    }
+    if event.event_time.ends_with('Z') || event.event_time.ends_with('z') {
+        return Ok(None);
+    }
+    Ok(DateTime::parse_from_rfc3339(&event.event_time).ok().map(|time| *time.offset()))
+}
+
+/// Event time as the local time of the site where it happened
+pub fn local_event_time(event: &EpcisEvent) -> Option<DateTime<FixedOffset>> {
+    let time = DateTime::parse_from_rfc3339(&event.event_time).ok()?;
+    let offset = event_offset(event).ok()??;
+    Some(time.with_timezone(&offset))
+}
+
+/// An instant as the local time of an IANA time zone
+pub fn to_site_time(time: DateTime<Utc>, zone: &str) -> Result<DateTime<FixedOffset>, EpcisKgError> {
+    let zone = parse_zone(zone)?;
+    Ok(time.with_timezone(&zone_offset_at(zone, time)))
+}
+
+/// A naive local time read in `offset`, as an instant
+pub fn from_local_time(local: chrono::NaiveDateTime, offset: FixedOffset) -> DateTime<Utc> {
+    (local - chrono::Duration::seconds(offset.local_minus_utc() as i64)).and_utc()
+}
+
+pub fn parse_zone(zone: &str) -> Result<Tz, EpcisKgError> {
+    zone.parse::<Tz>().map_err(|_| EpcisKgError::InvalidField {
+        field: "time_zone".to_string(),
+        message: format!("'{}' is not an IANA time zone", zone),
+    })
+}
+
+fn zone_offset_at(zone: Tz, time: DateTime<Utc>) -> FixedOffset {
+    time.with_timezone(&zone).offset().fix()
+}
+
+/// Warning when the event's offset is not the one its site's time zone had at the event time
+pub fn check_site_offset(event: &EpcisEvent, site_zone: &str) -> Option<String> {
+    let offset = event_offset(event).ok()??;
+    let time = DateTime::parse_from_rfc3339(&event.event_time).ok()?.with_timezone(&Utc);
+    let expected = zone_offset_at(parse_zone(site_zone).ok()?, time);
+    (offset != expected).then(|| {
+        format!(
+            "eventTimeZoneOffset {} does not match {} ({}) at {}",
+            format_offset(offset),
+            site_zone,
+            format_offset(expected),
+            event.biz_location.as_deref().unwrap_or("the business location")
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(time: &str, offset: Option<&str>) -> EpcisEvent {
+        EpcisEvent {
+            event_time: time.to_string(),
+            event_time_zone_offset: offset.map(str::to_string),
+            biz_location: Some("urn:epc:id:sgln:4012345.00001.0".to_string()),
+            ..EpcisEvent::default()
+        }
+    }
+
+    #[test]
+    fn test_offsets_local_time_and_site_consistency() {
+        assert_eq!(parse_offset("-06:00").unwrap().local_minus_utc(), -6 * 3600);
+        assert_eq!(format_offset(parse_offset("+05:30").unwrap()), "+05:30");
+        assert!(parse_offset("+15:00").is_err());
+        assert!(parse_offset("0600").is_err());
+
+        let winter = event("2024-01-15T09:00:00Z", Some("+01:00"));
+        assert_eq!(local_event_time(&winter).unwrap().to_rfc3339(), "2024-01-15T10:00:00+01:00");
+        assert!(check_site_offset(&winter, "Europe/Berlin").is_none());
+
+        // Berlin is on summer time in July
+        let summer = event("2024-07-15T09:00:00Z", Some("+01:00"));
+        let warning = check_site_offset(&summer, "Europe/Berlin").unwrap();
+        assert!(warning.contains("+02:00"), "{}", warning);
+
+        // Without an explicit offset, the one in eventTime is kept
+        let implicit = event("2024-07-15T11:00:00+02:00", None);
+        assert_eq!(event_offset(&implicit).unwrap(), Some(parse_offset("+02:00").unwrap()));
+        assert_eq!(event_offset(&event("2024-07-15T09:00:00Z", None)).unwrap(), None);
+    }
+}
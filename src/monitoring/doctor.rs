@@ -0,0 +1,308 @@
+//! Self-test of a deployment before it starts serving.
+//!
+//! Every check runs even when an earlier one fails, so a single run lists all
+//! problems; each failure or warning carries a hint on how to fix it.
+
+use crate::config::AppConfig;
+use crate::ontology::loader::{OntologyData, OntologyLoader};
+use crate::ontology::reasoner::OntologyReasoner;
+use crate::storage::encryption::StoreKeyring;
+use crate::storage::oxigraph_store::OxigraphStore;
+use oxrdf::{Literal, NamedNode, Triple};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Free space below which the disk check fails
+const MIN_FREE_BYTES: u64 = 100 * 1024 * 1024;
+/// Free space below which the disk check warns
+const LOW_FREE_BYTES: u64 = 1024 * 1024 * 1024;
+
+const PROBE_GRAPH: &str = "urn:epcis:doctor:probe";
+const PROBE_SUBJECT: &str = "urn:epcis:doctor:probe:subject";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// How to fix a failure or warning
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl DoctorCheck {
+    fn pass(name: &str, detail: impl Into<String>) -> Self {
+        Self { name: name.to_string(), status: CheckStatus::Pass, detail: detail.into(), hint: None }
+    }
+
+    fn warn(name: &str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { name: name.to_string(), status: CheckStatus::Warn, detail: detail.into(), hint: Some(hint.into()) }
+    }
+
+    fn fail(name: &str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { name: name.to_string(), status: CheckStatus::Fail, detail: detail.into(), hint: Some(hint.into()) }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DoctorReport {
+    pub checks: Vec<DoctorCheck>,
+}
+
+impl DoctorReport {
+    pub fn failures(&self) -> usize {
+        self.checks.iter().filter(|check| check.status == CheckStatus::Fail).count()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.checks.iter().filter(|check| check.status == CheckStatus::Warn).count()
+    }
+}
+
+/// What to check; `db_path` and `port` default to the configured ones
+#[derive(Debug, Clone, Default)]
+pub struct DoctorOptions {
+    pub config_path: String,
+    pub db_path: Option<String>,
+    pub port: Option<u16>,
+}
+
+/// Run every check: configuration, database directory, ontologies, reasoner,
+/// a store write and query, free disk space and the server port
+pub fn run_checks(options: &DoctorOptions) -> DoctorReport {
+    let mut report = DoctorReport::default();
+
+    let (check, config) = check_config(&options.config_path);
+    report.checks.push(check);
+    let config = config.unwrap_or_default();
+    let db_path = PathBuf::from(options.db_path.clone().unwrap_or_else(|| config.database_path.clone()));
+
+    let keyring = match StoreKeyring::from_config(&config.encryption) {
+        Ok(keyring) => keyring.map(Arc::new),
+        Err(e) => {
+            report.checks.push(DoctorCheck::fail(
+                "storage key",
+                e.to_string(),
+                format!("Provide the key through the configured source ({})", config.encryption.key_source),
+            ));
+            None
+        }
+    };
+
+    report.checks.push(check_database_path(&db_path));
+    let (check, ontologies) = check_ontologies(&config);
+    report.checks.push(check);
+    report.checks.push(check_reasoner(&config, &ontologies));
+    report.checks.push(check_store_round_trip(&db_path, keyring));
+    report.checks.push(check_disk_space(&db_path));
+    report.checks.push(check_port(options.port.unwrap_or(config.server_port)));
+    report
+}
+
+fn check_config(path: &str) -> (DoctorCheck, Option<AppConfig>) {
+    const NAME: &str = "configuration";
+    if !Path::new(path).exists() {
+        let check = DoctorCheck::warn(
+            NAME,
+            format!("{} not found; using built-in defaults", path),
+            "Pass --config with the path of your configuration file",
+        );
+        return (check, Some(AppConfig::default()));
+    }
+    match AppConfig::from_file(path) {
+        Ok(config) => match config.validate() {
+            Ok(()) => (DoctorCheck::pass(NAME, format!("{} is valid", path)), Some(config)),
+            Err(e) => (DoctorCheck::fail(NAME, e.to_string(), format!("Fix the setting in {}", path)), Some(config)),
+        },
+        Err(e) => (
+            DoctorCheck::fail(NAME, e.to_string(), format!("Fix the TOML syntax of {}; the remaining checks use defaults", path)),
+            None,
+        ),
+    }
+}
+
+/// The database directory, or the nearest existing parent it would be created in, must be writable
+fn check_database_path(db_path: &Path) -> DoctorCheck {
+    const NAME: &str = "database path";
+    let existing = db_path.ancestors().find(|path| path.exists()).unwrap_or(Path::new("."));
+    if existing == db_path && !db_path.is_dir() {
+        return DoctorCheck::fail(NAME, format!("{} is not a directory", db_path.display()), "Point database_path at a directory");
+    }
+
+    let probe = existing.join(format!(".doctor-{}", uuid::Uuid::new_v4()));
+    match std::fs::write(&probe, b"probe") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            if existing == db_path {
+                DoctorCheck::pass(NAME, format!("{} is writable", db_path.display()))
+            } else {
+                DoctorCheck::warn(
+                    NAME,
+                    format!("{} does not exist yet; {} is writable", db_path.display(), existing.display()),
+                    "Run `init` to create the database with the default ontologies",
+                )
+            }
+        }
+        Err(e) => DoctorCheck::fail(
+            NAME,
+            format!("Cannot write to {}: {}", existing.display(), e),
+            format!("Grant the service user write access to {} (chown/chmod)", existing.display()),
+        ),
+    }
+}
+
+fn check_ontologies(config: &AppConfig) -> (DoctorCheck, Vec<OntologyData>) {
+    const NAME: &str = "ontologies";
+    let loader = OntologyLoader::with_config(config);
+    let mut loaded = Vec::new();
+    let mut missing = Vec::new();
+    let mut broken = Vec::new();
+    for path in &config.ontology_paths {
+        if !Path::new(path).exists() {
+            missing.push(path.clone());
+            continue;
+        }
+        match loader.load_ontology(path) {
+            Ok(data) => loaded.push(data),
+            Err(e) => broken.push(format!("{}: {}", path, e)),
+        }
+    }
+
+    let triples: usize = loaded.iter().map(|data| data.triples_count).sum();
+    let check = if !broken.is_empty() {
+        DoctorCheck::fail(
+            NAME,
+            format!("Failed to parse {}", broken.join("; ")),
+            "Fix the files, or check them with `profile --diagnostics`",
+        )
+    } else if !missing.is_empty() {
+        DoctorCheck::warn(
+            NAME,
+            format!("Loaded {} file(s) ({} triples); missing: {}", loaded.len(), triples, missing.join(", ")),
+            "Correct ontology_paths or add the missing files",
+        )
+    } else {
+        DoctorCheck::pass(NAME, format!("Parsed {} file(s) ({} triples)", loaded.len(), triples))
+    };
+    (check, loaded)
+}
+
+fn check_reasoner(config: &AppConfig, ontologies: &[OntologyData]) -> DoctorCheck {
+    const NAME: &str = "reasoner";
+    let mut reasoner = OntologyReasoner::with_config(config);
+    for ontology in ontologies {
+        if let Err(e) = reasoner.load_ontology_data(ontology) {
+            return DoctorCheck::fail(
+                NAME,
+                format!("Failed to convert the ontologies for the reasoner: {}", e),
+                "Check the ontologies with `profile --diagnostics` for constructs outside the configured profile",
+            );
+        }
+    }
+    DoctorCheck::pass(NAME, format!("Initialized with {} ontology file(s)", ontologies.len()))
+}
+
+/// Write a probe triple to a scratch store next to the database, reopen it and query it back
+fn check_store_round_trip(db_path: &Path, keyring: Option<Arc<StoreKeyring>>) -> DoctorCheck {
+    const NAME: &str = "store round trip";
+    let parent = if db_path.is_dir() { db_path.to_path_buf() } else { std::env::temp_dir() };
+    let scratch = parent.join(format!(".doctor-store-{}", uuid::Uuid::new_v4()));
+    let result = store_round_trip(&scratch, keyring);
+    let _ = std::fs::remove_dir_all(&scratch);
+    match result {
+        Ok(()) => DoctorCheck::pass(NAME, format!("Wrote, reloaded and queried a probe graph in {}", parent.display())),
+        Err(e) => DoctorCheck::fail(NAME, e.to_string(), "Check free space, permissions and the [encryption] key"),
+    }
+}
+
+fn store_round_trip(scratch: &Path, keyring: Option<Arc<StoreKeyring>>) -> Result<(), crate::EpcisKgError> {
+    let probe = Triple::new(
+        NamedNode::new_unchecked(PROBE_SUBJECT),
+        NamedNode::new_unchecked("http://www.w3.org/2000/01/rdf-schema#label"),
+        Literal::new_simple_literal("doctor probe"),
+    );
+    let mut store = OxigraphStore::new_with_keyring(scratch, keyring.clone())?;
+    store.insert_triples(PROBE_GRAPH, &[probe])?;
+    drop(store);
+
+    let reopened = OxigraphStore::new_with_keyring(scratch, keyring)?;
+    let results = reopened.query_select(&format!("SELECT ?s ?p ?o FROM <{}> WHERE {{ ?s ?p ?o }}", PROBE_GRAPH))?;
+    if !results.contains(PROBE_SUBJECT) {
+        return Err(crate::EpcisKgError::Storage("The probe triple was not returned after reopening the store".to_string()));
+    }
+    Ok(())
+}
+
+fn check_disk_space(db_path: &Path) -> DoctorCheck {
+    const NAME: &str = "disk space";
+    let existing = db_path.ancestors().find(|path| path.exists()).unwrap_or(Path::new("."));
+    let mebibytes = |bytes: u64| bytes / (1024 * 1024);
+    match fs2::available_space(existing) {
+        Ok(free) if free < MIN_FREE_BYTES => DoctorCheck::fail(
+            NAME,
+            format!("{} MiB free at {}", mebibytes(free), existing.display()),
+            "Free disk space or move database_path to a larger volume",
+        ),
+        Ok(free) if free < LOW_FREE_BYTES => DoctorCheck::warn(
+            NAME,
+            format!("{} MiB free at {}", mebibytes(free), existing.display()),
+            "Plan for more space; snapshots, archives and the reasoner cache grow with the dataset",
+        ),
+        Ok(free) => DoctorCheck::pass(NAME, format!("{} MiB free at {}", mebibytes(free), existing.display())),
+        Err(e) => DoctorCheck::warn(NAME, format!("Cannot read free space of {}: {}", existing.display(), e), "Check free space manually"),
+    }
+}
+
+fn check_port(port: u16) -> DoctorCheck {
+    const NAME: &str = "server port";
+    match std::net::TcpListener::bind(std::net::SocketAddr::from(([0, 0, 0, 0], port))) {
+        Ok(_) => DoctorCheck::pass(NAME, format!("Port {} is available", port)),
+        Err(e) => DoctorCheck::fail(
+            NAME,
+            format!("Cannot bind port {}: {}", port, e),
+            "Stop the process using the port, or choose another with server_port or `serve --port`",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checks_report_problems_with_hints() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("data");
+
+        let missing = check_database_path(&db_path);
+        assert_eq!(missing.status, CheckStatus::Warn);
+        std::fs::create_dir(&db_path).unwrap();
+        assert_eq!(check_database_path(&db_path).status, CheckStatus::Pass);
+        let file = dir.path().join("file");
+        std::fs::write(&file, b"").unwrap();
+        assert_eq!(check_database_path(&file).status, CheckStatus::Fail);
+
+        assert_eq!(check_store_round_trip(&db_path, None).status, CheckStatus::Pass);
+        assert_eq!(std::fs::read_dir(&db_path).unwrap().count(), 0);
+
+        let listener = std::net::TcpListener::bind("0.0.0.0:0").unwrap();
+        let taken = check_port(listener.local_addr().unwrap().port());
+        assert_eq!(taken.status, CheckStatus::Fail);
+        assert!(taken.hint.is_some());
+
+        let config_file = dir.path().join("broken.toml");
+        std::fs::write(&config_file, "server_port = ").unwrap();
+        let (check, config) = check_config(config_file.to_str().unwrap());
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(config.is_none());
+    }
+}
@@ -0,0 +1,200 @@
+use crate::config::{LineageConfig, ResiliencePolicy};
+use crate::monitoring::webhooks::classify;
+use crate::utils::resilience::{Failure, Resilience};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::warn;
+
+/// OpenLineage spec version the run events follow
+const RUN_EVENT_SCHEMA: &str = "https://openlineage.io/spec/2-0-2/OpenLineage.json#/$defs/RunEvent";
+const ERROR_MESSAGE_FACET: &str = "https://openlineage.io/spec/facets/1-0-0/ErrorMessageRunFacet.json#/$defs/ErrorMessageRunFacet";
+const JOB_TYPE_FACET: &str = "https://openlineage.io/spec/facets/2-0-2/JobTypeJobFacet.json#/$defs/JobTypeJobFacet";
+const OUTPUT_STATISTICS_FACET: &str = "https://openlineage.io/spec/facets/1-0-1/OutputStatisticsOutputDatasetFacet.json#/$defs/OutputStatisticsOutputDatasetFacet";
+const DATASET_VERSION_FACET: &str = "https://openlineage.io/spec/facets/1-0-1/DatasetVersionDatasetFacet.json#/$defs/DatasetVersionDatasetFacet";
+
+/// Terminal state of a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RunState {
+    Complete,
+    Fail,
+}
+
+/// A dataset read or written by a run: a named graph, or a file for `namespace = "file"`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineageDataset {
+    /// `None` for graphs, which use the configured dataset namespace
+    pub namespace: Option<String>,
+    pub name: String,
+    /// Ontology version of an input graph
+    pub version: Option<String>,
+    /// Triples or events written, for outputs
+    pub row_count: Option<usize>,
+}
+
+impl LineageDataset {
+    pub fn graph(name: &str) -> Self {
+        Self { namespace: None, name: name.to_string(), version: None, row_count: None }
+    }
+
+    pub fn file(path: &str) -> Self {
+        Self { namespace: Some("file".to_string()), ..Self::graph(path) }
+    }
+
+    pub fn with_version(self, version: Option<String>) -> Self {
+        Self { version, ..self }
+    }
+
+    pub fn with_row_count(self, row_count: usize) -> Self {
+        Self { row_count: Some(row_count), ..self }
+    }
+}
+
+/// One finished run of a job, as reported to the catalog
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineageRun {
+    /// `load_ontology`, `capture` or `materialize`
+    pub job: String,
+    /// A UUID, such as the ID of the recorded inference run
+    pub run_id: String,
+    pub state: RunState,
+    pub error: Option<String>,
+    pub inputs: Vec<LineageDataset>,
+    pub outputs: Vec<LineageDataset>,
+}
+
+impl LineageRun {
+    pub fn new(job: &str, state: RunState) -> Self {
+        Self {
+            job: job.to_string(),
+            run_id: uuid::Uuid::new_v4().to_string(),
+            state,
+            error: None,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+}
+
+/// The OpenLineage `RunEvent` JSON for a run
+pub fn run_event(run: &LineageRun, config: &LineageConfig, event_time: DateTime<Utc>) -> serde_json::Value {
+    let facet = |schema: &str, fields: serde_json::Value| {
+        let mut facet = serde_json::json!({ "_producer": config.producer, "_schemaURL": schema });
+        if let (Some(facet), serde_json::Value::Object(fields)) = (facet.as_object_mut(), fields) {
+            facet.extend(fields);
+        }
+        facet
+    };
+    let dataset = |dataset: &LineageDataset, output: bool| {
+        let mut value = serde_json::json!({
+            "namespace": dataset.namespace.as_deref().unwrap_or(&config.dataset_namespace),
+            "name": dataset.name,
+            "facets": {},
+        });
+        if let Some(version) = &dataset.version {
+            value["facets"]["version"] = facet(DATASET_VERSION_FACET, serde_json::json!({ "datasetVersion": version }));
+        }
+        if let (true, Some(row_count)) = (output, dataset.row_count) {
+            value["outputFacets"] = serde_json::json!({
+                "outputStatistics": facet(OUTPUT_STATISTICS_FACET, serde_json::json!({ "rowCount": row_count })),
+            });
+        }
+        value
+    };
+
+    let mut run_facets = serde_json::Map::new();
+    if let Some(message) = &run.error {
+        run_facets.insert(
+            "errorMessage".to_string(),
+            facet(ERROR_MESSAGE_FACET, serde_json::json!({ "message": message, "programmingLanguage": "Rust" })),
+        );
+    }
+    serde_json::json!({
+        "eventType": run.state,
+        "eventTime": event_time.to_rfc3339(),
+        "run": { "runId": run.run_id, "facets": run_facets },
+        "job": {
+            "namespace": config.namespace,
+            "name": run.job,
+            "facets": {
+                "jobType": facet(JOB_TYPE_FACET, serde_json::json!({ "processingType": "BATCH", "integration": "EPCIS_KG", "jobType": run.job.to_uppercase() })),
+            },
+        },
+        "inputs": run.inputs.iter().map(|input| dataset(input, false)).collect::<Vec<_>>(),
+        "outputs": run.outputs.iter().map(|output| dataset(output, true)).collect::<Vec<_>>(),
+        "producer": config.producer,
+        "schemaURL": RUN_EVENT_SCHEMA,
+    })
+}
+
+/// Sends OpenLineage run events to the configured HTTP endpoint.
+///
+/// Like webhook deliveries, each event is a fire-and-forget POST with its own
+/// retries; a catalog that is down never holds up loads, captures or reasoning.
+#[derive(Debug, Clone, Default)]
+pub struct LineageEmitter {
+    config: LineageConfig,
+    resilience: Option<Arc<Resilience>>,
+    client: reqwest::Client,
+}
+
+impl LineageEmitter {
+    pub fn new(config: &LineageConfig, policy: ResiliencePolicy) -> Self {
+        Self {
+            config: config.clone(),
+            resilience: config.enabled.then(|| Arc::new(Resilience::new(format!("lineage {}", config.url), policy))),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn emit(&self, run: &LineageRun) {
+        let Some(resilience) = &self.resilience else { return };
+        let body = run_event(run, &self.config, Utc::now());
+        let (client, url, api_key, resilience) = (self.client.clone(), self.config.url.clone(), self.config.api_key.clone(), Arc::clone(resilience));
+        tokio::spawn(async move {
+            let delivered = resilience
+                .call(&body, || async {
+                    let mut request = client.post(&url).json(&body);
+                    if let Some(api_key) = &api_key {
+                        request = request.bearer_auth(api_key);
+                    }
+                    match request.send().await {
+                        Ok(response) if response.status().is_success() => Ok(()),
+                        Ok(response) => Err(classify(response.status())),
+                        Err(e) => Err(Failure::Transient(e.to_string())),
+                    }
+                })
+                .await;
+            if let Err(e) = delivered {
+                warn!("OpenLineage event to {} failed: {}", url, e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_event_follows_the_openlineage_shape() {
+        let config = LineageConfig::default();
+        let mut run = LineageRun::new("materialize", RunState::Complete);
+        run.inputs.push(LineageDataset::graph("urn:epcis:ontology:cbv").with_version(Some("2.0".to_string())));
+        run.outputs.push(LineageDataset::graph("urn:epcis:inferred").with_row_count(42));
+
+        let event = run_event(&run, &config, "2024-03-01T08:00:00Z".parse().unwrap());
+        assert_eq!(event["eventType"], "COMPLETE");
+        assert_eq!(event["job"]["namespace"], config.namespace);
+        assert_eq!(event["inputs"][0]["namespace"], config.dataset_namespace);
+        assert_eq!(event["inputs"][0]["facets"]["version"]["datasetVersion"], "2.0");
+        assert_eq!(event["outputs"][0]["outputFacets"]["outputStatistics"]["rowCount"], 42);
+        assert!(uuid::Uuid::parse_str(event["run"]["runId"].as_str().unwrap()).is_ok());
+
+        run.state = RunState::Fail;
+        run.error = Some("reasoner timed out".to_string());
+        let event = run_event(&run, &config, Utc::now());
+        assert_eq!(event["run"]["facets"]["errorMessage"]["message"], "reasoner timed out");
+    }
+}
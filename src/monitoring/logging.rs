@@ -1,10 +1,13 @@
+use crate::EpcisKgError;
 use tracing::{info, warn, error, debug, trace, Level};
-use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::fs;
 use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
 use serde::{Serialize, Deserialize};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 /// Logging configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +15,10 @@ pub struct LoggingConfig {
     /// Log level (trace, debug, info, warn, error)
     pub level: String,
     
+    /// Levels for individual targets, overriding `level` (e.g. `storage = "debug"`)
+    #[serde(default)]
+    pub targets: BTreeMap<String, String>,
+    
     /// Whether to log to console
     pub console_output: bool,
     
@@ -48,6 +55,7 @@ impl Default for LoggingConfig {
     fn default() -> Self {
         Self {
             level: "info".to_string(),
+            targets: BTreeMap::new(),
             console_output: true,
             file_output: false,
             log_directory: PathBuf::from("./logs"),
@@ -418,6 +426,116 @@ impl ReasoningLogger {
     }
 }
 
+/// Crate modules that per-target levels may name without the crate prefix
+const CRATE_MODULES: [&str; 14] = [
+    "api", "benchmarks", "client", "config", "context", "data_gen", "models",
+    "monitoring", "ontology", "pipeline", "scheduler", "storage", "testing", "utils",
+];
+
+const CRATE_TARGET: &str = "epcis_knowledge_graph";
+
+static LOG_LEVELS: OnceLock<LogLevelControl> = OnceLock::new();
+
+/// Global log level plus per-target overrides
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogLevels {
+    pub level: String,
+    #[serde(default)]
+    pub targets: BTreeMap<String, String>,
+}
+
+/// Change to the running log levels; a target set to `null` falls back to the global level
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LogLevelUpdate {
+    pub level: Option<String>,
+    #[serde(default)]
+    pub targets: BTreeMap<String, Option<String>>,
+}
+
+impl LogLevels {
+    pub fn from_config(config: &LoggingConfig) -> Self {
+        Self { level: config.level.clone(), targets: config.targets.clone() }
+    }
+
+    pub fn merged(&self, update: &LogLevelUpdate) -> Self {
+        let mut levels = self.clone();
+        if let Some(level) = &update.level {
+            levels.level = level.clone();
+        }
+        for (target, level) in &update.targets {
+            match level {
+                Some(level) => levels.targets.insert(target.clone(), level.clone()),
+                None => levels.targets.remove(target),
+            };
+        }
+        levels
+    }
+
+    /// Filter with one directive per target, so the most specific target wins
+    pub fn filter(&self) -> Result<EnvFilter, EpcisKgError> {
+        let mut directives = vec![level_name(&self.level)?];
+        for (target, level) in &self.targets {
+            directives.push(format!("{}={}", qualified_target(target)?, level_name(level)?));
+        }
+        EnvFilter::try_new(directives.join(","))
+            .map_err(|e| EpcisKgError::Validation(format!("Invalid log levels: {}", e)))
+    }
+}
+
+fn level_name(level: &str) -> Result<String, EpcisKgError> {
+    let level = level.trim().to_lowercase();
+    match level.as_str() {
+        "trace" | "debug" | "info" | "warn" | "error" | "off" => Ok(level),
+        _ => Err(EpcisKgError::Validation(format!(
+            "Invalid log level: {}. Must be one of: trace, debug, info, warn, error, off",
+            level
+        ))),
+    }
+}
+
+/// Tracing target for a configured name: crate modules (`storage`,
+/// `ontology::reasoner`) get the crate prefix, other names (`tower_http`) are used as is
+fn qualified_target(target: &str) -> Result<String, EpcisKgError> {
+    if target.is_empty() || !target.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':') {
+        return Err(EpcisKgError::Validation(format!("Invalid log target: '{}'", target)));
+    }
+    let module = target.split("::").next().unwrap_or_default();
+    if CRATE_MODULES.contains(&module) {
+        Ok(format!("{}::{}", CRATE_TARGET, target))
+    } else {
+        Ok(target.to_string())
+    }
+}
+
+/// Changes the levels of the running subscriber without a restart
+pub struct LogLevelControl {
+    handle: reload::Handle<EnvFilter, Registry>,
+    current: Mutex<LogLevels>,
+}
+
+impl LogLevelControl {
+    pub fn current(&self) -> LogLevels {
+        self.current.lock().clone()
+    }
+
+    /// Apply an update; nothing changes when any of its levels or targets is invalid
+    pub fn update(&self, update: &LogLevelUpdate) -> Result<LogLevels, EpcisKgError> {
+        let mut current = self.current.lock();
+        let levels = current.merged(update);
+        self.handle
+            .reload(levels.filter()?)
+            .map_err(|e| EpcisKgError::Config(format!("Failed to change log levels: {}", e)))?;
+        info!("Log levels changed to {} with targets {:?}", levels.level, levels.targets);
+        *current = levels.clone();
+        Ok(levels)
+    }
+}
+
+/// Level control of the subscriber installed by `init_logging`
+pub fn log_level_control() -> Option<&'static LogLevelControl> {
+    LOG_LEVELS.get()
+}
+
 /// Initialize logging system
 pub fn init_logging(config: LoggingConfig) -> Result<(), Box<dyn std::error::Error>> {
     // Create log directory if it doesn't exist
@@ -425,12 +543,13 @@ pub fn init_logging(config: LoggingConfig) -> Result<(), Box<dyn std::error::Err
         fs::create_dir_all(&config.log_directory)?;
     }
     
-    // Parse log level
-    let level = config.level.parse::<Level>()
-        .unwrap_or(Level::INFO);
+    // Levels live behind a reload handle so they can be changed at runtime
+    let levels = LogLevels::from_config(&config);
+    let (filter, handle) = reload::Layer::new(levels.filter()?);
     
     // Initialize tracing subscriber
     tracing_subscriber::registry()
+        .with(filter)
         .with(
             fmt::layer()
                 .with_target(false)
@@ -438,12 +557,10 @@ pub fn init_logging(config: LoggingConfig) -> Result<(), Box<dyn std::error::Err
                 .with_thread_names(true)
                 .compact()
         )
-        .with(
-            tracing_subscriber::filter::LevelFilter::from_level(level)
-        )
         .init();
     
-    info!("Logging system initialized with level: {}", level);
+    info!("Logging system initialized with level: {} (targets: {:?})", levels.level, levels.targets);
+    let _ = LOG_LEVELS.set(LogLevelControl { handle, current: Mutex::new(levels) });
     
     Ok(())
 }
@@ -461,4 +578,35 @@ pub fn get_database_logger(config: Arc<LoggingConfig>) -> DatabaseLogger {
 /// Get a reasoning logger instance
 pub fn get_reasoning_logger(config: Arc<LoggingConfig>) -> ReasoningLogger {
     ReasoningLogger::new(config)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_updates_merge_into_per_target_levels() {
+        let config = LoggingConfig {
+            targets: BTreeMap::from([("storage".to_string(), "debug".to_string())]),
+            ..LoggingConfig::default()
+        };
+        let levels = LogLevels::from_config(&config);
+        assert!(levels.filter().is_ok());
+        assert_eq!(qualified_target("ontology::reasoner").unwrap(), "epcis_knowledge_graph::ontology::reasoner");
+        assert_eq!(qualified_target("tower_http").unwrap(), "tower_http");
+        assert!(qualified_target("storage=trace").is_err());
+
+        let update = LogLevelUpdate {
+            level: Some("warn".to_string()),
+            targets: BTreeMap::from([
+                ("storage".to_string(), None),
+                ("ontology::reasoner".to_string(), Some("trace".to_string())),
+            ]),
+        };
+        let merged = levels.merged(&update);
+        assert_eq!(merged.level, "warn");
+        assert_eq!(merged.targets, BTreeMap::from([("ontology::reasoner".to_string(), "trace".to_string())]));
+
+        let invalid = LogLevelUpdate { level: Some("loud".to_string()), ..LogLevelUpdate::default() };
+        assert!(matches!(levels.merged(&invalid).filter(), Err(EpcisKgError::Validation(_))));
+    }
+}
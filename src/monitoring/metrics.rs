@@ -1,6 +1,6 @@
 use std::sync::atomic::{AtomicU64, AtomicU32, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use serde::{Serialize, Deserialize};
 use parking_lot::Mutex;
 
@@ -39,6 +39,10 @@ pub struct SystemMetrics {
     
     /// API endpoint metrics
     pub api_metrics: ApiMetrics,
+    
+    /// Bytes saved by response and request body encoding
+    #[serde(default)]
+    pub compression: CompressionStats,
 }
 
 /// Database-specific metrics
@@ -60,6 +64,108 @@ pub struct DatabaseMetrics {
     pub storage_size_mb: u64,
 }
 
+/// What a store records about itself: query latencies, dataset size and
+/// cache lookups. Clones of a store share one instance, and `SystemMonitor`
+/// reads it for `database_metrics`.
+#[derive(Debug, Default)]
+pub struct StoreMetrics {
+    queries: AtomicU64,
+    query_time_us: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    total_triples: AtomicU64,
+    named_graphs: AtomicU32,
+    storage_bytes: AtomicU64,
+}
+
+impl StoreMetrics {
+    pub fn record_query(&self, elapsed: Duration) {
+        self.queries.fetch_add(1, Ordering::Relaxed);
+        self.query_time_us.fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+    
+    /// A dataset read answered from a client's cached copy (`hit`) or recomputed
+    pub fn record_cache_lookup(&self, hit: bool) {
+        let counter = if hit { &self.cache_hits } else { &self.cache_misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    pub fn record_size(&self, total_triples: u64, named_graphs: u32, storage_bytes: u64) {
+        self.total_triples.store(total_triples, Ordering::Relaxed);
+        self.named_graphs.store(named_graphs, Ordering::Relaxed);
+        self.storage_bytes.store(storage_bytes, Ordering::Relaxed);
+    }
+    
+    pub fn snapshot(&self) -> DatabaseMetrics {
+        let queries = self.queries.load(Ordering::Relaxed);
+        let hits = self.cache_hits.load(Ordering::Relaxed);
+        let lookups = hits + self.cache_misses.load(Ordering::Relaxed);
+        DatabaseMetrics {
+            total_triples: self.total_triples.load(Ordering::Relaxed),
+            named_graphs: self.named_graphs.load(Ordering::Relaxed),
+            avg_query_time_ms: if queries == 0 {
+                0.0
+            } else {
+                self.query_time_us.load(Ordering::Relaxed) as f64 / queries as f64 / 1000.0
+            },
+            cache_hit_ratio: if lookups == 0 { 0.0 } else { hits as f64 / lookups as f64 },
+            storage_size_mb: self.storage_bytes.load(Ordering::Relaxed) / (1024 * 1024),
+        }
+    }
+}
+
+/// Bytes the server's gzip and Brotli codecs handled, shared by the server's
+/// compression layers and reported by `SystemMonitor`
+#[derive(Debug, Default)]
+pub struct CompressionMetrics {
+    responses: AtomicU64,
+    response_bytes: AtomicU64,
+    response_bytes_sent: AtomicU64,
+    requests: AtomicU64,
+    request_bytes: AtomicU64,
+    request_bytes_received: AtomicU64,
+}
+
+impl CompressionMetrics {
+    /// An encoded response: its size before encoding and the bytes sent
+    pub fn record_response(&self, original: u64, sent: u64) {
+        self.responses.fetch_add(1, Ordering::Relaxed);
+        self.response_bytes.fetch_add(original, Ordering::Relaxed);
+        self.response_bytes_sent.fetch_add(sent, Ordering::Relaxed);
+    }
+    
+    /// An encoded request body: the bytes received and its size once decoded
+    pub fn record_request(&self, received: u64, decoded: u64) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.request_bytes.fetch_add(decoded, Ordering::Relaxed);
+        self.request_bytes_received.fetch_add(received, Ordering::Relaxed);
+    }
+    
+    pub fn snapshot(&self) -> CompressionStats {
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+        let ratio = |encoded: u64, original: u64| if original == 0 { 1.0 } else { encoded as f64 / original as f64 };
+        CompressionStats {
+            compressed_responses: load(&self.responses),
+            response_bytes_saved: load(&self.response_bytes).saturating_sub(load(&self.response_bytes_sent)),
+            response_ratio: ratio(load(&self.response_bytes_sent), load(&self.response_bytes)),
+            compressed_requests: load(&self.requests),
+            request_bytes_saved: load(&self.request_bytes).saturating_sub(load(&self.request_bytes_received)),
+            request_ratio: ratio(load(&self.request_bytes_received), load(&self.request_bytes)),
+        }
+    }
+}
+
+/// Totals of `CompressionMetrics`; a ratio is encoded bytes over original bytes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompressionStats {
+    pub compressed_responses: u64,
+    pub response_bytes_saved: u64,
+    pub response_ratio: f64,
+    pub compressed_requests: u64,
+    pub request_bytes_saved: u64,
+    pub request_ratio: f64,
+}
+
 /// Reasoning-specific metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReasoningMetrics {
@@ -185,6 +291,9 @@ pub enum AlertType {
     Database,
     Api,
     System,
+    ColdChain,
+    EventPattern,
+    SchemaDrift,
 }
 
 /// Request tracking for monitoring
@@ -299,6 +408,12 @@ pub struct SystemMonitor {
     
     /// Request metrics history
     request_history: Arc<Mutex<Vec<RequestMetrics>>>,
+    
+    /// Recorded by the store that database metrics describe
+    store_metrics: Arc<StoreMetrics>,
+    
+    /// Recorded by the server's compression layers
+    compression_metrics: Arc<CompressionMetrics>,
 }
 
 impl SystemMonitor {
@@ -314,6 +429,8 @@ impl SystemMonitor {
             alert_config: AlertConfig::default(),
             alerts: Arc::new(Mutex::new(Vec::new())),
             request_history: Arc::new(Mutex::new(Vec::new())),
+            store_metrics: Arc::new(StoreMetrics::default()),
+            compression_metrics: Arc::new(CompressionMetrics::default()),
         }
     }
     
@@ -325,6 +442,17 @@ impl SystemMonitor {
         }
     }
     
+    /// Report database metrics recorded by a store (see `OxigraphStore::metrics`)
+    pub fn with_store_metrics(mut self, store_metrics: Arc<StoreMetrics>) -> Self {
+        self.store_metrics = store_metrics;
+        self
+    }
+    
+    /// Counters the server's compression layers record into
+    pub fn compression_metrics(&self) -> Arc<CompressionMetrics> {
+        Arc::clone(&self.compression_metrics)
+    }
+    
     /// Track a new request
     pub fn track_request(&self, endpoint: String, method: String) -> RequestTracker {
         self.total_requests.fetch_add(1, Ordering::Relaxed);
@@ -429,13 +557,8 @@ impl SystemMonitor {
             memory_usage_mb,
             cpu_usage_percent,
             active_connections: self.active_connections.load(Ordering::Relaxed),
-            database_metrics: DatabaseMetrics {
-                total_triples: 0, // Would be populated from actual database
-                named_graphs: 0,
-                avg_query_time_ms: 0.0,
-                cache_hit_ratio: 0.0,
-                storage_size_mb: 0,
-            },
+            database_metrics: self.store_metrics.snapshot(),
+            compression: self.compression_metrics.snapshot(),
             reasoning_metrics: ReasoningMetrics {
                 total_inferences: 0,
                 avg_inference_time_ms: 0.0,
@@ -537,6 +660,20 @@ impl SystemMonitor {
             });
         }
         
+        // Check database size
+        if metrics.database_metrics.storage_size_mb > self.alert_config.db_size_threshold_mb {
+            alerts.push(SystemAlert {
+                id: uuid::Uuid::new_v4().to_string(),
+                severity: AlertSeverity::Warning,
+                alert_type: AlertType::Database,
+                message: format!("Database size ({}MB) exceeds threshold ({}MB)", 
+                    metrics.database_metrics.storage_size_mb, self.alert_config.db_size_threshold_mb),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                acknowledged: false,
+                context: serde_json::json!({"current_storage_size_mb": metrics.database_metrics.storage_size_mb}),
+            });
+        }
+        
         alerts
     }
     
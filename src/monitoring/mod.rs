@@ -1,5 +1,10 @@
+pub mod access_log;
+pub mod doctor;
+pub mod lineage;
 pub mod logging;
 pub mod metrics;
+pub mod query_log;
+pub mod webhooks;
 
 pub use logging::*;
-pub use metrics::*;
\ No newline at end of file
+pub use metrics::*;
@@ -0,0 +1,182 @@
+use crate::config::QueryLogConfig;
+use crate::storage::oxigraph_store::QueryPlan;
+use crate::EpcisKgError;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::io::Write;
+use std::time::Duration;
+
+/// One executed SPARQL query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryLogEntry {
+    pub timestamp: String,
+    pub query: String,
+    pub duration_ms: f64,
+    /// Solutions returned; absent when the query failed
+    pub result_count: Option<usize>,
+    /// Name of the caller's API key, if any
+    pub caller: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub slow: bool,
+    /// Captured for slow queries only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plan: Option<QueryPlan>,
+}
+
+/// Recent queries in memory, every query appended to the log file
+pub struct QueryLog {
+    config: QueryLogConfig,
+    entries: Mutex<VecDeque<QueryLogEntry>>,
+}
+
+impl QueryLog {
+    pub fn from_config(config: &QueryLogConfig) -> Self {
+        Self {
+            config: config.clone(),
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Whether a query this long gets its plan captured
+    pub fn is_slow(&self, duration: Duration) -> bool {
+        self.config.enabled && duration >= Duration::from_millis(self.config.slow_threshold_ms)
+    }
+
+    pub fn record(&self, entry: QueryLogEntry) {
+        if !self.config.enabled {
+            return;
+        }
+        if !self.config.path.is_empty() {
+            if let Err(e) = self.append(&entry) {
+                tracing::warn!("Failed to append to query log {}: {}", self.config.path, e);
+            }
+        }
+        let mut entries = self.entries.lock();
+        entries.push_back(entry);
+        while entries.len() > self.config.capacity {
+            entries.pop_front();
+        }
+    }
+
+    fn append(&self, entry: &QueryLogEntry) -> Result<(), EpcisKgError> {
+        if let Some(parent) = std::path::Path::new(&self.config.path).parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&self.config.path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+
+    /// Recent slow queries, slowest first
+    pub fn slow(&self, limit: usize) -> Vec<QueryLogEntry> {
+        let mut slow: Vec<QueryLogEntry> = self.entries.lock().iter().filter(|entry| entry.slow).cloned().collect();
+        slow.sort_by(|a, b| b.duration_ms.total_cmp(&a.duration_ms));
+        slow.truncate(limit);
+        slow
+    }
+}
+
+/// Entries of a query log file; unreadable lines are skipped
+pub fn read_log(path: &str) -> Result<Vec<QueryLogEntry>, EpcisKgError> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
+
+/// Statistics of one distinct query text
+#[derive(Debug, Clone, Serialize)]
+pub struct QuerySummary {
+    pub query: String,
+    pub executions: usize,
+    pub slow_executions: usize,
+    pub failures: usize,
+    pub avg_duration_ms: f64,
+    pub max_duration_ms: f64,
+    pub callers: Vec<String>,
+    /// Plan of the slowest execution that captured one
+    pub plan: Option<QueryPlan>,
+}
+
+/// Group log entries by query text (whitespace-normalized), slowest total time first
+pub fn summarize(entries: &[QueryLogEntry]) -> Vec<QuerySummary> {
+    let mut groups: BTreeMap<String, Vec<&QueryLogEntry>> = BTreeMap::new();
+    for entry in entries {
+        let normalized = entry.query.split_whitespace().collect::<Vec<_>>().join(" ");
+        groups.entry(normalized).or_default().push(entry);
+    }
+
+    let mut summaries: Vec<(f64, QuerySummary)> = groups
+        .into_iter()
+        .map(|(query, runs)| {
+            let total: f64 = runs.iter().map(|entry| entry.duration_ms).sum();
+            let slowest_planned = runs
+                .iter()
+                .filter(|entry| entry.plan.is_some())
+                .max_by(|a, b| a.duration_ms.total_cmp(&b.duration_ms));
+            let mut callers: Vec<String> = runs.iter().filter_map(|entry| entry.caller.clone()).collect();
+            callers.sort();
+            callers.dedup();
+            let summary = QuerySummary {
+                query,
+                executions: runs.len(),
+                slow_executions: runs.iter().filter(|entry| entry.slow).count(),
+                failures: runs.iter().filter(|entry| entry.error.is_some()).count(),
+                avg_duration_ms: total / runs.len() as f64,
+                max_duration_ms: runs.iter().map(|entry| entry.duration_ms).fold(0.0, f64::max),
+                callers,
+                plan: slowest_planned.and_then(|entry| entry.plan.clone()),
+            };
+            (total, summary)
+        })
+        .collect();
+    summaries.sort_by(|a, b| b.0.total_cmp(&a.0));
+    summaries.into_iter().map(|(_, summary)| summary).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(query: &str, duration_ms: f64, slow: bool) -> QueryLogEntry {
+        QueryLogEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            query: query.to_string(),
+            duration_ms,
+            result_count: Some(1),
+            caller: Some("analytics".to_string()),
+            error: None,
+            slow,
+            plan: None,
+        }
+    }
+
+    #[test]
+    fn test_slow_queries_are_kept_and_summarized() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("queries.jsonl").to_string_lossy().to_string();
+        let log = QueryLog::from_config(&QueryLogConfig { path: path.clone(), slow_threshold_ms: 100, capacity: 2, ..QueryLogConfig::default() });
+        assert!(log.is_slow(Duration::from_millis(100)));
+        assert!(!log.is_slow(Duration::from_millis(99)));
+
+        log.record(entry("SELECT ?s WHERE { ?s ?p ?o }", 400.0, true));
+        log.record(entry("SELECT ?s  WHERE { ?s ?p ?o }", 150.0, true));
+        log.record(entry("SELECT ?o WHERE { ?s ?p ?o }", 5.0, false));
+
+        // Memory keeps the newest entries; the file keeps all of them
+        assert_eq!(log.slow(10).iter().map(|entry| entry.duration_ms).collect::<Vec<_>>(), vec![150.0]);
+        let logged = read_log(&path).unwrap();
+        assert_eq!(logged.len(), 3);
+
+        let summaries = summarize(&logged);
+        assert_eq!(summaries[0].query, "SELECT ?s WHERE { ?s ?p ?o }");
+        assert_eq!(summaries[0].executions, 2);
+        assert_eq!(summaries[0].slow_executions, 2);
+        assert_eq!(summaries[0].avg_duration_ms, 275.0);
+        assert_eq!(summaries[1].callers, vec!["analytics".to_string()]);
+    }
+}
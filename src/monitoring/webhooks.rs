@@ -0,0 +1,75 @@
+use crate::config::ResiliencePolicy;
+use crate::utils::resilience::{Failure, Resilience};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Delivers alert payloads to configured webhook URLs.
+///
+/// Each delivery is a fire-and-forget JSON POST on the Tokio runtime; failures
+/// are logged and never block the caller. Every URL has its own retries and
+/// circuit breaker, so one unreachable receiver does not affect the others.
+#[derive(Debug, Clone, Default)]
+pub struct WebhookNotifier {
+    endpoints: Vec<(String, Arc<Resilience>)>,
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    pub fn new(urls: Vec<String>, policy: ResiliencePolicy) -> Self {
+        Self {
+            endpoints: urls
+                .into_iter()
+                .map(|url| {
+                    let resilience = Arc::new(Resilience::new(format!("webhook {}", url), policy.clone()));
+                    (url, resilience)
+                })
+                .collect(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+
+    /// POST `{"event": <event>, "payload": <payload>}` to every URL
+    pub fn notify<T: Serialize>(&self, event: &str, payload: &T) {
+        if self.endpoints.is_empty() {
+            return;
+        }
+        let body = serde_json::json!({
+            "event": event,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "payload": payload,
+        });
+
+        for (url, resilience) in &self.endpoints {
+            let (client, url, body, resilience) = (self.client.clone(), url.clone(), body.clone(), Arc::clone(resilience));
+            tokio::spawn(async move {
+                let delivered = resilience
+                    .call(&body, || async {
+                        match client.post(&url).json(&body).send().await {
+                            Ok(response) if response.status().is_success() => Ok(()),
+                            Ok(response) => Err(classify(response.status())),
+                            Err(e) => Err(Failure::Transient(e.to_string())),
+                        }
+                    })
+                    .await;
+                if let Err(e) = delivered {
+                    warn!("Webhook {} failed: {}", url, e);
+                }
+            });
+        }
+    }
+}
+
+/// Server errors, throttling and timeouts may pass; other client errors will not
+pub(crate) fn classify(status: reqwest::StatusCode) -> Failure {
+    let message = format!("answered {}", status);
+    if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS || status == reqwest::StatusCode::REQUEST_TIMEOUT {
+        Failure::Transient(message)
+    } else {
+        Failure::Permanent(message)
+    }
+}
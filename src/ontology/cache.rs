@@ -0,0 +1,143 @@
+use crate::config::OntologyCacheConfig;
+use crate::ontology::loader::OntologyData;
+use crate::EpcisKgError;
+use oxrdf::Graph;
+use oxttl::NTriplesParser;
+use sha2::{Digest, Sha256};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever the cached layout or the Turtle parsing changes
+const CACHE_FORMAT_VERSION: u32 = 1;
+
+/// Environment variable overriding the cache directory
+pub const CACHE_DIR_ENV: &str = "EPCIS_KG_ONTOLOGY_CACHE_DIR";
+
+/// User-level cache of parsed ontologies, shared by every database path.
+///
+/// Entries are keyed by a hash of the source file's bytes, so the same
+/// `cbv.ttl` copied into many experiment directories is parsed once. Each entry
+/// holds the parsed triples as N-Triples, which reload without prefix handling.
+#[derive(Debug, Clone)]
+pub struct OntologyCache {
+    directory: PathBuf,
+}
+
+impl OntologyCache {
+    pub fn new<P: AsRef<Path>>(directory: P) -> Self {
+        Self {
+            directory: directory.as_ref().to_path_buf(),
+        }
+    }
+
+    /// The configured cache, or `None` if disabled or no cache directory can be determined
+    pub fn from_config(config: &OntologyCacheConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let directory = match std::env::var(CACHE_DIR_ENV) {
+            Ok(directory) if !directory.is_empty() => PathBuf::from(directory),
+            _ if !config.directory.is_empty() => PathBuf::from(&config.directory),
+            _ => user_cache_dir()?,
+        };
+        Some(Self::new(directory))
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    fn entry_path(&self, content_hash: &str) -> PathBuf {
+        self.directory.join(format!("{}.nt", content_hash))
+    }
+
+    /// Parsed triples for a content hash; unreadable entries count as misses
+    pub fn load(&self, content_hash: &str, source_file: &str) -> Option<OntologyData> {
+        let file = std::fs::File::open(self.entry_path(content_hash)).ok()?;
+        let mut graph = Graph::default();
+        for triple in NTriplesParser::new().with_quoted_triples().for_reader(BufReader::new(file)) {
+            graph.insert(triple.ok()?.as_ref());
+        }
+        Some(OntologyData {
+            triples_count: graph.len(),
+            graph,
+            source_file: source_file.to_string(),
+        })
+    }
+
+    /// Write an entry atomically (temporary file, then rename)
+    pub fn save(&self, content_hash: &str, ontology_data: &OntologyData) -> Result<(), EpcisKgError> {
+        std::fs::create_dir_all(&self.directory)?;
+
+        let mut content = String::new();
+        for triple in ontology_data.graph.iter() {
+            content.push_str(&triple.to_string());
+            content.push_str(" .\n");
+        }
+        let path = self.entry_path(content_hash);
+        let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+        std::fs::write(&temp_path, content)?;
+        std::fs::rename(&temp_path, &path)?;
+
+        Ok(())
+    }
+}
+
+/// SHA-256 of a file's bytes (and the cache format), read in chunks
+pub fn file_content_hash(path: &Path) -> Result<String, EpcisKgError> {
+    let mut reader = BufReader::new(std::fs::File::open(path)?);
+    let mut hasher = Sha256::new();
+    hasher.update(CACHE_FORMAT_VERSION.to_le_bytes());
+    loop {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            break;
+        }
+        hasher.update(chunk);
+        let length = chunk.len();
+        reader.consume(length);
+    }
+
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// `$XDG_CACHE_HOME/epcis-knowledge-graph/ontologies`, else under `~/.cache`
+fn user_cache_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CACHE_HOME") {
+        Some(directory) if !directory.is_empty() => PathBuf::from(directory),
+        _ => PathBuf::from(std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?).join(".cache"),
+    };
+    Some(base.join("epcis-knowledge-graph").join("ontologies"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ontology::loader::OntologyLoader;
+
+    #[test]
+    fn test_parsed_ontology_is_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("cbv.ttl");
+        std::fs::write(
+            &source,
+            "@prefix cbv: <urn:epcglobal:cbv:> .\n\
+             cbv:Bizstep <http://www.w3.org/2000/01/rdf-schema#label> \"Business step\\n\"@en .\n",
+        )
+        .unwrap();
+        let cache = OntologyCache::new(dir.path().join("cache"));
+        let loader = OntologyLoader::new().with_cache(Some(cache.clone()));
+
+        let parsed = loader.load_ontology(&source).unwrap();
+        let hash = file_content_hash(&source).unwrap();
+        let cached = cache.load(&hash, "cbv.ttl").unwrap();
+        assert_eq!(cached.triples_count, 1);
+        assert_eq!(cached.graph, parsed.graph);
+
+        // A copy elsewhere has the same content, so it hits the same entry
+        let copy = dir.path().join("copy.ttl");
+        std::fs::copy(&source, &copy).unwrap();
+        assert_eq!(file_content_hash(&copy).unwrap(), hash);
+        assert_eq!(loader.load_ontology(&copy).unwrap().graph, parsed.graph);
+    }
+}
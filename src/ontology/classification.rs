@@ -0,0 +1,125 @@
+use crate::ontology::warm_start::{class_hierarchy_closure, CachedAxiom, OWL_THING};
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// Transitive superclasses of every class named in a SubClassOf axiom.
+///
+/// From `parallel_min_classes` classes the hierarchy is computed per connected
+/// component in parallel (`classify_partitioned`); below that the sequential
+/// closure is cheaper than the partitioning.
+pub fn classify(axioms: &[CachedAxiom], parallel_min_classes: usize) -> HashMap<String, Vec<String>> {
+    let classes: HashSet<&str> = axioms
+        .iter()
+        .filter_map(|axiom| match axiom {
+            CachedAxiom::SubClassOf { sub_class, .. } => Some(sub_class.as_str()),
+            _ => None,
+        })
+        .collect();
+    if classes.len() < parallel_min_classes.max(1) {
+        return class_hierarchy_closure(axioms);
+    }
+    classify_partitioned(axioms, rayon::current_num_threads())
+}
+
+/// Class hierarchy computed over `partitions` groups of connected components in
+/// parallel, then merged.
+///
+/// A class's superclasses all lie in its own component, so partitions never
+/// need each other's results. Components are packed largest first into the
+/// partition with the fewest axioms, keeping one partition per worker thread busy.
+pub fn classify_partitioned(axioms: &[CachedAxiom], partitions: usize) -> HashMap<String, Vec<String>> {
+    let mut bins: Vec<Vec<CachedAxiom>> = vec![Vec::new(); partitions.max(1)];
+    for component in class_components(axioms) {
+        let emptiest = bins.iter_mut().min_by_key(|bin| bin.len()).expect("at least one partition");
+        emptiest.extend(component.into_iter().cloned());
+    }
+
+    bins.par_iter()
+        .filter(|bin| !bin.is_empty())
+        .map(|bin| class_hierarchy_closure(bin))
+        .reduce(HashMap::new, |mut merged, part| {
+            merged.extend(part);
+            merged
+        })
+}
+
+/// SubClassOf axioms grouped by connected component of the class graph, largest first.
+///
+/// `owl:Thing` does not connect components, since nearly every class reaches it.
+pub fn class_components(axioms: &[CachedAxiom]) -> Vec<Vec<&CachedAxiom>> {
+    let mut nodes: HashMap<&str, usize> = HashMap::new();
+    let mut parent: Vec<usize> = Vec::new();
+    let edges = axioms.iter().filter_map(|axiom| match axiom {
+        CachedAxiom::SubClassOf { sub_class, super_class } => Some((sub_class.as_str(), super_class.as_str())),
+        _ => None,
+    });
+    for (sub_class, super_class) in edges {
+        let sub = node(&mut nodes, &mut parent, sub_class);
+        if super_class == OWL_THING {
+            continue;
+        }
+        let sup = node(&mut nodes, &mut parent, super_class);
+        let (sub_root, sup_root) = (find(&mut parent, sub), find(&mut parent, sup));
+        if sub_root != sup_root {
+            parent[sub_root] = sup_root;
+        }
+    }
+
+    let mut components: HashMap<usize, Vec<&CachedAxiom>> = HashMap::new();
+    for axiom in axioms {
+        if let CachedAxiom::SubClassOf { sub_class, .. } = axiom {
+            let root = find(&mut parent, nodes[sub_class.as_str()]);
+            components.entry(root).or_default().push(axiom);
+        }
+    }
+    let mut components: Vec<Vec<&CachedAxiom>> = components.into_values().collect();
+    components.sort_by_key(|component| std::cmp::Reverse(component.len()));
+    components
+}
+
+/// Union-find node of a class, created on first sight
+fn node<'a>(nodes: &mut HashMap<&'a str, usize>, parent: &mut Vec<usize>, class: &'a str) -> usize {
+    *nodes.entry(class).or_insert_with(|| {
+        parent.push(parent.len());
+        parent.len() - 1
+    })
+}
+
+fn find(parent: &mut [usize], mut node: usize) -> usize {
+    while parent[node] != node {
+        parent[node] = parent[parent[node]];
+        node = parent[node];
+    }
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sub_class(sub: &str, sup: &str) -> CachedAxiom {
+        CachedAxiom::SubClassOf {
+            sub_class: sub.to_string(),
+            super_class: sup.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_partitioned_matches_sequential() {
+        let axioms = vec![
+            sub_class("urn:ex:ObjectEvent", "urn:ex:Event"),
+            sub_class("urn:ex:Event", "urn:ex:Resource"),
+            sub_class("urn:ex:Event", OWL_THING),
+            sub_class("urn:ex:Pallet", "urn:ex:Container"),
+            sub_class("urn:ex:Container", OWL_THING),
+            sub_class("urn:ex:Case", "urn:ex:Container"),
+            sub_class("urn:ex:Site", "urn:ex:Location"),
+        ];
+
+        assert_eq!(class_components(&axioms).len(), 3);
+        let partitioned = classify_partitioned(&axioms, 2);
+        assert_eq!(partitioned, class_hierarchy_closure(&axioms));
+        assert_eq!(partitioned["urn:ex:ObjectEvent"], vec!["urn:ex:Event", "urn:ex:Resource"]);
+        assert_eq!(classify(&axioms, 1), partitioned);
+    }
+}
@@ -0,0 +1,120 @@
+use crate::ontology::reasoner::PROVENANCE_NS;
+use chrono::{DateTime, Utc};
+use oxrdf::{Literal, NamedNode, Subject, Triple};
+use serde::Serialize;
+
+const CBV_NS: &str = "urn:epcglobal:cbv:";
+
+/// The vocabulary term closest to an unknown value
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TermSuggestion {
+    pub term: String,
+    /// 1 minus the edit distance relative to the longer of the two spellings
+    pub confidence: f64,
+    /// False when another term is just as close
+    pub unique: bool,
+}
+
+/// An event value replaced by its closest vocabulary term
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AutoCorrection {
+    /// Event property corrected, e.g. `bizStep`
+    pub field: String,
+    pub original: String,
+    pub corrected: String,
+    pub confidence: f64,
+}
+
+/// Levenshtein distance between two values, ignoring case
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// The candidate closest to `value`, if any candidate is given
+pub fn closest_term<'a>(value: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<TermSuggestion> {
+    let mut best: Option<TermSuggestion> = None;
+    for candidate in candidates {
+        let longer = value.chars().count().max(candidate.chars().count()).max(1);
+        let confidence = 1.0 - edit_distance(value, candidate) as f64 / longer as f64;
+        match &mut best {
+            Some(best) if confidence < best.confidence => {}
+            Some(best) if confidence == best.confidence => best.unique = false,
+            _ => best = Some(TermSuggestion { term: candidate.to_string(), confidence, unique: true }),
+        }
+    }
+    best
+}
+
+/// RDF-star annotations recording each correction on the corrected event triple
+pub fn correction_triples(event_id: &str, corrections: &[AutoCorrection], at: DateTime<Utc>) -> Vec<Triple> {
+    let provenance = |local: &str| NamedNode::new_unchecked(format!("{}{}", PROVENANCE_NS, local));
+    let xsd = |datatype: &str| NamedNode::new_unchecked(format!("http://www.w3.org/2001/XMLSchema#{}", datatype));
+    let event = NamedNode::new_unchecked(format!("urn:epc:event:{}", event_id));
+
+    let mut triples = Vec::new();
+    for correction in corrections {
+        let Ok(value) = NamedNode::new(format!("{}{}", CBV_NS, correction.corrected)) else { continue };
+        let corrected = Triple::new(
+            event.clone(),
+            NamedNode::new_unchecked(format!("urn:epcglobal:epcis:{}", correction.field)),
+            value,
+        );
+        let quoted = Subject::Triple(Box::new(corrected));
+        triples.push(Triple::new(quoted.clone(), provenance("correctedFrom"), Literal::new_simple_literal(&correction.original)));
+        triples.push(Triple::new(
+            quoted.clone(),
+            provenance("correctionConfidence"),
+            Literal::new_typed_literal(format!("{:.3}", correction.confidence), xsd("decimal")),
+        ));
+        triples.push(Triple::new(quoted, provenance("correctedAt"), Literal::new_typed_literal(at.to_rfc3339(), xsd("dateTime"))));
+    }
+    triples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_term_and_provenance() {
+        assert_eq!(edit_distance("shiping", "shipping"), 1);
+        assert_eq!(edit_distance("Receiving", "receiving"), 0);
+        assert_eq!(edit_distance("", "abc"), 3);
+
+        let bizsteps = ["shipping", "receiving", "storing", "packing"];
+        let suggestion = closest_term("shiping", bizsteps).unwrap();
+        assert_eq!(suggestion.term, "shipping");
+        assert_eq!(suggestion.confidence, 0.875);
+        assert!(suggestion.unique);
+
+        // "acking" is one edit from "packing" and two from "picking": still unique
+        assert!(closest_term("acking", ["packing", "picking"]).unwrap().unique);
+        assert!(!closest_term("pxcking", ["packing", "picking"]).unwrap().unique);
+        assert!(closest_term("shipping", std::iter::empty()).is_none());
+
+        let correction = AutoCorrection {
+            field: "bizStep".to_string(),
+            original: "shiping".to_string(),
+            corrected: "shipping".to_string(),
+            confidence: 0.875,
+        };
+        let triples = correction_triples("evt-1", &[correction], Utc::now());
+        assert_eq!(triples.len(), 3);
+        let Subject::Triple(quoted) = &triples[0].subject else { panic!("expected a quoted triple") };
+        assert_eq!(quoted.object.to_string(), "<urn:epcglobal:cbv:shipping>");
+        assert_eq!(triples[1].object.to_string(), "\"0.875\"^^<http://www.w3.org/2001/XMLSchema#decimal>");
+    }
+}
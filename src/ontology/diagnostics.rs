@@ -0,0 +1,324 @@
+use crate::ontology::scope::{GraphRole, InferenceScope};
+use crate::storage::oxigraph_store::OxigraphStore;
+use oxrdf::{Graph, GraphName, NamedNodeRef, SubjectRef, TermRef};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDF_PROPERTY: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#Property";
+const RDFS_CLASS: &str = "http://www.w3.org/2000/01/rdf-schema#Class";
+const RDFS_SUBCLASS_OF: &str = "http://www.w3.org/2000/01/rdf-schema#subClassOf";
+const RDFS_SUBPROPERTY_OF: &str = "http://www.w3.org/2000/01/rdf-schema#subPropertyOf";
+const OWL_CLASS: &str = "http://www.w3.org/2002/07/owl#Class";
+const OWL_THING: &str = "http://www.w3.org/2002/07/owl#Thing";
+const OWL_NOTHING: &str = "http://www.w3.org/2002/07/owl#Nothing";
+const OWL_EQUIVALENT_CLASS: &str = "http://www.w3.org/2002/07/owl#equivalentClass";
+const OWL_DISJOINT_WITH: &str = "http://www.w3.org/2002/07/owl#disjointWith";
+const OWL_OBJECT_PROPERTY: &str = "http://www.w3.org/2002/07/owl#ObjectProperty";
+const OWL_DATATYPE_PROPERTY: &str = "http://www.w3.org/2002/07/owl#DatatypeProperty";
+
+/// A class that can have no instances
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnsatisfiableClass {
+    pub class: String,
+    pub reason: String,
+}
+
+/// A `rdfs:subClassOf` axiom that adds nothing to the ontology
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RedundantAxiom {
+    pub sub_class: String,
+    pub super_class: String,
+    pub reason: String,
+}
+
+/// Quality findings for an ontology, to help authors clean up EPCIS extensions.
+///
+/// Unsatisfiable classes and redundant axioms come from the named class
+/// hierarchy (`rdfs:subClassOf`, `owl:equivalentClass`, `owl:disjointWith`).
+/// Unused classes and properties are only reported after `with_usage` has
+/// compared the ontology against instance data.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct OntologyDiagnostics {
+    pub classes: usize,
+    pub properties: usize,
+    pub unsatisfiable_classes: Vec<UnsatisfiableClass>,
+    pub redundant_axioms: Vec<RedundantAxiom>,
+    pub unused_classes: Vec<String>,
+    pub unused_properties: Vec<String>,
+    /// Whether the unused lists were computed
+    pub usage_checked: bool,
+    #[serde(skip)]
+    hierarchy: Hierarchy,
+}
+
+/// Named classes and properties of an ontology with their asserted super-entities
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Hierarchy {
+    classes: BTreeSet<String>,
+    properties: BTreeSet<String>,
+    /// Asserted `rdfs:subClassOf` edges
+    subclass_axioms: Vec<(String, String)>,
+    equivalences: Vec<(String, String)>,
+    /// Direct superclasses, including both directions of `owl:equivalentClass`
+    superclasses: BTreeMap<String, BTreeSet<String>>,
+    superproperties: BTreeMap<String, BTreeSet<String>>,
+    disjoint: Vec<(String, String)>,
+    /// Individuals typed in the ontology itself, e.g. CBV business steps
+    individuals: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl OntologyDiagnostics {
+    /// Check the class hierarchy of an ontology graph
+    pub fn analyze(ontology: &Graph) -> Self {
+        let hierarchy = Hierarchy::from_graph(ontology);
+        let mut diagnostics = Self {
+            classes: hierarchy.classes.len(),
+            properties: hierarchy.properties.len(),
+            ..Self::default()
+        };
+
+        for class in &hierarchy.classes {
+            if class == OWL_NOTHING {
+                continue;
+            }
+            if let Some(reason) = hierarchy.unsatisfiable_reason(class) {
+                diagnostics.unsatisfiable_classes.push(UnsatisfiableClass { class: class.clone(), reason });
+            }
+        }
+
+        for (sub_class, super_class) in &hierarchy.subclass_axioms {
+            if let Some(reason) = hierarchy.redundancy_reason(sub_class, super_class) {
+                diagnostics.redundant_axioms.push(RedundantAxiom {
+                    sub_class: sub_class.clone(),
+                    super_class: super_class.clone(),
+                    reason,
+                });
+            }
+        }
+
+        diagnostics.redundant_axioms.sort_by(|a, b| (&a.sub_class, &a.super_class).cmp(&(&b.sub_class, &b.super_class)));
+        diagnostics.hierarchy = hierarchy;
+        diagnostics
+    }
+
+    /// Report classes and properties that the scope's ABox graphs never use.
+    ///
+    /// A class counts as used when it or one of its subclasses types a
+    /// resource, or when one of the ontology's own individuals of it (such as
+    /// a CBV business step) is referenced. A property counts as used when it
+    /// or one of its sub-properties appears as a predicate.
+    pub fn with_usage(mut self, store: &OxigraphStore, scope: &InferenceScope) -> Self {
+        let is_instance_data = |graph: &GraphName| match graph {
+            GraphName::NamedNode(name) => scope.role(name.as_str()) == Some(GraphRole::ABox),
+            _ => false,
+        };
+        let used = |subject: Option<SubjectRef<'_>>, predicate: Option<NamedNodeRef<'_>>, object: Option<TermRef<'_>>| {
+            store
+                .quads_for_pattern(subject, predicate, object, None)
+                .iter()
+                .any(|quad| is_instance_data(&quad.graph_name))
+        };
+        let hierarchy = &self.hierarchy;
+        let rdf_type = NamedNodeRef::new_unchecked(RDF_TYPE);
+
+        let mut used_classes = HashSet::new();
+        for class in &hierarchy.classes {
+            let Ok(node) = NamedNodeRef::new(class) else { continue };
+            let referenced_individual = hierarchy.individuals.get(class).is_some_and(|individuals| {
+                individuals
+                    .iter()
+                    .filter_map(|individual| NamedNodeRef::new(individual).ok())
+                    .any(|individual| used(None, None, Some(individual.into())))
+            });
+            if referenced_individual || used(None, Some(rdf_type), Some(node.into())) {
+                used_classes.extend(hierarchy.ancestors(&hierarchy.superclasses, class, None));
+                used_classes.insert(class.clone());
+            }
+        }
+
+        let mut used_properties = HashSet::new();
+        for property in &hierarchy.properties {
+            let Ok(node) = NamedNodeRef::new(property) else { continue };
+            if used(None, Some(node), None) {
+                used_properties.extend(hierarchy.ancestors(&hierarchy.superproperties, property, None));
+                used_properties.insert(property.clone());
+            }
+        }
+
+        self.unused_classes = hierarchy
+            .classes
+            .iter()
+            .filter(|class| !used_classes.contains(*class) && *class != OWL_THING && *class != OWL_NOTHING)
+            .cloned()
+            .collect();
+        self.unused_properties = hierarchy.properties.iter().filter(|property| !used_properties.contains(*property)).cloned().collect();
+        self.usage_checked = true;
+        self
+    }
+
+    /// Whether nothing was found worth an author's attention
+    pub fn is_clean(&self) -> bool {
+        self.unsatisfiable_classes.is_empty()
+            && self.redundant_axioms.is_empty()
+            && self.unused_classes.is_empty()
+            && self.unused_properties.is_empty()
+    }
+}
+
+impl Hierarchy {
+    fn from_graph(graph: &Graph) -> Self {
+        let mut hierarchy = Self::default();
+        let named = |term: TermRef<'_>| match term {
+            TermRef::NamedNode(node) => Some(node.as_str().to_string()),
+            _ => None,
+        };
+
+        for triple in graph.iter() {
+            let SubjectRef::NamedNode(subject) = triple.subject else { continue };
+            let subject = subject.as_str().to_string();
+            let Some(object) = named(triple.object) else { continue };
+
+            match triple.predicate.as_str() {
+                RDF_TYPE => match object.as_str() {
+                    OWL_CLASS | RDFS_CLASS => {
+                        hierarchy.classes.insert(subject);
+                    }
+                    OWL_OBJECT_PROPERTY | OWL_DATATYPE_PROPERTY | RDF_PROPERTY => {
+                        hierarchy.properties.insert(subject);
+                    }
+                    _ => {
+                        hierarchy.individuals.entry(object).or_default().insert(subject);
+                    }
+                },
+                RDFS_SUBCLASS_OF => {
+                    hierarchy.classes.insert(subject.clone());
+                    hierarchy.classes.insert(object.clone());
+                    hierarchy.superclasses.entry(subject.clone()).or_default().insert(object.clone());
+                    hierarchy.subclass_axioms.push((subject, object));
+                }
+                OWL_EQUIVALENT_CLASS => {
+                    hierarchy.classes.insert(subject.clone());
+                    hierarchy.classes.insert(object.clone());
+                    hierarchy.superclasses.entry(subject.clone()).or_default().insert(object.clone());
+                    hierarchy.superclasses.entry(object.clone()).or_default().insert(subject.clone());
+                    hierarchy.equivalences.push((subject, object));
+                }
+                OWL_DISJOINT_WITH => hierarchy.disjoint.push((subject, object)),
+                RDFS_SUBPROPERTY_OF => {
+                    hierarchy.properties.insert(subject.clone());
+                    hierarchy.properties.insert(object.clone());
+                    hierarchy.superproperties.entry(subject).or_default().insert(object);
+                }
+                _ => {}
+            }
+        }
+
+        // Individuals typed with something that is not a class of this ontology are not ours to report on
+        let classes = &hierarchy.classes;
+        hierarchy.individuals.retain(|class, _| classes.contains(class));
+        hierarchy
+    }
+
+    /// Transitive super-entities of `start`, optionally ignoring one asserted edge
+    fn ancestors(&self, edges: &BTreeMap<String, BTreeSet<String>>, start: &str, skip: Option<(&str, &str)>) -> BTreeSet<String> {
+        let mut seen = BTreeSet::new();
+        let mut queue = VecDeque::from([start.to_string()]);
+        while let Some(current) = queue.pop_front() {
+            for parent in edges.get(&current).into_iter().flatten() {
+                if skip == Some((current.as_str(), parent.as_str())) {
+                    continue;
+                }
+                if seen.insert(parent.clone()) {
+                    queue.push_back(parent.clone());
+                }
+            }
+        }
+        seen
+    }
+
+    fn unsatisfiable_reason(&self, class: &str) -> Option<String> {
+        let mut ancestors = self.ancestors(&self.superclasses, class, None);
+        if ancestors.contains(OWL_NOTHING) {
+            return Some("subclass of owl:Nothing".to_string());
+        }
+        ancestors.insert(class.to_string());
+        self.disjoint
+            .iter()
+            .find(|(a, b)| ancestors.contains(a) && ancestors.contains(b))
+            .map(|(a, b)| format!("subclass of disjoint classes {} and {}", a, b))
+    }
+
+    fn redundancy_reason(&self, sub_class: &str, super_class: &str) -> Option<String> {
+        if sub_class == super_class {
+            return Some("a class is always a subclass of itself".to_string());
+        }
+        if super_class == OWL_THING {
+            return Some("every class is a subclass of owl:Thing".to_string());
+        }
+        if self.equivalences.iter().any(|(a, b)| (a == sub_class && b == super_class) || (a == super_class && b == sub_class)) {
+            return Some("entailed by owl:equivalentClass".to_string());
+        }
+        // Another direct superclass that still reaches the superclass once this axiom is ignored
+        let skip = Some((sub_class, super_class));
+        self.superclasses[sub_class]
+            .iter()
+            .filter(|direct| *direct != super_class)
+            .find(|direct| self.ancestors(&self.superclasses, direct, skip).contains(super_class))
+            .map(|via| format!("entailed through {}", via))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::InferenceScopeConfig;
+    use oxttl::TurtleParser;
+
+    fn graph(turtle: &str) -> Graph {
+        let mut graph = Graph::new();
+        for triple in TurtleParser::new().for_reader(turtle.as_bytes()) {
+            graph.insert(&triple.unwrap());
+        }
+        graph
+    }
+
+    #[test]
+    fn test_diagnostics_find_unsatisfiable_redundant_and_unused() {
+        let ontology = graph(r#"
+            @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+            @prefix owl: <http://www.w3.org/2002/07/owl#> .
+            @prefix ex: <urn:example:> .
+            ex:Container a owl:Class .
+            ex:Pallet a owl:Class ; rdfs:subClassOf ex:Container .
+            ex:Case a owl:Class ; rdfs:subClassOf ex:Container .
+            ex:SmartPallet rdfs:subClassOf ex:Pallet , ex:Container .
+            ex:Pallet owl:disjointWith ex:Case .
+            ex:Hybrid rdfs:subClassOf ex:Pallet , ex:Case .
+            ex:Case rdfs:subClassOf ex:Case .
+            ex:sealedWith a owl:ObjectProperty .
+        "#);
+
+        let diagnostics = OntologyDiagnostics::analyze(&ontology);
+        assert_eq!(diagnostics.unsatisfiable_classes.len(), 1);
+        assert_eq!(diagnostics.unsatisfiable_classes[0].class, "urn:example:Hybrid");
+        let redundant: Vec<(&str, &str)> = diagnostics
+            .redundant_axioms
+            .iter()
+            .map(|axiom| (axiom.sub_class.as_str(), axiom.super_class.as_str()))
+            .collect();
+        assert_eq!(redundant, vec![("urn:example:Case", "urn:example:Case"), ("urn:example:SmartPallet", "urn:example:Container")]);
+        assert!(!diagnostics.usage_checked);
+
+        let mut store = OxigraphStore::new_memory().unwrap();
+        let pallet = oxrdf::Triple::new(
+            oxrdf::NamedNode::new_unchecked("urn:epc:id:sscc:0614141.1234567890"),
+            oxrdf::NamedNode::new_unchecked(RDF_TYPE),
+            oxrdf::NamedNode::new_unchecked("urn:example:SmartPallet"),
+        );
+        store.insert_triples("urn:epcis:event:1", &[pallet]).unwrap();
+        let diagnostics = diagnostics.with_usage(&store, &InferenceScope::from_config(&InferenceScopeConfig::default()));
+        assert_eq!(diagnostics.unused_classes, vec!["urn:example:Case", "urn:example:Hybrid"]);
+        assert_eq!(diagnostics.unused_properties, vec!["urn:example:sealedWith"]);
+    }
+}
@@ -0,0 +1,252 @@
+use chrono::{DateTime, Utc};
+use oxrdf::{Graph as OxrdfGraph, Literal, NamedNode, SubjectRef, TermRef, Triple, TripleRef};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Graph recording the unknown terms reported by drift scans
+pub const SCHEMA_DRIFT_GRAPH: &str = "urn:epcis:inferred:schema_drift";
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+/// Namespaces whose terms are known without being declared in an ontology
+const BUILT_IN_NAMESPACES: [&str; 4] = [
+    "http://www.w3.org/1999/02/22-rdf-syntax-ns#",
+    "http://www.w3.org/2000/01/rdf-schema#",
+    "http://www.w3.org/2002/07/owl#",
+    "http://www.w3.org/2001/XMLSchema#",
+];
+
+/// How an unknown term is used in event data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TermKind {
+    Predicate,
+    Class,
+}
+
+impl TermKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Predicate => "predicate",
+            Self::Class => "class",
+        }
+    }
+}
+
+/// IRIs mentioned by the loaded ontologies
+#[derive(Debug, Clone, Default)]
+pub struct OntologyTerms {
+    terms: HashSet<String>,
+}
+
+impl OntologyTerms {
+    /// Every IRI used in the ontology graph, in any position
+    pub fn from_graph(graph: &OxrdfGraph) -> Self {
+        let mut terms = HashSet::new();
+        for triple in graph.iter() {
+            if let SubjectRef::NamedNode(node) = triple.subject {
+                terms.insert(node.as_str().to_string());
+            }
+            terms.insert(triple.predicate.as_str().to_string());
+            if let TermRef::NamedNode(node) = triple.object {
+                terms.insert(node.as_str().to_string());
+            }
+        }
+        Self { terms }
+    }
+
+    /// True when no ontology is loaded; drift is not checked then
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn is_known(&self, iri: &str) -> bool {
+        self.terms.contains(iri) || BUILT_IN_NAMESPACES.iter().any(|namespace| iri.starts_with(namespace))
+    }
+
+    /// Predicates and classes of `triples` the ontologies do not mention, in first-use order.
+    ///
+    /// `declared` accepts further terms, e.g. the fields of registered extension namespaces.
+    pub fn unknown_terms<'a>(
+        &self,
+        triples: impl IntoIterator<Item = TripleRef<'a>>,
+        declared: impl Fn(&str) -> bool,
+    ) -> Vec<(String, TermKind)> {
+        let mut unknown: Vec<(String, TermKind)> = Vec::new();
+        if self.is_empty() {
+            return unknown;
+        }
+
+        for triple in triples {
+            let mut check = |iri: &str, kind: TermKind| {
+                if !self.is_known(iri) && !declared(iri) && !unknown.iter().any(|(seen, _)| seen == iri) {
+                    unknown.push((iri.to_string(), kind));
+                }
+            };
+            check(triple.predicate.as_str(), TermKind::Predicate);
+            if triple.predicate.as_str() == RDF_TYPE {
+                if let TermRef::NamedNode(class) = triple.object {
+                    check(class.as_str(), TermKind::Class);
+                }
+            }
+        }
+        unknown
+    }
+}
+
+/// Namespace of an IRI: everything up to the last `#`, `/` or `:`
+pub fn namespace_of(iri: &str) -> &str {
+    match iri.rfind(|c| c == '#' || c == '/' || c == ':') {
+        Some(position) => &iri[..=position],
+        None => iri,
+    }
+}
+
+/// A predicate or class used by events but absent from every loaded ontology
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnknownTerm {
+    pub iri: String,
+    pub kind: TermKind,
+    pub namespace: String,
+    /// Events using the term
+    pub events: usize,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub example_event: String,
+}
+
+/// Unknown-term statistics, per term and per namespace
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaDriftReport {
+    pub events_checked: usize,
+    pub events_with_unknown_terms: usize,
+    /// Most used first
+    pub unknown_terms: Vec<UnknownTerm>,
+    /// Unknown terms per namespace; a new namespace often means a partner moved to a newer extension
+    pub namespaces: BTreeMap<String, usize>,
+}
+
+/// Aggregates the unknown terms seen across events
+#[derive(Debug, Clone, Default)]
+pub struct SchemaDriftTracker {
+    events_checked: usize,
+    events_with_unknown_terms: usize,
+    terms: HashMap<String, UnknownTerm>,
+}
+
+impl SchemaDriftTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the unknown terms of one event, returning those never seen before
+    pub fn observe(&mut self, event_id: &str, unknown: &[(String, TermKind)], at: DateTime<Utc>) -> Vec<UnknownTerm> {
+        self.events_checked += 1;
+        if !unknown.is_empty() {
+            self.events_with_unknown_terms += 1;
+        }
+
+        let mut new_terms = Vec::new();
+        for (iri, kind) in unknown {
+            match self.terms.get_mut(iri) {
+                Some(term) => {
+                    term.events += 1;
+                    term.first_seen = term.first_seen.min(at);
+                    term.last_seen = term.last_seen.max(at);
+                }
+                None => {
+                    let term = UnknownTerm {
+                        iri: iri.clone(),
+                        kind: *kind,
+                        namespace: namespace_of(iri).to_string(),
+                        events: 1,
+                        first_seen: at,
+                        last_seen: at,
+                        example_event: event_id.to_string(),
+                    };
+                    new_terms.push(term.clone());
+                    self.terms.insert(iri.clone(), term);
+                }
+            }
+        }
+        new_terms
+    }
+
+    pub fn report(&self) -> SchemaDriftReport {
+        let mut unknown_terms: Vec<UnknownTerm> = self.terms.values().cloned().collect();
+        unknown_terms.sort_by(|a, b| b.events.cmp(&a.events).then_with(|| a.iri.cmp(&b.iri)));
+
+        let mut namespaces = BTreeMap::new();
+        for term in &unknown_terms {
+            *namespaces.entry(term.namespace.clone()).or_insert(0) += 1;
+        }
+
+        SchemaDriftReport {
+            events_checked: self.events_checked,
+            events_with_unknown_terms: self.events_with_unknown_terms,
+            unknown_terms,
+            namespaces,
+        }
+    }
+}
+
+/// Triples recording unknown terms in the schema drift graph
+pub fn unknown_term_triples(terms: &[UnknownTerm]) -> Vec<Triple> {
+    let epcis = |local: &str| NamedNode::new_unchecked(format!("urn:epcglobal:epcis:{}", local));
+    let xsd = |datatype: &str| NamedNode::new_unchecked(format!("http://www.w3.org/2001/XMLSchema#{}", datatype));
+
+    let mut triples = Vec::new();
+    for term in terms {
+        let Ok(node) = NamedNode::new(term.iri.clone()) else { continue };
+        triples.push(Triple::new(node.clone(), NamedNode::new_unchecked(RDF_TYPE), epcis("UnknownTerm")));
+        triples.push(Triple::new(node.clone(), epcis("termKind"), Literal::new_simple_literal(term.kind.as_str())));
+        triples.push(Triple::new(node.clone(), epcis("eventCount"), Literal::new_typed_literal(term.events.to_string(), xsd("integer"))));
+        triples.push(Triple::new(node.clone(), epcis("firstSeen"), Literal::new_typed_literal(term.first_seen.to_rfc3339(), xsd("dateTime"))));
+        triples.push(Triple::new(node, epcis("lastSeen"), Literal::new_typed_literal(term.last_seen.to_rfc3339(), xsd("dateTime"))));
+    }
+    triples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_terms_are_aggregated() {
+        let node = |iri: &str| NamedNode::new_unchecked(iri);
+        let mut ontology = OxrdfGraph::default();
+        ontology.insert(&Triple::new(node("urn:epcglobal:epcis:ObjectEvent"), node(RDF_TYPE), node("http://www.w3.org/2002/07/owl#Class")));
+        ontology.insert(&Triple::new(node("urn:epcglobal:epcis:bizStep"), node(RDF_TYPE), node("http://www.w3.org/2002/07/owl#ObjectProperty")));
+        let known = OntologyTerms::from_graph(&ontology);
+
+        let event = node("urn:epc:event:1");
+        let triples = [
+            Triple::new(event.clone(), node(RDF_TYPE), node("urn:epcglobal:epcis:ObjectEvent")),
+            Triple::new(event.clone(), node(RDF_TYPE), node("https://example.com/epcis3#CarbonEvent")),
+            Triple::new(event.clone(), node("urn:epcglobal:epcis:bizStep"), node("urn:epcglobal:cbv:shipping")),
+            Triple::new(event.clone(), node("https://example.com/epcis3#carbonKg"), node("urn:x:1")),
+            Triple::new(event, node("https://partner.example/ns#declared"), node("urn:x:2")),
+        ];
+        let unknown = known.unknown_terms(triples.iter().map(Triple::as_ref), |iri| iri == "https://partner.example/ns#declared");
+        assert_eq!(
+            unknown,
+            vec![
+                ("https://example.com/epcis3#CarbonEvent".to_string(), TermKind::Class),
+                ("https://example.com/epcis3#carbonKg".to_string(), TermKind::Predicate),
+            ]
+        );
+        assert!(OntologyTerms::default().unknown_terms(triples.iter().map(Triple::as_ref), |_| false).is_empty());
+
+        let mut tracker = SchemaDriftTracker::new();
+        let now = Utc::now();
+        assert_eq!(tracker.observe("1", &unknown, now).len(), 2);
+        assert!(tracker.observe("2", &unknown[1..], now).is_empty());
+        tracker.observe("3", &[], now);
+
+        let report = tracker.report();
+        assert_eq!((report.events_checked, report.events_with_unknown_terms), (3, 2));
+        assert_eq!(report.unknown_terms[0].iri, "https://example.com/epcis3#carbonKg");
+        assert_eq!(report.unknown_terms[0].events, 2);
+        assert_eq!(report.namespaces["https://example.com/epcis3#"], 2);
+    }
+}
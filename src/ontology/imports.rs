@@ -0,0 +1,190 @@
+use crate::storage::graph_catalog::GraphKind;
+use crate::storage::oxigraph_store::OxigraphStore;
+use oxrdf::{NamedNodeRef, SubjectRef, TermRef};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const OWL_ONTOLOGY: &str = "http://www.w3.org/2002/07/owl#Ontology";
+const OWL_IMPORTS: &str = "http://www.w3.org/2002/07/owl#imports";
+const OWL_VERSION_IRI: &str = "http://www.w3.org/2002/07/owl#versionIRI";
+
+/// An ontology in the import graph
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportNode {
+    /// Ontology IRI; the graph name for ontology graphs that declare no `owl:Ontology`
+    pub id: String,
+    /// Named graph holding the ontology; absent for imports that are not loaded
+    pub graph: Option<String>,
+    pub version_iri: Option<String>,
+    pub triples: usize,
+    /// Triples of the ontology and of everything it imports, directly or not
+    pub closure_triples: usize,
+}
+
+/// `from owl:imports to`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ImportEdge {
+    pub from: String,
+    pub to: String,
+}
+
+/// The `owl:imports` graph of the loaded ontologies, for rendering
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImportGraph {
+    pub nodes: Vec<ImportNode>,
+    pub edges: Vec<ImportEdge>,
+    /// Imported ontologies that are not loaded
+    pub missing: Vec<String>,
+}
+
+impl ImportGraph {
+    /// Import graph of the ontology graphs `visible` lets through
+    pub fn build(store: &OxigraphStore, visible: impl Fn(&str) -> bool) -> Self {
+        let mut nodes: BTreeMap<String, ImportNode> = BTreeMap::new();
+        let mut imports: Vec<(String, String)> = Vec::new();
+
+        for graph_name in store.graph_names().into_iter().filter(|name| GraphKind::of(name) == GraphKind::Ontology && visible(name)) {
+            let graph = store.union_graph(std::slice::from_ref(&graph_name));
+            let declared: Vec<SubjectRef<'_>> = graph
+                .subjects_for_predicate_object(NamedNodeRef::new_unchecked(RDF_TYPE), NamedNodeRef::new_unchecked(OWL_ONTOLOGY))
+                .collect();
+            if declared.is_empty() {
+                nodes.insert(graph_name.clone(), loaded_node(graph_name.clone(), &graph_name, None, graph.len()));
+                continue;
+            }
+            for ontology in declared {
+                let id = match ontology {
+                    SubjectRef::NamedNode(node) => node.as_str().to_string(),
+                    _ => graph_name.clone(),
+                };
+                let version_iri = match graph.object_for_subject_predicate(ontology, NamedNodeRef::new_unchecked(OWL_VERSION_IRI)) {
+                    Some(TermRef::NamedNode(node)) => Some(node.as_str().to_string()),
+                    _ => None,
+                };
+                for target in graph.objects_for_subject_predicate(ontology, NamedNodeRef::new_unchecked(OWL_IMPORTS)) {
+                    if let TermRef::NamedNode(target) = target {
+                        imports.push((id.clone(), target.as_str().to_string()));
+                    }
+                }
+                nodes.insert(id.clone(), loaded_node(id, &graph_name, version_iri, graph.len()));
+            }
+        }
+
+        // Imports may name an ontology by its version IRI
+        let by_version: BTreeMap<String, String> =
+            nodes.values().filter_map(|node| node.version_iri.clone().map(|version| (version, node.id.clone()))).collect();
+        let edges: BTreeSet<ImportEdge> = imports
+            .into_iter()
+            .map(|(from, to)| {
+                let to = if nodes.contains_key(&to) { to } else { by_version.get(&to).cloned().unwrap_or(to) };
+                ImportEdge { from, to }
+            })
+            .collect();
+        let missing: BTreeSet<String> = edges.iter().filter(|edge| !nodes.contains_key(&edge.to)).map(|edge| edge.to.clone()).collect();
+        for id in &missing {
+            nodes.insert(id.clone(), ImportNode { id: id.clone(), graph: None, version_iri: None, triples: 0, closure_triples: 0 });
+        }
+
+        let mut graph = ImportGraph { nodes: Vec::new(), edges: edges.into_iter().collect(), missing: missing.into_iter().collect() };
+        let closure_triples: Vec<usize> = nodes
+            .keys()
+            .map(|id| {
+                // Ontologies sharing a graph count its triples once
+                let graphs: BTreeMap<&str, usize> = graph
+                    .reachable(id)
+                    .iter()
+                    .filter_map(|reached| nodes.get(reached))
+                    .filter_map(|node| node.graph.as_deref().map(|name| (name, node.triples)))
+                    .collect();
+                graphs.values().sum()
+            })
+            .collect();
+        graph.nodes = nodes
+            .into_values()
+            .zip(closure_triples)
+            .map(|(node, closure_triples)| ImportNode { closure_triples, ..node })
+            .collect();
+        graph
+    }
+
+    /// `root` and every ontology it imports, directly or not
+    fn reachable(&self, root: &str) -> BTreeSet<String> {
+        let mut reached = BTreeSet::from([root.to_string()]);
+        let mut queue = VecDeque::from([root.to_string()]);
+        while let Some(current) = queue.pop_front() {
+            for edge in self.edges.iter().filter(|edge| edge.from == current) {
+                if reached.insert(edge.to.clone()) {
+                    queue.push_back(edge.to.clone());
+                }
+            }
+        }
+        reached
+    }
+
+    /// The import closure of one ontology, named by its IRI, version IRI or graph
+    pub fn closure_of(self, root: &str) -> Option<Self> {
+        let root = self
+            .nodes
+            .iter()
+            .find(|node| node.id == root || node.version_iri.as_deref() == Some(root) || node.graph.as_deref() == Some(root))?
+            .id
+            .clone();
+        let reached = self.reachable(&root);
+        Some(ImportGraph {
+            nodes: self.nodes.into_iter().filter(|node| reached.contains(&node.id)).collect(),
+            edges: self.edges.into_iter().filter(|edge| reached.contains(&edge.from)).collect(),
+            missing: self.missing.into_iter().filter(|id| reached.contains(id)).collect(),
+        })
+    }
+}
+
+fn loaded_node(id: String, graph_name: &str, version_iri: Option<String>, triples: usize) -> ImportNode {
+    ImportNode { id, graph: Some(graph_name.to_string()), version_iri, triples, closure_triples: 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triple(subject: &str, predicate: &str, object: &str) -> oxrdf::Triple {
+        oxrdf::Triple::new(
+            oxrdf::NamedNode::new_unchecked(subject),
+            oxrdf::NamedNode::new_unchecked(predicate),
+            oxrdf::NamedNode::new_unchecked(object),
+        )
+    }
+
+    #[test]
+    fn test_import_graph_resolves_versions_and_closures() {
+        let mut store = OxigraphStore::new_memory().unwrap();
+        store
+            .insert_triples("urn:epcis:ontology:cbv", &[
+                triple("https://ref.gs1.org/cbv", RDF_TYPE, OWL_ONTOLOGY),
+                triple("https://ref.gs1.org/cbv", OWL_IMPORTS, "https://ref.gs1.org/epcis/2.0"),
+            ])
+            .unwrap();
+        store
+            .insert_triples("urn:epcis:ontology:epcis2", &[
+                triple("https://ref.gs1.org/epcis", RDF_TYPE, OWL_ONTOLOGY),
+                triple("https://ref.gs1.org/epcis", OWL_VERSION_IRI, "https://ref.gs1.org/epcis/2.0"),
+                triple("https://ref.gs1.org/epcis", OWL_IMPORTS, "http://www.w3.org/2006/time"),
+            ])
+            .unwrap();
+        store.insert_triples("urn:epcis:ontology:local", &[triple("urn:example:A", RDF_TYPE, "http://www.w3.org/2002/07/owl#Class")]).unwrap();
+
+        let graph = ImportGraph::build(&store, |_| true);
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(graph.missing, vec!["http://www.w3.org/2006/time".to_string()]);
+        assert!(graph.edges.contains(&ImportEdge { from: "https://ref.gs1.org/cbv".to_string(), to: "https://ref.gs1.org/epcis".to_string() }));
+        let cbv = graph.nodes.iter().find(|node| node.id == "https://ref.gs1.org/cbv").unwrap();
+        assert_eq!((cbv.triples, cbv.closure_triples), (2, 5));
+
+        let closure = graph.clone().closure_of("urn:epcis:ontology:epcis2").unwrap();
+        assert_eq!(closure.nodes.len(), 2);
+        assert!(graph.closure_of("urn:example:unknown").is_none());
+
+        let hidden = ImportGraph::build(&store, |name| name != "urn:epcis:ontology:epcis2");
+        assert!(hidden.missing.contains(&"https://ref.gs1.org/epcis/2.0".to_string()));
+    }
+}
@@ -5,6 +5,8 @@ use std::fs::File;
 use std::io::Read;
 use oxrdf::{Graph, NamedNodeRef, TermRef};
 use oxttl::TurtleParser;
+use crate::ontology::cache::{file_content_hash, OntologyCache};
+use tracing::{debug, warn};
 
 /// Represents loaded ontology data with parsing statistics
 pub struct OntologyData {
@@ -13,37 +15,85 @@ pub struct OntologyData {
     pub source_file: String,
 }
 
+/// Reader that reports how many bytes each read consumed
+struct ProgressReader<R, F> {
+    inner: R,
+    on_progress: F,
+}
+
+impl<R: Read, F: FnMut(u64)> Read for ProgressReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        (self.on_progress)(read as u64);
+        Ok(read)
+    }
+}
+
 pub struct OntologyLoader {
     config: Config,
+    cache: Option<OntologyCache>,
 }
 
 impl OntologyLoader {
     pub fn new() -> Self {
-        Self {
-            config: Config::default(),
-        }
+        Self::with_config(&Config::default())
     }
     
     pub fn with_config(config: &Config) -> Self {
         Self {
             config: config.clone(),
+            cache: OntologyCache::from_config(&config.ontology_cache),
         }
     }
     
+    /// Use `cache` for parsed ontologies instead of the configured one
+    pub fn with_cache(mut self, cache: Option<OntologyCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+    
     /// Load ontology from a Turtle file
     pub fn load_ontology<P: AsRef<Path>>(&self, path: P) -> Result<OntologyData, EpcisKgError> {
+        self.load_ontology_with_progress(path, |_| {})
+    }
+    
+    /// Load ontology from a Turtle file, streaming it through the parser and
+    /// reporting the number of bytes consumed after each read
+    ///
+    /// Files already parsed (anywhere, under any name) are served from the
+    /// ontology cache when one is enabled.
+    pub fn load_ontology_with_progress<P: AsRef<Path>>(
+        &self,
+        path: P,
+        mut on_progress: impl FnMut(u64),
+    ) -> Result<OntologyData, EpcisKgError> {
         let path = path.as_ref();
         let source_file = path.to_string_lossy().to_string();
         
-        // Open and read the file
-        let mut file = File::open(path)
+        let cached = self
+            .cache
+            .as_ref()
+            .and_then(|cache| file_content_hash(path).ok().map(|content_hash| (cache, content_hash)));
+        if let Some((cache, content_hash)) = &cached {
+            if let Some(ontology_data) = cache.load(content_hash, &source_file) {
+                debug!("Reusing parsed {} from the ontology cache", source_file);
+                on_progress(std::fs::metadata(path).map_or(0, |metadata| metadata.len()));
+                return Ok(ontology_data);
+            }
+        }
+        
+        let file = File::open(path)
             .map_err(|e| EpcisKgError::Ontology(format!("Failed to open ontology file: {}", e)))?;
+        let reader = ProgressReader { inner: std::io::BufReader::new(file), on_progress };
+        let ontology_data = self.parse_turtle_reader(reader, source_file)?;
         
-        let mut content = Vec::new();
-        file.read_to_end(&mut content)
-            .map_err(|e| EpcisKgError::Ontology(format!("Failed to read ontology file: {}", e)))?;
+        if let Some((cache, content_hash)) = &cached {
+            if let Err(e) = cache.save(content_hash, &ontology_data) {
+                warn!("Failed to cache parsed ontology in {}: {}", cache.directory().display(), e);
+            }
+        }
         
-        self.parse_turtle_content(&content, source_file)
+        Ok(ontology_data)
     }
     
     /// Load multiple ontology files
@@ -58,14 +108,13 @@ impl OntologyLoader {
         Ok(results)
     }
     
-    /// Parse Turtle content from bytes
-    fn parse_turtle_content(&self, content: &[u8], source_file: String) -> Result<OntologyData, EpcisKgError> {
+    /// Parse Turtle content from a reader
+    pub fn parse_turtle_reader<R: Read>(&self, reader: R, source_file: String) -> Result<OntologyData, EpcisKgError> {
         let mut graph = Graph::default();
         let mut triples_count = 0;
         
-        // Parse Turtle content using oxttl
-        let parser = TurtleParser::new();
-        let reader = std::io::Cursor::new(content);
+        // Parse Turtle content using oxttl (Turtle-star, so quoted triples are accepted)
+        let parser = TurtleParser::new().with_quoted_triples();
         for triple_result in parser.for_reader(reader) {
             let triple = triple_result
                 .map_err(|e| EpcisKgError::Ontology(format!("Turtle parsing error: {}", e)))?;
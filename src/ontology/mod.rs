@@ -1,2 +1,18 @@
+pub mod cache;
+pub mod classification;
+pub mod correction;
+pub mod diagnostics;
+pub mod drift;
+pub mod imports;
 pub mod loader;
-pub mod reasoner;
\ No newline at end of file
+pub mod progress;
+pub mod reasoner;
+pub mod reasoner_pool;
+pub mod reasoning_cache;
+pub mod rematerialize;
+pub mod rule_usage;
+pub mod runs;
+pub mod scope;
+pub mod skos;
+pub mod vocabulary;
+pub mod warm_start;
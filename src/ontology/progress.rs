@@ -0,0 +1,224 @@
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Phases of a materialization run with the share of the run each accounts for
+pub const MATERIALIZATION_PHASES: &[(&str, f64)] = &[
+    ("consistency", 0.10),
+    ("classification", 0.30),
+    ("realization", 0.20),
+    ("materialization", 0.20),
+    ("rules", 0.20),
+];
+
+/// Phases of a parallel (batched) inference run
+pub const PARALLEL_PHASES: &[(&str, f64)] = &[("materialization", 0.95), ("indexing", 0.05)];
+
+/// Finished jobs kept for status queries; older ones are forgotten first
+const FINISHED_JOBS_KEPT: usize = 100;
+
+/// Where a running inference is, as reported to progress callbacks
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReasoningProgress {
+    pub phase: String,
+    /// 0..100 across all phases
+    pub percent: f64,
+    pub axioms_total: usize,
+    /// Axioms behind the completed share of the run
+    pub axioms_processed: usize,
+    pub triples_materialized: usize,
+    pub elapsed_ms: u64,
+    /// Remaining time extrapolated from progress so far
+    pub eta_ms: Option<u64>,
+}
+
+/// Receives progress updates; called from the reasoning thread
+pub type ProgressCallback = Arc<dyn Fn(&ReasoningProgress) + Send + Sync>;
+
+/// Turns phase-level progress into percentages and ETAs for a callback
+pub struct ProgressReporter {
+    callback: Option<ProgressCallback>,
+    phases: &'static [(&'static str, f64)],
+    started: Instant,
+    axioms_total: usize,
+    triples_materialized: usize,
+}
+
+impl ProgressReporter {
+    pub fn new(callback: Option<ProgressCallback>, phases: &'static [(&'static str, f64)], axioms_total: usize) -> Self {
+        Self {
+            callback,
+            phases,
+            started: Instant::now(),
+            axioms_total,
+            triples_materialized: 0,
+        }
+    }
+
+    pub fn add_triples(&mut self, count: usize) {
+        self.triples_materialized += count;
+    }
+
+    /// Report that `fraction` (0..1) of `phase` is done
+    pub fn report(&self, phase: &str, fraction: f64) {
+        let Some(callback) = &self.callback else { return };
+        let done = overall_fraction(self.phases, phase, fraction);
+        let elapsed_ms = self.started.elapsed().as_millis() as u64;
+        callback(&ReasoningProgress {
+            phase: phase.to_string(),
+            percent: done * 100.0,
+            axioms_total: self.axioms_total,
+            axioms_processed: (self.axioms_total as f64 * done).round() as usize,
+            triples_materialized: self.triples_materialized,
+            elapsed_ms,
+            eta_ms: estimate_remaining_ms(done, elapsed_ms),
+        });
+    }
+}
+
+/// Share of the whole run done once `fraction` of `phase` is; unknown phases count as done
+pub fn overall_fraction(phases: &[(&str, f64)], phase: &str, fraction: f64) -> f64 {
+    let mut done = 0.0;
+    for &(name, share) in phases {
+        if name == phase {
+            return (done + share * fraction.clamp(0.0, 1.0)).min(1.0);
+        }
+        done += share;
+    }
+    1.0
+}
+
+/// Time left if the rest of the run goes at the pace so far
+pub fn estimate_remaining_ms(done: f64, elapsed_ms: u64) -> Option<u64> {
+    (done > 0.0).then(|| (elapsed_ms as f64 * (1.0 - done) / done).round() as u64)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+/// A background inference run started through the API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReasoningJob {
+    pub id: String,
+    pub status: JobStatus,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub progress: ReasoningProgress,
+    /// Response of the run once completed
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+/// Background inference jobs and their latest progress
+#[derive(Default)]
+pub struct ReasoningJobs {
+    jobs: Mutex<HashMap<String, ReasoningJob>>,
+}
+
+impl ReasoningJobs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a running job and return its ID
+    pub fn start(&self) -> String {
+        let id = uuid::Uuid::new_v4().to_string();
+        let mut jobs = self.jobs.lock();
+        forget_oldest_finished(&mut jobs);
+        jobs.insert(
+            id.clone(),
+            ReasoningJob {
+                id: id.clone(),
+                status: JobStatus::Running,
+                started_at: Utc::now(),
+                finished_at: None,
+                progress: ReasoningProgress::default(),
+                result: None,
+                error: None,
+            },
+        );
+        id
+    }
+
+    /// Callback recording progress on a job
+    pub fn progress_callback(self: &Arc<Self>, id: &str) -> ProgressCallback {
+        let jobs = Arc::clone(self);
+        let id = id.to_string();
+        Arc::new(move |progress: &ReasoningProgress| {
+            if let Some(job) = jobs.jobs.lock().get_mut(&id) {
+                job.progress = progress.clone();
+            }
+        })
+    }
+
+    pub fn finish(&self, id: &str, outcome: Result<serde_json::Value, String>) {
+        if let Some(job) = self.jobs.lock().get_mut(id) {
+            job.finished_at = Some(Utc::now());
+            match outcome {
+                Ok(result) => {
+                    job.status = JobStatus::Completed;
+                    job.progress.percent = 100.0;
+                    job.progress.eta_ms = Some(0);
+                    job.result = Some(result);
+                }
+                Err(error) => {
+                    job.status = JobStatus::Failed;
+                    job.progress.eta_ms = None;
+                    job.error = Some(error);
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<ReasoningJob> {
+        self.jobs.lock().get(id).cloned()
+    }
+}
+
+fn forget_oldest_finished(jobs: &mut HashMap<String, ReasoningJob>) {
+    let mut finished: Vec<(DateTime<Utc>, String)> = jobs
+        .values()
+        .filter_map(|job| job.finished_at.map(|at| (at, job.id.clone())))
+        .collect();
+    if finished.len() < FINISHED_JOBS_KEPT {
+        return;
+    }
+    finished.sort();
+    for (_, id) in finished.iter().take(finished.len() + 1 - FINISHED_JOBS_KEPT) {
+        jobs.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_spans_phases_and_estimates_remaining_time() {
+        assert_eq!(overall_fraction(MATERIALIZATION_PHASES, "consistency", 0.0), 0.0);
+        assert!((overall_fraction(MATERIALIZATION_PHASES, "classification", 0.5) - 0.25).abs() < 1e-9);
+        assert!((overall_fraction(MATERIALIZATION_PHASES, "rules", 1.0) - 1.0).abs() < 1e-9);
+        assert_eq!(estimate_remaining_ms(0.25, 1_000), Some(3_000));
+        assert_eq!(estimate_remaining_ms(0.0, 1_000), None);
+
+        let jobs = Arc::new(ReasoningJobs::new());
+        let id = jobs.start();
+        let reporter = ProgressReporter::new(Some(jobs.progress_callback(&id)), MATERIALIZATION_PHASES, 200);
+        reporter.report("realization", 1.0);
+        let job = jobs.get(&id).unwrap();
+        assert_eq!(job.status, JobStatus::Running);
+        assert_eq!(job.progress.phase, "realization");
+        assert_eq!(job.progress.axioms_processed, 120);
+
+        jobs.finish(&id, Ok(serde_json::json!({ "success": true })));
+        assert_eq!(jobs.get(&id).unwrap().status, JobStatus::Completed);
+    }
+}
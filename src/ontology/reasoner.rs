@@ -1,85 +1,200 @@
 use crate::EpcisKgError;
 use crate::Config;
 use crate::storage::oxigraph_store::OxigraphStore;
+use crate::ontology::diagnostics::OntologyDiagnostics;
 use crate::ontology::loader::OntologyData;
+use crate::ontology::progress::{ProgressCallback, ProgressReporter, MATERIALIZATION_PHASES, PARALLEL_PHASES};
+use crate::ontology::reasoning_cache::{CacheTierStats, ReasoningCaches};
+use crate::ontology::rule_usage::{self, AxiomKey, RuleUsage, RuleUsageReport};
+use crate::ontology::scope::{InferenceScope, ScopeSummary};
+use crate::ontology::warm_start::{ontology_content_hash, ReasonerCache, ReasonerSnapshot};
+use crate::utils::batching::{self, AdaptiveBatchSizer, BatchDecision};
 use owl2_rs::{api, Ontology, IRI, Class, ObjectProperty, Individual};
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 use rayon::prelude::*;
+use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Instant;
 
+/// Namespace of the inference provenance annotation predicates
+pub const PROVENANCE_NS: &str = "urn:epcglobal:epcis:provenance:";
+
+/// Named graph holding RDF-star provenance annotations of inferred triples
+pub const PROVENANCE_GRAPH: &str = "urn:epcis:inferred:provenance";
+
+/// Graphs that materialization writes inferred triples into
+pub const MATERIALIZED_GRAPHS: [&str; 3] = ["urn:epcis:inferred", "urn:epcis:sparql_inferred", "urn:epcis:inferred:parallel"];
+
 pub struct OntologyReasoner {
     config: Config,
     store: Option<OxigraphStore>,
     owl_ontology: Option<Ontology>,
     owl_reasoner: Option<api::Reasoner>,
-    reasoning_cache: HashMap<String, Vec<String>>,
+    caches: ReasoningCaches,
     materialized_triples: HashMap<String, Vec<oxrdf::Triple>>,
     inference_stats: InferenceStats,
     materialization_strategy: MaterializationStrategy,
     
     // Performance optimization fields
     parallel_processing: bool,
-    cache_size_limit: usize,
     performance_metrics: PerformanceMetrics,
     index_structures: IndexStructures,
-    batch_size: usize,
+    batch_sizer: AdaptiveBatchSizer,
+    
+    // Warm-start state
+    class_hierarchy: HashMap<String, Vec<String>>,
+    warm_start_cache: Option<ReasonerCache>,
+    
+    // TBox shared with other pooled reasoners; loaded data is treated as ABox
+    shared_tbox: Option<Arc<Ontology>>,
+    
+    // Named graphs selected by `load_scope`; `None` means every graph
+    scope_graphs: Option<Vec<String>>,
+    
+    // Receives progress of long inference runs
+    progress_callback: Option<ProgressCallback>,
+    
+    // Rule and axiom firing counts, possibly shared across reasoners
+    rule_usage: Arc<RuleUsage>,
 }
 
 impl OntologyReasoner {
     pub fn new() -> Self {
         Self {
+            batch_sizer: AdaptiveBatchSizer::new(Config::default().batching.inference),
+            caches: ReasoningCaches::default(),
             config: Config::default(),
             store: None,
             owl_ontology: None,
             owl_reasoner: None,
-            reasoning_cache: HashMap::new(),
             materialized_triples: HashMap::new(),
             inference_stats: InferenceStats::default(),
             materialization_strategy: MaterializationStrategy::Incremental,
             parallel_processing: true,
-            cache_size_limit: 10000,
             performance_metrics: PerformanceMetrics::default(),
             index_structures: IndexStructures::new(),
-            batch_size: 1000,
+            class_hierarchy: HashMap::new(),
+            warm_start_cache: None,
+            shared_tbox: None,
+            scope_graphs: None,
+            progress_callback: None,
+            rule_usage: Arc::new(RuleUsage::new()),
         }
     }
     
     pub fn with_store(store: OxigraphStore) -> Self {
         Self {
+            batch_sizer: AdaptiveBatchSizer::new(Config::default().batching.inference),
+            caches: ReasoningCaches::default(),
             config: Config::default(),
             store: Some(store),
             owl_ontology: None,
             owl_reasoner: None,
-            reasoning_cache: HashMap::new(),
             materialized_triples: HashMap::new(),
             inference_stats: InferenceStats::default(),
             materialization_strategy: MaterializationStrategy::Incremental,
             parallel_processing: true,
-            cache_size_limit: 10000,
             performance_metrics: PerformanceMetrics::default(),
             index_structures: IndexStructures::new(),
-            batch_size: 1000,
+            class_hierarchy: HashMap::new(),
+            warm_start_cache: None,
+            shared_tbox: None,
+            scope_graphs: None,
+            progress_callback: None,
+            rule_usage: Arc::new(RuleUsage::new()),
         }
     }
     
     pub fn with_config(config: &Config) -> Self {
         Self {
+            batch_sizer: AdaptiveBatchSizer::new(config.batching.inference.clone()),
+            caches: ReasoningCaches::from_config(&config.reasoning_cache),
             config: config.clone(),
             store: None,
             owl_ontology: None,
             owl_reasoner: None,
-            reasoning_cache: HashMap::new(),
             materialized_triples: HashMap::new(),
             inference_stats: InferenceStats::default(),
             materialization_strategy: MaterializationStrategy::Incremental,
             parallel_processing: true,
-            cache_size_limit: 10000,
             performance_metrics: PerformanceMetrics::default(),
             index_structures: IndexStructures::new(),
-            batch_size: 1000,
+            class_hierarchy: HashMap::new(),
+            warm_start_cache: None,
+            shared_tbox: None,
+            scope_graphs: None,
+            progress_callback: None,
+            rule_usage: Arc::new(RuleUsage::new()),
+        }
+    }
+}
+
+impl OntologyReasoner {
+    /// Create a reasoner over a TBox shared with other reasoners (see `ReasonerPool`)
+    pub fn with_shared_tbox(tbox: Arc<Ontology>) -> Self {
+        let mut reasoner = Self::new();
+        reasoner.shared_tbox = Some(tbox);
+        reasoner
+    }
+    
+    /// Reasoner with the loaded configuration over a store
+    pub fn with_config_and_store(config: &Config, store: OxigraphStore) -> Self {
+        let mut reasoner = Self::with_config(config);
+        reasoner.store = Some(store);
+        reasoner
+    }
+    
+    /// Reasoner with the loaded configuration over a TBox shared with other reasoners
+    pub fn with_config_and_tbox(config: &Config, tbox: Arc<Ontology>) -> Self {
+        let mut reasoner = Self::with_config(config);
+        reasoner.shared_tbox = Some(tbox);
+        reasoner
+    }
+    
+    /// Load reasoning input from the store's named graphs selected by a scope.
+    ///
+    /// TBox graphs become the reasoner's TBox and ABox graphs its data; rule-based
+    /// (SPARQL) inference then only sees the graphs in the scope. A reasoner
+    /// without a store keeps an in-memory view of the scoped graphs.
+    pub fn load_scope(&mut self, store: &OxigraphStore, scope: &InferenceScope) -> Result<ScopeSummary, EpcisKgError> {
+        let (tbox_graphs, abox_graphs) = scope.partition(&store.graph_names());
+        
+        let tbox = store.ontology_data(&tbox_graphs, "scope:tbox");
+        let abox = store.ontology_data(&abox_graphs, "scope:abox");
+        
+        self.shared_tbox = Some(Arc::new(self.convert_rdf_to_owl(&tbox)?));
+        let abox_ontology = self.convert_rdf_to_owl(&abox)?;
+        self.install_ontology(abox_ontology);
+        self.caches.invalidate_ontology();
+        
+        let mut scoped_graphs = tbox_graphs.clone();
+        scoped_graphs.extend(abox_graphs.iter().cloned());
+        if self.store.is_none() {
+            self.store = Some(store.scoped_view(&scoped_graphs));
         }
+        self.scope_graphs = Some(scoped_graphs);
+        
+        Ok(ScopeSummary {
+            tbox_triples: tbox.triples_count,
+            abox_triples: abox.triples_count,
+            tbox_graphs,
+            abox_graphs,
+        })
+    }
+    
+    /// Drop per-request ABox state, keeping configuration and the shared TBox
+    pub fn reset_working_state(&mut self) {
+        self.owl_ontology = None;
+        self.owl_reasoner = None;
+        self.caches.invalidate_ontology();
+        self.materialized_triples.clear();
+        self.index_structures = IndexStructures::new();
+        self.class_hierarchy.clear();
+        self.inference_stats = InferenceStats {
+            strategy: self.materialization_strategy.clone(),
+            ..InferenceStats::default()
+        };
     }
 }
 
@@ -91,36 +206,160 @@ impl Clone for OntologyReasoner {
             store: self.store.clone(),
             owl_ontology: self.owl_ontology.clone(),
             owl_reasoner: None,
-            reasoning_cache: self.reasoning_cache.clone(),
+            caches: self.caches.clone(),
             materialized_triples: self.materialized_triples.clone(),
             inference_stats: self.inference_stats.clone(),
             materialization_strategy: self.materialization_strategy.clone(),
             parallel_processing: self.parallel_processing,
-            cache_size_limit: self.cache_size_limit,
             performance_metrics: self.performance_metrics.clone(),
             index_structures: self.index_structures.clone(),
-            batch_size: self.batch_size,
+            batch_sizer: self.batch_sizer.clone(),
+            class_hierarchy: self.class_hierarchy.clone(),
+            warm_start_cache: self.warm_start_cache.clone(),
+            shared_tbox: self.shared_tbox.clone(),
+            scope_graphs: self.scope_graphs.clone(),
+            progress_callback: self.progress_callback.clone(),
+            rule_usage: Arc::clone(&self.rule_usage),
         }
     }
 }
 
 impl OntologyReasoner {
     
+    /// Persist and reuse converted reasoner state in the given directory
+    pub fn enable_warm_start<P: AsRef<std::path::Path>>(&mut self, cache_dir: P) {
+        self.warm_start_cache = Some(ReasonerCache::new(cache_dir));
+    }
+    
     /// Load ontology data into the OWL 2 reasoner
     pub fn load_ontology_data(&mut self, ontology_data: &OntologyData) -> Result<(), EpcisKgError> {
-        // Convert RDF graph to OWL 2 ontology
+        self.load_ontology_data_warm(ontology_data).map(|_| ())
+    }
+    
+    /// Load ontology data, reusing a warm-start snapshot when the ontology is unchanged.
+    ///
+    /// Returns `true` when the converted ontology, class hierarchy and indexes
+    /// were restored from the snapshot instead of being rebuilt. Either way the
+    /// class hierarchy and indexes are merged with those of the ontologies loaded
+    /// before, so loading several files keeps all of them.
+    pub fn load_ontology_data_warm(&mut self, ontology_data: &OntologyData) -> Result<bool, EpcisKgError> {
+        let cache = match self.warm_start_cache.clone() {
+            Some(cache) => cache,
+            None => {
+                let owl_ontology = self.convert_rdf_to_owl(ontology_data)?;
+                self.install_ontology(owl_ontology);
+                return Ok(false);
+            }
+        };
+        
+        let content_hash = ontology_content_hash(ontology_data);
+        if let Some(snapshot) = cache.load(&content_hash) {
+            self.merge_class_hierarchy(&snapshot.class_hierarchy);
+            self.index_structures.merge(&snapshot.indexes);
+            self.install_ontology(snapshot.to_ontology());
+            return Ok(true);
+        }
+        
+        // Cold start: convert, build indexes over the ontology and snapshot the result
         let owl_ontology = self.convert_rdf_to_owl(ontology_data)?;
-        self.owl_ontology = Some(owl_ontology.clone());
+        let triples: Vec<oxrdf::Triple> = ontology_data.graph.iter().map(|t| t.into_owned()).collect();
+        let mut indexes = IndexStructures::new();
+        indexes.build_indexes(&triples);
+        self.index_structures.merge(&indexes);
+        
+        // The snapshot holds this ontology alone, so it can be merged into any later load
+        if let Some(snapshot) = ReasonerSnapshot::capture(
+            content_hash,
+            &ontology_data.source_file,
+            &owl_ontology,
+            &indexes,
+            self.config.reasoning.parallel_classification_min_classes,
+        ) {
+            self.merge_class_hierarchy(&snapshot.class_hierarchy);
+            if let Err(e) = cache.save(&snapshot) {
+                tracing::warn!("Failed to save reasoner snapshot for {}: {}", ontology_data.source_file, e);
+            }
+        }
+        
+        self.install_ontology(owl_ontology);
+        Ok(false)
+    }
+    
+    fn install_ontology(&mut self, owl_ontology: Ontology) {
+        // With a shared TBox the loaded axioms are ABox data reasoned against it
+        if self.shared_tbox.is_some() {
+            self.caches.invalidate_data();
+        } else {
+            self.caches.invalidate_ontology();
+        }
+        let reasoning_ontology = match self.shared_tbox {
+            Some(ref tbox) => {
+                let mut combined = Ontology::default();
+                combined.axioms.extend(tbox.axioms.iter().cloned());
+                combined.axioms.extend(owl_ontology.axioms.iter().cloned());
+                combined
+            }
+            None => owl_ontology.clone(),
+        };
+        self.owl_ontology = Some(owl_ontology);
         
         // Create OWL 2 reasoner
-        let reasoner = api::Reasoner::new(owl_ontology);
+        let reasoner = api::Reasoner::new(reasoning_ontology);
         self.owl_reasoner = Some(reasoner);
+    }
+    
+    /// Add an ontology's class hierarchy to the one loaded so far, closing it
+    /// over both: a superclass from one ontology may have its own in another
+    fn merge_class_hierarchy(&mut self, hierarchy: &HashMap<String, Vec<String>>) {
+        for (class, superclasses) in hierarchy {
+            let known = self.class_hierarchy.entry(class.clone()).or_default();
+            for superclass in superclasses {
+                if !known.contains(superclass) {
+                    known.push(superclass.clone());
+                }
+            }
+        }
         
-        Ok(())
+        let classes: Vec<String> = self.class_hierarchy.keys().cloned().collect();
+        let mut grew = true;
+        while grew {
+            grew = false;
+            for class in &classes {
+                let inherited: Vec<String> = self.class_hierarchy[class]
+                    .iter()
+                    .filter_map(|superclass| self.class_hierarchy.get(superclass))
+                    .flatten()
+                    .filter(|inherited| *inherited != class && !self.class_hierarchy[class].contains(*inherited))
+                    .cloned()
+                    .collect();
+                let Some(known) = self.class_hierarchy.get_mut(class) else { continue };
+                for superclass in inherited {
+                    if !known.contains(&superclass) {
+                        known.push(superclass);
+                        grew = true;
+                    }
+                }
+            }
+        }
+    }
+    
+    /// Transitive superclasses of a class, as captured at ontology load
+    pub fn superclasses_of(&self, class: &str) -> &[String] {
+        self.class_hierarchy.get(class).map(|supers| supers.as_slice()).unwrap_or(&[])
+    }
+    
+    /// Quality diagnostics for an ontology; unused classes and properties are
+    /// checked against the scope's ABox graphs in the reasoner's store, when it has one
+    pub fn diagnostics(&self, ontology_data: &OntologyData, scope: &InferenceScope) -> OntologyDiagnostics {
+        let diagnostics = OntologyDiagnostics::analyze(&ontology_data.graph);
+        match &self.store {
+            Some(store) => diagnostics.with_usage(store, scope),
+            None => diagnostics,
+        }
     }
     
     /// Convert RDF graph data to OWL 2 ontology format
-    fn convert_rdf_to_owl(&self, ontology_data: &OntologyData) -> Result<Ontology, EpcisKgError> {
+    pub(crate) fn convert_rdf_to_owl(&self, ontology_data: &OntologyData) -> Result<Ontology, EpcisKgError> {
         let mut owl_ontology = Ontology::default();
         
         // Track processed entities to avoid duplicates
@@ -297,7 +536,7 @@ impl OntologyReasoner {
         
         // Cache results
         let cache_key = format!("inference_{}", std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs());
-        self.reasoning_cache.insert(cache_key, inferred_triples.clone());
+        self.caches.queries.insert(cache_key, inferred_triples.clone());
         
         Ok(inferred_triples)
     }
@@ -710,33 +949,47 @@ impl OntologyReasoner {
         }
     }
 
+    /// Receive progress (phase, percent, ETA) of later inference runs
+    pub fn set_progress_callback(&mut self, callback: Option<ProgressCallback>) {
+        self.progress_callback = callback;
+    }
+    
     /// Enhanced inference with materialization support
     pub fn perform_inference_with_materialization(&mut self) -> Result<InferenceResult, EpcisKgError> {
         let start_time = std::time::Instant::now();
         let mut inference_result = InferenceResult::default();
+        let axioms_total = self.owl_ontology.as_ref().map_or(0, |ontology| ontology.axioms.len());
+        let mut progress = ProgressReporter::new(self.progress_callback.clone(), MATERIALIZATION_PHASES, axioms_total);
+        progress.report("consistency", 0.0);
         
         // Update stats
         self.inference_stats.total_inferences += 1;
+        self.declare_hierarchy_axioms();
         
         // Use OWL 2 reasoner if available
         if let Some(ref mut reasoner) = self.owl_reasoner {
             // Check consistency first
             let is_consistent = reasoner.is_consistent();
             inference_result.consistent = is_consistent;
+            progress.report("consistency", 1.0);
             
             if is_consistent {
                 // Perform classification (compute class hierarchy)
                 let class_hierarchy = reasoner.classify();
                 inference_result.classification_performed = true;
+                progress.report("classification", 1.0);
                 
                 // Realize individuals (find their types)
                 let individual_types = reasoner.realize();
                 inference_result.realization_performed = true;
                 inference_result.individuals_classified = individual_types.len();
+                progress.report("realization", 1.0);
                 
                 // Materialize inferred triples
                 let materialized = self.materialize_inferences(&class_hierarchy, &individual_types)?;
                 inference_result.materialized_triples = materialized.len();
+                progress.add_triples(materialized.len());
+                progress.report("materialization", 1.0);
                 
                 // Store materialized triples by graph
                 let graph_name = "urn:epcis:inferred";
@@ -746,13 +999,17 @@ impl OntologyReasoner {
         
         // Fall back to basic SPARQL-based inference if OWL 2 reasoner not available
         if let Some(ref store) = self.store {
-            let sparql_inferences = self.perform_sparql_inference_with_materialization(store)?;
+            // Rule-based inference only sees the graphs in the inference scope
+            let scoped_store = self.scope_graphs.as_ref().map(|graphs| store.scoped_view(graphs));
+            let sparql_inferences = self.perform_sparql_inference_with_materialization(scoped_store.as_ref().unwrap_or(store))?;
             inference_result.sparql_inferences = sparql_inferences.len();
+            progress.add_triples(sparql_inferences.len());
             
             // Add SPARQL inferences to materialized triples
             let sparql_graph_name = "urn:epcis:sparql_inferred";
             self.materialized_triples.insert(sparql_graph_name.to_string(), sparql_inferences);
         }
+        progress.report("rules", 1.0);
         
         // Update performance stats
         inference_result.processing_time_ms = start_time.elapsed().as_millis() as u64;
@@ -761,7 +1018,7 @@ impl OntologyReasoner {
         
         // Cache results
         let cache_key = format!("inference_{}", std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs());
-        self.reasoning_cache.insert(cache_key, vec![format!("Inference completed in {}ms", inference_result.processing_time_ms)]);
+        self.caches.queries.insert(cache_key, vec![format!("Inference completed in {}ms", inference_result.processing_time_ms)]);
         
         Ok(inference_result)
     }
@@ -830,6 +1087,7 @@ impl OntologyReasoner {
                     object,
                 );
                 triples.push(type_triple);
+                self.rule_usage.record(rule_usage::OWL_REALIZATION, &[]);
             }
         }
         
@@ -858,7 +1116,7 @@ impl OntologyReasoner {
         
         // Infer transitive subclass relationships
         let transitive_subclass_query = r#"
-            SELECT ?subclass ?superclass
+            SELECT ?subclass ?intermediate ?superclass
             WHERE {
                 ?subclass <http://www.w3.org/2000/01/rdf-schema#subClassOf> ?intermediate .
                 ?intermediate <http://www.w3.org/2000/01/rdf-schema#subClassOf> ?superclass .
@@ -885,6 +1143,12 @@ impl OntologyReasoner {
                                     oxrdf::NamedNode::new(super_str)?,
                                 );
                                 inferred_triples.push(triple);
+                                
+                                let intermediate = binding.get("intermediate").and_then(|s| s.get("value")).and_then(|v| v.as_str()).unwrap_or_default();
+                                self.rule_usage.record(rule_usage::RDFS11, &[
+                                    AxiomKey::sub_class_of(sub_str, intermediate),
+                                    AxiomKey::sub_class_of(intermediate, super_str),
+                                ]);
                             }
                         }
                     }
@@ -894,7 +1158,7 @@ impl OntologyReasoner {
         
         // Infer type hierarchy relationships
         let type_hierarchy_query = r#"
-            SELECT ?instance ?superclass
+            SELECT ?instance ?subclass ?superclass
             WHERE {
                 ?instance a ?subclass .
                 ?subclass <http://www.w3.org/2000/01/rdf-schema#subClassOf> ?superclass .
@@ -921,6 +1185,9 @@ impl OntologyReasoner {
                                     oxrdf::NamedNode::new(super_str)?,
                                 );
                                 inferred_triples.push(triple);
+                                
+                                let subclass = binding.get("subclass").and_then(|s| s.get("value")).and_then(|v| v.as_str()).unwrap_or_default();
+                                self.rule_usage.record(rule_usage::RDFS9, &[AxiomKey::sub_class_of(subclass, super_str)]);
                             }
                         }
                     }
@@ -939,6 +1206,9 @@ impl OntologyReasoner {
         // Update stats
         self.inference_stats.incremental_inferences += 1;
         
+        // New data makes earlier run results stale; pattern and closure lookups still hold
+        self.caches.invalidate_data();
+        
         // For incremental inference, we only process the new triples
         // In a real implementation, you'd track dependencies and only recompute affected inferences
         
@@ -1033,6 +1303,29 @@ impl OntologyReasoner {
         self.inference_stats.clone()
     }
 
+    /// Record rule and axiom firings into `usage`, e.g. one kept across runs
+    pub fn set_rule_usage(&mut self, usage: Arc<RuleUsage>) {
+        self.rule_usage = usage;
+    }
+
+    /// Hot rules, the `top` most-fired axioms and the never-fired axioms of the
+    /// ontologies reasoned over so far
+    pub fn rule_usage_report(&self, top: usize) -> RuleUsageReport {
+        self.rule_usage.report(top)
+    }
+
+    /// Declare the loaded hierarchy axioms so those that never fire are reported
+    fn declare_hierarchy_axioms(&self) {
+        for ontology in self.shared_tbox.as_deref().into_iter().chain(self.owl_ontology.as_ref()) {
+            self.rule_usage.declare(rule_usage::hierarchy_axioms(ontology));
+        }
+    }
+
+    /// Get the loaded OWL ontology (excluding any shared TBox)
+    pub fn get_owl_ontology(&self) -> Option<&Ontology> {
+        self.owl_ontology.as_ref()
+    }
+
     /// Get materialized triples
     pub fn get_materialized_triples(&self) -> &HashMap<String, Vec<oxrdf::Triple>> {
         &self.materialized_triples
@@ -1043,13 +1336,82 @@ impl OntologyReasoner {
         self.materialized_triples.get(graph_name)
     }
 
+    /// RDF-star provenance annotations for the materialized triples.
+    ///
+    /// Each inferred statement is quoted and annotated with the rule source that
+    /// produced it, the graph it was materialized into and the inference time:
+    /// `<< s p o >> epcis:inferredBy "owl2_rs" ; epcis:inferredIn <graph> ; epcis:inferredAt "..."^^xsd:dateTime`
+    pub fn provenance_annotations(&self) -> Vec<oxrdf::Triple> {
+        let inferred_by = oxrdf::NamedNode::new_unchecked(format!("{}inferredBy", PROVENANCE_NS));
+        let inferred_in = oxrdf::NamedNode::new_unchecked(format!("{}inferredIn", PROVENANCE_NS));
+        let inferred_at = oxrdf::NamedNode::new_unchecked(format!("{}inferredAt", PROVENANCE_NS));
+
+        let timestamp: chrono::DateTime<chrono::Utc> = self
+            .inference_stats
+            .last_inference_time
+            .map(chrono::DateTime::from)
+            .unwrap_or_else(chrono::Utc::now);
+        let timestamp = oxrdf::Literal::new_typed_literal(
+            timestamp.to_rfc3339(),
+            oxrdf::NamedNode::new_unchecked("http://www.w3.org/2001/XMLSchema#dateTime"),
+        );
+
+        let mut annotations = Vec::new();
+        for (graph_name, triples) in &self.materialized_triples {
+            let source = match graph_name.as_str() {
+                "urn:epcis:sparql_inferred" => "sparql_rules",
+                "urn:epcis:inferred:parallel" => "parallel_rules",
+                _ => "owl2_rs",
+            };
+
+            for triple in triples {
+                let quoted = oxrdf::Subject::Triple(Box::new(triple.clone()));
+                annotations.push(oxrdf::Triple::new(quoted.clone(), inferred_by.clone(), oxrdf::Literal::new_simple_literal(source)));
+                annotations.push(oxrdf::Triple::new(quoted.clone(), inferred_in.clone(), oxrdf::NamedNode::new_unchecked(graph_name.as_str())));
+                annotations.push(oxrdf::Triple::new(quoted, inferred_at.clone(), timestamp.clone()));
+            }
+        }
+
+        annotations
+    }
+
+    /// The store the reasoner persists into, if it has one
+    pub fn store_mut(&mut self) -> Option<&mut OxigraphStore> {
+        self.store.as_mut()
+    }
+
+    /// Write materialized triples and their provenance annotations into the store
+    pub fn persist_materialized_with_provenance(&mut self) -> Result<usize, EpcisKgError> {
+        let annotations = self.provenance_annotations();
+        let store = self
+            .store
+            .as_mut()
+            .ok_or_else(|| EpcisKgError::Storage("Reasoner has no store to persist into".to_string()))?;
+
+        let mut persisted = 0;
+        for (graph_name, triples) in &self.materialized_triples {
+            persisted += store.insert_triples(graph_name, triples)?;
+        }
+        store.insert_triples(PROVENANCE_GRAPH, &annotations)?;
+
+        Ok(persisted)
+    }
+
     // ===== PERFORMANCE OPTIMIZATION METHODS =====
 
-    /// Configure performance settings
+    /// Configure performance settings, pinning the batch size
     pub fn configure_performance(&mut self, parallel: bool, cache_limit: usize, batch_size: usize) {
+        self.configure_adaptive_performance(parallel, cache_limit);
+        self.batch_sizer.set_override(Some(batch_size));
+    }
+
+    /// Configure performance settings, leaving the batch size to the adaptive controller.
+    ///
+    /// `cache_limit` sizes the triple pattern cache tier.
+    pub fn configure_adaptive_performance(&mut self, parallel: bool, cache_limit: usize) {
         self.parallel_processing = parallel;
-        self.cache_size_limit = cache_limit;
-        self.batch_size = batch_size;
+        self.caches.patterns.set_capacity(cache_limit);
+        self.batch_sizer.set_override(None);
     }
 
     /// Get current performance metrics
@@ -1066,11 +1428,9 @@ impl OntologyReasoner {
             .flat_map(|triples| triples.clone())
             .collect();
         
-        // Rebuild indexes in parallel
+        // Rebuild indexes in parallel; lookups cached through the old ones are stale
         self.index_structures.build_indexes(&all_triples);
-        
-        // Optimize cache size
-        self.optimize_cache_size();
+        self.caches.invalidate_indexes();
         
         // Record optimization
         let duration = start_time.elapsed();
@@ -1079,8 +1439,8 @@ impl OntologyReasoner {
         
         println!("✓ Performance optimization completed in {}ms", duration.as_millis());
         println!("  - Indexed {} triples", all_triples.len());
-        println!("  - Cache size limit: {}", self.cache_size_limit);
-        println!("  - Batch size: {}", self.batch_size);
+        println!("  - Cache size limit: {}", self.get_cache_size_limit());
+        println!("  - Batch size: {}", self.batch_size_description());
         println!("  - Parallel processing: {}", self.parallel_processing);
         
         Ok(())
@@ -1093,6 +1453,8 @@ impl OntologyReasoner {
         }
         
         let start_time = Instant::now();
+        let axioms_total = self.owl_ontology.as_ref().map_or(0, |ontology| ontology.axioms.len());
+        let mut progress = ProgressReporter::new(self.progress_callback.clone(), PARALLEL_PHASES, axioms_total);
         
         // Clear existing materialized triples for fresh inference
         self.materialized_triples.clear();
@@ -1108,26 +1470,31 @@ impl OntologyReasoner {
                 })
                 .unwrap_or_default();
             
-            // Process triples in parallel batches
-            let batch_size = self.batch_size.max(1);
-            let batches: Vec<_> = all_triples.chunks(batch_size).collect();
-            
-            // Process batches in parallel without mutable reference issues
-            let inferred_results: Vec<Result<Vec<oxrdf::Triple>, EpcisKgError>> = batches
-                .par_iter()
-                .map(|batch| self.process_batch_parallel_readonly(batch))
-                .collect::<Vec<_>>();
-            
-            // Collect results and update cache
-            for (batch, result) in batches.iter().zip(inferred_results) {
-                match result {
-                    Ok(triples) => {
-                        all_inferred_triples.extend(triples.clone());
-                        // Update cache for this batch
-                        self.update_cache_for_batch(batch, &triples);
-                    },
-                    Err(e) => return Err(e),
+            // Process triples in batches sized by the adaptive controller,
+            // each batch split across the thread pool
+            let mut remaining: &[oxrdf::Triple] = &all_triples;
+            while !remaining.is_empty() {
+                let batch_size = self.batch_sizer.next_size().max(1).min(remaining.len());
+                let (batch, rest) = remaining.split_at(batch_size);
+                remaining = rest;
+                let batch_start = Instant::now();
+                
+                // Process chunks in parallel without mutable reference issues
+                let chunk_size = (batch.len() / rayon::current_num_threads()).max(1);
+                let inferred_results: Vec<Result<Vec<oxrdf::Triple>, EpcisKgError>> = batch
+                    .par_chunks(chunk_size)
+                    .map(|chunk| self.process_batch_parallel_readonly(chunk))
+                    .collect::<Vec<_>>();
+                
+                for result in inferred_results {
+                    let triples = result?;
+                    progress.add_triples(triples.len());
+                    all_inferred_triples.extend(triples);
                 }
+                progress.report("materialization", 1.0 - remaining.len() as f64 / all_triples.len() as f64);
+                
+                let decision = self.batch_sizer.record(batch.len(), batch_start.elapsed(), batching::resident_memory_mb());
+                self.performance_metrics.record_batch_decision(decision);
             }
         }
         
@@ -1137,6 +1504,8 @@ impl OntologyReasoner {
         
         // Update indexes
         self.index_structures.build_indexes(&all_inferred_triples);
+        self.caches.invalidate_indexes();
+        progress.report("indexing", 1.0);
         
         // Record performance metrics
         let duration = start_time.elapsed();
@@ -1167,50 +1536,27 @@ impl OntologyReasoner {
         
         // Process each triple in the batch
         for triple in batch {
-            // Check cache first
-            let cache_key = format!("{} {} {}", triple.subject, triple.predicate, triple.object);
-            if self.reasoning_cache.get(&cache_key).is_some() {
+            // Check the triple pattern cache first
+            let cache_key = triple.to_string();
+            if let Some(cached) = self.caches.patterns.get(&cache_key) {
                 self.performance_metrics.cache_hits.fetch_add(1, Ordering::Relaxed);
-                // Convert cached strings back to triples (simplified)
+                self.record_firings(triple, &cached);
+                inferred_triples.extend(cached);
                 continue;
             }
             
             self.performance_metrics.cache_misses.fetch_add(1, Ordering::Relaxed);
             
             // Perform inference for this triple
-            let batch_inferences = self.infer_from_triple(triple)?;
-            inferred_triples.extend(batch_inferences);
+            let triple_inferences = self.infer_from_triple(triple)?;
+            self.caches.patterns.insert(cache_key, triple_inferences.clone());
+            self.record_firings(triple, &triple_inferences);
+            inferred_triples.extend(triple_inferences);
         }
         
         Ok(inferred_triples)
     }
 
-    /// Update cache for a batch of triples and their inferences
-    fn update_cache_for_batch(&mut self, batch: &[oxrdf::Triple], inferences: &[oxrdf::Triple]) {
-        for triple in batch {
-            let cache_key = format!("{} {} {}", triple.subject, triple.predicate, triple.object);
-            let cache_result: Vec<String> = inferences.iter()
-                .map(|t| format!("{} {} {}", t.subject, t.predicate, t.object))
-                .collect();
-            self.reasoning_cache.insert(cache_key, cache_result);
-        }
-    }
-
-    /// Optimize cache size based on usage patterns
-    fn optimize_cache_size(&mut self) {
-        if self.reasoning_cache.len() > self.cache_size_limit {
-            // Remove least recently used entries (simplified LRU)
-            let keys_to_remove: Vec<String> = self.reasoning_cache.keys()
-                .take(self.reasoning_cache.len() - self.cache_size_limit)
-                .cloned()
-                .collect();
-            
-            for key in keys_to_remove {
-                self.reasoning_cache.remove(&key);
-            }
-        }
-    }
-
     /// Get performance report
     pub fn get_performance_report(&self) -> String {
         let metrics = &self.performance_metrics;
@@ -1228,7 +1574,9 @@ impl OntologyReasoner {
              Cache Hit Rate: {:.1}%\n\
              Average Response Time: {}ms\n\
              Cache Size: {}/{}\n\
+             Cache Tiers: {}\n\
              Batch Size: {}\n\
+             Last Batch Decision: {}\n\
              Parallel Processing: {}\n\
              Indexes Built: {}\n\
              Last Optimization: {:?}\n\
@@ -1238,9 +1586,16 @@ impl OntologyReasoner {
             if total_ops > 0 { (parallel_ops as f64 / total_ops as f64) * 100.0 } else { 0.0 },
             if cache_hits + cache_misses > 0 { (cache_hits as f64 / (cache_hits + cache_misses) as f64) * 100.0 } else { 0.0 },
             avg_response,
-            self.reasoning_cache.len(),
-            self.cache_size_limit,
-            self.batch_size,
+            self.caches.patterns.len(),
+            self.get_cache_size_limit(),
+            self.cache_stats().iter()
+                .map(|tier| format!("{:?} {}/{} ({:.1}% hits, {} evictions)", tier.tier, tier.entries, tier.capacity, tier.hit_rate() * 100.0, tier.evictions))
+                .collect::<Vec<_>>()
+                .join(", "),
+            self.batch_size_description(),
+            metrics.batch_decisions.last()
+                .map(|decision| format!("{} triples in {}ms, next {} ({})", decision.batch_size, decision.latency_ms, decision.next_size, decision.reason))
+                .unwrap_or_else(|| "none".to_string()),
             self.parallel_processing,
             self.index_structures.class_index.len() + self.index_structures.property_index.len(),
             metrics.last_optimization_time,
@@ -1248,6 +1603,25 @@ impl OntologyReasoner {
         )
     }
 
+    /// Attribute triples inferred from `source` to the hierarchy rule and axiom that produced them;
+    /// cached inferences count too, since they would have fired again
+    fn record_firings(&self, source: &oxrdf::Triple, inferred: &[oxrdf::Triple]) {
+        const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+        for triple in inferred {
+            match (&source.object, &triple.object) {
+                (oxrdf::Term::NamedNode(class), oxrdf::Term::NamedNode(superclass))
+                    if source.predicate.as_str() == RDF_TYPE && triple.predicate.as_str() == RDF_TYPE =>
+                {
+                    self.rule_usage.record(rule_usage::RDFS9, &[AxiomKey::sub_class_of(class.as_str(), superclass.as_str())]);
+                }
+                _ => self.rule_usage.record(
+                    rule_usage::RDFS7,
+                    &[AxiomKey::sub_property_of(source.predicate.as_str(), triple.predicate.as_str())],
+                ),
+            }
+        }
+    }
+
     /// Perform inference from a single triple (simplified implementation)
     fn infer_from_triple(&self, triple: &oxrdf::Triple) -> Result<Vec<oxrdf::Triple>, EpcisKgError> {
         let mut inferred = Vec::new();
@@ -1285,22 +1659,29 @@ impl OntologyReasoner {
 
     /// Find superclasses for a given class using indexes
     fn find_superclasses(&self, class: &str) -> Vec<String> {
-        // Use the property index to find rdfs:subClassOf relationships
-        self.index_structures.find_subjects_by_property("http://www.w3.org/2000/01/rdf-schema#subClassOf")
-            .into_iter()
-            .filter(|subject| subject.contains(class)) // Simplified matching
-            .map(|s| s.to_string())
-            .collect()
+        self.closure("http://www.w3.org/2000/01/rdf-schema#subClassOf", class)
     }
 
     /// Find superproperties for a given property using indexes
     fn find_superproperties(&self, property: &str) -> Vec<String> {
-        // Use the property index to find rdfs:subPropertyOf relationships
-        self.index_structures.find_subjects_by_property("http://www.w3.org/2000/01/rdf-schema#subPropertyOf")
+        self.closure("http://www.w3.org/2000/01/rdf-schema#subPropertyOf", property)
+    }
+
+    /// Closure of `term` over a hierarchy property, through the closure cache
+    fn closure(&self, hierarchy_property: &str, term: &str) -> Vec<String> {
+        let cache_key = format!("{} {}", hierarchy_property, term);
+        if let Some(cached) = self.caches.closures.get(&cache_key) {
+            return cached;
+        }
+        
+        // Use the property index to find the hierarchy relationships
+        let closure: Vec<String> = self.index_structures.find_subjects_by_property(hierarchy_property)
             .into_iter()
-            .filter(|subject| subject.contains(property)) // Simplified matching
+            .filter(|subject| subject.contains(term)) // Simplified matching
             .map(|s| s.to_string())
-            .collect()
+            .collect();
+        self.caches.closures.insert(cache_key, closure.clone());
+        closure
     }
     
     /// Get parallel processing status
@@ -1308,14 +1689,31 @@ impl OntologyReasoner {
         self.parallel_processing
     }
     
-    /// Get cache size limit
+    /// Get cache size limit (of the triple pattern tier)
     pub fn get_cache_size_limit(&self) -> usize {
-        self.cache_size_limit
+        self.caches.patterns.capacity()
+    }
+    
+    /// Size, hit rate and evictions of each cache tier
+    pub fn cache_stats(&self) -> Vec<CacheTierStats> {
+        self.caches.stats()
     }
     
     /// Get batch size for processing
     pub fn get_batch_size(&self) -> usize {
-        self.batch_size
+        self.batch_sizer.next_size()
+    }
+
+    /// Whether the batch size is pinned via `configure_performance`
+    pub fn is_batch_size_pinned(&self) -> bool {
+        self.batch_sizer.override_size().is_some()
+    }
+
+    fn batch_size_description(&self) -> String {
+        match self.batch_sizer.override_size() {
+            Some(size) => format!("{} (pinned)", size),
+            None => format!("{} (adaptive)", self.batch_sizer.next_size()),
+        }
     }
 }
 
@@ -1558,6 +1956,9 @@ pub struct PerformanceMetrics {
     pub peak_memory_usage_mb: AtomicU64,
     pub operation_throughput: f64,
     pub last_optimization_time: Option<String>,
+    /// Most recent batch size decisions, oldest first
+    #[serde(default)]
+    pub batch_decisions: Vec<BatchDecision>,
 }
 
 impl Clone for PerformanceMetrics {
@@ -1571,6 +1972,7 @@ impl Clone for PerformanceMetrics {
             peak_memory_usage_mb: AtomicU64::new(self.peak_memory_usage_mb.load(Ordering::Relaxed)),
             operation_throughput: self.operation_throughput,
             last_optimization_time: self.last_optimization_time.clone(),
+            batch_decisions: self.batch_decisions.clone(),
         }
     }
 }
@@ -1586,6 +1988,7 @@ impl Default for PerformanceMetrics {
             peak_memory_usage_mb: AtomicU64::new(0),
             operation_throughput: 0.0,
             last_optimization_time: None,
+            batch_decisions: Vec::new(),
         }
     }
 }
@@ -1614,6 +2017,10 @@ impl PerformanceMetrics {
         }
     }
     
+    pub fn record_batch_decision(&mut self, decision: BatchDecision) {
+        batching::remember(&mut self.batch_decisions, decision);
+    }
+    
     pub fn parallel_operation_rate(&self) -> f64 {
         let total = self.total_operations.load(Ordering::Relaxed);
         let parallel = self.parallel_operations.load(Ordering::Relaxed);
@@ -1626,7 +2033,7 @@ impl PerformanceMetrics {
 }
 
 /// Index structures for fast lookups
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct IndexStructures {
     pub class_index: HashMap<String, Vec<String>>,        // class -> instances
     pub property_index: HashMap<String, Vec<String>>,      // property -> subjects
@@ -1696,6 +2103,22 @@ impl IndexStructures {
         }
     }
     
+    /// Add the entries of indexes built over another ontology
+    pub fn merge(&mut self, other: &IndexStructures) {
+        for (index, entries) in [
+            (&mut self.class_index, &other.class_index),
+            (&mut self.property_index, &other.property_index),
+            (&mut self.individual_index, &other.individual_index),
+        ] {
+            for (key, values) in entries {
+                index.entry(key.clone()).or_default().extend(values.iter().cloned());
+            }
+        }
+        for (pattern, positions) in &other.triple_pattern_index {
+            self.triple_pattern_index.entry(pattern.clone()).or_default().extend(positions.iter().copied());
+        }
+    }
+    
     pub fn find_instances_by_class(&self, class: &str) -> Vec<&String> {
         self.class_index.get(class)
             .map(|instances| instances.iter().collect())
@@ -0,0 +1,225 @@
+use crate::EpcisKgError;
+use crate::config::AppConfig;
+use crate::ontology::loader::OntologyLoader;
+use crate::ontology::reasoner::OntologyReasoner;
+use owl2_rs::Ontology;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Pool of lightweight reasoners sharing one immutable TBox.
+///
+/// The TBox (converted ontology axioms) is loaded lazily on the first checkout
+/// and shared through an `Arc`. Each pooled reasoner only owns its ABox working
+/// state, which is cleared when the reasoner is returned to the pool.
+pub struct ReasonerPool {
+    config: Arc<AppConfig>,
+    ontology_paths: Vec<String>,
+    tbox: Mutex<Option<Arc<Ontology>>>,
+    idle: Mutex<Vec<OntologyReasoner>>,
+    max_idle: usize,
+    created: AtomicUsize,
+    reused: AtomicUsize,
+}
+
+/// Pool usage counters
+#[derive(Debug, Clone, Serialize)]
+pub struct ReasonerPoolStats {
+    pub tbox_loaded: bool,
+    pub tbox_axioms: usize,
+    pub idle_reasoners: usize,
+    pub reasoners_created: usize,
+    pub reasoners_reused: usize,
+}
+
+impl ReasonerPool {
+    /// Create a pool that loads its TBox from the given ontology files on first use
+    pub fn new(ontology_paths: Vec<String>, max_idle: usize) -> Self {
+        Self::with_config(Arc::new(AppConfig::default()), ontology_paths, max_idle)
+    }
+
+    fn with_config(config: Arc<AppConfig>, ontology_paths: Vec<String>, max_idle: usize) -> Self {
+        Self {
+            config,
+            ontology_paths,
+            tbox: Mutex::new(None),
+            idle: Mutex::new(Vec::new()),
+            max_idle,
+            created: AtomicUsize::new(0),
+            reused: AtomicUsize::new(0),
+        }
+    }
+
+    /// Pool whose reasoners and ontology loading use the loaded configuration
+    pub fn from_config(config: &AppConfig) -> Self {
+        Self::with_config(Arc::new(config.clone()), config.ontology_paths.clone(), config.reasoning.pool_size)
+    }
+
+    /// Create a pool around an already converted TBox
+    pub fn with_tbox(tbox: Ontology, max_idle: usize) -> Self {
+        let pool = Self::new(Vec::new(), max_idle);
+        *pool.tbox.lock() = Some(Arc::new(tbox));
+        pool
+    }
+
+    /// Pool configured like `from_config`, around an already converted TBox
+    pub fn from_config_with_tbox(config: &AppConfig, tbox: Ontology) -> Self {
+        let pool = Self::with_config(Arc::new(config.clone()), Vec::new(), config.reasoning.pool_size);
+        *pool.tbox.lock() = Some(Arc::new(tbox));
+        pool
+    }
+
+    /// Shared TBox, loading and converting the ontology files if needed
+    pub fn tbox(&self) -> Result<Arc<Ontology>, EpcisKgError> {
+        let mut tbox = self.tbox.lock();
+        if let Some(ref loaded) = *tbox {
+            return Ok(Arc::clone(loaded));
+        }
+
+        let loader = OntologyLoader::with_config(&self.config);
+        let converter = OntologyReasoner::with_config(&self.config);
+        let mut ontology = Ontology::default();
+        for path in &self.ontology_paths {
+            if !std::path::Path::new(path).exists() {
+                tracing::warn!("Skipping missing ontology for reasoner pool: {}", path);
+                continue;
+            }
+            let ontology_data = loader.load_ontology(path)?;
+            ontology.axioms.extend(converter.convert_rdf_to_owl(&ontology_data)?.axioms);
+        }
+
+        let loaded = Arc::new(ontology);
+        *tbox = Some(Arc::clone(&loaded));
+        Ok(loaded)
+    }
+
+    /// Take a reasoner from the pool, creating one when none is idle
+    pub fn checkout(self: &Arc<Self>) -> Result<PooledReasoner, EpcisKgError> {
+        let idle = self.idle.lock().pop();
+        let reasoner = match idle {
+            Some(reasoner) => {
+                self.reused.fetch_add(1, Ordering::Relaxed);
+                reasoner
+            }
+            None => {
+                self.created.fetch_add(1, Ordering::Relaxed);
+                OntologyReasoner::with_config_and_tbox(&self.config, self.tbox()?)
+            }
+        };
+
+        Ok(PooledReasoner {
+            reasoner: Some(reasoner),
+            pool: Arc::clone(self),
+        })
+    }
+
+    pub fn stats(&self) -> ReasonerPoolStats {
+        let tbox = self.tbox.lock();
+        ReasonerPoolStats {
+            tbox_loaded: tbox.is_some(),
+            tbox_axioms: tbox.as_ref().map(|t| t.axioms.len()).unwrap_or(0),
+            idle_reasoners: self.idle.lock().len(),
+            reasoners_created: self.created.load(Ordering::Relaxed),
+            reasoners_reused: self.reused.load(Ordering::Relaxed),
+        }
+    }
+
+    fn check_in(&self, mut reasoner: OntologyReasoner) {
+        reasoner.reset_working_state();
+        let mut idle = self.idle.lock();
+        if idle.len() < self.max_idle {
+            idle.push(reasoner);
+        }
+    }
+}
+
+/// A reasoner checked out of a [`ReasonerPool`]; returned to the pool on drop
+pub struct PooledReasoner {
+    reasoner: Option<OntologyReasoner>,
+    pool: Arc<ReasonerPool>,
+}
+
+impl Deref for PooledReasoner {
+    type Target = OntologyReasoner;
+
+    fn deref(&self) -> &Self::Target {
+        self.reasoner.as_ref().expect("pooled reasoner is present until dropped")
+    }
+}
+
+impl DerefMut for PooledReasoner {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.reasoner.as_mut().expect("pooled reasoner is present until dropped")
+    }
+}
+
+impl Drop for PooledReasoner {
+    fn drop(&mut self) {
+        if let Some(reasoner) = self.reasoner.take() {
+            self.pool.check_in(reasoner);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reasoners_are_reused_and_share_tbox() {
+        let pool = Arc::new(ReasonerPool::with_tbox(Ontology::default(), 2));
+
+        {
+            let _first = pool.checkout().unwrap();
+            let _second = pool.checkout().unwrap();
+        }
+        let _third = pool.checkout().unwrap();
+
+        let stats = pool.stats();
+        assert!(stats.tbox_loaded);
+        assert_eq!(stats.reasoners_created, 2);
+        assert_eq!(stats.reasoners_reused, 1);
+        assert_eq!(stats.idle_reasoners, 1);
+    }
+
+    #[test]
+    fn test_checked_in_reasoners_forget_hierarchy_and_stats() {
+        let directory = tempfile::tempdir().unwrap();
+        let pool = Arc::new(ReasonerPool::with_tbox(Ontology::default(), 1));
+        let mut graph = oxrdf::Graph::default();
+        graph.insert(&oxrdf::Triple::new(
+            oxrdf::NamedNode::new_unchecked("urn:ex:ObjectEvent"),
+            oxrdf::NamedNode::new_unchecked("http://www.w3.org/2000/01/rdf-schema#subClassOf"),
+            oxrdf::NamedNode::new_unchecked("urn:ex:Event"),
+        ));
+        let ontology = crate::ontology::loader::OntologyData { graph, triples_count: 1, source_file: "events.ttl".to_string() };
+
+        {
+            let mut reasoner = pool.checkout().unwrap();
+            reasoner.enable_warm_start(directory.path());
+            reasoner.load_ontology_data_warm(&ontology).unwrap();
+            reasoner.perform_inference_with_materialization().unwrap();
+            assert_eq!(reasoner.superclasses_of("urn:ex:ObjectEvent"), ["urn:ex:Event"]);
+            assert_eq!(reasoner.get_detailed_stats().total_inferences, 1);
+        }
+
+        let reasoner = pool.checkout().unwrap();
+        assert_eq!(pool.stats().reasoners_reused, 1);
+        assert!(reasoner.superclasses_of("urn:ex:ObjectEvent").is_empty());
+        let stats = reasoner.get_detailed_stats();
+        assert_eq!(stats.total_inferences, 0);
+        assert_eq!(stats.total_processing_time_ms, 0);
+        assert!(stats.last_inference_time.is_none());
+    }
+
+    #[test]
+    fn test_tbox_is_loaded_lazily() {
+        let pool = ReasonerPool::new(vec!["does/not/exist.ttl".to_string()], 1);
+        assert!(!pool.stats().tbox_loaded);
+
+        pool.tbox().unwrap();
+        assert!(pool.stats().tbox_loaded);
+    }
+}
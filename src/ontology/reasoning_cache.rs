@@ -0,0 +1,261 @@
+use crate::config::ReasoningCacheConfig;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The reasoner's cache tiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheTier {
+    /// Results of whole inference runs
+    QueryResults,
+    /// Inferences drawn from one input triple
+    TriplePatterns,
+    /// Transitive superclasses and superproperties
+    Closures,
+}
+
+/// Size and effectiveness of one cache tier
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheTierStats {
+    pub tier: CacheTier,
+    pub entries: usize,
+    pub capacity: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub invalidations: u64,
+}
+
+impl CacheTierStats {
+    pub fn hit_rate(&self) -> f64 {
+        if self.hits + self.misses == 0 {
+            0.0
+        } else {
+            self.hits as f64 / (self.hits + self.misses) as f64
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TierEntries<V> {
+    capacity: usize,
+    /// key -> (value, last use)
+    entries: HashMap<String, (V, u64)>,
+    /// last use -> key, oldest first
+    recency: BTreeMap<u64, String>,
+    clock: u64,
+}
+
+/// Bounded least-recently-used cache for one tier.
+///
+/// Lookups take `&self` so the parallel inference workers can share it.
+#[derive(Debug)]
+pub struct TierCache<V> {
+    tier: CacheTier,
+    state: Mutex<TierEntries<V>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+impl<V: Clone> TierCache<V> {
+    pub fn new(tier: CacheTier, capacity: usize) -> Self {
+        Self {
+            tier,
+            state: Mutex::new(TierEntries {
+                capacity,
+                entries: HashMap::new(),
+                recency: BTreeMap::new(),
+                clock: 0,
+            }),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            invalidations: AtomicU64::new(0),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<V> {
+        let mut state = self.state.lock();
+        state.clock += 1;
+        let now = state.clock;
+        let TierEntries { entries, recency, .. } = &mut *state;
+        match entries.get_mut(key) {
+            Some((value, last_used)) => {
+                recency.remove(last_used);
+                recency.insert(now, key.to_string());
+                *last_used = now;
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(value.clone())
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub fn insert(&self, key: String, value: V) {
+        let mut state = self.state.lock();
+        if state.capacity == 0 {
+            return;
+        }
+        state.clock += 1;
+        let now = state.clock;
+        if let Some((_, last_used)) = state.entries.insert(key.clone(), (value, now)) {
+            state.recency.remove(&last_used);
+        }
+        state.recency.insert(now, key);
+        let capacity = state.capacity;
+        self.evict_to(&mut state, capacity);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.state.lock().capacity
+    }
+
+    /// Change the entry limit, evicting the least recently used entries beyond it
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut state = self.state.lock();
+        state.capacity = capacity;
+        self.evict_to(&mut state, capacity);
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Drop every entry, counting it as one invalidation
+    pub fn invalidate(&self) {
+        let mut state = self.state.lock();
+        if !state.entries.is_empty() {
+            state.entries.clear();
+            state.recency.clear();
+            self.invalidations.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn stats(&self) -> CacheTierStats {
+        let state = self.state.lock();
+        CacheTierStats {
+            tier: self.tier,
+            entries: state.entries.len(),
+            capacity: state.capacity,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+        }
+    }
+
+    fn evict_to(&self, state: &mut TierEntries<V>, capacity: usize) {
+        while state.entries.len() > capacity {
+            let Some((_, key)) = state.recency.pop_first() else { break };
+            state.entries.remove(&key);
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl<V: Clone> Clone for TierCache<V> {
+    fn clone(&self) -> Self {
+        let state = self.state.lock();
+        Self {
+            tier: self.tier,
+            state: Mutex::new(TierEntries {
+                capacity: state.capacity,
+                entries: state.entries.clone(),
+                recency: state.recency.clone(),
+                clock: state.clock,
+            }),
+            hits: AtomicU64::new(self.hits.load(Ordering::Relaxed)),
+            misses: AtomicU64::new(self.misses.load(Ordering::Relaxed)),
+            evictions: AtomicU64::new(self.evictions.load(Ordering::Relaxed)),
+            invalidations: AtomicU64::new(self.invalidations.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+/// The reasoner's caches, sized and invalidated independently.
+///
+/// - Query results depend on the data, so any data change drops them.
+/// - Triple patterns and closures are looked up through the indexes, so an
+///   index rebuild drops them but new data does not.
+/// - An ontology change drops every tier.
+#[derive(Debug, Clone)]
+pub struct ReasoningCaches {
+    pub queries: TierCache<Vec<String>>,
+    pub patterns: TierCache<Vec<oxrdf::Triple>>,
+    pub closures: TierCache<Vec<String>>,
+}
+
+impl ReasoningCaches {
+    pub fn from_config(config: &ReasoningCacheConfig) -> Self {
+        Self {
+            queries: TierCache::new(CacheTier::QueryResults, config.query_results),
+            patterns: TierCache::new(CacheTier::TriplePatterns, config.triple_patterns),
+            closures: TierCache::new(CacheTier::Closures, config.closures),
+        }
+    }
+
+    pub fn invalidate_data(&self) {
+        self.queries.invalidate();
+    }
+
+    pub fn invalidate_indexes(&self) {
+        self.patterns.invalidate();
+        self.closures.invalidate();
+    }
+
+    pub fn invalidate_ontology(&self) {
+        self.invalidate_data();
+        self.invalidate_indexes();
+    }
+
+    pub fn stats(&self) -> Vec<CacheTierStats> {
+        vec![self.queries.stats(), self.patterns.stats(), self.closures.stats()]
+    }
+}
+
+impl Default for ReasoningCaches {
+    fn default() -> Self {
+        Self::from_config(&ReasoningCacheConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tiers_evict_and_invalidate_independently() {
+        let caches = ReasoningCaches::from_config(&ReasoningCacheConfig {
+            query_results: 10,
+            triple_patterns: 10,
+            closures: 2,
+        });
+        caches.closures.insert("a".to_string(), vec!["A".to_string()]);
+        caches.closures.insert("b".to_string(), vec!["B".to_string()]);
+        assert!(caches.closures.get("a").is_some());
+        caches.closures.insert("c".to_string(), vec!["C".to_string()]);
+        assert!(caches.closures.get("b").is_none(), "least recently used entry is evicted");
+        assert!(caches.closures.get("a").is_some());
+
+        caches.queries.insert("run".to_string(), vec!["done".to_string()]);
+        caches.invalidate_data();
+        assert!(caches.queries.is_empty());
+        assert_eq!(caches.closures.len(), 2);
+
+        caches.invalidate_ontology();
+        assert!(caches.closures.is_empty());
+        let closures = &caches.stats()[2];
+        assert_eq!((closures.hits, closures.misses, closures.evictions, closures.invalidations), (2, 1, 1, 1));
+    }
+}
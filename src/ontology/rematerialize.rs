@@ -0,0 +1,205 @@
+use crate::api::access_control::graph_pattern_matches;
+use crate::ontology::reasoner::{OntologyReasoner, PROVENANCE_GRAPH};
+use crate::ontology::runs::InferenceRun;
+use crate::ontology::scope::InferenceScope;
+use crate::storage::oxigraph_store::{is_derived_graph, OxigraphStore};
+use crate::{Config, EpcisKgError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+
+const EVENT_TIME: &str = "urn:epcglobal:epcis:eventTime";
+
+/// Which data a partial re-materialization covers.
+///
+/// Criteria combine: a graph is in scope when it matches one of `graphs` (event
+/// graphs if none are listed), mentions one of `epcs` (if any) and holds an event
+/// time within `since`..`until` (if given). At least one criterion is required.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RematerializeScope {
+    /// Graph names or `prefix*` patterns
+    pub graphs: Vec<String>,
+    pub epcs: Vec<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl RematerializeScope {
+    pub fn validate(&self) -> Result<(), EpcisKgError> {
+        if self.graphs.is_empty() && self.epcs.is_empty() && self.since.is_none() && self.until.is_none() {
+            return Err(EpcisKgError::Validation(
+                "Re-materialization scope needs graphs, EPCs or a time range; run full inference otherwise".to_string(),
+            ));
+        }
+        if let Some(epc) = self.epcs.iter().find(|epc| oxrdf::NamedNodeRef::new(epc).is_err()) {
+            return Err(EpcisKgError::InvalidField { field: "epcs".to_string(), message: format!("'{}' is not an IRI", epc) });
+        }
+        if let (Some(since), Some(until)) = (self.since, self.until) {
+            if since > until {
+                return Err(EpcisKgError::InvalidField { field: "since".to_string(), message: "must not be after until".to_string() });
+            }
+        }
+        Ok(())
+    }
+
+    /// Data graphs of the store in scope, by name
+    pub fn matching_graphs(&self, store: &OxigraphStore) -> Vec<String> {
+        store
+            .graph_names()
+            .into_iter()
+            .filter(|name| !is_derived_graph(name))
+            .filter(|name| match self.graphs.is_empty() {
+                true => name.starts_with("urn:epcis:event:"),
+                false => self.graphs.iter().any(|pattern| graph_pattern_matches(pattern, name)),
+            })
+            .filter(|name| self.mentions_epc(store, name) && self.in_time_range(store, name))
+            .collect()
+    }
+
+    fn mentions_epc(&self, store: &OxigraphStore, graph: &str) -> bool {
+        self.epcs.is_empty()
+            || self.epcs.iter().filter_map(|epc| oxrdf::NamedNodeRef::new(epc).ok()).any(|epc| {
+                store.contains_pattern(Some(epc.into()), None, None, Some(graph))
+                    || store.contains_pattern(None, None, Some(epc.into()), Some(graph))
+            })
+    }
+
+    fn in_time_range(&self, store: &OxigraphStore, graph: &str) -> bool {
+        if self.since.is_none() && self.until.is_none() {
+            return true;
+        }
+        store
+            .quads_for_pattern(None, Some(oxrdf::NamedNodeRef::new_unchecked(EVENT_TIME)), None, Some(graph))
+            .iter()
+            .filter_map(|quad| match &quad.object {
+                oxrdf::Term::Literal(time) => DateTime::parse_from_rfc3339(time.value()).ok(),
+                _ => None,
+            })
+            .any(|time| self.since.map_or(true, |since| time >= since) && self.until.map_or(true, |until| time <= until))
+    }
+}
+
+/// What a partial re-materialization touched
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RematerializeReport {
+    /// Graphs matching the scope
+    pub scoped_graphs: usize,
+    /// Resources described by those graphs; only inferences about these are recomputed
+    pub subjects: usize,
+    /// Graphs reasoned over: the scoped graphs plus every other data graph describing one of the subjects
+    pub input_graphs: usize,
+    pub retracted: usize,
+    pub inserted: usize,
+    /// Recorded inference run, absent when nothing was in scope
+    pub run_id: Option<String>,
+}
+
+/// Delete and recompute the inferred triples about the resources in scope.
+///
+/// Inferences about other resources stay as they are. The reasoner sees every
+/// data graph that describes an affected resource, so facts about an EPC
+/// from events outside the scope still contribute to its inferences.
+pub fn rematerialize(store: &mut OxigraphStore, config: &Config, scope: &RematerializeScope, source: &str) -> Result<RematerializeReport, EpcisKgError> {
+    scope.validate()?;
+    let started_at = Utc::now();
+    let scoped = scope.matching_graphs(store);
+
+    let subjects: HashSet<String> = scoped
+        .iter()
+        .flat_map(|graph| store.quads_for_pattern(None, None, None, Some(graph.as_str())))
+        .filter_map(|quad| match quad.subject {
+            oxrdf::Subject::NamedNode(node) => Some(node.into_string()),
+            _ => None,
+        })
+        .collect();
+    let inputs: BTreeSet<String> = subjects
+        .iter()
+        .filter_map(|subject| oxrdf::NamedNodeRef::new(subject).ok())
+        .flat_map(|subject| store.quads_for_pattern(Some(subject.into()), None, None, None))
+        .filter_map(|quad| match quad.graph_name {
+            oxrdf::GraphName::NamedNode(graph) if !is_derived_graph(graph.as_str()) => Some(graph.into_string()),
+            _ => None,
+        })
+        .collect();
+
+    let mut report = RematerializeReport {
+        scoped_graphs: scoped.len(),
+        subjects: subjects.len(),
+        input_graphs: inputs.len(),
+        ..RematerializeReport::default()
+    };
+    if subjects.is_empty() {
+        return Ok(report);
+    }
+
+    // Reason before retracting, so a failed run leaves the old inferences in place
+    let inference_scope = InferenceScope::from_config(&config.reasoning.scope).with_overrides(None, Some(inputs.into_iter().collect()));
+    let mut reasoner = OntologyReasoner::with_config(config);
+    let summary = reasoner.load_scope(store, &inference_scope)?;
+    let result = reasoner.perform_inference_with_materialization()?;
+    report.retracted = store.retract_inferences_about(&subjects)?;
+
+    let about_scope = |triple: &oxrdf::Triple| matches!(&triple.subject, oxrdf::Subject::NamedNode(node) if subjects.contains(node.as_str()));
+    for (graph, triples) in reasoner.get_materialized_triples() {
+        let kept: Vec<oxrdf::Triple> = triples.iter().filter(|triple| about_scope(triple)).cloned().collect();
+        report.inserted += store.insert_triples(graph, &kept)?;
+    }
+    let annotations: Vec<oxrdf::Triple> = reasoner
+        .provenance_annotations()
+        .into_iter()
+        .filter(|annotation| matches!(&annotation.subject, oxrdf::Subject::Triple(quoted) if about_scope(quoted)))
+        .collect();
+    store.insert_triples(PROVENANCE_GRAPH, &annotations)?;
+
+    let run = InferenceRun {
+        ontology_versions: crate::ontology::runs::ontology_versions(store, &summary.tbox_graphs),
+        tbox_triples: summary.tbox_triples,
+        abox_triples: summary.abox_triples,
+        ..InferenceRun::new(source, "partial", started_at, &result, &config.reasoning)
+    };
+    store.record_inference_run(&run)?;
+    report.run_id = Some(run.id);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(iri: &str) -> oxrdf::NamedNode {
+        oxrdf::NamedNode::new_unchecked(iri)
+    }
+
+    fn event(id: &str, epc: &str, time: &str) -> Vec<oxrdf::Triple> {
+        let event = node(&format!("urn:epcis:event:{}", id));
+        vec![
+            oxrdf::Triple::new(event.clone(), node("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"), node("urn:epcglobal:epcis:ObjectEvent")),
+            oxrdf::Triple::new(event.clone(), node("urn:epcglobal:epcis:epcList"), node(epc)),
+            oxrdf::Triple::new(event, node(EVENT_TIME), oxrdf::Literal::new_simple_literal(time)),
+        ]
+    }
+
+    #[test]
+    fn test_only_inferences_about_the_scope_are_replaced() {
+        let mut store = OxigraphStore::new_memory().unwrap();
+        store.insert_triples("urn:epcis:event:e1", &event("e1", "urn:epc:id:sgtin:0614141.107346.1", "2024-03-01T09:00:00Z")).unwrap();
+        store.insert_triples("urn:epcis:event:e2", &event("e2", "urn:epc:id:sgtin:0614141.107346.2", "2024-03-02T09:00:00Z")).unwrap();
+        let stale = |id: &str| {
+            oxrdf::Triple::new(node(&format!("urn:epcis:event:{}", id)), node("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"), node("urn:example:Stale"))
+        };
+        store.insert_triples("urn:epcis:inferred", &[stale("e1"), stale("e2")]).unwrap();
+
+        assert!(RematerializeScope::default().validate().is_err());
+        let scope = RematerializeScope { epcs: vec!["urn:epc:id:sgtin:0614141.107346.1".to_string()], ..RematerializeScope::default() };
+        assert_eq!(scope.matching_graphs(&store), vec!["urn:epcis:event:e1".to_string()]);
+        let by_time = RematerializeScope { since: "2024-03-02T00:00:00Z".parse().ok(), ..RematerializeScope::default() };
+        assert_eq!(by_time.matching_graphs(&store), vec!["urn:epcis:event:e2".to_string()]);
+
+        let report = rematerialize(&mut store, &Config::default(), &scope, "test").unwrap();
+        assert_eq!((report.scoped_graphs, report.subjects, report.input_graphs, report.retracted), (1, 1, 1, 1));
+        assert!(report.run_id.is_some());
+        assert!(!store.contains_pattern(Some(stale("e1").subject.as_ref()), None, Some(node("urn:example:Stale").as_ref().into()), Some("urn:epcis:inferred")));
+        assert!(store.contains_pattern(Some(stale("e2").subject.as_ref()), None, Some(node("urn:example:Stale").as_ref().into()), Some("urn:epcis:inferred")));
+    }
+}
@@ -0,0 +1,188 @@
+use owl2_rs::{Axiom, Class, ClassExpression, Ontology, IRI};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// RDFS entailment rule inferring `x rdf:type D` from `x rdf:type C` and `C rdfs:subClassOf D`
+pub const RDFS9: &str = "rdfs9";
+/// RDFS entailment rule inferring `C rdfs:subClassOf E` from two chained subclass axioms
+pub const RDFS11: &str = "rdfs11";
+/// RDFS entailment rule inferring `x q y` from `x p y` and `p rdfs:subPropertyOf q`
+pub const RDFS7: &str = "rdfs7";
+/// Types materialized from the owl2_rs realization, which does not report the axioms it used
+pub const OWL_REALIZATION: &str = "owl2_rs:realization";
+
+/// Kind of hierarchy axiom a rule can fire on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AxiomKind {
+    SubClassOf,
+    SubPropertyOf,
+}
+
+/// An asserted `sub rdfs:subClassOf super` or `sub rdfs:subPropertyOf super` axiom
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct AxiomKey {
+    pub kind: AxiomKind,
+    pub sub: String,
+    #[serde(rename = "super")]
+    pub super_: String,
+}
+
+impl AxiomKey {
+    pub fn sub_class_of(sub: &str, super_: &str) -> Self {
+        Self { kind: AxiomKind::SubClassOf, sub: sub.to_string(), super_: super_.to_string() }
+    }
+
+    pub fn sub_property_of(sub: &str, super_: &str) -> Self {
+        Self { kind: AxiomKind::SubPropertyOf, sub: sub.to_string(), super_: super_.to_string() }
+    }
+}
+
+/// Named-class subclass axioms of an ontology, the axioms the usage report checks for firings
+pub fn hierarchy_axioms(ontology: &Ontology) -> Vec<AxiomKey> {
+    ontology
+        .axioms
+        .iter()
+        .filter_map(|axiom| match axiom {
+            Axiom::Class(owl2_rs::ClassAxiom::SubClassOf {
+                sub_class: ClassExpression::Class(Class(IRI(sub_class))),
+                super_class: ClassExpression::Class(Class(IRI(super_class))),
+            }) => Some(AxiomKey::sub_class_of(sub_class, super_class)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Firings of one rule
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuleCount {
+    pub rule: String,
+    pub firings: u64,
+}
+
+/// Firings of one axiom, across all rules
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AxiomCount {
+    pub axiom: AxiomKey,
+    pub firings: u64,
+}
+
+/// Hot rules and axioms, and the declared axioms that never fired
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleUsageReport {
+    pub total_firings: u64,
+    /// Every rule that fired, most firings first
+    pub rules: Vec<RuleCount>,
+    /// The most-fired axioms, most firings first
+    pub hot_axioms: Vec<AxiomCount>,
+    /// Share of axiom firings covered by `hot_axioms`; materializing only those
+    /// and answering the rest on demand keeps this share of inferences precomputed
+    pub hot_axiom_share: f64,
+    pub declared_axioms: usize,
+    /// Declared axioms with no firings, candidates for trimming from the ontology
+    pub unused_axioms: Vec<AxiomKey>,
+}
+
+/// How often each rule and axiom fired during materialization.
+///
+/// Counters are cumulative; a reasoner records into the instance it is given,
+/// so the server keeps one across all inference runs.
+#[derive(Debug, Default)]
+pub struct RuleUsage {
+    rules: Mutex<BTreeMap<String, u64>>,
+    axioms: Mutex<BTreeMap<AxiomKey, u64>>,
+    declared: Mutex<BTreeSet<AxiomKey>>,
+}
+
+impl RuleUsage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count one firing of `rule`, on each of the axioms it used
+    pub fn record(&self, rule: &str, axioms: &[AxiomKey]) {
+        *self.rules.lock().entry(rule.to_string()).or_insert(0) += 1;
+        let mut counts = self.axioms.lock();
+        for axiom in axioms {
+            *counts.entry(axiom.clone()).or_insert(0) += 1;
+        }
+    }
+
+    /// Axioms of the ontology reasoned over, reported as unused until they fire
+    pub fn declare(&self, axioms: impl IntoIterator<Item = AxiomKey>) {
+        self.declared.lock().extend(axioms);
+    }
+
+    pub fn firings(&self, rule: &str) -> u64 {
+        self.rules.lock().get(rule).copied().unwrap_or(0)
+    }
+
+    /// Report with the `top` most-fired axioms
+    pub fn report(&self, top: usize) -> RuleUsageReport {
+        let mut rules: Vec<RuleCount> = self
+            .rules
+            .lock()
+            .iter()
+            .map(|(rule, firings)| RuleCount { rule: rule.clone(), firings: *firings })
+            .collect();
+        rules.sort_by(|a, b| b.firings.cmp(&a.firings).then_with(|| a.rule.cmp(&b.rule)));
+
+        let axioms = self.axioms.lock();
+        let mut hot_axioms: Vec<AxiomCount> =
+            axioms.iter().map(|(axiom, firings)| AxiomCount { axiom: axiom.clone(), firings: *firings }).collect();
+        hot_axioms.sort_by(|a, b| b.firings.cmp(&a.firings).then_with(|| a.axiom.cmp(&b.axiom)));
+        hot_axioms.truncate(top);
+
+        let axiom_firings: u64 = axioms.values().sum();
+        let hot_firings: u64 = hot_axioms.iter().map(|count| count.firings).sum();
+        let declared = self.declared.lock();
+
+        RuleUsageReport {
+            total_firings: rules.iter().map(|count| count.firings).sum(),
+            rules,
+            hot_axioms,
+            hot_axiom_share: if axiom_firings == 0 { 0.0 } else { hot_firings as f64 / axiom_firings as f64 },
+            declared_axioms: declared.len(),
+            unused_axioms: declared.iter().filter(|axiom| !axioms.contains_key(*axiom)).cloned().collect(),
+        }
+    }
+
+    pub fn clear(&self) {
+        self.rules.lock().clear();
+        self.axioms.lock().clear();
+        self.declared.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_ranks_hot_axioms_and_lists_unused() {
+        let usage = RuleUsage::new();
+        let shipping = AxiomKey::sub_class_of("ex:ShippingEvent", "epcis:ObjectEvent");
+        let receiving = AxiomKey::sub_class_of("ex:ReceivingEvent", "epcis:ObjectEvent");
+        let recall = AxiomKey::sub_class_of("ex:RecallEvent", "epcis:ObjectEvent");
+        usage.declare([shipping.clone(), receiving.clone(), recall.clone()]);
+
+        for _ in 0..3 {
+            usage.record(RDFS9, &[shipping.clone()]);
+        }
+        usage.record(RDFS9, &[receiving.clone()]);
+        usage.record(RDFS7, &[AxiomKey::sub_property_of("ex:shippedTo", "ex:movedTo")]);
+        usage.record(OWL_REALIZATION, &[]);
+
+        let report = usage.report(1);
+        assert_eq!(report.total_firings, 6);
+        assert_eq!(report.rules[0], RuleCount { rule: RDFS9.to_string(), firings: 4 });
+        assert_eq!(report.hot_axioms, vec![AxiomCount { axiom: shipping, firings: 3 }]);
+        assert_eq!(report.hot_axiom_share, 0.6);
+        assert_eq!(report.declared_axioms, 3);
+        assert_eq!(report.unused_axioms, vec![recall]);
+
+        usage.clear();
+        assert_eq!(usage.firings(RDFS9), 0);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::config::ReasoningConfig;
+use crate::ontology::reasoner::InferenceResult;
+use crate::storage::canonical;
+use crate::storage::oxigraph_store::OxigraphStore;
+use chrono::{DateTime, Utc};
+use oxrdf::{Graph, GraphName, NamedNodeRef, Quad, TermRef};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const OWL_ONTOLOGY: &str = "http://www.w3.org/2002/07/owl#Ontology";
+const OWL_VERSION_IRI: &str = "http://www.w3.org/2002/07/owl#versionIRI";
+const OWL_VERSION_INFO: &str = "http://www.w3.org/2002/07/owl#versionInfo";
+
+/// Record of one materialization run, kept to compare strategies and ontology releases
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InferenceRun {
+    pub id: String,
+    pub started_at: DateTime<Utc>,
+    pub strategy: String,
+    /// Where the run was started: `cli` or `api`
+    pub source: String,
+    /// Ontology graph or file -> version (see `ontology_version`)
+    pub ontology_versions: BTreeMap<String, String>,
+    pub tbox_triples: usize,
+    pub abox_triples: usize,
+    pub materialized_triples: usize,
+    pub sparql_inferences: usize,
+    pub individuals_classified: usize,
+    pub consistent: bool,
+    pub duration_ms: u64,
+    /// The `[reasoning]` configuration in effect
+    pub config: serde_json::Value,
+}
+
+/// Differences between two runs; deltas are candidate minus baseline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunComparison {
+    pub baseline: String,
+    pub candidate: String,
+    pub strategy_changed: bool,
+    pub materialized_triples_delta: i64,
+    pub sparql_inferences_delta: i64,
+    pub duration_ms_delta: i64,
+    /// Candidate duration divided by baseline duration
+    pub duration_ratio: Option<f64>,
+    /// Ontologies added, removed or at a different version
+    pub changed_ontologies: Vec<String>,
+    /// Dotted paths of configuration values that differ
+    pub changed_config: Vec<String>,
+}
+
+impl InferenceRun {
+    /// Record of a run that started at `started_at` and has just finished
+    pub fn new(source: &str, strategy: &str, started_at: DateTime<Utc>, result: &InferenceResult, config: &ReasoningConfig) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            started_at,
+            strategy: strategy.to_lowercase(),
+            source: source.to_string(),
+            ontology_versions: BTreeMap::new(),
+            tbox_triples: 0,
+            abox_triples: 0,
+            materialized_triples: result.materialized_triples,
+            sparql_inferences: result.sparql_inferences,
+            individuals_classified: result.individuals_classified,
+            consistent: result.consistent,
+            duration_ms: (Utc::now() - started_at).num_milliseconds().max(0) as u64,
+            config: serde_json::to_value(config).unwrap_or_default(),
+        }
+    }
+
+    pub fn compare(&self, candidate: &InferenceRun) -> RunComparison {
+        let delta = |baseline: u64, candidate: u64| candidate as i64 - baseline as i64;
+        let mut changed_ontologies: Vec<String> = self
+            .ontology_versions
+            .keys()
+            .chain(candidate.ontology_versions.keys())
+            .filter(|name| self.ontology_versions.get(*name) != candidate.ontology_versions.get(*name))
+            .cloned()
+            .collect();
+        changed_ontologies.sort();
+        changed_ontologies.dedup();
+        let mut changed_config = Vec::new();
+        config_differences("", &self.config, &candidate.config, &mut changed_config);
+
+        RunComparison {
+            baseline: self.id.clone(),
+            candidate: candidate.id.clone(),
+            strategy_changed: self.strategy != candidate.strategy,
+            materialized_triples_delta: delta(self.materialized_triples as u64, candidate.materialized_triples as u64),
+            sparql_inferences_delta: delta(self.sparql_inferences as u64, candidate.sparql_inferences as u64),
+            duration_ms_delta: delta(self.duration_ms, candidate.duration_ms),
+            duration_ratio: (self.duration_ms > 0).then(|| candidate.duration_ms as f64 / self.duration_ms as f64),
+            changed_ontologies,
+            changed_config,
+        }
+    }
+}
+
+/// Collect the paths at which two JSON values differ
+fn config_differences(path: &str, baseline: &serde_json::Value, candidate: &serde_json::Value, changes: &mut Vec<String>) {
+    match (baseline, candidate) {
+        (serde_json::Value::Object(a), serde_json::Value::Object(b)) => {
+            let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            let null = serde_json::Value::Null;
+            for key in keys {
+                let child = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                config_differences(&child, a.get(key).unwrap_or(&null), b.get(key).unwrap_or(&null), changes);
+            }
+        }
+        (a, b) if a != b => changes.push(path.to_string()),
+        _ => {}
+    }
+}
+
+/// Version of an ontology graph: its `owl:versionIRI`, else its `owl:versionInfo`,
+/// else a `sha256:` digest of its canonical N-Quads so edits are still noticed
+pub fn ontology_version(graph: &Graph) -> String {
+    let ontology = graph
+        .subjects_for_predicate_object(NamedNodeRef::new_unchecked(RDF_TYPE), NamedNodeRef::new_unchecked(OWL_ONTOLOGY))
+        .next();
+    if let Some(ontology) = ontology {
+        for predicate in [OWL_VERSION_IRI, OWL_VERSION_INFO] {
+            match graph.object_for_subject_predicate(ontology, NamedNodeRef::new_unchecked(predicate)) {
+                Some(TermRef::NamedNode(node)) => return node.as_str().to_string(),
+                Some(TermRef::Literal(literal)) => return literal.value().to_string(),
+                _ => {}
+            }
+        }
+    }
+
+    let quads: Vec<Quad> = graph.iter().map(|triple| triple.into_owned().in_graph(GraphName::DefaultGraph)).collect();
+    let canonical = canonical::canonical_nquads(&quads).unwrap_or_else(|_| {
+        // Too symmetric to canonicalize; fall back to the sorted serialization
+        let mut lines: Vec<String> = graph.iter().map(|triple| triple.to_string()).collect();
+        lines.sort();
+        lines.concat()
+    });
+    let digest = Sha256::digest(canonical.as_bytes());
+    format!("sha256:{}", digest.iter().take(6).map(|byte| format!("{:02x}", byte)).collect::<String>())
+}
+
+/// Versions of the named ontology graphs of a store
+pub fn ontology_versions(store: &OxigraphStore, graph_names: &[String]) -> BTreeMap<String, String> {
+    graph_names
+        .iter()
+        .map(|name| (name.clone(), ontology_version(&store.union_graph(std::slice::from_ref(name)))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(id: &str, strategy: &str, materialized_triples: usize, duration_ms: u64, version: &str, warm_start: bool) -> InferenceRun {
+        InferenceRun {
+            id: id.to_string(),
+            started_at: Utc::now(),
+            strategy: strategy.to_string(),
+            source: "cli".to_string(),
+            ontology_versions: BTreeMap::from([("urn:epcis:ontology:cbv".to_string(), version.to_string())]),
+            tbox_triples: 100,
+            abox_triples: 50,
+            materialized_triples,
+            sparql_inferences: 0,
+            individuals_classified: 0,
+            consistent: true,
+            duration_ms,
+            config: serde_json::json!({ "warm_start": warm_start, "scope": { "abox_graphs": ["*"] } }),
+        }
+    }
+
+    #[test]
+    fn test_compare_reports_deltas_and_changes() {
+        let baseline = run("a", "full", 120, 400, "2.0", false);
+        let candidate = run("b", "incremental", 100, 100, "2.1", true);
+
+        let comparison = baseline.compare(&candidate);
+        assert!(comparison.strategy_changed);
+        assert_eq!(comparison.materialized_triples_delta, -20);
+        assert_eq!(comparison.duration_ms_delta, -300);
+        assert_eq!(comparison.duration_ratio, Some(0.25));
+        assert_eq!(comparison.changed_ontologies, vec!["urn:epcis:ontology:cbv"]);
+        assert_eq!(comparison.changed_config, vec!["warm_start"]);
+    }
+}
@@ -0,0 +1,109 @@
+use crate::api::access_control::graph_pattern_matches;
+use crate::config::InferenceScopeConfig;
+use crate::storage::oxigraph_store::is_derived_graph;
+use serde::Serialize;
+
+/// Role a named graph plays in reasoning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphRole {
+    TBox,
+    ABox,
+}
+
+/// Selects which named graphs form the TBox and the ABox.
+///
+/// A graph matching both a TBox and an ABox pattern is treated as TBox, so the
+/// default `abox_graphs = ["*"]` picks up everything that is not an ontology.
+#[derive(Debug, Clone)]
+pub struct InferenceScope {
+    config: InferenceScopeConfig,
+}
+
+/// Graphs selected by a scope, for reporting
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScopeSummary {
+    pub tbox_graphs: Vec<String>,
+    pub abox_graphs: Vec<String>,
+    pub tbox_triples: usize,
+    pub abox_triples: usize,
+}
+
+impl InferenceScope {
+    pub fn from_config(config: &InferenceScopeConfig) -> Self {
+        Self {
+            config: config.clone(),
+        }
+    }
+
+    /// Replace the TBox and/or ABox patterns, e.g. from an API request or CLI flags
+    pub fn with_overrides(mut self, tbox_graphs: Option<Vec<String>>, abox_graphs: Option<Vec<String>>) -> Self {
+        if let Some(tbox_graphs) = tbox_graphs.filter(|g| !g.is_empty()) {
+            self.config.tbox_graphs = tbox_graphs;
+        }
+        if let Some(abox_graphs) = abox_graphs.filter(|g| !g.is_empty()) {
+            self.config.abox_graphs = abox_graphs;
+        }
+        self
+    }
+
+    pub fn role(&self, graph: &str) -> Option<GraphRole> {
+        let matches_any = |patterns: &[String]| patterns.iter().any(|p| graph_pattern_matches(p, graph));
+
+        if is_derived_graph(graph) || matches_any(&self.config.exclude_graphs) {
+            None
+        } else if matches_any(&self.config.tbox_graphs) {
+            Some(GraphRole::TBox)
+        } else if matches_any(&self.config.abox_graphs) {
+            Some(GraphRole::ABox)
+        } else {
+            None
+        }
+    }
+
+    /// Split graph names into (TBox, ABox) graphs, dropping graphs outside the scope
+    pub fn partition(&self, graph_names: &[String]) -> (Vec<String>, Vec<String>) {
+        let mut tbox = Vec::new();
+        let mut abox = Vec::new();
+
+        for graph in graph_names {
+            match self.role(graph) {
+                Some(GraphRole::TBox) => tbox.push(graph.clone()),
+                Some(GraphRole::ABox) => abox.push(graph.clone()),
+                None => {}
+            }
+        }
+
+        (tbox, abox)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_scope_splits_ontologies_from_data() {
+        let scope = InferenceScope::from_config(&InferenceScopeConfig::default());
+        let graphs = vec![
+            "urn:epcis:ontology:ontologies:cbv.ttl".to_string(),
+            "urn:epcis:event:evt-1".to_string(),
+            "urn:epcis:inferred".to_string(),
+            "urn:epcis:sparql_inferred".to_string(),
+        ];
+
+        let (tbox, abox) = scope.partition(&graphs);
+        assert_eq!(tbox, vec!["urn:epcis:ontology:ontologies:cbv.ttl"]);
+        assert_eq!(abox, vec!["urn:epcis:event:evt-1"]);
+    }
+
+    #[test]
+    fn test_overrides_restrict_abox() {
+        let scope = InferenceScope::from_config(&InferenceScopeConfig::default())
+            .with_overrides(None, Some(vec!["urn:epcis:event:site-a*".to_string()]));
+
+        assert_eq!(scope.role("urn:epcis:event:site-a-1"), Some(GraphRole::ABox));
+        assert_eq!(scope.role("urn:epcis:event:site-b-1"), None);
+        assert_eq!(scope.role("urn:epcis:ontology:cbv"), Some(GraphRole::TBox));
+    }
+}
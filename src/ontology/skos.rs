@@ -0,0 +1,283 @@
+use crate::ontology::vocabulary::CbvVocabulary;
+use crate::EpcisKgError;
+use oxrdf::{Graph, Literal, NamedNode, NamedNodeRef, SubjectRef, TermRef, Triple};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Graphs holding registered vocabulary extensions; they count as ontology
+/// graphs, so profile checks and reasoning over stored ontologies include them
+pub const VOCABULARY_EXTENSION_GRAPH_PREFIX: &str = "urn:epcis:ontology:vocab:";
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const SKOS_CONCEPT_SCHEME: &str = "http://www.w3.org/2004/02/skos/core#ConceptScheme";
+const SKOS_CONCEPT: &str = "http://www.w3.org/2004/02/skos/core#Concept";
+const SKOS_IN_SCHEME: &str = "http://www.w3.org/2004/02/skos/core#inScheme";
+const SKOS_PREF_LABEL: &str = "http://www.w3.org/2004/02/skos/core#prefLabel";
+const SKOS_DEFINITION: &str = "http://www.w3.org/2004/02/skos/core#definition";
+const SKOS_BROADER: &str = "http://www.w3.org/2004/02/skos/core#broader";
+const DCT_TITLE: &str = "http://purl.org/dc/terms/title";
+const EXTENSION_NAMESPACE: &str = "urn:epcglobal:epcis:extensionNamespace";
+const EXTENDS_VOCABULARY: &str = "urn:epcglobal:epcis:extendsVocabulary";
+
+/// Prefixes of the standard vocabulary, which extensions may not claim
+const STANDARD_CBV_PREFIXES: &[&str] = &["urn:epcglobal:cbv:", "https://ref.gs1.org/cbv/"];
+
+/// A company's own bizSteps, dispositions or business transaction types,
+/// registered as a SKOS concept scheme.
+///
+/// Every concept is also typed with the class of the CBV vocabulary it
+/// extends (e.g. `cbv:BizStep`), so it appears in vocabulary autocomplete and
+/// passes the ontology checks of captured events like a standard term.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VocabularyExtension {
+    /// IRI of the `skos:ConceptScheme`
+    pub scheme: String,
+    /// CBV vocabulary extended: `bizsteps`, `dispositions` or `biztransactiontypes`
+    pub vocabulary: String,
+    /// IRI prefix of the extension's values, e.g. `urn:acme:bizstep:`
+    pub namespace: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    pub concepts: Vec<ExtensionConcept>,
+}
+
+/// One `skos:Concept` of an extension
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExtensionConcept {
+    pub iri: String,
+    /// `skos:prefLabel` per language tag; `""` for an untagged label
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub definition: Option<String>,
+    /// Broader concept: another concept of the scheme or a standard CBV term
+    #[serde(default)]
+    pub broader: Option<String>,
+}
+
+impl VocabularyExtension {
+    pub fn cbv_vocabulary(&self) -> Result<CbvVocabulary, EpcisKgError> {
+        CbvVocabulary::from_name(&self.vocabulary).ok_or_else(|| EpcisKgError::InvalidField {
+            field: "vocabulary".to_string(),
+            message: format!("unknown vocabulary '{}' (expected bizsteps, dispositions or biztransactiontypes)", self.vocabulary),
+        })
+    }
+
+    /// Check the scheme against SKOS integrity conditions and the extension rules
+    pub fn validate(&self) -> Result<(), EpcisKgError> {
+        self.cbv_vocabulary()?;
+        NamedNode::new(self.scheme.as_str())?;
+        let invalid = |field: String, message: String| EpcisKgError::InvalidField { field, message };
+        if self.namespace.trim().is_empty() {
+            return Err(invalid("namespace".to_string(), "an extension needs a namespace".to_string()));
+        }
+        if STANDARD_CBV_PREFIXES.iter().any(|prefix| self.namespace.starts_with(prefix) || prefix.starts_with(self.namespace.as_str())) {
+            return Err(invalid("namespace".to_string(), format!("{} overlaps the standard CBV", self.namespace)));
+        }
+        if self.concepts.is_empty() {
+            return Err(invalid("concepts".to_string(), "a concept scheme needs at least one concept".to_string()));
+        }
+
+        let iris: HashSet<&str> = self.concepts.iter().map(|concept| concept.iri.as_str()).collect();
+        if iris.len() != self.concepts.len() {
+            return Err(invalid("concepts".to_string(), "concept IRIs must be unique".to_string()));
+        }
+        let mut labels_seen: HashMap<(&str, String), &str> = HashMap::new();
+        for (index, concept) in self.concepts.iter().enumerate() {
+            let field = |name: &str| format!("concepts[{}].{}", index, name);
+            NamedNode::new(concept.iri.as_str())?;
+            if !concept.iri.starts_with(&self.namespace) {
+                return Err(invalid(field("iri"), format!("{} is outside the namespace {}", concept.iri, self.namespace)));
+            }
+            if concept.labels.values().all(|label| label.trim().is_empty()) {
+                return Err(invalid(field("labels"), format!("{} has no preferred label", concept.iri)));
+            }
+            // SKOS S14 allows one prefLabel per language; a label shared by two
+            // concepts would make them indistinguishable in a dropdown
+            for (language, label) in &concept.labels {
+                if let Some(other) = labels_seen.insert((language.as_str(), label.to_lowercase()), concept.iri.as_str()) {
+                    return Err(invalid(field("labels"), format!("'{}' labels both {} and {}", label, other, concept.iri)));
+                }
+            }
+            if let Some(broader) = &concept.broader {
+                let known = (iris.contains(broader.as_str()) && broader != &concept.iri)
+                    || STANDARD_CBV_PREFIXES.iter().any(|prefix| broader.starts_with(prefix));
+                if !known {
+                    return Err(invalid(field("broader"), format!("{} is neither a concept of the scheme nor a CBV term", broader)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// SKOS triples of the scheme, with each concept typed as a term of the extended vocabulary
+    pub fn to_triples(&self) -> Result<Vec<Triple>, EpcisKgError> {
+        let node = NamedNode::new_unchecked;
+        let scheme = NamedNode::new(self.scheme.as_str())?;
+        let class = node(self.cbv_vocabulary()?.class_iri());
+        let mut triples = vec![
+            Triple::new(scheme.clone(), node(RDF_TYPE), node(SKOS_CONCEPT_SCHEME)),
+            Triple::new(scheme.clone(), node(EXTENSION_NAMESPACE), Literal::new_simple_literal(self.namespace.clone())),
+            Triple::new(scheme.clone(), node(EXTENDS_VOCABULARY), class.clone()),
+        ];
+        if let Some(title) = &self.title {
+            triples.push(Triple::new(scheme.clone(), node(DCT_TITLE), Literal::new_simple_literal(title.clone())));
+        }
+
+        for concept in &self.concepts {
+            let subject = NamedNode::new(concept.iri.as_str())?;
+            triples.push(Triple::new(subject.clone(), node(RDF_TYPE), node(SKOS_CONCEPT)));
+            triples.push(Triple::new(subject.clone(), node(RDF_TYPE), class.clone()));
+            triples.push(Triple::new(subject.clone(), node(SKOS_IN_SCHEME), scheme.clone()));
+            for (language, label) in &concept.labels {
+                let literal = if language.is_empty() {
+                    Literal::new_simple_literal(label.clone())
+                } else {
+                    Literal::new_language_tagged_literal(label.clone(), language.clone()).map_err(|e| EpcisKgError::InvalidField {
+                        field: "labels".to_string(),
+                        message: format!("invalid language tag '{}': {}", language, e),
+                    })?
+                };
+                triples.push(Triple::new(subject.clone(), node(SKOS_PREF_LABEL), literal));
+            }
+            if let Some(definition) = &concept.definition {
+                triples.push(Triple::new(subject.clone(), node(SKOS_DEFINITION), Literal::new_simple_literal(definition.clone())));
+            }
+            if let Some(broader) = &concept.broader {
+                triples.push(Triple::new(subject.clone(), node(SKOS_BROADER), NamedNode::new(broader.as_str())?));
+            }
+        }
+        Ok(triples)
+    }
+
+    /// Read back an extension stored with `to_triples`
+    pub fn from_graph(graph: &Graph) -> Option<Self> {
+        let node = NamedNodeRef::new_unchecked;
+        let scheme = graph.subjects_for_predicate_object(node(RDF_TYPE), node(SKOS_CONCEPT_SCHEME)).find_map(|subject| match subject {
+            SubjectRef::NamedNode(scheme) => Some(scheme),
+            _ => None,
+        })?;
+        let literal = |subject: NamedNodeRef<'_>, predicate: &str| match graph.object_for_subject_predicate(subject, node(predicate)) {
+            Some(TermRef::Literal(literal)) => Some(literal.value().to_string()),
+            _ => None,
+        };
+        let class = match graph.object_for_subject_predicate(scheme, node(EXTENDS_VOCABULARY)) {
+            Some(TermRef::NamedNode(class)) => class.as_str(),
+            _ => return None,
+        };
+        let vocabulary = CbvVocabulary::ALL.iter().find(|vocabulary| vocabulary.class_iri() == class)?;
+
+        let mut concepts: Vec<ExtensionConcept> = graph
+            .subjects_for_predicate_object(node(SKOS_IN_SCHEME), scheme)
+            .filter_map(|subject| match subject {
+                SubjectRef::NamedNode(concept) => Some(concept),
+                _ => None,
+            })
+            .map(|concept| ExtensionConcept {
+                iri: concept.as_str().to_string(),
+                labels: graph
+                    .objects_for_subject_predicate(concept, node(SKOS_PREF_LABEL))
+                    .filter_map(|object| match object {
+                        TermRef::Literal(label) => Some((label.language().unwrap_or_default().to_string(), label.value().to_string())),
+                        _ => None,
+                    })
+                    .collect(),
+                definition: literal(concept, SKOS_DEFINITION),
+                broader: match graph.object_for_subject_predicate(concept, node(SKOS_BROADER)) {
+                    Some(TermRef::NamedNode(broader)) => Some(broader.as_str().to_string()),
+                    _ => None,
+                },
+            })
+            .collect();
+        concepts.sort_by(|a, b| a.iri.cmp(&b.iri));
+
+        Some(Self {
+            scheme: scheme.as_str().to_string(),
+            vocabulary: vocabulary.name().to_string(),
+            namespace: literal(scheme, EXTENSION_NAMESPACE)?,
+            title: literal(scheme, DCT_TITLE),
+            concepts,
+        })
+    }
+}
+
+/// Named graph of the extension with this scheme IRI.
+///
+/// Graph names become file names when the store is persisted, so the scheme
+/// IRI is reduced to safe characters plus a hash keeping the name unique.
+pub fn extension_graph_name(scheme: &str) -> String {
+    let digest = Sha256::digest(scheme.as_bytes());
+    let hash: String = digest.iter().take(6).map(|byte| format!("{:02x}", byte)).collect();
+    let readable: String = scheme
+        .rsplit(|c| c == '/' || c == '#' || c == ':')
+        .find(|segment| !segment.is_empty())
+        .unwrap_or("scheme")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    format!("{}{}-{}", VOCABULARY_EXTENSION_GRAPH_PREFIX, readable, hash)
+}
+
+/// Why a captured value is not acceptable for `vocabulary`: it uses the
+/// namespace of a registered extension but is not one of its concepts
+pub fn extension_violation(extensions: &[VocabularyExtension], vocabulary: CbvVocabulary, value: &str) -> Option<String> {
+    extensions
+        .iter()
+        .filter(|extension| extension.cbv_vocabulary().ok() == Some(vocabulary) && value.starts_with(&extension.namespace))
+        .find(|extension| !extension.concepts.iter().any(|concept| concept.iri == value))
+        .map(|extension| format!("'{}' is not a concept of the {} extension {}", value, extension.vocabulary, extension.scheme))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn picking_extension() -> VocabularyExtension {
+        VocabularyExtension {
+            scheme: "https://acme.example/vocab/bizsteps".to_string(),
+            vocabulary: "bizsteps".to_string(),
+            namespace: "urn:acme:bizstep:".to_string(),
+            title: Some("ACME warehouse steps".to_string()),
+            concepts: vec![
+                ExtensionConcept {
+                    iri: "urn:acme:bizstep:picking".to_string(),
+                    labels: BTreeMap::from([("en".to_string(), "Picking".to_string()), ("de".to_string(), "Kommissionierung".to_string())]),
+                    definition: Some("Collecting items for an order".to_string()),
+                    broader: Some("urn:epcglobal:cbv:storing".to_string()),
+                },
+                ExtensionConcept {
+                    iri: "urn:acme:bizstep:wave_picking".to_string(),
+                    labels: BTreeMap::from([("".to_string(), "Wave picking".to_string())]),
+                    definition: None,
+                    broader: Some("urn:acme:bizstep:picking".to_string()),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_extension_round_trip_and_value_checks() {
+        let extension = picking_extension();
+        extension.validate().unwrap();
+
+        let graph: Graph = extension.to_triples().unwrap().iter().collect();
+        assert_eq!(VocabularyExtension::from_graph(&graph).unwrap(), extension);
+        assert!(extension_graph_name(&extension.scheme).starts_with("urn:epcis:ontology:vocab:bizsteps-"));
+
+        let extensions = [extension.clone()];
+        assert!(extension_violation(&extensions, CbvVocabulary::BizSteps, "urn:acme:bizstep:picking").is_none());
+        assert!(extension_violation(&extensions, CbvVocabulary::BizSteps, "urn:acme:bizstep:pickng").is_some());
+        assert!(extension_violation(&extensions, CbvVocabulary::Dispositions, "urn:acme:bizstep:pickng").is_none());
+        assert!(extension_violation(&extensions, CbvVocabulary::BizSteps, "shipping").is_none());
+
+        let mut outside = extension.clone();
+        outside.concepts[1].iri = "urn:other:packing".to_string();
+        assert!(outside.validate().is_err());
+        let mut standard = extension.clone();
+        standard.namespace = "urn:epcglobal:cbv:bizstep:".to_string();
+        assert!(standard.validate().is_err());
+        let mut dangling = extension;
+        dangling.concepts[0].broader = Some("urn:acme:bizstep:unknown".to_string());
+        assert!(dangling.validate().is_err());
+    }
+}
@@ -0,0 +1,171 @@
+use crate::storage::oxigraph_store::OxigraphStore;
+use crate::utils::i18n::language_matches;
+use oxrdf::{NamedNodeRef, Subject, Term};
+use serde::Serialize;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDFS_LABEL: &str = "http://www.w3.org/2000/01/rdf-schema#label";
+const RDFS_COMMENT: &str = "http://www.w3.org/2000/01/rdf-schema#comment";
+const SKOS_PREF_LABEL: &str = "http://www.w3.org/2004/02/skos/core#prefLabel";
+const SKOS_DEFINITION: &str = "http://www.w3.org/2004/02/skos/core#definition";
+
+/// CBV vocabularies offered for autocompletion
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CbvVocabulary {
+    BizSteps,
+    Dispositions,
+    BizTransactionTypes,
+}
+
+/// One vocabulary term, as shown in a dropdown
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VocabularyTerm {
+    pub iri: String,
+    pub label: String,
+    /// Language tag of the label, when it is tagged
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    pub definition: Option<String>,
+}
+
+impl CbvVocabulary {
+    pub const ALL: [CbvVocabulary; 3] = [Self::BizSteps, Self::Dispositions, Self::BizTransactionTypes];
+
+    /// Parse the URL segment (`bizsteps`, `dispositions`, `biztransactiontypes`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "bizsteps" => Some(Self::BizSteps),
+            "dispositions" => Some(Self::Dispositions),
+            "biztransactiontypes" => Some(Self::BizTransactionTypes),
+            _ => None,
+        }
+    }
+
+    /// URL segment naming the vocabulary
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::BizSteps => "bizsteps",
+            Self::Dispositions => "dispositions",
+            Self::BizTransactionTypes => "biztransactiontypes",
+        }
+    }
+
+    /// Class whose instances make up the vocabulary
+    pub fn class_iri(&self) -> &'static str {
+        match self {
+            Self::BizSteps => "urn:epcglobal:cbv:BizStep",
+            Self::Dispositions => "urn:epcglobal:cbv:Disposition",
+            Self::BizTransactionTypes => "urn:epcglobal:cbv:BusinessTransaction",
+        }
+    }
+
+    /// Terms of this vocabulary found in the store, sorted by label.
+    ///
+    /// `search` keeps terms whose label or IRI local name starts with it
+    /// (case-insensitive); terms typed in several graphs are listed once.
+    /// Labels and definitions are taken in the first of `languages` that has
+    /// one, else untagged, else English.
+    pub fn terms(&self, store: &OxigraphStore, search: Option<&str>, languages: &[String]) -> Vec<VocabularyTerm> {
+        let search = search.map(str::to_lowercase).filter(|s| !s.is_empty());
+        let class = NamedNodeRef::new_unchecked(self.class_iri());
+        let mut terms: Vec<VocabularyTerm> = Vec::new();
+
+        for quad in store.quads_for_pattern(None, Some(NamedNodeRef::new_unchecked(RDF_TYPE)), Some(class.into()), None) {
+            let Subject::NamedNode(term) = quad.subject else { continue };
+            if terms.iter().any(|existing| existing.iri == term.as_str()) {
+                continue;
+            }
+
+            let local_name = local_name(term.as_str());
+            let (label, language) = literal_value(store, term.as_ref(), &[RDFS_LABEL, SKOS_PREF_LABEL], languages)
+                .unwrap_or_else(|| (local_name.to_string(), None));
+            let matches = match search.as_deref() {
+                Some(prefix) => label.to_lowercase().starts_with(prefix) || local_name.to_lowercase().starts_with(prefix),
+                None => true,
+            };
+
+            if matches {
+                terms.push(VocabularyTerm {
+                    iri: term.as_str().to_string(),
+                    definition: literal_value(store, term.as_ref(), &[SKOS_DEFINITION, RDFS_COMMENT], languages).map(|(text, _)| text),
+                    label,
+                    language,
+                });
+            }
+        }
+
+        terms.sort_by_cached_key(|term| term.label.to_lowercase());
+        terms
+    }
+}
+
+/// Last path, fragment or URN segment of an IRI
+fn local_name(iri: &str) -> &str {
+    iri.rsplit(|c| c == '#' || c == '/' || c == ':').next().unwrap_or(iri)
+}
+
+/// Literal of the first predicate that has one, in any graph, with its language tag.
+///
+/// Literals in an earlier of `languages` win (a `de` label serves `de-AT`), then
+/// untagged ones, then English, then any.
+fn literal_value(store: &OxigraphStore, subject: NamedNodeRef<'_>, predicates: &[&str], languages: &[String]) -> Option<(String, Option<String>)> {
+    predicates.iter().find_map(|predicate| {
+        let literals: Vec<(String, Option<String>)> = store
+            .quads_for_pattern(Some(subject.into()), Some(NamedNodeRef::new_unchecked(predicate)), None, None)
+            .into_iter()
+            .filter_map(|quad| match quad.object {
+                Term::Literal(literal) => Some((literal.value().to_string(), literal.language().map(str::to_string))),
+                _ => None,
+            })
+            .collect();
+        let rank = |language: &Option<String>| match language {
+            Some(tag) => languages
+                .iter()
+                .position(|range| language_matches(tag, range) || language_matches(range, tag))
+                .unwrap_or(if language_matches(tag, "en") { languages.len() + 1 } else { languages.len() + 2 }),
+            None => languages.len(),
+        };
+        literals.into_iter().min_by_key(|(_, language)| rank(language))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxttl::TurtleParser;
+
+    fn cbv_store() -> OxigraphStore {
+        let turtle = r#"
+            @prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+            @prefix cbv: <urn:epcglobal:cbv:> .
+            cbv:shipping a cbv:BizStep ; rdfs:label "Shipping" ; rdfs:comment "Shipping goods" .
+            cbv:storing a cbv:BizStep ; rdfs:label "Storing" , "Einlagerung"@de .
+            cbv:recalled a cbv:Disposition ; rdfs:label "Recalled" .
+        "#;
+        let triples: Vec<_> = TurtleParser::new().for_reader(turtle.as_bytes()).map(Result::unwrap).collect();
+        let mut store = OxigraphStore::new_memory().unwrap();
+        store.insert_triples("urn:epcis:ontology:cbv", &triples).unwrap();
+        store
+    }
+
+    #[test]
+    fn test_terms_by_vocabulary_and_prefix() {
+        let store = cbv_store();
+
+        let biz_steps = CbvVocabulary::BizSteps.terms(&store, None, &[]);
+        assert_eq!(biz_steps.len(), 2);
+        assert_eq!(biz_steps[0].label, "Shipping");
+        assert_eq!(biz_steps[0].definition.as_deref(), Some("Shipping goods"));
+
+        let searched = CbvVocabulary::BizSteps.terms(&store, Some("STO"), &[]);
+        assert_eq!(searched.len(), 1);
+        assert_eq!(searched[0].iri, "urn:epcglobal:cbv:storing");
+
+        let german = CbvVocabulary::BizSteps.terms(&store, None, &["de-at".to_string()]);
+        assert_eq!((german[0].label.as_str(), german[0].language.as_deref()), ("Einlagerung", Some("de")));
+        assert_eq!((german[1].label.as_str(), german[1].language.as_deref()), ("Shipping", None));
+
+        assert_eq!(CbvVocabulary::from_name("dispositions"), Some(CbvVocabulary::Dispositions));
+        assert!(CbvVocabulary::from_name("unknown").is_none());
+    }
+}
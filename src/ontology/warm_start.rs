@@ -0,0 +1,291 @@
+use crate::EpcisKgError;
+use crate::ontology::classification;
+use crate::ontology::loader::OntologyData;
+use crate::ontology::reasoner::IndexStructures;
+use owl2_rs::{Axiom, Class, ClassExpression, Individual, IRI, ObjectProperty, ObjectPropertyExpression, Ontology};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever the snapshot layout or the RDF-to-OWL conversion changes
+const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+pub(crate) const OWL_THING: &str = "http://www.w3.org/2002/07/owl#Thing";
+
+/// Converted reasoner state for one ontology, keyed by its content hash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReasonerSnapshot {
+    pub format_version: u32,
+    pub content_hash: String,
+    pub source_file: String,
+    pub axioms: Vec<CachedAxiom>,
+    /// Class -> all (transitive) superclasses
+    pub class_hierarchy: HashMap<String, Vec<String>>,
+    pub indexes: IndexStructures,
+}
+
+/// Serializable form of the axioms produced by the RDF-to-OWL conversion
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CachedAxiom {
+    SubClassOf { sub_class: String, super_class: String },
+    ObjectPropertyDomain { property: String, domain: String },
+    ClassAssertion { class: String, individual: String },
+}
+
+impl CachedAxiom {
+    /// Convert an OWL axiom; `None` for axiom kinds the conversion never produces
+    pub fn from_axiom(axiom: &Axiom) -> Option<Self> {
+        match axiom {
+            Axiom::Class(owl2_rs::ClassAxiom::SubClassOf {
+                sub_class: ClassExpression::Class(Class(IRI(sub_class))),
+                super_class: ClassExpression::Class(Class(IRI(super_class))),
+            }) => Some(Self::SubClassOf {
+                sub_class: sub_class.clone(),
+                super_class: super_class.clone(),
+            }),
+            Axiom::ObjectProperty(owl2_rs::ObjectPropertyAxiom::ObjectPropertyDomain {
+                property: ObjectPropertyExpression::ObjectProperty(ObjectProperty(IRI(property))),
+                domain: ClassExpression::Class(Class(IRI(domain))),
+            }) => Some(Self::ObjectPropertyDomain {
+                property: property.clone(),
+                domain: domain.clone(),
+            }),
+            Axiom::Assertion(owl2_rs::Assertion::ClassAssertion {
+                class: ClassExpression::Class(Class(IRI(class))),
+                individual: Individual::Named(IRI(individual)),
+            }) => Some(Self::ClassAssertion {
+                class: class.clone(),
+                individual: individual.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn to_axiom(&self) -> Axiom {
+        let class = |iri: &str| ClassExpression::Class(Class(IRI(iri.to_string())));
+        match self {
+            Self::SubClassOf { sub_class, super_class } => Axiom::Class(owl2_rs::ClassAxiom::SubClassOf {
+                sub_class: class(sub_class),
+                super_class: class(super_class),
+            }),
+            Self::ObjectPropertyDomain { property, domain } => {
+                Axiom::ObjectProperty(owl2_rs::ObjectPropertyAxiom::ObjectPropertyDomain {
+                    property: ObjectPropertyExpression::ObjectProperty(ObjectProperty(IRI(property.clone()))),
+                    domain: class(domain),
+                })
+            }
+            Self::ClassAssertion { class: class_iri, individual } => {
+                Axiom::Assertion(owl2_rs::Assertion::ClassAssertion {
+                    class: class(class_iri),
+                    individual: Individual::Named(IRI(individual.clone())),
+                })
+            }
+        }
+    }
+}
+
+impl ReasonerSnapshot {
+    /// Capture the converted ontology; `None` if it holds axioms that cannot be cached.
+    ///
+    /// The class hierarchy is classified per connected component in parallel
+    /// from `parallel_min_classes` classes (see `classification::classify`).
+    pub fn capture(
+        content_hash: String,
+        source_file: &str,
+        ontology: &Ontology,
+        indexes: &IndexStructures,
+        parallel_min_classes: usize,
+    ) -> Option<Self> {
+        let axioms = ontology
+            .axioms
+            .iter()
+            .map(CachedAxiom::from_axiom)
+            .collect::<Option<Vec<_>>>()?;
+        let class_hierarchy = classification::classify(&axioms, parallel_min_classes);
+
+        Some(Self {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            content_hash,
+            source_file: source_file.to_string(),
+            axioms,
+            class_hierarchy,
+            indexes: indexes.clone(),
+        })
+    }
+
+    pub fn to_ontology(&self) -> Ontology {
+        let mut ontology = Ontology::default();
+        ontology.axioms.extend(self.axioms.iter().map(CachedAxiom::to_axiom));
+        ontology
+    }
+}
+
+/// Transitive superclasses of every class named in a SubClassOf axiom
+pub fn class_hierarchy_closure(axioms: &[CachedAxiom]) -> HashMap<String, Vec<String>> {
+    let mut direct: HashMap<&str, Vec<&str>> = HashMap::new();
+    for axiom in axioms {
+        if let CachedAxiom::SubClassOf { sub_class, super_class } = axiom {
+            direct.entry(sub_class.as_str()).or_default().push(super_class.as_str());
+        }
+    }
+
+    let mut closure = HashMap::new();
+    for class in direct.keys() {
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut pending = direct[class].clone();
+        while let Some(super_class) = pending.pop() {
+            if super_class != *class && seen.insert(super_class) {
+                if let Some(parents) = direct.get(super_class) {
+                    pending.extend(parents.iter().copied());
+                }
+            }
+        }
+
+        let mut supers: Vec<String> = seen.into_iter().filter(|c| *c != OWL_THING).map(String::from).collect();
+        supers.sort();
+        closure.insert(class.to_string(), supers);
+    }
+
+    closure
+}
+
+/// Order-independent SHA-256 hash of an ontology's triples
+pub fn ontology_content_hash(ontology_data: &OntologyData) -> String {
+    let mut lines: Vec<String> = ontology_data.graph.iter().map(|triple| triple.to_string()).collect();
+    lines.sort_unstable();
+
+    let mut hasher = Sha256::new();
+    hasher.update(SNAPSHOT_FORMAT_VERSION.to_le_bytes());
+    for line in &lines {
+        hasher.update(line.as_bytes());
+        hasher.update(b"\n");
+    }
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// On-disk store of reasoner snapshots, one JSON file per content hash
+#[derive(Debug, Clone)]
+pub struct ReasonerCache {
+    directory: PathBuf,
+}
+
+impl ReasonerCache {
+    pub fn new<P: AsRef<Path>>(directory: P) -> Self {
+        Self {
+            directory: directory.as_ref().to_path_buf(),
+        }
+    }
+
+    fn snapshot_path(&self, content_hash: &str) -> PathBuf {
+        self.directory.join(format!("{}.json", content_hash))
+    }
+
+    /// Load the snapshot for a content hash; stale or unreadable snapshots are ignored
+    pub fn load(&self, content_hash: &str) -> Option<ReasonerSnapshot> {
+        let content = std::fs::read_to_string(self.snapshot_path(content_hash)).ok()?;
+        let snapshot: ReasonerSnapshot = serde_json::from_str(&content).ok()?;
+
+        (snapshot.format_version == SNAPSHOT_FORMAT_VERSION && snapshot.content_hash == content_hash)
+            .then_some(snapshot)
+    }
+
+    /// Write a snapshot atomically (temporary file, then rename)
+    pub fn save(&self, snapshot: &ReasonerSnapshot) -> Result<(), EpcisKgError> {
+        std::fs::create_dir_all(&self.directory)?;
+
+        let path = self.snapshot_path(&snapshot.content_hash);
+        let temp_path = path.with_extension("json.tmp");
+        std::fs::write(&temp_path, serde_json::to_string(snapshot)?)?;
+        std::fs::rename(&temp_path, &path)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sub_class(sub: &str, sup: &str) -> CachedAxiom {
+        CachedAxiom::SubClassOf {
+            sub_class: sub.to_string(),
+            super_class: sup.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_class_hierarchy_closure_is_transitive() {
+        let axioms = vec![
+            sub_class("urn:ex:ObjectEvent", "urn:ex:Event"),
+            sub_class("urn:ex:Event", OWL_THING),
+            sub_class("urn:ex:Event", "urn:ex:Resource"),
+        ];
+
+        let closure = class_hierarchy_closure(&axioms);
+        assert_eq!(closure["urn:ex:ObjectEvent"], vec!["urn:ex:Event", "urn:ex:Resource"]);
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let directory = std::env::temp_dir().join(format!("epcis-reasoner-cache-{}", std::process::id()));
+        let cache = ReasonerCache::new(&directory);
+
+        let mut ontology = Ontology::default();
+        ontology.axioms.push(sub_class("urn:ex:ObjectEvent", "urn:ex:Event").to_axiom());
+        let snapshot = ReasonerSnapshot::capture("abc123".to_string(), "test.ttl", &ontology, &IndexStructures::new(), usize::MAX).unwrap();
+
+        cache.save(&snapshot).unwrap();
+        let loaded = cache.load("abc123").unwrap();
+        assert_eq!(loaded.axioms, snapshot.axioms);
+        assert!(cache.load("other-hash").is_none());
+
+        std::fs::remove_dir_all(&directory).ok();
+    }
+
+    #[test]
+    fn test_warm_loads_merge_every_ontology() {
+        let directory = tempfile::tempdir().unwrap();
+        let sub_class_of = |source: &str, sub: &str, sup: &str| {
+            let mut graph = oxrdf::Graph::default();
+            graph.insert(&oxrdf::Triple::new(
+                oxrdf::NamedNode::new_unchecked(sub),
+                oxrdf::NamedNode::new_unchecked("http://www.w3.org/2000/01/rdf-schema#subClassOf"),
+                oxrdf::NamedNode::new_unchecked(sup),
+            ));
+            OntologyData { graph, triples_count: 1, source_file: source.to_string() }
+        };
+        let events = sub_class_of("events.ttl", "urn:ex:ObjectEvent", "urn:ex:Event");
+        let resources = sub_class_of("resources.ttl", "urn:ex:Event", "urn:ex:Resource");
+
+        let load_both = || {
+            let mut reasoner = crate::ontology::reasoner::OntologyReasoner::new();
+            reasoner.enable_warm_start(directory.path());
+            let warm = (reasoner.load_ontology_data_warm(&events).unwrap(), reasoner.load_ontology_data_warm(&resources).unwrap());
+            (reasoner, warm)
+        };
+        let (cold, warm) = load_both();
+        assert_eq!(warm, (false, false));
+        let (restored, warm) = load_both();
+        assert_eq!(warm, (true, true));
+        for reasoner in [&cold, &restored] {
+            assert_eq!(reasoner.superclasses_of("urn:ex:ObjectEvent"), ["urn:ex:Event", "urn:ex:Resource"]);
+            assert_eq!(reasoner.superclasses_of("urn:ex:Event"), ["urn:ex:Resource"]);
+        }
+
+        let indexes = |ontology: &OntologyData| {
+            let mut indexes = IndexStructures::new();
+            indexes.build_indexes(&ontology.graph.iter().map(|triple| triple.into_owned()).collect::<Vec<_>>());
+            indexes
+        };
+        let mut merged = indexes(&events);
+        merged.merge(&indexes(&resources));
+        assert_eq!(merged.find_subjects_by_property("http://www.w3.org/2000/01/rdf-schema#subClassOf").len(), 2);
+    }
+}
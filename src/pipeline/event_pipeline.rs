@@ -1,12 +1,26 @@
 use crate::models::epcis::EpcisEvent;
 use crate::models::events::{EventProcessor, ProcessingResult, ValidationResult};
+use crate::models::extensions::ExtensionRegistry;
+use crate::models::identifiers::{self, IdentifierNormalizer};
+use crate::pipeline::hooks::{EventHook, HookContribution, HookRegistry};
+use crate::pipeline::lanes::{IngestLanes, IngestPriority, LaneMetrics};
+use crate::pipeline::mappers::{self, EventMapper, MapperRegistry};
+use crate::pipeline::strict::Findings;
 use crate::storage::oxigraph_store::OxigraphStore;
-use crate::ontology::reasoner::OntologyReasoner;
+use crate::ontology::reasoner::{OntologyReasoner, PROVENANCE_GRAPH};
+use crate::ontology::reasoner_pool::ReasonerPool;
 use crate::ontology::loader::OntologyLoader;
-use crate::config::AppConfig;
+use crate::ontology::correction::{self, AutoCorrection};
+use crate::ontology::drift::{OntologyTerms, SchemaDriftReport, SchemaDriftTracker};
+use crate::ontology::skos::extension_violation;
+use crate::ontology::vocabulary::CbvVocabulary;
+use crate::config::{AppConfig, ScriptingConfig, WarningKind};
+use crate::utils::batching::{self, AdaptiveBatchSizer, BatchDecision};
+use crate::utils::masking::{pii_graph_name, MaskedTriples, PiiMasker};
 use crate::EpcisKgError;
+use parking_lot::{Mutex, RwLock};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tracing::{info, warn};
 
 /// EPCIS Event Processing Pipeline
 /// 
@@ -18,13 +32,36 @@ use tokio::sync::RwLock;
 /// 5. Persistence and storage
 pub struct EpcisEventPipeline {
     config: Arc<AppConfig>,
-    store: Arc<OxigraphStore>,
-    reasoner: Arc<RwLock<OntologyReasoner>>,
+    store: OxigraphStore,
+    reasoners: Arc<ReasonerPool>,
     loader: Arc<OntologyLoader>,
     event_processor: Arc<EventProcessor>,
+    extensions: Arc<RwLock<ExtensionRegistry>>,
+    hooks: Arc<RwLock<HookRegistry>>,
+    mappers: Arc<RwLock<MapperRegistry>>,
+    batch_sizer: AdaptiveBatchSizer,
+    lanes: Arc<Mutex<IngestLanes>>,
+    ontology_terms: OntologyTerms,
+    schema_drift: Arc<Mutex<SchemaDriftTracker>>,
+    masker: Option<Arc<PiiMasker>>,
+    identifiers: IdentifierNormalizer,
     processing_stats: ProcessingStats,
 }
 
+#[cfg(feature = "scripting")]
+fn register_scripts(hooks: &mut HookRegistry, config: &ScriptingConfig) -> Result<(), EpcisKgError> {
+    let scripts = crate::pipeline::scripting::ScriptHooks::new(config)?;
+    hooks.register(Arc::new(scripts), config.priority);
+    Ok(())
+}
+
+#[cfg(not(feature = "scripting"))]
+fn register_scripts(_hooks: &mut HookRegistry, _config: &ScriptingConfig) -> Result<(), EpcisKgError> {
+    Err(EpcisKgError::Config(
+        "Event scripting is not available; rebuild with `--features scripting`".to_string(),
+    ))
+}
+
 /// Processing statistics for the pipeline
 #[derive(Debug, Clone, Default, serde::Serialize)]
 pub struct ProcessingStats {
@@ -35,38 +72,151 @@ pub struct ProcessingStats {
     pub processing_errors: usize,
     pub average_processing_time_ms: f64,
     pub last_processed_time: Option<chrono::DateTime<chrono::Utc>>,
+    /// Most recent ingestion batch size decisions, oldest first
+    pub batch_decisions: Vec<BatchDecision>,
+    /// Wait and processing times per ingestion lane
+    pub lanes: LaneMetrics,
+    /// Business steps and dispositions replaced by their closest CBV term
+    pub auto_corrections: usize,
 }
 
 impl EpcisEventPipeline {
     /// Create a new event processing pipeline
+    ///
+    /// The ontology loaded into `reasoner` (if any) becomes the TBox shared by
+    /// the pipeline's reasoner pool; otherwise the pool loads the configured
+    /// ontology paths on first use.
     pub async fn new(
         config: AppConfig,
         store: OxigraphStore,
         reasoner: OntologyReasoner,
+    ) -> Result<Self, EpcisKgError> {
+        let reasoners = match reasoner.get_owl_ontology() {
+            Some(tbox) => ReasonerPool::from_config_with_tbox(&config, tbox.clone()),
+            None => ReasonerPool::from_config(&config),
+        };
+        Self::with_reasoner_pool(config, store, Arc::new(reasoners)).await
+    }
+    
+    /// Create a pipeline that checks reasoners out of a shared pool
+    pub async fn with_reasoner_pool(
+        config: AppConfig,
+        store: OxigraphStore,
+        reasoners: Arc<ReasonerPool>,
     ) -> Result<Self, EpcisKgError> {
         let config = Arc::new(config);
-        let store = Arc::new(store);
-        let reasoner = Arc::new(RwLock::new(reasoner));
-        let loader = Arc::new(OntologyLoader::new());
+        let loader = Arc::new(OntologyLoader::with_config(&config));
         let event_processor = Arc::new(EventProcessor::new());
+        let extensions = Arc::new(RwLock::new(ExtensionRegistry::from_config(&config.extensions)?));
+        let mut hooks = HookRegistry::new();
+        if config.scripting.enabled {
+            register_scripts(&mut hooks, &config.scripting)?;
+        }
+        let masker = PiiMasker::from_config(&config.pii_masking)?.map(Arc::new);
         
         Ok(Self {
+            batch_sizer: AdaptiveBatchSizer::new(config.batching.ingestion.clone()),
+            identifiers: IdentifierNormalizer::from_config(&config.identifiers),
+            ontology_terms: store.ontology_terms(),
             config,
             store,
-            reasoner,
+            reasoners,
             loader,
             event_processor,
+            extensions,
+            hooks: Arc::new(RwLock::new(hooks)),
+            mappers: Arc::new(RwLock::new(MapperRegistry::new())),
+            lanes: Arc::new(Mutex::new(IngestLanes::new())),
+            schema_drift: Arc::new(Mutex::new(SchemaDriftTracker::new())),
+            masker,
             processing_stats: ProcessingStats::default(),
         })
     }
     
+    /// Extension schema registry, shared so namespaces registered at runtime apply to later events
+    pub fn extension_registry(&self) -> Arc<RwLock<ExtensionRegistry>> {
+        Arc::clone(&self.extensions)
+    }
+    
+    /// Custom event hooks, shared so hooks registered at runtime apply to later events
+    pub fn hook_registry(&self) -> Arc<RwLock<HookRegistry>> {
+        Arc::clone(&self.hooks)
+    }
+    
+    /// Unknown predicates and classes seen in the events processed so far
+    pub fn schema_drift(&self) -> SchemaDriftReport {
+        self.schema_drift.lock().report()
+    }
+    
+    /// Event type mappers, shared so mappers registered at runtime apply to later events
+    pub fn mapper_registry(&self) -> Arc<RwLock<MapperRegistry>> {
+        Arc::clone(&self.mappers)
+    }
+    
+    /// Map a custom event type to RDF, or replace the mapping of a built-in one
+    pub fn register_mapper(&self, mapper: Arc<dyn EventMapper>) {
+        self.mappers.write().register(mapper);
+    }
+    
+    /// Register a custom event hook; lower priorities run first
+    pub fn register_hook(&self, hook: Arc<dyn EventHook>, priority: i32) {
+        self.hooks.write().register(hook, priority);
+    }
+    
     /// Process a single EPCIS event through the complete pipeline
-    pub async fn process_event(&mut self, event: EpcisEvent) -> Result<ProcessingResult, EpcisKgError> {
+    pub async fn process_event(&mut self, mut event: EpcisEvent) -> Result<ProcessingResult, EpcisKgError> {
         let start_time = std::time::Instant::now();
         let event_id = event.event_id.clone();
+        let hooks = self.hooks.read().hooks();
+        
+        // Step 0: Let custom hooks enrich (or reject) the event
+        for hook in &hooks {
+            if let Err(e) = hook.before_validate(&mut event) {
+                self.update_stats(false, false, start_time).await;
+                return Ok(ProcessingResult {
+                    event_id,
+                    success: false,
+                    processing_time_ms: start_time.elapsed().as_millis() as u64,
+                    error: Some(format!("Hook '{}' rejected the event: {}", hook.name(), e)),
+                    triples_generated: 0,
+                    inferences_made: 0,
+                });
+            }
+        }
+        
+        // Misspelled CBV values are corrected before hooks and validation see them
+        let corrections = self.auto_correct(&mut event);
+        // Digital Links are stored as EPC URNs, linked to the captured form by owl:sameAs
+        let same_as = self.normalize_identifiers(&mut event);
+        
+        let mut contribution = HookContribution::default();
+        for hook in &hooks {
+            match hook.contribute(&event) {
+                Ok(added) => contribution.merge(added),
+                Err(e) => contribution.errors.push(format!("Hook '{}' failed: {}", hook.name(), e)),
+            }
+        }
+        
+        if !corrections.is_empty() {
+            for fix in &corrections {
+                info!(
+                    "Event {}: corrected {} '{}' to '{}' (confidence {:.2})",
+                    event.event_id, fix.field, fix.original, fix.corrected, fix.confidence
+                );
+            }
+            self.processing_stats.auto_corrections += corrections.len();
+            contribution.triples.extend(correction::correction_triples(&event.event_id, &corrections, chrono::Utc::now()));
+        }
+        contribution.triples.extend(same_as);
         
         // Step 1: Validate the event
-        let validation_result = self.validate_event(&event)?;
+        let mut validation_result = self.validate_event(&event)?;
+        validation_result.is_valid &= contribution.errors.is_empty();
+        validation_result.errors.extend(contribution.errors);
+        for warning in &contribution.warnings {
+            warn!("Event {}: {}", event.event_id, warning);
+        }
+        validation_result.warnings.extend(contribution.warnings);
         if !validation_result.is_valid {
             self.update_stats(false, true, start_time).await;
             return Ok(ProcessingResult {
@@ -80,17 +230,29 @@ impl EpcisEventPipeline {
         }
         
         // Step 2: Process the event (transform to RDF)
-        let processing_result = self.process_event_internal(&event).await?;
+        let processing_result = self.process_event_internal(&event, &contribution.triples).await?;
         if !processing_result.success {
             self.update_stats(false, false, start_time).await;
             return Ok(processing_result);
         }
         
-        // Step 3: Store the event in the knowledge graph
-        self.store_event(&event, &processing_result).await?;
-        
-        // Step 4: Perform reasoning and inference
-        let inferences_count = self.perform_reasoning(&event).await?;
+        // Steps 3 and 4 write in one transaction, unless a batch already opened one
+        let own_transaction = !self.store.in_transaction();
+        if own_transaction {
+            self.store.begin()?;
+        }
+        let inferences_count = match self.store_and_reason(&event, &processing_result, &contribution.triples, &hooks).await {
+            Ok(count) => count,
+            Err(e) => {
+                if own_transaction {
+                    self.store.rollback()?;
+                }
+                return Err(e);
+            }
+        };
+        if own_transaction {
+            self.store.commit()?;
+        }
         
         // Step 5: Update statistics
         let final_result = ProcessingResult {
@@ -98,170 +260,295 @@ impl EpcisEventPipeline {
             success: true,
             processing_time_ms: start_time.elapsed().as_millis() as u64,
             error: None,
-            triples_generated: processing_result.triples_generated,
+            triples_generated: processing_result.triples_generated + contribution.triples.len(),
             inferences_made: inferences_count,
         };
+        for hook in &hooks {
+            if let Err(e) = hook.after_inference(&event, &final_result) {
+                warn!("Hook '{}' failed after inference on event {}: {}", hook.name(), event.event_id, e);
+            }
+        }
         
         self.update_stats(true, false, start_time).await;
         
         Ok(final_result)
     }
     
+    /// Step 3, storing the event in the knowledge graph, and step 4, reasoning over it
+    async fn store_and_reason(
+        &mut self,
+        event: &EpcisEvent,
+        processing_result: &ProcessingResult,
+        hook_triples: &[oxrdf::Triple],
+        hooks: &[Arc<dyn EventHook>],
+    ) -> Result<usize, EpcisKgError> {
+        self.store_event(event, hook_triples).await?;
+        for hook in hooks {
+            if let Err(e) = hook.after_store(event, processing_result) {
+                warn!("Hook '{}' failed after storing event {}: {}", hook.name(), event.event_id, e);
+            }
+        }
+        
+        self.perform_reasoning(event, hook_triples).await
+    }
+    
     /// Process multiple events in batch
+    ///
+    /// The events are queued as backfill; see `process_events_with_priority`.
     pub async fn process_events_batch(&mut self, events: Vec<EpcisEvent>) -> Vec<ProcessingResult> {
+        self.process_events_with_priority(events, IngestPriority::Backfill).await
+    }
+    
+    /// Queue events in the lane of `priority` and process everything queued
+    pub async fn process_events_with_priority(&mut self, events: Vec<EpcisEvent>, priority: IngestPriority) -> Vec<ProcessingResult> {
+        self.enqueue(priority, events);
+        self.process_queued().await
+    }
+    
+    /// Queue events without processing them yet
+    pub fn enqueue(&self, priority: IngestPriority, events: Vec<EpcisEvent>) {
+        self.lanes.lock().push(priority, events);
+    }
+    
+    /// The ingestion lanes, shared so real-time events can be queued while a backfill is processed
+    pub fn ingest_lanes(&self) -> Arc<Mutex<IngestLanes>> {
+        Arc::clone(&self.lanes)
+    }
+    
+    /// Process queued events until both lanes are empty, real-time first
+    ///
+    /// Backfill is taken in batches sized by the `[batching.ingestion]`
+    /// controller; each batch's latency feeds the size of the next one. The
+    /// real-time lane is checked again before every batch. Each batch is written
+    /// in one transaction: an error part-way rolls back the whole batch, and
+    /// every event in it is reported as failed.
+    pub async fn process_queued(&mut self) -> Vec<ProcessingResult> {
         let mut results = Vec::new();
         
-        for event in events {
-            match self.process_event(event).await {
-                Ok(result) => results.push(result),
-                Err(e) => {
-                    results.push(ProcessingResult {
-                        event_id: "unknown".to_string(),
-                        success: false,
-                        processing_time_ms: 0,
-                        error: Some(format!("Processing error: {}", e)),
-                        triples_generated: 0,
-                        inferences_made: 0,
-                    });
+        loop {
+            let next = {
+                let mut lanes = self.lanes.lock();
+                lanes
+                    .pop_batch(self.batch_sizer.next_size())
+                    .map(|(priority, batch)| (priority, batch, lanes.queued(priority)))
+            };
+            let Some((priority, batch, still_queued)) = next else { break };
+            self.processing_stats.lanes.lane_mut(priority).queued = still_queued;
+            let batch_len = batch.len();
+            let batch_start = std::time::Instant::now();
+            
+            let mut failure = self.store.begin().err();
+            let mut batch_results = Vec::with_capacity(batch_len);
+            for queued in batch {
+                let started = std::time::Instant::now();
+                let waited = started.duration_since(queued.enqueued);
+                let event_id = queued.event.event_id.clone();
+                // Events after a failure are not processed; the batch is rolled back anyway
+                let processed = match failure {
+                    Some(_) => None,
+                    None => match self.process_event(queued.event).await {
+                        Ok(result) => Some(result),
+                        Err(e) => {
+                            failure = Some(e);
+                            None
+                        }
+                    },
+                };
+                let result = processed.unwrap_or_else(|| ProcessingResult {
+                    event_id,
+                    success: false,
+                    processing_time_ms: started.elapsed().as_millis() as u64,
+                    error: None,
+                    triples_generated: 0,
+                    inferences_made: 0,
+                });
+                batch_results.push((result, waited, started.elapsed()));
+            }
+            
+            let outcome = match failure {
+                None => self.store.commit(),
+                Some(e) if self.store.in_transaction() => self.store.rollback().and(Err(e)),
+                Some(e) => Err(e),
+            };
+            for (mut result, waited, elapsed) in batch_results {
+                if let Err(e) = &outcome {
+                    if result.success {
+                        // Counted as stored when it was processed
+                        self.processing_stats.successful_events -= 1;
+                        self.processing_stats.failed_events += 1;
+                    }
+                    result.success = false;
+                    result.error = Some(format!("Batch rolled back: {}", e));
+                    result.triples_generated = 0;
+                    result.inferences_made = 0;
                 }
+                self.processing_stats.lanes.lane_mut(priority).record(result.success, waited, elapsed);
+                results.push(result);
+            }
+            
+            if priority == IngestPriority::Backfill {
+                let decision = self.batch_sizer.record(batch_len, batch_start.elapsed(), batching::resident_memory_mb());
+                batching::remember(&mut self.processing_stats.batch_decisions, decision);
             }
         }
         
         results
     }
     
-    /// Validate an EPCIS event
-    fn validate_event(&self, event: &EpcisEvent) -> Result<ValidationResult, EpcisKgError> {
+    /// Pin the ingestion batch size, or return it to the adaptive controller with `None`
+    pub fn set_batch_size_override(&mut self, batch_size: Option<usize>) {
+        self.batch_sizer.set_override(batch_size);
+    }
+    
+    /// Validate an EPCIS event, strictly when `[strict_mode]` is enabled
+    pub(crate) fn validate_event(&self, event: &EpcisEvent) -> Result<ValidationResult, EpcisKgError> {
+        self.validate_event_strict(event, self.config.strict_mode.enabled)
+    }
+    
+    /// Validate an EPCIS event; in strict mode the configured kinds of warning fail it
+    pub(crate) fn validate_event_strict(&self, event: &EpcisEvent, strict: bool) -> Result<ValidationResult, EpcisKgError> {
         // Structural validation
-        let structural_result = self.validate_event_structure(event)?;
+        let mut findings = self.validate_event_structure(event)?;
         
         // Semantic validation using ontologies
-        let semantic_result = self.validate_event_semantics(event)?;
+        findings.extend(self.validate_event_semantics(event)?);
         
         // Business rule validation
-        let business_result = self.validate_business_rules(event)?;
-        
-        Ok(ValidationResult {
-            is_valid: structural_result.is_valid && semantic_result.is_valid && business_result.is_valid,
-            errors: [
-                structural_result.errors,
-                semantic_result.errors,
-                business_result.errors,
-            ].concat(),
-            warnings: [
-                structural_result.warnings,
-                semantic_result.warnings,
-                business_result.warnings,
-            ].concat(),
-        })
+        findings.extend(self.validate_business_rules(event)?);
+        
+        // Partner extension fields against the schema registry
+        let extension_result = self.extensions.read().validate(&event.extensions);
+        findings.errors.extend(extension_result.errors);
+        for warning in extension_result.warnings {
+            findings.warn(WarningKind::ExtensionField, warning);
+        }
+        
+        Ok(findings.into_result(strict, &self.config.strict_mode.promote))
     }
     
     /// Validate event structure (syntax and required fields)
-    fn validate_event_structure(&self, event: &EpcisEvent) -> Result<ValidationResult, EpcisKgError> {
-        let mut errors = Vec::new();
-        let mut warnings = Vec::new();
+    fn validate_event_structure(&self, event: &EpcisEvent) -> Result<Findings, EpcisKgError> {
+        let mut findings = Findings::default();
         
         // Required fields validation
         if event.event_id.is_empty() {
-            errors.push("Event ID is required".to_string());
+            findings.error("Event ID is required".to_string());
         }
         
         if event.event_type.is_empty() {
-            errors.push("Event type is required".to_string());
+            findings.error("Event type is required".to_string());
         }
         
         if event.event_time.is_empty() {
-            errors.push("Event time is required".to_string());
+            findings.error("Event time is required".to_string());
         }
         
         if event.record_time.is_empty() {
-            errors.push("Record time is required".to_string());
+            findings.error("Record time is required".to_string());
         }
         
         if event.event_action.is_empty() {
-            errors.push("Event action is required".to_string());
+            findings.error("Event action is required".to_string());
         }
         
-        if event.epc_list.is_empty() {
-            errors.push("EPC list cannot be empty".to_string());
+        // TransformationEvents may carry only input and output lists
+        if event.epc_list.is_empty() && event.input_epc_list.is_empty() && event.output_epc_list.is_empty() {
+            findings.error("EPC list cannot be empty".to_string());
         }
         
-        // Event type validation
-        let valid_event_types = vec![
-            "ObjectEvent", "AggregationEvent", "QuantityEvent", 
-            "TransactionEvent", "TransformationEvent"
-        ];
-        
-        if !valid_event_types.contains(&event.event_type.as_str()) {
-            errors.push(format!("Invalid event type: {}", event.event_type));
+        // Event type validation; custom types are valid once a mapper is registered for them
+        if !self.mappers.read().contains(&event.event_type) {
+            findings.error(format!("Invalid event type: {}", event.event_type));
         }
         
         // Action validation
         let valid_actions = vec!["ADD", "OBSERVE", "DELETE"];
         if !valid_actions.contains(&event.event_action.as_str()) {
-            errors.push(format!("Invalid event action: {}", event.event_action));
+            findings.error(format!("Invalid event action: {}", event.event_action));
         }
         
         // DateTime format validation
         if let Err(_) = chrono::DateTime::parse_from_rfc3339(&event.event_time) {
-            errors.push(format!("Invalid event time format: {}", event.event_time));
+            findings.error(format!("Invalid event time format: {}", event.event_time));
         }
         
         if let Err(_) = chrono::DateTime::parse_from_rfc3339(&event.record_time) {
-            errors.push(format!("Invalid record time format: {}", event.record_time));
+            findings.error(format!("Invalid record time format: {}", event.record_time));
+        }
+        
+        if let Some(offset) = &event.event_time_zone_offset {
+            if crate::models::timezone::parse_offset(offset).is_err() {
+                findings.error(format!("Invalid event time zone offset: {}", offset));
+            }
+        }
+        
+        // ILMD dates must be xsd:date or xsd:dateTime for the shelf-life rules
+        if let Some(ilmd) = &event.ilmd {
+            for date in [&ilmd.item_expiration_date, &ilmd.best_before_date].into_iter().flatten() {
+                if crate::storage::expiry::expiry_instant(date).is_none() {
+                    findings.error(format!("Invalid ILMD date: {}", date));
+                }
+            }
         }
         
         // EPC format validation (basic check)
         for epc in &event.epc_list {
             if !epc.starts_with("urn:epc:id:") {
-                warnings.push(format!("EPC doesn't follow standard URN format: {}", epc));
+                findings.warn(WarningKind::NonUrnEpc, format!("EPC doesn't follow standard URN format: {}", epc));
             }
         }
         
-        Ok(ValidationResult {
-            is_valid: errors.is_empty(),
-            errors,
-            warnings,
-        })
+        Ok(findings)
     }
     
     /// Validate event semantics using ontologies
-    fn validate_event_semantics(&self, event: &EpcisEvent) -> Result<ValidationResult, EpcisKgError> {
-        let errors = Vec::new();
-        let mut warnings = Vec::new();
+    fn validate_event_semantics(&self, event: &EpcisEvent) -> Result<Findings, EpcisKgError> {
+        let mut findings = Findings::default();
+        
+        // Values in the namespace of a registered vocabulary extension must be its concepts
+        let extensions = self.store.vocabulary_extensions();
+        let checked = [(CbvVocabulary::BizSteps, &event.biz_step), (CbvVocabulary::Dispositions, &event.disposition)];
+        for (vocabulary, value) in checked {
+            if let Some(violation) = value.as_deref().and_then(|value| extension_violation(&extensions, vocabulary, value)) {
+                findings.error(violation);
+            }
+        }
         
         // Validate business step against ontology
         if let Some(biz_step) = &event.biz_step {
             if !self.is_valid_business_step(biz_step)? {
-                warnings.push(format!("Business step '{}' not found in ontology", biz_step));
+                findings.warn(WarningKind::UnknownBizStep, format!("Business step '{}' not found in ontology{}", biz_step, self.did_you_mean(CbvVocabulary::BizSteps, biz_step)));
             }
         }
         
         // Validate disposition against ontology
         if let Some(disposition) = &event.disposition {
             if !self.is_valid_disposition(disposition)? {
-                warnings.push(format!("Disposition '{}' not found in ontology", disposition));
+                findings.warn(WarningKind::UnknownDisposition, format!("Disposition '{}' not found in ontology{}", disposition, self.did_you_mean(CbvVocabulary::Dispositions, disposition)));
             }
         }
         
         // Validate business location format
         if let Some(location) = &event.biz_location {
             if !location.starts_with("urn:epc:id:sgln:") {
-                warnings.push(format!("Business location doesn't follow SGLN format: {}", location));
+                findings.warn(WarningKind::NonSglnLocation, format!("Business location doesn't follow SGLN format: {}", location));
+            }
+            
+            // The offset should be the one in effect at the site's registered time zone
+            let site_zone = self.store.parties().into_iter().find(|party| &party.gln == location).and_then(|party| party.time_zone);
+            if let Some(zone) = site_zone {
+                for warning in crate::models::timezone::check_site_offset(event, &zone) {
+                    findings.warn(WarningKind::SiteTimeZoneOffset, warning);
+                }
             }
         }
         
-        Ok(ValidationResult {
-            is_valid: errors.is_empty(),
-            errors,
-            warnings,
-        })
+        Ok(findings)
     }
     
     /// Validate business rules for the event
-    fn validate_business_rules(&self, event: &EpcisEvent) -> Result<ValidationResult, EpcisKgError> {
-        let mut errors = Vec::new();
-        let mut warnings = Vec::new();
+    fn validate_business_rules(&self, event: &EpcisEvent) -> Result<Findings, EpcisKgError> {
+        let mut findings = Findings::default();
         
         // Rule: Event time should not be in the future
         let event_time = chrono::DateTime::parse_from_rfc3339(&event.event_time)
@@ -269,7 +556,7 @@ impl EpcisEventPipeline {
         
         let now = chrono::Utc::now();
         if event_time > now {
-            warnings.push("Event time is in the future".to_string());
+            findings.warn(WarningKind::FutureEventTime, "Event time is in the future".to_string());
         }
         
         // Rule: Record time should be after or equal to event time
@@ -277,13 +564,13 @@ impl EpcisEventPipeline {
             .map_err(|e| EpcisKgError::Validation(format!("Invalid record time: {}", e)))?;
         
         if record_time < event_time {
-            errors.push("Record time cannot be before event time".to_string());
+            findings.error("Record time cannot be before event time".to_string());
         }
         
         // Rule: DELETE action should have valid business context
         if event.event_action == "DELETE" {
             if event.biz_step.is_none() {
-                warnings.push("DELETE action should have a business step specified".to_string());
+                findings.warn(WarningKind::DeleteWithoutBizStep, "DELETE action should have a business step specified".to_string());
             }
         }
         
@@ -291,25 +578,31 @@ impl EpcisEventPipeline {
         if let Some(biz_step) = &event.biz_step {
             if biz_step.to_lowercase() == "commissioning" {
                 if event.disposition.as_deref() != Some("active") {
-                    warnings.push("Commissioning events typically have 'active' disposition".to_string());
+                    findings.warn(WarningKind::CommissioningNotActive, "Commissioning events typically have 'active' disposition".to_string());
                 }
             }
         }
         
-        Ok(ValidationResult {
-            is_valid: errors.is_empty(),
-            errors,
-            warnings,
-        })
+        Ok(findings)
     }
     
     /// Process event and transform to RDF
-    async fn process_event_internal(&self, event: &EpcisEvent) -> Result<ProcessingResult, EpcisKgError> {
+    async fn process_event_internal(&self, event: &EpcisEvent, hook_triples: &[oxrdf::Triple]) -> Result<ProcessingResult, EpcisKgError> {
         // Generate RDF triples for the event
         let triples = self.generate_event_triples(event)?;
         
-        // Note: We can't store triples directly due to Arc<OxigraphStore> mutability
-        // In a real implementation, this would need a different approach
+        // Terms no loaded ontology mentions may be a partner's newer EPCIS extension
+        let unknown = {
+            let extensions = self.extensions.read();
+            self.ontology_terms
+                .unknown_terms(triples.iter().chain(hook_triples).map(oxrdf::Triple::as_ref), |iri| extensions.declares(iri))
+        };
+        for term in self.schema_drift.lock().observe(&event.event_id, &unknown, chrono::Utc::now()) {
+            warn!(
+                "Event {} uses {} {} which is not defined in any loaded ontology",
+                event.event_id, term.kind.as_str(), term.iri
+            );
+        }
         
         Ok(ProcessingResult {
             event_id: event.event_id.clone(),
@@ -321,6 +614,34 @@ impl EpcisEventPipeline {
         })
     }
     
+    /// RDF triples the pipeline generates for an event's graph, for callers that commit them to a store.
+    ///
+    /// Personal data is masked; see `masked_event_triples` for the original values.
+    pub fn event_triples(&self, event: &EpcisEvent) -> Result<Vec<oxrdf::Triple>, EpcisKgError> {
+        Ok(self.masked_event_triples(event)?.public)
+    }
+    
+    /// Convert the event's Digital Link identifiers to EPC URNs when `[identifiers]`
+    /// normalization is enabled; returns the `owl:sameAs` links to the captured forms
+    pub fn normalize_identifiers(&self, event: &mut EpcisEvent) -> Vec<oxrdf::Triple> {
+        identifiers::same_as_triples(&self.identifiers.normalize_event(event))
+    }
+    
+    /// The pipeline's identifier normalizer, for matching query values the same way
+    pub fn identifier_normalizer(&self) -> &IdentifierNormalizer {
+        &self.identifiers
+    }
+    
+    /// Event graph triples with personal data masked, plus the unmasked triples
+    /// that belong in the event's access-restricted graph (`pii_graph_name`)
+    pub fn masked_event_triples(&self, event: &EpcisEvent) -> Result<MaskedTriples, EpcisKgError> {
+        let triples = self.generate_event_triples(event)?;
+        Ok(match &self.masker {
+            Some(masker) => masker.mask(triples),
+            None => MaskedTriples { public: triples, restricted: Vec::new() },
+        })
+    }
+    
     /// Generate RDF triples for an EPCIS event
     fn generate_event_triples(&self, event: &EpcisEvent) -> Result<Vec<oxrdf::Triple>, EpcisKgError> {
         let mut triples = Vec::new();
@@ -328,14 +649,11 @@ impl EpcisEventPipeline {
         // Event URI
         let event_uri = oxrdf::NamedNode::new(format!("urn:epc:event:{}", event.event_id))?;
         
-        // Event type triple
-        let event_type_uri = match event.event_type.as_str() {
-            "ObjectEvent" => oxrdf::NamedNode::new("urn:epcglobal:epcis:ObjectEvent")?,
-            "AggregationEvent" => oxrdf::NamedNode::new("urn:epcglobal:epcis:AggregationEvent")?,
-            "QuantityEvent" => oxrdf::NamedNode::new("urn:epcglobal:epcis:QuantityEvent")?,
-            "TransactionEvent" => oxrdf::NamedNode::new("urn:epcglobal:epcis:TransactionEvent")?,
-            "TransformationEvent" => oxrdf::NamedNode::new("urn:epcglobal:epcis:TransformationEvent")?,
-            _ => oxrdf::NamedNode::new("urn:epcglobal:epcis:Event")?,
+        // Event type triple; the mapper of the type knows its class and shape
+        let mapper = self.mappers.read().get(&event.event_type);
+        let event_type_uri = match &mapper {
+            Some(mapper) => oxrdf::NamedNode::new(mapper.class_iri())?,
+            None => oxrdf::NamedNode::new("urn:epcglobal:epcis:Event")?,
         };
         
         triples.push(oxrdf::Triple::new(
@@ -362,6 +680,15 @@ impl EpcisEventPipeline {
             event_time_literal,
         ));
         
+        // Original offset of the event time, so local site time can be recovered
+        if let Some(offset) = &event.event_time_zone_offset {
+            triples.push(oxrdf::Triple::new(
+                event_uri.clone(),
+                oxrdf::NamedNode::new("urn:epcglobal:epcis:eventTimeZoneOffset")?,
+                oxrdf::Literal::new_simple_literal(offset.clone()),
+            ));
+        }
+        
         // Record time
         let record_time_literal = oxrdf::Literal::new_typed_literal(
             event.record_time.clone(),
@@ -386,16 +713,6 @@ impl EpcisEventPipeline {
             action_uri,
         ));
         
-        // EPC list
-        for epc in &event.epc_list {
-            let epc_uri = oxrdf::NamedNode::new(epc)?;
-            triples.push(oxrdf::Triple::new(
-                event_uri.clone(),
-                oxrdf::NamedNode::new("urn:epcglobal:epcis:epcList")?,
-                epc_uri,
-            ));
-        }
-        
         // Business step (if present)
         if let Some(biz_step) = &event.biz_step {
             let biz_step_uri = oxrdf::NamedNode::new(format!("urn:epcglobal:cbv:{}", biz_step))?;
@@ -426,13 +743,64 @@ impl EpcisEventPipeline {
             ));
         }
         
+        // Sources and destinations (owning party, possessing party, location)
+        for (direction, list) in [("source", &event.source_list), ("destination", &event.destination_list)] {
+            for source_destination in list {
+                triples.push(oxrdf::Triple::new(
+                    event_uri.clone(),
+                    oxrdf::NamedNode::new(source_destination.predicate(direction))?,
+                    oxrdf::NamedNode::new(source_destination.id.clone())?,
+                ));
+            }
+        }
+        
+        // EPC lists and ILMD, shaped by event type; unmapped types keep their EPC list
+        match &mapper {
+            Some(mapper) => triples.extend(mapper.map(event, &event_uri)?),
+            None => triples.extend(mappers::epc_list_triples(&event_uri, "epcList", &event.epc_list)?),
+        }
+        
+        // Temperature readings, one resource per reading
+        let xsd = |datatype: &str| oxrdf::NamedNode::new_unchecked(format!("http://www.w3.org/2001/XMLSchema#{}", datatype));
+        for (element_index, element) in event.sensor_element_list.iter().enumerate() {
+            let metadata_time = element.sensor_metadata.as_ref().and_then(|metadata| metadata.time.clone());
+            for (report_index, report) in element.sensor_report.iter().enumerate() {
+                let reading_time = report.time.clone().or_else(|| metadata_time.clone()).unwrap_or_else(|| event.event_time.clone());
+                for (value_index, celsius) in report.celsius().into_iter().enumerate() {
+                    let reading = oxrdf::NamedNode::new(format!(
+                        "urn:epc:event:{}:sensor:{}.{}.{}",
+                        event.event_id, element_index, report_index, value_index
+                    ))?;
+                    triples.push(oxrdf::Triple::new(event_uri.clone(), oxrdf::NamedNode::new("urn:epcglobal:epcis:temperatureReading")?, reading.clone()));
+                    triples.push(oxrdf::Triple::new(
+                        reading.clone(),
+                        oxrdf::NamedNode::new("urn:epcglobal:epcis:celsius")?,
+                        oxrdf::Literal::new_typed_literal(celsius.to_string(), xsd("decimal")),
+                    ));
+                    triples.push(oxrdf::Triple::new(
+                        reading,
+                        oxrdf::NamedNode::new("urn:epcglobal:epcis:readingTime")?,
+                        oxrdf::Literal::new_typed_literal(reading_time.clone(), xsd("dateTime")),
+                    ));
+                }
+            }
+        }
+        
+        // Partner extension fields
+        triples.extend(self.extensions.read().to_triples(&event_uri, &event.extensions)?);
+        
         Ok(triples)
     }
     
-    /// Store event in the knowledge graph
-    async fn store_event(&self, _event: &EpcisEvent, _processing_result: &ProcessingResult) -> Result<(), EpcisKgError> {
-        // Note: We can't modify the Arc<OxigraphStore> directly in this context
-        // The actual storage happens in process_event_internal
+    /// Store event in the knowledge graph, with the triples hooks contributed
+    /// and its personal data in the access-restricted graph
+    async fn store_event(&mut self, event: &EpcisEvent, hook_triples: &[oxrdf::Triple]) -> Result<(), EpcisKgError> {
+        let mut triples = self.masked_event_triples(event)?;
+        triples.public.extend_from_slice(hook_triples);
+        self.store.store_event_triples(&event.event_id, &triples.public).await?;
+        if !triples.restricted.is_empty() {
+            self.store.insert_triples(&pii_graph_name(&event.event_id), &triples.restricted)?;
+        }
         Ok(())
     }
     
@@ -470,22 +838,27 @@ impl EpcisEventPipeline {
     }
     
     /// Perform reasoning and inference on the event
-    async fn perform_reasoning(&self, event: &EpcisEvent) -> Result<usize, EpcisKgError> {
-        let mut reasoner = self.reasoner.write().await;
+    async fn perform_reasoning(&mut self, event: &EpcisEvent, hook_triples: &[oxrdf::Triple]) -> Result<usize, EpcisKgError> {
+        let mut reasoner = self.reasoners.checkout()?;
         
-        // Load event data for reasoning
-        let event_data = self.create_event_ontology_data(event)?;
+        // Load event data (plus triples contributed by hooks) for reasoning
+        let mut event_data = self.create_event_ontology_data(event)?;
+        for triple in hook_triples {
+            event_data.graph.insert(triple);
+        }
         
         // Load the event data into the reasoner
         reasoner.load_ontology_data(&event_data)?;
         
-        // Perform inference (using the existing method signature)
-        let inferences = reasoner.perform_inference()?;
-        
-        // Note: We can't store inferred triples directly due to Arc<OxigraphStore> mutability
-        // This would need to be handled differently in a real implementation
+        // Materialize the inferences and store them, with their provenance
+        reasoner.perform_inference_with_materialization()?;
+        let mut inferred = 0;
+        for (graph, triples) in reasoner.get_materialized_triples() {
+            inferred += self.store.insert_triples(graph, triples)?;
+        }
+        self.store.insert_triples(PROVENANCE_GRAPH, &reasoner.provenance_annotations())?;
         
-        Ok(inferences.len())
+        Ok(inferred)
     }
     
     /// Create ontology data from event for reasoning
@@ -507,17 +880,86 @@ impl EpcisEventPipeline {
     }
     
     /// Check if business step is valid according to ontology
-    fn is_valid_business_step(&self, _biz_step: &str) -> Result<bool, EpcisKgError> {
-        // For now, return true for basic validation
-        // In a real implementation, this would query the knowledge graph
-        Ok(true)
+    fn is_valid_business_step(&self, biz_step: &str) -> Result<bool, EpcisKgError> {
+        Ok(self.is_cbv_term(biz_step, "urn:epcglobal:cbv:BizStep"))
     }
     
     /// Check if disposition is valid according to ontology
-    fn is_valid_disposition(&self, _disposition: &str) -> Result<bool, EpcisKgError> {
-        // For now, return true for basic validation
-        // In a real implementation, this would query the knowledge graph
-        Ok(true)
+    fn is_valid_disposition(&self, disposition: &str) -> Result<bool, EpcisKgError> {
+        Ok(self.is_cbv_term(disposition, "urn:epcglobal:cbv:Disposition"))
+    }
+    
+    /// Whether `urn:epcglobal:cbv:{value}`, or `value` itself when it is the IRI
+    /// of a registered extension concept, is typed as `class` in the store.
+    ///
+    /// Every value passes while no instance of the class is loaded, so events
+    /// are not flagged before the CBV ontology is.
+    fn is_cbv_term(&self, value: &str, class: &str) -> bool {
+        let rdf_type = oxrdf::NamedNodeRef::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type");
+        let class = oxrdf::NamedNodeRef::new_unchecked(class);
+        if !self.store.contains_pattern(None, Some(rdf_type), Some(class.into()), None) {
+            return true;
+        }
+        if let Ok(term) = oxrdf::NamedNodeRef::new(value) {
+            if self.store.contains_pattern(Some(term.into()), Some(rdf_type), Some(class.into()), None) {
+                return true;
+            }
+        }
+        match oxrdf::NamedNode::new(format!("urn:epcglobal:cbv:{}", value)) {
+            Ok(term) => self.store.contains_pattern(Some(term.as_ref().into()), Some(rdf_type), Some(class.into()), None),
+            Err(_) => false,
+        }
+    }
+    
+    /// Short names of the CBV terms of a vocabulary loaded into the store
+    fn cbv_terms(&self, vocabulary: CbvVocabulary) -> Vec<String> {
+        vocabulary
+            .terms(&self.store, None, &[])
+            .into_iter()
+            .filter_map(|term| term.iri.strip_prefix("urn:epcglobal:cbv:").map(str::to_string))
+            .collect()
+    }
+    
+    /// `; did you mean '...'?` naming the closest term, or nothing when the vocabulary is empty
+    fn did_you_mean(&self, vocabulary: CbvVocabulary, value: &str) -> String {
+        let terms = self.cbv_terms(vocabulary);
+        match correction::closest_term(value, terms.iter().map(String::as_str)) {
+            Some(suggestion) => format!("; did you mean '{}'?", suggestion.term),
+            None => String::new(),
+        }
+    }
+    
+    /// Replace unknown business steps and dispositions by the closest CBV term,
+    /// when auto-correction is enabled and the match is close and unambiguous
+    fn auto_correct(&self, event: &mut EpcisEvent) -> Vec<AutoCorrection> {
+        let settings = &self.config.auto_correction;
+        let mut corrections = Vec::new();
+        if !settings.enabled {
+            return corrections;
+        }
+        
+        let fields = [
+            ("bizStep", CbvVocabulary::BizSteps, &mut event.biz_step),
+            ("disposition", CbvVocabulary::Dispositions, &mut event.disposition),
+        ];
+        for (field, vocabulary, value) in fields {
+            let Some(original) = value.clone() else { continue };
+            if self.is_cbv_term(&original, vocabulary.class_iri()) {
+                continue;
+            }
+            let terms = self.cbv_terms(vocabulary);
+            let Some(suggestion) = correction::closest_term(&original, terms.iter().map(String::as_str)) else { continue };
+            if suggestion.unique && suggestion.confidence >= settings.min_confidence {
+                *value = Some(suggestion.term.clone());
+                corrections.push(AutoCorrection {
+                    field: field.to_string(),
+                    original,
+                    corrected: suggestion.term,
+                    confidence: suggestion.confidence,
+                });
+            }
+        }
+        corrections
     }
     
     /// Update processing statistics
@@ -555,4 +997,52 @@ impl EpcisEventPipeline {
     pub fn reset_stats(&mut self) {
         self.processing_stats = ProcessingStats::default();
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str) -> EpcisEvent {
+        EpcisEvent {
+            event_id: id.to_string(),
+            event_type: "ObjectEvent".to_string(),
+            event_time: "2024-03-01T08:00:00Z".to_string(),
+            record_time: "2024-03-01T08:00:00Z".to_string(),
+            event_action: "ADD".to_string(),
+            epc_list: vec!["urn:epc:id:sgtin:0614141.107346.1".to_string()],
+            ..EpcisEvent::default()
+        }
+    }
+
+    /// Each graph's name and size
+    fn snapshot(store: &OxigraphStore) -> Vec<(String, usize)> {
+        store.graph_names().into_iter().map(|name| {
+            let len = store.graph_len(&name);
+            (name, len)
+        }).collect()
+    }
+
+    #[tokio::test]
+    async fn test_failing_batch_leaves_memory_and_disk_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = AppConfig { ontology_paths: Vec::new(), ..AppConfig::default() };
+        let reasoners = Arc::new(ReasonerPool::from_config(&config));
+        let store = OxigraphStore::new(dir.path()).unwrap();
+        let mut pipeline = EpcisEventPipeline::with_reasoner_pool(config, store, reasoners).await.unwrap();
+        pipeline.set_batch_size_override(Some(10));
+
+        let results = pipeline.process_events_batch(vec![event("e1")]).await;
+        assert!(results[0].success, "{:?}", results[0].error);
+        assert!(pipeline.store.graph_len("urn:epcis:event:e1") > 0);
+        let in_memory = snapshot(&pipeline.store);
+        let on_disk = snapshot(&OxigraphStore::new(dir.path()).unwrap());
+
+        // No IRI can be made of an ID with a space, so the second event fails after the first was written
+        let results = pipeline.process_events_batch(vec![event("e2"), event("bad id"), event("e3")]).await;
+        assert_eq!(results.iter().map(|result| result.event_id.as_str()).collect::<Vec<_>>(), vec!["e2", "bad id", "e3"]);
+        assert!(results.iter().all(|result| !result.success && result.error.as_deref().is_some_and(|error| error.starts_with("Batch rolled back"))));
+        assert_eq!(snapshot(&pipeline.store), in_memory);
+        assert_eq!(snapshot(&OxigraphStore::new(dir.path()).unwrap()), on_disk);
+        assert_eq!(pipeline.get_stats().successful_events, 1);
+    }
+}
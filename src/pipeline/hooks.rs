@@ -0,0 +1,135 @@
+use crate::models::epcis::EpcisEvent;
+use crate::models::events::ProcessingResult;
+use crate::EpcisKgError;
+use oxrdf::Triple;
+use std::sync::Arc;
+
+/// Custom processing step plugged into the event pipeline.
+///
+/// All callbacks default to no-ops, so a hook only implements the stages it
+/// cares about. `before_validate` may enrich the event (e.g. attach weather
+/// data to a sensor event) and can reject it by returning an error.
+/// `contribute` adds triples and validation findings of the hook's own.
+/// Errors from the later stages are logged and do not fail the stored event.
+pub trait EventHook: Send + Sync {
+    /// Unique name, used for ordering diagnostics and unregistration
+    fn name(&self) -> &str;
+
+    fn before_validate(&self, _event: &mut EpcisEvent) -> Result<(), EpcisKgError> {
+        Ok(())
+    }
+
+    fn contribute(&self, _event: &EpcisEvent) -> Result<HookContribution, EpcisKgError> {
+        Ok(HookContribution::default())
+    }
+
+    fn after_store(&self, _event: &EpcisEvent, _result: &ProcessingResult) -> Result<(), EpcisKgError> {
+        Ok(())
+    }
+
+    fn after_inference(&self, _event: &EpcisEvent, _result: &ProcessingResult) -> Result<(), EpcisKgError> {
+        Ok(())
+    }
+}
+
+/// Triples and validation findings a hook adds to an event
+#[derive(Debug, Clone, Default)]
+pub struct HookContribution {
+    pub triples: Vec<Triple>,
+    pub warnings: Vec<String>,
+    /// Any error makes the event invalid
+    pub errors: Vec<String>,
+}
+
+impl HookContribution {
+    pub fn merge(&mut self, other: HookContribution) {
+        self.triples.extend(other.triples);
+        self.warnings.extend(other.warnings);
+        self.errors.extend(other.errors);
+    }
+}
+
+struct RegisteredHook {
+    priority: i32,
+    hook: Arc<dyn EventHook>,
+}
+
+/// Hooks in execution order: ascending priority, then registration order
+#[derive(Default)]
+pub struct HookRegistry {
+    hooks: Vec<RegisteredHook>,
+}
+
+impl HookRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a hook, replacing any hook registered under the same name
+    pub fn register(&mut self, hook: Arc<dyn EventHook>, priority: i32) {
+        self.unregister(hook.name());
+        let position = self.hooks.partition_point(|existing| existing.priority <= priority);
+        self.hooks.insert(position, RegisteredHook { priority, hook });
+    }
+
+    /// Remove a hook by name; `false` if none was registered
+    pub fn unregister(&mut self, name: &str) -> bool {
+        let before = self.hooks.len();
+        self.hooks.retain(|registered| registered.hook.name() != name);
+        self.hooks.len() != before
+    }
+
+    /// Hook names with their priorities, in execution order
+    pub fn list(&self) -> Vec<(String, i32)> {
+        self.hooks
+            .iter()
+            .map(|registered| (registered.hook.name().to_string(), registered.priority))
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Snapshot of the hooks so callbacks run without holding the registry lock
+    pub fn hooks(&self) -> Vec<Arc<dyn EventHook>> {
+        self.hooks.iter().map(|registered| Arc::clone(&registered.hook)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Tag(&'static str);
+
+    impl EventHook for Tag {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn before_validate(&self, event: &mut EpcisEvent) -> Result<(), EpcisKgError> {
+            event.event_id.push_str(self.0);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_hook_ordering() {
+        let mut registry = HookRegistry::new();
+        registry.register(Arc::new(Tag("b")), 10);
+        registry.register(Arc::new(Tag("c")), 10);
+        registry.register(Arc::new(Tag("a")), -5);
+        // Re-registering moves the hook to its new priority
+        registry.register(Arc::new(Tag("b")), 20);
+
+        let mut event = EpcisEvent::default();
+        for hook in registry.hooks() {
+            hook.before_validate(&mut event).unwrap();
+        }
+        assert_eq!(event.event_id, "acb");
+        assert!(registry.unregister("c"));
+        assert!(!registry.unregister("c"));
+        assert_eq!(registry.list(), vec![("a".to_string(), -5), ("b".to_string(), 20)]);
+    }
+}
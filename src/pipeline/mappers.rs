@@ -0,0 +1,238 @@
+use crate::models::epcis::{EpcisEvent, Ilmd};
+use crate::EpcisKgError;
+use oxrdf::{Literal, NamedNode, Triple};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+const EPCIS_NS: &str = "urn:epcglobal:epcis:";
+
+/// Maps the type-specific part of an event to RDF.
+///
+/// The pipeline writes what every event has (type, ID, times, action, business
+/// step, disposition, location, sources and destinations, sensor readings and
+/// extension fields); a mapper adds the shape of its event type, such as which
+/// EPC lists exist and which EPCs the ILMD describes.
+pub trait EventMapper: Send + Sync {
+    /// Event type handled, as captured in `event_type`
+    fn event_type(&self) -> &str;
+
+    /// Class of the event resource
+    fn class_iri(&self) -> String {
+        format!("{}{}", EPCIS_NS, self.event_type())
+    }
+
+    fn map(&self, event: &EpcisEvent, event_uri: &NamedNode) -> Result<Vec<Triple>, EpcisKgError>;
+}
+
+/// `event epcis:{predicate} epc` for each EPC of a list
+pub fn epc_list_triples(event_uri: &NamedNode, predicate: &str, epcs: &[String]) -> Result<Vec<Triple>, EpcisKgError> {
+    let predicate = NamedNode::new(format!("{}{}", EPCIS_NS, predicate))?;
+    epcs.iter()
+        .map(|epc| Ok(Triple::new(event_uri.clone(), predicate.clone(), NamedNode::new(epc)?)))
+        .collect()
+}
+
+/// ILMD attributes as CBV master data of each EPC in `targets`
+pub fn ilmd_triples(ilmd: &Ilmd, targets: &[String]) -> Result<Vec<Triple>, EpcisKgError> {
+    let xsd = |value: &str| {
+        let datatype = if value.contains('T') { "dateTime" } else { "date" };
+        NamedNode::new_unchecked(format!("http://www.w3.org/2001/XMLSchema#{}", datatype))
+    };
+    let mut triples = Vec::new();
+    for epc in targets {
+        let epc_uri = NamedNode::new(epc)?;
+        for (attribute, value) in [("itemExpirationDate", &ilmd.item_expiration_date), ("bestBeforeDate", &ilmd.best_before_date)] {
+            if let Some(value) = value {
+                triples.push(Triple::new(
+                    epc_uri.clone(),
+                    NamedNode::new(format!("urn:epcglobal:cbv:mda:{}", attribute))?,
+                    Literal::new_typed_literal(value.clone(), xsd(value)),
+                ));
+            }
+        }
+        if let Some(lot_number) = &ilmd.lot_number {
+            triples.push(Triple::new(
+                epc_uri,
+                NamedNode::new("urn:epcglobal:cbv:mda:lotNumber")?,
+                Literal::new_simple_literal(lot_number.clone()),
+            ));
+        }
+    }
+    Ok(triples)
+}
+
+/// Observed or commissioned EPCs; the ILMD describes them
+pub struct ObjectEventMapper;
+
+impl EventMapper for ObjectEventMapper {
+    fn event_type(&self) -> &str {
+        "ObjectEvent"
+    }
+
+    fn map(&self, event: &EpcisEvent, event_uri: &NamedNode) -> Result<Vec<Triple>, EpcisKgError> {
+        let mut triples = epc_list_triples(event_uri, "epcList", &event.epc_list)?;
+        if let Some(ilmd) = &event.ilmd {
+            triples.extend(ilmd_triples(ilmd, &event.epc_list)?);
+        }
+        Ok(triples)
+    }
+}
+
+/// Packed or unpacked children; aggregations carry no ILMD
+pub struct AggregationEventMapper;
+
+impl EventMapper for AggregationEventMapper {
+    fn event_type(&self) -> &str {
+        "AggregationEvent"
+    }
+
+    fn map(&self, event: &EpcisEvent, event_uri: &NamedNode) -> Result<Vec<Triple>, EpcisKgError> {
+        // Capture folds `childEPCs` into the EPC list, where traces look for members
+        epc_list_triples(event_uri, "epcList", &event.epc_list)
+    }
+}
+
+/// Inputs consumed and outputs produced; the ILMD describes the outputs
+pub struct TransformationEventMapper;
+
+impl EventMapper for TransformationEventMapper {
+    fn event_type(&self) -> &str {
+        "TransformationEvent"
+    }
+
+    fn map(&self, event: &EpcisEvent, event_uri: &NamedNode) -> Result<Vec<Triple>, EpcisKgError> {
+        let mut triples = epc_list_triples(event_uri, "epcList", &event.epc_list)?;
+        triples.extend(epc_list_triples(event_uri, "inputEPCList", &event.input_epc_list)?);
+        triples.extend(epc_list_triples(event_uri, "outputEPCList", &event.output_epc_list)?);
+        if let Some(ilmd) = &event.ilmd {
+            let targets = if event.output_epc_list.is_empty() { &event.epc_list } else { &event.output_epc_list };
+            triples.extend(ilmd_triples(ilmd, targets)?);
+        }
+        Ok(triples)
+    }
+}
+
+/// Event types whose EPCs are a plain list, such as `QuantityEvent` and `TransactionEvent`
+pub struct EpcListMapper {
+    event_type: String,
+}
+
+impl EpcListMapper {
+    pub fn new(event_type: &str) -> Self {
+        Self { event_type: event_type.to_string() }
+    }
+}
+
+impl EventMapper for EpcListMapper {
+    fn event_type(&self) -> &str {
+        &self.event_type
+    }
+
+    fn map(&self, event: &EpcisEvent, event_uri: &NamedNode) -> Result<Vec<Triple>, EpcisKgError> {
+        epc_list_triples(event_uri, "epcList", &event.epc_list)
+    }
+}
+
+/// Mappers by event type; an event type without one is not accepted at capture
+pub struct MapperRegistry {
+    mappers: BTreeMap<String, Arc<dyn EventMapper>>,
+}
+
+impl Default for MapperRegistry {
+    fn default() -> Self {
+        let mut registry = Self { mappers: BTreeMap::new() };
+        registry.register(Arc::new(ObjectEventMapper));
+        registry.register(Arc::new(AggregationEventMapper));
+        registry.register(Arc::new(TransformationEventMapper));
+        registry.register(Arc::new(EpcListMapper::new("QuantityEvent")));
+        registry.register(Arc::new(EpcListMapper::new("TransactionEvent")));
+        registry
+    }
+}
+
+impl MapperRegistry {
+    /// The built-in mappers of the five EPCIS event types
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a mapper for a custom event type, or override the one of its type
+    pub fn register(&mut self, mapper: Arc<dyn EventMapper>) -> Option<Arc<dyn EventMapper>> {
+        self.mappers.insert(mapper.event_type().to_string(), mapper)
+    }
+
+    /// Remove the mapper of an event type, built-in or not
+    pub fn unregister(&mut self, event_type: &str) -> bool {
+        self.mappers.remove(event_type).is_some()
+    }
+
+    pub fn get(&self, event_type: &str) -> Option<Arc<dyn EventMapper>> {
+        self.mappers.get(event_type).cloned()
+    }
+
+    pub fn contains(&self, event_type: &str) -> bool {
+        self.mappers.contains_key(event_type)
+    }
+
+    pub fn event_types(&self) -> Vec<String> {
+        self.mappers.keys().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct ShipmentEventMapper;
+
+    impl EventMapper for ShipmentEventMapper {
+        fn event_type(&self) -> &str {
+            "ShipmentEvent"
+        }
+
+        fn class_iri(&self) -> String {
+            "https://ns.acme.example.com/epcis/ShipmentEvent".to_string()
+        }
+
+        fn map(&self, event: &EpcisEvent, event_uri: &NamedNode) -> Result<Vec<Triple>, EpcisKgError> {
+            epc_list_triples(event_uri, "childEPCs", &event.epc_list)
+        }
+    }
+
+    fn event(event_type: &str) -> EpcisEvent {
+        serde_json::from_value(serde_json::json!({
+            "event_id": "evt-1",
+            "event_type": event_type,
+            "event_time": "2024-03-01T09:00:00Z",
+            "record_time": "2024-03-01T09:00:05Z",
+            "event_action": "ADD",
+            "epc_list": ["urn:epc:id:sgtin:0614141.107346.1"],
+            "input_epc_list": ["urn:epc:id:sgtin:0614141.107346.2"],
+            "biz_step": null,
+            "disposition": null,
+            "biz_location": null,
+            "ilmd": { "lot_number": "LOT-7" }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_mappers_shape_each_event_type() {
+        let uri = NamedNode::new_unchecked("urn:epc:event:evt-1");
+        let mut registry = MapperRegistry::new();
+        let predicates = |registry: &MapperRegistry, event_type: &str| -> Vec<String> {
+            let mapper = registry.get(event_type).unwrap();
+            mapper.map(&event(event_type), &uri).unwrap().iter().map(|triple| triple.predicate.as_str().to_string()).collect()
+        };
+
+        assert_eq!(predicates(&registry, "ObjectEvent"), vec!["urn:epcglobal:epcis:epcList", "urn:epcglobal:cbv:mda:lotNumber"]);
+        assert_eq!(predicates(&registry, "AggregationEvent"), vec!["urn:epcglobal:epcis:epcList"]);
+        assert!(predicates(&registry, "TransformationEvent").contains(&"urn:epcglobal:epcis:inputEPCList".to_string()));
+
+        assert!(!registry.contains("ShipmentEvent"));
+        assert!(registry.register(Arc::new(ShipmentEventMapper)).is_none());
+        assert_eq!(predicates(&registry, "ShipmentEvent"), vec!["urn:epcglobal:epcis:childEPCs"]);
+        assert_eq!(registry.get("ShipmentEvent").unwrap().class_iri(), "https://ns.acme.example.com/epcis/ShipmentEvent");
+        assert_eq!(registry.get("QuantityEvent").unwrap().class_iri(), "urn:epcglobal:epcis:QuantityEvent");
+    }
+}
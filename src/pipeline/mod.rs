@@ -1,3 +1,12 @@
 pub mod event_pipeline;
+pub mod hooks;
+pub mod lanes;
+pub mod mappers;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+pub mod strict;
 
-pub use event_pipeline::EpcisEventPipeline;
\ No newline at end of file
+pub use event_pipeline::EpcisEventPipeline;
+pub use hooks::{EventHook, HookContribution, HookRegistry};
+pub use lanes::IngestPriority;
+pub use mappers::{EventMapper, MapperRegistry};
@@ -0,0 +1,257 @@
+//! Rhai scripting hooks for enrichment and validation without a Rust plugin.
+//!
+//! Every `*.rhai` file in the scripts directory runs once per event, in file
+//! name order. A script sees the event as a read-only `event` map and can only
+//! call `add_triple`, `add_literal`, `warning` and `error`. Module imports and
+//! `eval` are disabled and each run has an operation budget, so a script
+//! cannot touch the file system or stall the pipeline. With hot reload on,
+//! changed files are recompiled on the first event after the reload interval.
+
+use crate::config::ScriptingConfig;
+use crate::models::epcis::EpcisEvent;
+use crate::pipeline::hooks::{EventHook, HookContribution};
+use crate::EpcisKgError;
+use oxrdf::{Literal, NamedNode, Triple};
+use parking_lot::{Mutex, RwLock};
+use rhai::{Dynamic, Engine, EvalAltResult, Scope, AST};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use tracing::{debug, info, warn};
+
+thread_local! {
+    /// Output of the script currently running on this thread
+    static OUTPUT: RefCell<HookContribution> = RefCell::new(HookContribution::default());
+}
+
+struct Script {
+    name: String,
+    modified: Option<SystemTime>,
+    /// `None` if the file does not compile; retried once it changes
+    ast: Option<AST>,
+}
+
+/// All scripts of the scripts directory, registered as one event hook
+pub struct ScriptHooks {
+    engine: Engine,
+    config: ScriptingConfig,
+    dir: PathBuf,
+    scripts: RwLock<Vec<Script>>,
+    last_scan: Mutex<Instant>,
+}
+
+impl ScriptHooks {
+    pub fn new(config: &ScriptingConfig) -> Result<Self, EpcisKgError> {
+        let dir = PathBuf::from(&config.scripts_dir);
+        if !dir.is_dir() {
+            return Err(EpcisKgError::Config(format!("Scripts directory not found: {}", dir.display())));
+        }
+        let hooks = Self {
+            engine: sandboxed_engine(config.max_operations),
+            config: config.clone(),
+            dir,
+            scripts: RwLock::new(Vec::new()),
+            last_scan: Mutex::new(Instant::now()),
+        };
+        hooks.reload()?;
+        Ok(hooks)
+    }
+
+    /// Names of the loaded scripts, in run order
+    pub fn script_names(&self) -> Vec<String> {
+        self.scripts.read().iter().map(|script| script.name.clone()).collect()
+    }
+
+    /// Recompile added or changed scripts and drop removed ones; `false` if nothing changed
+    pub fn reload(&self) -> Result<bool, EpcisKgError> {
+        let files = list_scripts(&self.dir)?;
+        let previous: Vec<(String, Option<SystemTime>, Option<AST>)> = self
+            .scripts
+            .read()
+            .iter()
+            .map(|script| (script.name.clone(), script.modified, script.ast.clone()))
+            .collect();
+        let unchanged = files.len() == previous.len()
+            && files
+                .iter()
+                .zip(&previous)
+                .all(|((name, _, modified), (old_name, old_modified, _))| name == old_name && modified == old_modified);
+        if unchanged {
+            return Ok(false);
+        }
+
+        let mut scripts = Vec::with_capacity(files.len());
+        for (name, path, modified) in files {
+            let cached = previous
+                .iter()
+                .find(|(old_name, old_modified, _)| *old_name == name && *old_modified == modified)
+                .map(|(_, _, ast)| ast.clone());
+            let ast = match cached {
+                Some(ast) => ast,
+                None => match std::fs::read_to_string(&path).map_err(EpcisKgError::from).and_then(|source| {
+                    self.engine
+                        .compile(&source)
+                        .map_err(|e| EpcisKgError::Config(format!("Script {} does not compile: {}", name, e)))
+                }) {
+                    Ok(ast) => Some(ast),
+                    Err(e) => {
+                        warn!("{}", e);
+                        None
+                    }
+                },
+            };
+            scripts.push(Script { name, modified, ast });
+        }
+
+        info!("Loaded {} event scripts from {}", scripts.len(), self.dir.display());
+        *self.scripts.write() = scripts;
+        Ok(true)
+    }
+
+    fn reload_if_due(&self) {
+        if !self.config.hot_reload {
+            return;
+        }
+        {
+            let mut last_scan = self.last_scan.lock();
+            if last_scan.elapsed() < Duration::from_secs(self.config.reload_interval_seconds) {
+                return;
+            }
+            *last_scan = Instant::now();
+        }
+        if let Err(e) = self.reload() {
+            warn!("Failed to reload scripts from {}: {}", self.dir.display(), e);
+        }
+    }
+}
+
+impl EventHook for ScriptHooks {
+    fn name(&self) -> &str {
+        "scripts"
+    }
+
+    fn contribute(&self, event: &EpcisEvent) -> Result<HookContribution, EpcisKgError> {
+        self.reload_if_due();
+        let event = rhai::serde::to_dynamic(event)
+            .map_err(|e| EpcisKgError::Validation(format!("Event cannot be passed to scripts: {}", e)))?;
+
+        let mut contribution = HookContribution::default();
+        for script in self.scripts.read().iter() {
+            let Some(ast) = &script.ast else { continue };
+            let mut scope = Scope::new();
+            scope.push_constant("event", event.clone());
+
+            OUTPUT.with(|output| output.take());
+            let result = self.engine.run_ast_with_scope(&mut scope, ast);
+            let output = OUTPUT.with(|output| output.take());
+            match result {
+                Ok(()) => contribution.merge(output),
+                // A failing script rejects the event rather than silently skipping its checks
+                Err(e) => contribution.errors.push(format!("Script {} failed: {}", script.name, e)),
+            }
+        }
+        Ok(contribution)
+    }
+}
+
+/// `*.rhai` files of `dir` with their modification times, sorted by name
+fn list_scripts(dir: &Path) -> Result<Vec<(String, PathBuf, Option<SystemTime>)>, EpcisKgError> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|extension| extension.to_str()) != Some("rhai") {
+            continue;
+        }
+        let Some(name) = path.file_name().and_then(|name| name.to_str()).map(str::to_string) else { continue };
+        let modified = std::fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+        files.push((name, path, modified));
+    }
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(files)
+}
+
+fn sandboxed_engine(max_operations: u64) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(max_operations);
+    engine.set_max_call_levels(32);
+    engine.set_max_string_size(64 * 1024);
+    engine.set_max_array_size(10_000);
+    engine.set_max_map_size(10_000);
+    engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+    engine.on_print(|text| debug!("script: {}", text));
+
+    engine.register_fn("add_triple", |subject: &str, predicate: &str, object: &str| -> Result<(), Box<EvalAltResult>> {
+        let triple = Triple::new(iri(subject)?, iri(predicate)?, iri(object)?);
+        OUTPUT.with(|output| output.borrow_mut().triples.push(triple));
+        Ok(())
+    });
+    engine.register_fn("add_literal", |subject: &str, predicate: &str, value: Dynamic| -> Result<(), Box<EvalAltResult>> {
+        let literal = if let Ok(integer) = value.as_int() {
+            Literal::from(integer)
+        } else if let Ok(float) = value.as_float() {
+            Literal::from(float)
+        } else if let Ok(boolean) = value.as_bool() {
+            Literal::from(boolean)
+        } else {
+            Literal::new_simple_literal(value.to_string())
+        };
+        let triple = Triple::new(iri(subject)?, iri(predicate)?, literal);
+        OUTPUT.with(|output| output.borrow_mut().triples.push(triple));
+        Ok(())
+    });
+    engine.register_fn("warning", |message: &str| {
+        OUTPUT.with(|output| output.borrow_mut().warnings.push(message.to_string()));
+    });
+    engine.register_fn("error", |message: &str| {
+        OUTPUT.with(|output| output.borrow_mut().errors.push(message.to_string()));
+    });
+
+    engine
+}
+
+fn iri(value: &str) -> Result<NamedNode, Box<EvalAltResult>> {
+    NamedNode::new(value).map_err(|e| format!("Invalid IRI '{}': {}", value, e).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_contributions() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("10-enrich.rhai"),
+            r#"
+                let subject = "urn:epc:event:" + event.event_id;
+                add_literal(subject, "urn:example:epcCount", event.epc_list.len());
+                if event.biz_location == () { warning("no bizLocation"); }
+            "#,
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("20-broken.rhai"), "add_triple(").unwrap();
+        std::fs::write(dir.path().join("30-runaway.rhai"), "loop { }").unwrap();
+
+        let config = ScriptingConfig {
+            enabled: true,
+            scripts_dir: dir.path().display().to_string(),
+            hot_reload: false,
+            ..ScriptingConfig::default()
+        };
+        let hooks = ScriptHooks::new(&config).unwrap();
+        assert_eq!(hooks.script_names(), vec!["10-enrich.rhai", "20-broken.rhai", "30-runaway.rhai"]);
+
+        let event = EpcisEvent {
+            event_id: "e1".to_string(),
+            epc_list: vec!["urn:epc:id:sgtin:0614141.107346.1".to_string()],
+            ..EpcisEvent::default()
+        };
+        let contribution = hooks.contribute(&event).unwrap();
+        assert_eq!(contribution.triples.len(), 1);
+        assert_eq!(contribution.warnings, vec!["no bizLocation"]);
+        // The broken script is skipped; the runaway one hits its operation budget
+        assert_eq!(contribution.errors.len(), 1);
+        assert!(contribution.errors[0].starts_with("Script 30-runaway.rhai failed"));
+    }
+}
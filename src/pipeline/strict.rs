@@ -0,0 +1,59 @@
+use crate::config::WarningKind;
+use crate::models::events::ValidationResult;
+
+/// Validation findings before strict mode decides which warnings fail the event
+#[derive(Debug, Clone, Default)]
+pub struct Findings {
+    pub errors: Vec<String>,
+    pub warnings: Vec<(WarningKind, String)>,
+}
+
+impl Findings {
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.errors.push(message.into());
+    }
+
+    pub fn warn(&mut self, kind: WarningKind, message: impl Into<String>) {
+        self.warnings.push((kind, message.into()));
+    }
+
+    pub fn extend(&mut self, other: Findings) {
+        self.errors.extend(other.errors);
+        self.warnings.extend(other.warnings);
+    }
+
+    /// The validation result; in strict mode the `promote` kinds of warning become errors
+    pub fn into_result(self, strict: bool, promote: &[WarningKind]) -> ValidationResult {
+        let mut errors = self.errors;
+        let mut warnings = Vec::new();
+        for (kind, message) in self.warnings {
+            if strict && promote.contains(&kind) {
+                errors.push(format!("Strict mode: {}", message));
+            } else {
+                warnings.push(message);
+            }
+        }
+        ValidationResult { is_valid: errors.is_empty(), errors, warnings }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strict_mode_promotes_only_selected_warnings() {
+        let mut findings = Findings::default();
+        findings.warn(WarningKind::NonUrnEpc, "EPC doesn't follow standard URN format: epc-1");
+        findings.warn(WarningKind::UnknownBizStep, "Business step 'pack' not found in ontology");
+
+        let lenient = findings.clone().into_result(false, &[WarningKind::NonUrnEpc]);
+        assert!(lenient.is_valid);
+        assert_eq!(lenient.warnings.len(), 2);
+
+        let strict = findings.into_result(true, &[WarningKind::NonUrnEpc]);
+        assert!(!strict.is_valid);
+        assert_eq!(strict.errors, vec!["Strict mode: EPC doesn't follow standard URN format: epc-1".to_string()]);
+        assert_eq!(strict.warnings, vec!["Business step 'pack' not found in ontology".to_string()]);
+    }
+}
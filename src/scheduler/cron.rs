@@ -0,0 +1,176 @@
+use crate::EpcisKgError;
+use chrono::{DateTime, Datelike, Duration, DurationRound, Timelike, Utc};
+
+/// When a job runs: a fixed interval or a five-field cron expression in UTC
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schedule {
+    Every(Duration),
+    Cron(CronFields),
+}
+
+/// Allowed values of each cron field
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronFields {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days_of_month: Vec<bool>,
+    months: Vec<bool>,
+    days_of_week: Vec<bool>,
+    /// Whether day-of-month / day-of-week were restricted (cron ORs them when both are)
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+impl Schedule {
+    pub fn parse(expression: &str) -> Result<Self, EpcisKgError> {
+        let invalid = |reason: &str| EpcisKgError::Config(format!("Invalid schedule '{}': {}", expression, reason));
+        let trimmed = expression.trim();
+
+        if let Some(interval) = trimmed.strip_prefix("@every") {
+            let interval = interval.trim();
+            let (number, unit) = interval.split_at(interval.find(|c: char| !c.is_ascii_digit()).unwrap_or(interval.len()));
+            let count: i64 = number.parse().map_err(|_| invalid("expected @every <n>s|m|h"))?;
+            let duration = match unit {
+                "" | "s" => Duration::seconds(count),
+                "m" => Duration::minutes(count),
+                "h" => Duration::hours(count),
+                _ => return Err(invalid("interval unit must be s, m or h")),
+            };
+            if count <= 0 {
+                return Err(invalid("interval must be positive"));
+            }
+            return Ok(Schedule::Every(duration));
+        }
+
+        let expanded = match trimmed {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(invalid("expected five fields: minute hour day-of-month month day-of-week"));
+        }
+        let field = |index: usize, min: u32, max: u32| parse_field(fields[index], min, max).map_err(|reason| invalid(&reason));
+
+        let mut days_of_week = field(4, 0, 7)?;
+        // Both 0 and 7 mean Sunday
+        days_of_week[0] |= days_of_week[7];
+        Ok(Schedule::Cron(CronFields {
+            minutes: field(0, 0, 59)?,
+            hours: field(1, 0, 23)?,
+            days_of_month: field(2, 1, 31)?,
+            months: field(3, 1, 12)?,
+            days_of_week,
+            dom_restricted: fields[2] != "*",
+            dow_restricted: fields[4] != "*",
+        }))
+    }
+
+    /// First run strictly after `after`; `None` if a cron expression never matches
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::Every(interval) => Some(after + *interval),
+            Schedule::Cron(fields) => fields.next_after(after),
+        }
+    }
+}
+
+impl CronFields {
+    fn day_matches(&self, time: DateTime<Utc>) -> bool {
+        let dom = self.days_of_month[time.day() as usize];
+        let dow = self.days_of_week[time.weekday().num_days_from_sunday() as usize];
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+
+    fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut time = after.duration_trunc(Duration::minutes(1)).ok()? + Duration::minutes(1);
+        // Any satisfiable expression matches within a few years (Feb 29 on a given weekday)
+        let limit = after + Duration::days(366 * 8);
+        while time <= limit {
+            if !self.months[time.month() as usize] || !self.day_matches(time) {
+                time = (time + Duration::days(1)).with_hour(0)?.with_minute(0)?;
+            } else if !self.hours[time.hour() as usize] {
+                time = (time + Duration::hours(1)).with_minute(0)?;
+            } else if !self.minutes[time.minute() as usize] {
+                time += Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+}
+
+/// Allowed values of one field, indexed by value: `*`, `n`, `a-b`, `*/s`, `a-b/s`, comma lists
+fn parse_field(field: &str, min: u32, max: u32) -> Result<Vec<bool>, String> {
+    let mut allowed = vec![false; max as usize + 1];
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().map_err(|_| format!("invalid step in '{}'", part))?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(format!("step must be positive in '{}'", part));
+        }
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (parse_value(start, part)?, parse_value(end, part)?),
+                None => {
+                    let value = parse_value(range, part)?;
+                    (value, if part.contains('/') { max } else { value })
+                }
+            },
+        };
+        if start < min || end > max || start > end {
+            return Err(format!("'{}' is outside {}-{}", part, min, max));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            allowed[value as usize] = true;
+        }
+    }
+    Ok(allowed)
+}
+
+fn parse_value(value: &str, part: &str) -> Result<u32, String> {
+    value.parse().map_err(|_| format!("invalid value in '{}'", part))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_run() {
+        let at = |value: &str| value.parse::<DateTime<Utc>>().unwrap();
+        let now = at("2024-03-01T10:07:30Z"); // a Friday
+
+        let every = Schedule::parse("@every 90s").unwrap();
+        assert_eq!(every.next_after(now), Some(at("2024-03-01T10:09:00Z")));
+
+        let quarter = Schedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(quarter.next_after(now), Some(at("2024-03-01T10:15:00Z")));
+
+        let nightly = Schedule::parse("30 2 * * *").unwrap();
+        assert_eq!(nightly.next_after(now), Some(at("2024-03-02T02:30:00Z")));
+
+        let monday = Schedule::parse("0 6 * * 1").unwrap();
+        assert_eq!(monday.next_after(now), Some(at("2024-03-04T06:00:00Z")));
+
+        let leap_day = Schedule::parse("0 0 29 2 *").unwrap();
+        assert_eq!(leap_day.next_after(now), Some(at("2028-02-29T00:00:00Z")));
+
+        assert!(Schedule::parse("61 * * * *").is_err());
+        assert!(Schedule::parse("@every 0s").is_err());
+        assert!(Schedule::parse("* * *").is_err());
+    }
+}
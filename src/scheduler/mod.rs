@@ -0,0 +1,255 @@
+//! Cron-like scheduler for the server's periodic jobs.
+//!
+//! Each enabled job gets its own task that sleeps until the next run (plus
+//! optional jitter). A job never overlaps itself: a run that comes due while
+//! the previous one is still going is skipped and counted. Last-run state is
+//! written to `scheduler.state_path` so interval jobs keep their cadence and
+//! missed cron runs are caught up once after a restart.
+
+pub mod cron;
+
+use crate::config::{JobDefinition, JobTask};
+use crate::EpcisKgError;
+use chrono::{DateTime, Duration, Utc};
+use self::cron::Schedule;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::{error, info, warn};
+
+/// Executes a job's task, returning a short summary of what it did
+pub type JobRunner = Arc<dyn Fn(&JobTask) -> Result<String, EpcisKgError> + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Succeeded,
+    Failed,
+}
+
+/// Run history of a job, persisted across restarts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JobState {
+    pub last_started: Option<DateTime<Utc>>,
+    pub last_finished: Option<DateTime<Utc>>,
+    pub last_status: Option<JobStatus>,
+    /// Summary of the last run, or its error
+    pub last_message: Option<String>,
+    pub runs: u64,
+    pub failures: u64,
+    /// Runs skipped because the previous run was still going
+    pub skipped: u64,
+    #[serde(skip_deserializing)]
+    pub next_run: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub name: String,
+    pub kind: &'static str,
+    pub schedule: String,
+    pub enabled: bool,
+    pub running: bool,
+    #[serde(flatten)]
+    pub state: JobState,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerOutcome {
+    Started,
+    AlreadyRunning,
+    UnknownJob,
+}
+
+struct Job {
+    definition: JobDefinition,
+    schedule: Schedule,
+    running: AtomicBool,
+}
+
+pub struct Scheduler {
+    jobs: Vec<Arc<Job>>,
+    states: Mutex<HashMap<String, JobState>>,
+    state_path: Option<PathBuf>,
+    runner: JobRunner,
+}
+
+impl Scheduler {
+    /// Parse the job schedules and load persisted state from `state_path`, if any
+    pub fn new(definitions: Vec<JobDefinition>, state_path: Option<PathBuf>, runner: JobRunner) -> Result<Self, EpcisKgError> {
+        let jobs = definitions
+            .into_iter()
+            .map(|definition| {
+                Ok(Arc::new(Job {
+                    schedule: Schedule::parse(&definition.schedule)?,
+                    definition,
+                    running: AtomicBool::new(false),
+                }))
+            })
+            .collect::<Result<Vec<_>, EpcisKgError>>()?;
+
+        let states = match state_path.as_deref().filter(|path| path.exists()) {
+            Some(path) => match std::fs::read_to_string(path).map(|content| serde_json::from_str(&content)) {
+                Ok(Ok(states)) => states,
+                _ => {
+                    warn!("Ignoring unreadable scheduler state at {}", path.display());
+                    HashMap::new()
+                }
+            },
+            None => HashMap::new(),
+        };
+
+        Ok(Self { jobs, states: Mutex::new(states), state_path, runner })
+    }
+
+    /// Spawn one task per enabled job
+    pub fn start(self: &Arc<Self>) {
+        for job in self.jobs.iter().filter(|job| job.definition.enabled) {
+            info!("Job {} ({}) scheduled: {}", job.definition.name, job.definition.task.kind(), job.definition.schedule);
+            let (scheduler, job) = (Arc::clone(self), Arc::clone(job));
+            tokio::spawn(async move {
+                let mut base = scheduler.state(&job.definition.name).last_started.unwrap_or_else(Utc::now);
+                loop {
+                    let Some(slot) = job.schedule.next_after(base) else {
+                        warn!("Job {} has no future runs; not scheduling it", job.definition.name);
+                        return;
+                    };
+                    // Runs missed while the server was down are caught up once, not replayed
+                    let slot = slot.max(Utc::now());
+                    let jitter = match job.definition.jitter_seconds {
+                        0 => 0,
+                        max => rand::thread_rng().gen_range(0..=max),
+                    };
+                    let next = slot + Duration::seconds(jitter as i64);
+                    scheduler.update(&job.definition.name, |state| state.next_run = Some(next));
+
+                    tokio::time::sleep((next - Utc::now()).to_std().unwrap_or_default()).await;
+                    if scheduler.begin(&job) {
+                        Arc::clone(&scheduler).execute(Arc::clone(&job)).await;
+                    } else {
+                        warn!("Job {} is still running; skipping this run", job.definition.name);
+                        scheduler.update(&job.definition.name, |state| state.skipped += 1);
+                    }
+                    base = slot;
+                }
+            });
+        }
+    }
+
+    /// Run a job now, in the background
+    pub fn trigger(self: &Arc<Self>, name: &str) -> TriggerOutcome {
+        let Some(job) = self.jobs.iter().find(|job| job.definition.name == name) else {
+            return TriggerOutcome::UnknownJob;
+        };
+        if !self.begin(job) {
+            return TriggerOutcome::AlreadyRunning;
+        }
+        tokio::spawn(Arc::clone(self).execute(Arc::clone(job)));
+        TriggerOutcome::Started
+    }
+
+    pub fn jobs(&self) -> Vec<JobInfo> {
+        self.jobs
+            .iter()
+            .map(|job| JobInfo {
+                name: job.definition.name.clone(),
+                kind: job.definition.task.kind(),
+                schedule: job.definition.schedule.clone(),
+                enabled: job.definition.enabled,
+                running: job.running.load(Ordering::SeqCst),
+                state: self.state(&job.definition.name),
+            })
+            .collect()
+    }
+
+    fn state(&self, name: &str) -> JobState {
+        self.states.lock().ok().and_then(|states| states.get(name).cloned()).unwrap_or_default()
+    }
+
+    fn update(&self, name: &str, change: impl FnOnce(&mut JobState)) {
+        if let Ok(mut states) = self.states.lock() {
+            change(states.entry(name.to_string()).or_default());
+        }
+    }
+
+    /// Mark the job running; `false` if it already is
+    fn begin(&self, job: &Job) -> bool {
+        if job.running.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+        self.update(&job.definition.name, |state| state.last_started = Some(Utc::now()));
+        true
+    }
+
+    async fn execute(self: Arc<Self>, job: Arc<Job>) {
+        let name = job.definition.name.clone();
+        let (runner, task) = (Arc::clone(&self.runner), job.definition.task.clone());
+        let result = tokio::task::spawn_blocking(move || runner(&task))
+            .await
+            .unwrap_or_else(|e| Err(EpcisKgError::Storage(format!("Job panicked: {}", e))));
+
+        match &result {
+            Ok(summary) => info!("Job {} finished: {}", name, summary),
+            Err(e) => error!("Job {} failed: {}", name, e),
+        }
+        self.update(&name, |state| {
+            state.last_finished = Some(Utc::now());
+            state.runs += 1;
+            match result {
+                Ok(summary) => {
+                    state.last_status = Some(JobStatus::Succeeded);
+                    state.last_message = Some(summary);
+                }
+                Err(e) => {
+                    state.failures += 1;
+                    state.last_status = Some(JobStatus::Failed);
+                    state.last_message = Some(e.to_string());
+                }
+            }
+        });
+        job.running.store(false, Ordering::SeqCst);
+        self.persist();
+    }
+
+    fn persist(&self) {
+        let Some(path) = &self.state_path else { return };
+        let Ok(content) = self.states.lock().map(|states| serde_json::to_string_pretty(&*states)) else { return };
+        let written = content
+            .map_err(EpcisKgError::from)
+            .and_then(|content| {
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                Ok(std::fs::write(path, content)?)
+            });
+        if let Err(e) = written {
+            warn!("Failed to save scheduler state to {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Write `content` as a timestamped backup in `directory`, pruning all but the newest `keep`
+pub fn write_backup(directory: &Path, content: &str, keep: usize) -> Result<String, EpcisKgError> {
+    std::fs::create_dir_all(directory)?;
+    let path = directory.join(format!("backup-{}.nt", Utc::now().format("%Y%m%dT%H%M%SZ")));
+    std::fs::write(&path, content)?;
+
+    let mut backups: Vec<PathBuf> = std::fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| name.starts_with("backup-") && name.ends_with(".nt"))
+        })
+        .collect();
+    backups.sort();
+    let pruned = backups.len().saturating_sub(keep);
+    for old in &backups[..pruned] {
+        std::fs::remove_file(old)?;
+    }
+
+    Ok(format!("Wrote {} ({} bytes), pruned {} old backups", path.display(), content.len(), pruned))
+}
@@ -0,0 +1,224 @@
+use crate::EpcisKgError;
+use crate::config::ArchiveConfig;
+use crate::storage::oxigraph_store::OxigraphStore;
+use oxrdf::{Graph as OxrdfGraph, GraphName};
+use oxttl::{NQuadsParser, NTriplesParser};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Graph receiving archived triples that carry no graph name
+pub const DEFAULT_ARCHIVE_GRAPH: &str = "urn:epcis:archive:default";
+
+/// One archived snapshot file, as listed over the API
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveSnapshot {
+    pub name: String,
+    pub size_bytes: u64,
+    pub modified: Option<String>,
+    /// Whether the snapshot is currently parsed in memory
+    pub loaded: bool,
+}
+
+struct LoadedArchive {
+    name: String,
+    modified: Option<SystemTime>,
+    graphs: Arc<HashMap<String, OxrdfGraph>>,
+}
+
+/// Archived snapshots that queries can opt into.
+///
+/// Snapshots are N-Triples files as written by backup jobs (`# Graph:`
+/// comments name the graph of the triples that follow) or N-Quads files.
+/// They are parsed only when a query first asks for them, and the most
+/// recently used ones are kept in memory; a file changed on disk is parsed
+/// again.
+pub struct ArchiveFederation {
+    directory: PathBuf,
+    max_loaded: usize,
+    loaded: Mutex<Vec<LoadedArchive>>,
+}
+
+impl ArchiveFederation {
+    pub fn new(config: &ArchiveConfig) -> Self {
+        Self {
+            directory: PathBuf::from(&config.directory),
+            max_loaded: config.max_loaded,
+            loaded: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Snapshot files in the archive directory, oldest name first
+    pub fn snapshots(&self) -> Result<Vec<ArchiveSnapshot>, EpcisKgError> {
+        if !self.directory.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let loaded = self.loaded.lock();
+        let mut snapshots = Vec::new();
+        for entry in std::fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else { continue };
+            if !is_snapshot_file(name) || !path.is_file() {
+                continue;
+            }
+
+            let metadata = std::fs::metadata(&path)?;
+            snapshots.push(ArchiveSnapshot {
+                name: name.to_string(),
+                size_bytes: metadata.len(),
+                modified: metadata
+                    .modified()
+                    .ok()
+                    .map(|modified| chrono::DateTime::<chrono::Utc>::from(modified).to_rfc3339()),
+                loaded: loaded.iter().any(|archive| archive.name == name),
+            });
+        }
+
+        snapshots.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(snapshots)
+    }
+
+    /// Graphs of one snapshot, parsing the file unless a current copy is cached
+    pub fn load(&self, name: &str) -> Result<Arc<HashMap<String, OxrdfGraph>>, EpcisKgError> {
+        if !is_snapshot_file(name) || name.contains(['/', '\\']) {
+            return Err(EpcisKgError::InvalidField {
+                field: "archives".to_string(),
+                message: format!("{} is not an archived snapshot name", name),
+            });
+        }
+        let path = self.directory.join(name);
+        if !path.is_file() {
+            return Err(EpcisKgError::NotFound { resource: "Archive".to_string(), id: name.to_string() });
+        }
+        let modified = std::fs::metadata(&path)?.modified().ok();
+
+        let mut loaded = self.loaded.lock();
+        if let Some(position) = loaded.iter().position(|archive| archive.name == name) {
+            let archive = loaded.remove(position);
+            if archive.modified == modified {
+                let graphs = Arc::clone(&archive.graphs);
+                loaded.push(archive);
+                return Ok(graphs);
+            }
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        let graphs = Arc::new(if name.ends_with(".nq") { parse_nquads(&content)? } else { parse_ntriples(&content)? });
+        loaded.push(LoadedArchive { name: name.to_string(), modified, graphs: Arc::clone(&graphs) });
+        let evicted = loaded.len().saturating_sub(self.max_loaded);
+        loaded.drain(..evicted);
+        Ok(graphs)
+    }
+
+    /// In-memory view of the live store with archived snapshots merged under it.
+    ///
+    /// `names` picks the snapshots to include, all of them when `None`. Triples
+    /// present both live and in an archive appear once. Returns the view and
+    /// the names of the snapshots it includes.
+    pub fn federated_view(&self, live: &OxigraphStore, names: Option<&[String]>) -> Result<(OxigraphStore, Vec<String>), EpcisKgError> {
+        let names: Vec<String> = match names {
+            Some(names) => names.to_vec(),
+            None => self.snapshots()?.into_iter().map(|snapshot| snapshot.name).collect(),
+        };
+
+        let mut archived: HashMap<String, OxrdfGraph> = HashMap::new();
+        for name in &names {
+            for (graph_name, graph) in self.load(name)?.iter() {
+                let target = archived.entry(graph_name.clone()).or_default();
+                for triple in graph.iter() {
+                    target.insert(triple);
+                }
+            }
+        }
+
+        Ok((live.with_overlay(&archived), names))
+    }
+}
+
+fn is_snapshot_file(name: &str) -> bool {
+    name.ends_with(".nt") || name.ends_with(".nq")
+}
+
+/// Parse an N-Triples snapshot whose `# Graph: <name>` comments start each graph
+fn parse_ntriples(content: &str) -> Result<HashMap<String, OxrdfGraph>, EpcisKgError> {
+    let mut graphs: HashMap<String, OxrdfGraph> = HashMap::new();
+    let mut current = DEFAULT_ARCHIVE_GRAPH.to_string();
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if let Some(graph_name) = trimmed.strip_prefix("# Graph:") {
+            current = graph_name.trim().to_string();
+            continue;
+        }
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let graph = graphs.entry(current.clone()).or_default();
+        for triple in NTriplesParser::new().with_quoted_triples().for_reader(trimmed.as_bytes()) {
+            let triple = triple.map_err(|e| EpcisKgError::RdfParsing(format!("Invalid archived triple: {}", e)))?;
+            graph.insert(triple.as_ref());
+        }
+    }
+
+    Ok(graphs)
+}
+
+fn parse_nquads(content: &str) -> Result<HashMap<String, OxrdfGraph>, EpcisKgError> {
+    let mut graphs: HashMap<String, OxrdfGraph> = HashMap::new();
+
+    for quad in NQuadsParser::new().with_quoted_triples().for_reader(content.as_bytes()) {
+        let quad = quad.map_err(|e| EpcisKgError::RdfParsing(format!("Invalid archived quad: {}", e)))?;
+        let graph_name = match &quad.graph_name {
+            GraphName::NamedNode(node) => node.as_str().to_string(),
+            GraphName::BlankNode(node) => format!("_:{}", node.as_str()),
+            GraphName::DefaultGraph => DEFAULT_ARCHIVE_GRAPH.to_string(),
+        };
+        graphs
+            .entry(graph_name)
+            .or_default()
+            .insert(oxrdf::TripleRef::new(quad.subject.as_ref(), quad.predicate.as_ref(), quad.object.as_ref()));
+    }
+
+    Ok(graphs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_federated_view_merges_archives_with_live_data() {
+        let directory = std::env::temp_dir().join(format!("epcis-archive-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(
+            directory.join("backup-20240101T000000Z.nt"),
+            "# Graph: urn:epcis:events\n<urn:epc:event:old> <urn:epcglobal:epcis:bizStep> <urn:epcglobal:cbv:bizstep:shipping> .\n",
+        )
+        .unwrap();
+
+        let federation = ArchiveFederation::new(&ArchiveConfig {
+            directory: directory.to_string_lossy().to_string(),
+            max_loaded: 1,
+        });
+        let snapshots = federation.snapshots().unwrap();
+        assert_eq!(snapshots.len(), 1);
+        assert!(!snapshots[0].loaded);
+
+        let live = OxigraphStore::new_memory().unwrap();
+        let (view, included) = federation.federated_view(&live, None).unwrap();
+        assert_eq!(included, vec!["backup-20240101T000000Z.nt".to_string()]);
+        assert_eq!(view.triples_in_graphs("urn:epcis:events").len(), 1);
+        assert!(live.triples_in_graphs("urn:epcis:events").is_empty());
+        assert!(federation.snapshots().unwrap()[0].loaded);
+
+        assert!(federation.load("../secrets.nt").is_err());
+        assert!(federation.load("missing.nt").is_err());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,459 @@
+//! RDF Dataset Canonicalization (RDFC-1.0, formerly URDNA2015).
+//!
+//! Blank nodes get deterministic `c14nN` labels derived from the dataset's
+//! structure, and quads are written as canonical N-Quads in code point order,
+//! so the same dataset always serializes to the same bytes whatever order the
+//! store iterates its graphs in. Exports, golden tests and dataset signatures
+//! build on this.
+//!
+//! RDF-star quoted triples are outside the specification; blank nodes inside
+//! them are hashed as if they occurred at the enclosing subject or object.
+
+use crate::EpcisKgError;
+use oxrdf::vocab::xsd;
+use oxrdf::{BlankNode, GraphName, Literal, Quad, Subject, Term, Triple};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Bound on Hash N-Degree Quads calls, so crafted datasets cannot make canonicalization run for ever
+const MAX_N_DEGREE_CALLS: usize = 10_000;
+
+/// Largest group of indistinguishable related blank nodes whose permutations are tried
+const MAX_PERMUTED_NODES: usize = 8;
+
+/// Canonical N-Quads of a dataset: blank nodes relabeled, one quad per line, sorted and de-duplicated
+pub fn canonical_nquads(quads: &[Quad]) -> Result<String, EpcisKgError> {
+    let mut lines: Vec<String> = canonicalize(quads)?
+        .iter()
+        .map(|quad| serialize_quad(quad, &|label: &str| label.to_string()))
+        .collect();
+    lines.sort();
+    Ok(lines.concat())
+}
+
+/// The dataset's distinct quads with blank nodes relabeled to their canonical identifiers
+pub fn canonicalize(quads: &[Quad]) -> Result<Vec<Quad>, EpcisKgError> {
+    let mut seen = HashSet::new();
+    let quads: Vec<&Quad> = quads.iter().filter(|quad| seen.insert(*quad)).collect();
+
+    let mut canonicalizer = Canonicalizer::new(&quads);
+    canonicalizer.issue_canonical_identifiers()?;
+
+    let canonical = &canonicalizer.canonical;
+    Ok(quads
+        .iter()
+        .map(|quad| relabel_quad(quad, &|label: &str| canonical.get(label).unwrap_or(label).to_string()))
+        .collect())
+}
+
+/// Issues `<prefix><n>` identifiers in order, remembering what it issued
+#[derive(Debug, Clone)]
+struct IdentifierIssuer {
+    prefix: &'static str,
+    issued: HashMap<String, String>,
+    /// Existing identifiers in issue order
+    order: Vec<String>,
+}
+
+impl IdentifierIssuer {
+    fn new(prefix: &'static str) -> Self {
+        Self {
+            prefix,
+            issued: HashMap::new(),
+            order: Vec::new(),
+        }
+    }
+
+    fn get(&self, existing: &str) -> Option<&str> {
+        self.issued.get(existing).map(String::as_str)
+    }
+
+    fn issue(&mut self, existing: &str) -> String {
+        if let Some(issued) = self.issued.get(existing) {
+            return issued.clone();
+        }
+        let issued = format!("{}{}", self.prefix, self.order.len());
+        self.issued.insert(existing.to_string(), issued.clone());
+        self.order.push(existing.to_string());
+        issued
+    }
+}
+
+struct Canonicalizer<'a> {
+    quads: &'a [&'a Quad],
+    /// Indexes of the quads each blank node occurs in
+    blank_node_quads: HashMap<String, Vec<usize>>,
+    canonical: IdentifierIssuer,
+    n_degree_calls: usize,
+}
+
+impl<'a> Canonicalizer<'a> {
+    fn new(quads: &'a [&'a Quad]) -> Self {
+        let mut blank_node_quads: HashMap<String, Vec<usize>> = HashMap::new();
+        for (index, quad) in quads.iter().enumerate() {
+            for (_, blank_node) in positioned_blank_nodes(quad) {
+                let indexes = blank_node_quads.entry(blank_node).or_default();
+                if indexes.last() != Some(&index) {
+                    indexes.push(index);
+                }
+            }
+        }
+
+        Self {
+            quads,
+            blank_node_quads,
+            canonical: IdentifierIssuer::new("c14n"),
+            n_degree_calls: 0,
+        }
+    }
+
+    /// Steps 3 to 6 of the canonicalization algorithm
+    fn issue_canonical_identifiers(&mut self) -> Result<(), EpcisKgError> {
+        let mut blank_nodes: Vec<&String> = self.blank_node_quads.keys().collect();
+        blank_nodes.sort();
+
+        let mut by_hash: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for blank_node in blank_nodes {
+            by_hash.entry(self.hash_first_degree(blank_node)).or_default().push(blank_node.clone());
+        }
+
+        let mut shared = Vec::new();
+        for (_, blank_nodes) in by_hash {
+            match blank_nodes.as_slice() {
+                [unique] => {
+                    self.canonical.issue(unique);
+                }
+                _ => shared.push(blank_nodes),
+            }
+        }
+
+        for blank_nodes in shared {
+            let mut paths = Vec::new();
+            for blank_node in &blank_nodes {
+                if self.canonical.get(blank_node).is_some() {
+                    continue;
+                }
+                let mut issuer = IdentifierIssuer::new("b");
+                issuer.issue(blank_node);
+                paths.push(self.hash_n_degree(blank_node, issuer)?);
+            }
+            paths.sort_by(|a, b| a.0.cmp(&b.0));
+            for (_, issuer) in paths {
+                for existing in &issuer.order {
+                    self.canonical.issue(existing);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Hash of the quads mentioning `reference`, with it written `_:a` and every other blank node `_:z`
+    fn hash_first_degree(&self, reference: &str) -> String {
+        let mut lines: Vec<String> = self.blank_node_quads[reference]
+            .iter()
+            .map(|&index| serialize_quad(self.quads[index], &|label: &str| (if label == reference { "a" } else { "z" }).to_string()))
+            .collect();
+        lines.sort();
+        sha256_hex(&lines.concat())
+    }
+
+    fn hash_related(&self, related: &str, quad: &Quad, issuer: &IdentifierIssuer, position: char) -> String {
+        let mut input = position.to_string();
+        if position != 'g' {
+            input.push_str(&format!("<{}>", quad.predicate.as_str()));
+        }
+        match self.canonical.get(related).or_else(|| issuer.get(related)) {
+            Some(identifier) => input.push_str(&format!("_:{}", identifier)),
+            None => input.push_str(&self.hash_first_degree(related)),
+        }
+        sha256_hex(&input)
+    }
+
+    fn hash_n_degree(&mut self, identifier: &str, mut issuer: IdentifierIssuer) -> Result<(String, IdentifierIssuer), EpcisKgError> {
+        self.n_degree_calls += 1;
+        if self.n_degree_calls > MAX_N_DEGREE_CALLS {
+            return Err(EpcisKgError::Validation(
+                "Dataset is too complex to canonicalize (too many indistinguishable blank nodes)".to_string(),
+            ));
+        }
+
+        let mut related_by_hash: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for &index in &self.blank_node_quads[identifier] {
+            let quad = self.quads[index];
+            for (position, related) in positioned_blank_nodes(quad) {
+                if related != identifier {
+                    let hash = self.hash_related(&related, quad, &issuer, position);
+                    related_by_hash.entry(hash).or_default().push(related);
+                }
+            }
+        }
+
+        let mut data = String::new();
+        for (hash, related) in related_by_hash {
+            data.push_str(&hash);
+            if related.len() > MAX_PERMUTED_NODES {
+                return Err(EpcisKgError::Validation(format!(
+                    "Dataset is too complex to canonicalize ({} indistinguishable blank nodes)",
+                    related.len()
+                )));
+            }
+
+            let mut chosen: Option<(String, IdentifierIssuer)> = None;
+            'permutations: for permutation in permutations(&related) {
+                let mut issuer_copy = issuer.clone();
+                let mut path = String::new();
+                let mut recursion = Vec::new();
+
+                for node in &permutation {
+                    match self.canonical.get(node) {
+                        Some(canonical) => path.push_str(&format!("_:{}", canonical)),
+                        None => {
+                            if issuer_copy.get(node).is_none() {
+                                recursion.push(node.clone());
+                            }
+                            path.push_str(&format!("_:{}", issuer_copy.issue(node)));
+                        }
+                    }
+                    if is_worse(&chosen, &path) {
+                        continue 'permutations;
+                    }
+                }
+
+                for node in recursion {
+                    let (result_hash, result_issuer) = self.hash_n_degree(&node, issuer_copy.clone())?;
+                    path.push_str(&format!("_:{}<{}>", issuer_copy.issue(&node), result_hash));
+                    issuer_copy = result_issuer;
+                    if is_worse(&chosen, &path) {
+                        continue 'permutations;
+                    }
+                }
+
+                if !chosen.as_ref().is_some_and(|(chosen_path, _)| path >= *chosen_path) {
+                    chosen = Some((path, issuer_copy));
+                }
+            }
+
+            if let Some((path, chosen_issuer)) = chosen {
+                data.push_str(&path);
+                issuer = chosen_issuer;
+            }
+        }
+
+        Ok((sha256_hex(&data), issuer))
+    }
+}
+
+/// Whether `path` can no longer beat the chosen path
+fn is_worse(chosen: &Option<(String, IdentifierIssuer)>, path: &str) -> bool {
+    chosen
+        .as_ref()
+        .is_some_and(|(chosen_path, _)| path.len() >= chosen_path.len() && path > chosen_path.as_str())
+}
+
+fn permutations(items: &[String]) -> Vec<Vec<String>> {
+    if items.len() <= 1 {
+        return vec![items.to_vec()];
+    }
+    let mut result = Vec::new();
+    for (index, first) in items.iter().enumerate() {
+        let mut rest = items.to_vec();
+        rest.remove(index);
+        for mut permutation in permutations(&rest) {
+            permutation.insert(0, first.clone());
+            result.push(permutation);
+        }
+    }
+    result
+}
+
+fn sha256_hex(data: &str) -> String {
+    Sha256::digest(data.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Blank nodes of a quad with their position: `s`ubject, `o`bject or `g`raph
+fn positioned_blank_nodes(quad: &Quad) -> Vec<(char, String)> {
+    let mut subject = Vec::new();
+    subject_blank_nodes(&quad.subject, &mut subject);
+    let mut object = Vec::new();
+    term_blank_nodes(&quad.object, &mut object);
+
+    let mut positioned: Vec<(char, String)> = subject.into_iter().map(|node| ('s', node)).collect();
+    positioned.extend(object.into_iter().map(|node| ('o', node)));
+    if let GraphName::BlankNode(node) = &quad.graph_name {
+        positioned.push(('g', node.as_str().to_string()));
+    }
+    positioned
+}
+
+fn subject_blank_nodes(subject: &Subject, out: &mut Vec<String>) {
+    match subject {
+        Subject::BlankNode(node) => out.push(node.as_str().to_string()),
+        Subject::Triple(triple) => triple_blank_nodes(triple, out),
+        Subject::NamedNode(_) => {}
+    }
+}
+
+fn term_blank_nodes(term: &Term, out: &mut Vec<String>) {
+    match term {
+        Term::BlankNode(node) => out.push(node.as_str().to_string()),
+        Term::Triple(triple) => triple_blank_nodes(triple, out),
+        Term::NamedNode(_) | Term::Literal(_) => {}
+    }
+}
+
+fn triple_blank_nodes(triple: &Triple, out: &mut Vec<String>) {
+    subject_blank_nodes(&triple.subject, out);
+    term_blank_nodes(&triple.object, out);
+}
+
+/// Canonical N-Quads line, with blank node labels mapped by `label`
+fn serialize_quad(quad: &Quad, label: &dyn Fn(&str) -> String) -> String {
+    let mut line = format!(
+        "{} <{}> {}",
+        serialize_subject(&quad.subject, label),
+        quad.predicate.as_str(),
+        serialize_term(&quad.object, label)
+    );
+    match &quad.graph_name {
+        GraphName::NamedNode(graph) => line.push_str(&format!(" <{}>", graph.as_str())),
+        GraphName::BlankNode(graph) => line.push_str(&format!(" _:{}", label(graph.as_str()))),
+        GraphName::DefaultGraph => {}
+    }
+    line.push_str(" .\n");
+    line
+}
+
+fn serialize_subject(subject: &Subject, label: &dyn Fn(&str) -> String) -> String {
+    match subject {
+        Subject::NamedNode(node) => format!("<{}>", node.as_str()),
+        Subject::BlankNode(node) => format!("_:{}", label(node.as_str())),
+        Subject::Triple(triple) => serialize_triple(triple, label),
+    }
+}
+
+fn serialize_term(term: &Term, label: &dyn Fn(&str) -> String) -> String {
+    match term {
+        Term::NamedNode(node) => format!("<{}>", node.as_str()),
+        Term::BlankNode(node) => format!("_:{}", label(node.as_str())),
+        Term::Literal(literal) => serialize_literal(literal),
+        Term::Triple(triple) => serialize_triple(triple, label),
+    }
+}
+
+fn serialize_triple(triple: &Triple, label: &dyn Fn(&str) -> String) -> String {
+    format!(
+        "<< {} <{}> {} >>",
+        serialize_subject(&triple.subject, label),
+        triple.predicate.as_str(),
+        serialize_term(&triple.object, label)
+    )
+}
+
+/// `xsd:string` is implicit; only the characters canonical N-Quads requires are escaped
+fn serialize_literal(literal: &Literal) -> String {
+    let mut out = String::from("\"");
+    for c in literal.value().chars() {
+        match c {
+            '\u{8}' => out.push_str("\\b"),
+            '\t' => out.push_str("\\t"),
+            '\n' => out.push_str("\\n"),
+            '\u{c}' => out.push_str("\\f"),
+            '\r' => out.push_str("\\r"),
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{0}'..='\u{1f}' | '\u{7f}' => out.push_str(&format!("\\u{:04X}", c as u32)),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+
+    if let Some(language) = literal.language() {
+        out.push('@');
+        out.push_str(language);
+    } else if literal.datatype() != xsd::STRING {
+        out.push_str(&format!("^^<{}>", literal.datatype().as_str()));
+    }
+    out
+}
+
+fn relabel_quad(quad: &Quad, label: &dyn Fn(&str) -> String) -> Quad {
+    let graph_name = match &quad.graph_name {
+        GraphName::BlankNode(graph) => GraphName::BlankNode(BlankNode::new_unchecked(label(graph.as_str()))),
+        other => other.clone(),
+    };
+    Quad::new(
+        relabel_subject(&quad.subject, label),
+        quad.predicate.clone(),
+        relabel_term(&quad.object, label),
+        graph_name,
+    )
+}
+
+fn relabel_subject(subject: &Subject, label: &dyn Fn(&str) -> String) -> Subject {
+    match subject {
+        Subject::BlankNode(node) => BlankNode::new_unchecked(label(node.as_str())).into(),
+        Subject::Triple(triple) => Subject::Triple(Box::new(relabel_triple(triple, label))),
+        Subject::NamedNode(_) => subject.clone(),
+    }
+}
+
+fn relabel_term(term: &Term, label: &dyn Fn(&str) -> String) -> Term {
+    match term {
+        Term::BlankNode(node) => BlankNode::new_unchecked(label(node.as_str())).into(),
+        Term::Triple(triple) => Term::Triple(Box::new(relabel_triple(triple, label))),
+        Term::NamedNode(_) | Term::Literal(_) => term.clone(),
+    }
+}
+
+fn relabel_triple(triple: &Triple, label: &dyn Fn(&str) -> String) -> Triple {
+    Triple::new(
+        relabel_subject(&triple.subject, label),
+        triple.predicate.clone(),
+        relabel_term(&triple.object, label),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxrdf::NamedNode;
+
+    #[test]
+    fn test_canonical_labels_ignore_input_labels_and_order() {
+        let iri = |local: &str| NamedNode::new_unchecked(format!("http://example.com/#{}", local));
+        let quad = |subject: Subject, predicate: &str, object: Term| Quad::new(subject, iri(predicate), object, GraphName::DefaultGraph);
+        let dataset = |first: &str, second: &str| {
+            let (e0, e1) = (BlankNode::new_unchecked(first), BlankNode::new_unchecked(second));
+            vec![
+                quad(e1.clone().into(), "t", iri("u").into()),
+                quad(iri("p").into(), "q", e0.clone().into()),
+                quad(e0.into(), "s", iri("u").into()),
+                quad(iri("p").into(), "r", e1.into()),
+            ]
+        };
+
+        // Example from the RDFC-1.0 specification
+        let expected = "<http://example.com/#p> <http://example.com/#q> _:c14n0 .\n\
+                        <http://example.com/#p> <http://example.com/#r> _:c14n1 .\n\
+                        _:c14n0 <http://example.com/#s> <http://example.com/#u> .\n\
+                        _:c14n1 <http://example.com/#t> <http://example.com/#u> .\n";
+        assert_eq!(canonical_nquads(&dataset("e0", "e1")).unwrap(), expected);
+        let mut reordered = dataset("x", "y");
+        reordered.reverse();
+        assert_eq!(canonical_nquads(&reordered).unwrap(), expected);
+
+        // A cycle of indistinguishable nodes goes through Hash N-Degree Quads
+        let cycle = |labels: [&str; 3]| {
+            (0..3)
+                .map(|i| {
+                    let (from, to) = (BlankNode::new_unchecked(labels[i]), BlankNode::new_unchecked(labels[(i + 1) % 3]));
+                    quad(from.into(), "next", to.into())
+                })
+                .collect::<Vec<_>>()
+        };
+        let canonical = canonical_nquads(&cycle(["a", "b", "c"])).unwrap();
+        assert_eq!(canonical, canonical_nquads(&cycle(["z", "m", "b"])).unwrap());
+        assert!(canonical.contains("_:c14n2"));
+    }
+}
@@ -0,0 +1,278 @@
+use crate::config::CarbonConfig;
+use crate::storage::inventory::{class_pattern_matches, product_class};
+use crate::storage::recall::LineageEvent;
+use chrono::{DateTime, Utc};
+use oxrdf::{Literal, NamedNode, Triple};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+
+const EPCIS_NS: &str = "urn:epcglobal:epcis:";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// EPCs shipped by one event and received by another, with the estimated footprint
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Shipment {
+    /// Stable identifier derived from the two events
+    pub id: String,
+    pub shipping_event: String,
+    pub receiving_event: String,
+    pub origin: Option<String>,
+    pub destination: Option<String>,
+    pub shipped_at: DateTime<Utc>,
+    pub received_at: DateTime<Utc>,
+    pub epcs: Vec<String>,
+    pub weight_kg: f64,
+    /// Transport mode of the configured lane; `None` when the lane is not configured
+    pub mode: Option<String>,
+    pub distance_km: Option<f64>,
+    pub kg_co2e: Option<f64>,
+    pub cost: Option<f64>,
+}
+
+impl Shipment {
+    pub fn iri(&self) -> String {
+        format!("urn:epcis:shipment:{}", self.id)
+    }
+}
+
+/// Footprint of the shipments by one transport mode
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ModeTotals {
+    pub shipments: usize,
+    pub tonne_km: f64,
+    pub kg_co2e: f64,
+    pub cost: f64,
+}
+
+/// Shipments and their footprint totals, for ESG reporting
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CarbonReport {
+    pub shipments: Vec<Shipment>,
+    /// Shipments with an estimate
+    pub estimated: usize,
+    pub total_kg_co2e: f64,
+    pub total_cost: f64,
+    pub currency: String,
+    pub by_mode: BTreeMap<String, ModeTotals>,
+    /// `origin -> destination` of shipments on lanes without configured factors
+    pub unconfigured_lanes: Vec<String>,
+}
+
+fn is_step(event: &LineageEvent, step: &str) -> bool {
+    event.biz_step.as_deref().map_or(false, |biz_step| biz_step.rsplit([':', '/']).next() == Some(step))
+}
+
+/// Pair each shipping event with the next receiving event of the same EPC.
+///
+/// EPCs shipped and received by the same two events form one shipment. A
+/// later shipping event before any receipt replaces the earlier one.
+pub fn detect_shipments(events: &[LineageEvent]) -> Vec<Shipment> {
+    let mut events: Vec<&LineageEvent> = events.iter().filter(|event| is_step(event, "shipping") || is_step(event, "receiving")).collect();
+    events.sort_by(|a, b| a.event_time.cmp(&b.event_time).then_with(|| a.event_id.cmp(&b.event_id)));
+
+    let mut in_transit: HashMap<&str, &LineageEvent> = HashMap::new();
+    let mut pairs: BTreeMap<(&str, &str), (&LineageEvent, &LineageEvent, Vec<String>)> = BTreeMap::new();
+    for event in events {
+        let shipping = is_step(event, "shipping");
+        for epc in &event.epcs {
+            if shipping {
+                in_transit.insert(epc.as_str(), event);
+            } else if let Some(shipped) = in_transit.remove(epc.as_str()) {
+                pairs
+                    .entry((shipped.event_id.as_str(), event.event_id.as_str()))
+                    .or_insert_with(|| (shipped, event, Vec::new()))
+                    .2
+                    .push(epc.clone());
+            }
+        }
+    }
+
+    let mut shipments: Vec<Shipment> = pairs
+        .into_values()
+        .map(|(shipped, received, epcs)| {
+            let digest = Sha256::digest(format!("{}|{}", shipped.event_id, received.event_id).as_bytes());
+            Shipment {
+                id: digest.iter().take(8).map(|byte| format!("{:02x}", byte)).collect(),
+                shipping_event: shipped.event_id.clone(),
+                receiving_event: received.event_id.clone(),
+                origin: shipped.biz_location.clone(),
+                destination: received.biz_location.clone().or_else(|| shipped.destination_locations.first().cloned()),
+                shipped_at: shipped.event_time,
+                received_at: received.event_time,
+                epcs,
+                weight_kg: 0.0,
+                mode: None,
+                distance_km: None,
+                kg_co2e: None,
+                cost: None,
+            }
+        })
+        .collect();
+    shipments.sort_by(|a, b| a.shipped_at.cmp(&b.shipped_at).then_with(|| a.received_at.cmp(&b.received_at)));
+    shipments
+}
+
+/// Shipping weight of an EPC: the first product weight covering it, or the default
+pub fn epc_weight_kg(epc: &str, config: &CarbonConfig) -> f64 {
+    config
+        .product_weights
+        .iter()
+        .find(|weight| class_pattern_matches(&weight.product_class, epc) || weight.product_class == product_class(epc))
+        .map_or(config.default_epc_weight_kg, |weight| weight.kg)
+}
+
+/// Weigh each shipment and estimate CO2e and cost from its lane
+pub fn estimate(mut shipments: Vec<Shipment>, config: &CarbonConfig) -> Vec<Shipment> {
+    for shipment in &mut shipments {
+        shipment.weight_kg = shipment.epcs.iter().map(|epc| epc_weight_kg(epc, config)).sum();
+        let (Some(origin), Some(destination)) = (&shipment.origin, &shipment.destination) else { continue };
+        let Some(lane) = config.lane(origin, destination) else { continue };
+        let Some(mode) = config.mode(&lane.mode) else { continue };
+
+        let tonne_km = shipment.weight_kg / 1000.0 * lane.distance_km;
+        shipment.mode = Some(mode.name.clone());
+        shipment.distance_km = Some(lane.distance_km);
+        shipment.kg_co2e = Some(tonne_km * lane.kg_co2e_per_tonne_km.unwrap_or(mode.kg_co2e_per_tonne_km));
+        shipment.cost = Some(tonne_km * lane.cost_per_tonne_km.unwrap_or(mode.cost_per_tonne_km));
+    }
+    shipments
+}
+
+/// Totals over the shipments, per transport mode and overall
+pub fn summarize(shipments: Vec<Shipment>, currency: &str) -> CarbonReport {
+    let mut by_mode: BTreeMap<String, ModeTotals> = BTreeMap::new();
+    let mut unconfigured_lanes = Vec::new();
+    for shipment in &shipments {
+        let (Some(mode), Some(distance_km), Some(kg_co2e), Some(cost)) = (&shipment.mode, shipment.distance_km, shipment.kg_co2e, shipment.cost)
+        else {
+            let lane = format!(
+                "{} -> {}",
+                shipment.origin.as_deref().unwrap_or("unknown"),
+                shipment.destination.as_deref().unwrap_or("unknown")
+            );
+            if !unconfigured_lanes.contains(&lane) {
+                unconfigured_lanes.push(lane);
+            }
+            continue;
+        };
+        let totals = by_mode.entry(mode.clone()).or_default();
+        totals.shipments += 1;
+        totals.tonne_km += shipment.weight_kg / 1000.0 * distance_km;
+        totals.kg_co2e += kg_co2e;
+        totals.cost += cost;
+    }
+    unconfigured_lanes.sort();
+
+    CarbonReport {
+        estimated: by_mode.values().map(|totals| totals.shipments).sum(),
+        total_kg_co2e: by_mode.values().map(|totals| totals.kg_co2e).sum(),
+        total_cost: by_mode.values().map(|totals| totals.cost).sum(),
+        currency: currency.to_string(),
+        by_mode,
+        unconfigured_lanes,
+        shipments,
+    }
+}
+
+/// One `epcis:Shipment` resource per shipment, with its estimate when there is one
+pub fn shipment_triples(shipments: &[Shipment]) -> Vec<Triple> {
+    let epcis = |local: &str| NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+    let typed = |value: String, datatype: &str| Literal::new_typed_literal(value, NamedNode::new_unchecked(format!("{}{}", XSD, datatype)));
+    let decimal = |value: f64| typed(format!("{:.4}", value), "decimal");
+
+    let mut triples = Vec::new();
+    for shipment in shipments {
+        let (Ok(node), Ok(shipped), Ok(received)) = (
+            NamedNode::new(shipment.iri()),
+            NamedNode::new(format!("urn:epc:event:{}", shipment.shipping_event)),
+            NamedNode::new(format!("urn:epc:event:{}", shipment.receiving_event)),
+        ) else {
+            continue;
+        };
+        triples.push(Triple::new(node.clone(), NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"), epcis("Shipment")));
+        triples.push(Triple::new(node.clone(), epcis("shippingEvent"), shipped));
+        triples.push(Triple::new(node.clone(), epcis("receivingEvent"), received));
+        triples.push(Triple::new(node.clone(), epcis("shipmentWeightKg"), decimal(shipment.weight_kg)));
+        for epc in &shipment.epcs {
+            let Ok(epc) = NamedNode::new(epc.as_str()) else { continue };
+            triples.push(Triple::new(node.clone(), epcis("shipmentEPC"), epc));
+        }
+        let (Some(mode), Some(distance_km), Some(kg_co2e), Some(cost)) = (&shipment.mode, shipment.distance_km, shipment.kg_co2e, shipment.cost) else { continue };
+        triples.push(Triple::new(node.clone(), epcis("transportMode"), Literal::new_simple_literal(mode)));
+        triples.push(Triple::new(node.clone(), epcis("distanceKm"), decimal(distance_km)));
+        triples.push(Triple::new(node.clone(), epcis("estimatedCO2eKg"), decimal(kg_co2e)));
+        triples.push(Triple::new(node, epcis("estimatedCost"), decimal(cost)));
+    }
+    triples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ProductWeight, TransportLane, TransportMode};
+    use chrono::Duration;
+
+    fn event(id: &str, hours: i64, biz_step: &str, location: &str, epcs: &[&str]) -> LineageEvent {
+        let start: DateTime<Utc> = "2024-03-01T00:00:00Z".parse().unwrap();
+        LineageEvent {
+            event_id: id.to_string(),
+            event_type: "ObjectEvent".to_string(),
+            action: "OBSERVE".to_string(),
+            event_time: start + Duration::hours(hours),
+            biz_step: Some(format!("urn:epcglobal:cbv:bizstep:{}", biz_step)),
+            biz_location: Some(location.to_string()),
+            epcs: epcs.iter().map(|epc| epc.to_string()).collect(),
+            parent: None,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            destination_parties: Vec::new(),
+            destination_locations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_shipments_are_paired_weighed_and_estimated_per_lane() {
+        let (plant, dc, store) = ("urn:epc:id:sgln:0614141.00001.0", "urn:epc:id:sgln:0614141.00002.0", "urn:epc:id:sgln:0614141.00003.0");
+        let (a, b) = ("urn:epc:id:sgtin:0614141.107346.1", "urn:epc:id:sgtin:0614141.107346.2");
+        let events = vec![
+            event("ship-1", 0, "shipping", plant, &[a, b]),
+            // Received at the DC in two deliveries
+            event("recv-1", 10, "receiving", dc, &[a]),
+            event("recv-2", 12, "receiving", dc, &[b]),
+            event("ship-2", 20, "shipping", dc, &[a]),
+            event("recv-3", 30, "receiving", store, &[a]),
+            // Received without being shipped
+            event("recv-4", 40, "receiving", store, &[b]),
+        ];
+        let config = CarbonConfig {
+            modes: vec![TransportMode { name: "road".to_string(), kg_co2e_per_tonne_km: 0.1, cost_per_tonne_km: 0.5 }],
+            lanes: vec![TransportLane {
+                from: dc.to_string(),
+                to: plant.to_string(),
+                mode: "road".to_string(),
+                distance_km: 400.0,
+                kg_co2e_per_tonne_km: None,
+                cost_per_tonne_km: Some(1.0),
+            }],
+            product_weights: vec![ProductWeight { product_class: "urn:epc:idpat:sgtin:0614141.107346.*".to_string(), kg: 500.0 }],
+            ..CarbonConfig::default()
+        };
+
+        let shipments = estimate(detect_shipments(&events), &config);
+        let pairs: Vec<(&str, &str)> = shipments.iter().map(|s| (s.shipping_event.as_str(), s.receiving_event.as_str())).collect();
+        assert_eq!(pairs, vec![("ship-1", "recv-1"), ("ship-1", "recv-2"), ("ship-2", "recv-3")]);
+        // 0.5 t over 400 km, on a lane configured in the other direction
+        assert_eq!(shipments[0].kg_co2e, Some(20.0));
+        assert_eq!(shipments[0].cost, Some(200.0));
+
+        let report = summarize(shipments, "EUR");
+        assert_eq!(report.estimated, 2);
+        assert_eq!(report.total_kg_co2e, 40.0);
+        assert_eq!(report.by_mode["road"].tonne_km, 400.0);
+        assert_eq!(report.unconfigured_lanes, vec![format!("{} -> {}", dc, store)]);
+
+        let triples = shipment_triples(&report.shipments);
+        assert_eq!(triples.iter().filter(|t| t.predicate.as_str() == "urn:epcglobal:epcis:estimatedCO2eKg").count(), 2);
+    }
+}
@@ -0,0 +1,99 @@
+use crate::config::ChangefeedFormat;
+use crate::utils::jsonld::JsonLdFramer;
+use crate::EpcisKgError;
+use oxrdf::{GraphName, NamedNode, Quad, Triple};
+use serde_json::{json, Value};
+
+/// A change committed to the store
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// An event graph was stored
+    Event { event_id: String, graph: String, triples: Vec<Triple> },
+    /// Facts were added to a derived graph
+    Inferred { graph: String, triples: Vec<Triple> },
+}
+
+impl Change {
+    pub fn graph(&self) -> &str {
+        match self {
+            Change::Event { graph, .. } | Change::Inferred { graph, .. } => graph,
+        }
+    }
+
+    pub fn triples(&self) -> &[Triple] {
+        match self {
+            Change::Event { triples, .. } | Change::Inferred { triples, .. } => triples,
+        }
+    }
+
+    /// Record key: the event ID for events, the graph name for inferences
+    pub fn key(&self) -> &str {
+        match self {
+            Change::Event { event_id, .. } => event_id,
+            Change::Inferred { graph, .. } => graph,
+        }
+    }
+}
+
+/// Receives every change after the store applied it.
+///
+/// Sinks are called with the store lock held, so they should hand the change
+/// off (e.g. to a producer queue) rather than block on I/O.
+pub trait ChangeSink: Send + Sync {
+    fn publish(&self, change: &Change);
+}
+
+/// One `<s> <p> <o> <graph> .` line per triple
+pub fn to_nquads(graph: &str, triples: &[Triple]) -> String {
+    let graph_name = NamedNode::new(graph).map(GraphName::NamedNode).unwrap_or(GraphName::DefaultGraph);
+    triples
+        .iter()
+        .map(|triple| {
+            let quad = Quad::new(triple.subject.clone(), triple.predicate.clone(), triple.object.clone(), graph_name.clone());
+            format!("{} .\n", quad)
+        })
+        .collect()
+}
+
+/// Record payload: events in the configured format framed with `frame`,
+/// inferences always as N-Quads
+pub fn serialize(change: &Change, format: ChangefeedFormat, frame: &Value) -> Result<Vec<u8>, EpcisKgError> {
+    match (change, format) {
+        (Change::Event { event_id, triples, .. }, ChangefeedFormat::EpcisJson) => {
+            let document = JsonLdFramer::frame(triples, frame)?;
+            Ok(serde_json::to_vec(&json!({ "eventID": event_id, "event": document }))?)
+        }
+        _ => Ok(to_nquads(change.graph(), change.triples()).into_bytes()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::JsonLdConfig;
+    use crate::utils::jsonld::{load_frame, EVENT_FRAME};
+    use oxrdf::Literal;
+
+    #[test]
+    fn test_serialize_event_as_nquads_and_json() {
+        let event = NamedNode::new_unchecked("urn:epc:event:e1");
+        let triples = vec![
+            Triple::new(
+                event.clone(),
+                NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"),
+                NamedNode::new_unchecked("urn:epcglobal:epcis:ObjectEvent"),
+            ),
+            Triple::new(event, NamedNode::new_unchecked("urn:epcglobal:epcis:action"), Literal::new_simple_literal("ADD")),
+        ];
+        let change = Change::Event { event_id: "e1".to_string(), graph: "urn:epcis:event:e1".to_string(), triples };
+        let frame = load_frame(EVENT_FRAME, &JsonLdConfig::default()).unwrap();
+
+        let nquads = String::from_utf8(serialize(&change, ChangefeedFormat::Nquads, &frame).unwrap()).unwrap();
+        assert_eq!(nquads.lines().count(), 2);
+        assert!(nquads.lines().all(|line| line.ends_with("<urn:epcis:event:e1> .")));
+
+        let json: Value = serde_json::from_slice(&serialize(&change, ChangefeedFormat::EpcisJson, &frame).unwrap()).unwrap();
+        assert_eq!(json["eventID"], "e1");
+        assert_eq!(json["event"]["@graph"].as_array().map(Vec::len), Some(1));
+    }
+}
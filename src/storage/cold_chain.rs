@@ -0,0 +1,194 @@
+use crate::config::TemperatureProfile;
+use crate::storage::inventory::{class_pattern_matches, product_class};
+use chrono::{DateTime, Utc};
+use oxrdf::{Literal, NamedNode, Triple};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+const EPCIS_NS: &str = "urn:epcglobal:epcis:";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// A temperature reading attributed to one EPC
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemperatureReading {
+    pub epc: String,
+    pub event_id: String,
+    pub time: DateTime<Utc>,
+    pub celsius: f64,
+}
+
+/// An interval in which an EPC was outside its profile's temperature range
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Excursion {
+    /// Stable identifier derived from the EPC and start time
+    pub id: String,
+    pub epc: String,
+    pub product_class: String,
+    pub min_celsius: f64,
+    pub max_celsius: f64,
+    pub start: String,
+    /// First in-range reading after the excursion; `None` while still out of range
+    pub end: Option<String>,
+    pub duration_minutes: i64,
+    /// Reading furthest outside the range
+    pub extreme_celsius: f64,
+    pub readings: usize,
+    pub start_event: String,
+}
+
+impl Excursion {
+    pub fn iri(&self) -> String {
+        format!("urn:epcis:excursion:{}", self.id)
+    }
+}
+
+/// The first profile whose product class pattern covers the EPC
+pub fn profile_for<'a>(epc: &str, profiles: &'a [TemperatureProfile]) -> Option<&'a TemperatureProfile> {
+    profiles.iter().find(|profile| {
+        class_pattern_matches(&profile.product_class, epc) || profile.product_class == product_class(epc)
+    })
+}
+
+/// Find excursion intervals per EPC.
+///
+/// Readings are taken in time order; an interval starts at the first
+/// out-of-range reading and ends at the next in-range one. Intervals shorter
+/// than the profile's tolerance are ignored.
+pub fn detect_excursions(readings: &[TemperatureReading], profiles: &[TemperatureProfile]) -> Vec<Excursion> {
+    let mut by_epc: BTreeMap<&str, Vec<&TemperatureReading>> = BTreeMap::new();
+    for reading in readings {
+        by_epc.entry(reading.epc.as_str()).or_default().push(reading);
+    }
+
+    let mut excursions = Vec::new();
+    for (epc, mut readings) in by_epc {
+        let Some(profile) = profile_for(epc, profiles) else { continue };
+        readings.sort_by_key(|reading| reading.time);
+
+        let deviation = |celsius: f64| (profile.min_celsius - celsius).max(celsius - profile.max_celsius);
+        let mut open: Option<(&TemperatureReading, f64, usize)> = None;
+
+        for reading in readings.iter().copied() {
+            let out_of_range = deviation(reading.celsius) > 0.0;
+            open = match (open, out_of_range) {
+                (None, true) => Some((reading, reading.celsius, 1)),
+                (Some((start, extreme, count)), true) => {
+                    let extreme = if deviation(reading.celsius) > deviation(extreme) { reading.celsius } else { extreme };
+                    Some((start, extreme, count + 1))
+                }
+                (Some((start, extreme, count)), false) => {
+                    excursions.extend(close(epc, profile, start, extreme, count, reading.time, true));
+                    None
+                }
+                (None, false) => None,
+            };
+        }
+        if let (Some((start, extreme, count)), Some(last)) = (open, readings.last()) {
+            excursions.extend(close(epc, profile, start, extreme, count, last.time, false));
+        }
+    }
+    excursions
+}
+
+fn close(
+    epc: &str,
+    profile: &TemperatureProfile,
+    start: &TemperatureReading,
+    extreme_celsius: f64,
+    readings: usize,
+    end: DateTime<Utc>,
+    ended: bool,
+) -> Option<Excursion> {
+    let duration_minutes = (end - start.time).num_minutes();
+    if duration_minutes < profile.tolerance_minutes as i64 {
+        return None;
+    }
+
+    let digest = Sha256::digest(format!("{}|{}", epc, start.time.to_rfc3339()).as_bytes());
+    let id: String = digest.iter().take(8).map(|byte| format!("{:02x}", byte)).collect();
+    Some(Excursion {
+        id,
+        epc: epc.to_string(),
+        product_class: product_class(epc),
+        min_celsius: profile.min_celsius,
+        max_celsius: profile.max_celsius,
+        start: start.time.to_rfc3339(),
+        end: ended.then(|| end.to_rfc3339()),
+        duration_minutes,
+        extreme_celsius,
+        readings,
+        start_event: start.event_id.clone(),
+    })
+}
+
+/// `excursionDetected` facts and one resource per excursion interval
+pub fn excursion_triples(excursions: &[Excursion]) -> Vec<Triple> {
+    let epcis = |local: &str| NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+    let typed = |value: String, datatype: &str| Literal::new_typed_literal(value, NamedNode::new_unchecked(format!("{}{}", XSD, datatype)));
+
+    let mut triples = Vec::new();
+    for excursion in excursions {
+        let (Ok(epc), Ok(node)) = (NamedNode::new(excursion.epc.as_str()), NamedNode::new(excursion.iri())) else { continue };
+        triples.push(Triple::new(epc.clone(), epcis("excursionDetected"), Literal::from(true)));
+        triples.push(Triple::new(
+            node.clone(),
+            NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"),
+            epcis("TemperatureExcursion"),
+        ));
+        triples.push(Triple::new(node.clone(), epcis("excursionOf"), epc));
+        triples.push(Triple::new(node.clone(), epcis("excursionStart"), typed(excursion.start.clone(), "dateTime")));
+        if let Some(end) = &excursion.end {
+            triples.push(Triple::new(node.clone(), epcis("excursionEnd"), typed(end.clone(), "dateTime")));
+        }
+        triples.push(Triple::new(node.clone(), epcis("extremeCelsius"), typed(excursion.extreme_celsius.to_string(), "decimal")));
+        if let Ok(event) = NamedNode::new(format!("urn:epc:event:{}", excursion.start_event)) {
+            triples.push(Triple::new(node, epcis("startEvent"), event));
+        }
+    }
+    triples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_excursions_respect_tolerance_and_stay_open() {
+        let vaccine = "urn:epc:id:sgtin:0614141.107346.1";
+        let profiles = vec![TemperatureProfile {
+            product_class: "urn:epc:idpat:sgtin:0614141.107346.*".to_string(),
+            min_celsius: 2.0,
+            max_celsius: 8.0,
+            tolerance_minutes: 30,
+        }];
+        let reading = |minute: u32, celsius: f64| TemperatureReading {
+            epc: vaccine.to_string(),
+            event_id: format!("e{}", minute),
+            time: format!("2024-06-01T10:{:02}:00Z", minute).parse().unwrap(),
+            celsius,
+        };
+        let readings = vec![
+            reading(0, 5.0),
+            // Five minutes warm: within tolerance
+            reading(5, 9.0),
+            reading(10, 7.0),
+            // Warm from 10:20 and still warm at the last reading
+            reading(20, 9.5),
+            reading(59, 10.0),
+            reading(40, 12.0),
+        ];
+
+        let excursions = detect_excursions(&readings, &profiles);
+        assert_eq!(excursions.len(), 1);
+        let excursion = &excursions[0];
+        assert_eq!(excursion.start_event, "e20");
+        assert_eq!(excursion.end, None);
+        assert_eq!(excursion.duration_minutes, 39);
+        assert_eq!(excursion.extreme_celsius, 12.0);
+        assert_eq!(excursion.readings, 3);
+
+        let triples = excursion_triples(&excursions);
+        assert!(triples.iter().any(|t| t.predicate.as_str() == "urn:epcglobal:epcis:excursionDetected"));
+    }
+}
@@ -0,0 +1,204 @@
+use crate::storage::cold_chain::TemperatureReading;
+use crate::storage::recall::LineageEvent;
+use crate::utils::anonymization::Pseudonymizer;
+use chrono::{DateTime, Utc};
+
+/// File names of the exported tables
+pub const EVENTS_TABLE: &str = "events.parquet";
+pub const EPC_LIST_TABLE: &str = "epc_list.parquet";
+pub const SENSOR_READINGS_TABLE: &str = "sensor_readings.parquet";
+
+/// One row per stored event
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventRow {
+    pub event_id: String,
+    pub event_type: String,
+    pub action: String,
+    pub event_time: DateTime<Utc>,
+    pub biz_step: Option<String>,
+    pub biz_location: Option<String>,
+    pub parent_id: Option<String>,
+}
+
+/// One row per EPC an event references
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpcListRow {
+    pub event_id: String,
+    pub epc: String,
+    /// `epc`, `input` or `output`
+    pub role: &'static str,
+}
+
+/// One row per sensor reading and EPC it is attributed to
+#[derive(Debug, Clone, PartialEq)]
+pub struct SensorReadingRow {
+    pub event_id: String,
+    pub epc: String,
+    pub time: DateTime<Utc>,
+    pub celsius: f64,
+}
+
+/// Event history flattened into the three export tables
+#[derive(Debug, Clone, Default)]
+pub struct EventTables {
+    pub events: Vec<EventRow>,
+    pub epc_list: Vec<EpcListRow>,
+    pub sensor_readings: Vec<SensorReadingRow>,
+}
+
+impl EventTables {
+    /// Rows sorted by event time so time-range scans read few row groups
+    pub fn from_history(events: &[LineageEvent], readings: &[TemperatureReading]) -> Self {
+        let mut events: Vec<&LineageEvent> = events.iter().collect();
+        events.sort_by(|a, b| (a.event_time, &a.event_id).cmp(&(b.event_time, &b.event_id)));
+
+        let mut tables = EventTables::default();
+        for event in events {
+            tables.events.push(EventRow {
+                event_id: event.event_id.clone(),
+                event_type: event.event_type.clone(),
+                action: event.action.clone(),
+                event_time: event.event_time,
+                biz_step: event.biz_step.clone(),
+                biz_location: event.biz_location.clone(),
+                parent_id: event.parent.clone(),
+            });
+            let roles = [("epc", &event.epcs), ("input", &event.inputs), ("output", &event.outputs)];
+            for (role, epcs) in roles {
+                tables.epc_list.extend(epcs.iter().map(|epc| EpcListRow {
+                    event_id: event.event_id.clone(),
+                    epc: epc.clone(),
+                    role,
+                }));
+            }
+        }
+
+        let mut readings: Vec<&TemperatureReading> = readings.iter().collect();
+        readings.sort_by(|a, b| (a.time, &a.epc).cmp(&(b.time, &b.epc)));
+        tables.sensor_readings = readings
+            .into_iter()
+            .map(|reading| SensorReadingRow {
+                event_id: reading.event_id.clone(),
+                epc: reading.epc.clone(),
+                time: reading.time,
+                celsius: reading.celsius,
+            })
+            .collect();
+
+        tables
+    }
+
+    /// Pseudonymize EPCs and location/party GLNs as the RDF export does
+    pub fn pseudonymize(&mut self, pseudonymizer: &Pseudonymizer) {
+        for event in &mut self.events {
+            event.biz_location = event.biz_location.as_deref().map(|location| pseudonymizer.pseudonymize_identifier(location));
+            event.parent_id = event.parent_id.as_deref().map(|parent| pseudonymizer.pseudonymize_identifier(parent));
+        }
+        for row in &mut self.epc_list {
+            row.epc = pseudonymizer.pseudonymize_identifier(&row.epc);
+        }
+        for row in &mut self.sensor_readings {
+            row.epc = pseudonymizer.pseudonymize_identifier(&row.epc);
+        }
+    }
+
+    /// Write `events.parquet`, `epc_list.parquet` and `sensor_readings.parquet` into `dir`
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet(&self, dir: &std::path::Path) -> Result<(), crate::EpcisKgError> {
+        use arrow::array::{ArrayRef, Float64Array, StringArray, TimestampMicrosecondArray};
+        use std::sync::Arc;
+
+        let strings = |values: Vec<Option<&str>>| Arc::new(StringArray::from(values)) as ArrayRef;
+        let timestamps = |values: Vec<i64>| Arc::new(TimestampMicrosecondArray::from(values).with_timezone("UTC")) as ArrayRef;
+
+        std::fs::create_dir_all(dir)?;
+        write_table(
+            &dir.join(EVENTS_TABLE),
+            vec![
+                ("event_id", strings(self.events.iter().map(|row| Some(row.event_id.as_str())).collect())),
+                ("event_type", strings(self.events.iter().map(|row| Some(row.event_type.as_str())).collect())),
+                ("action", strings(self.events.iter().map(|row| Some(row.action.as_str())).collect())),
+                ("event_time", timestamps(self.events.iter().map(|row| row.event_time.timestamp_micros()).collect())),
+                ("biz_step", strings(self.events.iter().map(|row| row.biz_step.as_deref()).collect())),
+                ("biz_location", strings(self.events.iter().map(|row| row.biz_location.as_deref()).collect())),
+                ("parent_id", strings(self.events.iter().map(|row| row.parent_id.as_deref()).collect())),
+            ],
+        )?;
+        write_table(
+            &dir.join(EPC_LIST_TABLE),
+            vec![
+                ("event_id", strings(self.epc_list.iter().map(|row| Some(row.event_id.as_str())).collect())),
+                ("epc", strings(self.epc_list.iter().map(|row| Some(row.epc.as_str())).collect())),
+                ("role", strings(self.epc_list.iter().map(|row| Some(row.role)).collect())),
+            ],
+        )?;
+        write_table(
+            &dir.join(SENSOR_READINGS_TABLE),
+            vec![
+                ("event_id", strings(self.sensor_readings.iter().map(|row| Some(row.event_id.as_str())).collect())),
+                ("epc", strings(self.sensor_readings.iter().map(|row| Some(row.epc.as_str())).collect())),
+                ("time", timestamps(self.sensor_readings.iter().map(|row| row.time.timestamp_micros()).collect())),
+                ("celsius", Arc::new(Float64Array::from(self.sensor_readings.iter().map(|row| row.celsius).collect::<Vec<_>>())) as ArrayRef),
+            ],
+        )
+    }
+}
+
+#[cfg(feature = "parquet")]
+fn write_table(path: &std::path::Path, columns: Vec<(&str, arrow::array::ArrayRef)>) -> Result<(), crate::EpcisKgError> {
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::properties::WriterProperties;
+
+    let failure = |e: &dyn std::fmt::Display| crate::EpcisKgError::Storage(format!("Failed to write {}: {}", path.display(), e));
+    let batch = arrow::record_batch::RecordBatch::try_from_iter(columns).map_err(|e| failure(&e))?;
+    let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+
+    let mut writer = ArrowWriter::try_new(std::fs::File::create(path)?, batch.schema(), Some(properties)).map_err(|e| failure(&e))?;
+    writer.write(&batch).map_err(|e| failure(&e))?;
+    writer.close().map_err(|e| failure(&e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tables_from_history() {
+        let time = |hour: u32| format!("2024-03-01T{:02}:00:00Z", hour).parse::<DateTime<Utc>>().unwrap();
+        let transform = LineageEvent {
+            event_id: "t1".to_string(),
+            event_type: "TransformationEvent".to_string(),
+            action: "ADD".to_string(),
+            event_time: time(9),
+            biz_step: Some("urn:epcglobal:cbv:bizstep:commissioning".to_string()),
+            biz_location: None,
+            epcs: Vec::new(),
+            parent: None,
+            inputs: vec!["urn:epc:id:sgtin:0614141.107346.1".to_string(), "urn:epc:id:sgtin:0614141.107346.2".to_string()],
+            outputs: vec!["urn:epc:id:sgtin:0614141.200000.1".to_string()],
+            destination_parties: Vec::new(),
+            destination_locations: Vec::new(),
+        };
+        let mut observe = transform.clone();
+        observe.event_id = "o1".to_string();
+        observe.event_type = "ObjectEvent".to_string();
+        observe.event_time = time(8);
+        observe.epcs = vec!["urn:epc:id:sgtin:0614141.107346.1".to_string()];
+        observe.inputs.clear();
+        observe.outputs.clear();
+        let reading = TemperatureReading {
+            epc: "urn:epc:id:sgtin:0614141.107346.1".to_string(),
+            event_id: "o1".to_string(),
+            time: time(8),
+            celsius: 4.5,
+        };
+
+        let tables = EventTables::from_history(&[transform, observe], &[reading]);
+        assert_eq!(tables.events.iter().map(|row| row.event_id.as_str()).collect::<Vec<_>>(), vec!["o1", "t1"]);
+        assert_eq!(tables.epc_list.len(), 4);
+        assert_eq!(tables.epc_list.iter().filter(|row| row.role == "input").count(), 2);
+        assert_eq!(tables.sensor_readings[0].celsius, 4.5);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::config::CounterfeitConfig;
+use crate::storage::recall::LineageEvent;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignalKind {
+    /// Observed at two locations closer in time than a transfer could take
+    ConcurrentCustody,
+    /// Observed without a commissioning event, or before it
+    NeverCommissioned,
+    /// A verification request for the EPC failed
+    VerificationFailure,
+}
+
+impl SignalKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ConcurrentCustody => "concurrent_custody",
+            Self::NeverCommissioned => "never_commissioned",
+            Self::VerificationFailure => "verification_failure",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::ConcurrentCustody, Self::NeverCommissioned, Self::VerificationFailure]
+            .into_iter()
+            .find(|kind| kind.as_str() == name)
+    }
+
+    fn weight(&self, config: &CounterfeitConfig) -> f64 {
+        match self {
+            Self::ConcurrentCustody => config.concurrent_custody_weight,
+            Self::NeverCommissioned => config.never_commissioned_weight,
+            Self::VerificationFailure => config.verification_failure_weight,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SuspicionSignal {
+    pub kind: SignalKind,
+    pub detail: String,
+}
+
+/// Suspicion score of one EPC and the signals behind it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SuspicionReport {
+    pub epc: String,
+    pub score: f64,
+    pub signals: Vec<SuspicionSignal>,
+}
+
+/// Outcome of a verification request (e.g. a product identifier check with the brand owner)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Verification {
+    pub epc: String,
+    pub verified: bool,
+    #[serde(default)]
+    pub verifier: Option<String>,
+    /// Where the check took place (a GLN)
+    #[serde(default)]
+    pub location: Option<String>,
+    /// RFC 3339; defaults to the time it is recorded
+    #[serde(default)]
+    pub time: Option<String>,
+}
+
+/// Combine signals as independent probabilities: 1 - Π(1 - weight)
+pub fn suspicion_score(signals: &[SuspicionSignal], config: &CounterfeitConfig) -> f64 {
+    1.0 - signals
+        .iter()
+        .map(|signal| 1.0 - signal.kind.weight(config).clamp(0.0, 1.0))
+        .product::<f64>()
+}
+
+/// Run the cloned-EPC heuristics over the event history and verification results.
+///
+/// Only EPCs with at least one signal are returned, highest score first.
+pub fn detect_suspects(
+    events: &[LineageEvent],
+    verifications: &[Verification],
+    config: &CounterfeitConfig,
+) -> Vec<SuspicionReport> {
+    let mut events: Vec<&LineageEvent> = events.iter().collect();
+    events.sort_by_key(|event| event.event_time);
+
+    let mut signals: BTreeMap<String, Vec<SuspicionSignal>> = BTreeMap::new();
+    let mut first_seen: BTreeMap<&str, &LineageEvent> = BTreeMap::new();
+    let mut commissioned: BTreeMap<&str, DateTime<Utc>> = BTreeMap::new();
+    let mut last_location: BTreeMap<&str, (&str, &LineageEvent)> = BTreeMap::new();
+
+    for event in events.iter().copied() {
+        let is_commissioning = event.biz_step.as_deref().map_or(false, |step| step.ends_with("commissioning"))
+            || (event.event_type == "ObjectEvent" && event.action == "ADD");
+        let created = event
+            .outputs
+            .iter()
+            .chain(event.parent.iter().filter(|_| event.event_type == "AggregationEvent"))
+            .chain(event.epcs.iter().filter(|_| is_commissioning));
+        for epc in created {
+            commissioned.entry(epc.as_str()).or_insert(event.event_time);
+        }
+
+        for epc in event.epcs.iter().chain(&event.inputs) {
+            first_seen.entry(epc.as_str()).or_insert(event);
+
+            let Some(location) = event.biz_location.as_deref() else { continue };
+            if let Some((previous_location, previous)) = last_location.get(epc.as_str()) {
+                let gap = (event.event_time - previous.event_time).num_minutes();
+                if *previous_location != location && gap < config.min_transit_minutes {
+                    signals.entry(epc.clone()).or_default().push(SuspicionSignal {
+                        kind: SignalKind::ConcurrentCustody,
+                        detail: format!(
+                            "At {} (event {}) and {} (event {}) {} minutes apart",
+                            previous_location, previous.event_id, location, event.event_id, gap
+                        ),
+                    });
+                }
+            }
+            last_location.insert(epc.as_str(), (location, event));
+        }
+    }
+
+    for (epc, first) in &first_seen {
+        let detail = match commissioned.get(epc) {
+            None => format!("Observed in event {} but never commissioned", first.event_id),
+            Some(time) if *time > first.event_time => {
+                format!("Observed in event {} before being commissioned at {}", first.event_id, time.to_rfc3339())
+            }
+            Some(_) => continue,
+        };
+        signals.entry(epc.to_string()).or_default().push(SuspicionSignal {
+            kind: SignalKind::NeverCommissioned,
+            detail,
+        });
+    }
+
+    for verification in verifications.iter().filter(|verification| !verification.verified) {
+        signals.entry(verification.epc.clone()).or_default().push(SuspicionSignal {
+            kind: SignalKind::VerificationFailure,
+            detail: format!(
+                "Verification failed{}{}",
+                verification.verifier.as_deref().map(|v| format!(" at {}", v)).unwrap_or_default(),
+                verification.time.as_deref().map(|t| format!(" ({})", t)).unwrap_or_default()
+            ),
+        });
+    }
+
+    let mut reports: Vec<SuspicionReport> = signals
+        .into_iter()
+        .map(|(epc, signals)| SuspicionReport {
+            score: suspicion_score(&signals, config),
+            epc,
+            signals,
+        })
+        .collect();
+    reports.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.epc.cmp(&b.epc)));
+    reports
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str, minute: i64, biz_step: &str, location: &str, epc: &str) -> LineageEvent {
+        let start: DateTime<Utc> = "2024-02-01T10:00:00Z".parse().unwrap();
+        LineageEvent {
+            event_id: id.to_string(),
+            event_type: "ObjectEvent".to_string(),
+            action: "OBSERVE".to_string(),
+            event_time: start + chrono::Duration::minutes(minute),
+            biz_step: Some(format!("urn:epcglobal:cbv:bizstep:{}", biz_step)),
+            biz_location: Some(location.to_string()),
+            epcs: vec![epc.to_string()],
+            parent: None,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            destination_parties: Vec::new(),
+            destination_locations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_clone_signals_and_scores() {
+        let (genuine, clone) = ("urn:epc:id:sgtin:0614141.107346.1", "urn:epc:id:sgtin:0614141.107346.2");
+        let (plant, store_a, store_b) = ("urn:epc:id:sgln:0614141.1.0", "urn:epc:id:sgln:0614142.1.0", "urn:epc:id:sgln:0614143.1.0");
+        let events = vec![
+            event("c1", 0, "commissioning", plant, genuine),
+            event("c2", 0, "commissioning", plant, clone),
+            event("g", 180, "receiving", store_a, genuine),
+            // The clone turns up at two stores 15 minutes apart
+            event("a", 120, "receiving", store_a, clone),
+            event("b", 135, "receiving", store_b, clone),
+            // Never commissioned
+            event("x", 150, "receiving", store_a, "urn:epc:id:sgtin:0614141.107346.3"),
+        ];
+        let verifications = vec![Verification {
+            epc: clone.to_string(),
+            verified: false,
+            verifier: None,
+            location: None,
+            time: None,
+        }];
+        let config = CounterfeitConfig::default();
+
+        let reports = detect_suspects(&events, &verifications, &config);
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].epc, clone);
+        assert_eq!(reports[0].signals.len(), 2);
+        assert!((reports[0].score - (1.0 - 0.4 * 0.3)).abs() < 1e-9);
+        assert_eq!(reports[1].signals[0].kind, SignalKind::NeverCommissioned);
+        assert!(reports.iter().all(|report| report.epc != genuine));
+    }
+}
@@ -0,0 +1,289 @@
+//! Encryption at rest of persisted graph files.
+//!
+//! Graph files are sealed with AES-256-GCM. A sealed file starts with a magic
+//! tag and the ID of the key that sealed it, so a store stays readable while
+//! its files are part-way through a key rotation: readers hold the current key
+//! plus the retired ones, and `rotate_keys` rewrites one file at a time with an
+//! atomic rename. Plain Turtle files are still read, which lets an existing
+//! store be encrypted in place. The metadata file, which only lists graph
+//! names, is not encrypted.
+
+use crate::config::EncryptionConfig;
+use crate::EpcisKgError;
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+const MAGIC: &[u8; 8] = b"EKGENC01";
+const KEY_ID_LEN: usize = 8;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + KEY_ID_LEN + NONCE_LEN;
+
+static INSTALLED: OnceLock<Arc<StoreKeyring>> = OnceLock::new();
+
+/// Use `keyring` for every store opened from a path in this process
+pub fn install(keyring: StoreKeyring) {
+    if INSTALLED.set(Arc::new(keyring)).is_err() {
+        tracing::warn!("A storage keyring is already installed; keeping the first one");
+    }
+}
+
+/// The keyring installed for this process, if storage encryption is enabled
+pub fn installed() -> Option<Arc<StoreKeyring>> {
+    INSTALLED.get().cloned()
+}
+
+/// One AES-256 key and its ID (a hash prefix, never the key itself)
+pub struct StoreKey {
+    id: [u8; KEY_ID_LEN],
+    cipher: Aes256Gcm,
+}
+
+impl StoreKey {
+    /// `key` must be 32 bytes
+    pub fn new(key: &[u8]) -> Result<Self, EpcisKgError> {
+        let cipher = Aes256Gcm::new_from_slice(key)
+            .map_err(|_| EpcisKgError::Config(format!("Storage key must be 32 bytes, got {}", key.len())))?;
+        let mut id = [0; KEY_ID_LEN];
+        id.copy_from_slice(&Sha256::digest(key)[..KEY_ID_LEN]);
+        Ok(Self { id, cipher })
+    }
+
+    /// Key from its base64 encoding
+    pub fn from_base64(value: &str, origin: &str) -> Result<Self, EpcisKgError> {
+        let key = STANDARD
+            .decode(value.trim())
+            .map_err(|e| EpcisKgError::Config(format!("Storage key from {} is not base64: {}", origin, e)))?;
+        Self::new(&key)
+    }
+
+    pub fn key_id(&self) -> String {
+        hex(&self.id)
+    }
+}
+
+/// The key new files are sealed with, plus retired keys still accepted for reading
+pub struct StoreKeyring {
+    current: StoreKey,
+    retired: Vec<StoreKey>,
+}
+
+impl StoreKeyring {
+    pub fn new(current: StoreKey) -> Self {
+        Self { current, retired: Vec::new() }
+    }
+
+    pub fn with_retired(mut self, key: StoreKey) -> Self {
+        self.retired.push(key);
+        self
+    }
+
+    /// Keys from the `[encryption]` section; `None` when encryption is off
+    pub fn from_config(config: &EncryptionConfig) -> Result<Option<Self>, EpcisKgError> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let current = match config.key_source.as_str() {
+            "config" => {
+                let key = config
+                    .key
+                    .as_deref()
+                    .ok_or_else(|| EpcisKgError::Config("[encryption] key_source = \"config\" needs a key".to_string()))?;
+                StoreKey::from_base64(key, "the configuration")?
+            }
+            "env" => key_from_env(&config.key_env)?,
+            "command" => {
+                let command = config.key_command.as_deref().ok_or_else(|| {
+                    EpcisKgError::Config("[encryption] key_source = \"command\" needs a key_command".to_string())
+                })?;
+                key_from_command(command)?
+            }
+            other => {
+                return Err(EpcisKgError::Config(format!(
+                    "Unknown storage key source '{}' (expected config, env or command)",
+                    other
+                )))
+            }
+        };
+
+        let mut keyring = Self::new(current);
+        for variable in &config.retired_key_envs {
+            keyring = keyring.with_retired(key_from_env(variable)?);
+        }
+        Ok(Some(keyring))
+    }
+
+    pub fn current_key_id(&self) -> String {
+        self.current.key_id()
+    }
+
+    /// Encrypt file content with the current key
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>, EpcisKgError> {
+        let nonce: [u8; NONCE_LEN] = rand::random();
+        let mut sealed = Vec::with_capacity(HEADER_LEN + plaintext.len() + 16);
+        sealed.extend_from_slice(MAGIC);
+        sealed.extend_from_slice(&self.current.id);
+        sealed.extend_from_slice(&nonce);
+        let ciphertext = self
+            .current
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad: &sealed[..MAGIC.len() + KEY_ID_LEN] })
+            .map_err(|_| EpcisKgError::Storage("Failed to encrypt graph data".to_string()))?;
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Decrypt sealed file content with whichever key sealed it; plain content is returned as is
+    pub fn open(&self, data: &[u8]) -> Result<Vec<u8>, EpcisKgError> {
+        let Some(key_id) = sealed_key_id(data) else { return Ok(data.to_vec()) };
+        let key = std::iter::once(&self.current)
+            .chain(&self.retired)
+            .find(|key| key.id == key_id)
+            .ok_or_else(|| EpcisKgError::Storage(format!("Data is encrypted with unknown key {}", hex(&key_id))))?;
+        let nonce = &data[MAGIC.len() + KEY_ID_LEN..HEADER_LEN];
+        key.cipher
+            .decrypt(Nonce::from_slice(nonce), Payload { msg: &data[HEADER_LEN..], aad: &data[..MAGIC.len() + KEY_ID_LEN] })
+            .map_err(|_| EpcisKgError::Storage(format!("Data encrypted with key {} failed authentication", key.key_id())))
+    }
+}
+
+/// Whether file content was written by `StoreKeyring::seal`
+pub fn is_sealed(data: &[u8]) -> bool {
+    sealed_key_id(data).is_some()
+}
+
+fn sealed_key_id(data: &[u8]) -> Option<[u8; KEY_ID_LEN]> {
+    if data.len() < HEADER_LEN || !data.starts_with(MAGIC) {
+        return None;
+    }
+    data[MAGIC.len()..MAGIC.len() + KEY_ID_LEN].try_into().ok()
+}
+
+fn key_from_env(variable: &str) -> Result<StoreKey, EpcisKgError> {
+    let value = std::env::var(variable)
+        .map_err(|_| EpcisKgError::Config(format!("Storage key not found in environment variable {}", variable)))?;
+    StoreKey::from_base64(&value, variable)
+}
+
+/// Run a KMS hook (e.g. a cloud CLI decrypting a data key) that prints the base64 key
+fn key_from_command(command: &str) -> Result<StoreKey, EpcisKgError> {
+    let output = std::process::Command::new("sh")
+        .args(["-c", command])
+        .output()
+        .map_err(|e| EpcisKgError::Config(format!("Failed to run storage key command: {}", e)))?;
+    if !output.status.success() {
+        return Err(EpcisKgError::Config(format!(
+            "Storage key command exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    StoreKey::from_base64(&String::from_utf8_lossy(&output.stdout), "the key command")
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Outcome of re-encrypting a store's graph files
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RotationReport {
+    pub key_id: String,
+    /// Files sealed with the current key by this run
+    pub rewritten: usize,
+    /// Files that were already sealed with the current key
+    pub up_to_date: usize,
+}
+
+/// Re-encrypt every graph file under `path` with the keyring's current key.
+///
+/// Files are replaced one by one through a temporary file and a rename, so
+/// readers holding the current and retired keys can open the store throughout.
+pub fn rotate_keys(path: &Path, keyring: &StoreKeyring) -> Result<RotationReport, EpcisKgError> {
+    if !path.join("store_metadata.json").exists() {
+        return Err(EpcisKgError::NotFound { resource: "Dataset".to_string(), id: path.display().to_string() });
+    }
+
+    // A save in between reading and replacing a file would be lost, whether
+    // this process or a server running on the same directory makes it
+    let lock = crate::storage::oxigraph_store::directory_write_lock(path);
+    let _saving = lock.lock();
+    let _other_processes = crate::storage::oxigraph_store::lock_directory_file(path)?;
+    let mut report = RotationReport { key_id: keyring.current_key_id(), ..RotationReport::default() };
+    for entry in std::fs::read_dir(path)? {
+        let file = entry?.path();
+        if file.extension().and_then(|extension| extension.to_str()) != Some("ttl") {
+            continue;
+        }
+        let data = std::fs::read(&file)?;
+        if sealed_key_id(&data) == Some(keyring.current.id) {
+            report.up_to_date += 1;
+            continue;
+        }
+        let sealed = keyring.seal(&keyring.open(&data)?)?;
+        let temporary = file.with_extension("ttl.tmp");
+        std::fs::write(&temporary, sealed)?;
+        std::fs::rename(&temporary, &file)?;
+        report.rewritten += 1;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::oxigraph_store::OxigraphStore;
+    use oxrdf::{NamedNode, Triple};
+
+    #[test]
+    fn test_sealed_files_survive_key_rotation() {
+        let old_key = || StoreKey::new(&[7; 32]).unwrap();
+        let new_key = || StoreKey::new(&[9; 32]).unwrap();
+        assert!(StoreKey::new(&[1; 16]).is_err());
+
+        let old = StoreKeyring::new(old_key());
+        let sealed = old.seal(b"<urn:a> <urn:b> <urn:c> .").unwrap();
+        assert!(is_sealed(&sealed));
+        assert_eq!(old.open(&sealed).unwrap(), b"<urn:a> <urn:b> <urn:c> .");
+        assert_eq!(old.open(b"plain turtle").unwrap(), b"plain turtle");
+        assert!(StoreKeyring::new(new_key()).open(&sealed).is_err());
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(old.open(&tampered).is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let triple = Triple::new(
+            NamedNode::new_unchecked("urn:epc:event:1"),
+            NamedNode::new_unchecked("urn:epcglobal:epcis:bizStep"),
+            NamedNode::new_unchecked("urn:epcglobal:cbv:shipping"),
+        );
+        let mut store = OxigraphStore::new_with_keyring(dir.path(), Some(Arc::new(StoreKeyring::new(old_key())))).unwrap();
+        store.insert_triples("urn:epcis:event:1", &[triple]).unwrap();
+
+        let graph_file = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|file| file.extension().is_some_and(|extension| extension == "ttl"))
+            .unwrap();
+        assert!(is_sealed(&std::fs::read(&graph_file).unwrap()));
+
+        // The new key takes over while the old one is retired, then rotation rewrites the files
+        let rotating = Arc::new(StoreKeyring::new(new_key()).with_retired(old_key()));
+        let saving = crate::storage::oxigraph_store::lock_directory_file(dir.path()).unwrap();
+        let other_process = std::fs::File::open(dir.path().join(".write.lock")).unwrap();
+        assert!(fs2::FileExt::try_lock_exclusive(&other_process).is_err(), "rotation waits for a save in another process");
+        drop(saving);
+        let report = rotate_keys(dir.path(), &rotating).unwrap();
+        assert_eq!((report.rewritten, report.up_to_date), (1, 0));
+        assert_eq!(rotate_keys(dir.path(), &rotating).unwrap().up_to_date, 1);
+
+        let reopened = OxigraphStore::new_with_keyring(dir.path(), Some(Arc::new(StoreKeyring::new(new_key())))).unwrap();
+        assert_eq!(reopened.graph_len("urn:epcis:event:1"), 1);
+        assert!(OxigraphStore::new_with_keyring(dir.path(), None).is_err());
+    }
+}
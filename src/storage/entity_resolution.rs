@@ -0,0 +1,327 @@
+use crate::config::EntityResolutionConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+
+/// Legal-form words ignored when comparing names
+const NAME_STOPWORDS: &[&str] = &["the", "inc", "ltd", "llc", "co", "corp", "corporation", "company", "gmbh", "ag", "sa", "plc"];
+
+/// Identifier kinds a pair of records can be matched on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKey {
+    /// Same Global Location Number (from an SGLN/PGLN EPC URI, a GS1 Digital Link or a plain GLN)
+    Gln,
+    /// Same GTIN (from an SGTIN/LGTIN EPC URI, a GS1 Digital Link or a plain GTIN)
+    Gtin,
+    /// Similar normalized names
+    Name,
+}
+
+impl MatchKey {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gln => "gln",
+            Self::Gtin => "gtin",
+            Self::Name => "name",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [Self::Gln, Self::Gtin, Self::Name].into_iter().find(|key| key.as_str() == name)
+    }
+}
+
+/// A master data resource (location, product, party) as seen by the matcher
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MasterRecord {
+    pub iri: String,
+    pub types: BTreeSet<String>,
+    pub names: Vec<String>,
+    /// Identifier values besides the IRI itself, e.g. an `epc` or `gln` literal
+    pub identifiers: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyMatch {
+    pub key: MatchKey,
+    pub detail: String,
+}
+
+/// A proposed `owl:sameAs` link awaiting human review
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SameAsProposal {
+    pub left: String,
+    pub right: String,
+    /// 0..1, combining the matched keys as independent evidence
+    pub confidence: f64,
+    pub matches: Vec<KeyMatch>,
+}
+
+/// A reviewer's decision on a proposed link
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SameAsReview {
+    pub left: String,
+    pub right: String,
+    pub accept: bool,
+    #[serde(default)]
+    pub reviewer: Option<String>,
+}
+
+/// Propose `owl:sameAs` links between records that look like the same entity.
+///
+/// Records are only compared when they share a type (or either is untyped).
+/// Records whose GLNs or GTINs differ are never linked, however similar their
+/// names. `skip` holds pairs already reviewed, in either order.
+pub fn propose_links(records: &[MasterRecord], config: &EntityResolutionConfig, skip: &HashSet<(String, String)>) -> Vec<SameAsProposal> {
+    let keys: Vec<MatchKey> = config.keys.iter().filter_map(|key| MatchKey::from_name(key)).collect();
+    let prepared: Vec<Prepared> = records.iter().map(Prepared::new).collect();
+
+    let mut proposals = Vec::new();
+    for (i, left) in prepared.iter().enumerate() {
+        for right in &prepared[i + 1..] {
+            let (a, b) = (&left.record.iri, &right.record.iri);
+            if a == b || skip.contains(&(a.clone(), b.clone())) || skip.contains(&(b.clone(), a.clone())) {
+                continue;
+            }
+            let comparable = left.record.types.is_empty()
+                || right.record.types.is_empty()
+                || !left.record.types.is_disjoint(&right.record.types);
+            if !comparable {
+                continue;
+            }
+            if let Some(proposal) = compare(left, right, &keys, config) {
+                if proposal.confidence >= config.min_confidence {
+                    proposals.push(proposal);
+                }
+            }
+        }
+    }
+
+    proposals.sort_by(|a, b| b.confidence.total_cmp(&a.confidence).then_with(|| (&a.left, &a.right).cmp(&(&b.left, &b.right))));
+    proposals
+}
+
+/// A record with its comparison keys worked out once
+struct Prepared<'a> {
+    record: &'a MasterRecord,
+    glns: BTreeSet<String>,
+    gtins: BTreeSet<String>,
+    names: Vec<String>,
+}
+
+impl<'a> Prepared<'a> {
+    fn new(record: &'a MasterRecord) -> Self {
+        let values = || std::iter::once(&record.iri).chain(&record.identifiers);
+        Self {
+            record,
+            glns: values().filter_map(|value| gln_key(value)).collect(),
+            gtins: values().filter_map(|value| gtin_key(value)).collect(),
+            names: record.names.iter().map(|name| normalize_name(name)).filter(|name| !name.is_empty()).collect(),
+        }
+    }
+}
+
+fn compare(left: &Prepared<'_>, right: &Prepared<'_>, keys: &[MatchKey], config: &EntityResolutionConfig) -> Option<SameAsProposal> {
+    let mut matches = Vec::new();
+    let mut evidence = Vec::new();
+
+    for key in keys {
+        match key {
+            MatchKey::Gln | MatchKey::Gtin => {
+                let (ours, theirs, weight) = match key {
+                    MatchKey::Gln => (&left.glns, &right.glns, config.gln_weight),
+                    _ => (&left.gtins, &right.gtins, config.gtin_weight),
+                };
+                if ours.is_empty() || theirs.is_empty() {
+                    continue;
+                }
+                // Different identifiers of the same kind rule a match out
+                let shared = ours.intersection(theirs).next()?;
+                matches.push(KeyMatch { key: *key, detail: shared.clone() });
+                evidence.push(weight);
+            }
+            MatchKey::Name => {
+                let best = left
+                    .names
+                    .iter()
+                    .flat_map(|a| right.names.iter().map(move |b| (a, b, name_similarity(a, b))))
+                    .max_by(|x, y| x.2.total_cmp(&y.2));
+                if let Some((a, b, similarity)) = best {
+                    if similarity >= config.min_name_similarity {
+                        matches.push(KeyMatch {
+                            key: MatchKey::Name,
+                            detail: format!("\"{}\" ~ \"{}\" ({:.2})", a, b, similarity),
+                        });
+                        evidence.push(config.name_weight * similarity);
+                    }
+                }
+            }
+        }
+    }
+
+    if matches.is_empty() {
+        return None;
+    }
+    let confidence = 1.0 - evidence.iter().map(|weight| 1.0 - weight.clamp(0.0, 1.0)).product::<f64>();
+    Some(SameAsProposal {
+        left: left.record.iri.clone(),
+        right: right.record.iri.clone(),
+        confidence,
+        matches,
+    })
+}
+
+/// GLN without its check digit, plus `/extension` for a non-zero SGLN extension
+pub fn gln_key(value: &str) -> Option<String> {
+    let value = value.trim();
+    if let Some(rest) = value.strip_prefix("urn:epc:id:sgln:") {
+        let mut parts = rest.split('.');
+        let (prefix, reference) = (parts.next()?, parts.next()?);
+        let key = digits(&format!("{}{}", prefix, reference))?;
+        return Some(match parts.next() {
+            Some(extension) if !extension.is_empty() && extension != "0" => format!("{}/{}", key, extension),
+            _ => key,
+        });
+    }
+    if let Some(rest) = value.strip_prefix("urn:epc:id:pgln:") {
+        let (prefix, reference) = rest.split_once('.')?;
+        return digits(&format!("{}{}", prefix, reference));
+    }
+    if let Some(gln) = digital_link_segment(value, "417") {
+        return digits(gln.get(..12)?);
+    }
+    if let Some(gln) = digital_link_segment(value, "414") {
+        let key = digits(gln.get(..12)?)?;
+        return Some(match digital_link_segment(value, "254") {
+            Some(extension) if extension != "0" => format!("{}/{}", key, extension),
+            _ => key,
+        });
+    }
+    match digits(value) {
+        Some(gln) if gln.len() == 13 => Some(gln[..12].to_string()),
+        _ => None,
+    }
+}
+
+/// GTIN-14 without its check digit
+pub fn gtin_key(value: &str) -> Option<String> {
+    let value = value.trim();
+    let epc_class = ["urn:epc:id:sgtin:", "urn:epc:idpat:sgtin:", "urn:epc:class:lgtin:"]
+        .iter()
+        .find_map(|scheme| value.strip_prefix(scheme));
+    if let Some(rest) = epc_class {
+        let mut parts = rest.split('.');
+        let (prefix, item) = (parts.next()?, parts.next()?);
+        let (indicator, item_rest) = (item.get(..1)?, item.get(1..)?);
+        return digits(&format!("{}{}{}", indicator, prefix, item_rest));
+    }
+    if let Some(gtin) = digital_link_segment(value, "01") {
+        return digits(gtin.get(..13)?);
+    }
+    match digits(value) {
+        Some(gtin) if matches!(gtin.len(), 8 | 12 | 13 | 14) => {
+            let padded = format!("{:0>14}", gtin);
+            Some(padded[..13].to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Value following `/{ai}/` in a GS1 Digital Link URI
+fn digital_link_segment<'a>(value: &'a str, ai: &str) -> Option<&'a str> {
+    if !value.starts_with("http://") && !value.starts_with("https://") {
+        return None;
+    }
+    let path = value.split(['?', '#']).next()?;
+    let mut segments = path.split('/');
+    segments.find(|segment| *segment == ai)?;
+    segments.next().filter(|segment| !segment.is_empty())
+}
+
+fn digits(value: &str) -> Option<String> {
+    (!value.is_empty() && value.chars().all(|c| c.is_ascii_digit())).then(|| value.to_string())
+}
+
+/// Lowercase, punctuation-free name without legal-form words
+pub fn normalize_name(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty() && !NAME_STOPWORDS.contains(token))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Dice coefficient over character bigrams of two normalized names.
+///
+/// Names whose numbers differ ("Dock 12" and "Dock 13") score 0, since they
+/// usually name sibling entities rather than spellings of one.
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    let numbers = |name: &str| -> Vec<String> {
+        name.split(' ').filter(|token| token.chars().any(|c| c.is_ascii_digit())).map(str::to_string).collect()
+    };
+    if numbers(a) != numbers(b) {
+        return 0.0;
+    }
+    if a == b {
+        return 1.0;
+    }
+    let bigrams = |name: &str| -> Vec<(char, char)> {
+        let chars: Vec<char> = name.chars().collect();
+        chars.windows(2).map(|pair| (pair[0], pair[1])).collect()
+    };
+    let (left, mut right) = (bigrams(a), bigrams(b));
+    if left.is_empty() || right.is_empty() {
+        return 0.0;
+    }
+    let total = left.len() + right.len();
+    let mut shared = 0;
+    for bigram in &left {
+        if let Some(position) = right.iter().position(|other| other == bigram) {
+            right.swap_remove(position);
+            shared += 1;
+        }
+    }
+    2.0 * shared as f64 / total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(iri: &str, kind: &str, name: &str) -> MasterRecord {
+        MasterRecord {
+            iri: iri.to_string(),
+            types: BTreeSet::from([kind.to_string()]),
+            names: vec![name.to_string()],
+            identifiers: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_proposals_use_identifiers_and_names() {
+        assert_eq!(gln_key("urn:epc:id:sgln:0614141.00777.0").as_deref(), Some("061414100777"));
+        assert_eq!(gln_key("https://id.gs1.org/414/0614141007774").as_deref(), Some("061414100777"));
+        assert_eq!(gtin_key("urn:epc:id:sgtin:0614141.107346.2017").as_deref(), Some("1061414107346"));
+        assert_eq!(gtin_key("https://id.gs1.org/01/10614141073467/21/2017").as_deref(), Some("1061414107346"));
+
+        let records = vec![
+            record("urn:epc:id:sgln:0614141.00777.0", "urn:ex:Location", "Acme Distribution Center, Inc."),
+            record("https://id.gs1.org/414/0614141007774", "urn:ex:Location", "ACME Distribution Centre"),
+            record("urn:ex:loc:acme-dc", "urn:ex:Location", "Acme Distribution Center"),
+            record("urn:ex:loc:dock-12", "urn:ex:Location", "Dock 12"),
+            record("urn:ex:loc:dock-13", "urn:ex:Location", "Dock 13"),
+            record("urn:ex:product:acme-dc", "urn:ex:Product", "Acme Distribution Center"),
+        ];
+        let config = EntityResolutionConfig::default();
+        let proposals = propose_links(&records, &config, &HashSet::new());
+
+        assert_eq!(proposals[0].left, "urn:epc:id:sgln:0614141.00777.0");
+        assert_eq!(proposals[0].right, "https://id.gs1.org/414/0614141007774");
+        assert_eq!(proposals[0].matches.len(), 2);
+        assert!(proposals.iter().all(|proposal| !proposal.left.contains("dock") && !proposal.right.contains("product")));
+        assert!(proposals.iter().any(|proposal| proposal.right == "urn:ex:loc:acme-dc"));
+
+        let reviewed = HashSet::from([(records[1].iri.clone(), records[0].iri.clone())]);
+        assert!(propose_links(&records, &config, &reviewed).iter().all(|proposal| proposal.right != records[1].iri));
+    }
+}
@@ -0,0 +1,133 @@
+use crate::storage::recall::LineageEvent;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Business steps that take an EPC out of circulation
+const DECOMMISSIONING_BIZ_STEPS: [&str; 2] = ["decommissioning", "destroying"];
+
+/// Lifecycle status of one EPC, as a verification service needs it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EpcStatus {
+    pub epc: String,
+    /// Whether any event mentions the EPC at all
+    pub known: bool,
+    pub commissioned: bool,
+    pub commissioned_at: Option<DateTime<Utc>>,
+    /// Disposition of the latest event that stated one
+    pub disposition: Option<String>,
+    pub last_event_id: Option<String>,
+    pub last_observed_at: Option<DateTime<Utc>>,
+    /// bizLocation of the latest event that stated one
+    pub last_location: Option<String>,
+    pub decommissioned: bool,
+    pub decommissioned_at: Option<DateTime<Utc>>,
+}
+
+/// Status of each requested EPC, in request order, from one pass over the event history.
+///
+/// `dispositions` maps event IDs to the disposition they state. An EPC is
+/// commissioned by a commissioning bizStep, an ObjectEvent ADD or as a
+/// transformation output, and decommissioned by a decommissioning or
+/// destroying bizStep or an ObjectEvent DELETE; commissioning it again
+/// clears the decommissioned flag.
+pub fn epc_statuses(events: &[LineageEvent], dispositions: &HashMap<String, String>, epcs: &[String]) -> Vec<EpcStatus> {
+    let mut statuses: BTreeMap<&str, EpcStatus> = epcs
+        .iter()
+        .map(|epc| (epc.as_str(), EpcStatus { epc: epc.clone(), ..EpcStatus::default() }))
+        .collect();
+
+    let mut events: Vec<&LineageEvent> = events.iter().collect();
+    events.sort_by_key(|event| event.event_time);
+
+    for event in events {
+        let biz_step = event.biz_step.as_deref().unwrap_or_default();
+        let is_commissioning = (biz_step.ends_with("commissioning") && !biz_step.ends_with("decommissioning"))
+            || (event.event_type == "ObjectEvent" && event.action == "ADD");
+        let is_decommissioning = DECOMMISSIONING_BIZ_STEPS.iter().any(|step| biz_step.ends_with(step))
+            || (event.event_type == "ObjectEvent" && event.action == "DELETE");
+
+        let mentioned = event
+            .epcs
+            .iter()
+            .map(|epc| (epc, is_commissioning, is_decommissioning))
+            .chain(event.inputs.iter().map(|epc| (epc, false, false)))
+            .chain(event.outputs.iter().map(|epc| (epc, true, false)))
+            .chain(event.parent.iter().map(|epc| (epc, event.event_type == "AggregationEvent", false)));
+        for (epc, created, retired) in mentioned {
+            let Some(status) = statuses.get_mut(epc.as_str()) else { continue };
+            status.known = true;
+            if created && (!status.commissioned || status.decommissioned) {
+                status.commissioned = true;
+                status.commissioned_at = Some(event.event_time);
+                status.decommissioned = false;
+                status.decommissioned_at = None;
+            }
+            if retired {
+                status.decommissioned = true;
+                status.decommissioned_at = Some(event.event_time);
+            }
+            if let Some(disposition) = dispositions.get(&event.event_id) {
+                status.disposition = Some(disposition.clone());
+            }
+            if let Some(location) = &event.biz_location {
+                status.last_location = Some(location.clone());
+            }
+            status.last_event_id = Some(event.event_id.clone());
+            status.last_observed_at = Some(event.event_time);
+        }
+    }
+
+    epcs.iter().map(|epc| statuses[epc.as_str()].clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn event(id: &str, day: u32, action: &str, biz_step: &str, location: &str, epc: &str) -> LineageEvent {
+        LineageEvent {
+            event_id: id.to_string(),
+            event_type: "ObjectEvent".to_string(),
+            action: action.to_string(),
+            event_time: Utc.with_ymd_and_hms(2024, 1, day, 8, 0, 0).unwrap(),
+            biz_step: Some(format!("urn:epcglobal:cbv:bizstep:{}", biz_step)),
+            biz_location: Some(location.to_string()),
+            epcs: vec![epc.to_string()],
+            parent: None,
+            inputs: vec![],
+            outputs: vec![],
+            destination_parties: vec![],
+            destination_locations: vec![],
+        }
+    }
+
+    #[test]
+    fn test_statuses_follow_the_lifecycle() {
+        let shipped = "urn:epc:id:sgtin:0614141.107346.1";
+        let destroyed = "urn:epc:id:sgtin:0614141.107346.2";
+        let unknown = "urn:epc:id:sgtin:0614141.107346.3";
+        let plant = "urn:epc:id:sgln:0614141.00001.0";
+        let warehouse = "urn:epc:id:sgln:0614141.00002.0";
+        let events = vec![
+            event("3", 3, "OBSERVE", "shipping", warehouse, shipped),
+            event("1", 1, "ADD", "commissioning", plant, shipped),
+            event("2", 2, "ADD", "commissioning", plant, destroyed),
+            event("4", 4, "DELETE", "destroying", plant, destroyed),
+        ];
+        let dispositions = HashMap::from([
+            ("3".to_string(), "urn:epcglobal:cbv:disp:in_transit".to_string()),
+            ("4".to_string(), "urn:epcglobal:cbv:disp:destroyed".to_string()),
+        ]);
+
+        let statuses = epc_statuses(&events, &dispositions, &[unknown.to_string(), shipped.to_string(), destroyed.to_string()]);
+        assert!(!statuses[0].known && !statuses[0].commissioned);
+        assert!(statuses[1].commissioned && !statuses[1].decommissioned);
+        assert_eq!(statuses[1].commissioned_at, Some(Utc.with_ymd_and_hms(2024, 1, 1, 8, 0, 0).unwrap()));
+        assert_eq!(statuses[1].last_location.as_deref(), Some(warehouse));
+        assert_eq!(statuses[1].disposition.as_deref(), Some("urn:epcglobal:cbv:disp:in_transit"));
+        assert!(statuses[2].decommissioned);
+        assert_eq!(statuses[2].last_event_id.as_deref(), Some("4"));
+    }
+}
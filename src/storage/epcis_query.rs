@@ -0,0 +1,175 @@
+//! Simple event queries of the EPCIS 2.0 query interface (`GET /events?...`).
+//!
+//! Supported parameters: `eventType`, `GE_eventTime`, `LT_eventTime`,
+//! `GE_recordTime`, `LT_recordTime`, `EQ_action`, `EQ_bizStep`,
+//! `EQ_disposition`, `EQ_bizLocation`, `EQ_eventID`, `MATCH_epc` and
+//! `MATCH_anyEPC`. List parameters take comma separated values, which are
+//! OR-ed; different parameters are AND-ed. EPC matches accept
+//! `urn:epc:idpat:...*` patterns. With `[identifiers]` normalization on, GS1
+//! Digital Links in EPC and location parameters match the EPC URNs they were
+//! stored as.
+//!
+//! `GE_localEventTime` and `LT_localEventTime` take a time without offset
+//! (`2024-03-01T08:00:00`) and compare it with each event's time at its own
+//! `eventTimeZoneOffset`, e.g. to find events before 8am site time across
+//! sites in different zones. Events without an offset are taken as UTC.
+
+use crate::models::document::cbv_short_name;
+use crate::models::identifiers::IdentifierNormalizer;
+use crate::storage::inventory::class_pattern_matches;
+use crate::storage::recall::LineageEvent;
+use crate::EpcisKgError;
+use chrono::{DateTime, FixedOffset, NaiveDateTime, Utc};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EpcisQuery {
+    pub event_types: Vec<String>,
+    pub ge_event_time: Option<DateTime<Utc>>,
+    pub lt_event_time: Option<DateTime<Utc>>,
+    pub ge_local_event_time: Option<NaiveDateTime>,
+    pub lt_local_event_time: Option<NaiveDateTime>,
+    pub ge_record_time: Option<DateTime<Utc>>,
+    pub lt_record_time: Option<DateTime<Utc>>,
+    pub actions: Vec<String>,
+    pub biz_steps: Vec<String>,
+    pub dispositions: Vec<String>,
+    pub biz_locations: Vec<String>,
+    pub event_ids: Vec<String>,
+    /// Matched against `epcList` and `childEPCs`
+    pub match_epc: Vec<String>,
+    /// Matched against every EPC of the event, including parent, inputs and outputs
+    pub match_any_epc: Vec<String>,
+}
+
+/// Facts of a stored event that are not part of its lineage
+#[derive(Debug, Clone, Default)]
+pub struct EventDetails {
+    pub disposition: Option<String>,
+    pub record_time: Option<DateTime<Utc>>,
+    /// `eventTimeZoneOffset` recorded with the event
+    pub time_zone_offset: Option<FixedOffset>,
+}
+
+impl EpcisQuery {
+    /// Query from URL parameters; unknown parameters are rejected as the standard requires
+    pub fn from_params(params: &HashMap<String, String>) -> Result<Self, EpcisKgError> {
+        let mut query = Self::default();
+        for (name, value) in params {
+            let list = || value.split(',').map(str::trim).filter(|v| !v.is_empty()).map(str::to_string).collect::<Vec<_>>();
+            let time = || {
+                DateTime::parse_from_rfc3339(value)
+                    .map(|time| time.with_timezone(&Utc))
+                    .map_err(|e| EpcisKgError::Query(format!("Invalid {} '{}': {}", name, value, e)))
+            };
+            let local_time = || {
+                NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+                    .map_err(|e| EpcisKgError::Query(format!("Invalid {} '{}': {}", name, value, e)))
+            };
+            match name.as_str() {
+                "eventType" => query.event_types = list(),
+                "GE_eventTime" => query.ge_event_time = Some(time()?),
+                "LT_eventTime" => query.lt_event_time = Some(time()?),
+                "GE_localEventTime" => query.ge_local_event_time = Some(local_time()?),
+                "LT_localEventTime" => query.lt_local_event_time = Some(local_time()?),
+                "GE_recordTime" => query.ge_record_time = Some(time()?),
+                "LT_recordTime" => query.lt_record_time = Some(time()?),
+                "EQ_action" => query.actions = list(),
+                "EQ_bizStep" => query.biz_steps = list(),
+                "EQ_disposition" => query.dispositions = list(),
+                "EQ_bizLocation" => query.biz_locations = list(),
+                "EQ_eventID" => query.event_ids = list(),
+                "MATCH_epc" => query.match_epc = list(),
+                "MATCH_anyEPC" => query.match_any_epc = list(),
+                _ => return Err(EpcisKgError::Query(format!("Unsupported query parameter: {}", name))),
+            }
+        }
+        Ok(query)
+    }
+
+    /// Match Digital Link values the way captures store them, as EPC URNs
+    pub fn normalize_identifiers(&mut self, normalizer: &IdentifierNormalizer) {
+        normalizer.normalize_values(&mut self.biz_locations);
+        normalizer.normalize_values(&mut self.match_epc);
+        normalizer.normalize_values(&mut self.match_any_epc);
+    }
+
+    pub fn matches(&self, event: &LineageEvent, details: &EventDetails) -> bool {
+        let one_of = |values: &[String], actual: Option<&str>| {
+            values.is_empty() || actual.is_some_and(|actual| values.iter().any(|value| value == actual))
+        };
+        let cbv_one_of = |values: &[String], actual: Option<&str>| {
+            values.is_empty()
+                || actual.is_some_and(|actual| values.iter().any(|value| cbv_short_name(value) == cbv_short_name(actual)))
+        };
+        let in_range = |time: Option<DateTime<Utc>>, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>| match time {
+            Some(time) => !from.is_some_and(|from| time < from) && !to.is_some_and(|to| time >= to),
+            None => from.is_none() && to.is_none(),
+        };
+        let offset = details.time_zone_offset.unwrap_or_else(|| FixedOffset::east_opt(0).expect("zero offset"));
+        let local_event_time = event.event_time.with_timezone(&offset).naive_local();
+        let in_local_range = !self.ge_local_event_time.is_some_and(|from| local_event_time < from)
+            && !self.lt_local_event_time.is_some_and(|to| local_event_time >= to);
+
+        one_of(&self.event_types, Some(event.event_type.as_str()))
+            && one_of(&self.actions, Some(event.action.as_str()))
+            && one_of(&self.biz_locations, event.biz_location.as_deref())
+            && one_of(&self.event_ids, Some(event.event_id.as_str()))
+            && cbv_one_of(&self.biz_steps, event.biz_step.as_deref())
+            && cbv_one_of(&self.dispositions, details.disposition.as_deref())
+            && in_range(Some(event.event_time), self.ge_event_time, self.lt_event_time)
+            && in_local_range
+            && in_range(details.record_time, self.ge_record_time, self.lt_record_time)
+            && any_epc_matches(&self.match_epc, event.epcs.iter())
+            && any_epc_matches(
+                &self.match_any_epc,
+                event.epcs.iter().chain(&event.parent).chain(&event.inputs).chain(&event.outputs),
+            )
+    }
+}
+
+/// Whether any EPC matches one of the patterns (no patterns match everything)
+fn any_epc_matches<'a>(patterns: &[String], mut epcs: impl Iterator<Item = &'a String>) -> bool {
+    patterns.is_empty() || epcs.any(|epc| patterns.iter().any(|pattern| class_pattern_matches(pattern, epc)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_parameters() {
+        let params = |pairs: &[(&str, &str)]| pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>();
+        let event = LineageEvent {
+            event_id: "e1".to_string(),
+            event_type: "ObjectEvent".to_string(),
+            action: "OBSERVE".to_string(),
+            event_time: "2005-04-03T20:33:31Z".parse().unwrap(),
+            biz_step: Some("urn:epcglobal:cbv:shipping".to_string()),
+            biz_location: None,
+            epcs: vec!["urn:epc:id:sgtin:0614141.107346.2017".to_string()],
+            parent: None,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            destination_parties: Vec::new(),
+            destination_locations: Vec::new(),
+        };
+        let details = EventDetails::default();
+        let matches = |pairs: &[(&str, &str)]| EpcisQuery::from_params(&params(pairs)).unwrap().matches(&event, &details);
+
+        assert!(matches(&[("EQ_bizStep", "receiving, https://ref.gs1.org/cbv/BizStep-shipping"), ("eventType", "ObjectEvent")]));
+        assert!(matches(&[("MATCH_epc", "urn:epc:idpat:sgtin:0614141.107346.*"), ("LT_eventTime", "2005-04-04T00:00:00Z")]));
+        assert!(!matches(&[("GE_eventTime", "2005-04-04T00:00:00Z")]));
+        assert!(!matches(&[("EQ_bizLocation", "urn:epc:id:sgln:0614141.00888.0")]));
+        assert!(EpcisQuery::from_params(&params(&[("EQ_readPoint", "x")])).is_err());
+
+        // 20:33 UTC is 14:33 local time at a -06:00 site
+        let local = EventDetails {
+            time_zone_offset: FixedOffset::west_opt(6 * 3600),
+            ..EventDetails::default()
+        };
+        let query = EpcisQuery::from_params(&params(&[("LT_localEventTime", "2005-04-03T15:00:00")])).unwrap();
+        assert!(query.matches(&event, &local));
+        assert!(!query.matches(&event, &details));
+    }
+}
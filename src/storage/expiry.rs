@@ -0,0 +1,170 @@
+use crate::storage::inventory::product_class;
+use crate::storage::recall::LineageEvent;
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// CBV master data attribute holding an item's expiry
+pub const ITEM_EXPIRATION_DATE: &str = "urn:epcglobal:cbv:mda:itemExpirationDate";
+
+/// Disposition inferred for events that observe expired product
+pub const EXPIRED_DISPOSITION: &str = "urn:epcglobal:cbv:disp:expired";
+
+/// An event that observed (or added) an EPC after it expired
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExpiredObservation {
+    pub event_id: String,
+    pub epc: String,
+    pub event_time: String,
+    pub expired_at: String,
+}
+
+/// An EPC in inventory that expires within the requested horizon
+#[derive(Debug, Clone, Serialize)]
+pub struct ExpiringItem {
+    pub epc: String,
+    pub product_class: String,
+    pub expires_at: String,
+    /// Negative once the item has expired
+    pub days_remaining: i64,
+    pub expired: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LocationExpiry {
+    pub location: String,
+    pub items: Vec<ExpiringItem>,
+}
+
+/// Instant from which an item counts as expired.
+///
+/// An `xsd:date` expiry lasts the whole day, so the item expires at the
+/// following midnight (UTC); an `xsd:dateTime` expires at that instant.
+pub fn expiry_instant(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    let next_day = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?.succ_opt()?;
+    Some(Utc.from_utc_datetime(&next_day.and_hms_opt(0, 0, 0)?))
+}
+
+/// ADD and OBSERVE events whose eventTime is at or past the expiry of an EPC they list
+pub fn expired_observations(
+    events: &[LineageEvent],
+    expiries: &HashMap<String, DateTime<Utc>>,
+) -> Vec<ExpiredObservation> {
+    let mut observations: Vec<(DateTime<Utc>, ExpiredObservation)> = events
+        .iter()
+        .filter(|event| matches!(event.action.as_str(), "ADD" | "OBSERVE"))
+        .flat_map(|event| {
+            event.epcs.iter().filter_map(move |epc| {
+                let expired_at = expiries.get(epc).filter(|expiry| event.event_time >= **expiry)?;
+                Some((
+                    event.event_time,
+                    ExpiredObservation {
+                        event_id: event.event_id.clone(),
+                        epc: epc.clone(),
+                        event_time: event.event_time.to_rfc3339(),
+                        expired_at: expired_at.to_rfc3339(),
+                    },
+                ))
+            })
+        })
+        .collect();
+
+    observations.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.epc.cmp(&b.1.epc)));
+    observations.into_iter().map(|(_, observation)| observation).collect()
+}
+
+/// Items in inventory expiring before `now + within`, grouped by location and soonest first.
+///
+/// Items that have already expired are included (and marked) so they can be pulled.
+pub fn expiring_inventory(
+    locations: &HashMap<String, String>,
+    expiries: &HashMap<String, DateTime<Utc>>,
+    now: DateTime<Utc>,
+    within: Duration,
+) -> Vec<LocationExpiry> {
+    let horizon = now + within;
+    let mut by_location: BTreeMap<&str, Vec<(DateTime<Utc>, ExpiringItem)>> = BTreeMap::new();
+
+    for (epc, location) in locations {
+        let Some(expiry) = expiries.get(epc).filter(|expiry| **expiry <= horizon) else { continue };
+        by_location.entry(location.as_str()).or_default().push((
+            *expiry,
+            ExpiringItem {
+                epc: epc.clone(),
+                product_class: product_class(epc),
+                expires_at: expiry.to_rfc3339(),
+                days_remaining: (*expiry - now).num_days(),
+                expired: *expiry <= now,
+            },
+        ));
+    }
+
+    by_location
+        .into_iter()
+        .map(|(location, mut items)| {
+            items.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.epc.cmp(&b.1.epc)));
+            LocationExpiry {
+                location: location.to_string(),
+                items: items.into_iter().map(|(_, item)| item).collect(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_expiry_lasts_the_whole_day() {
+        let expiry = expiry_instant("2024-05-10").unwrap();
+        assert_eq!(expiry.to_rfc3339(), "2024-05-11T00:00:00+00:00");
+        assert_eq!(
+            expiry_instant("2024-05-10T12:00:00+02:00").unwrap().to_rfc3339(),
+            "2024-05-10T10:00:00+00:00"
+        );
+        assert!(expiry_instant("next week").is_none());
+    }
+
+    #[test]
+    fn test_expired_observations_and_expiring_inventory() {
+        let (milk, cheese) = ("urn:epc:id:sgtin:0614141.100001.1", "urn:epc:id:sgtin:0614141.100002.1");
+        let store = "urn:epc:id:sgln:0614141.00002.0";
+        let expiries = HashMap::from([
+            (milk.to_string(), expiry_instant("2024-05-10").unwrap()),
+            (cheese.to_string(), expiry_instant("2024-07-01").unwrap()),
+        ]);
+        let observe = |id: &str, time: &str| LineageEvent {
+            event_id: id.to_string(),
+            event_type: "ObjectEvent".to_string(),
+            action: "OBSERVE".to_string(),
+            event_time: time.parse().unwrap(),
+            biz_step: None,
+            biz_location: Some(store.to_string()),
+            epcs: vec![milk.to_string(), cheese.to_string()],
+            parent: None,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            destination_parties: Vec::new(),
+            destination_locations: Vec::new(),
+        };
+
+        let flagged = expired_observations(
+            &[observe("e1", "2024-05-10T18:00:00Z"), observe("e2", "2024-05-11T08:00:00Z")],
+            &expiries,
+        );
+        assert_eq!(flagged.len(), 1);
+        assert_eq!((flagged[0].event_id.as_str(), flagged[0].epc.as_str()), ("e2", milk));
+
+        let locations = HashMap::from([(milk.to_string(), store.to_string()), (cheese.to_string(), store.to_string())]);
+        let now = "2024-05-08T00:00:00Z".parse().unwrap();
+        let soon = expiring_inventory(&locations, &expiries, now, Duration::days(7));
+        assert_eq!(soon.len(), 1);
+        assert_eq!(soon[0].items.len(), 1);
+        assert_eq!(soon[0].items[0].days_remaining, 3);
+        assert!(!soon[0].items[0].expired);
+    }
+}
@@ -0,0 +1,166 @@
+use crate::storage::oxigraph_store::is_derived_graph;
+use crate::EpcisKgError;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Named graph recording where and when graphs were loaded
+pub const GRAPH_LOADS_GRAPH: &str = "urn:epcis:graph_loads";
+
+/// What a named graph holds, judged by its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphKind {
+    Ontology,
+    Event,
+    Inferred,
+    Master,
+    Other,
+}
+
+impl GraphKind {
+    pub fn of(graph_name: &str) -> Self {
+        if graph_name.starts_with("urn:epcis:ontology:") {
+            GraphKind::Ontology
+        } else if graph_name.starts_with("urn:epcis:event:") {
+            GraphKind::Event
+        } else if is_derived_graph(graph_name) {
+            GraphKind::Inferred
+        } else if graph_name.starts_with("urn:epcis:master:") {
+            GraphKind::Master
+        } else {
+            GraphKind::Other
+        }
+    }
+}
+
+impl FromStr for GraphKind {
+    type Err = EpcisKgError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "ontology" => Ok(GraphKind::Ontology),
+            "event" => Ok(GraphKind::Event),
+            "inferred" => Ok(GraphKind::Inferred),
+            "master" => Ok(GraphKind::Master),
+            "other" => Ok(GraphKind::Other),
+            other => Err(EpcisKgError::InvalidField {
+                field: "type".to_string(),
+                message: format!("unknown graph type '{}' (expected ontology, event, inferred, master or other)", other),
+            }),
+        }
+    }
+}
+
+/// Outcome of checking an ontology graph against an OWL 2 profile
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfileStatus {
+    pub profile: String,
+    pub compliant: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<String>,
+}
+
+/// One named graph of the store and what is known about it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphInfo {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: GraphKind,
+    /// File or upload the graph was loaded from; `capture` for events, `inference` for derived graphs
+    pub source: Option<String>,
+    /// Ontology version (see `runs::ontology_version`); ontology graphs only
+    pub version: Option<String>,
+    pub triples: usize,
+    /// When the graph was last loaded; an event's `recordTime`
+    pub loaded_at: Option<String>,
+    /// Filled in for ontology graphs by callers that check profiles
+    pub profile: Option<ProfileStatus>,
+}
+
+/// Which graphs to list and which page of them
+#[derive(Debug, Clone, Default)]
+pub struct GraphFilter {
+    pub kind: Option<GraphKind>,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+/// A page of graphs, ordered by name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphPage {
+    /// Number of graphs matching the filter across all pages
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    /// Offset of the following page, if there is one
+    pub next_offset: Option<usize>,
+    pub graphs: Vec<GraphInfo>,
+}
+
+impl GraphPage {
+    /// Cut the requested page out of the sorted, filtered graph names
+    pub fn page_names(names: Vec<String>, filter: &GraphFilter) -> (usize, Vec<String>) {
+        let matching: Vec<String> =
+            names.into_iter().filter(|name| filter.kind.map_or(true, |kind| GraphKind::of(name) == kind)).collect();
+        let total = matching.len();
+        (total, matching.into_iter().skip(filter.offset).take(filter.limit).collect())
+    }
+
+    pub fn new(total: usize, filter: &GraphFilter, graphs: Vec<GraphInfo>) -> Self {
+        let end = filter.offset.saturating_add(graphs.len());
+        Self {
+            total,
+            offset: filter.offset,
+            limit: filter.limit,
+            next_offset: (end < total).then_some(end),
+            graphs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ontology::loader::OntologyData;
+    use crate::storage::oxigraph_store::{LoadMode, OxigraphStore};
+
+    #[test]
+    fn test_graphs_are_typed_paged_and_carry_load_metadata() {
+        let mut store = OxigraphStore::new_memory().unwrap();
+        let mut graph = oxrdf::Graph::default();
+        graph.insert(oxrdf::TripleRef::new(
+            oxrdf::NamedNodeRef::new_unchecked("urn:epcglobal:epcis:ObjectEvent"),
+            oxrdf::NamedNodeRef::new_unchecked("http://www.w3.org/2000/01/rdf-schema#subClassOf"),
+            oxrdf::NamedNodeRef::new_unchecked("urn:epcglobal:epcis:EPCISEvent"),
+        ));
+        let ontology = OntologyData { triples_count: 1, graph, source_file: "ontologies/epcis2.ttl".to_string() };
+        store.store_ontology_data_as(&ontology, Some("urn:epcis:ontology:epcis2"), LoadMode::Replace).unwrap();
+        let typed = oxrdf::Triple::new(
+            oxrdf::NamedNode::new_unchecked("urn:epc:id:sgtin:0614141.107346.1"),
+            oxrdf::NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"),
+            oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:PhysicalObject"),
+        );
+        for id in ["evt-1", "evt-2", "evt-3"] {
+            store.insert_triples(&format!("urn:epcis:event:{}", id), &[typed.clone()]).unwrap();
+        }
+        store.insert_triples("urn:epcis:inferred", &[typed]).unwrap();
+
+        let ontologies = store.graph_catalog(&GraphFilter { kind: Some(GraphKind::Ontology), offset: 0, limit: 10 }, |_| true);
+        assert_eq!(ontologies.total, 1);
+        let epcis2 = &ontologies.graphs[0];
+        assert_eq!(epcis2.source.as_deref(), Some("ontologies/epcis2.ttl"));
+        assert_eq!(epcis2.triples, 1);
+        assert!(epcis2.loaded_at.is_some());
+        assert!(epcis2.version.as_deref().is_some_and(|version| version.starts_with("sha256:")));
+
+        let events = store.graph_catalog(&GraphFilter { kind: Some(GraphKind::Event), offset: 1, limit: 1 }, |_| true);
+        assert_eq!((events.total, events.next_offset), (3, Some(2)));
+        assert_eq!(events.graphs[0].name, "urn:epcis:event:evt-2");
+        assert_eq!(events.graphs[0].source.as_deref(), Some("capture"));
+
+        // The load records themselves are bookkeeping, not data
+        let all = store.graph_catalog(&GraphFilter { kind: None, offset: 0, limit: 10 }, |_| true);
+        assert_eq!(all.total, 5);
+        assert!("ontologies".parse::<GraphKind>().is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use crate::config::{GraphTtlPolicy, TtlScope};
+use chrono::{DateTime, Duration, Utc};
+use oxrdf::{Graph, NamedNodeRef, Subject, Triple};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// What one TTL sweep deleted
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TtlSweepReport {
+    /// Graphs deleted whole, or left empty by expired subjects
+    pub graphs_removed: Vec<String>,
+    pub subjects_expired: usize,
+    pub triples_removed: usize,
+    /// Materialized triples about the deleted resources
+    pub inferences_retracted: usize,
+}
+
+/// The policy of a graph: the first one whose pattern matches it
+pub fn policy_for<'a>(policies: &'a [GraphTtlPolicy], graph_name: &str) -> Option<&'a GraphTtlPolicy> {
+    policies.iter().find(|policy| policy.applies_to(graph_name))
+}
+
+/// Triples of `graph` that are past the policy's TTL at `now`
+pub fn expired_triples(graph: &Graph, policy: &GraphTtlPolicy, now: DateTime<Utc>) -> Vec<Triple> {
+    let Ok(time_predicate) = NamedNodeRef::new(&policy.time_predicate) else { return Vec::new() };
+    let cutoff = now - Duration::seconds(i64::try_from(policy.ttl_seconds).unwrap_or(i64::MAX));
+    let times: Vec<(Subject, DateTime<Utc>)> = graph
+        .triples_for_predicate(time_predicate)
+        .filter_map(|triple| match triple.object {
+            oxrdf::TermRef::Literal(time) => {
+                let time = DateTime::parse_from_rfc3339(time.value()).ok()?;
+                Some((triple.subject.into_owned(), time.with_timezone(&Utc)))
+            }
+            _ => None,
+        })
+        .collect();
+
+    match policy.scope {
+        // Undated graphs are left alone
+        TtlScope::Graph => match times.iter().map(|(_, time)| *time).max() {
+            Some(newest) if newest < cutoff => graph.iter().map(|triple| triple.into_owned()).collect(),
+            _ => Vec::new(),
+        },
+        TtlScope::Subject => {
+            // A subject with several times lives as long as its newest one
+            let fresh: HashSet<&Subject> = times.iter().filter(|(_, time)| *time >= cutoff).map(|(subject, _)| subject).collect();
+            let expired: HashSet<Subject> = times.iter().map(|(subject, _)| subject).filter(|subject| !fresh.contains(subject)).cloned().collect();
+            graph.iter().map(|triple| triple.into_owned()).filter(|triple| expired.contains(&triple.subject)).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{GraphTtlPolicy, TtlScope};
+    use crate::storage::oxigraph_store::{graph_file, OxigraphStore};
+    use chrono::{TimeZone, Utc};
+
+    fn policy(graphs: &str, scope: TtlScope) -> GraphTtlPolicy {
+        GraphTtlPolicy {
+            graphs: graphs.to_string(),
+            ttl_seconds: 3600,
+            scope,
+            time_predicate: "urn:epcglobal:epcis:recordTime".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_sweep_deletes_expired_graphs_subjects_and_their_inferences() {
+        let node = |iri: &str| oxrdf::NamedNode::new_unchecked(iri);
+        let dated = |subject: &str, time: &str| {
+            vec![
+                oxrdf::Triple::new(
+                    node(subject),
+                    node("urn:epcglobal:epcis:recordTime"),
+                    oxrdf::Literal::new_typed_literal(time, node("http://www.w3.org/2001/XMLSchema#dateTime")),
+                ),
+                oxrdf::Triple::new(node(subject), node("urn:x:status"), oxrdf::Literal::new_simple_literal("up")),
+            ]
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = OxigraphStore::new(dir.path()).unwrap();
+        store.insert_triples("urn:epcis:heartbeat:old", &dated("urn:x:sensor-1", "2024-03-01T08:00:00Z")).unwrap();
+        store.insert_triples("urn:epcis:heartbeat:new", &dated("urn:x:sensor-2", "2024-03-01T09:30:00Z")).unwrap();
+        store.insert_triples("urn:epcis:reservations", &dated("urn:x:hold-1", "2024-03-01T07:00:00Z")).unwrap();
+        store.insert_triples("urn:epcis:reservations", &dated("urn:x:hold-2", "2024-03-01T09:45:00Z")).unwrap();
+        let blocks = oxrdf::Triple::new(node("urn:x:hold-1"), node("urn:x:blocks"), node("urn:x:dock-4"));
+        store.insert_triples("urn:epcis:inferred", &[blocks]).unwrap();
+
+        let policies = vec![policy("urn:epcis:heartbeat:*", TtlScope::Graph), policy("urn:epcis:reservations", TtlScope::Subject)];
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 10, 0, 0).unwrap();
+        let report = store.expire_by_ttl(&policies, now).unwrap();
+
+        assert_eq!(report.graphs_removed, vec!["urn:epcis:heartbeat:old".to_string()]);
+        assert_eq!(report.subjects_expired, 2);
+        assert_eq!(report.triples_removed, 4);
+        assert_eq!(report.inferences_retracted, 1);
+        assert_eq!(store.graph_len("urn:epcis:heartbeat:new"), 2);
+        assert_eq!(store.graph_len("urn:epcis:reservations"), 2);
+        assert_eq!(store.graph_len("urn:epcis:inferred"), 0);
+        // The expired graph's file goes too, so it does not come back on reopening
+        assert!(!graph_file(dir.path(), "urn:epcis:heartbeat:old").exists());
+        assert_eq!(OxigraphStore::new(dir.path()).unwrap().graph_len("urn:epcis:heartbeat:old"), 0);
+
+        // Nothing left to expire
+        assert_eq!(store.expire_by_ttl(&policies, now).unwrap().triples_removed, 0);
+    }
+}
@@ -0,0 +1,214 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Prefix of the dated inventory snapshot graphs (`urn:epcis:inventory:2024-01-31`)
+pub const INVENTORY_SNAPSHOT_PREFIX: &str = "urn:epcis:inventory:";
+
+/// The parts of a stored event that affect inventory
+#[derive(Debug, Clone, PartialEq)]
+pub struct InventoryEvent {
+    pub event_id: String,
+    pub event_type: String,
+    /// ADD, OBSERVE or DELETE
+    pub action: String,
+    pub event_time: DateTime<Utc>,
+    pub biz_location: Option<String>,
+    pub epcs: Vec<String>,
+    /// Aggregation parent (AggregationEvent only)
+    pub parent: Option<String>,
+}
+
+/// Number of EPCs of one product class at one location
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InventoryPosition {
+    pub location: String,
+    pub product_class: String,
+    pub quantity: usize,
+}
+
+/// Inventory positions as of a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InventorySnapshot {
+    /// UTC day the snapshot is stored under (YYYY-MM-DD)
+    pub date: String,
+    pub as_of: String,
+    pub events_replayed: usize,
+    pub positions: Vec<InventoryPosition>,
+}
+
+impl InventorySnapshot {
+    pub fn graph_name(&self) -> String {
+        snapshot_graph_name(&self.date)
+    }
+}
+
+pub fn snapshot_graph_name(date: &str) -> String {
+    format!("{}{}", INVENTORY_SNAPSHOT_PREFIX, date)
+}
+
+/// Product class of an EPC: the `idpat` pattern of an SGTIN, or the EPC scheme otherwise
+pub fn product_class(epc: &str) -> String {
+    match epc.strip_prefix("urn:epc:id:sgtin:") {
+        Some(sgtin) => match sgtin.rsplit_once('.') {
+            Some((class, _serial)) => format!("urn:epc:idpat:sgtin:{}.*", class),
+            None => epc.to_string(),
+        },
+        None => match epc.strip_prefix("urn:epc:id:").and_then(|rest| rest.split(':').next()) {
+            Some(scheme) => format!("urn:epc:idpat:{}:*", scheme),
+            None => epc.to_string(),
+        },
+    }
+}
+
+/// Whether an EPC falls under a class pattern: an exact identifier, or an
+/// `urn:epc:idpat:...*` pattern (a trailing `*` matches any suffix)
+pub fn class_pattern_matches(pattern: &str, epc: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => epc.starts_with(&prefix.replacen("urn:epc:idpat:", "urn:epc:id:", 1)),
+        None => pattern == epc,
+    }
+}
+
+/// Replay events up to `as_of` and count EPCs per location and product class
+pub fn compute_snapshot(events: &[InventoryEvent], as_of: DateTime<Utc>) -> InventorySnapshot {
+    let (location_of, events_replayed) = current_locations(events, as_of);
+
+    let mut counts: BTreeMap<(String, String), usize> = BTreeMap::new();
+    for (epc, location) in &location_of {
+        *counts.entry((location.clone(), product_class(epc))).or_default() += 1;
+    }
+
+    InventorySnapshot {
+        date: as_of.format("%Y-%m-%d").to_string(),
+        as_of: as_of.to_rfc3339(),
+        events_replayed,
+        positions: counts
+            .into_iter()
+            .map(|((location, product_class), quantity)| InventoryPosition {
+                location,
+                product_class,
+                quantity,
+            })
+            .collect(),
+    }
+}
+
+/// Location of every EPC in inventory as of `as_of`, and the number of events replayed.
+///
+/// ADD and OBSERVE place the event's EPCs at its bizLocation, DELETE on an
+/// object-level event removes them from inventory. AggregationEvents pack
+/// (ADD) or unpack (DELETE) children into a parent; packed children move
+/// with their parent when the parent is observed elsewhere.
+pub fn current_locations(events: &[InventoryEvent], as_of: DateTime<Utc>) -> (HashMap<String, String>, usize) {
+    let mut replay: Vec<&InventoryEvent> = events.iter().filter(|event| event.event_time <= as_of).collect();
+    replay.sort_by_key(|event| event.event_time);
+
+    let mut location_of: HashMap<String, String> = HashMap::new();
+    let mut parent_of: HashMap<String, String> = HashMap::new();
+
+    for event in &replay {
+        match (event.event_type.as_str(), event.action.as_str()) {
+            ("AggregationEvent", action) => {
+                let Some(parent) = &event.parent else { continue };
+                if action == "DELETE" {
+                    // No child list unpacks everything in the parent
+                    let children: Vec<String> = if event.epcs.is_empty() {
+                        parent_of.iter().filter(|(_, p)| *p == parent).map(|(c, _)| c.clone()).collect()
+                    } else {
+                        event.epcs.clone()
+                    };
+                    for child in children {
+                        parent_of.remove(&child);
+                    }
+                } else {
+                    for child in &event.epcs {
+                        parent_of.insert(child.clone(), parent.clone());
+                    }
+                }
+                if let Some(location) = &event.biz_location {
+                    move_with_contents(parent, location, &mut location_of, &parent_of);
+                }
+            }
+            (_, "DELETE") => {
+                for epc in &event.epcs {
+                    location_of.remove(epc);
+                    parent_of.remove(epc);
+                }
+            }
+            _ => {
+                if let Some(location) = &event.biz_location {
+                    for epc in &event.epcs {
+                        move_with_contents(epc, location, &mut location_of, &parent_of);
+                    }
+                }
+            }
+        }
+    }
+
+    (location_of, replay.len())
+}
+
+/// Place an EPC and everything packed inside it (transitively) at a location
+fn move_with_contents(
+    epc: &str,
+    location: &str,
+    location_of: &mut HashMap<String, String>,
+    parent_of: &HashMap<String, String>,
+) {
+    let mut pending = vec![epc.to_string()];
+    let mut seen = HashSet::new();
+    while let Some(current) = pending.pop() {
+        if !seen.insert(current.clone()) {
+            continue;
+        }
+        pending.extend(parent_of.iter().filter(|(_, parent)| **parent == current).map(|(child, _)| child.clone()));
+        location_of.insert(current, location.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str, event_type: &str, action: &str, day: u32, location: &str, epcs: &[&str], parent: Option<&str>) -> InventoryEvent {
+        InventoryEvent {
+            event_id: id.to_string(),
+            event_type: event_type.to_string(),
+            action: action.to_string(),
+            event_time: format!("2024-01-{:02}T12:00:00Z", day).parse().unwrap(),
+            biz_location: Some(location.to_string()),
+            epcs: epcs.iter().map(|epc| epc.to_string()).collect(),
+            parent: parent.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_replays_add_aggregation_and_delete() {
+        let (factory, store) = ("urn:epc:id:sgln:0614141.00001.0", "urn:epc:id:sgln:0614141.00002.0");
+        let (item_a, item_b) = ("urn:epc:id:sgtin:0614141.107346.1", "urn:epc:id:sgtin:0614141.107346.2");
+        let case = "urn:epc:id:sscc:0614141.1234567890";
+        let events = vec![
+            event("1", "ObjectEvent", "ADD", 1, factory, &[item_a, item_b], None),
+            event("2", "AggregationEvent", "ADD", 2, factory, &[item_a, item_b], Some(case)),
+            event("3", "ObjectEvent", "OBSERVE", 3, store, &[case], None),
+            event("4", "ObjectEvent", "DELETE", 4, store, &[item_b], None),
+        ];
+
+        let day_three = compute_snapshot(&events, "2024-01-03T23:59:59Z".parse().unwrap());
+        assert_eq!(day_three.date, "2024-01-03");
+        assert!(day_three.positions.contains(&InventoryPosition {
+            location: store.to_string(),
+            product_class: "urn:epc:idpat:sgtin:0614141.107346.*".to_string(),
+            quantity: 2,
+        }));
+
+        let day_four = compute_snapshot(&events, "2024-01-04T23:59:59Z".parse().unwrap());
+        let items_at_store = day_four
+            .positions
+            .iter()
+            .find(|p| p.location == store && p.product_class.starts_with("urn:epc:idpat:sgtin"))
+            .unwrap();
+        assert_eq!(items_at_store.quantity, 1);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::config::{ChangefeedConfig, JsonLdConfig, ResiliencePolicy};
+use crate::storage::changefeed::{self, Change, ChangeSink};
+use crate::utils::jsonld;
+use crate::utils::resilience::{Failure, Resilience};
+use crate::EpcisKgError;
+use rdkafka::config::ClientConfig;
+use rdkafka::error::{KafkaError, RDKafkaErrorCode};
+use rdkafka::producer::{BaseRecord, DefaultProducerContext, Producer, ThreadedProducer};
+use serde_json::Value;
+use std::time::Duration;
+use tracing::warn;
+
+/// Changefeed sink producing one Kafka record per committed change.
+///
+/// Records are keyed by event ID (events) or graph name (inferences) so a
+/// consumer sees the changes of one key in commit order. A full producer queue
+/// is retried with backoff; records that cannot be queued are dead-lettered.
+pub struct KafkaChangefeed {
+    producer: ThreadedProducer<DefaultProducerContext>,
+    config: ChangefeedConfig,
+    frame: Value,
+    resilience: Resilience,
+}
+
+impl KafkaChangefeed {
+    pub fn new(config: &ChangefeedConfig, jsonld_config: &JsonLdConfig, policy: ResiliencePolicy) -> Result<Self, EpcisKgError> {
+        let producer = ClientConfig::new()
+            .set("bootstrap.servers", &config.brokers)
+            .set("client.id", &config.client_id)
+            .create()
+            .map_err(|e| EpcisKgError::Config(format!("Failed to create Kafka producer: {}", e)))?;
+
+        Ok(Self {
+            producer,
+            config: config.clone(),
+            frame: jsonld::load_frame(jsonld::EVENT_FRAME, jsonld_config)?,
+            resilience: Resilience::new("kafka", policy),
+        })
+    }
+}
+
+impl ChangeSink for KafkaChangefeed {
+    fn publish(&self, change: &Change) {
+        let topic = match change {
+            Change::Event { .. } => self.config.events_topic.as_str(),
+            Change::Inferred { .. } => match &self.config.inferences_topic {
+                Some(topic) => topic.as_str(),
+                None => return,
+            },
+        };
+        let payload = match changefeed::serialize(change, self.config.format, &self.frame) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("Failed to serialize changefeed record for {}: {}", change.key(), e);
+                return;
+            }
+        };
+
+        let record = serde_json::json!({
+            "topic": topic,
+            "key": change.key(),
+            "payload": String::from_utf8_lossy(&payload),
+        });
+        let queued = self.resilience.call_blocking(&record, || {
+            match self.producer.send(BaseRecord::to(topic).key(change.key()).payload(&payload)) {
+                Ok(()) => Ok(()),
+                Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), _)) => {
+                    Err(Failure::Transient("producer queue is full".to_string()))
+                }
+                Err((e, _)) => Err(Failure::Permanent(e.to_string())),
+            }
+        });
+        if let Err(e) = queued {
+            warn!("Failed to queue changefeed record for {} on {}: {}", change.key(), topic, e);
+        }
+    }
+}
+
+impl Drop for KafkaChangefeed {
+    fn drop(&mut self) {
+        if let Err(e) = self.producer.flush(Duration::from_secs(5)) {
+            warn!("Changefeed records may be lost, flush failed: {}", e);
+        }
+    }
+}
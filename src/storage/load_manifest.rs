@@ -0,0 +1,106 @@
+use crate::EpcisKgError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// File name of the manifest kept in the database directory
+pub const LOAD_MANIFEST_FILE: &str = "load_manifest.json";
+
+/// A file whose triples were fully stored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompletedFile {
+    /// Canonical path, so `./a.ttl` and `a.ttl` are the same file
+    pub path: String,
+    pub size: u64,
+    pub modified: Option<DateTime<Utc>>,
+    pub graph: String,
+    pub triples: usize,
+    pub loaded_at: DateTime<Utc>,
+}
+
+/// Files completed by a multi-file load, so an interrupted load can resume
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LoadManifest {
+    pub completed: Vec<CompletedFile>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+/// Identity of a file on disk: canonical path, size and modification time
+fn fingerprint(file: &Path) -> Result<(String, u64, Option<DateTime<Utc>>), EpcisKgError> {
+    let canonical = std::fs::canonicalize(file)?;
+    let metadata = std::fs::metadata(&canonical)?;
+    let modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+    Ok((canonical.to_string_lossy().into_owned(), metadata.len(), modified))
+}
+
+impl LoadManifest {
+    /// Manifest of `db_path`; a fresh one unless resuming
+    pub fn open(db_path: &str, resume: bool) -> Result<Self, EpcisKgError> {
+        let path = Path::new(db_path).join(LOAD_MANIFEST_FILE);
+        let mut manifest = if resume && path.exists() {
+            serde_json::from_str(&std::fs::read_to_string(&path)?)?
+        } else {
+            LoadManifest::default()
+        };
+        manifest.path = path;
+        Ok(manifest)
+    }
+
+    /// The completed entry for `file`, if it is unchanged since it was loaded
+    pub fn completed(&self, file: &Path) -> Option<&CompletedFile> {
+        let (path, size, modified) = fingerprint(file).ok()?;
+        self.completed
+            .iter()
+            .find(|entry| entry.path == path && entry.size == size && entry.modified == modified)
+    }
+
+    /// Record `file` as loaded and write the manifest
+    pub fn record(&mut self, file: &Path, graph: &str, triples: usize) -> Result<(), EpcisKgError> {
+        let (path, size, modified) = fingerprint(file)?;
+        self.completed.retain(|entry| entry.path != path);
+        self.completed.push(CompletedFile {
+            path,
+            size,
+            modified,
+            graph: graph.to_string(),
+            triples,
+            loaded_at: Utc::now(),
+        });
+        self.save()
+    }
+
+    /// Write via a temporary file so an interrupted write never leaves a truncated manifest
+    fn save(&self) -> Result<(), EpcisKgError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let temporary = self.path.with_extension("json.tmp");
+        std::fs::write(&temporary, serde_json::to_string_pretty(self)?)?;
+        std::fs::rename(&temporary, &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_skips_unchanged_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("db");
+        let file = dir.path().join("a.ttl");
+        std::fs::write(&file, "<urn:a> <urn:b> <urn:c> .\n").unwrap();
+
+        let mut manifest = LoadManifest::open(db_path.to_str().unwrap(), false).unwrap();
+        manifest.record(&file, "urn:epcis:ontology:a.ttl-0", 1).unwrap();
+
+        let resumed = LoadManifest::open(db_path.to_str().unwrap(), true).unwrap();
+        assert_eq!(resumed.completed(&file).map(|entry| entry.triples), Some(1));
+        assert!(LoadManifest::open(db_path.to_str().unwrap(), false).unwrap().completed(&file).is_none());
+
+        std::fs::write(&file, "<urn:a> <urn:b> <urn:c> .\n<urn:a> <urn:b> <urn:d> .\n").unwrap();
+        assert!(resumed.completed(&file).is_none());
+    }
+}
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+/// Page size used when a request does not ask for one
+pub const DEFAULT_PAGE_SIZE: usize = 100;
+
+/// Which materialized triples to list and which page of them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaterializedFilter {
+    /// Exact name of a derived graph
+    pub graph: Option<String>,
+    /// Predicate IRI
+    pub predicate: Option<String>,
+    /// Prefix of the subject IRI, e.g. `urn:epc:id:sgtin:0614141.`
+    pub subject_prefix: Option<String>,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl Default for MaterializedFilter {
+    fn default() -> Self {
+        Self {
+            graph: None,
+            predicate: None,
+            subject_prefix: None,
+            offset: 0,
+            limit: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+/// One materialized triple, with terms in N-Triples-star syntax
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct MaterializedTriple {
+    pub graph: String,
+    pub subject: String,
+    pub predicate: String,
+    pub object: String,
+}
+
+/// A page of materialized triples, ordered by graph, subject, predicate and object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaterializedPage {
+    /// Number of triples matching the filter across all pages
+    pub total: usize,
+    pub offset: usize,
+    pub limit: usize,
+    /// Offset of the following page, if there is one
+    pub next_offset: Option<usize>,
+    pub triples: Vec<MaterializedTriple>,
+}
+
+impl MaterializedPage {
+    /// Sort the matching triples and cut out the requested page
+    pub fn paginate(mut triples: Vec<MaterializedTriple>, offset: usize, limit: usize) -> Self {
+        triples.sort();
+        let total = triples.len();
+        let triples: Vec<MaterializedTriple> = triples.into_iter().skip(offset).take(limit).collect();
+        let end = offset.saturating_add(triples.len());
+        Self {
+            total,
+            offset,
+            limit,
+            next_offset: (end < total).then_some(end),
+            triples,
+        }
+    }
+}
@@ -1 +1,27 @@
-pub mod oxigraph_store;
\ No newline at end of file
+pub mod archive;
+pub mod canonical;
+pub mod carbon;
+pub mod changefeed;
+pub mod cold_chain;
+pub mod columnar;
+pub mod counterfeit;
+pub mod encryption;
+pub mod entity_resolution;
+pub mod epc_status;
+pub mod epcis_query;
+pub mod expiry;
+pub mod graph_catalog;
+pub mod graph_ttl;
+pub mod inventory;
+#[cfg(feature = "kafka")]
+pub mod kafka;
+pub mod load_manifest;
+pub mod materialized;
+pub mod oxigraph_store;
+pub mod overlay;
+pub mod patterns;
+pub mod quality;
+pub mod recall;
+pub mod snapshots;
+pub mod sparql_update;
+pub mod views;
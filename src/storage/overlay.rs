@@ -0,0 +1,331 @@
+use crate::EpcisKgError;
+use crate::config::SessionConfig;
+use crate::storage::oxigraph_store::{OverlayView, OxigraphStore};
+use oxrdf::Graph as OxrdfGraph;
+use oxttl::TurtleParser;
+use parking_lot::Mutex;
+use serde::Serialize;
+use spargebra::term::GraphName;
+use spargebra::{GraphTarget, GraphUpdateOperation, SparqlParser};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Graph receiving hypothetical triples when no graph is named
+pub const DEFAULT_OVERLAY_GRAPH: &str = "urn:epcis:session:hypothetical";
+
+/// A "what-if" session layered over the persisted store.
+///
+/// Hypothetical triples and inferences live only in the session's overlay
+/// graphs; reads see the base store with the overlay merged on top, and
+/// discarding the session drops the overlay without touching the store.
+pub struct OverlaySession {
+    id: String,
+    created_at: chrono::DateTime<chrono::Utc>,
+    last_used: Instant,
+    /// API key the session was opened with; only that key may use it
+    owner: Option<String>,
+    overlay: HashMap<String, OxrdfGraph>,
+}
+
+/// Session description returned over the API
+#[derive(Debug, Clone, Serialize)]
+pub struct OverlaySessionSummary {
+    pub session_id: String,
+    pub created_at: String,
+    pub overlay_graphs: HashMap<String, usize>,
+    pub overlay_triples: usize,
+    /// When the session expires unless it is used again
+    pub expires_at: String,
+}
+
+impl OverlaySession {
+    fn new(owner: Option<&str>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            created_at: chrono::Utc::now(),
+            last_used: Instant::now(),
+            owner: owner.map(str::to_string),
+            overlay: HashMap::new(),
+        }
+    }
+
+    /// Refuse callers other than the API key the session was opened with
+    fn check_owner(&self, api_key: Option<&str>) -> Result<(), EpcisKgError> {
+        match &self.owner {
+            Some(owner) if api_key != Some(owner.as_str()) => Err(EpcisKgError::AccessDenied(format!(
+                "Session {} belongs to another API key",
+                self.id
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Add hypothetical triples to an overlay graph, returning how many were new
+    pub fn add_triples<I>(&mut self, graph_name: &str, triples: I) -> usize
+    where
+        I: IntoIterator<Item = oxrdf::Triple>,
+    {
+        let graph = self.overlay.entry(graph_name.to_string()).or_default();
+        triples.into_iter().filter(|triple| graph.insert(triple.as_ref())).count()
+    }
+
+    /// Parse Turtle (or N-Triples) and add it to an overlay graph
+    pub fn add_turtle(&mut self, graph_name: &str, turtle: &str) -> Result<usize, EpcisKgError> {
+        let triples = TurtleParser::new()
+            .with_quoted_triples()
+            .for_reader(std::io::Cursor::new(turtle.as_bytes()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| EpcisKgError::RdfParsing(format!("Invalid hypothetical triples: {}", e)))?;
+        Ok(self.add_triples(graph_name, triples))
+    }
+
+    /// Apply a SPARQL update to the overlay, returning how many triples were new.
+    ///
+    /// `INSERT DATA` adds working data, to the hypothetical graph when no
+    /// graph is named; `CLEAR` and `DROP` empty overlay graphs. The update
+    /// applies entirely or not at all, and never reaches the persisted store.
+    pub fn apply_update(&mut self, update: &str) -> Result<usize, EpcisKgError> {
+        let update = SparqlParser::new()
+            .parse_update(update)
+            .map_err(|e| EpcisKgError::Query(format!("Invalid SPARQL update: {}", e)))?;
+
+        let mut overlay = self.overlay.clone();
+        let mut added = 0;
+        for operation in update.operations {
+            match operation {
+                GraphUpdateOperation::InsertData { data } => {
+                    for quad in data {
+                        let graph_name = match &quad.graph_name {
+                            GraphName::NamedNode(node) => node.as_str().to_string(),
+                            GraphName::DefaultGraph => DEFAULT_OVERLAY_GRAPH.to_string(),
+                        };
+                        let triple = oxrdf::Triple::new(quad.subject, quad.predicate, quad.object);
+                        if overlay.entry(graph_name).or_default().insert(triple.as_ref()) {
+                            added += 1;
+                        }
+                    }
+                }
+                GraphUpdateOperation::Clear { graph, .. } | GraphUpdateOperation::Drop { graph, .. } => match graph {
+                    GraphTarget::NamedNode(node) => {
+                        overlay.remove(node.as_str());
+                    }
+                    GraphTarget::DefaultGraph => {
+                        overlay.remove(DEFAULT_OVERLAY_GRAPH);
+                    }
+                    GraphTarget::NamedGraphs | GraphTarget::AllGraphs => overlay.clear(),
+                },
+                other => {
+                    return Err(EpcisKgError::NotImplemented(format!(
+                        "Sessions accept INSERT DATA, CLEAR and DROP only, not: {}",
+                        other
+                    )))
+                }
+            }
+        }
+
+        self.overlay = overlay;
+        Ok(added)
+    }
+
+    /// The base store with this session's overlay applied, read in place
+    pub fn view<'a>(&self, base: &'a OxigraphStore) -> OverlayView<'a> {
+        base.overlay_view(&self.overlay)
+    }
+
+    pub fn summary(&self, ttl: Duration) -> OverlaySessionSummary {
+        let overlay_graphs: HashMap<String, usize> = self
+            .overlay
+            .iter()
+            .map(|(name, graph)| (name.clone(), graph.len()))
+            .collect();
+
+        OverlaySessionSummary {
+            session_id: self.id.clone(),
+            created_at: self.created_at.to_rfc3339(),
+            overlay_triples: overlay_graphs.values().sum(),
+            overlay_graphs,
+            expires_at: (chrono::Utc::now() + ttl.saturating_sub(self.last_used.elapsed())).to_rfc3339(),
+        }
+    }
+}
+
+/// Open overlay sessions, expiring those idle for longer than the configured TTL
+pub struct OverlaySessionManager {
+    sessions: Mutex<HashMap<String, OverlaySession>>,
+    max_sessions: usize,
+    ttl: Duration,
+}
+
+impl OverlaySessionManager {
+    pub fn new(config: &SessionConfig) -> Self {
+        Self {
+            sessions: Mutex::new(HashMap::new()),
+            max_sessions: config.max_sessions,
+            ttl: Duration::from_secs(config.ttl_seconds),
+        }
+    }
+
+    /// Open a new, empty session for `owner`, the caller's API key when access control is enabled
+    pub fn open(&self, owner: Option<&str>) -> Result<OverlaySessionSummary, EpcisKgError> {
+        let mut sessions = self.sessions.lock();
+        let ttl = self.ttl;
+        sessions.retain(|_, session| session.last_used.elapsed() < ttl);
+
+        if sessions.len() >= self.max_sessions {
+            return Err(EpcisKgError::LimitExceeded(format!(
+                "Too many open sessions (maximum {})",
+                self.max_sessions
+            )));
+        }
+
+        let session = OverlaySession::new(owner);
+        let summary = session.summary(ttl);
+        sessions.insert(session.id().to_string(), session);
+        Ok(summary)
+    }
+
+    /// Idle time after which a session is discarded
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Discard sessions idle for longer than the TTL, returning how many were dropped
+    pub fn purge_expired(&self) -> usize {
+        let mut sessions = self.sessions.lock();
+        let before = sessions.len();
+        sessions.retain(|_, session| session.last_used.elapsed() < self.ttl);
+        before - sessions.len()
+    }
+
+    /// Run a closure against an open session of `api_key`, refreshing its idle timer
+    pub fn with_session<R>(
+        &self,
+        session_id: &str,
+        api_key: Option<&str>,
+        f: impl FnOnce(&mut OverlaySession) -> R,
+    ) -> Result<R, EpcisKgError> {
+        let mut sessions = self.sessions.lock();
+        let ttl = self.ttl;
+        sessions.retain(|_, session| session.last_used.elapsed() < ttl);
+
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| EpcisKgError::NotFound { resource: "Session".to_string(), id: session_id.to_string() })?;
+        session.check_owner(api_key)?;
+        session.last_used = Instant::now();
+        Ok(f(session))
+    }
+
+    /// Discard a session of `api_key` and all its hypothetical data
+    pub fn discard(&self, session_id: &str, api_key: Option<&str>) -> Result<bool, EpcisKgError> {
+        let mut sessions = self.sessions.lock();
+        match sessions.get(session_id) {
+            Some(session) => session.check_owner(api_key)?,
+            None => return Ok(false),
+        }
+        Ok(sessions.remove(session_id).is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlay_does_not_touch_base_store() {
+        let mut base = OxigraphStore::new_memory().unwrap();
+        let shipped = oxrdf::Triple::new(
+            oxrdf::NamedNode::new_unchecked("urn:epc:event:e1"),
+            oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:bizStep"),
+            oxrdf::NamedNode::new_unchecked("urn:epcglobal:cbv:bizstep:shipping"),
+        );
+        base.insert_triples("urn:epcis:event:e1", &[shipped]).unwrap();
+        let manager = OverlaySessionManager::new(&SessionConfig::default());
+        let session_id = manager.open(None).unwrap().session_id;
+
+        let added = manager
+            .with_session(&session_id, None, |session| {
+                session.add_turtle(
+                    DEFAULT_OVERLAY_GRAPH,
+                    "<urn:epc:event:what-if> <urn:epcglobal:epcis:bizStep> <urn:epcglobal:cbv:bizstep:shipping> .",
+                )
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(added, 1);
+
+        let view = manager.with_session(&session_id, None, |session| session.view(&base)).unwrap();
+        assert_eq!(view.triples_in_graphs(DEFAULT_OVERLAY_GRAPH).len(), 1);
+        // Graphs the overlay does not name are read from the base store
+        assert_eq!(view.graph_names(), vec!["urn:epcis:event:e1".to_string(), DEFAULT_OVERLAY_GRAPH.to_string()]);
+        assert_eq!(view.triples_in_graphs("urn:epcis:event:").len(), 1);
+        assert!(base.triples_in_graphs(DEFAULT_OVERLAY_GRAPH).is_empty());
+
+        assert!(manager.discard(&session_id, None).unwrap());
+        assert!(manager.with_session(&session_id, None, |_| ()).is_err());
+    }
+
+    #[test]
+    fn test_sparql_insert_data_into_session_graphs() {
+        let manager = OverlaySessionManager::new(&SessionConfig::default());
+        let session_id = manager.open(None).unwrap().session_id;
+
+        let added = manager
+            .with_session(&session_id, None, |session| {
+                session.apply_update(
+                    "INSERT DATA { <urn:x:lot1> <urn:x:score> \"3\" . \
+                     GRAPH <urn:epcis:session:compare> { <urn:x:lot2> <urn:x:score> \"5\" } }",
+                )
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(added, 2);
+
+        let summary = manager.with_session(&session_id, None, |session| session.summary(manager.ttl())).unwrap();
+        assert_eq!(summary.overlay_graphs[DEFAULT_OVERLAY_GRAPH], 1);
+        assert_eq!(summary.overlay_graphs["urn:epcis:session:compare"], 1);
+
+        // A refused operation leaves the overlay as it was
+        let refused = manager
+            .with_session(&session_id, None, |session| {
+                session.apply_update("DROP GRAPH <urn:epcis:session:compare> ; DELETE WHERE { ?s ?p ?o }")
+            })
+            .unwrap();
+        assert!(refused.is_err());
+        let summary = manager.with_session(&session_id, None, |session| session.summary(manager.ttl())).unwrap();
+        assert_eq!(summary.overlay_triples, 2);
+
+        assert_eq!(manager.purge_expired(), 0);
+    }
+
+    #[test]
+    fn test_session_limit() {
+        let manager = OverlaySessionManager::new(&SessionConfig {
+            max_sessions: 1,
+            ttl_seconds: 3600,
+        });
+
+        manager.open(None).unwrap();
+        assert!(manager.open(None).is_err());
+    }
+
+    #[test]
+    fn test_sessions_belong_to_their_api_key() {
+        let manager = OverlaySessionManager::new(&SessionConfig::default());
+        let session_id = manager.open(Some("partner-a-key")).unwrap().session_id;
+
+        for caller in [None, Some("partner-b-key")] {
+            assert!(matches!(
+                manager.with_session(&session_id, caller, |_| ()),
+                Err(EpcisKgError::AccessDenied(_))
+            ));
+            assert!(manager.discard(&session_id, caller).is_err());
+        }
+        assert!(manager.with_session(&session_id, Some("partner-a-key"), |_| ()).is_ok());
+        assert!(manager.discard(&session_id, Some("partner-a-key")).unwrap());
+    }
+}
@@ -1,28 +1,236 @@
 use crate::EpcisKgError;
+use crate::models::diff::EventFields;
+use crate::models::events::{ErrorDeclaration, ErrorDeclarationResult};
+use crate::models::parties::{OwnershipRecord, OwnershipTransfer, Party, OWNERSHIP_TRANSFER_BIZ_STEPS};
 use crate::ontology::loader::OntologyData;
+use crate::ontology::drift::{self, OntologyTerms, SchemaDriftReport, SchemaDriftTracker, UnknownTerm, SCHEMA_DRIFT_GRAPH};
+use crate::ontology::reasoner::{MATERIALIZED_GRAPHS, PROVENANCE_GRAPH};
+use crate::ontology::runs::InferenceRun;
+use crate::ontology::skos::{extension_graph_name, VocabularyExtension, VOCABULARY_EXTENSION_GRAPH_PREFIX};
+use crate::monitoring::metrics::StoreMetrics;
+use crate::config::{CarbonConfig, CounterfeitConfig, EntityResolutionConfig, GraphTtlPolicy, PatternRule, TemperatureProfile};
+use crate::storage::canonical;
+use crate::storage::carbon::{self, Shipment};
+use crate::storage::changefeed::{Change, ChangeSink};
+use crate::storage::cold_chain::{self, Excursion, TemperatureReading};
+use crate::storage::columnar::EventTables;
+use crate::storage::encryption::{self, StoreKeyring};
+use crate::storage::counterfeit::{self, SignalKind, SuspicionReport, SuspicionSignal, Verification};
+use crate::storage::entity_resolution::{self, MasterRecord, SameAsProposal, SameAsReview};
+use crate::storage::epc_status::{self, EpcStatus};
+use crate::storage::epcis_query::{EpcisQuery, EventDetails};
+use crate::storage::expiry::{self, ExpiredObservation, LocationExpiry, EXPIRED_DISPOSITION, ITEM_EXPIRATION_DATE};
+use crate::storage::patterns::{self, PatternMatch};
+use crate::storage::quality::{self, ProfiledEvent, QualityOptions, QualityReport};
+use crate::storage::materialized::{MaterializedFilter, MaterializedPage, MaterializedTriple};
+use crate::storage::graph_catalog::{GraphFilter, GraphInfo, GraphKind, GraphPage, GRAPH_LOADS_GRAPH};
+use crate::storage::graph_ttl::{self, TtlSweepReport};
+use crate::storage::inventory::{self, InventoryEvent, InventoryPosition, InventorySnapshot, INVENTORY_SNAPSHOT_PREFIX};
+use crate::storage::recall::{self, LineageEvent, RecallReport, RecallRequest};
+use crate::storage::sparql_update::{self, UpdateReport};
+use crate::storage::views::{self, ViewDefinition, ViewStatus, VIEW_DEFINITIONS_GRAPH};
+use crate::utils::anonymization::Pseudonymizer;
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 use oxrdf::Graph as OxrdfGraph;
+use sha2::{Digest, Sha256};
+use spargebra::term::{GraphName as UpdateGraphName, GraphNamePattern};
+use spargebra::{GraphTarget, GraphUpdateOperation, Query, SparqlParser};
+use tracing::{debug, info, warn};
 
+/// What loading data into a graph that already holds triples does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoadMode {
+    /// Drop the graph's previous content
+    #[default]
+    Replace,
+    /// Add to the graph's previous content
+    Merge,
+    /// Refuse to touch a non-empty graph
+    Fail,
+}
+
+impl std::str::FromStr for LoadMode {
+    type Err = EpcisKgError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "replace" => Ok(LoadMode::Replace),
+            "merge" => Ok(LoadMode::Merge),
+            "fail" => Ok(LoadMode::Fail),
+            other => Err(EpcisKgError::Config(format!(
+                "Unknown load mode '{}' (expected replace, merge or fail)",
+                other
+            ))),
+        }
+    }
+}
+
+/// Deterministic graph name for an ontology file: its base name plus a hash of
+/// its canonical path, so same-named files in different directories don't collide
+pub fn ontology_graph_name(source_file: &str) -> String {
+    let path = Path::new(source_file);
+    let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let digest = Sha256::digest(canonical.to_string_lossy().as_bytes());
+    format!("{}{}", ontology_graph_prefix(source_file), short_hash(&digest))
+}
+
+/// Graph name for an ontology uploaded by a client: its file name plus a hash
+/// of its content. The name is never resolved on the server's filesystem, and
+/// different uploads under one name (raw bodies all arrive as `upload.ttl`)
+/// get different graphs.
+pub fn upload_graph_name(file_name: &str, sha256: &[u8; 32]) -> String {
+    format!("{}{}", ontology_graph_prefix(file_name), short_hash(sha256))
+}
+
+/// Prefix shared by every graph `ontology_graph_name` and `upload_graph_name`
+/// derive for a file of this name
+pub fn ontology_graph_prefix(file_name: &str) -> String {
+    let base: String = Path::new(file_name)
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    format!("urn:epcis:ontology:{}-", base)
+}
+
+fn short_hash(digest: &[u8]) -> String {
+    digest.iter().take(6).map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// RDF dataset of named graphs, persisted as one Turtle file per graph plus
+/// `store_metadata.json` listing them. Each file is named by the SHA-256 of
+/// its graph's name (see `graph_file`).
+///
+/// A store value is not shared: writers hold it behind a lock (the server's
+/// `Mutex`) or work on clones, such as the event pipeline's. Clones and stores
+/// opened on the same directory persist through one write lock per directory,
+/// and each save only rewrites the graphs that copy changed, so concurrent
+/// captures, SPARQL updates and materializations never lose each other's graphs
+/// or leave a half-written file behind. Two copies changing the same graph
+/// still race: the later save of that graph wins. The lock is per process, so
+/// only one process may write a data directory.
 #[derive(Clone)]
 pub struct OxigraphStore {
+    /// Quads, grouped by graph name; every triple lives in a named graph
     graphs: HashMap<String, OxrdfGraph>,
     storage_path: String,
+    /// Changefeed sinks notified of stored events and new derived facts
+    change_sinks: Vec<Arc<dyn ChangeSink>>,
+    /// Opened with `open_read_only`; every mutation is refused
+    read_only: bool,
+    /// Seals graph files on save and opens them on load
+    keyring: Option<Arc<StoreKeyring>>,
+    /// Dataset version, counting the mutations since the store was opened
+    version: u64,
+    /// Query latencies, size and cache lookups, shared with clones and views
+    metrics: Arc<StoreMetrics>,
+    /// Digest of each graph's Turtle as this copy last loaded or saved it
+    persisted: HashMap<String, [u8; 32]>,
+    /// Open transaction, between `begin` and `commit` or `rollback`
+    transaction: Option<Transaction>,
 }
 
 impl OxigraphStore {
-    /// Create a new Oxigraph store with persistent storage
+    /// Create a new Oxigraph store with persistent storage, encrypted with the
+    /// process's installed keyring if there is one
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, EpcisKgError> {
+        Self::new_with_keyring(path, encryption::installed())
+    }
+    
+    /// Create a persistent store whose graph files are encrypted with `keyring`
+    pub fn new_with_keyring<P: AsRef<Path>>(path: P, keyring: Option<Arc<StoreKeyring>>) -> Result<Self, EpcisKgError> {
         let path = path.as_ref();
         let storage_path = path.to_string_lossy().to_string();
         
         // Try to load existing data or create empty store
-        let graphs = Self::load_graphs(path)?;
+        migrate_graph_files(path)?;
+        let (graphs, persisted) = Self::load_graphs(path, false, keyring.as_deref())?;
         
-        Ok(Self {
+        let store = Self {
             graphs,
             storage_path,
-        })
+            change_sinks: Vec::new(),
+            read_only: false,
+            keyring,
+            version: 0,
+            metrics: Arc::new(StoreMetrics::default()),
+            persisted,
+            transaction: None,
+        };
+        store.refresh_size_metrics();
+        Ok(store)
+    }
+    
+    /// Open a prepared dataset for querying only.
+    ///
+    /// With `mmap` the graph files are memory-mapped and parsed in place rather
+    /// than read into heap buffers first, so loading a large snapshot peaks at
+    /// roughly the size of the parsed graphs. Mutations fail with a conflict.
+    pub fn open_read_only<P: AsRef<Path>>(path: P, mmap: bool) -> Result<Self, EpcisKgError> {
+        let path = path.as_ref();
+        if !path.join("store_metadata.json").exists() {
+            return Err(EpcisKgError::NotFound {
+                resource: "Dataset".to_string(),
+                id: path.display().to_string(),
+            });
+        }
+        let keyring = encryption::installed();
+        let (graphs, persisted) = Self::load_graphs(path, mmap, keyring.as_deref())?;
+        info!("Opened {} graphs at {} read-only (mmap: {})", graphs.len(), path.display(), mmap);
+        
+        let store = Self {
+            graphs,
+            storage_path: path.to_string_lossy().to_string(),
+            change_sinks: Vec::new(),
+            read_only: true,
+            keyring,
+            version: 0,
+            metrics: Arc::new(StoreMetrics::default()),
+            persisted,
+            transaction: None,
+        };
+        store.refresh_size_metrics();
+        Ok(store)
+    }
+    
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+    
+    /// Refuse mutations of a read-only store; every other mutation starts a new dataset version
+    fn ensure_writable(&mut self) -> Result<(), EpcisKgError> {
+        if self.read_only {
+            return Err(EpcisKgError::Conflict(format!("the store at {} is open read-only", self.storage_path)));
+        }
+        self.version = self.version.wrapping_add(1);
+        Ok(())
+    }
+    
+    /// Dataset version, for cache validators; changes whenever the store may have changed
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+    
+    /// Metrics this store records, for `SystemMonitor::with_store_metrics`
+    pub fn metrics(&self) -> Arc<StoreMetrics> {
+        Arc::clone(&self.metrics)
+    }
+    
+    /// Record the current triple and graph counts and the size of the graph files
+    pub fn refresh_size_metrics(&self) {
+        let total_triples: usize = self.graphs.values().map(|graph| graph.len()).sum();
+        let storage_bytes = if self.storage_path == ":memory:" {
+            0
+        } else {
+            std::fs::read_dir(&self.storage_path)
+                .map(|entries| entries.filter_map(|entry| entry.ok()?.metadata().ok()).map(|metadata| metadata.len()).sum())
+                .unwrap_or(0)
+        };
+        self.metrics.record_size(total_triples as u64, self.graphs.len() as u32, storage_bytes);
     }
     
     /// Create a new in-memory Oxigraph store (for testing)
@@ -32,17 +240,186 @@ impl OxigraphStore {
         Ok(Self {
             graphs,
             storage_path: ":memory:".to_string(),
+            change_sinks: Vec::new(),
+            read_only: false,
+            keyring: None,
+            version: 0,
+            metrics: Arc::new(StoreMetrics::default()),
+            persisted: HashMap::new(),
+            transaction: None,
         })
     }
     
-    /// Store ontology data from OntologyData struct
+    /// Publish stored events and new derived facts to this sink
+    pub fn add_change_sink(&mut self, sink: Arc<dyn ChangeSink>) {
+        self.change_sinks.push(sink);
+    }
+    
+    fn publish(&mut self, change: Change) {
+        if let Some(transaction) = &mut self.transaction {
+            transaction.changes.push(change);
+            return;
+        }
+        for sink in &self.change_sinks {
+            sink.publish(&change);
+        }
+    }
+    
+    /// Start a transaction. Until `commit`, writes stay in memory and their
+    /// changes are held back from the changefeed; `rollback` restores the
+    /// graphs as they are now. Nothing is copied up front: each graph is kept
+    /// as it was just before the transaction first writes it.
+    pub fn begin(&mut self) -> Result<(), EpcisKgError> {
+        if self.transaction.is_some() {
+            return Err(EpcisKgError::Conflict("a transaction is already open on this store".to_string()));
+        }
+        self.ensure_writable()?;
+        self.transaction = Some(Transaction { graphs: HashMap::new(), changes: Vec::new() });
+        Ok(())
+    }
+    
+    /// Save the transaction's writes in one go, then publish its changes.
+    ///
+    /// If the save fails, the transaction is rolled back and the graphs saved
+    /// before the failure are written back as they were.
+    pub fn commit(&mut self) -> Result<(), EpcisKgError> {
+        let transaction = self.transaction.take().ok_or_else(no_transaction)?;
+        if self.storage_path != ":memory:" {
+            if let Err(e) = self.save_graphs() {
+                self.restore_graphs(transaction.graphs);
+                if let Err(restore) = self.save_graphs() {
+                    warn!("Failed to restore {} after a failed commit: {}", self.storage_path, restore);
+                }
+                return Err(e);
+            }
+        }
+        for change in transaction.changes {
+            self.publish(change);
+        }
+        Ok(())
+    }
+    
+    /// Discard the transaction's writes and the changes they would have published
+    pub fn rollback(&mut self) -> Result<(), EpcisKgError> {
+        let transaction = self.transaction.take().ok_or_else(no_transaction)?;
+        self.restore_graphs(transaction.graphs);
+        self.version = self.version.wrapping_add(1);
+        Ok(())
+    }
+    
+    /// Put back the graphs a transaction wrote, as they were before it
+    fn restore_graphs(&mut self, originals: HashMap<String, Option<OxrdfGraph>>) {
+        for (graph_name, original) in originals {
+            match original {
+                Some(graph) => self.graphs.insert(graph_name, graph),
+                None => self.graphs.remove(&graph_name),
+            };
+        }
+    }
+    
+    /// In a transaction, keep the graph as it was before its first write
+    fn touch(&mut self, graph_name: &str) {
+        if let Some(transaction) = &mut self.transaction {
+            if !transaction.graphs.contains_key(graph_name) {
+                transaction.graphs.insert(graph_name.to_string(), self.graphs.get(graph_name).cloned());
+            }
+        }
+    }
+    
+    // Every write to `graphs` goes through these, so transactions can roll it back
+    
+    fn graph_entry(&mut self, graph_name: &str) -> &mut OxrdfGraph {
+        self.touch(graph_name);
+        self.graphs.entry(graph_name.to_string()).or_default()
+    }
+    
+    fn graph_mut(&mut self, graph_name: &str) -> Option<&mut OxrdfGraph> {
+        self.touch(graph_name);
+        self.graphs.get_mut(graph_name)
+    }
+    
+    fn put_graph(&mut self, graph_name: String, graph: OxrdfGraph) {
+        self.touch(&graph_name);
+        self.graphs.insert(graph_name, graph);
+    }
+    
+    fn take_graph(&mut self, graph_name: &str) -> Option<OxrdfGraph> {
+        self.touch(graph_name);
+        self.graphs.remove(graph_name)
+    }
+    
+    /// Remove every graph, returning their names
+    fn take_all_graphs(&mut self) -> Vec<String> {
+        let graph_names: Vec<String> = self.graphs.keys().cloned().collect();
+        for graph_name in &graph_names {
+            self.take_graph(graph_name);
+        }
+        graph_names
+    }
+    
+    pub fn in_transaction(&self) -> bool {
+        self.transaction.is_some()
+    }
+    
+    /// Run `write` in a transaction: commit what it wrote if it succeeds, roll all of it back if it fails
+    pub fn transaction<T>(&mut self, write: impl FnOnce(&mut Self) -> Result<T, EpcisKgError>) -> Result<T, EpcisKgError> {
+        self.begin()?;
+        match write(self) {
+            Ok(value) => {
+                self.commit()?;
+                Ok(value)
+            }
+            Err(e) => {
+                self.rollback()?;
+                Err(e)
+            }
+        }
+    }
+    
+    /// Store ontology data from OntologyData struct, replacing an earlier load of the same file
     pub fn store_ontology_data(&mut self, ontology_data: &OntologyData) -> Result<(), EpcisKgError> {
-        // Create a named graph for this ontology
-        let graph_name = format!("urn:epcis:ontology:{}", 
-                                ontology_data.source_file.replace("/", ":").replace("\\", ":"));
+        self.store_ontology_data_as(ontology_data, None, LoadMode::Replace)?;
+        Ok(())
+    }
+    
+    /// Store ontology data into `graph_name` (or the graph derived from its source file)
+    ///
+    /// Returns the name of the graph written.
+    pub fn store_ontology_data_as(
+        &mut self,
+        ontology_data: &OntologyData,
+        graph_name: Option<&str>,
+        mode: LoadMode,
+    ) -> Result<String, EpcisKgError> {
+        self.ensure_writable()?;
+        let graph_name = match graph_name {
+            Some(name) => oxrdf::NamedNode::new(name)?.into_string(),
+            None => ontology_graph_name(&ontology_data.source_file),
+        };
+        // Graphs stored before names were hashed are named by the slash-replaced path
+        let legacy_name = format!("urn:epcis:ontology:{}", ontology_data.source_file.replace(['/', '\\'], ":"));
+        
+        let existing = self.graphs.get(&graph_name).map_or(0, |graph| graph.len());
+        let mut graph = match mode {
+            LoadMode::Fail if existing > 0 || (graph_name != legacy_name && self.graphs.contains_key(&legacy_name)) => {
+                return Err(EpcisKgError::Storage(format!(
+                    "{} is already loaded in {}; load with replace or merge",
+                    ontology_data.source_file, graph_name
+                )));
+            }
+            LoadMode::Merge => self.take_graph(&graph_name).unwrap_or_default(),
+            LoadMode::Replace | LoadMode::Fail => {
+                if existing > 0 {
+                    warn!("Replacing {} existing triples in {}", existing, graph_name);
+                }
+                if graph_name != legacy_name && self.take_graph(&legacy_name).is_some() {
+                    info!("Removed {} loaded under its previous graph name {}", ontology_data.source_file, legacy_name);
+                }
+                OxrdfGraph::default()
+            }
+        };
         
         // Convert the ontology graph to our internal format
-        let mut graph = OxrdfGraph::default();
         let mut triple_count = 0;
         
         println!("🔍 DEBUG: Storing {} triples from {}", ontology_data.triples_count, ontology_data.source_file);
@@ -61,136 +438,94 @@ impl OxigraphStore {
         println!("🔍 DEBUG: Total triples stored: {}", triple_count);
         
         // Store the graph
-        self.graphs.insert(graph_name, graph);
+        self.put_graph(graph_name.clone(), graph);
+        self.record_graph_load(&graph_name, &ontology_data.source_file)?;
         
         // Save to persistent storage
-        self.save_graphs()?;
+        if self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
         
+        Ok(graph_name)
+    }
+    
+    /// Note where a graph was loaded from and when, replacing any earlier record
+    fn record_graph_load(&mut self, graph_name: &str, source: &str) -> Result<(), EpcisKgError> {
+        let epcis = |local: &str| oxrdf::NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+        let subject = oxrdf::NamedNode::new(graph_name)?;
+        let loads = self.graph_entry(GRAPH_LOADS_GRAPH);
+        let stale: Vec<oxrdf::Triple> = loads.triples_for_subject(subject.as_ref()).map(|triple| triple.into_owned()).collect();
+        for triple in &stale {
+            loads.remove(triple);
+        }
+        loads.insert(&oxrdf::Triple::new(subject.clone(), epcis("loadedFrom"), oxrdf::Literal::new_simple_literal(source)));
+        loads.insert(&oxrdf::Triple::new(
+            subject,
+            epcis("loadedAt"),
+            oxrdf::Literal::new_typed_literal(
+                chrono::Utc::now().to_rfc3339(),
+                oxrdf::NamedNode::new_unchecked("http://www.w3.org/2001/XMLSchema#dateTime"),
+            ),
+        ));
         Ok(())
     }
     
-    /// Store ontology data from Turtle format string
+    /// Store ontology data from a Turtle, N-Triples or RDF-star string
     pub fn store_ontology_turtle(&mut self, turtle_data: &str, graph_name: &str) -> Result<(), EpcisKgError> {
-        let mut graph = OxrdfGraph::default();
-        
-        // Parse prefixes from Turtle data
-        let mut prefixes = std::collections::HashMap::new();
-        let mut triple_count = 0;
-        
-        // Simple Turtle parser - extract real triples
-        for line in turtle_data.lines() {
-            let trimmed = line.trim();
-            
-            // Skip empty lines and comments
-            if trimmed.is_empty() || trimmed.starts_with('#') {
-                continue;
-            }
-            
-            // Parse prefix declarations
-            if trimmed.starts_with("@prefix") {
-                if let Some(prefix_part) = trimmed.strip_prefix("@prefix") {
-                    let parts: Vec<&str> = prefix_part.split(':').collect();
-                    if parts.len() >= 2 {
-                        let prefix_name = parts[0].trim();
-                        let uri_part = parts[1].trim();
-                        if let Some(uri) = uri_part.strip_suffix('.').and_then(|s| s.strip_prefix('<')).and_then(|s| s.strip_suffix('>')) {
-                            prefixes.insert(prefix_name, uri.to_string());
-                        }
-                    }
-                }
-                continue;
-            }
-            
-            // Parse triples (simplified Turtle parsing)
-            if trimmed.contains(' ') && !trimmed.starts_with('@') {
-                let parts: Vec<&str> = trimmed.split_whitespace().collect();
-                if parts.len() >= 3 {
-                    let subject_str = parts[0];
-                    let predicate_str = parts[1];
-                    let mut object_str = parts[2];
-                    
-                    // Remove trailing dot from object if present
-                    if object_str.ends_with('.') {
-                        object_str = &object_str[0..object_str.len()-1];
-                    }
-                    
-                    // Convert subject
-                    let subject = if subject_str.starts_with('<') && subject_str.ends_with('>') {
-                        let uri = &subject_str[1..subject_str.len()-1];
-                        oxrdf::NamedNode::new_unchecked(uri)
-                    } else if subject_str.contains(':') {
-                        // Handle prefixed names
-                        let mut expanded = subject_str.to_string();
-                        for (prefix, uri) in &prefixes {
-                            if subject_str.starts_with(&format!("{}:", prefix)) {
-                                expanded = subject_str.replace(&format!("{}:", prefix), uri);
-                                break;
-                            }
-                        }
-                        oxrdf::NamedNode::new_unchecked(expanded)
-                    } else {
-                        continue; // Skip invalid subjects
-                    };
-                    
-                    // Convert predicate
-                    let predicate = if predicate_str.starts_with('<') && predicate_str.ends_with('>') {
-                        let uri = &predicate_str[1..predicate_str.len()-1];
-                        oxrdf::NamedNode::new_unchecked(uri)
-                    } else if predicate_str.contains(':') {
-                        let mut expanded = predicate_str.to_string();
-                        for (prefix, uri) in &prefixes {
-                            if predicate_str.starts_with(&format!("{}:", prefix)) {
-                                expanded = predicate_str.replace(&format!("{}:", prefix), uri);
-                                break;
-                            }
-                        }
-                        oxrdf::NamedNode::new_unchecked(expanded)
-                    } else {
-                        continue; // Skip invalid predicates
-                    };
-                    
-                    // Convert object
-                    let object = if object_str.starts_with('<') && object_str.ends_with('>') {
-                        let uri = &object_str[1..object_str.len()-1];
-                        oxrdf::Term::NamedNode(oxrdf::NamedNode::new_unchecked(uri))
-                    } else if object_str.starts_with('"') && object_str.ends_with('"') {
-                        // Literal
-                        let literal_content = &object_str[1..object_str.len()-1];
-                        oxrdf::Term::Literal(oxrdf::Literal::new_simple_literal(literal_content))
-                    } else if object_str.contains(':') {
-                        // Prefixed name or URI
-                        let mut expanded = object_str.to_string();
-                        for (prefix, uri) in &prefixes {
-                            if object_str.starts_with(&format!("{}:", prefix)) {
-                                expanded = object_str.replace(&format!("{}:", prefix), uri);
-                                break;
-                            }
-                        }
-                        oxrdf::Term::NamedNode(oxrdf::NamedNode::new_unchecked(expanded))
-                    } else {
-                        continue; // Skip invalid objects
-                    };
-                    
-                    // Create and store the triple
-                    let triple = oxrdf::Triple::new(subject, predicate, object);
-                    graph.insert(triple.as_ref());
-                    triple_count += 1;
-                }
-            }
-        }
-        
-        println!("✓ Parsed and stored {} real triples from Turtle data for graph: {}", triple_count, graph_name);
+        self.ensure_writable()?;
+        let graph = Self::parse_turtle_to_graph(turtle_data)?;
+        println!("✓ Parsed and stored {} real triples from Turtle data for graph: {}", graph.len(), graph_name);
         
         // Store the graph
-        self.graphs.insert(graph_name.to_string(), graph);
+        self.put_graph(graph_name.to_string(), graph);
         
         Ok(())
     }
     
     /// Execute SPARQL SELECT query and return results as JSON
     pub fn query_select(&self, sparql_query: &str) -> Result<String, EpcisKgError> {
+        self.timed(|| self.evaluate_select(sparql_query))
+    }
+    
+    /// Plan of a SELECT query: what it asks for and how much of the dataset it scans
+    pub fn explain(&self, sparql_query: &str) -> Result<QueryPlan, EpcisKgError> {
+        let dataset_graphs = Self::parse_dataset_clauses(sparql_query);
+        let scanned: Vec<&OxrdfGraph> = self
+            .graphs
+            .iter()
+            .filter(|(name, _)| dataset_graphs.is_empty() || dataset_graphs.contains(name))
+            .map(|(_, graph)| graph)
+            .collect();
+        Ok(QueryPlan {
+            variables: self.get_query_variables(sparql_query)?,
+            limit: self.parse_limit_clause(sparql_query)?,
+            values_blocks: self.parse_values_clauses(sparql_query)?.len(),
+            graphs_scanned: scanned.len(),
+            triples_scanned: scanned.iter().map(|graph| graph.len()).sum(),
+            dataset_graphs,
+            strategy: "full scan".to_string(),
+        })
+    }
+    
+    /// Run a query, recording how long it took
+    fn timed<T>(&self, query: impl FnOnce() -> Result<T, EpcisKgError>) -> Result<T, EpcisKgError> {
+        let started = std::time::Instant::now();
+        let result = query();
+        self.metrics.record_query(started.elapsed());
+        result
+    }
+    
+    fn evaluate_select(&self, sparql_query: &str) -> Result<String, EpcisKgError> {
+        self.evaluate_select_over(self.graphs.iter().collect(), sparql_query)
+    }
+    
+    /// Evaluate a SELECT query over `graphs`, which need not be this store's own
+    fn evaluate_select_over(&self, graphs: Vec<(&String, &OxrdfGraph)>, sparql_query: &str) -> Result<String, EpcisKgError> {
+        if let Some(Query::Select { pattern, dataset, .. }) = Self::graph_query(sparql_query) {
+            return Self::evaluate_graph_select(graphs, &pattern, dataset.as_ref());
+        }
         println!("🔍 DEBUG: Executing SPARQL query: {}", sparql_query);
-        println!("🔍 DEBUG: Available graphs: {}", self.graphs.len());
+        println!("🔍 DEBUG: Available graphs: {}", graphs.len());
         
         // For now, implement a very basic SELECT query handler
         // This is a simplified implementation that handles basic patterns
@@ -203,13 +538,25 @@ impl OxigraphStore {
             // Parse LIMIT clause if present
             let limit = self.parse_limit_clause(sparql_query)?;
             println!("🔍 DEBUG: Query LIMIT: {}", limit);
+
+            // Parse inline VALUES data blocks (e.g. a list of EPCs bound to ?epc)
+            let values_clauses = self.parse_values_clauses(sparql_query)?;
+            if !values_clauses.is_empty() {
+                debug!("Query VALUES blocks: {}", values_clauses.len());
+            }
+
+            // Restrict the dataset to graphs named in FROM / FROM NAMED clauses
+            let dataset_graphs = Self::parse_dataset_clauses(sparql_query);
             
             // For demonstration, return some basic results
             let mut json_results = Vec::new();
             let mut total_triples = 0;
             
             // Collect all triples from all graphs
-            for (graph_name, graph) in &self.graphs {
+            for (graph_name, graph) in graphs {
+                if !dataset_graphs.is_empty() && !dataset_graphs.contains(graph_name) {
+                    continue;
+                }
                 println!("🔍 DEBUG: Graph '{}' has {} triples", graph_name, graph.len());
                 for triple in graph.iter() {
                     total_triples += 1;
@@ -258,10 +605,14 @@ impl OxigraphStore {
                         for var in &variables {
                             match var.as_str() {
                                 "s" | "subject" => {
-                                    solution_map.insert(var.clone(), serde_json::json!({
-                                        "type": "uri",
-                                        "value": format!("{}", triple.subject)
-                                    }));
+                                    let json_value = match triple.subject {
+                                        oxrdf::SubjectRef::Triple(quoted) => quoted_triple_json(quoted),
+                                        _ => serde_json::json!({
+                                            "type": "uri",
+                                            "value": format!("{}", triple.subject)
+                                        }),
+                                    };
+                                    solution_map.insert(var.clone(), json_value);
                                 },
                                 "p" | "predicate" => {
                                     solution_map.insert(var.clone(), serde_json::json!({
@@ -270,10 +621,13 @@ impl OxigraphStore {
                                     }));
                                 },
                                 "o" | "object" => {
-                                    let json_value = serde_json::json!({
-                                        "type": "literal",
-                                        "value": format!("{}", triple.object)
-                                    });
+                                    let json_value = match triple.object {
+                                        oxrdf::TermRef::Triple(quoted) => quoted_triple_json(quoted),
+                                        _ => serde_json::json!({
+                                            "type": "literal",
+                                            "value": format!("{}", triple.object)
+                                        }),
+                                    };
                                     solution_map.insert(var.clone(), json_value);
                                 },
                                 "g" | "graph" => {
@@ -331,7 +685,12 @@ impl OxigraphStore {
                         }
                     }
                     
-                    if !solution_map.is_empty() {
+                    // Every VALUES block must accept the solution (blocks are joined)
+                    let values_match = values_clauses.iter().all(|clause| {
+                        clause.accepts(&solution_map, &triple.subject.to_string(), &triple.object.to_string())
+                    });
+
+                    if !solution_map.is_empty() && values_match {
                         json_results.push(solution_map);
                     }
                     
@@ -365,373 +724,3763 @@ impl OxigraphStore {
     
     /// Execute SPARQL ASK query and return boolean result
     pub fn query_ask(&self, sparql_query: &str) -> Result<bool, EpcisKgError> {
-        // Simplified ASK query implementation
-        if sparql_query.contains("ASK") && sparql_query.contains("WHERE") {
-            // For demonstration, return true if we have any data
-            Ok(!self.graphs.is_empty())
-        } else {
-            Err(EpcisKgError::Query("Unsupported SPARQL ASK query".to_string()))
-        }
+        self.timed(|| {
+            if let Some(Query::Ask { pattern, dataset, .. }) = Self::graph_query(sparql_query) {
+                return Ok(!sparql_update::query_solutions(&self.graphs, &pattern, dataset.as_ref())?.is_empty());
+            }
+            // Simplified ASK query implementation
+            if sparql_query.contains("ASK") && sparql_query.contains("WHERE") {
+                // For demonstration, return true if we have any data
+                Ok(!self.graphs.is_empty())
+            } else {
+                Err(EpcisKgError::Query("Unsupported SPARQL ASK query".to_string()))
+            }
+        })
     }
     
     /// Execute SPARQL CONSTRUCT query and return Turtle format
     pub fn query_construct(&self, sparql_query: &str) -> Result<String, EpcisKgError> {
-        // Simplified CONSTRUCT query implementation
-        if sparql_query.contains("CONSTRUCT") {
-            // For demonstration, return all triples as Turtle
-            self.export_turtle()
-        } else {
-            Err(EpcisKgError::Query("Unsupported SPARQL CONSTRUCT query".to_string()))
-        }
+        self.timed(|| {
+            if let Some(Query::Construct { template, pattern, dataset, .. }) = Self::graph_query(sparql_query) {
+                let mut constructed = OxrdfGraph::default();
+                for solution in sparql_update::query_solutions(&self.graphs, &pattern, dataset.as_ref())? {
+                    for triple in sparql_update::construct_triples(&template, &solution) {
+                        constructed.insert(&triple);
+                    }
+                }
+                return Ok(constructed.iter().map(|triple| format!("{} .\n", format_triple_star(triple))).collect());
+            }
+            // Simplified CONSTRUCT query implementation
+            if sparql_query.contains("CONSTRUCT") {
+                // For demonstration, return all triples as Turtle
+                self.export_turtle()
+            } else {
+                Err(EpcisKgError::Query("Unsupported SPARQL CONSTRUCT query".to_string()))
+            }
+        })
     }
     
-    /// Execute SPARQL update operation (simplified implementation)
-    pub fn update(&mut self, sparql_update: &str) -> Result<(), EpcisKgError> {
-        // For now, we'll implement a simplified version that handles basic INSERT DATA operations
-        if sparql_update.contains("INSERT DATA") {
-            // This is a very basic implementation - in production you'd want to use a proper SPARQL update parser
-            return Err(EpcisKgError::Query("SPARQL UPDATE operations not fully implemented yet".to_string()));
-        }
-        
-        Err(EpcisKgError::Query("Unsupported SPARQL update operation".to_string()))
+    /// A query addressing named graphs through `GRAPH`, `FROM` or `FROM NAMED`.
+    ///
+    /// These are evaluated over the quads of each graph; other queries keep the
+    /// simplified evaluation above.
+    fn graph_query(sparql_query: &str) -> Option<Query> {
+        let query = SparqlParser::new().parse_query(sparql_query).ok()?;
+        let addresses_graphs = match &query {
+            Query::Select { pattern, dataset, .. } | Query::Ask { pattern, dataset, .. } | Query::Construct { pattern, dataset, .. } => {
+                sparql_update::addresses_graphs(pattern, dataset.as_ref())
+            }
+            Query::Describe { .. } => false,
+        };
+        addresses_graphs.then_some(query)
     }
-    
-    /// Get store statistics
-    pub fn get_statistics(&self) -> Result<OxigraphStats, EpcisKgError> {
-        let total_quads: usize = self.graphs.values().map(|graph| graph.len()).sum();
-        let named_graphs = self.graphs.len();
-        let default_graph_quads = 0; // We don't store default graph quads in this implementation
-        
-        Ok(OxigraphStats {
-            total_quads,
-            named_graphs,
-            default_graph_quads,
-            storage_path: self.storage_path.clone(),
-        })
+
+    fn evaluate_graph_select(
+        graphs: Vec<(&String, &OxrdfGraph)>,
+        pattern: &spargebra::algebra::GraphPattern,
+        dataset: Option<&spargebra::algebra::QueryDataset>,
+    ) -> Result<String, EpcisKgError> {
+        let bindings: Vec<serde_json::Value> = sparql_update::query_solutions(graphs, pattern, dataset)?
+            .iter()
+            .map(|solution| {
+                let binding: serde_json::Map<String, serde_json::Value> = solution.iter().map(|(name, value)| (name.clone(), term_json(value))).collect();
+                serde_json::Value::Object(binding)
+            })
+            .collect();
+        let result = serde_json::json!({
+            "head": { "vars": sparql_update::projected_variables(pattern) },
+            "results": { "bindings": bindings }
+        });
+        serde_json::to_string_pretty(&result).map_err(|e| EpcisKgError::Query(format!("Failed to serialize JSON: {}", e)))
     }
     
-    /// Clear all data from the store
-    pub fn clear(&mut self) -> Result<(), EpcisKgError> {
-        self.graphs.clear();
-        Ok(())
+    /// Apply a SPARQL update: `INSERT DATA` and `DELETE DATA`, `DELETE`/`INSERT`
+    /// `WHERE` (with `WITH`, `USING` and `GRAPH` targeting), `CLEAR` and `DROP`.
+    ///
+    /// Operations apply in order, each seeing the effects of the ones before.
+    /// The update is checked whole before any of it applies, and is saved once.
+    /// Deleting from the default graph deletes from every graph; inserts must
+    /// name their graph.
+    pub fn update(&mut self, sparql_update: &str) -> Result<UpdateReport, EpcisKgError> {
+        self.update_reading(sparql_update, |_| true)
     }
     
-    /// Store event triples in a named graph (async version)
-    pub async fn store_event_triples(&mut self, event_id: &str, triples: &[oxrdf::Triple]) -> Result<(), EpcisKgError> {
-        // Create a named graph for this event
-        let graph_name = format!("urn:epcis:event:{}", event_id);
-        
-        // Create or get the graph
-        let mut graph = OxrdfGraph::default();
-        
-        // Add all triples to the graph
-        for triple in triples {
-            graph.insert(triple.as_ref());
+    /// Apply a SPARQL update whose `WHERE` clauses only see the graphs `readable` accepts
+    pub fn update_reading(&mut self, sparql_update: &str, readable: impl Fn(&str) -> bool) -> Result<UpdateReport, EpcisKgError> {
+        let update = SparqlParser::new()
+            .parse_update(sparql_update)
+            .map_err(|e| EpcisKgError::Query(format!("Invalid SPARQL update: {}", e)))?;
+        for operation in &update.operations {
+            match operation {
+                GraphUpdateOperation::InsertData { data } => {
+                    if data.iter().any(|quad| quad.graph_name == UpdateGraphName::DefaultGraph) {
+                        return Err(EpcisKgError::Query("INSERT DATA must name the graph of its triples".to_string()));
+                    }
+                }
+                GraphUpdateOperation::DeleteInsert { insert, pattern, .. } => {
+                    if insert.iter().any(|quad| quad.graph_name == GraphNamePattern::DefaultGraph) {
+                        return Err(EpcisKgError::Query("INSERT templates must name the graph of their triples".to_string()));
+                    }
+                    sparql_update::check_pattern(pattern)?;
+                }
+                GraphUpdateOperation::DeleteData { .. } | GraphUpdateOperation::Clear { .. } | GraphUpdateOperation::Drop { .. } => {}
+                other => {
+                    return Err(EpcisKgError::NotImplemented(format!(
+                        "SPARQL updates support INSERT DATA, DELETE DATA, DELETE/INSERT WHERE, CLEAR and DROP only, not: {}",
+                        other
+                    )))
+                }
+            }
         }
+        self.ensure_writable()?;
         
-        // Store the graph
-        self.graphs.insert(graph_name, graph);
-        
-        // Save to persistent storage if not in-memory
-        if self.storage_path != ":memory:" {
-            self.save_graphs()?;
+        let mut writes = GraphWrites::default();
+        for operation in update.operations {
+            let (deletions, insertions): (Vec<(Option<String>, oxrdf::Triple)>, Vec<(String, oxrdf::Triple)>) = match operation {
+                GraphUpdateOperation::InsertData { data } => {
+                    let insertions = data
+                        .into_iter()
+                        .filter_map(|quad| match quad.graph_name {
+                            UpdateGraphName::NamedNode(graph) => Some((graph.into_string(), oxrdf::Triple::new(quad.subject, quad.predicate, quad.object))),
+                            UpdateGraphName::DefaultGraph => None,
+                        })
+                        .collect();
+                    (Vec::new(), insertions)
+                }
+                GraphUpdateOperation::DeleteData { data } => {
+                    let deletions = data
+                        .into_iter()
+                        .map(|quad| {
+                            let graph = match quad.graph_name {
+                                UpdateGraphName::NamedNode(graph) => Some(graph.into_string()),
+                                UpdateGraphName::DefaultGraph => None,
+                            };
+                            (graph, sparql_update::ground_triple(quad.subject, quad.predicate, quad.object))
+                        })
+                        .collect();
+                    (deletions, Vec::new())
+                }
+                // Templates are filled in from the matches before the graphs change
+                GraphUpdateOperation::DeleteInsert { delete, insert, using, pattern } => {
+                    let solutions = sparql_update::solutions(self.graphs.iter().filter(|(name, _)| readable(name.as_str())), &pattern, using.as_ref());
+                    let deletions = solutions.iter().flat_map(|solution| sparql_update::delete_triples(&delete, solution)).collect();
+                    let insertions = solutions.iter().flat_map(|solution| sparql_update::insert_triples(&insert, solution)).collect();
+                    (deletions, insertions)
+                }
+                GraphUpdateOperation::Clear { graph, .. } | GraphUpdateOperation::Drop { graph, .. } => {
+                    match graph {
+                        GraphTarget::NamedNode(node) => {
+                            if self.take_graph(node.as_str()).is_some() {
+                                writes.changed.insert(node.into_string());
+                            }
+                        }
+                        // Every triple lives in a named graph
+                        GraphTarget::DefaultGraph => {}
+                        GraphTarget::NamedGraphs | GraphTarget::AllGraphs => writes.changed.extend(self.take_all_graphs()),
+                    }
+                    continue;
+                }
+                // Rejected before anything applied
+                _ => continue,
+            };
+            self.write_quads(deletions, insertions, &mut writes);
         }
-        
-        Ok(())
+        self.finish_writes(writes)
     }
     
-    /// Export all data as Turtle format
-    pub fn export_turtle(&self) -> Result<String, EpcisKgError> {
-        let mut turtle_output = String::new();
-        
-        for (graph_name, graph) in &self.graphs {
-            turtle_output.push_str(&format!("# Graph: {}\n", graph_name));
-            for triple in graph.iter() {
-                let s = format!("{}", triple.subject);
-                let p = format!("{}", triple.predicate);
-                let o = format!("{}", triple.object);
-                turtle_output.push_str(&format!("{} {} {} .\n", s, p, o));
-            }
-            turtle_output.push('\n');
+    /// Insert quads into their named graphs, keeping the triples already there
+    pub fn insert_quads(&mut self, quads: &[oxrdf::Quad]) -> Result<usize, EpcisKgError> {
+        let mut insertions = Vec::with_capacity(quads.len());
+        for quad in quads {
+            let oxrdf::GraphName::NamedNode(graph_name) = &quad.graph_name else {
+                return Err(EpcisKgError::InvalidField {
+                    field: "graph".to_string(),
+                    message: format!("{} must name its graph", quad),
+                });
+            };
+            insertions.push((graph_name.as_str().to_string(), oxrdf::Triple::from(quad.clone())));
         }
-        
-        Ok(turtle_output)
+        self.ensure_writable()?;
+        let mut writes = GraphWrites::default();
+        self.write_quads(Vec::new(), insertions, &mut writes);
+        Ok(self.finish_writes(writes)?.inserted)
     }
     
-    /// Get query variables from SPARQL query string (simplified parsing)
-    fn get_query_variables(&self, query: &str) -> Result<Vec<String>, EpcisKgError> {
-        // Extract variables from the SELECT clause more accurately
-        let query_upper = query.to_uppercase();
-        let select_start = query_upper.find("SELECT").ok_or_else(|| {
-            EpcisKgError::Query("No SELECT clause found in query".to_string())
-        })?;
-        
-        // Find the WHERE clause or end of SELECT variables
-        let where_pos = query_upper.find("WHERE").unwrap_or(query.len());
-        let select_clause = &query[select_start + 6..where_pos].trim();
-        
-        // Parse variables from SELECT clause
-        let mut vars = Vec::new();
-        let mut in_distinct = false;
-        
-        for token in select_clause.split_whitespace() {
-            let token_upper = token.to_uppercase();
-            
-            if token_upper == "DISTINCT" {
-                in_distinct = true;
-                continue;
-            }
-            
-            if token_upper == "REDUCED" || token_upper == "*" {
-                // Handle REDUCED or wildcard - return all common variables
-                return Ok(vec!["s".to_string(), "p".to_string(), "o".to_string()]);
+    /// Remove quads; a quad in the default graph is removed from every graph
+    pub fn remove_quads(&mut self, quads: &[oxrdf::Quad]) -> Result<usize, EpcisKgError> {
+        let deletions = quads
+            .iter()
+            .map(|quad| {
+                let graph_name = match &quad.graph_name {
+                    oxrdf::GraphName::NamedNode(graph_name) => Some(graph_name.as_str().to_string()),
+                    _ => None,
+                };
+                (graph_name, oxrdf::Triple::from(quad.clone()))
+            })
+            .collect();
+        self.ensure_writable()?;
+        let mut writes = GraphWrites::default();
+        self.write_quads(deletions, Vec::new(), &mut writes);
+        Ok(self.finish_writes(writes)?.deleted)
+    }
+    
+    /// Remove triples from one named graph, dropping the graph once it is empty
+    pub fn remove_triples(&mut self, graph_name: &str, triples: &[oxrdf::Triple]) -> Result<usize, EpcisKgError> {
+        let deletions = triples.iter().map(|triple| (Some(graph_name.to_string()), triple.clone())).collect();
+        self.ensure_writable()?;
+        let mut writes = GraphWrites::default();
+        self.write_quads(deletions, Vec::new(), &mut writes);
+        Ok(self.finish_writes(writes)?.deleted)
+    }
+    
+    /// Remove a named graph and all its triples; false if there was no such graph
+    pub fn drop_graph(&mut self, graph_name: &str) -> Result<bool, EpcisKgError> {
+        self.ensure_writable()?;
+        let mut writes = GraphWrites::default();
+        if self.take_graph(graph_name).is_some() {
+            writes.changed.insert(graph_name.to_string());
+        }
+        let dropped = !writes.changed.is_empty();
+        self.finish_writes(writes)?;
+        Ok(dropped)
+    }
+    
+    /// Apply deletions, then insertions, recording what changed in `writes`.
+    /// A deletion without a graph deletes from every graph.
+    fn write_quads(&mut self, deletions: Vec<(Option<String>, oxrdf::Triple)>, insertions: Vec<(String, oxrdf::Triple)>, writes: &mut GraphWrites) {
+        for (graph_name, triple) in deletions {
+            let targets: Vec<String> = match graph_name {
+                Some(graph_name) => vec![graph_name],
+                None => self.graphs.keys().cloned().collect(),
+            };
+            for graph_name in targets {
+                // Only graphs holding the triple are written, and so snapshotted in a transaction
+                if !self.graphs.get(&graph_name).is_some_and(|graph| graph.contains(triple.as_ref())) {
+                    continue;
+                }
+                let Some(graph) = self.graph_mut(&graph_name) else { continue };
+                graph.remove(triple.as_ref());
+                writes.report.deleted += 1;
+                if graph.is_empty() {
+                    self.take_graph(&graph_name);
+                }
+                writes.changed.insert(graph_name);
             }
-            
-            if token.starts_with('?') {
-                let var_name = token[1..].to_string();
-                if !vars.contains(&var_name) {
-                    vars.push(var_name);
+        }
+        for (graph_name, triple) in insertions {
+            if self.graph_entry(&graph_name).insert(triple.as_ref()) {
+                writes.report.inserted += 1;
+                if is_derived_graph(&graph_name) {
+                    writes.inferred.entry(graph_name.clone()).or_default().push(triple);
                 }
+                writes.changed.insert(graph_name);
             }
         }
-        
-        // If no variables found in SELECT clause, default to s, p, o
-        if vars.is_empty() {
-            vars = vec!["s".to_string(), "p".to_string(), "o".to_string()];
+    }
+    
+    /// Publish new derived facts, refresh what reads the changed graphs and save them
+    fn finish_writes(&mut self, writes: GraphWrites) -> Result<UpdateReport, EpcisKgError> {
+        for (graph, triples) in writes.inferred {
+            self.publish(Change::Inferred { graph, triples });
         }
-        
-        Ok(vars)
+        if writes.changed.iter().any(|graph| graph.starts_with("urn:epcis:event:")) {
+            self.refresh_ownership_view();
+            self.refresh_expiry_view();
+        }
+        let changed: Vec<String> = writes.changed.into_iter().collect();
+        self.refresh_views_reading(&changed);
+        if self.storage_path != ":memory:" && !changed.is_empty() {
+            self.save_graphs()?;
+        }
+        Ok(writes.report)
     }
     
-    /// Parse LIMIT clause from SPARQL query string
-    fn parse_limit_clause(&self, query: &str) -> Result<usize, EpcisKgError> {
-        // Look for LIMIT clause in the query
-        let query_upper = query.to_uppercase();
-        if let Some(limit_pos) = query_upper.find("LIMIT") {
-            // Get the part after LIMIT
-            let after_limit = &query[limit_pos + 5..];
-            // Extract the number (simplified - just take the first sequence of digits)
-            let limit_str = after_limit.trim().split_whitespace().next().unwrap_or("0");
-            // Parse the number
-            limit_str.parse::<usize>().map_err(|_| {
-                EpcisKgError::Query(format!("Invalid LIMIT value: {}", limit_str))
-            })
-        } else {
-            // No LIMIT specified, return 0 (unlimited)
-            Ok(0)
+    /// Names of all graphs currently held in the store
+    pub fn graph_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.graphs.keys().cloned().collect();
+        names.sort();
+        names
+    }
+    
+    /// Triples held in a graph (0 if it does not exist)
+    pub fn graph_len(&self, graph_name: &str) -> usize {
+        self.graphs.get(graph_name).map_or(0, |graph| graph.len())
+    }
+    
+    /// Bytes a graph takes on disk, or would take once persisted for an in-memory store
+    pub fn graph_storage_bytes(&self, graph_name: &str) -> u64 {
+        let Some(graph) = self.graphs.get(graph_name) else { return 0 };
+        if self.storage_path != ":memory:" {
+            if let Ok(metadata) = std::fs::metadata(graph_file(Path::new(&self.storage_path), graph_name)) {
+                return metadata.len();
+            }
         }
+        graph.iter().map(|triple| format_triple_star(triple).len() as u64 + 3).sum()
     }
     
-    /// Load graphs from persistent storage
-    fn load_graphs(path: &Path) -> Result<HashMap<String, OxrdfGraph>, EpcisKgError> {
-        let metadata_path = path.join("store_metadata.json");
+    /// One page of the `visible` named graphs, with each graph's type, source,
+    /// version, size and load time; profile compliance is left to the caller
+    pub fn graph_catalog(&self, filter: &GraphFilter, visible: impl Fn(&str) -> bool) -> GraphPage {
+        let names: Vec<String> = self.graph_names().into_iter().filter(|name| name != GRAPH_LOADS_GRAPH && visible(name)).collect();
+        let (total, page) = GraphPage::page_names(names, filter);
         
-        if metadata_path.exists() {
-            // Load existing data
-            let metadata_content = std::fs::read_to_string(&metadata_path)?;
-            let metadata: StoreMetadata = serde_json::from_str(&metadata_content)?;
-            
-            let mut graphs = HashMap::new();
-            
-            for graph_name in &metadata.graphs {
-                let graph_path = path.join(format!("{}.ttl", graph_name.replace(":", "_")));
-                if graph_path.exists() {
-                    let turtle_content = std::fs::read_to_string(&graph_path)?;
-                    let graph = Self::parse_turtle_to_graph(&turtle_content)?;
-                    graphs.insert(graph_name.clone(), graph);
+        let literal = |graph: &OxrdfGraph, subject: Option<oxrdf::NamedNodeRef<'_>>, local: &str| -> Option<String> {
+            let predicate = format!("{}{}", EPCIS_NS, local);
+            let predicate = oxrdf::NamedNodeRef::new_unchecked(&predicate);
+            let object = match subject {
+                Some(subject) => graph.object_for_subject_predicate(subject, predicate),
+                None => graph.triples_for_predicate(predicate).next().map(|triple| triple.object),
+            };
+            match object? {
+                oxrdf::TermRef::Literal(literal) => Some(literal.value().to_string()),
+                _ => None,
+            }
+        };
+        let recorded = |name: &str, local: &str| {
+            let loads = self.graphs.get(GRAPH_LOADS_GRAPH)?;
+            literal(loads, Some(oxrdf::NamedNodeRef::new(name).ok()?), local)
+        };
+        
+        let graphs = page
+            .into_iter()
+            .map(|name| {
+                let kind = GraphKind::of(&name);
+                let graph = self.graphs.get(&name);
+                let (source, loaded_at) = match kind {
+                    GraphKind::Event => (Some("capture".to_string()), graph.and_then(|graph| literal(graph, None, "recordTime"))),
+                    GraphKind::Inferred => (Some("inference".to_string()), recorded(&name, "loadedAt")),
+                    _ => (recorded(&name, "loadedFrom"), recorded(&name, "loadedAt")),
+                };
+                GraphInfo {
+                    version: match (kind, graph) {
+                        (GraphKind::Ontology, Some(graph)) => Some(crate::ontology::runs::ontology_version(graph)),
+                        _ => None,
+                    },
+                    triples: graph.map_or(0, |graph| graph.len()),
+                    name,
+                    kind,
+                    source,
+                    loaded_at,
+                    profile: None,
                 }
+            })
+            .collect();
+        GraphPage::new(total, filter, graphs)
+    }
+    
+    /// Ontologies previously loaded into the store, one per ontology graph
+    pub fn stored_ontologies(&self) -> Vec<OntologyData> {
+        self.graph_names()
+            .into_iter()
+            .filter(|name| name.starts_with("urn:epcis:ontology:"))
+            .map(|name| self.ontology_data(std::slice::from_ref(&name), &name))
+            .collect()
+    }
+    
+    /// Rebuild reasoner input from store-resident named graphs, without re-reading files
+    pub fn ontology_data(&self, graph_names: &[String], source: &str) -> OntologyData {
+        let graph = self.union_graph(graph_names);
+        OntologyData {
+            triples_count: graph.len(),
+            graph,
+            source_file: source.to_string(),
+        }
+    }
+    
+    /// Collect the triples of every graph whose name starts with the given prefix
+    pub fn triples_in_graphs(&self, graph_prefix: &str) -> Vec<oxrdf::Triple> {
+        self.graphs
+            .iter()
+            .filter(|(name, _)| name.starts_with(graph_prefix))
+            .flat_map(|(_, graph)| graph.iter().map(|triple| triple.into_owned()))
+            .collect()
+    }
+    
+    /// Union of the given named graphs
+    pub fn union_graph(&self, graph_names: &[String]) -> OxrdfGraph {
+        let mut union = OxrdfGraph::default();
+        for graph in graph_names.iter().filter_map(|name| self.graphs.get(name)) {
+            for triple in graph.iter() {
+                union.insert(triple);
             }
-            
-            Ok(graphs)
-        } else {
-            // Return empty store
-            Ok(HashMap::new())
         }
+        union
     }
     
-    /// Save graphs to persistent storage
-    fn save_graphs(&self) -> Result<(), EpcisKgError> {
-        let path = Path::new(&self.storage_path);
-        std::fs::create_dir_all(path)?;
+    /// In-memory view holding only the given named graphs
+    pub fn scoped_view(&self, graph_names: &[String]) -> OxigraphStore {
+        let graphs = graph_names
+            .iter()
+            .filter_map(|name| self.graphs.get(name).map(|graph| (name.clone(), graph.clone())))
+            .collect();
         
-        // Save metadata
-        let metadata = StoreMetadata {
-            graphs: self.graphs.keys().cloned().collect(),
-            created_at: chrono::Utc::now().to_rfc3339(),
+        OxigraphStore {
+            graphs,
+            storage_path: ":memory:".to_string(),
+            change_sinks: Vec::new(),
+            read_only: false,
+            keyring: None,
+            version: 0,
+            metrics: Arc::clone(&self.metrics),
+            persisted: HashMap::new(),
+            transaction: None,
+        }
+    }
+    
+    /// Read-only in-memory copy of the whole store, keeping its dataset version
+    pub fn snapshot(&self) -> OxigraphStore {
+        OxigraphStore {
+            graphs: self.graphs.clone(),
+            storage_path: ":memory:".to_string(),
+            change_sinks: Vec::new(),
+            read_only: true,
+            keyring: None,
+            version: self.version,
+            metrics: Arc::clone(&self.metrics),
+            persisted: HashMap::new(),
+            transaction: None,
+        }
+    }
+    
+    /// In-memory view of the store with overlay graphs merged on top
+    pub fn with_overlay(&self, overlay: &HashMap<String, OxrdfGraph>) -> OxigraphStore {
+        let mut view = OxigraphStore {
+            graphs: self.graphs.clone(),
+            storage_path: ":memory:".to_string(),
+            change_sinks: Vec::new(),
+            read_only: false,
+            keyring: None,
+            version: 0,
+            metrics: Arc::clone(&self.metrics),
+            persisted: HashMap::new(),
+            transaction: None,
         };
         
-        let metadata_path = path.join("store_metadata.json");
-        std::fs::write(&metadata_path, serde_json::to_string_pretty(&metadata)?)?;
-        
-        // Save each graph
-        for (graph_name, graph) in &self.graphs {
-            let turtle_content = Self::graph_to_turtle(graph)?;
-            let graph_filename = format!("{}.ttl", graph_name.replace(":", "_"));
-            let graph_path = path.join(graph_filename);
-            std::fs::write(&graph_path, turtle_content)?;
+        for (name, graph) in overlay {
+            let target = view.graphs.entry(name.clone()).or_default();
+            for triple in graph.iter() {
+                target.insert(triple);
+            }
         }
         
-        Ok(())
+        view
     }
     
-    /// Parse Turtle content to Graph
-    fn parse_turtle_to_graph(turtle_content: &str) -> Result<OxrdfGraph, EpcisKgError> {
-        let mut graph = OxrdfGraph::default();
-        let mut triple_count = 0;
+    /// Read-only view of the store with overlay graphs merged on top.
+    ///
+    /// Unlike `with_overlay`, only the graphs the overlay names are copied;
+    /// every other graph is read from the store in place.
+    pub fn overlay_view(&self, overlay: &HashMap<String, OxrdfGraph>) -> OverlayView<'_> {
+        let merged = overlay
+            .iter()
+            .map(|(name, graph)| {
+                let mut target = self.graphs.get(name).cloned().unwrap_or_default();
+                for triple in graph.iter() {
+                    target.insert(triple);
+                }
+                (name.clone(), target)
+            })
+            .collect();
+        OverlayView { base: self, overlay: merged }
+    }
+    
+    /// Insert triples into a named graph, keeping any triples already in it
+    pub fn insert_triples(&mut self, graph_name: &str, triples: &[oxrdf::Triple]) -> Result<usize, EpcisKgError> {
+        self.ensure_writable()?;
+        let graph = self.graph_entry(graph_name);
+        let inserted: Vec<oxrdf::Triple> = triples.iter().filter(|triple| graph.insert(triple.as_ref())).cloned().collect();
+        if !inserted.is_empty() {
+            self.refresh_views_reading(&[graph_name.to_string()]);
+        }
+        
+        if self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
         
-        // Parse prefixes from Turtle data
-        let mut prefixes = std::collections::HashMap::new();
+        if is_derived_graph(graph_name) && !inserted.is_empty() {
+            self.publish(Change::Inferred { graph: graph_name.to_string(), triples: inserted.clone() });
+        }
         
-        // Simple Turtle parser - extract real triples
-        for line in turtle_content.lines() {
-            let trimmed = line.trim();
-            
-            // Skip empty lines and comments
-            if trimmed.is_empty() || trimmed.starts_with('#') {
+        Ok(inserted.len())
+    }
+    
+    /// Load graphs pulled from another instance in a single write.
+    ///
+    /// Event graphs replace the local copy when they differ from it; other graphs
+    /// keep their triples and gain the new ones. Returns the number of graphs
+    /// changed and of triples written.
+    pub fn merge_graphs(&mut self, graphs: HashMap<String, OxrdfGraph>) -> Result<(usize, usize), EpcisKgError> {
+        self.ensure_writable()?;
+        let (mut changed, mut written) = (0, 0);
+        let mut changed_graphs = Vec::new();
+        for (graph_name, incoming) in graphs {
+            if let Some(event_id) = graph_name.strip_prefix("urn:epcis:event:") {
+                if self.graphs.get(&graph_name) == Some(&incoming) {
+                    continue;
+                }
+                let triples: Vec<oxrdf::Triple> = incoming.iter().map(|triple| triple.into_owned()).collect();
+                (changed, written) = (changed + 1, written + triples.len());
+                self.publish(Change::Event { event_id: event_id.to_string(), graph: graph_name.clone(), triples });
+                self.put_graph(graph_name.clone(), incoming);
+                changed_graphs.push(graph_name);
                 continue;
             }
-            
-            // Parse prefix declarations
-            if trimmed.starts_with("@prefix") {
-                if let Some(prefix_part) = trimmed.strip_prefix("@prefix") {
-                    let parts: Vec<&str> = prefix_part.split(':').collect();
-                    if parts.len() >= 2 {
-                        let prefix_name = parts[0].trim();
-                        let uri_part = parts[1].trim();
-                        if let Some(uri) = uri_part.strip_suffix('.').and_then(|s| s.strip_prefix('<')).and_then(|s| s.strip_suffix('>')) {
-                            prefixes.insert(prefix_name, uri.to_string());
-                        }
-                    }
-                }
+
+            let graph = self.graph_entry(&graph_name);
+            let inserted: Vec<oxrdf::Triple> = incoming.iter().filter(|triple| graph.insert(*triple)).map(|triple| triple.into_owned()).collect();
+            if inserted.is_empty() {
                 continue;
             }
-            
-            // Parse triples (simplified Turtle parsing)
-            if trimmed.contains(' ') && !trimmed.starts_with('@') {
-                let parts: Vec<&str> = trimmed.split_whitespace().collect();
-                if parts.len() >= 3 {
-                    let subject_str = parts[0];
-                    let predicate_str = parts[1];
-                    let mut object_str = parts[2];
-                    
-                    // Remove trailing dot from object if present
-                    if object_str.ends_with('.') {
-                        object_str = &object_str[0..object_str.len()-1];
+            (changed, written) = (changed + 1, written + inserted.len());
+            if is_derived_graph(&graph_name) {
+                self.publish(Change::Inferred { graph: graph_name.clone(), triples: inserted });
+            }
+            changed_graphs.push(graph_name);
+        }
+
+        if changed > 0 {
+            self.refresh_ownership_view();
+            self.refresh_expiry_view();
+            self.refresh_views_reading(&changed_graphs);
+            if self.storage_path != ":memory:" {
+                self.save_graphs()?;
+            }
+        }
+        Ok((changed, written))
+    }
+
+    /// Annotate a statement with RDF-star triples (`<< s p o >> predicate value`)
+    pub fn annotate_statement(
+        &mut self,
+        graph_name: &str,
+        statement: &oxrdf::Triple,
+        annotations: &[(oxrdf::NamedNode, oxrdf::Term)],
+    ) -> Result<usize, EpcisKgError> {
+        let quoted = oxrdf::Subject::Triple(Box::new(statement.clone()));
+        let triples: Vec<oxrdf::Triple> = annotations
+            .iter()
+            .map(|(predicate, value)| oxrdf::Triple::new(quoted.clone(), predicate.clone(), value.clone()))
+            .collect();
+        self.insert_triples(graph_name, &triples)
+    }
+    
+    /// Find the RDF-star annotations of a statement across all graphs
+    pub fn statement_annotations(&self, statement: &oxrdf::Triple) -> Vec<(String, oxrdf::NamedNode, oxrdf::Term)> {
+        let mut annotations = Vec::new();
+        
+        for (graph_name, graph) in &self.graphs {
+            for triple in graph.iter() {
+                if let oxrdf::SubjectRef::Triple(quoted) = triple.subject {
+                    if quoted == statement {
+                        annotations.push((graph_name.clone(), triple.predicate.into_owned(), triple.object.into_owned()));
                     }
-                    
-                    // Convert subject
-                    let subject = if subject_str.starts_with('<') && subject_str.ends_with('>') {
-                        let uri = &subject_str[1..subject_str.len()-1];
-                        oxrdf::NamedNode::new_unchecked(uri)
-                    } else if subject_str.contains(':') {
-                        // Handle prefixed names
-                        let mut expanded = subject_str.to_string();
-                        for (prefix, uri) in &prefixes {
-                            if subject_str.starts_with(&format!("{}:", prefix)) {
-                                expanded = subject_str.replace(&format!("{}:", prefix), uri);
-                                break;
-                            }
-                        }
-                        oxrdf::NamedNode::new_unchecked(expanded)
-                    } else {
-                        continue; // Skip invalid subjects
-                    };
-                    
-                    // Convert predicate
-                    let predicate = if predicate_str.starts_with('<') && predicate_str.ends_with('>') {
-                        let uri = &predicate_str[1..predicate_str.len()-1];
-                        oxrdf::NamedNode::new_unchecked(uri)
-                    } else if predicate_str.contains(':') {
-                        let mut expanded = predicate_str.to_string();
-                        for (prefix, uri) in &prefixes {
-                            if predicate_str.starts_with(&format!("{}:", prefix)) {
-                                expanded = predicate_str.replace(&format!("{}:", prefix), uri);
-                                break;
-                            }
-                        }
-                        oxrdf::NamedNode::new_unchecked(expanded)
-                    } else {
-                        continue; // Skip invalid predicates
-                    };
-                    
-                    // Convert object
-                    let object = if object_str.starts_with('<') && object_str.ends_with('>') {
-                        let uri = &object_str[1..object_str.len()-1];
-                        oxrdf::Term::NamedNode(oxrdf::NamedNode::new_unchecked(uri))
-                    } else if object_str.starts_with('"') && object_str.ends_with('"') {
-                        // Literal
-                        let literal_content = &object_str[1..object_str.len()-1];
-                        oxrdf::Term::Literal(oxrdf::Literal::new_simple_literal(literal_content))
-                    } else if object_str.contains(':') {
-                        // Prefixed name or URI
-                        let mut expanded = object_str.to_string();
-                        for (prefix, uri) in &prefixes {
-                            if object_str.starts_with(&format!("{}:", prefix)) {
-                                expanded = object_str.replace(&format!("{}:", prefix), uri);
-                                break;
-                            }
-                        }
-                        oxrdf::Term::NamedNode(oxrdf::NamedNode::new_unchecked(expanded))
-                    } else {
-                        continue; // Skip invalid objects
-                    };
-                    
-                    // Create and store the triple
-                    let triple = oxrdf::Triple::new(subject, predicate, object);
-                    graph.insert(triple.as_ref());
-                    triple_count += 1;
                 }
             }
         }
         
-        println!("✓ Parsed and stored {} triples from persisted Turtle data", triple_count);
+        annotations
+    }
+    
+    /// Get store statistics
+    pub fn get_statistics(&self) -> Result<OxigraphStats, EpcisKgError> {
+        let total_quads: usize = self.graphs.values().map(|graph| graph.len()).sum();
+        let named_graphs = self.graphs.len();
+        let default_graph_quads = 0; // We don't store default graph quads in this implementation
         
-        Ok(graph)
+        Ok(OxigraphStats {
+            total_quads,
+            named_graphs,
+            default_graph_quads,
+            storage_path: self.storage_path.clone(),
+            read_only: self.read_only,
+        })
     }
     
-    /// Convert Graph to Turtle format
-    fn graph_to_turtle(graph: &OxrdfGraph) -> Result<String, EpcisKgError> {
-        let mut turtle = String::new();
+    /// Clear all data from the store
+    pub fn clear(&mut self) -> Result<(), EpcisKgError> {
+        self.ensure_writable()?;
+        self.take_all_graphs();
+        Ok(())
+    }
+    
+    /// Store event triples in a named graph (async version)
+    pub async fn store_event_triples(&mut self, event_id: &str, triples: &[oxrdf::Triple]) -> Result<(), EpcisKgError> {
+        self.ensure_writable()?;
+        // Create a named graph for this event
+        let graph_name = format!("urn:epcis:event:{}", event_id);
         
-        for triple in graph.iter() {
-            let s = format!("{}", triple.subject);
-            let p = format!("{}", triple.predicate);
-            let o = format!("{}", triple.object);
-            turtle.push_str(&format!("{} {} {} .\n", s, p, o));
+        // Create or get the graph
+        let mut graph = OxrdfGraph::default();
+        
+        // Add all triples to the graph
+        for triple in triples {
+            graph.insert(triple.as_ref());
         }
         
-        Ok(turtle)
+        // Store the graph
+        self.put_graph(graph_name.clone(), graph);
+        self.publish(Change::Event {
+            event_id: event_id.to_string(),
+            graph: graph_name.clone(),
+            triples: triples.to_vec(),
+        });
+        self.refresh_ownership_view();
+        self.refresh_expiry_view();
+        self.refresh_views_reading(&[graph_name]);
+        
+        // Save to persistent storage if not in-memory
+        if self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        
+        Ok(())
     }
     
-    /// Parse a single triple from a Turtle line (simplified)
-    fn parse_triple_from_line(line: &str) -> Result<oxrdf::Triple, EpcisKgError> {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 3 {
-            let subject = oxrdf::NamedNode::new(parts[0])
-                .map_err(|e| EpcisKgError::RdfParsing(format!("Invalid subject IRI: {}", e)))?;
-            let predicate = oxrdf::NamedNode::new(parts[1])
-                .map_err(|e| EpcisKgError::RdfParsing(format!("Invalid predicate IRI: {}", e)))?;
-            let object = oxrdf::NamedNode::new(parts[2])
-                .map_err(|e| EpcisKgError::RdfParsing(format!("Invalid object IRI: {}", e)))?;
-            Ok(oxrdf::Triple::new(subject, predicate, object))
-        } else {
-            Err(EpcisKgError::Validation("Invalid triple format".to_string()))
+    /// Add or replace a trading partner in the party master data
+    pub fn upsert_party(&mut self, party: &Party) -> Result<(), EpcisKgError> {
+        self.ensure_writable()?;
+        party.validate()?;
+        let subject = oxrdf::NamedNode::new(party.gln.clone())?;
+        let epcis = |local: &str| oxrdf::NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+        
+        let graph = self.graph_entry(PARTIES_GRAPH);
+        let existing: Vec<oxrdf::Triple> = graph.triples_for_subject(&subject).map(|t| t.into_owned()).collect();
+        for triple in &existing {
+            graph.remove(triple);
+        }
+        
+        let mut triples = vec![
+            oxrdf::Triple::new(
+                subject.clone(),
+                oxrdf::NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"),
+                epcis("Party"),
+            ),
+            oxrdf::Triple::new(subject.clone(), epcis("partyName"), oxrdf::Literal::new_simple_literal(party.name.clone())),
+        ];
+        if let Some(role) = &party.role {
+            triples.push(oxrdf::Triple::new(subject.clone(), epcis("partyRole"), oxrdf::Literal::new_simple_literal(role.clone())));
+        }
+        if let Some(address) = &party.address {
+            triples.push(oxrdf::Triple::new(subject.clone(), epcis("partyAddress"), oxrdf::Literal::new_simple_literal(address.clone())));
         }
+        if let Some(zone) = &party.time_zone {
+            triples.push(oxrdf::Triple::new(subject.clone(), epcis("partyTimeZone"), oxrdf::Literal::new_simple_literal(zone.clone())));
+        }
+        for triple in &triples {
+            graph.insert(triple);
+        }
+        self.refresh_views_reading(&[PARTIES_GRAPH.to_string()]);
+        
+        if self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        
+        Ok(())
     }
-}
-
-/// Store metadata for persistence
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-struct StoreMetadata {
-    pub graphs: Vec<String>,
-    pub created_at: String,
-}
-
-/// Statistics about the Oxigraph store
-#[derive(Debug, Clone, serde::Serialize, Default)]
-pub struct OxigraphStats {
-    pub total_quads: usize,
-    pub named_graphs: usize,
-    pub default_graph_quads: usize,
-    pub storage_path: String,
-}
\ No newline at end of file
+    
+    /// All registered trading partners, sorted by GLN
+    pub fn parties(&self) -> Vec<Party> {
+        let Some(graph) = self.graphs.get(PARTIES_GRAPH) else {
+            return Vec::new();
+        };
+        let rdf_type = oxrdf::NamedNodeRef::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type");
+        let party_class = oxrdf::NamedNode::new_unchecked(format!("{}Party", EPCIS_NS));
+        let literal = |subject: oxrdf::NamedNodeRef<'_>, local: &str| {
+            let predicate = oxrdf::NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+            match graph.object_for_subject_predicate(subject, &predicate) {
+                Some(oxrdf::TermRef::Literal(value)) => Some(value.value().to_string()),
+                _ => None,
+            }
+        };
+        
+        let mut parties: Vec<Party> = graph
+            .subjects_for_predicate_object(rdf_type, &party_class)
+            .filter_map(|subject| match subject {
+                oxrdf::SubjectRef::NamedNode(node) => Some(Party {
+                    gln: node.as_str().to_string(),
+                    name: literal(node, "partyName").unwrap_or_default(),
+                    role: literal(node, "partyRole"),
+                    address: literal(node, "partyAddress"),
+                    time_zone: literal(node, "partyTimeZone"),
+                }),
+                _ => None,
+            })
+            .collect();
+        parties.sort_by(|a, b| a.gln.cmp(&b.gln));
+        parties
+    }
+    
+    /// Register a SKOS vocabulary extension in its own ontology graph,
+    /// replacing an earlier version of the same scheme
+    pub fn register_vocabulary_extension(&mut self, extension: &VocabularyExtension) -> Result<String, EpcisKgError> {
+        self.ensure_writable()?;
+        extension.validate()?;
+        let overlapping = self.vocabulary_extensions().into_iter().find(|other| {
+            other.scheme != extension.scheme
+                && (other.namespace.starts_with(&extension.namespace) || extension.namespace.starts_with(&other.namespace))
+        });
+        if let Some(other) = overlapping {
+            return Err(EpcisKgError::Conflict(format!(
+                "namespace {} overlaps {} of the registered scheme {}",
+                extension.namespace, other.namespace, other.scheme
+            )));
+        }
+        
+        let graph_name = extension_graph_name(&extension.scheme);
+        let triples = extension.to_triples()?;
+        self.put_graph(graph_name.clone(), triples.iter().collect());
+        
+        if self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        
+        Ok(graph_name)
+    }
+    
+    /// Registered vocabulary extensions, sorted by scheme IRI
+    pub fn vocabulary_extensions(&self) -> Vec<VocabularyExtension> {
+        let mut extensions: Vec<VocabularyExtension> = self
+            .graphs
+            .iter()
+            .filter(|(name, _)| name.starts_with(VOCABULARY_EXTENSION_GRAPH_PREFIX))
+            .filter_map(|(_, graph)| VocabularyExtension::from_graph(graph))
+            .collect();
+        extensions.sort_by(|a, b| a.scheme.cmp(&b.scheme));
+        extensions
+    }
+    
+    /// Drop a vocabulary extension; false if the scheme was not registered
+    pub fn remove_vocabulary_extension(&mut self, scheme: &str) -> Result<bool, EpcisKgError> {
+        self.ensure_writable()?;
+        let removed = self.take_graph(&extension_graph_name(scheme)).is_some();
+        
+        if removed && self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        
+        Ok(removed)
+    }
+    
+    /// Register a materialized view, replacing the view of the same name, and compute its results
+    pub fn register_view(&mut self, definition: &ViewDefinition) -> Result<ViewStatus, EpcisKgError> {
+        self.ensure_writable()?;
+        definition.validate()?;
+        self.materialize_view(definition)?;
+        
+        if self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        
+        self.view(&definition.name)
+            .ok_or_else(|| EpcisKgError::Storage(format!("view {} was not recorded", definition.name)))
+    }
+    
+    /// Registered views with the size and last refresh of their graphs, sorted by name
+    pub fn views(&self) -> Vec<ViewStatus> {
+        match self.graphs.get(VIEW_DEFINITIONS_GRAPH) {
+            Some(definitions) => views::recorded_views(definitions, |graph_name| self.graph_len(graph_name)),
+            None => Vec::new(),
+        }
+    }
+    
+    pub fn view(&self, name: &str) -> Option<ViewStatus> {
+        self.views().into_iter().find(|view| view.definition.name == name)
+    }
+    
+    /// Recompute a view's results now, whether or not its sources changed
+    pub fn refresh_view(&mut self, name: &str) -> Result<ViewStatus, EpcisKgError> {
+        self.ensure_writable()?;
+        let Some(view) = self.view(name) else {
+            return Err(EpcisKgError::NotFound { resource: "View".to_string(), id: name.to_string() });
+        };
+        self.materialize_view(&view.definition)?;
+        
+        if self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        
+        self.view(name).ok_or_else(|| EpcisKgError::NotFound { resource: "View".to_string(), id: name.to_string() })
+    }
+    
+    /// Drop a view and its results; false if no view has this name
+    pub fn remove_view(&mut self, name: &str) -> Result<bool, EpcisKgError> {
+        self.ensure_writable()?;
+        let view_graph = views::view_graph_name(name);
+        let subject = oxrdf::NamedNode::new_unchecked(view_graph.clone());
+        let Some(definitions) = self.graph_mut(VIEW_DEFINITIONS_GRAPH) else { return Ok(false) };
+        let recorded: Vec<oxrdf::Triple> = definitions.triples_for_subject(&subject).map(|triple| triple.into_owned()).collect();
+        if recorded.is_empty() {
+            return Ok(false);
+        }
+        for triple in &recorded {
+            definitions.remove(triple);
+        }
+        if definitions.is_empty() {
+            self.take_graph(VIEW_DEFINITIONS_GRAPH);
+        }
+        self.take_graph(&view_graph);
+        
+        if self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        
+        Ok(true)
+    }
+    
+    /// Replace a view's graph with its current results and record the refresh
+    fn materialize_view(&mut self, definition: &ViewDefinition) -> Result<(), EpcisKgError> {
+        let started = std::time::Instant::now();
+        let results = views::evaluate(&self.graphs, definition)?;
+        self.replace_derived_graph(&definition.graph_name(), results);
+        
+        let subject = oxrdf::NamedNode::new_unchecked(definition.graph_name());
+        let definitions = self.graph_entry(VIEW_DEFINITIONS_GRAPH);
+        let previous: Vec<oxrdf::Triple> = definitions.triples_for_subject(&subject).map(|triple| triple.into_owned()).collect();
+        for triple in &previous {
+            definitions.remove(triple);
+        }
+        for triple in views::definition_triples(definition, chrono::Utc::now(), started.elapsed().as_millis() as u64) {
+            definitions.insert(&triple);
+        }
+        Ok(())
+    }
+    
+    /// Refresh the views reading any of the `changed` graphs; the others keep their results
+    fn refresh_views_reading(&mut self, changed: &[String]) {
+        for view in self.views() {
+            if !changed.iter().any(|graph_name| view.definition.reads(graph_name)) {
+                continue;
+            }
+            if let Err(e) = self.materialize_view(&view.definition) {
+                warn!("View {} was not refreshed: {}", view.definition.name, e);
+            }
+        }
+    }
+    
+    /// Named master data resources, i.e. subjects with a name literal outside
+    /// event, ontology and derived graphs, with their types and identifiers
+    pub fn master_records(&self, config: &EntityResolutionConfig) -> Vec<MasterRecord> {
+        let rdf_type = oxrdf::NamedNodeRef::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type");
+        let master_graphs: Vec<&OxrdfGraph> = self
+            .graphs
+            .iter()
+            .filter(|(name, _)| is_master_data_graph(name))
+            .map(|(_, graph)| graph)
+            .collect();
+        let term_value = |term: oxrdf::TermRef<'_>| match term {
+            oxrdf::TermRef::NamedNode(node) => Some(node.as_str().to_string()),
+            oxrdf::TermRef::Literal(literal) => Some(literal.value().to_string()),
+            _ => None,
+        };
+        
+        let mut records: std::collections::BTreeMap<String, MasterRecord> = std::collections::BTreeMap::new();
+        for graph in &master_graphs {
+            for predicate in config.name_predicates.iter().filter_map(|p| oxrdf::NamedNodeRef::new(p).ok()) {
+                for triple in graph.triples_for_predicate(predicate) {
+                    let (oxrdf::SubjectRef::NamedNode(subject), oxrdf::TermRef::Literal(name)) = (triple.subject, triple.object) else { continue };
+                    let record = records.entry(subject.as_str().to_string()).or_insert_with(|| MasterRecord {
+                        iri: subject.as_str().to_string(),
+                        ..MasterRecord::default()
+                    });
+                    if !record.names.iter().any(|existing| existing == name.value()) {
+                        record.names.push(name.value().to_string());
+                    }
+                }
+            }
+        }
+        
+        for record in records.values_mut() {
+            let subject = oxrdf::NamedNodeRef::new_unchecked(&record.iri);
+            for graph in &master_graphs {
+                record.types.extend(graph.objects_for_subject_predicate(subject, rdf_type).filter_map(term_value));
+                for predicate in config.identifier_predicates.iter().filter_map(|p| oxrdf::NamedNodeRef::new(p).ok()) {
+                    record.identifiers.extend(graph.objects_for_subject_predicate(subject, predicate).filter_map(term_value));
+                }
+            }
+        }
+        
+        records.into_values().collect()
+    }
+    
+    /// Pairs of resources with a recorded `owl:sameAs` review, accepted or rejected
+    pub fn reviewed_same_as(&self) -> std::collections::HashSet<(String, String)> {
+        let Some(graph) = self.graphs.get(SAME_AS_GRAPH) else {
+            return std::collections::HashSet::new();
+        };
+        graph
+            .iter()
+            .filter_map(|triple| match triple.subject {
+                oxrdf::SubjectRef::Triple(quoted) => match (&quoted.subject, &quoted.object) {
+                    (oxrdf::Subject::NamedNode(left), oxrdf::Term::NamedNode(right)) => {
+                        Some((left.as_str().to_string(), right.as_str().to_string()))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+    
+    /// Proposed `owl:sameAs` links between near-duplicate master data that nobody has reviewed yet
+    pub fn propose_same_as(&self, config: &EntityResolutionConfig) -> Vec<SameAsProposal> {
+        entity_resolution::propose_links(&self.master_records(config), config, &self.reviewed_same_as())
+    }
+    
+    /// Record a review of a proposed link.
+    ///
+    /// An accepted link is asserted as `owl:sameAs`; a rejected one is
+    /// retracted if it was accepted before. Either way the decision is kept
+    /// as RDF-star annotations so the pair is not proposed again.
+    pub fn review_same_as(&mut self, review: &SameAsReview) -> Result<(), EpcisKgError> {
+        self.ensure_writable()?;
+        let left = oxrdf::NamedNode::new(review.left.clone())?;
+        let right = oxrdf::NamedNode::new(review.right.clone())?;
+        if left == right {
+            return Err(EpcisKgError::InvalidField {
+                field: "right".to_string(),
+                message: "A resource cannot be linked to itself".to_string(),
+            });
+        }
+        let same_as = oxrdf::NamedNode::new_unchecked("http://www.w3.org/2002/07/owl#sameAs");
+        let epcis = |local: &str| oxrdf::NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+        let statement = oxrdf::Triple::new(left.clone(), same_as.clone(), right.clone());
+        let reversed = oxrdf::Triple::new(right, same_as, left);
+        
+        // Replace any earlier decision on the pair, in either direction
+        if let Some(graph) = self.graph_mut(SAME_AS_GRAPH) {
+            let stale: Vec<oxrdf::Triple> = graph
+                .iter()
+                .filter(|triple| match triple.subject {
+                    oxrdf::SubjectRef::Triple(quoted) => *quoted == statement || *quoted == reversed,
+                    _ => *triple == statement.as_ref() || *triple == reversed.as_ref(),
+                })
+                .map(|triple| triple.into_owned())
+                .collect();
+            for triple in &stale {
+                graph.remove(triple);
+            }
+        }
+        
+        let status = if review.accept { "accepted" } else { "rejected" };
+        let mut annotations: Vec<(oxrdf::NamedNode, oxrdf::Term)> = vec![
+            (epcis("reviewStatus"), oxrdf::Literal::new_simple_literal(status).into()),
+            (
+                epcis("reviewTime"),
+                oxrdf::Literal::new_typed_literal(
+                    chrono::Utc::now().to_rfc3339(),
+                    oxrdf::NamedNode::new_unchecked("http://www.w3.org/2001/XMLSchema#dateTime"),
+                )
+                .into(),
+            ),
+        ];
+        if let Some(reviewer) = &review.reviewer {
+            annotations.push((epcis("reviewedBy"), oxrdf::Literal::new_simple_literal(reviewer.clone()).into()));
+        }
+        if review.accept {
+            self.insert_triples(SAME_AS_GRAPH, std::slice::from_ref(&statement))?;
+        }
+        self.annotate_statement(SAME_AS_GRAPH, &statement, &annotations)?;
+        Ok(())
+    }
+    
+    /// Ownership transfers recorded by TransactionEvents, keyed by EPC, oldest first.
+    ///
+    /// A transfer is a TransactionEvent with an ownership-transfer bizStep that
+    /// names a destination owning party; events declared in error are ignored.
+    pub fn ownership_transfers(&self) -> HashMap<String, Vec<OwnershipTransfer>> {
+        let epcis = |local: &str| oxrdf::NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+        let rdf_type = oxrdf::NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type");
+        let term_value = |term: oxrdf::TermRef<'_>| match term {
+            oxrdf::TermRef::NamedNode(node) => node.as_str().to_string(),
+            oxrdf::TermRef::Literal(literal) => literal.value().to_string(),
+            other => other.to_string(),
+        };
+        
+        let mut transfers: HashMap<String, Vec<OwnershipTransfer>> = HashMap::new();
+        for (name, graph) in &self.graphs {
+            let Some(event_id) = name.strip_prefix("urn:epcis:event:") else { continue };
+            if self.is_declared_in_error(event_id) {
+                continue;
+            }
+            
+            let event = oxrdf::NamedNode::new_unchecked(format!("urn:epc:event:{}", event_id));
+            if !graph.contains(oxrdf::TripleRef::new(&event, &rdf_type, &epcis("TransactionEvent"))) {
+                continue;
+            }
+            let Some(biz_step) = graph.object_for_subject_predicate(&event, &epcis("bizStep")).map(term_value) else { continue };
+            let step = biz_step.rsplit(':').next().unwrap_or(&biz_step);
+            if !OWNERSHIP_TRANSFER_BIZ_STEPS.contains(&step) {
+                continue;
+            }
+            let Some(to) = graph.object_for_subject_predicate(&event, &epcis("destinationOwningParty")).map(term_value) else { continue };
+            
+            let transfer = OwnershipTransfer {
+                event_id: event_id.to_string(),
+                event_time: graph
+                    .object_for_subject_predicate(&event, &epcis("eventTime"))
+                    .map(term_value)
+                    .unwrap_or_default(),
+                from: graph.object_for_subject_predicate(&event, &epcis("sourceOwningParty")).map(term_value),
+                biz_step: biz_step.clone(),
+                to,
+            };
+            for epc in graph.objects_for_subject_predicate(&event, &epcis("epcList")) {
+                transfers.entry(term_value(epc)).or_default().push(transfer.clone());
+            }
+        }
+        
+        for history in transfers.values_mut() {
+            history.sort_by_key(|transfer| {
+                chrono::DateTime::parse_from_rfc3339(&transfer.event_time)
+                    .map(|time| time.timestamp_millis())
+                    .unwrap_or(i64::MIN)
+            });
+        }
+        transfers
+    }
+    
+    /// Current owner of an EPC, or `None` if no ownership transfer has been recorded
+    pub fn current_owner(&self, epc: &str) -> Option<OwnershipRecord> {
+        let history = self.ownership_transfers().remove(epc)?;
+        let latest = history.last()?.clone();
+        
+        Some(OwnershipRecord {
+            epc: epc.to_string(),
+            owner_party: self.parties().into_iter().find(|party| party.gln == latest.to),
+            owner: latest.to,
+            since: latest.event_time,
+            history,
+        })
+    }
+    
+    /// Rebuild the current-owner graph so ownership can be queried with SPARQL
+    fn refresh_ownership_view(&mut self) {
+        let current_owner = oxrdf::NamedNode::new_unchecked(format!("{}currentOwner", EPCIS_NS));
+        let owned_since = oxrdf::NamedNode::new_unchecked(format!("{}ownedSince", EPCIS_NS));
+        
+        let mut view = OxrdfGraph::default();
+        for (epc, history) in self.ownership_transfers() {
+            let (Some(latest), Ok(epc)) = (history.last(), oxrdf::NamedNode::new(epc)) else { continue };
+            let Ok(owner) = oxrdf::NamedNode::new(latest.to.clone()) else { continue };
+            
+            view.insert(&oxrdf::Triple::new(epc.clone(), current_owner.clone(), owner));
+            view.insert(&oxrdf::Triple::new(
+                epc,
+                owned_since.clone(),
+                oxrdf::Literal::new_typed_literal(
+                    latest.event_time.clone(),
+                    oxrdf::NamedNode::new_unchecked("http://www.w3.org/2001/XMLSchema#dateTime"),
+                ),
+            ));
+        }
+        
+        self.replace_derived_graph(OWNERSHIP_GRAPH, view);
+    }
+    
+    /// Replace a derived graph wholesale, publishing the facts it did not hold before
+    fn replace_derived_graph(&mut self, graph_name: &str, view: OxrdfGraph) {
+        let added: Vec<oxrdf::Triple> = match self.graphs.get(graph_name) {
+            Some(previous) => view.iter().filter(|triple| !previous.contains(*triple)).map(|triple| triple.into_owned()).collect(),
+            None => view.iter().map(|triple| triple.into_owned()).collect(),
+        };
+        
+        if view.is_empty() {
+            self.take_graph(graph_name);
+        } else {
+            self.put_graph(graph_name.to_string(), view);
+        }
+        
+        if !added.is_empty() {
+            self.publish(Change::Inferred { graph: graph_name.to_string(), triples: added });
+        }
+    }
+    
+    /// Expiry instant of every EPC with an ILMD `itemExpirationDate` (the earliest, if several)
+    pub fn expiry_dates(&self) -> HashMap<String, chrono::DateTime<chrono::Utc>> {
+        let predicate = oxrdf::NamedNode::new_unchecked(ITEM_EXPIRATION_DATE);
+        let mut expiries: HashMap<String, chrono::DateTime<chrono::Utc>> = HashMap::new();
+        
+        for (name, graph) in &self.graphs {
+            let Some(event_id) = name.strip_prefix("urn:epcis:event:") else { continue };
+            if self.is_declared_in_error(event_id) {
+                continue;
+            }
+            for triple in graph.triples_for_predicate(&predicate) {
+                let (oxrdf::SubjectRef::NamedNode(epc), oxrdf::TermRef::Literal(value)) = (triple.subject, triple.object) else { continue };
+                let Some(expiry) = expiry::expiry_instant(value.value()) else { continue };
+                expiries
+                    .entry(epc.as_str().to_string())
+                    .and_modify(|existing| *existing = (*existing).min(expiry))
+                    .or_insert(expiry);
+            }
+        }
+        expiries
+    }
+    
+    /// Events that observed or added product past its expiry
+    pub fn expired_observations(&self) -> Vec<ExpiredObservation> {
+        expiry::expired_observations(&self.lineage_events(), &self.expiry_dates())
+    }
+    
+    /// Inventory expiring before `now + within`, per location
+    pub fn expiring_inventory(&self, now: chrono::DateTime<chrono::Utc>, within: chrono::Duration) -> Vec<LocationExpiry> {
+        let (locations, _) = inventory::current_locations(&self.inventory_events(), now);
+        expiry::expiring_inventory(&locations, &self.expiry_dates(), now, within)
+    }
+    
+    /// Rebuild the expiry graph: flagged events get an inferred `expired` disposition
+    fn refresh_expiry_view(&mut self) {
+        let epcis = |local: &str| oxrdf::NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+        let expired = oxrdf::NamedNode::new_unchecked(EXPIRED_DISPOSITION);
+        
+        let mut view = OxrdfGraph::default();
+        for observation in self.expired_observations() {
+            let (Ok(event), Ok(epc)) = (
+                oxrdf::NamedNode::new(format!("urn:epc:event:{}", observation.event_id)),
+                oxrdf::NamedNode::new(observation.epc),
+            ) else {
+                continue;
+            };
+            view.insert(&oxrdf::Triple::new(event.clone(), epcis("inferredDisposition"), expired.clone()));
+            view.insert(&oxrdf::Triple::new(event, epcis("observesExpiredProduct"), epc));
+        }
+        
+        self.replace_derived_graph(EXPIRY_GRAPH, view);
+    }
+    
+    /// Temperature readings of events not declared in error, attributed to every EPC of the event
+    pub fn temperature_readings(&self) -> Vec<TemperatureReading> {
+        let epcis = |local: &str| oxrdf::NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+        let literal = |graph: &OxrdfGraph, subject: oxrdf::NamedNodeRef<'_>, predicate: &oxrdf::NamedNode| {
+            match graph.object_for_subject_predicate(subject, predicate) {
+                Some(oxrdf::TermRef::Literal(literal)) => Some(literal.value().to_string()),
+                _ => None,
+            }
+        };
+        
+        let mut readings = Vec::new();
+        for (name, graph) in &self.graphs {
+            let Some(event_id) = name.strip_prefix("urn:epcis:event:") else { continue };
+            if self.is_declared_in_error(event_id) {
+                continue;
+            }
+            
+            let event = oxrdf::NamedNode::new_unchecked(format!("urn:epc:event:{}", event_id));
+            let epcs: Vec<String> = ["epcList", "childEPCs", "parentID", "outputEPCList"]
+                .iter()
+                .flat_map(|predicate| graph.objects_for_subject_predicate(&event, &epcis(predicate)).collect::<Vec<_>>())
+                .filter_map(|term| match term {
+                    oxrdf::TermRef::NamedNode(node) => Some(node.as_str().to_string()),
+                    _ => None,
+                })
+                .collect();
+            
+            for reading in graph.objects_for_subject_predicate(&event, &epcis("temperatureReading")) {
+                let oxrdf::TermRef::NamedNode(reading) = reading else { continue };
+                let celsius = literal(graph, reading, &epcis("celsius")).and_then(|value| value.parse::<f64>().ok());
+                let time = literal(graph, reading, &epcis("readingTime"))
+                    .and_then(|value| chrono::DateTime::parse_from_rfc3339(&value).ok());
+                let (Some(celsius), Some(time)) = (celsius, time) else { continue };
+                
+                readings.extend(epcs.iter().map(|epc| TemperatureReading {
+                    epc: epc.clone(),
+                    event_id: event_id.to_string(),
+                    time: time.with_timezone(&chrono::Utc),
+                    celsius,
+                }));
+            }
+        }
+        readings
+    }
+    
+    /// Temperature excursions of EPCs covered by one of the profiles
+    pub fn detect_excursions(&self, profiles: &[TemperatureProfile]) -> Vec<Excursion> {
+        cold_chain::detect_excursions(&self.temperature_readings(), profiles)
+    }
+    
+    /// Replace the cold-chain graph with these excursions, returning those not recorded before
+    pub fn materialize_excursions(&mut self, excursions: &[Excursion]) -> Result<Vec<Excursion>, EpcisKgError> {
+        self.ensure_writable()?;
+        let known: std::collections::HashSet<String> = self
+            .graphs
+            .get(COLD_CHAIN_GRAPH)
+            .map(|graph| {
+                graph
+                    .subjects_for_predicate_object(
+                        oxrdf::NamedNodeRef::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"),
+                        oxrdf::NamedNodeRef::new_unchecked("urn:epcglobal:epcis:TemperatureExcursion"),
+                    )
+                    .map(|subject| match subject {
+                        oxrdf::SubjectRef::NamedNode(node) => node.as_str().to_string(),
+                        other => other.to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let new_excursions: Vec<Excursion> = excursions
+            .iter()
+            .filter(|excursion| !known.contains(&excursion.iri()))
+            .cloned()
+            .collect();
+        
+        let mut view = OxrdfGraph::default();
+        for triple in cold_chain::excursion_triples(excursions) {
+            view.insert(&triple);
+        }
+        self.replace_derived_graph(COLD_CHAIN_GRAPH, view);
+        
+        if self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        
+        Ok(new_excursions)
+    }
+    
+    /// Events not declared in error, their EPCs and sensor readings as flat tables
+    pub fn event_tables(&self) -> EventTables {
+        EventTables::from_history(&self.lineage_events(), &self.temperature_readings())
+    }
+    
+    /// IRIs mentioned by the ontology graphs, for schema drift checks
+    pub fn ontology_terms(&self) -> OntologyTerms {
+        let names: Vec<String> = self.graph_names().into_iter().filter(|name| name.starts_with("urn:epcis:ontology:")).collect();
+        OntologyTerms::from_graph(&self.union_graph(&names))
+    }
+    
+    /// Predicates and classes used by stored events but absent from the ontology graphs.
+    ///
+    /// `declared` accepts further terms, such as declared extension fields. Terms
+    /// are dated by the record time of the events using them.
+    pub fn schema_drift(&self, declared: impl Fn(&str) -> bool) -> SchemaDriftReport {
+        let known = self.ontology_terms();
+        let record_time = oxrdf::NamedNode::new_unchecked(format!("{}recordTime", EPCIS_NS));
+        let mut tracker = SchemaDriftTracker::new();
+        
+        let mut events: Vec<(&str, &OxrdfGraph)> = self
+            .graphs
+            .iter()
+            .filter_map(|(name, graph)| name.strip_prefix("urn:epcis:event:").map(|event_id| (event_id, graph)))
+            .collect();
+        events.sort_by_key(|(event_id, _)| *event_id);
+        for (event_id, graph) in events {
+            let event = oxrdf::NamedNode::new_unchecked(format!("urn:epc:event:{}", event_id));
+            let recorded = match graph.object_for_subject_predicate(&event, &record_time) {
+                Some(oxrdf::TermRef::Literal(literal)) => chrono::DateTime::parse_from_rfc3339(literal.value())
+                    .map(|time| time.with_timezone(&chrono::Utc))
+                    .ok(),
+                _ => None,
+            };
+            let unknown = known.unknown_terms(graph.iter(), &declared);
+            tracker.observe(event_id, &unknown, recorded.unwrap_or_else(chrono::Utc::now));
+        }
+        
+        tracker.report()
+    }
+    
+    /// Replace the schema drift graph with these unknown terms, returning those not recorded before
+    pub fn materialize_schema_drift(&mut self, terms: &[UnknownTerm]) -> Result<Vec<UnknownTerm>, EpcisKgError> {
+        self.ensure_writable()?;
+        let known: std::collections::HashSet<String> = self
+            .graphs
+            .get(SCHEMA_DRIFT_GRAPH)
+            .map(|graph| {
+                graph
+                    .subjects_for_predicate_object(
+                        oxrdf::NamedNodeRef::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"),
+                        oxrdf::NamedNodeRef::new_unchecked("urn:epcglobal:epcis:UnknownTerm"),
+                    )
+                    .map(|subject| match subject {
+                        oxrdf::SubjectRef::NamedNode(node) => node.as_str().to_string(),
+                        other => other.to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let new_terms: Vec<UnknownTerm> = terms.iter().filter(|term| !known.contains(&term.iri)).cloned().collect();
+        
+        let mut view = OxrdfGraph::default();
+        for triple in drift::unknown_term_triples(terms) {
+            view.insert(&triple);
+        }
+        self.replace_derived_graph(SCHEMA_DRIFT_GRAPH, view);
+        
+        if self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        
+        Ok(new_terms)
+    }
+    
+    /// Data-quality profile of the events not declared in error
+    pub fn quality_report(&self, options: &QualityOptions) -> QualityReport {
+        let event_time = oxrdf::NamedNode::new_unchecked(format!("{}eventTime", EPCIS_NS));
+        let events: Vec<(oxrdf::NamedNode, &OxrdfGraph)> = self
+            .graphs
+            .iter()
+            .filter_map(|(name, graph)| {
+                let event_id = name.strip_prefix("urn:epcis:event:")?;
+                (!self.is_declared_in_error(event_id))
+                    .then(|| (oxrdf::NamedNode::new_unchecked(format!("urn:epc:event:{}", event_id)), graph))
+            })
+            .collect();
+        
+        quality::profile_events(
+            events.iter().map(|(node, graph)| ProfiledEvent {
+                node: node.as_ref(),
+                event_time: match graph.object_for_subject_predicate(node, &event_time) {
+                    Some(oxrdf::TermRef::Literal(literal)) => chrono::DateTime::parse_from_rfc3339(literal.value())
+                        .ok()
+                        .map(|time| time.with_timezone(&chrono::Utc)),
+                    _ => None,
+                },
+                graph,
+            }),
+            options,
+        )
+    }
+    
+    /// Fields of every stored event, keyed by event ID, for comparing event sets.
+    ///
+    /// Fields are the predicates of the event node. A blank-node value is
+    /// described by its own predicates and values, so readings compare by
+    /// content rather than by their store-specific labels.
+    pub fn event_fields(&self) -> std::collections::BTreeMap<String, EventFields> {
+        self.graphs
+            .iter()
+            .filter_map(|(name, graph)| {
+                let event_id = name.strip_prefix("urn:epcis:event:")?;
+                let node = oxrdf::NamedNode::new_unchecked(format!("urn:epc:event:{}", event_id));
+                let mut fields = EventFields::new();
+                for triple in graph.triples_for_subject(&node) {
+                    let value = match triple.object {
+                        oxrdf::TermRef::BlankNode(blank) => {
+                            let mut parts: Vec<String> = graph
+                                .triples_for_subject(blank)
+                                .map(|inner| format!("{} {}", inner.predicate, inner.object))
+                                .collect();
+                            parts.sort();
+                            format!("[ {} ]", parts.join(" ; "))
+                        }
+                        object => object.to_string(),
+                    };
+                    fields.entry(triple.predicate.as_str().to_string()).or_default().insert(value);
+                }
+                Some((event_id.to_string(), fields))
+            })
+            .collect()
+    }
+    
+    /// Matches of the pattern rules over the event history and verification results as of `now`
+    pub fn evaluate_patterns(&self, rules: &[PatternRule], now: chrono::DateTime<chrono::Utc>) -> Vec<PatternMatch> {
+        patterns::evaluate_rules(rules, &self.lineage_events(), &self.verifications(), now)
+    }
+    
+    /// Replace the pattern graph with these matches, returning those not recorded before
+    pub fn materialize_pattern_matches(&mut self, matches: &[PatternMatch]) -> Result<Vec<PatternMatch>, EpcisKgError> {
+        self.ensure_writable()?;
+        let known: std::collections::HashSet<String> = self
+            .graphs
+            .get(EVENT_PATTERNS_GRAPH)
+            .map(|graph| {
+                graph
+                    .subjects_for_predicate_object(
+                        oxrdf::NamedNodeRef::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"),
+                        oxrdf::NamedNodeRef::new_unchecked("urn:epcglobal:epcis:PatternMatch"),
+                    )
+                    .map(|subject| match subject {
+                        oxrdf::SubjectRef::NamedNode(node) => node.as_str().to_string(),
+                        other => other.to_string(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let new_matches: Vec<PatternMatch> = matches
+            .iter()
+            .filter(|pattern_match| !known.contains(&pattern_match.iri()))
+            .cloned()
+            .collect();
+        
+        let mut view = OxrdfGraph::default();
+        for triple in patterns::match_triples(matches) {
+            view.insert(&triple);
+        }
+        self.replace_derived_graph(EVENT_PATTERNS_GRAPH, view);
+        
+        if self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        
+        Ok(new_matches)
+    }
+    
+    /// Record the outcome of a verification request for an EPC
+    pub fn record_verification(&mut self, verification: &Verification) -> Result<(), EpcisKgError> {
+        self.ensure_writable()?;
+        let epc = oxrdf::NamedNode::new(verification.epc.clone())?;
+        let time = match &verification.time {
+            Some(time) => chrono::DateTime::parse_from_rfc3339(time)
+                .map_err(|e| EpcisKgError::Validation(format!("Invalid verification time '{}': {}", time, e)))?
+                .to_rfc3339(),
+            None => chrono::Utc::now().to_rfc3339(),
+        };
+        let epcis = |local: &str| oxrdf::NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+        let node = oxrdf::NamedNode::new_unchecked(format!("urn:epcis:verification:{}", uuid::Uuid::new_v4()));
+        
+        let mut triples = vec![
+            oxrdf::Triple::new(node.clone(), epcis("verifies"), epc),
+            oxrdf::Triple::new(node.clone(), epcis("verified"), oxrdf::Literal::from(verification.verified)),
+            oxrdf::Triple::new(
+                node.clone(),
+                epcis("verificationTime"),
+                oxrdf::Literal::new_typed_literal(time, oxrdf::NamedNode::new_unchecked("http://www.w3.org/2001/XMLSchema#dateTime")),
+            ),
+        ];
+        if let Some(verifier) = &verification.verifier {
+            triples.push(oxrdf::Triple::new(node.clone(), epcis("verifier"), oxrdf::Literal::new_simple_literal(verifier.clone())));
+        }
+        if let Some(location) = &verification.location {
+            triples.push(oxrdf::Triple::new(node, epcis("verificationLocation"), oxrdf::NamedNode::new(location.clone())?));
+        }
+        let graph = self.graph_entry(VERIFICATIONS_GRAPH);
+        for triple in &triples {
+            graph.insert(triple);
+        }
+        
+        if self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        
+        Ok(())
+    }
+    
+    /// All recorded verification results, oldest first
+    pub fn verifications(&self) -> Vec<Verification> {
+        let Some(graph) = self.graphs.get(VERIFICATIONS_GRAPH) else {
+            return Vec::new();
+        };
+        let epcis = |local: &str| oxrdf::NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+        let literal = |subject: oxrdf::SubjectRef<'_>, local: &str| match graph.object_for_subject_predicate(subject, &epcis(local)) {
+            Some(oxrdf::TermRef::Literal(value)) => Some(value.value().to_string()),
+            _ => None,
+        };
+        
+        let mut verifications: Vec<Verification> = graph
+            .triples_for_predicate(&epcis("verifies"))
+            .filter_map(|triple| {
+                let oxrdf::TermRef::NamedNode(epc) = triple.object else { return None };
+                Some(Verification {
+                    epc: epc.as_str().to_string(),
+                    verified: literal(triple.subject, "verified").as_deref() == Some("true"),
+                    verifier: literal(triple.subject, "verifier"),
+                    location: match graph.object_for_subject_predicate(triple.subject, &epcis("verificationLocation")) {
+                        Some(oxrdf::TermRef::NamedNode(location)) => Some(location.as_str().to_string()),
+                        _ => None,
+                    },
+                    time: literal(triple.subject, "verificationTime"),
+                })
+            })
+            .collect();
+        verifications.sort_by(|a, b| a.time.cmp(&b.time).then_with(|| a.epc.cmp(&b.epc)));
+        verifications
+    }
+    
+    /// Run the counterfeit heuristics over the event history and verification results
+    pub fn detect_suspicion(&self, config: &CounterfeitConfig) -> Vec<SuspicionReport> {
+        counterfeit::detect_suspects(&self.lineage_events(), &self.verifications(), config)
+    }
+    
+    /// Replace the counterfeit graph with these suspicion scores
+    pub fn persist_suspicion(&mut self, reports: &[SuspicionReport]) -> Result<(), EpcisKgError> {
+        self.ensure_writable()?;
+        let epcis = |local: &str| oxrdf::NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+        let mut view = OxrdfGraph::default();
+        for report in reports {
+            let Ok(epc) = oxrdf::NamedNode::new(report.epc.as_str()) else { continue };
+            view.insert(&oxrdf::Triple::new(
+                epc.clone(),
+                epcis("suspicionScore"),
+                oxrdf::Literal::new_typed_literal(
+                    format!("{:.4}", report.score),
+                    oxrdf::NamedNode::new_unchecked("http://www.w3.org/2001/XMLSchema#decimal"),
+                ),
+            ));
+            for (i, signal) in report.signals.iter().enumerate() {
+                let node = oxrdf::NamedNode::new_unchecked(format!("{}:signal:{}", epc.as_str(), i));
+                view.insert(&oxrdf::Triple::new(epc.clone(), epcis("suspicionSignal"), node.clone()));
+                view.insert(&oxrdf::Triple::new(node.clone(), epcis("signalKind"), oxrdf::Literal::new_simple_literal(signal.kind.as_str())));
+                view.insert(&oxrdf::Triple::new(node, epcis("signalDetail"), oxrdf::Literal::new_simple_literal(signal.detail.clone())));
+            }
+        }
+        self.replace_derived_graph(COUNTERFEIT_GRAPH, view);
+        
+        if self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Shipping/receiving pairs in the event history with their estimated CO2e and cost
+    pub fn estimate_shipments(&self, config: &CarbonConfig) -> Vec<Shipment> {
+        carbon::estimate(carbon::detect_shipments(&self.lineage_events()), config)
+    }
+    
+    /// Replace the carbon graph with these shipments
+    pub fn materialize_shipments(&mut self, shipments: &[Shipment]) -> Result<(), EpcisKgError> {
+        self.ensure_writable()?;
+        let mut view = OxrdfGraph::default();
+        for triple in carbon::shipment_triples(shipments) {
+            view.insert(&triple);
+        }
+        self.replace_derived_graph(CARBON_GRAPH, view);
+        
+        if self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Persisted suspicion scores at or above `min_score`, highest first
+    pub fn suspects(&self, min_score: f64) -> Vec<SuspicionReport> {
+        let Some(graph) = self.graphs.get(COUNTERFEIT_GRAPH) else {
+            return Vec::new();
+        };
+        let score_predicate = oxrdf::NamedNode::new_unchecked(format!("{}suspicionScore", EPCIS_NS));
+        let mut reports: Vec<SuspicionReport> = graph
+            .triples_for_predicate(&score_predicate)
+            .filter_map(|triple| match triple.subject {
+                oxrdf::SubjectRef::NamedNode(epc) => self.suspicion(epc.as_str()),
+                _ => None,
+            })
+            .filter(|report| report.score >= min_score)
+            .collect();
+        reports.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.epc.cmp(&b.epc)));
+        reports
+    }
+    
+    /// Persisted suspicion score and signals of one EPC
+    pub fn suspicion(&self, epc: &str) -> Option<SuspicionReport> {
+        let graph = self.graphs.get(COUNTERFEIT_GRAPH)?;
+        let epcis = |local: &str| oxrdf::NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+        let subject = oxrdf::NamedNode::new(epc).ok()?;
+        let literal = |subject: oxrdf::NamedNodeRef<'_>, local: &str| match graph.object_for_subject_predicate(subject, &epcis(local)) {
+            Some(oxrdf::TermRef::Literal(value)) => Some(value.value().to_string()),
+            _ => None,
+        };
+        
+        let score = literal(subject.as_ref(), "suspicionScore")?.parse::<f64>().ok()?;
+        let mut signals: Vec<(String, SuspicionSignal)> = graph
+            .objects_for_subject_predicate(&subject, &epcis("suspicionSignal"))
+            .filter_map(|term| {
+                let oxrdf::TermRef::NamedNode(node) = term else { return None };
+                Some((
+                    node.as_str().to_string(),
+                    SuspicionSignal {
+                        kind: SignalKind::from_name(&literal(node, "signalKind")?)?,
+                        detail: literal(node, "signalDetail").unwrap_or_default(),
+                    },
+                ))
+            })
+            .collect();
+        signals.sort_by(|a, b| a.0.cmp(&b.0));
+        
+        Some(SuspicionReport {
+            epc: epc.to_string(),
+            score,
+            signals: signals.into_iter().map(|(_, signal)| signal).collect(),
+        })
+    }
+    
+    /// Lineage-relevant facts of every event not declared in error
+    fn lineage_events(&self) -> Vec<LineageEvent> {
+        let epcis = |local: &str| oxrdf::NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+        let rdf_type = oxrdf::NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type");
+        let term_value = |term: oxrdf::TermRef<'_>| match term {
+            oxrdf::TermRef::NamedNode(node) => node.as_str().to_string(),
+            oxrdf::TermRef::Literal(literal) => literal.value().to_string(),
+            other => other.to_string(),
+        };
+        let local_name = |iri: String| iri.rsplit(':').next().unwrap_or(&iri).to_string();
+        
+        let mut events = Vec::new();
+        for (name, graph) in &self.graphs {
+            let Some(event_id) = name.strip_prefix("urn:epcis:event:") else { continue };
+            if self.is_declared_in_error(event_id) {
+                continue;
+            }
+            
+            let event = oxrdf::NamedNode::new_unchecked(format!("urn:epc:event:{}", event_id));
+            let Some(event_time) = graph
+                .object_for_subject_predicate(&event, &epcis("eventTime"))
+                .map(term_value)
+                .and_then(|time| chrono::DateTime::parse_from_rfc3339(&time).ok())
+            else {
+                continue;
+            };
+            let values = |predicates: &[&str]| -> Vec<String> {
+                let mut values: Vec<String> = predicates
+                    .iter()
+                    .flat_map(|predicate| {
+                        graph
+                            .objects_for_subject_predicate(&event, &epcis(predicate))
+                            .map(term_value)
+                            .collect::<Vec<_>>()
+                    })
+                    .collect();
+                values.sort();
+                values.dedup();
+                values
+            };
+            let value = |predicate: &str| graph.object_for_subject_predicate(&event, &epcis(predicate)).map(term_value);
+            
+            events.push(LineageEvent {
+                event_id: event_id.to_string(),
+                event_type: graph
+                    .object_for_subject_predicate(&event, &rdf_type)
+                    .map(|t| local_name(term_value(t)))
+                    .unwrap_or_default(),
+                action: value("action").map(local_name).unwrap_or_default(),
+                event_time: event_time.with_timezone(&chrono::Utc),
+                biz_step: value("bizStep"),
+                biz_location: value("bizLocation"),
+                epcs: values(&["epcList", "childEPCs"]),
+                parent: value("parentID"),
+                inputs: values(&["inputEPCList"]),
+                outputs: values(&["outputEPCList"]),
+                destination_parties: values(&["destinationOwningParty", "destinationPossessingParty"]),
+                destination_locations: values(&["destinationLocation"]),
+            });
+        }
+        events
+    }
+    
+    /// Inventory-relevant facts of every event that has an action
+    fn inventory_events(&self) -> Vec<InventoryEvent> {
+        self.lineage_events()
+            .into_iter()
+            .filter(|event| !event.action.is_empty())
+            .map(|event| InventoryEvent {
+                event_id: event.event_id,
+                event_type: event.event_type,
+                action: event.action,
+                event_time: event.event_time,
+                biz_location: event.biz_location,
+                epcs: event.epcs,
+                parent: event.parent,
+            })
+            .collect()
+    }
+    
+    /// IDs of the events matching an EPCIS simple event query, ordered by event time
+    pub fn query_events(&self, query: &EpcisQuery) -> Vec<String> {
+        let xsd_time = |term: Option<oxrdf::TermRef<'_>>| match term {
+            Some(oxrdf::TermRef::Literal(literal)) => chrono::DateTime::parse_from_rfc3339(literal.value())
+                .ok()
+                .map(|time| time.with_timezone(&chrono::Utc)),
+            _ => None,
+        };
+        
+        let mut matches: Vec<(chrono::DateTime<chrono::Utc>, String)> = self
+            .lineage_events()
+            .into_iter()
+            .filter(|event| {
+                let event_node = oxrdf::NamedNode::new_unchecked(format!("urn:epc:event:{}", event.event_id));
+                let graph = self.graphs.get(&format!("urn:epcis:event:{}", event.event_id));
+                let object = |local: &str| {
+                    graph.and_then(|graph| {
+                        graph.object_for_subject_predicate(&event_node, &oxrdf::NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local)))
+                    })
+                };
+                let details = EventDetails {
+                    disposition: object("disposition").map(|term| match term {
+                        oxrdf::TermRef::NamedNode(node) => node.as_str().to_string(),
+                        other => other.to_string(),
+                    }),
+                    record_time: xsd_time(object("recordTime")),
+                    time_zone_offset: event_time_zone_offset(object("eventTimeZoneOffset"), object("eventTime")),
+                };
+                query.matches(event, &details)
+            })
+            .map(|event| (event.event_time, event.event_id))
+            .collect();
+        matches.sort();
+        matches.into_iter().map(|(_, event_id)| event_id).collect()
+    }
+    
+    /// Lifecycle status of a batch of EPCs, computed in one pass over the event history
+    pub fn epc_statuses(&self, epcs: &[String]) -> Vec<EpcStatus> {
+        let disposition = oxrdf::NamedNode::new_unchecked(format!("{}disposition", EPCIS_NS));
+        let dispositions: HashMap<String, String> = self
+            .graphs
+            .iter()
+            .filter_map(|(name, graph)| {
+                let event_id = name.strip_prefix("urn:epcis:event:")?;
+                let event = oxrdf::NamedNode::new_unchecked(format!("urn:epc:event:{}", event_id));
+                let value = match graph.object_for_subject_predicate(&event, &disposition)? {
+                    oxrdf::TermRef::NamedNode(node) => node.as_str().to_string(),
+                    other => other.to_string(),
+                };
+                Some((event_id.to_string(), value))
+            })
+            .collect();
+        epc_status::epc_statuses(&self.lineage_events(), &dispositions, epcs)
+    }
+    
+    /// Downstream impact of contaminated EPCs or lots (see `recall::simulate_recall`)
+    pub fn simulate_recall(&self, request: &RecallRequest) -> RecallReport {
+        recall::simulate_recall(&self.lineage_events(), request)
+    }
+    
+    /// Inventory positions as of a point in time, computed by replaying the event history
+    pub fn compute_inventory_snapshot(&self, as_of: chrono::DateTime<chrono::Utc>) -> InventorySnapshot {
+        inventory::compute_snapshot(&self.inventory_events(), as_of)
+    }
+    
+    /// Store a snapshot in its dated graph, replacing any earlier snapshot of the same day
+    pub fn store_inventory_snapshot(&mut self, snapshot: &InventorySnapshot) -> Result<(), EpcisKgError> {
+        self.ensure_writable()?;
+        let epcis = |local: &str| oxrdf::NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+        let xsd = |local: &str| oxrdf::NamedNode::new_unchecked(format!("http://www.w3.org/2001/XMLSchema#{}", local));
+        let rdf_type = oxrdf::NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type");
+        let graph_name = snapshot.graph_name();
+        let snapshot_node = oxrdf::NamedNode::new(graph_name.clone())?;
+        
+        let mut graph = OxrdfGraph::default();
+        graph.insert(&oxrdf::Triple::new(snapshot_node.clone(), rdf_type.clone(), epcis("InventorySnapshot")));
+        graph.insert(&oxrdf::Triple::new(
+            snapshot_node.clone(),
+            epcis("asOf"),
+            oxrdf::Literal::new_typed_literal(snapshot.as_of.clone(), xsd("dateTime")),
+        ));
+        graph.insert(&oxrdf::Triple::new(
+            snapshot_node.clone(),
+            epcis("eventsReplayed"),
+            oxrdf::Literal::new_typed_literal(snapshot.events_replayed.to_string(), xsd("integer")),
+        ));
+        
+        for (index, position) in snapshot.positions.iter().enumerate() {
+            let node = oxrdf::NamedNode::new(format!("{}:position:{}", graph_name, index))?;
+            graph.insert(&oxrdf::Triple::new(node.clone(), rdf_type.clone(), epcis("InventoryPosition")));
+            graph.insert(&oxrdf::Triple::new(node.clone(), epcis("snapshot"), snapshot_node.clone()));
+            graph.insert(&oxrdf::Triple::new(node.clone(), epcis("location"), oxrdf::NamedNode::new(position.location.clone())?));
+            graph.insert(&oxrdf::Triple::new(
+                node.clone(),
+                epcis("productClass"),
+                oxrdf::Literal::new_simple_literal(position.product_class.clone()),
+            ));
+            graph.insert(&oxrdf::Triple::new(
+                node,
+                epcis("quantity"),
+                oxrdf::Literal::new_typed_literal(position.quantity.to_string(), xsd("integer")),
+            ));
+        }
+        
+        self.put_graph(graph_name, graph);
+        
+        if self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Dates (YYYY-MM-DD) that have a stored inventory snapshot, oldest first
+    pub fn inventory_snapshot_dates(&self) -> Vec<String> {
+        let mut dates: Vec<String> = self
+            .graphs
+            .keys()
+            .filter_map(|name| name.strip_prefix(INVENTORY_SNAPSHOT_PREFIX))
+            .map(str::to_string)
+            .collect();
+        dates.sort();
+        dates
+    }
+    
+    /// Read a stored snapshot without replaying any events
+    pub fn inventory_snapshot(&self, date: &str) -> Option<InventorySnapshot> {
+        let graph_name = inventory::snapshot_graph_name(date);
+        let graph = self.graphs.get(&graph_name)?;
+        let epcis = |local: &str| oxrdf::NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+        let snapshot_node = oxrdf::NamedNode::new_unchecked(graph_name.as_str());
+        let literal = |subject: oxrdf::SubjectRef<'_>, predicate: &str| match graph.object_for_subject_predicate(subject, &epcis(predicate)) {
+            Some(oxrdf::TermRef::Literal(literal)) => Some(literal.value().to_string()),
+            Some(oxrdf::TermRef::NamedNode(node)) => Some(node.as_str().to_string()),
+            _ => None,
+        };
+        
+        let mut positions: Vec<InventoryPosition> = graph
+            .subjects_for_predicate_object(&epcis("snapshot"), &snapshot_node)
+            .filter_map(|position| {
+                Some(InventoryPosition {
+                    location: literal(position, "location")?,
+                    product_class: literal(position, "productClass")?,
+                    quantity: literal(position, "quantity")?.parse().ok()?,
+                })
+            })
+            .collect();
+        positions.sort_by(|a, b| (&a.location, &a.product_class).cmp(&(&b.location, &b.product_class)));
+        
+        Some(InventorySnapshot {
+            date: date.to_string(),
+            as_of: literal(snapshot_node.as_ref().into(), "asOf").unwrap_or_default(),
+            events_replayed: literal(snapshot_node.as_ref().into(), "eventsReplayed")
+                .and_then(|count| count.parse().ok())
+                .unwrap_or(0),
+            positions,
+        })
+    }
+    
+    /// Keep the record of a materialization run
+    pub fn record_inference_run(&mut self, run: &InferenceRun) -> Result<(), EpcisKgError> {
+        self.ensure_writable()?;
+        let epcis = |local: &str| oxrdf::NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+        let xsd = |local: &str| oxrdf::NamedNode::new_unchecked(format!("http://www.w3.org/2001/XMLSchema#{}", local));
+        let node = oxrdf::NamedNode::new(format!("urn:epcis:inference_run:{}", run.id))?;
+        let record = serde_json::to_string(run)?;
+        
+        let triples = vec![
+            oxrdf::Triple::new(
+                node.clone(),
+                oxrdf::NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"),
+                epcis("InferenceRun"),
+            ),
+            oxrdf::Triple::new(node.clone(), epcis("startedAt"), oxrdf::Literal::new_typed_literal(run.started_at.to_rfc3339(), xsd("dateTime"))),
+            oxrdf::Triple::new(node.clone(), epcis("strategy"), oxrdf::Literal::new_simple_literal(&run.strategy)),
+            oxrdf::Triple::new(
+                node.clone(),
+                epcis("materializedTriples"),
+                oxrdf::Literal::new_typed_literal(run.materialized_triples.to_string(), xsd("integer")),
+            ),
+            oxrdf::Triple::new(node.clone(), epcis("durationMs"), oxrdf::Literal::new_typed_literal(run.duration_ms.to_string(), xsd("integer"))),
+            oxrdf::Triple::new(
+                node,
+                epcis("runRecord"),
+                oxrdf::Literal::new_typed_literal(record, oxrdf::NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#JSON")),
+            ),
+        ];
+        let graph = self.graph_entry(INFERENCE_RUNS_GRAPH);
+        for triple in &triples {
+            graph.insert(triple);
+        }
+        
+        if self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        Ok(())
+    }
+    
+    /// Recorded materialization runs, oldest first
+    pub fn inference_runs(&self) -> Vec<InferenceRun> {
+        let Some(graph) = self.graphs.get(INFERENCE_RUNS_GRAPH) else { return Vec::new() };
+        let run_record = oxrdf::NamedNode::new_unchecked(format!("{}runRecord", EPCIS_NS));
+        let mut runs: Vec<InferenceRun> = graph
+            .triples_for_predicate(&run_record)
+            .filter_map(|triple| match triple.object {
+                oxrdf::TermRef::Literal(literal) => serde_json::from_str(literal.value()).ok(),
+                _ => None,
+            })
+            .collect();
+        runs.sort_by(|a, b| (a.started_at, &a.id).cmp(&(b.started_at, &b.id)));
+        runs
+    }
+    
+    /// Graphs holding the triples of an event (its own event graph and any graph describing it)
+    pub fn event_graphs(&self, event_id: &str) -> Vec<String> {
+        let own_graph = format!("urn:epcis:event:{}", event_id);
+        let event_node = oxrdf::NamedNode::new_unchecked(format!("urn:epc:event:{}", event_id));
+        
+        let mut graphs: Vec<String> = self
+            .graphs
+            .iter()
+            .filter(|(name, _)| !is_derived_graph(name))
+            .filter(|(name, graph)| {
+                **name == own_graph || graph.triples_for_subject(&event_node).next().is_some()
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        graphs.sort();
+        graphs
+    }
+    
+    /// Check whether an event has been declared in error
+    pub fn is_declared_in_error(&self, event_id: &str) -> bool {
+        let event_node = oxrdf::NamedNode::new_unchecked(format!("urn:epc:event:{}", event_id));
+        let predicate = oxrdf::NamedNode::new_unchecked(format!("{}errorDeclaration", EPCIS_NS));
+        self.graphs
+            .get(ERROR_DECLARATIONS_GRAPH)
+            .map(|graph| graph.object_for_subject_predicate(&event_node, &predicate).is_some())
+            .unwrap_or(false)
+    }
+    
+    /// Declare an event in error following EPCIS 2.0 error-declaration semantics.
+    ///
+    /// The original event triples are kept, its graphs are marked as declared
+    /// in error, the correction is recorded in the error declarations graph and
+    /// inferences that were only supported by the event are retracted.
+    pub fn declare_event_in_error(&mut self, declaration: &ErrorDeclaration) -> Result<ErrorDeclarationResult, EpcisKgError> {
+        self.ensure_writable()?;
+        let declared_graphs = self.event_graphs(&declaration.event_id);
+        if declared_graphs.is_empty() {
+            return Err(EpcisKgError::NotFound { resource: "Event".to_string(), id: declaration.event_id.clone() });
+        }
+        
+        let declaration_time = declaration
+            .declaration_time
+            .clone()
+            .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+        let reason = declaration.reason_uri();
+        
+        let event_node = oxrdf::NamedNode::new(format!("urn:epc:event:{}", declaration.event_id))?;
+        let declaration_node = oxrdf::NamedNode::new(format!("urn:epcis:error_declaration:{}", declaration.event_id))?;
+        let epcis = |local: &str| oxrdf::NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+        
+        let mut triples = vec![
+            oxrdf::Triple::new(event_node.clone(), epcis("errorDeclaration"), declaration_node.clone()),
+            oxrdf::Triple::new(
+                declaration_node.clone(),
+                oxrdf::NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"),
+                epcis("ErrorDeclaration"),
+            ),
+            oxrdf::Triple::new(
+                declaration_node.clone(),
+                epcis("declarationTime"),
+                oxrdf::Literal::new_typed_literal(
+                    declaration_time.clone(),
+                    oxrdf::NamedNode::new_unchecked("http://www.w3.org/2001/XMLSchema#dateTime"),
+                ),
+            ),
+            oxrdf::Triple::new(declaration_node.clone(), epcis("reason"), oxrdf::NamedNode::new(reason.clone())?),
+        ];
+        for corrective_id in &declaration.corrective_event_ids {
+            triples.push(oxrdf::Triple::new(
+                declaration_node.clone(),
+                epcis("correctiveEventID"),
+                oxrdf::Literal::new_simple_literal(corrective_id.clone()),
+            ));
+        }
+        for graph_name in &declared_graphs {
+            triples.push(oxrdf::Triple::new(
+                oxrdf::NamedNode::new(graph_name.clone())?,
+                epcis("declaredInError"),
+                oxrdf::Literal::from(true),
+            ));
+        }
+        
+        let declarations = self.graph_entry(ERROR_DECLARATIONS_GRAPH);
+        for triple in &triples {
+            declarations.insert(triple.as_ref());
+        }
+        
+        let retracted_inferences = self.retract_unsupported_inferences(&declaration.event_id, &event_node);
+        self.refresh_ownership_view();
+        self.refresh_expiry_view();
+        self.refresh_views_reading(&[ERROR_DECLARATIONS_GRAPH.to_string()]);
+        
+        if self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        
+        Ok(ErrorDeclarationResult {
+            event_id: declaration.event_id.clone(),
+            declaration_time,
+            reason,
+            declared_graphs,
+            corrective_event_ids: declaration.corrective_event_ids.clone(),
+            retracted_inferences,
+        })
+    }
+    
+    /// Truth maintenance after an error declaration.
+    ///
+    /// Inferred triples about the event itself lose their only justification.
+    /// Inferred triples about resources the event referenced (EPCs, locations)
+    /// are retracted too unless another event that is not declared in error
+    /// still references them. RDF-star annotations quoting a retracted triple
+    /// are removed along with it.
+    fn retract_unsupported_inferences(&mut self, event_id: &str, event_node: &oxrdf::NamedNode) -> usize {
+        let event_graph = format!("urn:epcis:event:{}", event_id);
+        let mut unsupported: std::collections::HashSet<String> = std::collections::HashSet::new();
+        unsupported.insert(event_node.as_str().to_string());
+        
+        if let Some(graph) = self.graphs.get(&event_graph) {
+            for triple in graph.triples_for_subject(event_node) {
+                if let oxrdf::TermRef::NamedNode(resource) = triple.object {
+                    if !self.is_supported_by_other_event(resource, event_id) {
+                        unsupported.insert(resource.as_str().to_string());
+                    }
+                }
+            }
+        }
+        
+        let mentions_unsupported = |triple: oxrdf::TripleRef<'_>| -> bool {
+            let subject = matches!(triple.subject, oxrdf::SubjectRef::NamedNode(n) if unsupported.contains(n.as_str()));
+            let object = matches!(triple.object, oxrdf::TermRef::NamedNode(n) if unsupported.contains(n.as_str()));
+            subject || object
+        };
+        
+        // The declaration just recorded mentions the event, but is not an inference
+        let mut retracted = 0;
+        let stale: Vec<(String, Vec<oxrdf::Triple>)> = self
+            .graphs
+            .iter()
+            .filter(|(name, _)| is_derived_graph(name) && name.as_str() != ERROR_DECLARATIONS_GRAPH)
+            .map(|(name, graph)| {
+                let stale: Vec<oxrdf::Triple> = graph
+                    .iter()
+                    .filter(|triple| match triple.subject {
+                        oxrdf::SubjectRef::Triple(quoted) => mentions_unsupported(quoted.as_ref()),
+                        _ => mentions_unsupported(*triple),
+                    })
+                    .map(|triple| triple.into_owned())
+                    .collect();
+                (name.clone(), stale)
+            })
+            .filter(|(_, stale)| !stale.is_empty())
+            .collect();
+        for (name, stale) in stale {
+            let Some(graph) = self.graph_mut(&name) else { continue };
+            for triple in &stale {
+                graph.remove(triple.as_ref());
+                if !matches!(triple.subject, oxrdf::Subject::Triple(_)) {
+                    retracted += 1;
+                }
+            }
+        }
+        
+        retracted
+    }
+    
+    /// Remove materialized triples about any of `subjects`, and the RDF-star
+    /// provenance annotations quoting them; returns the inferred triples removed
+    pub fn retract_inferences_about(&mut self, subjects: &std::collections::HashSet<String>) -> Result<usize, EpcisKgError> {
+        self.ensure_writable()?;
+        let about = |triple: oxrdf::TripleRef<'_>| matches!(triple.subject, oxrdf::SubjectRef::NamedNode(n) if subjects.contains(n.as_str()));
+        
+        let mut retracted = 0;
+        let mut stale_by_graph = Vec::new();
+        for (name, graph) in &self.graphs {
+            let stale: Vec<oxrdf::Triple> = if MATERIALIZED_GRAPHS.contains(&name.as_str()) {
+                graph.iter().filter(|triple| about(*triple)).map(|triple| triple.into_owned()).collect()
+            } else if name == PROVENANCE_GRAPH {
+                graph
+                    .iter()
+                    .filter(|triple| matches!(triple.subject, oxrdf::SubjectRef::Triple(quoted) if about(quoted.as_ref())))
+                    .map(|triple| triple.into_owned())
+                    .collect()
+            } else {
+                continue;
+            };
+            if !stale.is_empty() {
+                stale_by_graph.push((name.clone(), stale));
+            }
+        }
+        for (name, stale) in stale_by_graph {
+            let Some(graph) = self.graph_mut(&name) else { continue };
+            for triple in &stale {
+                graph.remove(triple.as_ref());
+            }
+            if name != PROVENANCE_GRAPH {
+                retracted += stale.len();
+            }
+        }
+        
+        if retracted > 0 && self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        Ok(retracted)
+    }
+    
+    /// Delete data past the TTL of its graph's policy: whole graphs, or the
+    /// triples of expired subjects, then the inferences about what was deleted
+    pub fn expire_by_ttl(&mut self, policies: &[GraphTtlPolicy], now: chrono::DateTime<chrono::Utc>) -> Result<TtlSweepReport, EpcisKgError> {
+        self.ensure_writable()?;
+        let mut report = TtlSweepReport::default();
+        let mut expired_subjects: std::collections::HashSet<oxrdf::Subject> = std::collections::HashSet::new();
+        let mut swept = Vec::new();
+        
+        let expired_by_graph: Vec<(String, Vec<oxrdf::Triple>)> = self
+            .graphs
+            .iter()
+            .filter_map(|(name, graph)| Some((name.clone(), graph_ttl::expired_triples(graph, graph_ttl::policy_for(policies, name)?, now))))
+            .filter(|(_, expired)| !expired.is_empty())
+            .collect();
+        for (name, expired) in expired_by_graph {
+            let Some(graph) = self.graph_mut(&name) else { continue };
+            for triple in &expired {
+                graph.remove(triple.as_ref());
+            }
+            report.triples_removed += expired.len();
+            expired_subjects.extend(expired.into_iter().map(|triple| triple.subject));
+            swept.push(name.clone());
+            if graph.is_empty() {
+                report.graphs_removed.push(name.clone());
+            }
+        }
+        for name in &report.graphs_removed {
+            self.take_graph(name);
+        }
+        report.graphs_removed.sort();
+        report.subjects_expired = expired_subjects.len();
+        
+        let about: std::collections::HashSet<String> = expired_subjects
+            .iter()
+            .filter_map(|subject| match subject {
+                oxrdf::Subject::NamedNode(node) => Some(node.as_str().to_string()),
+                _ => None,
+            })
+            .collect();
+        if !about.is_empty() {
+            report.inferences_retracted = self.retract_inferences_about(&about)?;
+        }
+        if swept.iter().any(|name| name.starts_with("urn:epcis:event:")) {
+            self.refresh_ownership_view();
+            self.refresh_expiry_view();
+        }
+        self.refresh_views_reading(&swept);
+        if report.triples_removed > 0 && self.storage_path != ":memory:" {
+            self.save_graphs()?;
+        }
+        Ok(report)
+    }
+    
+    /// Whether a resource is referenced by an event other than `event_id` that is not declared in error
+    fn is_supported_by_other_event(&self, resource: oxrdf::NamedNodeRef<'_>, event_id: &str) -> bool {
+        self.graphs
+            .iter()
+            .filter_map(|(name, graph)| name.strip_prefix("urn:epcis:event:").map(|id| (id, graph)))
+            .filter(|(id, _)| *id != event_id && !self.is_declared_in_error(id))
+            .any(|(_, graph)| graph.triples_for_object(resource).next().is_some())
+    }
+    
+    /// Export all data as Turtle format
+    pub fn export_turtle(&self) -> Result<String, EpcisKgError> {
+        let mut turtle_output = String::new();
+        
+        for (graph_name, graph) in &self.graphs {
+            turtle_output.push_str(&format!("# Graph: {}\n", graph_name));
+            for triple in graph.iter() {
+                turtle_output.push_str(&format!("{} .\n", format_triple_star(triple)));
+            }
+            turtle_output.push('\n');
+        }
+        
+        Ok(turtle_output)
+    }
+    
+    /// Export all data with identifiers and names pseudonymized for sharing
+    pub fn export_pseudonymized(&self, pseudonymizer: &Pseudonymizer) -> Result<String, EpcisKgError> {
+        let mut output = String::new();
+        
+        for (graph_name, triples) in self.pseudonymized_graphs(pseudonymizer) {
+            output.push_str(&format!("# Graph: {}\n", graph_name));
+            for triple in &triples {
+                output.push_str(&format!("{} .\n", format_triple_star(triple.as_ref())));
+            }
+            output.push('\n');
+        }
+        
+        Ok(output)
+    }
+    
+    /// Every named graph by name, pseudonymized except for the excluded graphs
+    fn pseudonymized_graphs(&self, pseudonymizer: &Pseudonymizer) -> Vec<(String, Vec<oxrdf::Triple>)> {
+        let mut graph_names: Vec<&String> = self.graphs.keys().collect();
+        graph_names.sort();
+        
+        graph_names
+            .into_iter()
+            .map(|graph_name| {
+                let graph = &self.graphs[graph_name];
+                if pseudonymizer.is_excluded_graph(graph_name) {
+                    return (graph_name.clone(), graph.iter().map(|triple| triple.into_owned()).collect());
+                }
+                let triples = graph.iter().filter_map(|triple| pseudonymizer.pseudonymize_triple(triple)).collect();
+                (pseudonymizer.pseudonymize_identifier(graph_name), triples)
+            })
+            .collect()
+    }
+    
+    /// Every stored triple as a quad in its named graph
+    pub fn quads(&self) -> Vec<oxrdf::Quad> {
+        self.graphs
+            .iter()
+            .flat_map(|(graph_name, graph)| {
+                let name = graph_name_node(graph_name);
+                graph.iter().map(move |triple| triple.into_owned().in_graph(name.clone()))
+            })
+            .collect()
+    }
+
+    /// Quads matching a pattern, `None` being a wildcard for that position.
+    ///
+    /// Each graph is searched through its subject, predicate or object index,
+    /// whichever the bound positions allow, so lookups with any position bound
+    /// do not scan the graph.
+    pub fn quads_for_pattern(
+        &self,
+        subject: Option<oxrdf::SubjectRef<'_>>,
+        predicate: Option<oxrdf::NamedNodeRef<'_>>,
+        object: Option<oxrdf::TermRef<'_>>,
+        graph_name: Option<&str>,
+    ) -> Vec<oxrdf::Quad> {
+        self.graphs_for_pattern(graph_name)
+            .flat_map(|(name, graph)| {
+                let name = graph_name_node(name);
+                triples_for_pattern(graph, subject, predicate, object)
+                    .map(move |triple| triple.into_owned().in_graph(name.clone()))
+            })
+            .collect()
+    }
+
+    /// Whether any quad matches the pattern; stops at the first match
+    pub fn contains_pattern(
+        &self,
+        subject: Option<oxrdf::SubjectRef<'_>>,
+        predicate: Option<oxrdf::NamedNodeRef<'_>>,
+        object: Option<oxrdf::TermRef<'_>>,
+        graph_name: Option<&str>,
+    ) -> bool {
+        self.graphs_for_pattern(graph_name)
+            .any(|(_, graph)| triples_for_pattern(graph, subject, predicate, object).next().is_some())
+    }
+
+    fn graphs_for_pattern<'a>(&'a self, graph_name: Option<&str>) -> Box<dyn Iterator<Item = (&'a String, &'a OxrdfGraph)> + 'a> {
+        match graph_name {
+            Some(name) => Box::new(self.graphs.get_key_value(name).into_iter()),
+            None => Box::new(self.graphs.iter()),
+        }
+    }
+
+    /// One page of the triples in derived graphs, filtered by graph, predicate and subject prefix
+    pub fn materialized_triples(&self, filter: &MaterializedFilter) -> Result<MaterializedPage, EpcisKgError> {
+        let predicate = filter
+            .predicate
+            .as_deref()
+            .map(oxrdf::NamedNodeRef::new)
+            .transpose()
+            .map_err(|e| EpcisKgError::InvalidField { field: "predicate".to_string(), message: e.to_string() })?;
+        let subject_matches = |subject: oxrdf::SubjectRef<'_>| match (&filter.subject_prefix, subject) {
+            (None, _) => true,
+            (Some(prefix), oxrdf::SubjectRef::NamedNode(node)) => node.as_str().starts_with(prefix.as_str()),
+            (Some(_), _) => false,
+        };
+        
+        let mut triples = Vec::new();
+        for (name, graph) in &self.graphs {
+            if !is_derived_graph(name) || name == ERROR_DECLARATIONS_GRAPH || name == INFERENCE_RUNS_GRAPH {
+                continue;
+            }
+            if filter.graph.as_ref().is_some_and(|wanted| wanted != name) {
+                continue;
+            }
+            for triple in triples_for_pattern(graph, None, predicate, None) {
+                if subject_matches(triple.subject) {
+                    triples.push(MaterializedTriple {
+                        graph: name.clone(),
+                        subject: triple.subject.to_string(),
+                        predicate: triple.predicate.to_string(),
+                        object: triple.object.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(MaterializedPage::paginate(triples, filter.offset, filter.limit))
+    }
+    
+    /// Export all data as canonical N-Quads (RDFC-1.0), byte-identical for the same dataset
+    pub fn export_canonical_nquads(&self) -> Result<String, EpcisKgError> {
+        canonical::canonical_nquads(&self.quads())
+    }
+    
+    /// Canonical N-Quads of the pseudonymized dataset
+    pub fn export_pseudonymized_nquads(&self, pseudonymizer: &Pseudonymizer) -> Result<String, EpcisKgError> {
+        let quads: Vec<oxrdf::Quad> = self
+            .pseudonymized_graphs(pseudonymizer)
+            .into_iter()
+            .flat_map(|(graph_name, triples)| {
+                let graph = graph_name_node(&graph_name);
+                triples.into_iter().map(move |triple| triple.in_graph(graph.clone()))
+            })
+            .collect();
+        canonical::canonical_nquads(&quads)
+    }
+    
+    /// Get query variables from SPARQL query string (simplified parsing)
+    fn get_query_variables(&self, query: &str) -> Result<Vec<String>, EpcisKgError> {
+        // Extract variables from the SELECT clause more accurately
+        // ASCII upper-casing keeps byte offsets valid for slicing the original query
+        let query_upper = query.to_ascii_uppercase();
+        let select_start = query_upper.find("SELECT").ok_or_else(|| {
+            EpcisKgError::Query("No SELECT clause found in query".to_string())
+        })?;
+        let variables_start = select_start + "SELECT".len();
+        
+        // Find the WHERE clause or end of SELECT variables
+        let where_pos = query_upper[variables_start..]
+            .find("WHERE")
+            .map_or(query.len(), |pos| variables_start + pos);
+        let select_clause = &query[variables_start..where_pos].trim();
+        
+        // Parse variables from SELECT clause
+        let mut vars = Vec::new();
+        let mut in_distinct = false;
+        
+        for token in select_clause.split_whitespace() {
+            let token_upper = token.to_uppercase();
+            
+            if token_upper == "DISTINCT" {
+                in_distinct = true;
+                continue;
+            }
+            
+            if token_upper == "REDUCED" || token_upper == "*" {
+                // Handle REDUCED or wildcard - return all common variables
+                return Ok(vec!["s".to_string(), "p".to_string(), "o".to_string()]);
+            }
+            
+            if token.starts_with('?') {
+                let var_name = token[1..].to_string();
+                if !vars.contains(&var_name) {
+                    vars.push(var_name);
+                }
+            }
+        }
+        
+        // If no variables found in SELECT clause, default to s, p, o
+        if vars.is_empty() {
+            vars = vec!["s".to_string(), "p".to_string(), "o".to_string()];
+        }
+        
+        Ok(vars)
+    }
+    
+    /// Parse LIMIT clause from SPARQL query string
+    fn parse_limit_clause(&self, query: &str) -> Result<usize, EpcisKgError> {
+        // Look for LIMIT clause in the query
+        let query_upper = query.to_ascii_uppercase();
+        if let Some(limit_pos) = query_upper.find("LIMIT") {
+            // Get the part after LIMIT
+            let after_limit = &query[limit_pos + 5..];
+            // Extract the number (simplified - just take the first sequence of digits)
+            let limit_str = after_limit.trim().split_whitespace().next().unwrap_or("0");
+            // Parse the number
+            limit_str.parse::<usize>().map_err(|_| {
+                EpcisKgError::Query(format!("Invalid LIMIT value: {}", limit_str))
+            })
+        } else {
+            // No LIMIT specified, return 0 (unlimited)
+            Ok(0)
+        }
+    }
+
+    /// Parse all VALUES data blocks from SPARQL query string
+    ///
+    /// Supports both the single variable form `VALUES ?epc { <a> <b> }` and the
+    /// multi variable form `VALUES (?epc ?loc) { (<a> <x>) (<b> UNDEF) }`.
+    /// Prefixed names are expanded using the query's PREFIX declarations.
+    fn parse_values_clauses(&self, query: &str) -> Result<Vec<ValuesClause>, EpcisKgError> {
+        let query_upper = query.to_ascii_uppercase();
+        let prefixes = Self::parse_query_prefixes(query);
+        let mut clauses = Vec::new();
+        let mut search_from = 0;
+
+        while let Some(offset) = query_upper[search_from..].find("VALUES") {
+            let keyword_pos = search_from + offset;
+            let after_keyword = keyword_pos + "VALUES".len();
+            search_from = after_keyword;
+
+            // Skip matches inside longer identifiers (e.g. ?myValues)
+            let preceded_by_word = query[..keyword_pos]
+                .chars()
+                .next_back()
+                .map_or(false, |c| c.is_alphanumeric() || c == '_' || c == '?' || c == '$');
+            if preceded_by_word {
+                continue;
+            }
+
+            let body_start = query[after_keyword..].find('{').map(|p| after_keyword + p).ok_or_else(|| {
+                EpcisKgError::Query("VALUES clause is missing its data block".to_string())
+            })?;
+            let body_end = query[body_start..].find('}').map(|p| body_start + p).ok_or_else(|| {
+                EpcisKgError::Query("VALUES data block is not closed".to_string())
+            })?;
+
+            let variables: Vec<String> = query[after_keyword..body_start]
+                .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+                .filter(|token| !token.is_empty())
+                .map(|token| {
+                    token
+                        .strip_prefix('?')
+                        .or_else(|| token.strip_prefix('$'))
+                        .map(|name| name.to_string())
+                        .ok_or_else(|| EpcisKgError::Query(format!("Invalid VALUES variable: {}", token)))
+                })
+                .collect::<Result<_, _>>()?;
+
+            if variables.is_empty() {
+                return Err(EpcisKgError::Query("VALUES clause declares no variables".to_string()));
+            }
+
+            let tokens = Self::tokenize_values_block(&query[body_start + 1..body_end])?;
+            let mut rows = Vec::new();
+
+            if variables.len() == 1 {
+                for token in tokens {
+                    rows.push(vec![Self::normalize_values_term(&token, &prefixes)]);
+                }
+            } else {
+                let mut current: Option<Vec<Option<String>>> = None;
+                for token in tokens {
+                    match token.as_str() {
+                        "(" => current = Some(Vec::new()),
+                        ")" => {
+                            let row = current.take().ok_or_else(|| {
+                                EpcisKgError::Query("Unbalanced parentheses in VALUES block".to_string())
+                            })?;
+                            if row.len() != variables.len() {
+                                return Err(EpcisKgError::Query(format!(
+                                    "VALUES row has {} values but {} variables were declared",
+                                    row.len(),
+                                    variables.len()
+                                )));
+                            }
+                            rows.push(row);
+                        }
+                        _ => match current.as_mut() {
+                            Some(row) => row.push(Self::normalize_values_term(&token, &prefixes)),
+                            None => {
+                                return Err(EpcisKgError::Query(format!(
+                                    "VALUES value outside of a row: {}",
+                                    token
+                                )))
+                            }
+                        },
+                    }
+                }
+            }
+
+            clauses.push(ValuesClause { variables, rows });
+            search_from = body_end;
+        }
+
+        Ok(clauses)
+    }
+
+    /// Parse graph IRIs from FROM and FROM NAMED clauses
+    fn parse_dataset_clauses(query: &str) -> Vec<String> {
+        let tokens: Vec<&str> = query.split_whitespace().collect();
+        let mut graphs = Vec::new();
+
+        for (i, token) in tokens.iter().enumerate() {
+            if !token.eq_ignore_ascii_case("FROM") {
+                continue;
+            }
+            let mut next = i + 1;
+            if tokens.get(next).is_some_and(|t| t.eq_ignore_ascii_case("NAMED")) {
+                next += 1;
+            }
+            if let Some(iri) = tokens.get(next).and_then(|t| t.strip_prefix('<')).and_then(|t| t.strip_suffix('>')) {
+                graphs.push(iri.to_string());
+            }
+        }
+
+        graphs
+    }
+
+    /// Parse PREFIX declarations from SPARQL query string
+    fn parse_query_prefixes(query: &str) -> HashMap<String, String> {
+        let mut prefixes = HashMap::new();
+        let tokens: Vec<&str> = query.split_whitespace().collect();
+
+        for window in tokens.windows(3) {
+            if window[0].eq_ignore_ascii_case("PREFIX") {
+                if let Some(iri) = window[2].strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+                    prefixes.insert(window[1].trim_end_matches(':').to_string(), iri.to_string());
+                }
+            }
+        }
+
+        prefixes
+    }
+
+    /// Split a VALUES data block into terms and row delimiters
+    fn tokenize_values_block(block: &str) -> Result<Vec<String>, EpcisKgError> {
+        let mut tokens = Vec::new();
+        let mut chars = block.chars().peekable();
+
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+            } else if c == '(' || c == ')' {
+                tokens.push(c.to_string());
+                chars.next();
+            } else if c == '<' {
+                let mut token = String::new();
+                for next in chars.by_ref() {
+                    token.push(next);
+                    if next == '>' {
+                        break;
+                    }
+                }
+                if !token.ends_with('>') {
+                    return Err(EpcisKgError::Query(format!("Unterminated IRI in VALUES block: {}", token)));
+                }
+                tokens.push(token);
+            } else if c == '"' || c == '\'' {
+                let quote = c;
+                let mut token = String::new();
+                token.push(quote);
+                chars.next();
+                let mut closed = false;
+                while let Some(next) = chars.next() {
+                    token.push(next);
+                    if next == '\\' {
+                        if let Some(escaped) = chars.next() {
+                            token.push(escaped);
+                        }
+                    } else if next == quote {
+                        closed = true;
+                        break;
+                    }
+                }
+                if !closed {
+                    return Err(EpcisKgError::Query(format!("Unterminated literal in VALUES block: {}", token)));
+                }
+                // Keep language tags and datatypes attached to the literal
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || next == ')' {
+                        break;
+                    }
+                    token.push(next);
+                    chars.next();
+                }
+                tokens.push(token);
+            } else {
+                let mut token = String::new();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || next == '(' || next == ')' {
+                        break;
+                    }
+                    token.push(next);
+                    chars.next();
+                }
+                tokens.push(token);
+            }
+        }
+
+        Ok(tokens)
+    }
+
+    /// Normalize a VALUES term to the lexical value used in JSON bindings
+    fn normalize_values_term(token: &str, prefixes: &HashMap<String, String>) -> Option<String> {
+        if token.eq_ignore_ascii_case("UNDEF") {
+            return None;
+        }
+        if token.starts_with('<') || token.starts_with('"') || token.starts_with('\'') {
+            return Some(normalize_term_value(token));
+        }
+        if let Some((prefix, local)) = token.split_once(':') {
+            if let Some(namespace) = prefixes.get(prefix) {
+                return Some(format!("{}{}", namespace, local));
+            }
+        }
+        Some(token.to_string())
+    }
+    
+    /// Load graphs from persistent storage, parsing the graph files in place when
+    /// `mmap` is set, along with the digest of each graph file's Turtle
+    fn load_graphs(path: &Path, mmap: bool, keyring: Option<&StoreKeyring>) -> Result<(HashMap<String, OxrdfGraph>, HashMap<String, [u8; 32]>), EpcisKgError> {
+        let metadata_path = path.join("store_metadata.json");
+        
+        if metadata_path.exists() {
+            // Don't read a directory halfway through another copy's save
+            let lock = directory_write_lock(path);
+            let _saving = lock.lock();
+            
+            // Load existing data
+            let metadata_content = std::fs::read_to_string(&metadata_path)?;
+            let metadata: StoreMetadata = serde_json::from_str(&metadata_content)?;
+            
+            let mut graphs = HashMap::new();
+            let mut digests = HashMap::new();
+            
+            for graph_name in &metadata.graphs {
+                // Read-only datasets are not migrated, so they may still use the old names
+                let graph_path = std::iter::once(graph_file(path, graph_name))
+                    .chain(legacy_graph_file(path, graph_name))
+                    .find(|graph_path| graph_path.is_file());
+                let Some(graph_path) = graph_path else {
+                    continue;
+                };
+                let not_utf8 = |e: std::str::Utf8Error| EpcisKgError::Storage(format!("{} is not UTF-8: {}", graph_path.display(), e));
+                let mut parse = |content: &[u8]| -> Result<OxrdfGraph, EpcisKgError> {
+                    digests.insert(graph_name.clone(), Sha256::digest(content).into());
+                    Self::parse_turtle_to_graph(std::str::from_utf8(content).map_err(not_utf8)?)
+                };
+                let graph = if mmap {
+                    let file = std::fs::File::open(&graph_path)?;
+                    // SAFETY: the mapping is only read during this parse, and the
+                    // dataset is opened read-only, so nothing in this process writes the file
+                    let mapped = unsafe { memmap2::Mmap::map(&file)? };
+                    if encryption::is_sealed(&mapped) {
+                        parse(&Self::open_sealed(&mapped, &graph_path, keyring)?)?
+                    } else {
+                        parse(&mapped)?
+                    }
+                } else {
+                    parse(&Self::open_sealed(&std::fs::read(&graph_path)?, &graph_path, keyring)?)?
+                };
+                graphs.insert(graph_name.clone(), graph);
+            }
+            
+            Ok((graphs, digests))
+        } else {
+            // Return empty store
+            Ok((HashMap::new(), HashMap::new()))
+        }
+    }
+    
+    /// Decrypt a graph file's content; plain files pass through unchanged
+    fn open_sealed(data: &[u8], graph_path: &Path, keyring: Option<&StoreKeyring>) -> Result<Vec<u8>, EpcisKgError> {
+        match keyring {
+            Some(keyring) => keyring.open(data),
+            None if encryption::is_sealed(data) => Err(EpcisKgError::Config(format!(
+                "{} is encrypted but no storage key is configured ([encryption] section)",
+                graph_path.display()
+            ))),
+            None => Ok(data.to_vec()),
+        }
+    }
+    
+    /// Save graphs to persistent storage.
+    ///
+    /// Saves to one directory take turns, and every file is replaced whole, the
+    /// metadata last, so it never lists a graph whose file is half-written. Only
+    /// the graphs this copy changed since it last loaded or saved are written;
+    /// graphs other copies listed in the meantime stay listed.
+    ///
+    /// A graph this copy removed has its file deleted and is recorded as removed
+    /// in the metadata. Copies still holding it unchanged drop it on their next
+    /// save instead of listing it again; a copy that changed it saves it anew.
+    fn save_graphs(&mut self) -> Result<(), EpcisKgError> {
+        // A transaction saves once, when it commits
+        if self.transaction.is_some() {
+            return Ok(());
+        }
+        let path = Path::new(&self.storage_path);
+        std::fs::create_dir_all(path)?;
+        let lock = directory_write_lock(path);
+        let _saving = lock.lock();
+        let _other_processes = lock_directory_file(path)?;
+        
+        let metadata_path = path.join("store_metadata.json");
+        let listed: Option<StoreMetadata> = match metadata_path.exists() {
+            true => Some(serde_json::from_str(&std::fs::read_to_string(&metadata_path)?)?),
+            false => None,
+        };
+        let mut removed: std::collections::BTreeSet<String> = listed.iter().flat_map(|listed| listed.removed.iter().cloned()).collect();
+        
+        // Save each changed graph
+        let mut removed_elsewhere = Vec::new();
+        for (graph_name, graph) in &self.graphs {
+            let turtle_content = Self::graph_to_turtle(graph)?;
+            let digest: [u8; 32] = Sha256::digest(turtle_content.as_bytes()).into();
+            let unchanged = self.persisted.get(graph_name) == Some(&digest);
+            if unchanged && removed.contains(graph_name) {
+                removed_elsewhere.push(graph_name.clone());
+                continue;
+            }
+            removed.remove(graph_name);
+            if unchanged {
+                continue;
+            }
+            let graph_path = graph_file(path, graph_name);
+            match &self.keyring {
+                Some(keyring) => write_atomically(&graph_path, &keyring.seal(turtle_content.as_bytes())?)?,
+                None => write_atomically(&graph_path, turtle_content.as_bytes())?,
+            }
+            self.persisted.insert(graph_name.clone(), digest);
+        }
+        for graph_name in removed_elsewhere {
+            self.graphs.remove(&graph_name);
+            self.persisted.remove(&graph_name);
+        }
+        
+        // The graphs this copy saved before and no longer holds were removed
+        for graph_name in self.persisted.keys().filter(|name| !self.graphs.contains_key(*name)) {
+            match std::fs::remove_file(graph_file(path, graph_name)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+            removed.insert(graph_name.clone());
+        }
+        let graphs_held = &self.graphs;
+        self.persisted.retain(|name, _| graphs_held.contains_key(name));
+        
+        // Graphs this copy never saw were saved by another
+        let mut graphs: std::collections::BTreeSet<String> = self.graphs.keys().cloned().collect();
+        if let Some(listed) = listed {
+            graphs.extend(listed.graphs.into_iter().filter(|name| !self.persisted.contains_key(name) && !removed.contains(name)));
+        }
+        
+        let metadata = StoreMetadata {
+            graphs: graphs.into_iter().collect(),
+            removed: removed.into_iter().collect(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+        write_atomically(&metadata_path, serde_json::to_string_pretty(&metadata)?.as_bytes())?;
+        
+        self.refresh_size_metrics();
+        Ok(())
+    }
+    
+    /// Parse Turtle content to Graph.
+    ///
+    /// N-Triples, and the RDF-star forms of both, are Turtle-star too, so this
+    /// also reads the files `graph_to_turtle` writes.
+    fn parse_turtle_to_graph(turtle_content: &str) -> Result<OxrdfGraph, EpcisKgError> {
+        let mut graph = OxrdfGraph::default();
+        for triple in oxttl::TurtleParser::new().with_quoted_triples().for_reader(turtle_content.as_bytes()) {
+            let triple = triple.map_err(|e| match e {
+                oxttl::TurtleParseError::Syntax(e) => {
+                    let start = e.location().start;
+                    EpcisKgError::RdfSyntax { line: start.line + 1, column: start.column + 1, message: e.message().to_string() }
+                }
+                oxttl::TurtleParseError::Io(e) => EpcisKgError::Io(e),
+            })?;
+            graph.insert(triple.as_ref());
+        }
+        Ok(graph)
+    }
+    
+    /// Convert Graph to Turtle format
+    fn graph_to_turtle(graph: &OxrdfGraph) -> Result<String, EpcisKgError> {
+        let mut turtle = String::new();
+        
+        for triple in graph.iter() {
+            turtle.push_str(&format!("{} .\n", format_triple_star(triple)));
+        }
+        
+        Ok(turtle)
+    }
+}
+
+/// Write lock of a data directory, shared by every store saving to it in this process
+pub(crate) fn directory_write_lock(path: &Path) -> Arc<parking_lot::Mutex<()>> {
+    static LOCKS: std::sync::OnceLock<parking_lot::Mutex<HashMap<std::path::PathBuf, Arc<parking_lot::Mutex<()>>>>> = std::sync::OnceLock::new();
+    let directory = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut locks = LOCKS.get_or_init(Default::default).lock();
+    Arc::clone(locks.entry(directory).or_default())
+}
+
+/// Lock on a data directory's `.write.lock` file, held until the returned file
+/// is dropped. It keeps other processes, such as `rotate-keys` next to a running
+/// server, from rewriting the graph files while a save is under way.
+pub(crate) fn lock_directory_file(path: &Path) -> Result<std::fs::File, EpcisKgError> {
+    let file = std::fs::OpenOptions::new().create(true).truncate(false).write(true).open(path.join(".write.lock"))?;
+    fs2::FileExt::lock_exclusive(&file)?;
+    Ok(file)
+}
+
+/// Replace a file through a temporary one, so readers and crashes never see it half-written
+fn write_atomically(path: &Path, content: &[u8]) -> Result<(), EpcisKgError> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    std::fs::write(&temporary, content)?;
+    std::fs::rename(&temporary, path)?;
+    Ok(())
+}
+
+/// File holding a graph in a data directory: the SHA-256 of its name in hex,
+/// so every graph name gets its own file whatever characters it contains
+pub(crate) fn graph_file(path: &Path, graph_name: &str) -> std::path::PathBuf {
+    let digest: String = Sha256::digest(graph_name.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect();
+    path.join(format!("{}.ttl", digest))
+}
+
+/// File a graph was saved to before `graph_file`, where names differing only in
+/// `:` and `_` shared one. Names with path separators never had one in the directory.
+fn legacy_graph_file(path: &Path, graph_name: &str) -> Option<std::path::PathBuf> {
+    if graph_name.contains(['/', '\\']) {
+        return None;
+    }
+    Some(path.join(format!("{}.ttl", graph_name.replace(":", "_"))))
+}
+
+/// Rename graph files still under their legacy names to `graph_file`
+fn migrate_graph_files(path: &Path) -> Result<(), EpcisKgError> {
+    let metadata_path = path.join("store_metadata.json");
+    if !metadata_path.exists() {
+        return Ok(());
+    }
+    let lock = directory_write_lock(path);
+    let _saving = lock.lock();
+    let _other_processes = lock_directory_file(path)?;
+    let metadata: StoreMetadata = serde_json::from_str(&std::fs::read_to_string(&metadata_path)?)?;
+    for graph_name in &metadata.graphs {
+        let current = graph_file(path, graph_name);
+        let Some(legacy) = legacy_graph_file(path, graph_name) else {
+            continue;
+        };
+        if !current.exists() && legacy.is_file() {
+            std::fs::rename(&legacy, &current)?;
+            info!("Renamed the file of graph {} to {}", graph_name, current.display());
+        }
+    }
+    Ok(())
+}
+
+/// Store metadata for persistence; graph files are found from the names listed here
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct StoreMetadata {
+    pub graphs: Vec<String>,
+    /// Graphs removed by one copy, which others must not list again unless they change them
+    #[serde(default)]
+    pub removed: Vec<String>,
+    pub created_at: String,
+}
+
+/// Statistics about the Oxigraph store
+#[derive(Debug, Clone, serde::Serialize, Default)]
+pub struct OxigraphStats {
+    pub total_quads: usize,
+    pub named_graphs: usize,
+    pub default_graph_quads: usize,
+    pub storage_path: String,
+    pub read_only: bool,
+}
+
+/// How the store evaluates a SELECT query, captured for slow queries
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct QueryPlan {
+    pub variables: Vec<String>,
+    /// 0 when the query has no LIMIT
+    pub limit: usize,
+    pub values_blocks: usize,
+    /// Graphs named in FROM clauses; empty means every graph
+    pub dataset_graphs: Vec<String>,
+    pub graphs_scanned: usize,
+    pub triples_scanned: usize,
+    /// Every query is a scan of the dataset graphs today; recorded so plans stay comparable
+    pub strategy: String,
+}
+
+/// EPCIS vocabulary namespace used for error declarations
+const EPCIS_NS: &str = "urn:epcglobal:epcis:";
+
+/// Named graph holding EPCIS error declarations
+pub const ERROR_DECLARATIONS_GRAPH: &str = "urn:epcis:error_declarations";
+
+/// Trading partner master data
+pub const PARTIES_GRAPH: &str = "urn:epcis:master:parties";
+
+/// Reviewed `owl:sameAs` links between near-duplicate master data
+pub const SAME_AS_GRAPH: &str = "urn:epcis:master:same_as";
+
+/// Current owner of each EPC, derived from ownership-transfer TransactionEvents
+pub const OWNERSHIP_GRAPH: &str = "urn:epcis:inferred:ownership";
+
+/// Events observing expired product, with their inferred `expired` disposition
+pub const EXPIRY_GRAPH: &str = "urn:epcis:inferred:expiry";
+
+/// Temperature excursions and `excursionDetected` facts from sensor readings
+pub const COLD_CHAIN_GRAPH: &str = "urn:epcis:inferred:cold_chain";
+
+/// Results of EPC verification requests
+pub const VERIFICATIONS_GRAPH: &str = "urn:epcis:master:verifications";
+
+/// Counterfeit suspicion scores and the signals behind them
+pub const COUNTERFEIT_GRAPH: &str = "urn:epcis:inferred:counterfeit";
+
+/// Shipments with their estimated CO2e and transport cost
+pub const CARBON_GRAPH: &str = "urn:epcis:inferred:carbon";
+
+/// Derived events recording event pattern rule matches
+pub const EVENT_PATTERNS_GRAPH: &str = "urn:epcis:inferred:patterns";
+
+/// Records of materialization runs
+pub const INFERENCE_RUNS_GRAPH: &str = "urn:epcis:inferred:runs";
+
+/// Graphs holding derived data (inferences, their provenance and error declarations)
+pub fn is_derived_graph(name: &str) -> bool {
+    name.starts_with("urn:epcis:inferred")
+        || name.starts_with("urn:epcis:sparql_inferred")
+        || name == ERROR_DECLARATIONS_GRAPH
+}
+
+/// Graphs that may hold master data: everything but events, ontologies, derived data and link reviews
+fn is_master_data_graph(name: &str) -> bool {
+    !is_derived_graph(name)
+        && !name.starts_with("urn:epcis:event:")
+        && !name.starts_with("urn:epcis:ontology:")
+        && name != SAME_AS_GRAPH
+        && name != GRAPH_LOADS_GRAPH
+        && name != VIEW_DEFINITIONS_GRAPH
+}
+
+/// Offset of a stored event: its `eventTimeZoneOffset`, else the one written into its `eventTime`
+fn event_time_zone_offset(offset: Option<oxrdf::TermRef<'_>>, event_time: Option<oxrdf::TermRef<'_>>) -> Option<chrono::FixedOffset> {
+    if let Some(oxrdf::TermRef::Literal(offset)) = offset {
+        return crate::models::timezone::parse_offset(offset.value()).ok();
+    }
+    match event_time {
+        Some(oxrdf::TermRef::Literal(time)) if !time.value().ends_with('Z') => {
+            chrono::DateTime::parse_from_rfc3339(time.value()).ok().map(|time| *time.offset())
+        }
+        _ => None,
+    }
+}
+
+/// Graph name term of a stored graph; names that are not IRIs fall back to the default graph
+fn graph_name_node(graph_name: &str) -> oxrdf::GraphName {
+    oxrdf::NamedNode::new(graph_name)
+        .map(oxrdf::GraphName::NamedNode)
+        .unwrap_or(oxrdf::GraphName::DefaultGraph)
+}
+
+/// Triples of one graph matching a pattern, read from the index of the most selective bound position
+pub(crate) fn triples_for_pattern<'a>(
+    graph: &'a OxrdfGraph,
+    subject: Option<oxrdf::SubjectRef<'a>>,
+    predicate: Option<oxrdf::NamedNodeRef<'a>>,
+    object: Option<oxrdf::TermRef<'a>>,
+) -> Box<dyn Iterator<Item = oxrdf::TripleRef<'a>> + 'a> {
+    use oxrdf::TripleRef;
+    match (subject, predicate, object) {
+        (Some(s), Some(p), Some(o)) => {
+            let triple = TripleRef::new(s, p, o);
+            Box::new(graph.contains(triple).then_some(triple).into_iter())
+        }
+        (Some(s), Some(p), None) => Box::new(graph.objects_for_subject_predicate(s, p).map(move |o| TripleRef::new(s, p, o))),
+        (None, Some(p), Some(o)) => Box::new(graph.subjects_for_predicate_object(p, o).map(move |s| TripleRef::new(s, p, o))),
+        (Some(s), None, Some(o)) => Box::new(graph.triples_for_subject(s).filter(move |triple| triple.object == o)),
+        (Some(s), None, None) => Box::new(graph.triples_for_subject(s)),
+        (None, Some(p), None) => Box::new(graph.triples_for_predicate(p)),
+        (None, None, Some(o)) => Box::new(graph.triples_for_object(o)),
+        (None, None, None) => Box::new(graph.iter()),
+    }
+}
+
+/// Format a triple as N-Triples-star, writing quoted triples as `<< s p o >>`
+fn format_triple_star(triple: oxrdf::TripleRef<'_>) -> String {
+    let subject = match triple.subject {
+        oxrdf::SubjectRef::Triple(quoted) => format!("<< {} >>", format_triple_star(quoted.as_ref())),
+        other => other.to_string(),
+    };
+    let object = match triple.object {
+        oxrdf::TermRef::Triple(quoted) => format!("<< {} >>", format_triple_star(quoted.as_ref())),
+        other => other.to_string(),
+    };
+    format!("{} {} {}", subject, triple.predicate, object)
+}
+
+/// A term in the SPARQL JSON results format
+fn term_json(term: &oxrdf::Term) -> serde_json::Value {
+    match term {
+        oxrdf::Term::NamedNode(node) => serde_json::json!({"type": "uri", "value": node.as_str()}),
+        oxrdf::Term::BlankNode(node) => serde_json::json!({"type": "bnode", "value": node.as_str()}),
+        oxrdf::Term::Literal(literal) => {
+            let mut value = serde_json::json!({"type": "literal", "value": literal.value()});
+            if let Some(language) = literal.language() {
+                value["xml:lang"] = language.into();
+            } else if literal.datatype() != oxrdf::vocab::xsd::STRING {
+                value["datatype"] = literal.datatype().as_str().into();
+            }
+            value
+        }
+        oxrdf::Term::Triple(quoted) => quoted_triple_json(quoted),
+    }
+}
+
+/// SPARQL-star JSON results serialization of a quoted triple
+fn quoted_triple_json(triple: &oxrdf::Triple) -> serde_json::Value {
+    let subject = match &triple.subject {
+        oxrdf::Subject::Triple(quoted) => quoted_triple_json(quoted),
+        oxrdf::Subject::BlankNode(node) => serde_json::json!({"type": "bnode", "value": node.as_str()}),
+        oxrdf::Subject::NamedNode(node) => serde_json::json!({"type": "uri", "value": node.as_str()}),
+    };
+    let object = match &triple.object {
+        oxrdf::Term::Triple(quoted) => quoted_triple_json(quoted),
+        oxrdf::Term::BlankNode(node) => serde_json::json!({"type": "bnode", "value": node.as_str()}),
+        oxrdf::Term::NamedNode(node) => serde_json::json!({"type": "uri", "value": node.as_str()}),
+        oxrdf::Term::Literal(literal) => serde_json::json!({"type": "literal", "value": literal.value()}),
+    };
+    
+    serde_json::json!({
+        "type": "triple",
+        "value": {
+            "subject": subject,
+            "predicate": {"type": "uri", "value": triple.predicate.as_str()},
+            "object": object
+        }
+    })
+}
+
+/// A store read through overlay graphs, from `OxigraphStore::overlay_view`
+pub struct OverlayView<'a> {
+    base: &'a OxigraphStore,
+    /// Overlay graphs, each merged with the store's graph of the same name
+    overlay: HashMap<String, OxrdfGraph>,
+}
+
+impl OverlayView<'_> {
+    /// Each graph of the view: the overlay's first, then the store's it does not shadow
+    fn graphs(&self) -> impl Iterator<Item = (&String, &OxrdfGraph)> {
+        let overlay = &self.overlay;
+        overlay.iter().chain(self.base.graphs.iter().filter(move |(name, _)| !overlay.contains_key(*name)))
+    }
+    
+    /// Names of all graphs in the view
+    pub fn graph_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.graphs().map(|(name, _)| name.clone()).collect();
+        names.sort();
+        names
+    }
+    
+    /// Triples in every graph whose name starts with `graph_prefix`
+    pub fn triples_in_graphs(&self, graph_prefix: &str) -> Vec<oxrdf::Triple> {
+        self.graphs()
+            .filter(|(name, _)| name.starts_with(graph_prefix))
+            .flat_map(|(_, graph)| graph.iter().map(|triple| triple.into_owned()))
+            .collect()
+    }
+    
+    /// Execute a SPARQL SELECT query over the view and return results as JSON
+    pub fn query_select(&self, sparql_query: &str) -> Result<String, EpcisKgError> {
+        self.base.timed(|| self.base.evaluate_select_over(self.graphs().collect(), sparql_query))
+    }
+    
+    /// In-memory store holding only the given graphs of the view, for reasoning over them
+    pub fn scoped_view(&self, graph_names: &[String]) -> OxigraphStore {
+        OxigraphStore {
+            graphs: self.graphs().filter(|(name, _)| graph_names.contains(name)).map(|(name, graph)| (name.clone(), graph.clone())).collect(),
+            ..self.base.scoped_view(&[])
+        }
+    }
+}
+
+/// Graphs a transaction wrote, as they were before its first write to each
+/// (`None` for graphs it created), and the changes it holds back
+#[derive(Clone)]
+struct Transaction {
+    graphs: HashMap<String, Option<OxrdfGraph>>,
+    changes: Vec<Change>,
+}
+
+fn no_transaction() -> EpcisKgError {
+    EpcisKgError::Conflict("no transaction is open on this store".to_string())
+}
+
+/// Triples written by one update or quad write, and the graphs they changed
+#[derive(Default)]
+struct GraphWrites {
+    report: UpdateReport,
+    /// Triples new to each derived graph, for the changefeed
+    inferred: HashMap<String, Vec<oxrdf::Triple>>,
+    changed: std::collections::HashSet<String>,
+}
+
+/// Inline data block parsed from a SPARQL VALUES clause
+#[derive(Debug, Clone)]
+struct ValuesClause {
+    variables: Vec<String>,
+    rows: Vec<Vec<Option<String>>>,
+}
+
+impl ValuesClause {
+    /// Check whether a solution is compatible with at least one row of the block.
+    ///
+    /// Projected variables are compared against their binding; variables that are
+    /// only used inside the pattern are matched against the triple's subject or object.
+    fn accepts(&self, solution: &serde_json::Map<String, serde_json::Value>, subject: &str, object: &str) -> bool {
+        let subject = normalize_term_value(subject);
+        let object = normalize_term_value(object);
+
+        self.rows.iter().any(|row| {
+            self.variables.iter().zip(row.iter()).all(|(variable, value)| match value {
+                None => true,
+                Some(expected) => match solution.get(variable) {
+                    Some(binding) => binding
+                        .get("value")
+                        .and_then(|v| v.as_str())
+                        .is_some_and(|bound| normalize_term_value(bound) == *expected),
+                    None => subject == *expected || object == *expected,
+                },
+            })
+        })
+    }
+}
+
+/// Strip IRI brackets and literal quoting so terms can be compared by lexical value
+fn normalize_term_value(term: &str) -> String {
+    let term = term.trim();
+    if let Some(iri) = term.strip_prefix('<').and_then(|t| t.strip_suffix('>')) {
+        return iri.to_string();
+    }
+    for quote in ['"', '\''] {
+        if let Some(rest) = term.strip_prefix(quote) {
+            if let Some(end) = rest.rfind(quote) {
+                return rest[..end].replace(&format!("\\{}", quote), &quote.to_string());
+            }
+        }
+    }
+    term.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store_with_events() -> OxigraphStore {
+        let mut store = OxigraphStore::new_memory().unwrap();
+        let turtle = "<urn:epc:event:1> <urn:epcglobal:epcis:epcList> <urn:epc:id:sgtin:0614141.107346.2017> .\n\
+                      <urn:epc:event:2> <urn:epcglobal:epcis:epcList> <urn:epc:id:sgtin:0614141.107346.2018> .\n\
+                      <urn:epc:event:3> <urn:epcglobal:epcis:epcList> <urn:epc:id:sgtin:0614141.107346.2019> .\n";
+        store.store_ontology_turtle(turtle, "urn:epcis:test").unwrap();
+        store
+    }
+
+    fn binding_count(result: &str) -> usize {
+        let json: serde_json::Value = serde_json::from_str(result).unwrap();
+        json["results"]["bindings"].as_array().unwrap().len()
+    }
+
+    #[test]
+    fn test_quads_for_pattern_with_wildcards() {
+        let mut store = store_with_events();
+        let other = oxrdf::Triple::new(
+            oxrdf::NamedNode::new_unchecked("urn:epc:event:1"),
+            oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:bizStep"),
+            oxrdf::NamedNode::new_unchecked("urn:epcglobal:cbv:shipping"),
+        );
+        store.insert_triples("urn:epcis:other", &[other]).unwrap();
+        let event = oxrdf::NamedNodeRef::new_unchecked("urn:epc:event:1");
+        let epc_list = oxrdf::NamedNodeRef::new_unchecked("urn:epcglobal:epcis:epcList");
+        let epc = oxrdf::NamedNodeRef::new_unchecked("urn:epc:id:sgtin:0614141.107346.2017");
+
+        assert_eq!(store.quads_for_pattern(Some(event.into()), None, None, None).len(), 2);
+        assert_eq!(store.quads_for_pattern(Some(event.into()), None, None, Some("urn:epcis:other")).len(), 1);
+        assert_eq!(store.quads_for_pattern(None, Some(epc_list), None, None).len(), 3);
+
+        let listing = store.quads_for_pattern(None, Some(epc_list), Some(epc.into()), None);
+        assert_eq!(listing.len(), 1);
+        assert_eq!(listing[0].subject, oxrdf::Subject::from(event.into_owned()));
+        assert_eq!(listing[0].graph_name, oxrdf::GraphName::from(oxrdf::NamedNode::new_unchecked("urn:epcis:test")));
+
+        assert!(store.contains_pattern(Some(event.into()), Some(epc_list), Some(epc.into()), Some("urn:epcis:test")));
+        assert!(!store.contains_pattern(Some(event.into()), Some(epc_list), Some(epc.into()), Some("urn:epcis:other")));
+    }
+
+    #[test]
+    fn test_materialized_triples_are_filtered_and_paged() {
+        let mut store = store_with_events();
+        let rdf_type = oxrdf::NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type");
+        let inferred: Vec<oxrdf::Triple> = (1..=5)
+            .map(|serial| {
+                oxrdf::Triple::new(
+                    oxrdf::NamedNode::new_unchecked(format!("urn:epc:id:sgtin:0614141.107346.{}", serial)),
+                    rdf_type.clone(),
+                    oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:Product"),
+                )
+            })
+            .collect();
+        store.insert_triples("urn:epcis:inferred", &inferred).unwrap();
+        store.insert_triples("urn:epcis:inferred:other", &inferred[..1]).unwrap();
+        
+        let filter = MaterializedFilter {
+            graph: Some("urn:epcis:inferred".to_string()),
+            predicate: Some(rdf_type.as_str().to_string()),
+            subject_prefix: Some("urn:epc:id:sgtin:0614141.".to_string()),
+            offset: 2,
+            limit: 2,
+        };
+        let page = store.materialized_triples(&filter).unwrap();
+        assert_eq!(page.total, 5);
+        assert_eq!(page.next_offset, Some(4));
+        assert_eq!(page.triples[0].subject, "<urn:epc:id:sgtin:0614141.107346.3>");
+        
+        // Asserted graphs are left out
+        let page = store.materialized_triples(&MaterializedFilter::default()).unwrap();
+        assert_eq!(page.total, 6);
+        assert_eq!(page.next_offset, None);
+    }
+
+    #[test]
+    fn test_values_clause_restricts_solutions() {
+        let store = store_with_events();
+        let query = "SELECT ?s ?p ?o WHERE { VALUES ?o { <urn:epc:id:sgtin:0614141.107346.2017> <urn:epc:id:sgtin:0614141.107346.2019> } ?s ?p ?o }";
+
+        let result = store.query_select(query).unwrap();
+        assert_eq!(binding_count(&result), 2);
+    }
+
+    #[test]
+    fn test_values_clause_on_unprojected_variable() {
+        let store = store_with_events();
+        let query = "PREFIX sgtin: <urn:epc:id:sgtin:> \
+                     SELECT ?s WHERE { VALUES ?epc { sgtin:0614141.107346.2018 } ?s ?p ?epc }";
+
+        let result = store.query_select(query).unwrap();
+        assert_eq!(binding_count(&result), 1);
+    }
+
+    #[test]
+    fn test_values_clause_multi_variable_with_undef() {
+        let store = store_with_events();
+        let query = "SELECT ?s ?o WHERE { VALUES (?s ?o) { (<urn:epc:event:1> UNDEF) (<urn:epc:event:3> <urn:epc:id:sgtin:0614141.107346.2017>) } ?s ?p ?o }";
+
+        let result = store.query_select(query).unwrap();
+        assert_eq!(binding_count(&result), 1);
+    }
+
+    #[test]
+    fn test_ontology_graph_names_and_load_modes() {
+        assert_ne!(ontology_graph_name("a/cbv.ttl"), ontology_graph_name("b/cbv.ttl"));
+        assert_ne!(ontology_graph_name("a/b.ttl"), ontology_graph_name("a:b.ttl"));
+        assert!(ontology_graph_name("a/cbv.ttl").starts_with("urn:epcis:ontology:cbv.ttl-"));
+        let first: [u8; 32] = Sha256::digest(b"<urn:a> <urn:b> <urn:c> .").into();
+        let second: [u8; 32] = Sha256::digest(b"<urn:d> <urn:e> <urn:f> .").into();
+        assert_ne!(upload_graph_name("upload.ttl", &first), upload_graph_name("upload.ttl", &second));
+        assert_eq!(upload_graph_name("../../etc/cbv.ttl", &first), upload_graph_name("cbv.ttl", &first));
+        assert!(upload_graph_name("cbv.ttl", &first).starts_with(&ontology_graph_prefix("cbv.ttl")));
+
+        let data = |object: &str| {
+            let mut graph = OxrdfGraph::default();
+            graph.insert(&oxrdf::Triple::new(
+                oxrdf::NamedNode::new_unchecked("urn:epcglobal:cbv:Bizstep"),
+                oxrdf::NamedNode::new_unchecked("http://www.w3.org/2000/01/rdf-schema#label"),
+                oxrdf::Literal::new_simple_literal(object),
+            ));
+            OntologyData { triples_count: 1, graph, source_file: "ontologies/cbv.ttl".to_string() }
+        };
+        let mut store = OxigraphStore::new_memory().unwrap();
+        let graph = store.store_ontology_data_as(&data("a"), None, LoadMode::Replace).unwrap();
+        store.store_ontology_data_as(&data("b"), None, LoadMode::Merge).unwrap();
+        assert_eq!(store.triples_in_graphs(&graph).len(), 2);
+        assert!(store.store_ontology_data_as(&data("c"), None, LoadMode::Fail).is_err());
+        store.store_ontology_data_as(&data("c"), None, LoadMode::Replace).unwrap();
+        assert_eq!(store.triples_in_graphs(&graph).len(), 1);
+
+        let custom = store.store_ontology_data_as(&data("d"), Some("urn:example:cbv"), LoadMode::Fail).unwrap();
+        assert_eq!(custom, "urn:example:cbv");
+    }
+
+    #[test]
+    fn test_from_clause_restricts_graphs() {
+        let mut store = store_with_events();
+        store
+            .store_ontology_turtle("<urn:epc:event:4> <urn:epcglobal:epcis:action> \"ADD\" .\n", "urn:epcis:other")
+            .unwrap();
+
+        let result = store.query_select("SELECT ?s ?p ?o FROM <urn:epcis:other> WHERE { ?s ?p ?o }").unwrap();
+        assert_eq!(binding_count(&result), 1);
+    }
+
+    #[test]
+    fn test_graph_patterns_address_quads_written_per_graph() {
+        let mut store = store_with_events();
+        let shipped = |event: &str| {
+            oxrdf::Triple::new(
+                oxrdf::NamedNode::new_unchecked(format!("urn:epc:event:{}", event)),
+                oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:bizStep"),
+                oxrdf::NamedNode::new_unchecked("urn:epcglobal:cbv:bizstep:shipping"),
+            )
+        };
+        let quads: Vec<oxrdf::Quad> = ["1", "2"]
+            .iter()
+            .map(|event| shipped(event).in_graph(oxrdf::NamedNode::new_unchecked(format!("urn:epcis:event:{}", event))))
+            .collect();
+        assert_eq!(store.insert_quads(&quads).unwrap(), 2);
+        assert!(store.insert_quads(&[shipped("3").in_graph(oxrdf::GraphName::DefaultGraph)]).is_err());
+
+        let result = store
+            .query_select("SELECT ?g ?event WHERE { GRAPH ?g { ?event <urn:epcglobal:epcis:bizStep> ?step } }")
+            .unwrap();
+        assert_eq!(binding_count(&result), 2);
+        let json: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(json["head"]["vars"], serde_json::json!(["g", "event"]));
+        assert!(json["results"]["bindings"][0]["g"]["value"].as_str().unwrap().starts_with("urn:epcis:event:"));
+
+        let named = "SELECT ?event FROM NAMED <urn:epcis:event:1> WHERE { GRAPH ?g { ?event ?p ?o } }";
+        assert_eq!(binding_count(&store.query_select(named).unwrap()), 1);
+        let ask = "ASK WHERE { GRAPH <urn:epcis:event:2> { ?event ?p ?o } }";
+        assert!(store.query_ask(ask).unwrap());
+        let construct = "CONSTRUCT { ?event <urn:x:shippedIn> ?g } WHERE { GRAPH ?g { ?event ?p ?o } }";
+        assert_eq!(store.query_construct(construct).unwrap().lines().count(), 2);
+
+        assert_eq!(store.remove_triples("urn:epcis:event:2", &[shipped("2")]).unwrap(), 1);
+        assert!(!store.query_ask(ask).unwrap());
+        assert!(!store.graph_names().contains(&"urn:epcis:event:2".to_string()));
+        assert_eq!(store.remove_quads(&[shipped("1").in_graph(oxrdf::GraphName::DefaultGraph)]).unwrap(), 1);
+        assert!(store.drop_graph("urn:epcis:test").unwrap());
+        assert!(!store.drop_graph("urn:epcis:test").unwrap());
+        assert!(store.graph_names().is_empty());
+    }
+
+    #[test]
+    fn test_turtle_loads_losslessly_and_reports_syntax_errors() {
+        let mut store = OxigraphStore::new_memory().unwrap();
+        let turtle = "@prefix ex: <urn:x:> .\n\
+                      ex:site a ex:Site ;\n\
+                          ex:name \"Dock 2\"@en, \"Quai 2\"@fr ;\n\
+                          ex:address [ ex:city \"Lyon\" ] ;\n\
+                          ex:doors ( 1 2 ) .\n";
+        store.store_ontology_turtle(turtle, "urn:epcis:master:sites").unwrap();
+        // Five statements about the site, one about its address and two per list cell
+        assert_eq!(store.graph_len("urn:epcis:master:sites"), 10);
+        let french = oxrdf::Literal::new_language_tagged_literal("Quai 2", "fr").unwrap();
+        assert!(store.graphs["urn:epcis:master:sites"].iter().any(|triple| triple.object == oxrdf::TermRef::Literal(french.as_ref())));
+
+        let error = store.store_ontology_turtle("<urn:x:a> <urn:x:b> <urn:x:c> .\n<urn:x:a> <urn:x:b> \"open .\n", "urn:epcis:broken").unwrap_err();
+        assert!(matches!(error, EpcisKgError::RdfSyntax { line: 2, .. }), "{}", error);
+        assert_eq!(store.graph_len("urn:epcis:broken"), 0);
+    }
+
+    #[test]
+    fn test_statement_annotations_round_trip() {
+        let mut store = OxigraphStore::new_memory().unwrap();
+        let statement = oxrdf::Triple::new(
+            oxrdf::NamedNode::new_unchecked("urn:epc:event:1"),
+            oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:bizLocation"),
+            oxrdf::NamedNode::new_unchecked("urn:epc:id:sgln:0614141.12345.0"),
+        );
+        let confidence = oxrdf::NamedNode::new_unchecked("urn:epcis:annotation:confidence");
+
+        store
+            .annotate_statement("urn:epcis:annotations", &statement, &[(confidence.clone(), oxrdf::Literal::new_simple_literal("0.9").into())])
+            .unwrap();
+
+        // Quoted triples survive the N-Triples-star export and re-parse
+        let exported = OxigraphStore::graph_to_turtle(&store.graphs["urn:epcis:annotations"]).unwrap();
+        assert!(exported.starts_with("<< <urn:epc:event:1>"));
+        let reparsed = OxigraphStore::parse_turtle_to_graph(&exported).unwrap();
+        assert_eq!(reparsed.len(), 1);
+
+        let annotations = store.statement_annotations(&statement);
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].1, confidence);
+    }
+
+    #[test]
+    fn test_ontology_data_from_store_resident_graphs() {
+        let mut store = OxigraphStore::new_memory().unwrap();
+        let triple = |s: &str| oxrdf::Triple::new(
+            oxrdf::NamedNode::new_unchecked(s),
+            oxrdf::NamedNode::new_unchecked("http://www.w3.org/2000/01/rdf-schema#subClassOf"),
+            oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:Event"),
+        );
+        store.insert_triples("urn:epcis:ontology:ontologies:epcis2.ttl", &[triple("urn:epcglobal:epcis:ObjectEvent")]).unwrap();
+        store.insert_triples("urn:epcis:event:evt-1", &[triple("urn:ex:Other")]).unwrap();
+
+        let stored = store.stored_ontologies();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].source_file, "urn:epcis:ontology:ontologies:epcis2.ttl");
+
+        let data = store.ontology_data(&store.graph_names(), "store");
+        assert_eq!(data.triples_count, 2);
+    }
+
+    #[test]
+    fn test_reviewed_same_as_links_are_not_proposed_again() {
+        let mut store = OxigraphStore::new_memory().unwrap();
+        for (gln, name) in [("urn:epc:id:pgln:0614141.00001", "Acme Foods Inc."), ("https://id.gs1.org/417/0614141000012", "ACME Foods")] {
+            store.upsert_party(&Party { gln: gln.to_string(), name: name.to_string(), role: None, address: None, time_zone: None }).unwrap();
+        }
+        let config = EntityResolutionConfig::default();
+        let proposals = store.propose_same_as(&config);
+        assert_eq!(proposals.len(), 1);
+
+        let review = SameAsReview { left: proposals[0].left.clone(), right: proposals[0].right.clone(), accept: true, reviewer: Some("alice".to_string()) };
+        store.review_same_as(&review).unwrap();
+        assert!(store.propose_same_as(&config).is_empty());
+        assert_eq!(store.triples_in_graphs(SAME_AS_GRAPH).len(), 4);
+
+        store.review_same_as(&SameAsReview { accept: false, reviewer: None, ..review }).unwrap();
+        assert_eq!(store.triples_in_graphs(SAME_AS_GRAPH).len(), 2);
+    }
+
+    #[test]
+    fn test_ownership_follows_transaction_events() {
+        let mut store = OxigraphStore::new_memory().unwrap();
+        let epc = "urn:epc:id:sgtin:0614141.107346.2017";
+        let node = |iri: &str| oxrdf::NamedNode::new_unchecked(iri);
+        let transfer = |id: &str, time: &str, from: &str, to: &str| {
+            let event = node(&format!("urn:epc:event:{}", id));
+            vec![
+                oxrdf::Triple::new(event.clone(), node("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"), node("urn:epcglobal:epcis:TransactionEvent")),
+                oxrdf::Triple::new(event.clone(), node("urn:epcglobal:epcis:bizStep"), node("urn:epcglobal:cbv:bizstep:shipping")),
+                oxrdf::Triple::new(event.clone(), node("urn:epcglobal:epcis:epcList"), node(epc)),
+                oxrdf::Triple::new(event.clone(), node("urn:epcglobal:epcis:sourceOwningParty"), node(from)),
+                oxrdf::Triple::new(event.clone(), node("urn:epcglobal:epcis:destinationOwningParty"), node(to)),
+                oxrdf::Triple::new(event, node("urn:epcglobal:epcis:eventTime"), oxrdf::Literal::new_simple_literal(time)),
+            ]
+        };
+        let (maker, distributor, retailer) = ("urn:epc:id:pgln:0614141.00000", "urn:epc:id:pgln:0614142.00000", "urn:epc:id:pgln:0614143.00000");
+
+        futures::executor::block_on(store.store_event_triples("t2", &transfer("t2", "2024-01-02T00:00:00Z", distributor, retailer))).unwrap();
+        futures::executor::block_on(store.store_event_triples("t1", &transfer("t1", "2024-01-01T00:00:00Z", maker, distributor))).unwrap();
+        store.upsert_party(&Party {
+            gln: retailer.to_string(),
+            name: "Corner Store".to_string(),
+            role: Some("retailer".to_string()),
+            address: None,
+            time_zone: Some("Europe/Berlin".to_string()),
+        }).unwrap();
+
+        let record = store.current_owner(epc).unwrap();
+        assert_eq!(record.owner, retailer);
+        assert_eq!(record.history.len(), 2);
+        let owner_party = record.owner_party.unwrap();
+        assert_eq!(owner_party.name, "Corner Store");
+        assert_eq!(owner_party.time_zone.as_deref(), Some("Europe/Berlin"));
+        assert_eq!(store.graphs[OWNERSHIP_GRAPH].len(), 2);
+
+        // Retracting the latest transfer moves ownership back
+        store.declare_event_in_error(&ErrorDeclaration::new("t2")).unwrap();
+        assert_eq!(store.current_owner(epc).unwrap().owner, distributor);
+    }
+
+    #[test]
+    fn test_inventory_snapshot_round_trip() {
+        let mut store = OxigraphStore::new_memory().unwrap();
+        let node = |iri: &str| oxrdf::NamedNode::new_unchecked(iri);
+        let location = "urn:epc:id:sgln:0614141.00001.0";
+        let observe = |id: &str, epc: &str| {
+            let event = node(&format!("urn:epc:event:{}", id));
+            vec![
+                oxrdf::Triple::new(event.clone(), node("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"), node("urn:epcglobal:epcis:ObjectEvent")),
+                oxrdf::Triple::new(event.clone(), node("urn:epcglobal:epcis:action"), node("urn:epcglobal:cbv:ADD")),
+                oxrdf::Triple::new(event.clone(), node("urn:epcglobal:epcis:epcList"), node(epc)),
+                oxrdf::Triple::new(event.clone(), node("urn:epcglobal:epcis:bizLocation"), node(location)),
+                oxrdf::Triple::new(event, node("urn:epcglobal:epcis:eventTime"), oxrdf::Literal::new_simple_literal("2024-01-01T08:00:00Z")),
+            ]
+        };
+
+        futures::executor::block_on(store.store_event_triples("a1", &observe("a1", "urn:epc:id:sgtin:0614141.107346.1"))).unwrap();
+        futures::executor::block_on(store.store_event_triples("a2", &observe("a2", "urn:epc:id:sgtin:0614141.107346.2"))).unwrap();
+
+        let snapshot = store.compute_inventory_snapshot("2024-01-01T23:59:59Z".parse().unwrap());
+        assert_eq!(snapshot.events_replayed, 2);
+        store.store_inventory_snapshot(&snapshot).unwrap();
+
+        assert_eq!(store.inventory_snapshot_dates(), vec!["2024-01-01".to_string()]);
+        let stored = store.inventory_snapshot("2024-01-01").unwrap();
+        assert_eq!(stored.positions, snapshot.positions);
+        assert_eq!(stored.positions[0].quantity, 2);
+        assert!(store.inventory_snapshot("2024-01-02").is_none());
+    }
+
+    #[test]
+    fn test_declare_event_in_error_retracts_inferences() {
+        let mut store = OxigraphStore::new_memory().unwrap();
+        let event = |id: &str| oxrdf::NamedNode::new_unchecked(format!("urn:epc:event:{}", id));
+        let epc_list = oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:epcList");
+        let rdf_type = oxrdf::NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type");
+        let shared = oxrdf::NamedNode::new_unchecked("urn:epc:id:sgtin:0614141.107346.2017");
+        let only_bad = oxrdf::NamedNode::new_unchecked("urn:epc:id:sgtin:0614141.107346.2018");
+
+        store.insert_triples("urn:epcis:event:bad", &[
+            oxrdf::Triple::new(event("bad"), epc_list.clone(), shared.clone()),
+            oxrdf::Triple::new(event("bad"), epc_list.clone(), only_bad.clone()),
+        ]).unwrap();
+        store.insert_triples("urn:epcis:event:good", &[
+            oxrdf::Triple::new(event("good"), epc_list.clone(), shared.clone()),
+        ]).unwrap();
+        let product = oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:PhysicalObject");
+        store.insert_triples("urn:epcis:inferred", &[
+            oxrdf::Triple::new(event("bad"), rdf_type.clone(), oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:ObjectEvent")),
+            oxrdf::Triple::new(shared.clone(), rdf_type.clone(), product.clone()),
+            oxrdf::Triple::new(only_bad.clone(), rdf_type.clone(), product.clone()),
+        ]).unwrap();
+
+        let mut declaration = ErrorDeclaration::new("bad");
+        declaration.corrective_event_ids.push("good".to_string());
+        let result = store.declare_event_in_error(&declaration).unwrap();
+
+        assert_eq!(result.declared_graphs, vec!["urn:epcis:event:bad".to_string()]);
+        assert_eq!(result.reason, "urn:epcglobal:cbv:er:incorrect_data");
+        assert_eq!(result.retracted_inferences, 2);
+        assert!(store.is_declared_in_error("bad"));
+        // The shared EPC is still supported by the good event
+        assert_eq!(store.graphs["urn:epcis:inferred"].len(), 1);
+        // The original event is kept, as required by EPCIS
+        assert_eq!(store.graphs["urn:epcis:event:bad"].len(), 2);
+
+        assert!(store.declare_event_in_error(&ErrorDeclaration::new("missing")).is_err());
+    }
+
+    #[test]
+    fn test_values_clause_rejects_mismatched_row() {
+        let store = store_with_events();
+        let query = "SELECT ?s ?o WHERE { VALUES (?s ?o) { (<urn:epc:event:1>) } ?s ?p ?o }";
+
+        assert!(store.query_select(query).is_err());
+    }
+
+    #[test]
+    fn test_read_only_store_loads_mapped_graphs_and_refuses_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let triple = oxrdf::Triple::new(
+            oxrdf::NamedNode::new_unchecked("urn:epc:event:1"),
+            oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:bizStep"),
+            oxrdf::NamedNode::new_unchecked("urn:epcglobal:cbv:bizstep:shipping"),
+        );
+        let mut writable = OxigraphStore::new(dir.path()).unwrap();
+        writable.insert_triples("urn:epcis:event:1", std::slice::from_ref(&triple)).unwrap();
+
+        let mut store = OxigraphStore::open_read_only(dir.path(), true).unwrap();
+        assert!(store.is_read_only());
+        assert_eq!(store.graphs["urn:epcis:event:1"].len(), 1);
+        assert!(matches!(store.insert_triples("urn:epcis:event:2", &[triple]), Err(EpcisKgError::Conflict(_))));
+        assert!(store.graphs.get("urn:epcis:event:2").is_none());
+
+        assert!(OxigraphStore::open_read_only(dir.path().join("missing"), true).is_err());
+    }
+
+    #[test]
+    fn test_transactions_save_on_commit_and_roll_back_whole() {
+        let dir = tempfile::tempdir().unwrap();
+        let triple = |event: &str| {
+            oxrdf::Triple::new(
+                oxrdf::NamedNode::new_unchecked(format!("urn:epc:event:{}", event)),
+                oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:bizStep"),
+                oxrdf::NamedNode::new_unchecked("urn:epcglobal:cbv:bizstep:shipping"),
+            )
+        };
+        let mut store = OxigraphStore::new(dir.path()).unwrap();
+        store.begin().unwrap();
+        assert!(matches!(store.begin(), Err(EpcisKgError::Conflict(_))));
+        futures::executor::block_on(store.store_event_triples("1", &[triple("1")])).unwrap();
+        store.insert_triples("urn:epcis:inferred", &[triple("1")]).unwrap();
+        // Nothing reaches the disk before the commit
+        assert_eq!(OxigraphStore::new(dir.path()).unwrap().graph_len("urn:epcis:event:1"), 0);
+        store.commit().unwrap();
+        let reopened = OxigraphStore::new(dir.path()).unwrap();
+        assert_eq!((reopened.graph_len("urn:epcis:event:1"), reopened.graph_len("urn:epcis:inferred")), (1, 1));
+
+        // A failed batch leaves the store as it was, in memory and on disk
+        let failed: Result<(), EpcisKgError> = store.transaction(|store| {
+            futures::executor::block_on(store.store_event_triples("2", &[triple("2")]))?;
+            store.remove_triples("urn:epcis:event:1", &[triple("1")])?;
+            // Only the graphs written are kept for the rollback
+            let kept = &store.transaction.as_ref().unwrap().graphs;
+            assert!(matches!(kept.get("urn:epcis:event:2"), Some(None)));
+            assert_eq!(kept.get("urn:epcis:event:1").and_then(Option::as_ref).map(OxrdfGraph::len), Some(1));
+            assert!(!kept.contains_key("urn:epcis:inferred"));
+            Err(EpcisKgError::Validation("batch rejected".to_string()))
+        });
+        assert!(failed.is_err());
+        assert!(!store.in_transaction());
+        assert_eq!((store.graph_len("urn:epcis:event:1"), store.graph_len("urn:epcis:event:2")), (1, 0));
+        assert_eq!(OxigraphStore::new(dir.path()).unwrap().graph_len("urn:epcis:event:1"), 1);
+        assert!(matches!(store.rollback(), Err(EpcisKgError::Conflict(_))));
+    }
+
+    #[test]
+    fn test_vocabulary_extensions_survive_reopening() {
+        use crate::ontology::skos::ExtensionConcept;
+
+        let dir = tempfile::tempdir().unwrap();
+        let extension = VocabularyExtension {
+            scheme: "https://acme.example/vocab/dispositions".to_string(),
+            vocabulary: "dispositions".to_string(),
+            namespace: "urn:acme:disp:".to_string(),
+            title: None,
+            concepts: vec![ExtensionConcept {
+                iri: "urn:acme:disp:awaiting_rework".to_string(),
+                labels: std::collections::BTreeMap::from([("en".to_string(), "Awaiting rework".to_string())]),
+                definition: None,
+                broader: None,
+            }],
+        };
+        let mut store = OxigraphStore::new(dir.path()).unwrap();
+        store.register_vocabulary_extension(&extension).unwrap();
+
+        let mut overlapping = extension.clone();
+        overlapping.scheme = "https://other.example/vocab".to_string();
+        assert!(matches!(store.register_vocabulary_extension(&overlapping), Err(EpcisKgError::Conflict(_))));
+
+        let mut reopened = OxigraphStore::new(dir.path()).unwrap();
+        assert_eq!(reopened.vocabulary_extensions(), vec![extension.clone()]);
+        assert!(reopened.remove_vocabulary_extension(&extension.scheme).unwrap());
+        assert!(reopened.vocabulary_extensions().is_empty());
+    }
+
+    #[test]
+    fn test_concurrent_writers_keep_every_graph() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = OxigraphStore::new(dir.path()).unwrap();
+        let triple = |subject: String, object: &str| {
+            oxrdf::Triple::new(
+                oxrdf::NamedNode::new_unchecked(subject),
+                oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:bizStep"),
+                oxrdf::NamedNode::new_unchecked(object),
+            )
+        };
+        const WRITES: usize = 15;
+
+        // Capture, graph writes and materialization each write through their own copy, as the pipeline and server do
+        std::thread::scope(|scope| {
+            let mut capture = store.clone();
+            scope.spawn(move || {
+                for i in 0..WRITES {
+                    let event = triple(format!("urn:epc:event:capture-{}", i), "urn:epcglobal:cbv:bizstep:shipping");
+                    futures::executor::block_on(capture.store_event_triples(&format!("capture-{}", i), &[event])).unwrap();
+                }
+            });
+            let mut update = store.clone();
+            scope.spawn(move || {
+                for i in 0..WRITES {
+                    let event = triple(format!("urn:epc:event:update-{}", i), "urn:epcglobal:cbv:bizstep:receiving");
+                    update.insert_triples(&format!("urn:epcis:event:update-{}", i), &[event]).unwrap();
+                }
+            });
+            let mut materialize = store.clone();
+            scope.spawn(move || {
+                for i in 0..WRITES {
+                    let inferred = triple(format!("urn:epc:event:capture-{}", i), "urn:epcglobal:cbv:bizstep:departing");
+                    materialize.insert_triples("urn:epcis:inferred", &[inferred]).unwrap();
+                }
+            });
+        });
+
+        let reopened = OxigraphStore::new(dir.path()).unwrap();
+        let graphs = reopened.graph_names();
+        for i in 0..WRITES {
+            assert!(graphs.contains(&format!("urn:epcis:event:capture-{}", i)), "capture-{} lost", i);
+            assert!(graphs.contains(&format!("urn:epcis:event:update-{}", i)), "update-{} lost", i);
+        }
+        assert_eq!(reopened.graph_len("urn:epcis:inferred"), WRITES);
+        let leftovers: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn test_removed_graph_stays_removed_when_another_copy_saves() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut first = OxigraphStore::new(dir.path()).unwrap();
+        let shipped = oxrdf::Triple::new(
+            oxrdf::NamedNode::new_unchecked("urn:epc:event:e1"),
+            oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:bizStep"),
+            oxrdf::NamedNode::new_unchecked("urn:epcglobal:cbv:bizstep:shipping"),
+        );
+        first.insert_triples("urn:epcis:event:a", &[shipped.clone()]).unwrap();
+        first.insert_triples("urn:epcis:event:b", &[shipped.clone()]).unwrap();
+        let mut second = first.clone();
+
+        first.update("DROP GRAPH <urn:epcis:event:a>").unwrap();
+        assert!(!graph_file(dir.path(), "urn:epcis:event:a").exists());
+        second.insert_triples("urn:epcis:event:c", &[shipped.clone()]).unwrap();
+        assert_eq!(second.graph_len("urn:epcis:event:a"), 0);
+        assert_eq!(
+            OxigraphStore::new(dir.path()).unwrap().graph_names(),
+            vec!["urn:epcis:event:b".to_string(), "urn:epcis:event:c".to_string()]
+        );
+
+        // Writing the graph again brings it back
+        second.insert_triples("urn:epcis:event:a", &[shipped]).unwrap();
+        first.insert_triples("urn:epcis:event:b", &[]).unwrap();
+        assert_eq!(OxigraphStore::new(dir.path()).unwrap().graph_len("urn:epcis:event:a"), 1);
+    }
+
+    #[test]
+    fn test_every_graph_name_gets_its_own_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = OxigraphStore::new(dir.path()).unwrap();
+        let shipped = oxrdf::Triple::new(
+            oxrdf::NamedNode::new_unchecked("urn:epc:event:e1"),
+            oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:bizStep"),
+            oxrdf::NamedNode::new_unchecked("urn:epcglobal:cbv:bizstep:shipping"),
+        );
+        // Used to share a file, or to point outside the data directory
+        let names = ["urn:epcis:event:a:b", "urn:epcis:event:a_b", "urn:epcis:event:a/b", "https://example.com/graphs/../../events"];
+        for name in names {
+            store.insert_triples(name, &[shipped.clone()]).unwrap();
+        }
+
+        let reopened = OxigraphStore::new(dir.path()).unwrap();
+        for name in names {
+            assert_eq!(reopened.graph_len(name), 1, "{}", name);
+        }
+        let graph_files = std::fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file() && entry.file_name().to_string_lossy().ends_with(".ttl"))
+            .count();
+        assert_eq!(graph_files, names.len());
+
+        // Files saved under the old names are renamed when the store opens
+        let legacy = tempfile::tempdir().unwrap();
+        std::fs::write(legacy.path().join("store_metadata.json"), r#"{"graphs": ["urn:epcis:event:e1"], "created_at": ""}"#).unwrap();
+        std::fs::write(legacy.path().join("urn_epcis_event_e1.ttl"), format!("{} .\n", format_triple_star(shipped.as_ref()))).unwrap();
+        let migrated = OxigraphStore::new(legacy.path()).unwrap();
+        assert_eq!(migrated.graph_len("urn:epcis:event:e1"), 1);
+        assert!(!legacy.path().join("urn_epcis_event_e1.ttl").exists());
+        assert!(graph_file(legacy.path(), "urn:epcis:event:e1").exists());
+    }
+
+    #[test]
+    fn test_sparql_update_persists_and_rejects_unsupported_operations() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = OxigraphStore::new(dir.path()).unwrap();
+        for i in 0..3 {
+            store
+                .update(&format!("INSERT DATA {{ GRAPH <urn:epcis:event:update-{}> {{ <urn:epc:event:update-{}> <urn:epcglobal:epcis:bizStep> <urn:epcglobal:cbv:bizstep:receiving> }} }}", i, i))
+                .unwrap();
+        }
+        store.update("DROP GRAPH <urn:epcis:event:update-0>").unwrap();
+
+        let mut reopened = OxigraphStore::new(dir.path()).unwrap();
+        let graphs = reopened.graph_names();
+        for i in 0..3 {
+            assert_eq!(graphs.contains(&format!("urn:epcis:event:update-{}", i)), i > 0, "update-{}", i);
+        }
+
+        // A malformed or unsupported update changes nothing
+        assert!(reopened.update("INSERT DATA { <urn:x:a> <urn:x:b> <urn:x:c> }").is_err());
+        assert!(reopened.update("DROP GRAPH <urn:epcis:event:update-1> ; LOAD <https://example.com/events.ttl>").is_err());
+        assert_eq!(reopened.graph_len("urn:epcis:event:update-1"), 1);
+
+        // WHERE clauses only match in the graphs the caller may read
+        let report = reopened
+            .update_reading(
+                "INSERT { GRAPH <urn:epcis:event:copy> { ?s ?p ?o } } WHERE { GRAPH ?g { ?s ?p ?o } }",
+                |graph| graph == "urn:epcis:event:update-2",
+            )
+            .unwrap();
+        assert_eq!(report.inserted, 1);
+    }
+
+    #[test]
+    fn test_store_records_queries_and_size_for_the_monitor() {
+        use crate::monitoring::metrics::SystemMonitor;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = OxigraphStore::new(dir.path()).unwrap();
+        let monitor = SystemMonitor::new().with_store_metrics(store.metrics());
+        let triple = oxrdf::Triple::new(
+            oxrdf::NamedNode::new_unchecked("urn:epc:event:1"),
+            oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:epcList"),
+            oxrdf::NamedNode::new_unchecked("urn:epc:id:sgtin:0614141.107346.2017"),
+        );
+        store.insert_triples("urn:epcis:test", &[triple]).unwrap();
+
+        // Views and clones record into the same metrics
+        store.scoped_view(&["urn:epcis:test".to_string()]).query_select("SELECT ?s WHERE { ?s ?p ?o }").unwrap();
+        store.metrics().record_query(std::time::Duration::from_millis(4));
+        store.clone().metrics().record_cache_lookup(true);
+        store.metrics().record_cache_lookup(false);
+
+        let database = monitor.get_metrics().database_metrics;
+        assert_eq!(database.total_triples, 1);
+        assert_eq!(database.named_graphs, 1);
+        assert!(database.avg_query_time_ms >= 2.0);
+        assert_eq!(database.cache_hit_ratio, 0.5);
+        assert!(store.metrics().snapshot().storage_size_mb < 1);
+    }
+}
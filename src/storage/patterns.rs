@@ -0,0 +1,260 @@
+use crate::config::{EventMatcher, PatternRule, PatternSource};
+use crate::storage::counterfeit::Verification;
+use crate::storage::recall::LineageEvent;
+use chrono::{DateTime, Duration, Utc};
+use oxrdf::{Literal, NamedNode, Triple};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+const EPCIS_NS: &str = "urn:epcglobal:epcis:";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// A derived event recording that a pattern rule matched
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PatternMatch {
+    /// Stable identifier derived from the rule and what triggered it
+    pub id: String,
+    pub rule: String,
+    /// EPC for absence rules, location for threshold rules
+    pub subject: String,
+    pub window_start: String,
+    pub window_end: String,
+    /// Triggering events (empty when counting verifications)
+    pub event_ids: Vec<String>,
+    pub detail: String,
+}
+
+impl PatternMatch {
+    pub fn iri(&self) -> String {
+        format!("urn:epcis:pattern_match:{}", self.id)
+    }
+}
+
+impl EventMatcher {
+    pub fn matches(&self, event: &LineageEvent) -> bool {
+        let biz_step_matches = |expected: &str| {
+            event.biz_step.as_deref().map_or(false, |step| {
+                step == expected || step.rsplit(|c| c == ':' || c == '/').next() == Some(expected)
+            })
+        };
+        self.event_type.as_deref().map_or(true, |event_type| event.event_type == event_type)
+            && self.action.as_deref().map_or(true, |action| event.action.eq_ignore_ascii_case(action))
+            && self.biz_step.as_deref().map_or(true, biz_step_matches)
+    }
+}
+
+/// Evaluate every rule as of `now`.
+///
+/// Absence rules only fire once their window has fully elapsed, so a shipment
+/// is not reported as unreceived while it can still arrive.
+pub fn evaluate_rules(
+    rules: &[PatternRule],
+    events: &[LineageEvent],
+    verifications: &[Verification],
+    now: DateTime<Utc>,
+) -> Vec<PatternMatch> {
+    let mut events: Vec<&LineageEvent> = events.iter().collect();
+    events.sort_by_key(|event| event.event_time);
+
+    let mut matches = Vec::new();
+    for rule in rules {
+        match rule {
+            PatternRule::Absence { name, after, expect, window_minutes } => {
+                matches.extend(absences(name, after, expect, Duration::minutes(*window_minutes), &events, now));
+            }
+            PatternRule::Threshold { name, source, matcher, count, window_minutes } => {
+                let occurrences: Vec<(DateTime<Utc>, &str, Option<&str>)> = match source {
+                    PatternSource::Events => events
+                        .iter()
+                        .filter(|event| matcher.matches(event))
+                        .filter_map(|event| Some((event.event_time, event.biz_location.as_deref()?, Some(event.event_id.as_str()))))
+                        .collect(),
+                    PatternSource::FailedVerifications => verifications
+                        .iter()
+                        .filter(|verification| !verification.verified)
+                        .filter_map(|verification| {
+                            let time = DateTime::parse_from_rfc3339(verification.time.as_deref()?).ok()?;
+                            Some((time.with_timezone(&Utc), verification.location.as_deref()?, None))
+                        })
+                        .collect(),
+                };
+                matches.extend(thresholds(name, *count, Duration::minutes(*window_minutes), occurrences));
+            }
+        }
+    }
+    matches
+}
+
+fn absences(
+    rule: &str,
+    after: &EventMatcher,
+    expect: &EventMatcher,
+    window: Duration,
+    events: &[&LineageEvent],
+    now: DateTime<Utc>,
+) -> Vec<PatternMatch> {
+    let mut matches = Vec::new();
+    for (i, trigger) in events.iter().enumerate() {
+        let deadline = trigger.event_time + window;
+        if !after.matches(trigger) || deadline > now {
+            continue;
+        }
+        for epc in trigger.epcs.iter().chain(&trigger.parent) {
+            let fulfilled = events[i + 1..]
+                .iter()
+                .take_while(|event| event.event_time <= deadline)
+                .any(|event| expect.matches(event) && event.epcs.iter().chain(&event.parent).any(|other| other == epc));
+            if fulfilled {
+                continue;
+            }
+            matches.push(PatternMatch {
+                id: match_id(rule, epc, &trigger.event_id),
+                rule: rule.to_string(),
+                subject: epc.clone(),
+                window_start: trigger.event_time.to_rfc3339(),
+                window_end: deadline.to_rfc3339(),
+                event_ids: vec![trigger.event_id.clone()],
+                detail: format!("No matching follow-up for {} within {} minutes of event {}", epc, window.num_minutes(), trigger.event_id),
+            });
+        }
+    }
+    matches
+}
+
+/// One match per burst: once more than `count` occurrences fall in a window,
+/// counting restarts after the last of them.
+fn thresholds(
+    rule: &str,
+    count: usize,
+    window: Duration,
+    occurrences: Vec<(DateTime<Utc>, &str, Option<&str>)>,
+) -> Vec<PatternMatch> {
+    let mut by_location: BTreeMap<&str, Vec<(DateTime<Utc>, Option<&str>)>> = BTreeMap::new();
+    for (time, location, event_id) in occurrences {
+        by_location.entry(location).or_default().push((time, event_id));
+    }
+
+    let mut matches = Vec::new();
+    for (location, mut occurrences) in by_location {
+        occurrences.sort_by_key(|(time, _)| *time);
+        let mut start = 0;
+        for end in 0..occurrences.len() {
+            while occurrences[end].0 - occurrences[start].0 > window {
+                start += 1;
+            }
+            if end + 1 - start <= count {
+                continue;
+            }
+            let burst = &occurrences[start..=end];
+            let first = burst[0].0;
+            matches.push(PatternMatch {
+                id: match_id(rule, location, &first.to_rfc3339()),
+                rule: rule.to_string(),
+                subject: location.to_string(),
+                window_start: first.to_rfc3339(),
+                window_end: occurrences[end].0.to_rfc3339(),
+                event_ids: burst.iter().filter_map(|(_, event_id)| event_id.map(str::to_string)).collect(),
+                detail: format!("{} occurrences at {} within {} minutes (limit {})", burst.len(), location, window.num_minutes(), count),
+            });
+            start = end + 1;
+        }
+    }
+    matches
+}
+
+fn match_id(rule: &str, subject: &str, trigger: &str) -> String {
+    let digest = Sha256::digest(format!("{}|{}|{}", rule, subject, trigger).as_bytes());
+    digest.iter().take(8).map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// One `epcis:PatternMatch` resource per match, linked to its triggering events
+pub fn match_triples(matches: &[PatternMatch]) -> Vec<Triple> {
+    let epcis = |local: &str| NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+    let date_time = |value: &str| Literal::new_typed_literal(value, NamedNode::new_unchecked(format!("{}dateTime", XSD)));
+
+    let mut triples = Vec::new();
+    for pattern_match in matches {
+        let (Ok(node), Ok(subject)) = (NamedNode::new(pattern_match.iri()), NamedNode::new(pattern_match.subject.as_str())) else { continue };
+        triples.push(Triple::new(
+            node.clone(),
+            NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"),
+            epcis("PatternMatch"),
+        ));
+        triples.push(Triple::new(node.clone(), epcis("matchedRule"), Literal::new_simple_literal(pattern_match.rule.clone())));
+        triples.push(Triple::new(node.clone(), epcis("matchSubject"), subject));
+        triples.push(Triple::new(node.clone(), epcis("windowStart"), date_time(&pattern_match.window_start)));
+        triples.push(Triple::new(node.clone(), epcis("windowEnd"), date_time(&pattern_match.window_end)));
+        triples.push(Triple::new(node.clone(), epcis("matchDetail"), Literal::new_simple_literal(pattern_match.detail.clone())));
+        for event_id in &pattern_match.event_ids {
+            if let Ok(event) = NamedNode::new(format!("urn:epc:event:{}", event_id)) {
+                triples.push(Triple::new(node.clone(), epcis("matchedEvent"), event));
+            }
+        }
+    }
+    triples
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str, hours: i64, biz_step: &str, epc: &str) -> LineageEvent {
+        let start: DateTime<Utc> = "2024-03-01T00:00:00Z".parse().unwrap();
+        LineageEvent {
+            event_id: id.to_string(),
+            event_type: "ObjectEvent".to_string(),
+            action: "OBSERVE".to_string(),
+            event_time: start + Duration::hours(hours),
+            biz_step: Some(format!("urn:epcglobal:cbv:bizstep:{}", biz_step)),
+            biz_location: Some("urn:epc:id:sgln:0614141.00001.0".to_string()),
+            epcs: vec![epc.to_string()],
+            parent: None,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            destination_parties: Vec::new(),
+            destination_locations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_absence_and_threshold_rules() {
+        let step = |name: &str| EventMatcher { biz_step: Some(name.to_string()), ..Default::default() };
+        let rules = vec![
+            PatternRule::Absence {
+                name: "shipped-not-received".to_string(),
+                after: step("shipping"),
+                expect: step("receiving"),
+                window_minutes: 7 * 24 * 60,
+            },
+            PatternRule::Threshold {
+                name: "busy-dock".to_string(),
+                source: PatternSource::Events,
+                matcher: step("shipping"),
+                count: 2,
+                window_minutes: 60,
+            },
+        ];
+        let (a, b, c) = ("urn:epc:id:sgtin:0614141.107346.1", "urn:epc:id:sgtin:0614141.107346.2", "urn:epc:id:sgtin:0614141.107346.3");
+        let events = vec![
+            event("s1", 0, "shipping", a),
+            event("s2", 0, "shipping", b),
+            event("r1", 48, "receiving", a),
+            // Still inside its window at `now`
+            event("s3", 200, "shipping", c),
+        ];
+        let now = "2024-03-10T00:00:00Z".parse().unwrap();
+
+        let matches = evaluate_rules(&rules, &events, &[], now);
+        assert_eq!(matches.len(), 1);
+        assert_eq!((matches[0].rule.as_str(), matches[0].subject.as_str()), ("shipped-not-received", b));
+
+        // Two shipments an hour are within the limit, three are not
+        let burst = |ids: &[&str]| ids.iter().map(|id| event(id, 300, "shipping", a)).collect::<Vec<_>>();
+        assert!(evaluate_rules(&rules[1..], &burst(&["s4", "s5"]), &[], now).is_empty());
+        let matches = evaluate_rules(&rules[1..], &burst(&["s4", "s5", "s6"]), &[], now);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].event_ids.len(), 3);
+        assert!(match_triples(&matches).iter().any(|t| t.predicate.as_str() == "urn:epcglobal:epcis:matchedEvent"));
+    }
+}
@@ -0,0 +1,261 @@
+use crate::EpcisKgError;
+use chrono::{DateTime, Datelike, Utc};
+use oxrdf::{Graph as OxrdfGraph, NamedNodeRef, TermRef};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// Period by which the report groups events for its trend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrendBucket {
+    #[default]
+    Day,
+    Week,
+    Month,
+}
+
+impl std::str::FromStr for TrendBucket {
+    type Err = EpcisKgError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            "month" => Ok(Self::Month),
+            other => Err(EpcisKgError::InvalidField {
+                field: "bucket".to_string(),
+                message: format!("unknown trend bucket '{}' (use day, week or month)", other),
+            }),
+        }
+    }
+}
+
+impl TrendBucket {
+    /// Label of the period an event time falls into (`2024-03-15`, `2024-W11`, `2024-03`)
+    fn period(&self, time: DateTime<Utc>) -> String {
+        match self {
+            Self::Day => time.format("%Y-%m-%d").to_string(),
+            Self::Week => {
+                let week = time.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            Self::Month => time.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+/// Events the report covers
+#[derive(Debug, Clone, Default)]
+pub struct QualityOptions {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub bucket: TrendBucket,
+}
+
+/// Statistics of one predicate over the profiled events
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PredicateProfile {
+    pub predicate: String,
+    /// Events with at least one value
+    pub events_with_value: usize,
+    /// Share of the profiled events with a value, in percent
+    pub completeness_percent: f64,
+    pub distinct_values: usize,
+    pub literal_values: usize,
+    /// Typed literals whose lexical form is not valid for their XSD datatype
+    pub invalid_literals: usize,
+    pub invalid_literal_percent: f64,
+}
+
+/// Completeness of every profiled predicate within one period
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QualityTrendPoint {
+    pub period: String,
+    pub events: usize,
+    pub completeness_percent: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QualityReport {
+    pub generated_at: String,
+    pub total_events: usize,
+    /// Profiled events without a parsable eventTime; they are left out of the trend
+    pub events_without_time: usize,
+    pub predicates: Vec<PredicateProfile>,
+    pub trend: Vec<QualityTrendPoint>,
+}
+
+/// One event to profile: its time and the triples about its node
+pub struct ProfiledEvent<'a> {
+    pub node: NamedNodeRef<'a>,
+    pub event_time: Option<DateTime<Utc>>,
+    pub graph: &'a OxrdfGraph,
+}
+
+#[derive(Default)]
+struct PredicateStats {
+    events_with_value: usize,
+    distinct_values: HashSet<String>,
+    literal_values: usize,
+    invalid_literals: usize,
+}
+
+/// Profile event predicates: completeness, distinct values, invalid literals and their trend
+pub fn profile_events<'a>(events: impl IntoIterator<Item = ProfiledEvent<'a>>, options: &QualityOptions) -> QualityReport {
+    let mut total_events = 0;
+    let mut events_without_time = 0;
+    let mut stats: HashMap<String, PredicateStats> = HashMap::new();
+    let mut periods: BTreeMap<String, (usize, HashMap<String, usize>)> = BTreeMap::new();
+
+    for event in events {
+        let in_range = match event.event_time {
+            Some(time) => options.since.map_or(true, |since| time >= since) && options.until.map_or(true, |until| time < until),
+            None => options.since.is_none() && options.until.is_none(),
+        };
+        if !in_range {
+            continue;
+        }
+        total_events += 1;
+
+        let mut predicates: HashSet<String> = HashSet::new();
+        for triple in event.graph.triples_for_subject(event.node) {
+            let predicate = triple.predicate.as_str().to_string();
+            let entry = stats.entry(predicate.clone()).or_default();
+            entry.distinct_values.insert(triple.object.to_string());
+            if let TermRef::Literal(literal) = triple.object {
+                entry.literal_values += 1;
+                if !is_valid_lexical_form(literal.value(), literal.datatype().as_str()) {
+                    entry.invalid_literals += 1;
+                }
+            }
+            predicates.insert(predicate);
+        }
+        for predicate in &predicates {
+            if let Some(entry) = stats.get_mut(predicate) {
+                entry.events_with_value += 1;
+            }
+        }
+
+        match event.event_time {
+            Some(time) => {
+                let (count, with_value) = periods.entry(options.bucket.period(time)).or_default();
+                *count += 1;
+                for predicate in predicates {
+                    *with_value.entry(predicate).or_default() += 1;
+                }
+            }
+            None => events_without_time += 1,
+        }
+    }
+
+    let mut predicates: Vec<PredicateProfile> = stats
+        .into_iter()
+        .map(|(predicate, stats)| PredicateProfile {
+            completeness_percent: percent(stats.events_with_value, total_events),
+            invalid_literal_percent: percent(stats.invalid_literals, stats.literal_values),
+            events_with_value: stats.events_with_value,
+            distinct_values: stats.distinct_values.len(),
+            literal_values: stats.literal_values,
+            invalid_literals: stats.invalid_literals,
+            predicate,
+        })
+        .collect();
+    predicates.sort_by(|a, b| a.predicate.cmp(&b.predicate));
+
+    let trend = periods
+        .into_iter()
+        .map(|(period, (events, with_value))| QualityTrendPoint {
+            completeness_percent: predicates
+                .iter()
+                .map(|profile| (profile.predicate.clone(), percent(with_value.get(&profile.predicate).copied().unwrap_or(0), events)))
+                .collect(),
+            period,
+            events,
+        })
+        .collect();
+
+    QualityReport {
+        generated_at: Utc::now().to_rfc3339(),
+        total_events,
+        events_without_time,
+        predicates,
+        trend,
+    }
+}
+
+/// Share in percent, rounded to one decimal
+fn percent(part: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    (part as f64 * 1000.0 / total as f64).round() / 10.0
+}
+
+/// Whether a literal's lexical form is valid for its datatype; unchecked datatypes are valid
+fn is_valid_lexical_form(value: &str, datatype: &str) -> bool {
+    let Some(local) = datatype.strip_prefix(XSD) else { return true };
+    match local {
+        "dateTime" => DateTime::parse_from_rfc3339(value).is_ok(),
+        "date" => chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+        "integer" | "long" | "int" | "short" => value.parse::<i64>().is_ok(),
+        "nonNegativeInteger" | "positiveInteger" => value.parse::<u64>().is_ok_and(|n| local == "nonNegativeInteger" || n > 0),
+        "decimal" => !value.contains(['e', 'E']) && value.parse::<f64>().is_ok_and(f64::is_finite),
+        "double" | "float" => matches!(value, "INF" | "-INF" | "NaN") || value.parse::<f64>().is_ok_and(f64::is_finite),
+        "boolean" => matches!(value, "true" | "false" | "1" | "0"),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxrdf::{Literal, NamedNode, Triple};
+
+    #[test]
+    fn test_profile_completeness_invalid_literals_and_trend() {
+        let epcis = |local: &str| NamedNode::new_unchecked(format!("urn:epcglobal:epcis:{}", local));
+        let xsd_date_time = NamedNode::new_unchecked(format!("{}dateTime", XSD));
+        let event = |id: &str, time: &str, location: Option<&str>| {
+            let node = NamedNode::new_unchecked(format!("urn:epc:event:{}", id));
+            let mut graph = OxrdfGraph::default();
+            graph.insert(&Triple::new(node.clone(), epcis("eventTime"), Literal::new_typed_literal(time, xsd_date_time.clone())));
+            if let Some(location) = location {
+                graph.insert(&Triple::new(node.clone(), epcis("bizLocation"), NamedNode::new_unchecked(location)));
+            }
+            (node, graph)
+        };
+        let events = vec![
+            event("1", "2024-03-01T10:00:00Z", Some("urn:epc:id:sgln:0614141.00001.0")),
+            event("2", "2024-03-01T12:00:00Z", None),
+            event("3", "not-a-time", Some("urn:epc:id:sgln:0614141.00001.0")),
+        ];
+        let profiled = events.iter().map(|(node, graph)| ProfiledEvent {
+            node: node.as_ref(),
+            event_time: graph
+                .object_for_subject_predicate(node, &epcis("eventTime"))
+                .and_then(|term| match term {
+                    TermRef::Literal(literal) => DateTime::parse_from_rfc3339(literal.value()).ok(),
+                    _ => None,
+                })
+                .map(|time| time.with_timezone(&Utc)),
+            graph,
+        });
+
+        let report = profile_events(profiled, &QualityOptions::default());
+        assert_eq!(report.total_events, 3);
+        assert_eq!(report.events_without_time, 1);
+
+        let location = report.predicates.iter().find(|p| p.predicate.ends_with("bizLocation")).unwrap();
+        assert_eq!((location.events_with_value, location.completeness_percent, location.distinct_values), (2, 66.7, 1));
+        let time = report.predicates.iter().find(|p| p.predicate.ends_with("eventTime")).unwrap();
+        assert_eq!((time.invalid_literals, time.invalid_literal_percent), (1, 33.3));
+
+        assert_eq!(report.trend.len(), 1);
+        assert_eq!(report.trend[0].period, "2024-03-01");
+        assert_eq!(report.trend[0].completeness_percent["urn:epcglobal:epcis:bizLocation"], 50.0);
+
+        assert_eq!("month".parse::<TrendBucket>().unwrap(), TrendBucket::Month);
+        assert!("hour".parse::<TrendBucket>().is_err());
+    }
+}
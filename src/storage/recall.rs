@@ -0,0 +1,321 @@
+use crate::storage::inventory::{class_pattern_matches, product_class};
+use chrono::{DateTime, Utc};
+use oxrdf::{Literal, NamedNode, Triple};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Prefix of recall impact graphs (`urn:epcis:recall:<report id>`)
+pub const RECALL_GRAPH_PREFIX: &str = "urn:epcis:recall:";
+
+const EPCIS_NS: &str = "urn:epcglobal:epcis:";
+
+/// The parts of a stored event needed to follow product lineage and shipments
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineageEvent {
+    pub event_id: String,
+    pub event_type: String,
+    pub action: String,
+    pub event_time: DateTime<Utc>,
+    pub biz_step: Option<String>,
+    pub biz_location: Option<String>,
+    pub epcs: Vec<String>,
+    /// Aggregation parent (AggregationEvent only)
+    pub parent: Option<String>,
+    pub inputs: Vec<String>,
+    pub outputs: Vec<String>,
+    /// Destination owning and possessing parties
+    pub destination_parties: Vec<String>,
+    pub destination_locations: Vec<String>,
+}
+
+/// What is contaminated and which part of the history to follow
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecallRequest {
+    #[serde(default)]
+    pub epcs: Vec<String>,
+    /// LGTIN class URIs, or `urn:epc:idpat:...*` patterns matching a range of EPCs
+    #[serde(default)]
+    pub lots: Vec<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImpactReason {
+    /// Named in the request, directly or through a lot
+    Contaminated,
+    /// Produced by a TransformationEvent from an affected input
+    DerivedProduct,
+    /// A logistic unit an affected EPC was packed into; not counted as exposure
+    Container,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AffectedEpc {
+    pub epc: String,
+    pub product_class: String,
+    pub reason: ImpactReason,
+    /// Event that made the EPC affected, if any
+    pub event_id: Option<String>,
+    /// Affected inputs a derived product was made from
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub derived_from: Vec<String>,
+}
+
+/// An event that moved affected goods towards other locations or partners
+#[derive(Debug, Clone, Serialize)]
+pub struct ShipmentImpact {
+    pub event_id: String,
+    pub event_time: String,
+    pub biz_location: Option<String>,
+    pub epcs: Vec<String>,
+    pub destination_locations: Vec<String>,
+    pub destination_parties: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExposureLine {
+    pub product_class: String,
+    pub quantity: usize,
+}
+
+/// Downstream impact of a recall
+#[derive(Debug, Clone, Serialize)]
+pub struct RecallReport {
+    pub report_id: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub events_considered: usize,
+    pub affected: Vec<AffectedEpc>,
+    pub derived_products: Vec<String>,
+    pub shipments: Vec<ShipmentImpact>,
+    /// Business locations affected goods were seen at or shipped to
+    pub locations: Vec<String>,
+    pub partners: Vec<String>,
+    pub exposure: Vec<ExposureLine>,
+    pub total_quantity: usize,
+}
+
+impl RecallRequest {
+    fn is_seed(&self, epc: &str) -> bool {
+        self.epcs.iter().any(|seed| seed == epc) || self.lots.iter().any(|lot| class_pattern_matches(lot, epc))
+    }
+
+    fn in_window(&self, time: DateTime<Utc>) -> bool {
+        self.from.map_or(true, |from| time >= from) && self.to.map_or(true, |to| time <= to)
+    }
+}
+
+/// Follow contaminated EPCs forward through the events in the request window.
+///
+/// Events are replayed in time order: transformations spread contamination
+/// to their outputs, aggregations mark the containers affected goods were
+/// packed into, and every event touching affected goods contributes its
+/// locations, destinations and destination parties.
+pub fn simulate_recall(events: &[LineageEvent], request: &RecallRequest) -> RecallReport {
+    let mut replay: Vec<&LineageEvent> = events.iter().filter(|event| request.in_window(event.event_time)).collect();
+    replay.sort_by_key(|event| event.event_time);
+
+    let mut affected: BTreeMap<String, AffectedEpc> = BTreeMap::new();
+    let mark = |affected: &mut BTreeMap<String, AffectedEpc>, epc: &str, reason, event_id: Option<&str>, derived_from: Vec<String>| {
+        affected.entry(epc.to_string()).or_insert_with(|| AffectedEpc {
+            epc: epc.to_string(),
+            product_class: product_class(epc),
+            reason,
+            event_id: event_id.map(str::to_string),
+            derived_from,
+        });
+    };
+    for epc in &request.epcs {
+        mark(&mut affected, epc, ImpactReason::Contaminated, None, Vec::new());
+    }
+
+    let mut shipments = Vec::new();
+    let mut locations = BTreeSet::new();
+    let mut partners = BTreeSet::new();
+
+    for event in &replay {
+        let identifiers = event.epcs.iter().chain(&event.inputs).chain(event.parent.iter());
+        for epc in identifiers.filter(|epc| request.is_seed(epc)) {
+            mark(&mut affected, epc, ImpactReason::Contaminated, Some(event.event_id.as_str()), Vec::new());
+        }
+
+        let affected_inputs: Vec<String> = event.inputs.iter().filter(|epc| affected.contains_key(*epc)).cloned().collect();
+        if event.event_type == "TransformationEvent" && !affected_inputs.is_empty() {
+            for output in &event.outputs {
+                mark(&mut affected, output, ImpactReason::DerivedProduct, Some(event.event_id.as_str()), affected_inputs.clone());
+            }
+        }
+
+        if let (Some(parent), true) = (&event.parent, event.event_type == "AggregationEvent" && event.action != "DELETE") {
+            if event.epcs.iter().any(|epc| affected.contains_key(epc)) {
+                mark(&mut affected, parent, ImpactReason::Container, Some(event.event_id.as_str()), Vec::new());
+            }
+        }
+
+        let touched: Vec<String> = event
+            .epcs
+            .iter()
+            .chain(&event.outputs)
+            .chain(event.parent.iter())
+            .filter(|epc| affected.contains_key(*epc))
+            .cloned()
+            .collect();
+        if touched.is_empty() && affected_inputs.is_empty() {
+            continue;
+        }
+
+        locations.extend(event.biz_location.iter().cloned());
+        locations.extend(event.destination_locations.iter().cloned());
+        partners.extend(event.destination_parties.iter().cloned());
+
+        let is_shipping = event.biz_step.as_deref().map_or(false, |step| step.ends_with("shipping"));
+        if is_shipping || !event.destination_locations.is_empty() || !event.destination_parties.is_empty() {
+            shipments.push(ShipmentImpact {
+                event_id: event.event_id.clone(),
+                event_time: event.event_time.to_rfc3339(),
+                biz_location: event.biz_location.clone(),
+                epcs: touched,
+                destination_locations: event.destination_locations.clone(),
+                destination_parties: event.destination_parties.clone(),
+            });
+        }
+    }
+
+    let mut exposure: BTreeMap<String, usize> = BTreeMap::new();
+    for epc in affected.values().filter(|epc| epc.reason != ImpactReason::Container) {
+        *exposure.entry(epc.product_class.clone()).or_default() += 1;
+    }
+
+    RecallReport {
+        report_id: uuid::Uuid::new_v4().to_string(),
+        from: request.from.map(|time| time.to_rfc3339()),
+        to: request.to.map(|time| time.to_rfc3339()),
+        events_considered: replay.len(),
+        derived_products: affected
+            .values()
+            .filter(|epc| epc.reason == ImpactReason::DerivedProduct)
+            .map(|epc| epc.epc.clone())
+            .collect(),
+        affected: affected.into_values().collect(),
+        shipments,
+        locations: locations.into_iter().collect(),
+        partners: partners.into_iter().collect(),
+        total_quantity: exposure.values().sum(),
+        exposure: exposure
+            .into_iter()
+            .map(|(product_class, quantity)| ExposureLine { product_class, quantity })
+            .collect(),
+    }
+}
+
+impl RecallReport {
+    pub fn graph_name(&self) -> String {
+        format!("{}{}", RECALL_GRAPH_PREFIX, self.report_id)
+    }
+
+    /// The report as an RDF impact graph; identifiers that are not IRIs are skipped
+    pub fn to_triples(&self) -> Vec<Triple> {
+        let epcis = |local: &str| NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+        let iri = |value: &str| NamedNode::new(value).ok();
+        let report = NamedNode::new_unchecked(self.graph_name());
+        let mut triples = vec![Triple::new(
+            report.clone(),
+            NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"),
+            epcis("RecallImpact"),
+        )];
+
+        for affected in &self.affected {
+            let Some(epc) = iri(&affected.epc) else { continue };
+            triples.push(Triple::new(report.clone(), epcis("affects"), epc.clone()));
+            let reason = serde_json::to_value(affected.reason).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default();
+            triples.push(Triple::new(epc.clone(), epcis("impactReason"), Literal::new_simple_literal(reason)));
+            for input in affected.derived_from.iter().filter_map(|input| iri(input)) {
+                triples.push(Triple::new(epc.clone(), epcis("derivedFrom"), input));
+            }
+        }
+        for shipment in &self.shipments {
+            if let Some(event) = iri(&format!("urn:epc:event:{}", shipment.event_id)) {
+                triples.push(Triple::new(report.clone(), epcis("impactedEvent"), event));
+            }
+        }
+        for location in self.locations.iter().filter_map(|location| iri(location)) {
+            triples.push(Triple::new(report.clone(), epcis("impactedLocation"), location));
+        }
+        for party in self.partners.iter().filter_map(|party| iri(party)) {
+            triples.push(Triple::new(report.clone(), epcis("impactedParty"), party));
+        }
+        triples.push(Triple::new(
+            report,
+            epcis("exposureQuantity"),
+            Literal::new_typed_literal(
+                self.total_quantity.to_string(),
+                NamedNode::new_unchecked("http://www.w3.org/2001/XMLSchema#integer"),
+            ),
+        ));
+
+        triples
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(id: &str, event_type: &str, day: u32) -> LineageEvent {
+        LineageEvent {
+            event_id: id.to_string(),
+            event_type: event_type.to_string(),
+            action: "ADD".to_string(),
+            event_time: format!("2024-03-{:02}T09:00:00Z", day).parse().unwrap(),
+            biz_step: None,
+            biz_location: Some("urn:epc:id:sgln:0614141.00001.0".to_string()),
+            epcs: Vec::new(),
+            parent: None,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            destination_parties: Vec::new(),
+            destination_locations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_recall_follows_transformation_and_shipment() {
+        let lot = "urn:epc:class:lgtin:0614141.107346.LOT7";
+        let blend = "urn:epc:id:sgtin:0614141.200000.1";
+        let retailer = "urn:epc:id:pgln:0614143.00000";
+
+        let mut transform = event("t1", "TransformationEvent", 2);
+        transform.inputs = vec![lot.to_string(), "urn:epc:class:lgtin:0614141.107346.LOT8".to_string()];
+        transform.outputs = vec![blend.to_string()];
+
+        let mut ship = event("s1", "ObjectEvent", 3);
+        ship.biz_step = Some("urn:epcglobal:cbv:bizstep:shipping".to_string());
+        ship.epcs = vec![blend.to_string(), "urn:epc:id:sgtin:0614141.300000.9".to_string()];
+        ship.destination_parties = vec![retailer.to_string()];
+
+        let mut late = event("s2", "ObjectEvent", 20);
+        late.epcs = vec![blend.to_string()];
+        late.biz_location = Some("urn:epc:id:sgln:0614143.00009.0".to_string());
+
+        let request = RecallRequest {
+            lots: vec![lot.to_string()],
+            to: Some("2024-03-10T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        let report = simulate_recall(&[late, ship, transform], &request);
+
+        assert_eq!(report.events_considered, 2);
+        assert_eq!(report.derived_products, vec![blend.to_string()]);
+        assert_eq!(report.partners, vec![retailer.to_string()]);
+        assert_eq!(report.shipments.len(), 1);
+        assert_eq!(report.shipments[0].epcs, vec![blend.to_string()]);
+        assert_eq!(report.locations, vec!["urn:epc:id:sgln:0614141.00001.0".to_string()]);
+        assert_eq!(report.total_quantity, 2);
+        assert!(report
+            .to_triples()
+            .iter()
+            .any(|t| t.predicate.as_str().ends_with("derivedFrom") && t.object.to_string().contains("LOT7")));
+    }
+}
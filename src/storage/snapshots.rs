@@ -0,0 +1,196 @@
+use crate::config::SnapshotConfig;
+use crate::storage::oxigraph_store::OxigraphStore;
+use crate::EpcisKgError;
+use chrono::{DateTime, Utc};
+use parking_lot::{Mutex, RwLock};
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Snapshot name pinning a query to the last batch boundary
+pub const LATEST_STABLE: &str = "latest-stable";
+
+/// Snapshot name for the live store, the default
+pub const LIVE: &str = "live";
+
+/// Copy of the store taken while no write batch was in flight
+pub struct StoreSnapshot {
+    pub id: u64,
+    pub taken_at: DateTime<Utc>,
+    pub store: OxigraphStore,
+}
+
+impl StoreSnapshot {
+    pub fn info(&self) -> SnapshotInfo {
+        SnapshotInfo {
+            id: self.id,
+            version: self.store.version(),
+            taken_at: self.taken_at.to_rfc3339(),
+            triples: self.store.graph_names().iter().map(|name| self.store.graph_len(name)).sum(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotInfo {
+    pub id: u64,
+    /// Store version the snapshot was taken at
+    pub version: u64,
+    pub taken_at: String,
+    pub triples: usize,
+}
+
+/// Pinned snapshots for analytics queries that must not see half-committed batches.
+///
+/// Every write request is a batch, which may lock the store several times.
+/// Writers hold a `WriteBatch` while they run; a snapshot is only taken while
+/// none is held, so it always sits on a batch boundary. The latest stable
+/// snapshot advances when the last in-flight batch finishes, at most once per
+/// `min_interval_ms`, and catches up when queried after ingestion pauses.
+pub struct SnapshotManager {
+    config: SnapshotConfig,
+    in_flight: AtomicUsize,
+    next_id: AtomicU64,
+    latest: RwLock<Option<Arc<StoreSnapshot>>>,
+    last_advance: Mutex<Option<Instant>>,
+}
+
+/// An in-flight write batch; the batch ends when this is dropped
+pub struct WriteBatch {
+    manager: Arc<SnapshotManager>,
+}
+
+impl Drop for WriteBatch {
+    fn drop(&mut self) {
+        self.manager.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl SnapshotManager {
+    pub fn from_config(config: &SnapshotConfig) -> Self {
+        Self {
+            config: config.clone(),
+            in_flight: AtomicUsize::new(0),
+            next_id: AtomicU64::new(1),
+            latest: RwLock::new(None),
+            last_advance: Mutex::new(None),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    pub fn begin_write(self: &Arc<Self>) -> WriteBatch {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        WriteBatch { manager: Arc::clone(self) }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    /// Advance the latest stable snapshot after a batch, unless another one is
+    /// in flight or the last advance was under `min_interval_ms` ago
+    pub fn advance(&self, store: &std::sync::Mutex<OxigraphStore>) -> Result<Option<Arc<StoreSnapshot>>, EpcisKgError> {
+        let interval = Duration::from_millis(self.config.min_interval_ms);
+        if self.last_advance.lock().is_some_and(|last| last.elapsed() < interval) {
+            return Ok(None);
+        }
+        self.take(store)
+    }
+
+    /// Snapshot the store if it sits on a batch boundary and changed since the latest snapshot
+    fn take(&self, store: &std::sync::Mutex<OxigraphStore>) -> Result<Option<Arc<StoreSnapshot>>, EpcisKgError> {
+        if !self.config.enabled {
+            return Ok(None);
+        }
+        let store = store.lock().map_err(|e| EpcisKgError::Storage(format!("Failed to acquire store lock: {}", e)))?;
+        // Writers register before they lock the store, so none can be half-way through a batch now
+        if self.in_flight() > 0 {
+            return Ok(None);
+        }
+        let current = self.latest.read().clone();
+        if let Some(current) = current.filter(|current| current.store.version() == store.version()) {
+            return Ok(Some(current));
+        }
+        let snapshot = Arc::new(StoreSnapshot {
+            id: self.next_id.fetch_add(1, Ordering::SeqCst),
+            taken_at: Utc::now(),
+            store: store.snapshot(),
+        });
+        drop(store);
+        *self.latest.write() = Some(Arc::clone(&snapshot));
+        *self.last_advance.lock() = Some(Instant::now());
+        Ok(Some(snapshot))
+    }
+
+    /// The latest stable snapshot, brought up to date first if no batch is in flight
+    pub fn latest_stable(&self, store: &std::sync::Mutex<OxigraphStore>) -> Result<Arc<StoreSnapshot>, EpcisKgError> {
+        if !self.config.enabled {
+            return Err(EpcisKgError::Config("Query snapshots are disabled; set enabled = true in [snapshots]".to_string()));
+        }
+        if let Some(snapshot) = self.take(store)? {
+            return Ok(snapshot);
+        }
+        self.latest
+            .read()
+            .clone()
+            .ok_or_else(|| EpcisKgError::Conflict("No stable snapshot yet; ingestion has not paused since startup".to_string()))
+    }
+
+    /// Snapshot a query asked for by name; `None` means the live store
+    pub fn resolve(&self, name: Option<&str>, store: &std::sync::Mutex<OxigraphStore>) -> Result<Option<Arc<StoreSnapshot>>, EpcisKgError> {
+        match name {
+            None | Some(LIVE) => Ok(None),
+            Some(LATEST_STABLE) => self.latest_stable(store).map(Some),
+            Some(other) => Err(EpcisKgError::InvalidField {
+                field: "snapshot".to_string(),
+                message: format!("unknown snapshot '{}' (expected {} or {})", other, LATEST_STABLE, LIVE),
+            }),
+        }
+    }
+
+    pub fn latest(&self) -> Option<SnapshotInfo> {
+        self.latest.read().as_ref().map(|snapshot| snapshot.info())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(id: &str) -> oxrdf::Triple {
+        oxrdf::Triple::new(
+            oxrdf::NamedNode::new_unchecked(format!("urn:epc:event:{}", id)),
+            oxrdf::NamedNode::new_unchecked("http://www.w3.org/1999/02/22-rdf-syntax-ns#type"),
+            oxrdf::NamedNode::new_unchecked("urn:epcglobal:epcis:ObjectEvent"),
+        )
+    }
+
+    #[test]
+    fn test_snapshots_only_advance_on_batch_boundaries() {
+        let store = std::sync::Mutex::new(OxigraphStore::new_memory().unwrap());
+        let manager = Arc::new(SnapshotManager::from_config(&SnapshotConfig { enabled: true, min_interval_ms: 0 }));
+        store.lock().unwrap().insert_triples("urn:epcis:event:e1", &[typed("e1")]).unwrap();
+        let first = manager.latest_stable(&store).unwrap();
+        assert_eq!(first.info().triples, 1);
+
+        // Half of a batch is written; queries keep seeing the boundary before it
+        let batch = manager.begin_write();
+        store.lock().unwrap().insert_triples("urn:epcis:event:e2", &[typed("e2")]).unwrap();
+        assert!(manager.advance(&store).unwrap().is_none());
+        assert_eq!(manager.latest_stable(&store).unwrap().id, first.id);
+        store.lock().unwrap().insert_triples("urn:epcis:event:e3", &[typed("e3")]).unwrap();
+        drop(batch);
+
+        let advanced = manager.advance(&store).unwrap().unwrap();
+        assert_eq!(advanced.info().triples, 3);
+        assert!(advanced.store.is_read_only());
+        // Nothing changed since, so the same snapshot is served
+        assert_eq!(manager.latest_stable(&store).unwrap().id, advanced.id);
+        assert!(manager.resolve(Some(LIVE), &store).unwrap().is_none());
+        assert!(manager.resolve(Some("yesterday"), &store).is_err());
+    }
+}
@@ -0,0 +1,819 @@
+use crate::storage::oxigraph_store::triples_for_pattern;
+use crate::EpcisKgError;
+use chrono::DateTime;
+use oxrdf::{BlankNode, Graph, Literal, NamedNode, NamedNodeRef, Subject, SubjectRef, Term, TermRef, Triple};
+use serde::Serialize;
+use spargebra::algebra::{Expression, Function, GraphPattern, QueryDataset};
+use spargebra::term::{
+    GraphName, GraphNamePattern, GroundQuadPattern, GroundSubject, GroundTerm, GroundTermPattern, GroundTriple, NamedNodePattern,
+    QuadPattern, TermPattern, TriplePattern,
+};
+use spargebra::{GraphUpdateOperation, Query, Update};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// Values of the variables of one `WHERE` match by name; blank nodes of the
+/// pattern act as variables named `_:label`
+pub type Solution = HashMap<String, Term>;
+
+/// Triples one SPARQL update added and removed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct UpdateReport {
+    pub inserted: usize,
+    /// A triple removed from several graphs counts once per graph
+    pub deleted: usize,
+}
+
+/// Reject `WHERE` clauses using what [`solutions`] does not evaluate, before anything applies
+pub fn check_pattern(pattern: &GraphPattern) -> Result<(), EpcisKgError> {
+    match pattern {
+        GraphPattern::Bgp { .. } | GraphPattern::Values { .. } => Ok(()),
+        GraphPattern::Join { left, right } | GraphPattern::Union { left, right } => {
+            check_pattern(left)?;
+            check_pattern(right)
+        }
+        GraphPattern::LeftJoin { left, right, expression } => {
+            check_pattern(left)?;
+            check_pattern(right)?;
+            expression.as_ref().map_or(Ok(()), check_expression)
+        }
+        GraphPattern::Filter { expr, inner } => {
+            check_expression(expr)?;
+            check_pattern(inner)
+        }
+        GraphPattern::Extend { inner, expression, .. } => {
+            check_expression(expression)?;
+            check_pattern(inner)
+        }
+        GraphPattern::Graph { inner, .. } => check_pattern(inner),
+        other => {
+            let construct = match other {
+                GraphPattern::Path { .. } => "property paths",
+                GraphPattern::Minus { .. } => "MINUS",
+                GraphPattern::Service { .. } => "SERVICE",
+                GraphPattern::Group { .. } => "aggregates",
+                GraphPattern::OrderBy { .. } => "ORDER BY",
+                _ => "subqueries",
+            };
+            Err(EpcisKgError::NotImplemented(format!("Graph-aware queries, updates and views do not support {} in WHERE clauses", construct)))
+        }
+    }
+}
+
+fn check_expression(expression: &Expression) -> Result<(), EpcisKgError> {
+    let operands: Vec<&Expression> = match expression {
+        Expression::NamedNode(_) | Expression::Literal(_) | Expression::Variable(_) | Expression::Bound(_) => Vec::new(),
+        Expression::Not(inner) => vec![inner.as_ref()],
+        Expression::And(left, right)
+        | Expression::Or(left, right)
+        | Expression::Equal(left, right)
+        | Expression::SameTerm(left, right)
+        | Expression::Greater(left, right)
+        | Expression::GreaterOrEqual(left, right)
+        | Expression::Less(left, right)
+        | Expression::LessOrEqual(left, right) => vec![left.as_ref(), right.as_ref()],
+        Expression::In(value, list) => std::iter::once(value.as_ref()).chain(list).collect(),
+        Expression::FunctionCall(
+            Function::Str | Function::StrStarts | Function::StrEnds | Function::Contains | Function::IsIri | Function::IsBlank | Function::IsLiteral,
+            arguments,
+        ) => arguments.iter().collect(),
+        other => {
+            return Err(EpcisKgError::NotImplemented(format!("Graph-aware queries, updates and views do not support the filter expression {}", other)));
+        }
+    };
+    operands.into_iter().try_for_each(check_expression)
+}
+
+/// Matches of a `WHERE` clause over `graphs`.
+///
+/// Every triple lives in a named graph, so the default graph is the union of
+/// all of them unless `USING`, `WITH` or `FROM` names the graphs to use.
+pub fn solutions<'a>(
+    graphs: impl IntoIterator<Item = (&'a String, &'a Graph)>,
+    pattern: &GraphPattern,
+    using: Option<&QueryDataset>,
+) -> Vec<Solution> {
+    let graphs: Vec<(&String, &Graph)> = graphs.into_iter().collect();
+    let pick = |names: &[NamedNode]| -> Vec<(&'a String, &'a Graph)> {
+        graphs.iter().filter(|(name, _)| names.iter().any(|wanted| wanted.as_str() == name.as_str())).copied().collect()
+    };
+    let default: Vec<&Graph> = match using {
+        Some(dataset) => pick(&dataset.default).into_iter().map(|(_, graph)| graph).collect(),
+        None => graphs.iter().map(|(_, graph)| *graph).collect(),
+    };
+    let named = match using.and_then(|dataset| dataset.named.as_deref()) {
+        Some(names) => pick(names),
+        None => graphs.clone(),
+    };
+    Dataset { named }.eval(pattern, &default, vec![Solution::new()])
+}
+
+/// Matches of a query pattern with its `DISTINCT`, `REDUCED`, `OFFSET`, `LIMIT`
+/// and projection applied, checking the pattern before evaluating it
+pub fn query_solutions<'a>(
+    graphs: impl IntoIterator<Item = (&'a String, &'a Graph)>,
+    pattern: &GraphPattern,
+    dataset: Option<&QueryDataset>,
+) -> Result<Vec<Solution>, EpcisKgError> {
+    let graphs: Vec<(&String, &Graph)> = graphs.into_iter().collect();
+    match pattern {
+        GraphPattern::Slice { inner, start, length } => {
+            let solutions = query_solutions(graphs, inner, dataset)?;
+            Ok(solutions.into_iter().skip(*start).take(length.unwrap_or(usize::MAX)).collect())
+        }
+        GraphPattern::Distinct { inner } | GraphPattern::Reduced { inner } => {
+            let mut seen = HashSet::new();
+            let mut solutions = query_solutions(graphs, inner, dataset)?;
+            solutions.retain(|solution| {
+                let mut key: Vec<(&String, &Term)> = solution.iter().collect();
+                key.sort_by(|a, b| a.0.cmp(b.0));
+                seen.insert(key.into_iter().map(|(name, value)| (name.clone(), value.clone())).collect::<Vec<_>>())
+            });
+            Ok(solutions)
+        }
+        GraphPattern::Project { inner, variables } => {
+            let mut solutions = query_solutions(graphs, inner, dataset)?;
+            for solution in &mut solutions {
+                solution.retain(|name, _| variables.iter().any(|variable| variable.as_str() == name));
+            }
+            Ok(solutions)
+        }
+        other => {
+            check_pattern(other)?;
+            Ok(solutions(graphs, other, dataset))
+        }
+    }
+}
+
+/// Variables a SELECT projects, in order
+pub fn projected_variables(pattern: &GraphPattern) -> Vec<String> {
+    match pattern {
+        GraphPattern::Slice { inner, .. } | GraphPattern::Distinct { inner } | GraphPattern::Reduced { inner } => projected_variables(inner),
+        GraphPattern::Project { variables, .. } => variables.iter().map(|variable| variable.as_str().to_string()).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Whether a query addresses named graphs, through `FROM`/`FROM NAMED` or a `GRAPH` pattern
+pub fn addresses_graphs(pattern: &GraphPattern, dataset: Option<&QueryDataset>) -> bool {
+    dataset.is_some() || uses_graph_patterns(pattern)
+}
+
+fn uses_graph_patterns(pattern: &GraphPattern) -> bool {
+    match pattern {
+        GraphPattern::Graph { .. } => true,
+        GraphPattern::Join { left, right }
+        | GraphPattern::Union { left, right }
+        | GraphPattern::LeftJoin { left, right, .. }
+        | GraphPattern::Minus { left, right } => uses_graph_patterns(left) || uses_graph_patterns(right),
+        GraphPattern::Filter { inner, .. }
+        | GraphPattern::Extend { inner, .. }
+        | GraphPattern::OrderBy { inner, .. }
+        | GraphPattern::Project { inner, .. }
+        | GraphPattern::Distinct { inner }
+        | GraphPattern::Reduced { inner }
+        | GraphPattern::Slice { inner, .. }
+        | GraphPattern::Group { inner, .. } => uses_graph_patterns(inner),
+        _ => false,
+    }
+}
+
+struct Dataset<'a> {
+    named: Vec<(&'a String, &'a Graph)>,
+}
+
+impl Dataset<'_> {
+    /// Extend each of `seeds` with the matches of `pattern` in the `active` graphs
+    fn eval(&self, pattern: &GraphPattern, active: &[&Graph], seeds: Vec<Solution>) -> Vec<Solution> {
+        match pattern {
+            GraphPattern::Bgp { patterns } => patterns.iter().fold(seeds, |solutions, triple| {
+                solutions.into_iter().flat_map(|solution| match_triple(triple, active, solution)).collect()
+            }),
+            GraphPattern::Join { left, right } => {
+                let left = self.eval(left, active, seeds);
+                self.eval(right, active, left)
+            }
+            GraphPattern::Union { left, right } => {
+                let mut solutions = self.eval(left, active, seeds.clone());
+                solutions.extend(self.eval(right, active, seeds));
+                solutions
+            }
+            GraphPattern::LeftJoin { left, right, expression } => self
+                .eval(left, active, seeds)
+                .into_iter()
+                .flat_map(|solution| {
+                    let mut joined = self.eval(right, active, vec![solution.clone()]);
+                    if let Some(expression) = expression {
+                        joined.retain(|joined| is_true(expression, joined));
+                    }
+                    if joined.is_empty() {
+                        vec![solution]
+                    } else {
+                        joined
+                    }
+                })
+                .collect(),
+            GraphPattern::Filter { expr, inner } => {
+                let mut solutions = self.eval(inner, active, seeds);
+                solutions.retain(|solution| is_true(expr, solution));
+                solutions
+            }
+            GraphPattern::Extend { inner, variable, expression } => self
+                .eval(inner, active, seeds)
+                .into_iter()
+                .filter_map(|mut solution| match evaluate(expression, &solution) {
+                    Some(value) => bind(&mut solution, variable.as_str(), value).then_some(solution),
+                    None => Some(solution),
+                })
+                .collect(),
+            GraphPattern::Graph { name, inner } => seeds
+                .into_iter()
+                .flat_map(|seed| {
+                    self.named
+                        .iter()
+                        .filter_map(|&(graph_name, graph)| {
+                            let mut seed = seed.clone();
+                            let matches = match name {
+                                NamedNodePattern::NamedNode(node) => node.as_str() == graph_name.as_str(),
+                                NamedNodePattern::Variable(variable) => {
+                                    bind(&mut seed, variable.as_str(), NamedNode::new_unchecked(graph_name.as_str()).into())
+                                }
+                            };
+                            matches.then(|| self.eval(inner, &[graph], vec![seed]))
+                        })
+                        .flatten()
+                        .collect::<Vec<_>>()
+                })
+                .collect(),
+            GraphPattern::Values { variables, bindings } => seeds
+                .into_iter()
+                .flat_map(|seed| {
+                    bindings.iter().filter_map(move |row| {
+                        let mut solution = seed.clone();
+                        for (variable, value) in variables.iter().zip(row) {
+                            if let Some(value) = value {
+                                if !bind(&mut solution, variable.as_str(), ground_term(value.clone())) {
+                                    return None;
+                                }
+                            }
+                        }
+                        Some(solution)
+                    })
+                })
+                .collect(),
+            // Rejected by check_pattern
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn match_triple(pattern: &TriplePattern, active: &[&Graph], solution: Solution) -> Vec<Solution> {
+    let subject = resolve(&pattern.subject, &solution);
+    let predicate = match &pattern.predicate {
+        NamedNodePattern::NamedNode(node) => Some(Term::NamedNode(node.clone())),
+        NamedNodePattern::Variable(variable) => solution.get(variable.as_str()).cloned(),
+    };
+    let object = resolve(&pattern.object, &solution);
+    // A bound value that cannot stand in its position matches nothing
+    let subject_ref = match &subject {
+        Some(term) => match as_subject(term) {
+            Some(subject) => Some(subject),
+            None => return Vec::new(),
+        },
+        None => None,
+    };
+    let predicate_ref = match &predicate {
+        Some(Term::NamedNode(node)) => Some(node.as_ref()),
+        Some(_) => return Vec::new(),
+        None => None,
+    };
+
+    // The union of several graphs holds each triple once
+    let mut seen = HashSet::new();
+    let mut solutions = Vec::new();
+    for graph in active {
+        for triple in triples_for_pattern(graph, subject_ref, predicate_ref, object.as_ref().map(Term::as_ref)) {
+            if !seen.insert(triple) {
+                continue;
+            }
+            let mut candidate = solution.clone();
+            if unify(&pattern.subject, triple.subject.into(), &mut candidate)
+                && unify_predicate(&pattern.predicate, triple.predicate, &mut candidate)
+                && unify(&pattern.object, triple.object, &mut candidate)
+            {
+                solutions.push(candidate);
+            }
+        }
+    }
+    solutions
+}
+
+fn unify(pattern: &TermPattern, term: TermRef<'_>, solution: &mut Solution) -> bool {
+    match pattern {
+        TermPattern::NamedNode(node) => TermRef::from(node.as_ref()) == term,
+        TermPattern::Literal(literal) => TermRef::from(literal.as_ref()) == term,
+        TermPattern::BlankNode(node) => bind(solution, &format!("_:{}", node.as_str()), term.into_owned()),
+        TermPattern::Variable(variable) => bind(solution, variable.as_str(), term.into_owned()),
+        TermPattern::Triple(quoted) => match term {
+            TermRef::Triple(triple) => {
+                unify(&quoted.subject, triple.subject.as_ref().into(), solution)
+                    && unify_predicate(&quoted.predicate, triple.predicate.as_ref(), solution)
+                    && unify(&quoted.object, triple.object.as_ref(), solution)
+            }
+            _ => false,
+        },
+    }
+}
+
+fn unify_predicate(pattern: &NamedNodePattern, predicate: NamedNodeRef<'_>, solution: &mut Solution) -> bool {
+    match pattern {
+        NamedNodePattern::NamedNode(node) => node.as_ref() == predicate,
+        NamedNodePattern::Variable(variable) => bind(solution, variable.as_str(), predicate.into_owned().into()),
+    }
+}
+
+/// Bind a variable, or check it already has this value
+fn bind(solution: &mut Solution, name: &str, value: Term) -> bool {
+    match solution.get(name) {
+        Some(bound) => *bound == value,
+        None => {
+            solution.insert(name.to_string(), value);
+            true
+        }
+    }
+}
+
+/// Value of a pattern under a solution, if all of it is bound
+fn resolve(pattern: &TermPattern, solution: &Solution) -> Option<Term> {
+    match pattern {
+        TermPattern::NamedNode(node) => Some(node.clone().into()),
+        TermPattern::Literal(literal) => Some(literal.clone().into()),
+        TermPattern::BlankNode(node) => solution.get(&format!("_:{}", node.as_str())).cloned(),
+        TermPattern::Variable(variable) => solution.get(variable.as_str()).cloned(),
+        TermPattern::Triple(quoted) => {
+            let predicate = match &quoted.predicate {
+                NamedNodePattern::NamedNode(node) => node.clone(),
+                NamedNodePattern::Variable(variable) => match solution.get(variable.as_str())? {
+                    Term::NamedNode(node) => node.clone(),
+                    _ => return None,
+                },
+            };
+            let subject = into_subject(resolve(&quoted.subject, solution)?)?;
+            Some(Term::Triple(Box::new(Triple::new(subject, predicate, resolve(&quoted.object, solution)?))))
+        }
+    }
+}
+
+fn as_subject(term: &Term) -> Option<SubjectRef<'_>> {
+    match term {
+        Term::NamedNode(node) => Some(node.as_ref().into()),
+        Term::BlankNode(node) => Some(node.as_ref().into()),
+        Term::Triple(triple) => Some(SubjectRef::Triple(&**triple)),
+        Term::Literal(_) => None,
+    }
+}
+
+fn into_subject(term: Term) -> Option<Subject> {
+    match term {
+        Term::NamedNode(node) => Some(node.into()),
+        Term::BlankNode(node) => Some(node.into()),
+        Term::Triple(triple) => Some(Subject::Triple(triple)),
+        Term::Literal(_) => None,
+    }
+}
+
+/// Triples the `DELETE` templates remove under one solution, by graph;
+/// `None` is the default graph, that is every graph. Templates with an
+/// unbound variable remove nothing.
+pub fn delete_triples(templates: &[GroundQuadPattern], solution: &Solution) -> Vec<(Option<String>, Triple)> {
+    let ground = |pattern: &GroundTermPattern| instantiate_ground(pattern, solution);
+    templates
+        .iter()
+        .filter_map(|template| {
+            let graph = match &template.graph_name {
+                GraphNamePattern::DefaultGraph => None,
+                other => Some(graph_of(other, solution)?),
+            };
+            let triple = Triple::new(into_subject(ground(&template.subject)?)?, predicate_of(&template.predicate, solution)?, ground(&template.object)?);
+            Some((graph, triple))
+        })
+        .collect()
+}
+
+/// Triples the `INSERT` templates add under one solution, by graph; each
+/// solution gets fresh blank nodes
+pub fn insert_triples(templates: &[QuadPattern], solution: &Solution) -> Vec<(String, Triple)> {
+    let mut fresh = HashMap::new();
+    templates
+        .iter()
+        .filter_map(|template| {
+            let graph = graph_of(&template.graph_name, solution)?;
+            let subject = into_subject(instantiate(&template.subject, solution, &mut fresh)?)?;
+            let predicate = predicate_of(&template.predicate, solution)?;
+            let object = instantiate(&template.object, solution, &mut fresh)?;
+            Some((graph, Triple::new(subject, predicate, object)))
+        })
+        .collect()
+}
+
+/// Triples a `CONSTRUCT` template builds under one solution, with fresh blank nodes
+pub fn construct_triples(template: &[TriplePattern], solution: &Solution) -> Vec<Triple> {
+    let mut fresh = HashMap::new();
+    template
+        .iter()
+        .filter_map(|pattern| {
+            let subject = into_subject(instantiate(&pattern.subject, solution, &mut fresh)?)?;
+            let predicate = predicate_of(&pattern.predicate, solution)?;
+            let object = instantiate(&pattern.object, solution, &mut fresh)?;
+            Some(Triple::new(subject, predicate, object))
+        })
+        .collect()
+}
+
+fn instantiate(pattern: &TermPattern, solution: &Solution, fresh: &mut HashMap<String, BlankNode>) -> Option<Term> {
+    match pattern {
+        TermPattern::BlankNode(node) => Some(fresh.entry(node.as_str().to_string()).or_default().clone().into()),
+        TermPattern::Triple(quoted) => {
+            let subject = into_subject(instantiate(&quoted.subject, solution, fresh)?)?;
+            let predicate = predicate_of(&quoted.predicate, solution)?;
+            let object = instantiate(&quoted.object, solution, fresh)?;
+            Some(Term::Triple(Box::new(Triple::new(subject, predicate, object))))
+        }
+        other => resolve(other, solution),
+    }
+}
+
+fn instantiate_ground(pattern: &GroundTermPattern, solution: &Solution) -> Option<Term> {
+    match pattern {
+        GroundTermPattern::NamedNode(node) => Some(node.clone().into()),
+        GroundTermPattern::Literal(literal) => Some(literal.clone().into()),
+        GroundTermPattern::Variable(variable) => solution.get(variable.as_str()).cloned(),
+        GroundTermPattern::Triple(quoted) => {
+            let subject = into_subject(instantiate_ground(&quoted.subject, solution)?)?;
+            let predicate = predicate_of(&quoted.predicate, solution)?;
+            Some(Term::Triple(Box::new(Triple::new(subject, predicate, instantiate_ground(&quoted.object, solution)?))))
+        }
+    }
+}
+
+fn predicate_of(pattern: &NamedNodePattern, solution: &Solution) -> Option<NamedNode> {
+    match pattern {
+        NamedNodePattern::NamedNode(node) => Some(node.clone()),
+        NamedNodePattern::Variable(variable) => match solution.get(variable.as_str())? {
+            Term::NamedNode(node) => Some(node.clone()),
+            _ => None,
+        },
+    }
+}
+
+fn graph_of(pattern: &GraphNamePattern, solution: &Solution) -> Option<String> {
+    match pattern {
+        GraphNamePattern::NamedNode(node) => Some(node.as_str().to_string()),
+        GraphNamePattern::Variable(variable) => match solution.get(variable.as_str())? {
+            Term::NamedNode(node) => Some(node.as_str().to_string()),
+            _ => None,
+        },
+        GraphNamePattern::DefaultGraph => None,
+    }
+}
+
+/// The RDF triple of a `DELETE DATA` quad
+pub fn ground_triple(subject: GroundSubject, predicate: NamedNode, object: GroundTerm) -> Triple {
+    let subject = match subject {
+        GroundSubject::NamedNode(node) => node.into(),
+        GroundSubject::Triple(quoted) => Subject::Triple(Box::new(quoted_triple(*quoted))),
+    };
+    Triple::new(subject, predicate, ground_term(object))
+}
+
+fn quoted_triple(triple: GroundTriple) -> Triple {
+    ground_triple(triple.subject, triple.predicate, triple.object)
+}
+
+fn ground_term(term: GroundTerm) -> Term {
+    match term {
+        GroundTerm::NamedNode(node) => node.into(),
+        GroundTerm::Literal(literal) => literal.into(),
+        GroundTerm::Triple(quoted) => Term::Triple(Box::new(quoted_triple(*quoted))),
+    }
+}
+
+/// Named graphs an update writes, or `None` when it may write the default
+/// graph, a graph chosen by a variable, or every graph
+pub fn written_graphs(update: &Update) -> Option<Vec<String>> {
+    let mut graphs = Vec::new();
+    let mut add = |graph: &str| {
+        if !graphs.iter().any(|known| known == graph) {
+            graphs.push(graph.to_string());
+        }
+    };
+    for operation in &update.operations {
+        match operation {
+            GraphUpdateOperation::InsertData { data } => {
+                for quad in data {
+                    match &quad.graph_name {
+                        GraphName::NamedNode(node) => add(node.as_str()),
+                        GraphName::DefaultGraph => return None,
+                    }
+                }
+            }
+            GraphUpdateOperation::DeleteData { data } => {
+                for quad in data {
+                    match &quad.graph_name {
+                        GraphName::NamedNode(node) => add(node.as_str()),
+                        GraphName::DefaultGraph => return None,
+                    }
+                }
+            }
+            GraphUpdateOperation::DeleteInsert { delete, insert, .. } => {
+                let templates = delete.iter().map(|quad| &quad.graph_name).chain(insert.iter().map(|quad| &quad.graph_name));
+                for graph in templates {
+                    match graph {
+                        GraphNamePattern::NamedNode(node) => add(node.as_str()),
+                        _ => return None,
+                    }
+                }
+            }
+            GraphUpdateOperation::Load { destination, .. } => match destination {
+                GraphName::NamedNode(node) => add(node.as_str()),
+                GraphName::DefaultGraph => return None,
+            },
+            GraphUpdateOperation::Create { graph, .. } => add(graph.as_str()),
+            GraphUpdateOperation::Clear { graph, .. } | GraphUpdateOperation::Drop { graph, .. } => match graph {
+                spargebra::GraphTarget::NamedNode(node) => add(node.as_str()),
+                _ => return None,
+            },
+        }
+    }
+    Some(graphs)
+}
+
+/// Named graphs the `WHERE` clauses of an update read, or `None` once one
+/// reads the default graph or a variable graph that `USING` does not bound
+pub fn read_graphs(update: &Update) -> Option<Vec<String>> {
+    let mut graphs = Vec::new();
+    for operation in &update.operations {
+        if let GraphUpdateOperation::DeleteInsert { using, pattern, .. } = operation {
+            if !collect_pattern_graphs(pattern, using.as_ref(), false, &mut graphs) {
+                return None;
+            }
+        }
+    }
+    Some(graphs)
+}
+
+/// Named graphs a pattern reads, or `None` when it reads every graph
+pub fn pattern_graphs(pattern: &GraphPattern, dataset: Option<&QueryDataset>) -> Option<Vec<String>> {
+    let mut graphs = Vec::new();
+    collect_pattern_graphs(pattern, dataset, false, &mut graphs).then_some(graphs)
+}
+
+/// Collect the graphs a pattern reads into `graphs`; false once it reads the
+/// default graph or a variable graph without `FROM` naming them
+fn collect_pattern_graphs(pattern: &GraphPattern, dataset: Option<&QueryDataset>, in_graph: bool, graphs: &mut Vec<String>) -> bool {
+    let add = |names: &[NamedNode], graphs: &mut Vec<String>| {
+        for name in names {
+            if !graphs.iter().any(|known| known == name.as_str()) {
+                graphs.push(name.as_str().to_string());
+            }
+        }
+    };
+    match pattern {
+        GraphPattern::Bgp { patterns } if !in_graph && !patterns.is_empty() => match dataset {
+            Some(dataset) => {
+                add(&dataset.default, graphs);
+                true
+            }
+            None => false,
+        },
+        GraphPattern::Bgp { .. } | GraphPattern::Values { .. } => true,
+        GraphPattern::Graph { name, inner } => {
+            let bounded = match name {
+                NamedNodePattern::NamedNode(node) => {
+                    add(std::slice::from_ref(node), graphs);
+                    true
+                }
+                NamedNodePattern::Variable(_) => match dataset.and_then(|dataset| dataset.named.as_deref()) {
+                    Some(names) => {
+                        add(names, graphs);
+                        true
+                    }
+                    None => false,
+                },
+            };
+            bounded && collect_pattern_graphs(inner, dataset, true, graphs)
+        }
+        GraphPattern::Join { left, right } | GraphPattern::Union { left, right } | GraphPattern::LeftJoin { left, right, .. } => {
+            collect_pattern_graphs(left, dataset, in_graph, graphs) && collect_pattern_graphs(right, dataset, in_graph, graphs)
+        }
+        GraphPattern::Filter { inner, .. } | GraphPattern::Extend { inner, .. } => collect_pattern_graphs(inner, dataset, in_graph, graphs),
+        _ => false,
+    }
+}
+
+/// Named graphs a query names: its `FROM` and `FROM NAMED` graphs and the
+/// IRIs of its `GRAPH` patterns. `GRAPH ?g` ranges over `FROM NAMED`.
+pub fn queried_graphs(query: &Query) -> Vec<String> {
+    let (Query::Select { dataset, pattern, .. }
+    | Query::Construct { dataset, pattern, .. }
+    | Query::Describe { dataset, pattern, .. }
+    | Query::Ask { dataset, pattern, .. }) = query;
+    let mut graphs = Vec::new();
+    if let Some(dataset) = dataset {
+        for node in dataset.default.iter().chain(dataset.named.iter().flatten()) {
+            if !graphs.iter().any(|known| known == node.as_str()) {
+                graphs.push(node.as_str().to_string());
+            }
+        }
+    }
+    graph_pattern_names(pattern, &mut graphs);
+    graphs
+}
+
+fn graph_pattern_names(pattern: &GraphPattern, graphs: &mut Vec<String>) {
+    match pattern {
+        GraphPattern::Graph { name, inner } => {
+            if let NamedNodePattern::NamedNode(node) = name {
+                if !graphs.iter().any(|known| known == node.as_str()) {
+                    graphs.push(node.as_str().to_string());
+                }
+            }
+            graph_pattern_names(inner, graphs);
+        }
+        GraphPattern::Join { left, right }
+        | GraphPattern::Union { left, right }
+        | GraphPattern::LeftJoin { left, right, .. }
+        | GraphPattern::Minus { left, right } => {
+            graph_pattern_names(left, graphs);
+            graph_pattern_names(right, graphs);
+        }
+        GraphPattern::Filter { inner, .. }
+        | GraphPattern::Extend { inner, .. }
+        | GraphPattern::OrderBy { inner, .. }
+        | GraphPattern::Project { inner, .. }
+        | GraphPattern::Distinct { inner }
+        | GraphPattern::Reduced { inner }
+        | GraphPattern::Slice { inner, .. }
+        | GraphPattern::Group { inner, .. }
+        | GraphPattern::Service { inner, .. } => graph_pattern_names(inner, graphs),
+        _ => {}
+    }
+}
+
+fn is_true(expression: &Expression, solution: &Solution) -> bool {
+    evaluate(expression, solution).as_ref().and_then(effective_boolean) == Some(true)
+}
+
+/// Value of a filter expression; `None` is an evaluation error, which fails the filter
+fn evaluate(expression: &Expression, solution: &Solution) -> Option<Term> {
+    let boolean = |value: bool| -> Option<Term> { Some(Literal::from(value).into()) };
+    let truth = |expression: &Expression| evaluate(expression, solution).as_ref().and_then(effective_boolean);
+    let compared = |left: &Expression, right: &Expression| compare(&evaluate(left, solution)?, &evaluate(right, solution)?);
+    match expression {
+        Expression::NamedNode(node) => Some(node.clone().into()),
+        Expression::Literal(literal) => Some(literal.clone().into()),
+        Expression::Variable(variable) => solution.get(variable.as_str()).cloned(),
+        Expression::Bound(variable) => boolean(solution.contains_key(variable.as_str())),
+        Expression::Not(inner) => boolean(!truth(inner)?),
+        // An error on one side is overridden by a deciding other side
+        Expression::And(left, right) => match (truth(left), truth(right)) {
+            (Some(false), _) | (_, Some(false)) => boolean(false),
+            (Some(true), Some(true)) => boolean(true),
+            _ => None,
+        },
+        Expression::Or(left, right) => match (truth(left), truth(right)) {
+            (Some(true), _) | (_, Some(true)) => boolean(true),
+            (Some(false), Some(false)) => boolean(false),
+            _ => None,
+        },
+        Expression::Equal(left, right) => boolean(equals(&evaluate(left, solution)?, &evaluate(right, solution)?)),
+        Expression::SameTerm(left, right) => boolean(evaluate(left, solution)? == evaluate(right, solution)?),
+        Expression::Greater(left, right) => boolean(compared(left, right)? == Ordering::Greater),
+        Expression::GreaterOrEqual(left, right) => boolean(compared(left, right)? != Ordering::Less),
+        Expression::Less(left, right) => boolean(compared(left, right)? == Ordering::Less),
+        Expression::LessOrEqual(left, right) => boolean(compared(left, right)? != Ordering::Greater),
+        Expression::In(value, list) => {
+            let value = evaluate(value, solution)?;
+            boolean(list.iter().any(|item| evaluate(item, solution).is_some_and(|item| equals(&value, &item))))
+        }
+        Expression::FunctionCall(function, arguments) => {
+            let values: Vec<Term> = arguments.iter().map(|argument| evaluate(argument, solution)).collect::<Option<_>>()?;
+            let strings = || -> Option<(String, String)> { Some((string_value(values.first()?)?, string_value(values.get(1)?)?)) };
+            match function {
+                Function::Str => Some(Literal::new_simple_literal(string_value(values.first()?)?).into()),
+                Function::StrStarts => strings().and_then(|(text, part)| boolean(text.starts_with(&part))),
+                Function::StrEnds => strings().and_then(|(text, part)| boolean(text.ends_with(&part))),
+                Function::Contains => strings().and_then(|(text, part)| boolean(text.contains(&part))),
+                Function::IsIri => boolean(matches!(values.first()?, Term::NamedNode(_))),
+                Function::IsBlank => boolean(matches!(values.first()?, Term::BlankNode(_))),
+                Function::IsLiteral => boolean(matches!(values.first()?, Term::Literal(_))),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn string_value(term: &Term) -> Option<String> {
+    match term {
+        Term::NamedNode(node) => Some(node.as_str().to_string()),
+        Term::Literal(literal) => Some(literal.value().to_string()),
+        _ => None,
+    }
+}
+
+fn effective_boolean(term: &Term) -> Option<bool> {
+    let Term::Literal(literal) = term else { return None };
+    match literal.datatype().as_str().strip_prefix(XSD).unwrap_or_default() {
+        "boolean" => Some(matches!(literal.value(), "true" | "1")),
+        "string" => Some(!literal.value().is_empty()),
+        _ if is_numeric(literal) => literal.value().parse::<f64>().ok().map(|number| number != 0.0 && !number.is_nan()),
+        _ => None,
+    }
+}
+
+fn is_numeric(literal: &Literal) -> bool {
+    let Some(datatype) = literal.datatype().as_str().strip_prefix(XSD) else { return false };
+    matches!(datatype, "integer" | "decimal" | "double" | "float" | "int" | "long" | "short" | "byte")
+        || datatype.ends_with("Integer")
+        || datatype.starts_with("unsigned")
+}
+
+fn equals(left: &Term, right: &Term) -> bool {
+    compare(left, right).map_or(left == right, |ordering| ordering == Ordering::Equal)
+}
+
+/// Order of two literals of comparable types: numbers, `xsd:dateTime` values,
+/// or lexical forms of the same datatype and language
+fn compare(left: &Term, right: &Term) -> Option<Ordering> {
+    let (Term::Literal(left), Term::Literal(right)) = (left, right) else { return None };
+    if is_numeric(left) && is_numeric(right) {
+        return left.value().parse::<f64>().ok()?.partial_cmp(&right.value().parse::<f64>().ok()?);
+    }
+    let date_time = |literal: &Literal| {
+        (literal.datatype().as_str() == format!("{}dateTime", XSD)).then(|| DateTime::parse_from_rfc3339(literal.value()).ok()).flatten()
+    };
+    if let (Some(left), Some(right)) = (date_time(left), date_time(right)) {
+        return Some(left.cmp(&right));
+    }
+    (left.datatype() == right.datatype() && left.language() == right.language()).then(|| left.value().cmp(right.value()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::storage::oxigraph_store::OxigraphStore;
+
+    const SHIPPED: &str = "<urn:epc:event:ship-1> <urn:epcglobal:epcis:bizStep> <urn:epcglobal:cbv:bizstep:shipping> . \
+        <urn:epc:event:ship-1> <urn:epcglobal:epcis:disposition> <urn:epcglobal:cbv:disp:in_transit> . \
+        <urn:epc:event:ship-1> <urn:x:temperature> 9 .";
+
+    #[test]
+    fn test_update_deletes_and_rewrites_matched_triples() {
+        let mut store = OxigraphStore::new_memory().unwrap();
+        let report = store
+            .update(&format!(
+                "INSERT DATA {{ GRAPH <urn:epcis:event:ship-1> {{ {} }} GRAPH <urn:epcis:event:recv-1> {{ <urn:epc:event:recv-1> <urn:x:temperature> 4 }} }}",
+                SHIPPED
+            ))
+            .unwrap();
+        assert_eq!(report.inserted, 4);
+
+        // Only the event over 8 degrees is flagged, in the graph it was found in
+        let report = store
+            .update(
+                "DELETE { GRAPH ?g { ?event <urn:epcglobal:epcis:disposition> ?disposition } } \
+                 INSERT { GRAPH ?g { ?event <urn:epcglobal:epcis:disposition> <urn:epcglobal:cbv:disp:damaged> } } \
+                 WHERE { GRAPH ?g { ?event <urn:x:temperature> ?t OPTIONAL { ?event <urn:epcglobal:epcis:disposition> ?disposition } } FILTER(?t > 8) }",
+            )
+            .unwrap();
+        assert_eq!((report.inserted, report.deleted), (1, 1));
+        let damaged = "<urn:epc:event:ship-1> <urn:epcglobal:epcis:disposition> <urn:epcglobal:cbv:disp:damaged>";
+        assert!(store.export_turtle().unwrap().contains("urn:epcglobal:cbv:disp:damaged"));
+        assert_eq!(store.graph_len("urn:epcis:event:recv-1"), 1);
+
+        let report = store.update(&format!("DELETE DATA {{ {} }}", damaged)).unwrap();
+        assert_eq!(report.deleted, 1);
+        assert_eq!(store.graph_len("urn:epcis:event:ship-1"), 2);
+
+        // DELETE WHERE empties a graph, which goes away
+        store.update("DELETE WHERE { GRAPH <urn:epcis:event:recv-1> { ?s ?p ?o } }").unwrap();
+        assert!(!store.graph_names().contains(&"urn:epcis:event:recv-1".to_string()));
+
+        // Nothing of an update with an unsupported part applies
+        let rejected = "INSERT DATA { GRAPH <urn:epcis:event:x> { <urn:x:a> <urn:x:b> <urn:x:c> } } ; \
+                        DELETE { GRAPH <urn:epcis:event:x> { ?s <urn:x:b> ?o } } WHERE { ?s <urn:x:p>+ ?o }";
+        assert!(store.update(rejected).is_err());
+        assert!(!store.graph_names().contains(&"urn:epcis:event:x".to_string()));
+    }
+
+    #[test]
+    fn test_written_graphs_of_variable_and_default_targets() {
+        let parse = |update: &str| spargebra::SparqlParser::new().parse_update(update).unwrap();
+        let named = parse("DELETE { GRAPH <urn:g:1> { ?s ?p ?o } } INSERT { GRAPH <urn:g:2> { ?s ?p ?o } } WHERE { GRAPH <urn:g:3> { ?s ?p ?o } }");
+        assert_eq!(super::written_graphs(&named), Some(vec!["urn:g:1".to_string(), "urn:g:2".to_string()]));
+        assert_eq!(super::written_graphs(&parse("DELETE { ?s ?p ?o } WHERE { GRAPH <urn:g:1> { ?s ?p ?o } }")), None);
+        assert_eq!(super::written_graphs(&parse("DELETE WHERE { GRAPH ?g { ?s ?p ?o } }")), None);
+    }
+}
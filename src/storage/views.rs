@@ -0,0 +1,216 @@
+use crate::storage::sparql_update;
+use crate::EpcisKgError;
+use chrono::{DateTime, Utc};
+use oxrdf::{Graph, Literal, NamedNode, NamedNodeRef, Triple};
+use serde::{Deserialize, Serialize};
+use spargebra::algebra::{GraphPattern, QueryDataset};
+use spargebra::term::TriplePattern;
+use spargebra::{Query, SparqlParser};
+use std::collections::HashMap;
+
+/// Prefix of the graphs holding view results, which are derived graphs
+pub const VIEW_GRAPH_PREFIX: &str = "urn:epcis:inferred:view:";
+
+/// Graph holding the view definitions and when each view was last refreshed
+pub const VIEW_DEFINITIONS_GRAPH: &str = "urn:epcis:views";
+
+const EPCIS_NS: &str = "urn:epcglobal:epcis:";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+
+/// A CONSTRUCT query whose results the store keeps in a graph of their own
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ViewDefinition {
+    /// Letters, digits, `-` and `_`; the results go to `urn:epcis:inferred:view:{name}`
+    pub name: String,
+    pub query: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// A view and the state of its graph
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ViewStatus {
+    #[serde(flatten)]
+    pub definition: ViewDefinition,
+    pub graph: String,
+    /// Graphs whose writes refresh the view; `["*"]` when it reads every graph
+    pub sources: Vec<String>,
+    pub triples: usize,
+    pub refreshed_at: Option<DateTime<Utc>>,
+    pub refresh_ms: Option<u64>,
+}
+
+struct ParsedView {
+    template: Vec<TriplePattern>,
+    dataset: Option<QueryDataset>,
+    pattern: GraphPattern,
+}
+
+pub fn view_graph_name(name: &str) -> String {
+    format!("{}{}", VIEW_GRAPH_PREFIX, name)
+}
+
+/// Graphs views read: everything but view results and definitions, so views never feed each other
+pub fn is_view_source(graph_name: &str) -> bool {
+    !graph_name.starts_with(VIEW_GRAPH_PREFIX) && graph_name != VIEW_DEFINITIONS_GRAPH
+}
+
+impl ViewDefinition {
+    pub fn graph_name(&self) -> String {
+        view_graph_name(&self.name)
+    }
+
+    pub fn validate(&self) -> Result<(), EpcisKgError> {
+        self.parse().map(|_| ())
+    }
+
+    fn parse(&self) -> Result<ParsedView, EpcisKgError> {
+        if self.name.is_empty() || !self.name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_')) {
+            return Err(EpcisKgError::InvalidField {
+                field: "name".to_string(),
+                message: format!("'{}' must be letters, digits, '-' or '_'", self.name),
+            });
+        }
+        let query = SparqlParser::new()
+            .parse_query(&self.query)
+            .map_err(|e| EpcisKgError::InvalidField { field: "query".to_string(), message: e.to_string() })?;
+        let Query::Construct { template, dataset, pattern, .. } = query else {
+            return Err(EpcisKgError::InvalidField { field: "query".to_string(), message: "a view is defined by a CONSTRUCT query".to_string() });
+        };
+        sparql_update::check_pattern(&pattern)?;
+        Ok(ParsedView { template, dataset, pattern })
+    }
+
+    /// Named graphs the view reads, or `None` when it reads every graph
+    pub fn sources(&self) -> Result<Option<Vec<String>>, EpcisKgError> {
+        let view = self.parse()?;
+        Ok(sparql_update::pattern_graphs(&view.pattern, view.dataset.as_ref()))
+    }
+
+    /// Whether a write to `graph_name` may change the view's results
+    pub fn reads(&self, graph_name: &str) -> bool {
+        is_view_source(graph_name)
+            && match self.sources() {
+                Ok(Some(graphs)) => graphs.iter().any(|graph| graph == graph_name),
+                _ => true,
+            }
+    }
+}
+
+/// Results of a view over `graphs`, leaving out view results and definitions
+pub fn evaluate(graphs: &HashMap<String, Graph>, definition: &ViewDefinition) -> Result<Graph, EpcisKgError> {
+    let view = definition.parse()?;
+    let sources = graphs.iter().filter(|(name, _)| is_view_source(name));
+    let mut results = Graph::default();
+    for solution in sparql_update::solutions(sources, &view.pattern, view.dataset.as_ref()) {
+        for triple in sparql_update::construct_triples(&view.template, &solution) {
+            results.insert(&triple);
+        }
+    }
+    Ok(results)
+}
+
+/// Triples recording a view in the definitions graph, with its last refresh
+pub fn definition_triples(definition: &ViewDefinition, refreshed_at: DateTime<Utc>, refresh_ms: u64) -> Vec<Triple> {
+    let view = NamedNode::new_unchecked(definition.graph_name());
+    let epcis = |local: &str| NamedNode::new_unchecked(format!("{}{}", EPCIS_NS, local));
+    let mut triples = vec![
+        Triple::new(view.clone(), NamedNode::new_unchecked(RDF_TYPE), epcis("MaterializedView")),
+        Triple::new(view.clone(), epcis("viewName"), Literal::new_simple_literal(&definition.name)),
+        Triple::new(view.clone(), epcis("viewQuery"), Literal::new_simple_literal(&definition.query)),
+        Triple::new(
+            view.clone(),
+            epcis("refreshedAt"),
+            Literal::new_typed_literal(refreshed_at.to_rfc3339(), NamedNode::new_unchecked(format!("{}dateTime", XSD))),
+        ),
+        Triple::new(view.clone(), epcis("refreshMillis"), Literal::from(refresh_ms as i64)),
+    ];
+    if let Some(description) = &definition.description {
+        triples.push(Triple::new(view, epcis("viewDescription"), Literal::new_simple_literal(description)));
+    }
+    triples
+}
+
+/// Views recorded in the definitions graph, sorted by name; `graph_len` sizes their result graphs
+pub fn recorded_views(definitions: &Graph, graph_len: impl Fn(&str) -> usize) -> Vec<ViewStatus> {
+    let predicate = |local: &str| format!("{}{}", EPCIS_NS, local);
+    let view_name = predicate("viewName");
+    let mut views: Vec<ViewStatus> = definitions
+        .triples_for_predicate(NamedNodeRef::new_unchecked(&view_name))
+        .filter_map(|triple| {
+            let oxrdf::SubjectRef::NamedNode(view) = triple.subject else { return None };
+            let value = |local: &str| match definitions.object_for_subject_predicate(view, NamedNodeRef::new_unchecked(&predicate(local)))? {
+                oxrdf::TermRef::Literal(literal) => Some(literal.value().to_string()),
+                _ => None,
+            };
+            let definition = ViewDefinition { name: value("viewName")?, query: value("viewQuery")?, description: value("viewDescription") };
+            let sources = match definition.sources() {
+                Ok(Some(graphs)) => graphs,
+                _ => vec!["*".to_string()],
+            };
+            Some(ViewStatus {
+                graph: definition.graph_name(),
+                triples: graph_len(&definition.graph_name()),
+                refreshed_at: value("refreshedAt").and_then(|time| DateTime::parse_from_rfc3339(&time).ok()).map(|time| time.with_timezone(&Utc)),
+                refresh_ms: value("refreshMillis").and_then(|ms| ms.parse().ok()),
+                sources,
+                definition,
+            })
+        })
+        .collect();
+    views.sort_by(|a, b| a.definition.name.cmp(&b.definition.name));
+    views
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::oxigraph_store::OxigraphStore;
+
+    const SHIPMENTS: &str = "CONSTRUCT { ?event <urn:x:shipsTo> ?name } WHERE { \
+        GRAPH ?g { ?event <urn:epcglobal:epcis:bizStep> <urn:epcglobal:cbv:bizstep:shipping> ; <urn:epcglobal:epcis:destination> ?site } \
+        GRAPH <urn:epcis:master:sites> { ?site <urn:x:siteName> ?name } }";
+
+    fn definition(query: &str) -> ViewDefinition {
+        ViewDefinition { name: "shipments".to_string(), query: query.to_string(), description: None }
+    }
+
+    #[test]
+    fn test_view_follows_writes_to_events_and_master_data() {
+        let mut store = OxigraphStore::new_memory().unwrap();
+        store
+            .update(
+                "INSERT DATA { GRAPH <urn:epcis:event:ship-1> { <urn:epc:event:ship-1> <urn:epcglobal:epcis:bizStep> <urn:epcglobal:cbv:bizstep:shipping> ; \
+                 <urn:epcglobal:epcis:destination> <urn:epc:id:sgln:0614141.00002.0> } }",
+            )
+            .unwrap();
+        let status = store.register_view(&definition(SHIPMENTS)).unwrap();
+        assert_eq!((status.triples, status.sources.clone()), (0, vec!["*".to_string()]));
+
+        // Master data arriving later completes the join
+        store
+            .update("INSERT DATA { GRAPH <urn:epcis:master:sites> { <urn:epc:id:sgln:0614141.00002.0> <urn:x:siteName> \"Dock 2\" } }")
+            .unwrap();
+        let status = store.view("shipments").unwrap();
+        assert_eq!(status.triples, 1);
+        assert!(status.refreshed_at.is_some());
+
+        store.update("DROP GRAPH <urn:epcis:event:ship-1>").unwrap();
+        assert_eq!(store.view("shipments").unwrap().triples, 0);
+        assert!(store.remove_view("shipments").unwrap());
+        assert!(store.views().is_empty());
+    }
+
+    #[test]
+    fn test_views_need_a_construct_query_and_know_their_sources() {
+        assert!(definition("SELECT ?s WHERE { ?s ?p ?o }").validate().is_err());
+        assert!(ViewDefinition { name: "a b".to_string(), ..definition(SHIPMENTS) }.validate().is_err());
+
+        let scoped = definition("CONSTRUCT { ?s <urn:x:seen> true } WHERE { GRAPH <urn:epcis:master:sites> { ?s ?p ?o } }");
+        assert_eq!(scoped.sources().unwrap(), Some(vec!["urn:epcis:master:sites".to_string()]));
+        assert!(scoped.reads("urn:epcis:master:sites"));
+        assert!(!scoped.reads("urn:epcis:event:ship-1"));
+        assert!(!definition(SHIPMENTS).reads(&view_graph_name("shipments")));
+    }
+}
@@ -0,0 +1,156 @@
+use crate::models::epcis::{EpcisEvent, Ilmd};
+use crate::models::parties::SourceDestination;
+use crate::models::sensors::{SensorElement, SensorReport};
+
+/// Event and record time of built events unless set, so scenarios are reproducible
+pub const DEFAULT_EVENT_TIME: &str = "2024-01-01T00:00:00Z";
+
+/// Fluent construction of realistic EPCIS events for tests.
+///
+/// ```ignore
+/// let shipped = EpcisEventBuilder::object("ship-1")
+///     .epc("urn:epc:id:sgtin:0614141.107346.1")
+///     .biz_step("shipping")
+///     .location("urn:epc:id:sgln:0614141.00001.0")
+///     .at("2024-03-01T08:00:00Z")
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct EpcisEventBuilder {
+    event: EpcisEvent,
+}
+
+impl EpcisEventBuilder {
+    pub fn new(event_type: &str, event_id: &str) -> Self {
+        Self {
+            event: EpcisEvent {
+                event_id: event_id.to_string(),
+                event_type: event_type.to_string(),
+                event_time: DEFAULT_EVENT_TIME.to_string(),
+                record_time: DEFAULT_EVENT_TIME.to_string(),
+                ..EpcisEvent::default()
+            },
+        }
+    }
+
+    pub fn object(event_id: &str) -> Self {
+        Self::new("ObjectEvent", event_id)
+    }
+
+    pub fn aggregation(event_id: &str) -> Self {
+        Self::new("AggregationEvent", event_id)
+    }
+
+    pub fn transaction(event_id: &str) -> Self {
+        Self::new("TransactionEvent", event_id)
+    }
+
+    pub fn transformation(event_id: &str) -> Self {
+        Self::new("TransformationEvent", event_id)
+    }
+
+    /// ADD, OBSERVE or DELETE (default ADD)
+    pub fn action(mut self, action: &str) -> Self {
+        self.event.event_action = action.to_string();
+        self
+    }
+
+    /// Event time; the record time follows unless set with [`recorded`](Self::recorded)
+    pub fn at(mut self, time: &str) -> Self {
+        if self.event.record_time == self.event.event_time {
+            self.event.record_time = time.to_string();
+        }
+        self.event.event_time = time.to_string();
+        self
+    }
+
+    pub fn recorded(mut self, time: &str) -> Self {
+        self.event.record_time = time.to_string();
+        self
+    }
+
+    pub fn epc(mut self, epc: &str) -> Self {
+        self.event.epc_list.push(epc.to_string());
+        self
+    }
+
+    pub fn epcs<I: IntoIterator<Item = S>, S: Into<String>>(mut self, epcs: I) -> Self {
+        self.event.epc_list.extend(epcs.into_iter().map(Into::into));
+        self
+    }
+
+    /// CBV business step as the pipeline expects it (e.g. `shipping`)
+    pub fn biz_step(mut self, biz_step: &str) -> Self {
+        self.event.biz_step = Some(biz_step.to_string());
+        self
+    }
+
+    pub fn disposition(mut self, disposition: &str) -> Self {
+        self.event.disposition = Some(disposition.to_string());
+        self
+    }
+
+    pub fn location(mut self, location: &str) -> Self {
+        self.event.biz_location = Some(location.to_string());
+        self
+    }
+
+    pub fn source(mut self, kind: &str, id: &str) -> Self {
+        self.event.source_list.push(SourceDestination { kind: kind.to_string(), id: id.to_string() });
+        self
+    }
+
+    pub fn destination(mut self, kind: &str, id: &str) -> Self {
+        self.event.destination_list.push(SourceDestination { kind: kind.to_string(), id: id.to_string() });
+        self
+    }
+
+    /// Input consumed by a TransformationEvent
+    pub fn input(mut self, epc: &str) -> Self {
+        self.event.input_epc_list.push(epc.to_string());
+        self
+    }
+
+    /// Output produced by a TransformationEvent
+    pub fn output(mut self, epc: &str) -> Self {
+        self.event.output_epc_list.push(epc.to_string());
+        self
+    }
+
+    /// `cbvmda:itemExpirationDate` of the commissioned EPCs
+    pub fn expires(mut self, date: &str) -> Self {
+        self.event.ilmd.get_or_insert_with(Ilmd::default).item_expiration_date = Some(date.to_string());
+        self
+    }
+
+    pub fn lot(mut self, lot_number: &str) -> Self {
+        self.event.ilmd.get_or_insert_with(Ilmd::default).lot_number = Some(lot_number.to_string());
+        self
+    }
+
+    /// A `gs1:Temperature` reading in degrees Celsius
+    pub fn temperature(mut self, celsius: f64, time: &str) -> Self {
+        self.event.sensor_element_list.push(SensorElement {
+            sensor_metadata: None,
+            sensor_report: vec![SensorReport {
+                kind: "gs1:Temperature".to_string(),
+                value: Some(celsius),
+                min_value: None,
+                max_value: None,
+                uom: Some("CEL".to_string()),
+                time: Some(time.to_string()),
+            }],
+        });
+        self
+    }
+
+    /// Partner extension field keyed by IRI or `prefix:field`
+    pub fn extension(mut self, key: &str, value: serde_json::Value) -> Self {
+        self.event.extensions.insert(key.to_string(), value);
+        self
+    }
+
+    pub fn build(self) -> EpcisEvent {
+        self.event
+    }
+}
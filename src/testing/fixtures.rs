@@ -0,0 +1,106 @@
+use crate::ontology::loader::OntologyData;
+use crate::storage::oxigraph_store::{LoadMode, OxigraphStore};
+use crate::EpcisKgError;
+use oxrdf::Graph;
+use oxttl::TurtleParser;
+
+const EPCIS_CORE: &str = r#"
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+@prefix owl: <http://www.w3.org/2002/07/owl#> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+@prefix epcis: <urn:epcglobal:epcis:> .
+
+epcis:Event a owl:Class ; rdfs:label "Event" .
+epcis:ObjectEvent a owl:Class ; rdfs:subClassOf epcis:Event .
+epcis:AggregationEvent a owl:Class ; rdfs:subClassOf epcis:Event .
+epcis:TransactionEvent a owl:Class ; rdfs:subClassOf epcis:Event .
+epcis:TransformationEvent a owl:Class ; rdfs:subClassOf epcis:Event .
+epcis:PhysicalObject a owl:Class ; rdfs:label "Physical Object" .
+
+epcis:eventID a owl:DatatypeProperty ; rdfs:domain epcis:Event ; rdfs:range xsd:string .
+epcis:eventTime a owl:DatatypeProperty ; rdfs:domain epcis:Event ; rdfs:range xsd:dateTime .
+epcis:action a owl:DatatypeProperty ; rdfs:domain epcis:Event .
+epcis:epcList a owl:ObjectProperty ; rdfs:domain epcis:Event ; rdfs:range epcis:PhysicalObject .
+epcis:bizStep a owl:ObjectProperty ; rdfs:domain epcis:Event .
+epcis:disposition a owl:ObjectProperty ; rdfs:domain epcis:Event .
+epcis:bizLocation a owl:ObjectProperty ; rdfs:domain epcis:Event .
+"#;
+
+const CBV_CORE: &str = r#"
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+@prefix cbv: <urn:epcglobal:cbv:> .
+
+cbv:BizStep a rdfs:Class .
+cbv:commissioning a cbv:BizStep .
+cbv:shipping a cbv:BizStep .
+cbv:receiving a cbv:BizStep .
+cbv:storing a cbv:BizStep .
+cbv:selling a cbv:BizStep .
+
+cbv:Disposition a rdfs:Class .
+cbv:active a cbv:Disposition .
+cbv:in_transit a cbv:Disposition .
+cbv:in_progress a cbv:Disposition .
+cbv:recalled a cbv:Disposition .
+cbv:expired a cbv:Disposition .
+
+cbv:BusinessLocation a rdfs:Class .
+cbv:Warehouse a cbv:BusinessLocation .
+cbv:RetailStore a cbv:BusinessLocation .
+"#;
+
+/// Minimal subsets of the EPCIS 2.0 and CBV ontologies, small enough to
+/// reason over in unit tests yet covering the event classes and the business
+/// steps and dispositions scenarios commonly use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OntologyFixture {
+    pub name: &'static str,
+    pub turtle: &'static str,
+}
+
+impl OntologyFixture {
+    pub const EPCIS: OntologyFixture = OntologyFixture { name: "fixture:epcis", turtle: EPCIS_CORE };
+    pub const CBV: OntologyFixture = OntologyFixture { name: "fixture:cbv", turtle: CBV_CORE };
+
+    pub fn all() -> [OntologyFixture; 2] {
+        [Self::EPCIS, Self::CBV]
+    }
+
+    /// Graph name the fixture is stored under
+    pub fn graph_name(&self) -> String {
+        format!("urn:epcis:ontology:{}", self.name.trim_start_matches("fixture:"))
+    }
+
+    pub fn ontology_data(&self) -> Result<OntologyData, EpcisKgError> {
+        let mut graph = Graph::default();
+        for triple in TurtleParser::new().for_reader(self.turtle.as_bytes()) {
+            let triple = triple.map_err(|e| EpcisKgError::Ontology(format!("Fixture {} does not parse: {}", self.name, e)))?;
+            graph.insert(triple.as_ref());
+        }
+        Ok(OntologyData {
+            triples_count: graph.len(),
+            graph,
+            source_file: self.name.to_string(),
+        })
+    }
+
+    pub fn load_into(&self, store: &mut OxigraphStore) -> Result<(), EpcisKgError> {
+        store.store_ontology_data_as(&self.ontology_data()?, Some(&self.graph_name()), LoadMode::Replace)?;
+        Ok(())
+    }
+
+    /// Both fixtures as one ontology, e.g. for a reasoner TBox
+    pub fn combined() -> Result<OntologyData, EpcisKgError> {
+        let mut graph = Graph::default();
+        for fixture in Self::all() {
+            for triple in fixture.ontology_data()?.graph.iter() {
+                graph.insert(triple);
+            }
+        }
+        Ok(OntologyData {
+            triples_count: graph.len(),
+            graph,
+            source_file: "fixture:epcis+cbv".to_string(),
+        })
+    }
+}
@@ -0,0 +1,276 @@
+//! End-to-end scenarios against a running server (feature `integration`).
+//!
+//! [`IntegrationServer::start`] runs a small supply chain through the
+//! [`TestHarness`] and boots the server in-process on a free port over the
+//! harness's store. With `EPCIS_KG_IT_URL` set it seeds that server through
+//! the SPARQL update endpoint and tests it instead, which is how
+//! `docker-compose.integration.yml` runs the same scenarios against the
+//! container image. Seeded IDs carry a per-run tag, so the invariants hold on
+//! a server that already has data.
+//!
+//! ```ignore
+//! let server = IntegrationServer::start().await?;
+//! for outcome in server.run_scenarios().await {
+//!     assert!(outcome.passed(), "{}: {:?}", outcome.name, outcome.failures);
+//! }
+//! ```
+
+use super::{EpcisEventBuilder, TestHarness};
+use crate::api::server::WebServer;
+use crate::client::EpcisClient;
+use crate::config::AppConfig;
+use crate::models::epcis::EpcisEvent;
+use crate::EpcisKgError;
+use serde::Serialize;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Base URL of an already running server to test instead of booting one
+pub const SERVER_URL_ENV: &str = "EPCIS_KG_IT_URL";
+
+/// How long a server gets to answer its health check
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// EPCs each seeded supply chain is built around
+const SEEDED_EPCS: usize = 3;
+
+/// Result of one scenario; it passed when no invariant failed
+#[derive(Debug, Clone, Serialize)]
+pub struct ScenarioOutcome {
+    pub name: &'static str,
+    pub failures: Vec<String>,
+    pub duration_ms: u64,
+}
+
+impl ScenarioOutcome {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// A server under test and the events seeded into it
+pub struct IntegrationServer {
+    pub client: EpcisClient,
+    pub base_url: String,
+    /// Tag in every seeded event ID and EPC of this run
+    pub run_id: String,
+    pub seeded: Vec<EpcisEvent>,
+}
+
+impl IntegrationServer {
+    /// Boot a seeded server on a free port, or seed and use the one at `EPCIS_KG_IT_URL`
+    pub async fn start() -> Result<Self, EpcisKgError> {
+        let run_id = format!("it{}", chrono::Utc::now().timestamp_millis());
+        let seeded = seed_events(&run_id);
+        let external = std::env::var(SERVER_URL_ENV).ok().filter(|url| !url.is_empty());
+        let base_url = match &external {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => boot_server(&seeded).await?,
+        };
+        let client = EpcisClient::new(base_url.clone());
+        wait_until_healthy(&client).await?;
+
+        let server = Self { client, base_url, run_id, seeded };
+        if external.is_some() {
+            server.seed_over_api().await?;
+        }
+        Ok(server)
+    }
+
+    /// Commit the seeded events through `POST /sparql/update`, one named graph per event
+    async fn seed_over_api(&self) -> Result<(), EpcisKgError> {
+        let harness = TestHarness::new().await?;
+        for event in &self.seeded {
+            let triples = harness.pipeline.masked_event_triples(event)?;
+            let data: Vec<String> = triples.public.iter().map(|triple| format!("{} .", triple)).collect();
+            let update = format!("INSERT DATA {{ GRAPH <urn:epcis:event:{}> {{ {} }} }}", event.event_id, data.join(" "));
+            self.client.sparql_update(&update).await?;
+        }
+        Ok(())
+    }
+
+    /// Capture, query, trace and inference scenarios, run concurrently
+    pub async fn run_scenarios(&self) -> Vec<ScenarioOutcome> {
+        let (capture, query, trace, inference) = futures::join!(
+            scenario("capture", self.capture()),
+            scenario("query", self.query()),
+            scenario("trace", self.trace()),
+            scenario("inference", self.inference()),
+        );
+        vec![capture, query, trace, inference]
+    }
+
+    /// Every valid event of a batch is accepted and an invalid one is reported on its own
+    async fn capture(&self) -> Result<Vec<String>, EpcisKgError> {
+        let mut failures = Vec::new();
+        let events: Vec<EpcisEvent> = (0..SEEDED_EPCS)
+            .map(|i| {
+                EpcisEventBuilder::object(&format!("{}-capture-{}", self.run_id, i))
+                    .action("OBSERVE")
+                    .epc(&self.epc(i))
+                    .biz_step("inspecting")
+                    .build()
+            })
+            .collect();
+        let response = self.client.capture_events(&events, true, false).await?;
+        check(&mut failures, response.success, "batch of valid events was not accepted");
+        check(&mut failures, response.events_processed == events.len(), format!("{} of {} events processed", response.events_processed, events.len()));
+        check(&mut failures, response.results.iter().all(|result| result.success), "a valid event failed");
+
+        let mut mixed = events;
+        mixed.push(EpcisEventBuilder::object("").build());
+        let response = self.client.capture_events(&mixed, true, false).await?;
+        let failed: Vec<&str> = response.results.iter().filter(|result| !result.success).map(|result| result.event_id.as_str()).collect();
+        check(&mut failures, !response.success, "batch with an invalid event reported success");
+        check(&mut failures, failed == vec![""], format!("expected only the invalid event to fail, got {:?}", failed));
+        Ok(failures)
+    }
+
+    /// SPARQL and the event listing agree on the number of seeded events
+    async fn query(&self) -> Result<Vec<String>, EpcisKgError> {
+        let mut failures = Vec::new();
+        let counted = self.count_seeded_events().await?;
+        check(&mut failures, counted == self.seeded.len(), format!("SPARQL counted {} of {} seeded events", counted, self.seeded.len()));
+
+        let listing = self.client.list_events().await?;
+        let listed = listing["events"]
+            .as_array()
+            .map(|events| events.iter().filter(|event| event["@id"].as_str().is_some_and(|id| id.contains(&self.run_id))).count())
+            .unwrap_or_default();
+        check(&mut failures, listed == self.seeded.len(), format!("GET /events listed {} of {} seeded events", listed, self.seeded.len()));
+        Ok(failures)
+    }
+
+    /// The trace of each EPC holds every event that listed it
+    async fn trace(&self) -> Result<Vec<String>, EpcisKgError> {
+        let traces = futures::future::join_all((0..SEEDED_EPCS).map(|i| async move {
+            let epc = self.epc(i);
+            self.client.trace(&epc).await.map(|trace| (epc, trace.to_string()))
+        }))
+        .await;
+
+        let mut failures = Vec::new();
+        for trace in traces {
+            let (epc, trace) = trace?;
+            for event in self.seeded.iter().filter(|event| event.epc_list.contains(&epc)) {
+                let event_uri = format!("urn:epc:event:{}", event.event_id);
+                check(&mut failures, trace.contains(&event_uri), format!("trace of {} lacks {}", epc, event_uri));
+            }
+        }
+        Ok(failures)
+    }
+
+    /// Reasoning succeeds and leaves the captured events as they were
+    async fn inference(&self) -> Result<Vec<String>, EpcisKgError> {
+        let mut failures = Vec::new();
+        let response = self.client.infer(None).await?;
+        check(&mut failures, response["success"] == true, format!("inference failed: {}", response));
+
+        let counted = self.count_seeded_events().await?;
+        check(&mut failures, counted == self.seeded.len(), format!("{} of {} seeded events after inference", counted, self.seeded.len()));
+        Ok(failures)
+    }
+
+    async fn count_seeded_events(&self) -> Result<usize, EpcisKgError> {
+        let query = format!(
+            "SELECT (COUNT(DISTINCT ?g) AS ?n) WHERE {{ GRAPH ?g {{ ?event a ?type }} FILTER(STRSTARTS(STR(?g), \"urn:epcis:event:{}-\")) }}",
+            self.run_id
+        );
+        let results = self.client.sparql(&query).await?;
+        let count = results.results["bindings"][0]["n"]["value"].as_str().and_then(|n| n.parse().ok());
+        count.ok_or_else(|| EpcisKgError::Query(format!("Unexpected count results: {}", results.results)))
+    }
+
+    fn epc(&self, i: usize) -> String {
+        format!("urn:epc:id:sgtin:0614141.107346.{}{}", self.run_id, i)
+    }
+}
+
+/// Commissioning, shipping and receiving of each seeded EPC
+pub fn seed_events(run_id: &str) -> Vec<EpcisEvent> {
+    let mut events = Vec::new();
+    for i in 0..SEEDED_EPCS {
+        let epc = format!("urn:epc:id:sgtin:0614141.107346.{}{}", run_id, i);
+        let steps = [
+            ("commissioning", "active", "ADD", "urn:epc:id:sgln:0614141.00001.0", "2024-03-01T08:00:00Z"),
+            ("shipping", "in_transit", "OBSERVE", "urn:epc:id:sgln:0614141.00001.0", "2024-03-02T08:00:00Z"),
+            ("receiving", "in_progress", "OBSERVE", "urn:epc:id:sgln:0614141.00002.0", "2024-03-03T08:00:00Z"),
+        ];
+        for (biz_step, disposition, action, location, time) in steps {
+            events.push(
+                EpcisEventBuilder::object(&format!("{}-{}-{}", run_id, biz_step, i))
+                    .action(action)
+                    .epc(&epc)
+                    .biz_step(biz_step)
+                    .disposition(disposition)
+                    .location(location)
+                    .at(time)
+                    .build(),
+            );
+        }
+    }
+    events
+}
+
+/// Serve the ontology fixtures and `seeded` from an in-memory store on a free port, from its own thread
+async fn boot_server(seeded: &[EpcisEvent]) -> Result<String, EpcisKgError> {
+    let mut harness = TestHarness::new().await?;
+    for result in harness.process_all(seeded.to_vec()).await? {
+        if !result.success {
+            return Err(EpcisKgError::Validation(format!("Seed event {} failed: {}", result.event_id, result.error.unwrap_or_default())));
+        }
+    }
+    let store = harness.store.clone();
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+    let mut config = AppConfig::default();
+    config.database_path = ":memory:".to_string();
+    config.sparql.enable_updates = true;
+
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Runtime::new().expect("integration server runtime");
+        runtime.block_on(async move {
+            match WebServer::new(config, store).await {
+                Ok(server) => {
+                    if let Err(e) = server.run(port).await {
+                        eprintln!("Integration server stopped: {}", e);
+                    }
+                }
+                Err(e) => eprintln!("Integration server failed to start: {}", e),
+            }
+        });
+    });
+    Ok(format!("http://127.0.0.1:{}", port))
+}
+
+async fn wait_until_healthy(client: &EpcisClient) -> Result<(), EpcisKgError> {
+    let started = Instant::now();
+    while client.health().await.is_err() {
+        if started.elapsed() > STARTUP_TIMEOUT {
+            return Err(EpcisKgError::Unavailable {
+                message: format!("Server did not become healthy within {}s", STARTUP_TIMEOUT.as_secs()),
+                retry_after_secs: None,
+            });
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    Ok(())
+}
+
+async fn scenario<F>(name: &'static str, run: F) -> ScenarioOutcome
+where
+    F: Future<Output = Result<Vec<String>, EpcisKgError>>,
+{
+    let started = Instant::now();
+    let failures = match run.await {
+        Ok(failures) => failures,
+        Err(e) => vec![e.to_string()],
+    };
+    ScenarioOutcome { name, failures, duration_ms: started.elapsed().as_millis() as u64 }
+}
+
+fn check(failures: &mut Vec<String>, holds: bool, message: impl Into<String>) {
+    if !holds {
+        failures.push(message.into());
+    }
+}
@@ -0,0 +1,108 @@
+//! Fixtures, builders, proptest strategies and an in-memory harness for tests
+//! (feature `testing`).
+//!
+//! Downstream crates and our own integration tests use these to set up a
+//! realistic scenario in a few lines:
+//!
+//! ```ignore
+//! let mut harness = TestHarness::new().await?;
+//! let shipped = EpcisEventBuilder::object("e1").epc(EPC).biz_step("shipping").build();
+//! assert!(harness.process(shipped).await?.success);
+//! let rows = harness.query("SELECT ?s WHERE { ?s <urn:epcglobal:epcis:bizStep> ?o }")?;
+//! ```
+
+pub mod builder;
+pub mod fixtures;
+#[cfg(feature = "integration")]
+pub mod integration;
+pub mod strategies;
+
+pub use builder::EpcisEventBuilder;
+pub use fixtures::OntologyFixture;
+
+use crate::config::AppConfig;
+use crate::models::epcis::EpcisEvent;
+use crate::models::events::ProcessingResult;
+use crate::ontology::reasoner::OntologyReasoner;
+use crate::pipeline::EpcisEventPipeline;
+use crate::storage::oxigraph_store::OxigraphStore;
+use crate::utils::masking::pii_graph_name;
+use crate::EpcisKgError;
+
+/// In-memory store and event pipeline preloaded with the ontology fixtures
+pub struct TestHarness {
+    pub store: OxigraphStore,
+    pub pipeline: EpcisEventPipeline,
+}
+
+impl TestHarness {
+    pub async fn new() -> Result<Self, EpcisKgError> {
+        Self::with_config(AppConfig::default()).await
+    }
+
+    /// Harness with a custom configuration; the fixtures replace `ontology_paths`
+    pub async fn with_config(config: AppConfig) -> Result<Self, EpcisKgError> {
+        let mut store = OxigraphStore::new_memory()?;
+        for fixture in OntologyFixture::all() {
+            fixture.load_into(&mut store)?;
+        }
+
+        let mut reasoner = OntologyReasoner::with_config(&config);
+        reasoner.load_ontology_data(&OntologyFixture::combined()?)?;
+        let pipeline = EpcisEventPipeline::new(config, store.clone(), reasoner).await?;
+
+        Ok(Self { store, pipeline })
+    }
+
+    /// Run an event through the pipeline and, if it succeeds, commit its triples to the store
+    pub async fn process(&mut self, event: EpcisEvent) -> Result<ProcessingResult, EpcisKgError> {
+        let result = self.pipeline.process_event(event.clone()).await?;
+        if result.success {
+            let triples = self.pipeline.masked_event_triples(&event)?;
+            self.store.store_event_triples(&event.event_id, &triples.public).await?;
+            if !triples.restricted.is_empty() {
+                self.store.insert_triples(&pii_graph_name(&event.event_id), &triples.restricted)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Process events in order, stopping at the first pipeline error
+    pub async fn process_all<I: IntoIterator<Item = EpcisEvent>>(&mut self, events: I) -> Result<Vec<ProcessingResult>, EpcisKgError> {
+        let mut results = Vec::new();
+        for event in events {
+            results.push(self.process(event).await?);
+        }
+        Ok(results)
+    }
+
+    /// SELECT query results as SPARQL JSON
+    pub fn query(&self, sparql: &str) -> Result<serde_json::Value, EpcisKgError> {
+        Ok(serde_json::from_str(&self.store.query_select(sparql)?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_harness_commits_processed_events() {
+        let epc = "urn:epc:id:sgtin:0614141.107346.1";
+        let mut harness = futures::executor::block_on(TestHarness::new()).unwrap();
+        let shipped = EpcisEventBuilder::object("ship-1")
+            .epc(epc)
+            .biz_step("shipping")
+            .disposition("in_transit")
+            .location("urn:epc:id:sgln:0614141.00001.0")
+            .at("2024-03-01T08:00:00Z")
+            .build();
+        assert_eq!(shipped.record_time, "2024-03-01T08:00:00Z");
+
+        let results = futures::executor::block_on(harness.process_all([shipped, EpcisEventBuilder::object("").build()])).unwrap();
+        assert!(results[0].success);
+        assert!(!results[1].success);
+        assert!(harness.store.graph_names().contains(&"urn:epcis:event:ship-1".to_string()));
+        assert!(harness.store.graph_names().contains(&OntologyFixture::CBV.graph_name()));
+    }
+}
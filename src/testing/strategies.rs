@@ -0,0 +1,128 @@
+//! Proptest strategies for events, RDF statements and SPARQL queries.
+//!
+//! The generators favour the shapes partners actually send (EPC URNs, CBV
+//! vocabulary, SELECT queries with LIMIT and VALUES) and mix in arbitrary text
+//! where a parser must cope with garbage.
+
+use crate::models::epcis::EpcisEvent;
+use crate::testing::builder::EpcisEventBuilder;
+use chrono::{DateTime, SecondsFormat};
+use oxrdf::{Literal, NamedNode, Term, Triple};
+use proptest::prelude::*;
+
+const EVENT_TYPES: &[&str] = &["ObjectEvent", "AggregationEvent", "TransactionEvent", "TransformationEvent"];
+const ACTIONS: &[&str] = &["ADD", "OBSERVE", "DELETE"];
+const BIZ_STEPS: &[&str] = &["commissioning", "shipping", "receiving", "storing", "packing", "destroying"];
+const DISPOSITIONS: &[&str] = &["active", "in_transit", "in_progress", "sellable_accessible", "destroyed"];
+const VARIABLES: &[&str] = &["s", "p", "o", "g", "name", "eventTime", "bizLocation"];
+
+/// SGTIN EPC URN such as `urn:epc:id:sgtin:0614141.107346.42`
+pub fn sgtin() -> impl Strategy<Value = String> {
+    (0u32..10_000_000, 0u32..1_000_000, any::<u32>())
+        .prop_map(|(company, item, serial)| format!("urn:epc:id:sgtin:{:07}.{:06}.{}", company, item, serial))
+}
+
+/// SGLN location URN such as `urn:epc:id:sgln:0614141.00001.0`
+pub fn sgln() -> impl Strategy<Value = String> {
+    (0u32..10_000_000, 0u32..100_000).prop_map(|(company, location)| format!("urn:epc:id:sgln:{:07}.{:05}.0", company, location))
+}
+
+/// IRIs as found in event data and ontologies
+pub fn iri() -> impl Strategy<Value = NamedNode> {
+    prop_oneof![
+        sgtin(),
+        sgln(),
+        prop::sample::select(BIZ_STEPS).prop_map(|step| format!("urn:epcglobal:cbv:bizstep:{}", step)),
+        "[a-z]{1,8}".prop_map(|name| format!("urn:epcglobal:epcis:{}", name)),
+        "[a-z]{1,8}(/[a-zA-Z0-9]{1,8}){0,3}".prop_map(|path| format!("http://example.org/{}", path)),
+    ]
+    .prop_map(NamedNode::new_unchecked)
+}
+
+/// Triples with IRI or single-token literal objects, so they also fit the store's line parser
+pub fn triple() -> impl Strategy<Value = Triple> {
+    let object = prop_oneof![
+        iri().prop_map(Term::from),
+        "[a-zA-Z0-9_.-]{0,16}".prop_map(|value| Term::from(Literal::new_simple_literal(value))),
+    ];
+    (iri(), iri(), object).prop_map(|(subject, predicate, object)| Triple::new(subject, predicate, object))
+}
+
+/// RFC 3339 UTC timestamp between 2020 and 2030
+pub fn timestamp() -> impl Strategy<Value = String> {
+    (1_577_836_800i64..1_893_456_000).prop_map(|seconds| {
+        DateTime::from_timestamp(seconds, 0)
+            .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true))
+            .unwrap_or_default()
+    })
+}
+
+/// Well-formed events of every type, with optional CBV step, disposition and location
+pub fn epcis_event() -> impl Strategy<Value = EpcisEvent> {
+    (
+        prop::sample::select(EVENT_TYPES),
+        "[a-z0-9-]{1,16}",
+        prop::sample::select(ACTIONS),
+        timestamp(),
+        prop::collection::vec(sgtin(), 1..5),
+        prop::option::of(prop::sample::select(BIZ_STEPS)),
+        prop::option::of(prop::sample::select(DISPOSITIONS)),
+        prop::option::of(sgln()),
+    )
+        .prop_map(|(event_type, event_id, action, time, epcs, biz_step, disposition, location)| {
+            let mut builder = EpcisEventBuilder::new(event_type, &event_id).action(action).at(&time);
+            builder = if event_type == "TransformationEvent" {
+                epcs.iter().fold(builder, |builder, epc| builder.input(epc))
+            } else {
+                builder.epcs(epcs)
+            };
+            if let Some(biz_step) = biz_step {
+                builder = builder.biz_step(biz_step);
+            }
+            if let Some(disposition) = disposition {
+                builder = builder.disposition(disposition);
+            }
+            if let Some(location) = location {
+                builder = builder.location(&location);
+            }
+            builder.build()
+        })
+}
+
+/// SELECT queries in the forms the endpoint supports, plus arbitrary text
+pub fn sparql_query() -> impl Strategy<Value = String> {
+    let select = (
+        prop::bool::ANY,
+        prop::collection::vec(prop::sample::select(VARIABLES), 1..4),
+        prop::option::of(0usize..100),
+        prop::option::of(prop::collection::vec(sgtin(), 1..4)),
+        prop::option::of(iri()),
+    )
+        .prop_map(|(distinct, variables, limit, values, from)| {
+            let mut query = String::from("SELECT ");
+            if distinct {
+                query.push_str("DISTINCT ");
+            }
+            for variable in &variables {
+                query.push_str(&format!("?{} ", variable));
+            }
+            if let Some(graph) = from {
+                query.push_str(&format!("FROM {} ", graph));
+            }
+            query.push_str("WHERE { ?s ?p ?o ");
+            if let Some(values) = values {
+                let terms: Vec<String> = values.iter().map(|epc| format!("<{}>", epc)).collect();
+                query.push_str(&format!("VALUES ?s {{ {} }} ", terms.join(" ")));
+            }
+            query.push('}');
+            if let Some(limit) = limit {
+                query.push_str(&format!(" LIMIT {}", limit));
+            }
+            query
+        });
+
+    prop_oneof![
+        3 => select,
+        1 => "(SELECT|ASK|CONSTRUCT|WHERE|LIMIT|VALUES|FROM|PREFIX|[{}()?<>\"' ]|\\PC){0,40}",
+    ]
+}
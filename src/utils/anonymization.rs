@@ -0,0 +1,257 @@
+use crate::config::{AnonymizationConfig, PseudonymizationRule};
+use crate::EpcisKgError;
+use hmac::{Hmac, Mac};
+use oxrdf::{Literal, NamedNode, Subject, Term, Triple, TripleRef};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// EPC URN schemes whose last component is a serial number
+const SERIALIZED_EPC_SCHEMES: &[&str] = &[
+    "sgtin", "sscc", "grai", "giai", "gsrn", "gdti", "sgcn", "cpi", "itip", "upui",
+];
+
+/// Number of HMAC bytes kept in a pseudonym (rendered as hex)
+const PSEUDONYM_BYTES: usize = 8;
+
+/// Pseudonymizes identifiers and names for sharing datasets.
+///
+/// Values are replaced by a keyed hash (HMAC-SHA256), so the same identifier
+/// always maps to the same pseudonym and the graph structure is preserved,
+/// while the original value cannot be recovered without the key.
+pub struct Pseudonymizer {
+    key: Vec<u8>,
+    config: AnonymizationConfig,
+}
+
+impl Pseudonymizer {
+    pub fn new(key: &[u8], config: AnonymizationConfig) -> Result<Self, EpcisKgError> {
+        if key.is_empty() {
+            return Err(EpcisKgError::Config("Pseudonymization key cannot be empty".to_string()));
+        }
+
+        Ok(Self {
+            key: key.to_vec(),
+            config,
+        })
+    }
+
+    /// Create a pseudonymizer using the key from the configured environment variable
+    pub fn from_config(config: AnonymizationConfig) -> Result<Self, EpcisKgError> {
+        let key = std::env::var(&config.key_env).map_err(|_| {
+            EpcisKgError::Config(format!(
+                "Pseudonymization key not found in environment variable {}",
+                config.key_env
+            ))
+        })?;
+        Self::new(key.as_bytes(), config)
+    }
+
+    /// Keyed hash of a value, truncated and hex encoded
+    pub fn pseudonym(&self, value: &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(value.as_bytes());
+        let digest = mac.finalize().into_bytes();
+
+        digest[..PSEUDONYM_BYTES]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Check whether a graph is exported without pseudonymization
+    pub fn is_excluded_graph(&self, graph_name: &str) -> bool {
+        self.config.exclude_graphs.iter().any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => graph_name.starts_with(prefix),
+            None => pattern == graph_name,
+        })
+    }
+
+    /// Pseudonymize EPC serial components and GLNs inside an identifier.
+    ///
+    /// Company prefixes and item references of EPCs are kept so that
+    /// product-level analysis remains possible; other identifiers are unchanged.
+    pub fn pseudonymize_identifier(&self, identifier: &str) -> String {
+        if let Some(body) = identifier.strip_prefix("urn:epc:id:") {
+            if let Some((scheme, value)) = body.split_once(':') {
+                return match scheme {
+                    "sgln" | "pgln" if self.config.pseudonymize_glns => {
+                        format!("urn:epc:id:{}:{}", scheme, self.pseudonymize_gln_body(value))
+                    }
+                    _ if self.config.pseudonymize_epcs && SERIALIZED_EPC_SCHEMES.contains(&scheme) => {
+                        format!("urn:epc:id:{}:{}", scheme, self.pseudonymize_serial_body(value))
+                    }
+                    _ => identifier.to_string(),
+                };
+            }
+        }
+
+        if identifier.starts_with("http://") || identifier.starts_with("https://") {
+            return self.pseudonymize_digital_link(identifier);
+        }
+
+        identifier.to_string()
+    }
+
+    /// Apply identifier and per-predicate rules to a triple; `None` drops it
+    pub fn pseudonymize_triple(&self, triple: TripleRef<'_>) -> Option<Triple> {
+        let rule = self
+            .config
+            .predicates
+            .get(triple.predicate.as_str())
+            .copied()
+            .unwrap_or(PseudonymizationRule::Keep);
+
+        if rule == PseudonymizationRule::Drop {
+            return None;
+        }
+
+        let subject: Subject = match triple.subject.into_owned() {
+            Subject::NamedNode(node) => {
+                NamedNode::new_unchecked(self.pseudonymize_identifier(node.as_str())).into()
+            }
+            // Annotated statements are pseudonymized like the statements themselves
+            Subject::Triple(quoted) => match self.pseudonymize_triple((*quoted).as_ref()) {
+                Some(quoted) => Subject::Triple(Box::new(quoted)),
+                None => return None,
+            },
+            other => other,
+        };
+
+        let object: Term = match triple.object.into_owned() {
+            Term::NamedNode(node) => {
+                let value = match rule {
+                    PseudonymizationRule::Hash | PseudonymizationRule::EntityName => {
+                        format!("urn:epcis:anon:{}", self.pseudonym(node.as_str()))
+                    }
+                    _ => self.pseudonymize_identifier(node.as_str()),
+                };
+                NamedNode::new_unchecked(value).into()
+            }
+            Term::Literal(literal) => self.pseudonymize_literal(&literal, rule).into(),
+            Term::Triple(quoted) => match self.pseudonymize_triple((*quoted).as_ref()) {
+                Some(quoted) => Term::Triple(Box::new(quoted)),
+                None => return None,
+            },
+            other => other,
+        };
+
+        Some(Triple::new(subject, triple.predicate.into_owned(), object))
+    }
+
+    fn pseudonymize_literal(&self, literal: &Literal, rule: PseudonymizationRule) -> Literal {
+        let value = literal.value();
+        match rule {
+            PseudonymizationRule::Keep | PseudonymizationRule::Drop => literal.clone(),
+            PseudonymizationRule::EpcSerial | PseudonymizationRule::Gln => {
+                Literal::new_simple_literal(self.pseudonymize_identifier(value))
+            }
+            PseudonymizationRule::EntityName => {
+                Literal::new_simple_literal(format!("entity-{}", self.pseudonym(value)))
+            }
+            PseudonymizationRule::Hash => Literal::new_simple_literal(self.pseudonym(value)),
+        }
+    }
+
+    /// `company.reference.serial` -> `company.reference.<pseudonym>`
+    fn pseudonymize_serial_body(&self, body: &str) -> String {
+        match body.rsplit_once('.') {
+            Some((class, _serial)) => format!("{}.{}", class, self.pseudonym(body)),
+            None => self.pseudonym(body),
+        }
+    }
+
+    /// `company.location.extension` -> `<pseudonym>.<pseudonym>.extension`
+    fn pseudonymize_gln_body(&self, body: &str) -> String {
+        let parts: Vec<&str> = body.split('.').collect();
+        match parts.as_slice() {
+            [company, location, extension] => format!(
+                "{}.{}.{}",
+                self.pseudonym(company),
+                self.pseudonym(&format!("{}.{}", company, location)),
+                extension
+            ),
+            _ => self.pseudonym(body),
+        }
+    }
+
+    /// Pseudonymize serial (AI 21) and GLN (AI 414, 417) segments of a GS1 Digital Link URI
+    fn pseudonymize_digital_link(&self, uri: &str) -> String {
+        let mut segments: Vec<String> = uri.split('/').map(|s| s.to_string()).collect();
+
+        for i in 0..segments.len().saturating_sub(1) {
+            let pseudonymize = match segments[i].as_str() {
+                "21" => self.config.pseudonymize_epcs,
+                "414" | "417" => self.config.pseudonymize_glns,
+                _ => false,
+            };
+            if pseudonymize && !segments[i + 1].is_empty() {
+                segments[i + 1] = self.pseudonym(&segments[i + 1]);
+            }
+        }
+
+        segments.join("/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pseudonymizer() -> Pseudonymizer {
+        Pseudonymizer::new(b"test-key", AnonymizationConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_epc_serial_is_pseudonymized_consistently() {
+        let p = pseudonymizer();
+        let epc = "urn:epc:id:sgtin:0614141.107346.2017";
+
+        let first = p.pseudonymize_identifier(epc);
+        assert!(first.starts_with("urn:epc:id:sgtin:0614141.107346."));
+        assert!(!first.ends_with(".2017"));
+        assert_eq!(first, p.pseudonymize_identifier(epc));
+    }
+
+    #[test]
+    fn test_gln_is_pseudonymized() {
+        let p = pseudonymizer();
+        let gln = p.pseudonymize_identifier("urn:epc:id:sgln:0614141.12345.0");
+
+        assert!(gln.starts_with("urn:epc:id:sgln:"));
+        assert!(!gln.contains("0614141"));
+        assert!(gln.ends_with(".0"));
+    }
+
+    #[test]
+    fn test_different_keys_give_different_pseudonyms() {
+        let a = Pseudonymizer::new(b"key-a", AnonymizationConfig::default()).unwrap();
+        let b = Pseudonymizer::new(b"key-b", AnonymizationConfig::default()).unwrap();
+
+        assert_ne!(a.pseudonym("TechCorp Manufacturing"), b.pseudonym("TechCorp Manufacturing"));
+    }
+
+    #[test]
+    fn test_predicate_rules() {
+        let p = pseudonymizer();
+        let subject = NamedNode::new_unchecked("http://example.com/manufacturer1");
+
+        let name = Triple::new(
+            subject.clone(),
+            NamedNode::new_unchecked("http://example.com/name"),
+            Literal::new_simple_literal("TechCorp Manufacturing"),
+        );
+        let anonymized = p.pseudonymize_triple(name.as_ref()).unwrap();
+        match anonymized.object {
+            Term::Literal(literal) => assert!(literal.value().starts_with("entity-")),
+            _ => panic!("expected literal object"),
+        }
+
+        let entity_type = Triple::new(
+            subject,
+            NamedNode::new_unchecked("http://example.com/entityType"),
+            Literal::new_simple_literal("Manufacturer"),
+        );
+        assert_eq!(p.pseudonymize_triple(entity_type.as_ref()).unwrap(), entity_type);
+    }
+}
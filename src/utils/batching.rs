@@ -0,0 +1,151 @@
+use crate::config::BatchingPolicy;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Decisions kept in performance metrics
+pub const DECISION_HISTORY: usize = 32;
+
+/// Batches finishing under this share of the target latency may grow
+const GROW_BELOW: f64 = 0.7;
+
+/// Batches overrunning the target latency by more than this share shrink
+const SHRINK_ABOVE: f64 = 1.2;
+
+/// Growth factor per batch while there is headroom
+const GROWTH: f64 = 1.5;
+
+/// One adjustment (or deliberate non-adjustment) of the batch size
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchDecision {
+    pub batch_size: usize,
+    pub latency_ms: u64,
+    pub memory_mb: Option<u64>,
+    pub next_size: usize,
+    pub reason: String,
+}
+
+/// Batch size controller steering towards a target latency and memory budget.
+///
+/// After each batch the size grows by half while the batch took well under
+/// the target latency, and shrinks in proportion to the overrun when it took
+/// too long. Going over the memory budget halves the size regardless of
+/// latency. A manual override pins the size until it is cleared.
+#[derive(Debug, Clone)]
+pub struct AdaptiveBatchSizer {
+    policy: BatchingPolicy,
+    size: usize,
+    override_size: Option<usize>,
+}
+
+impl AdaptiveBatchSizer {
+    pub fn new(policy: BatchingPolicy) -> Self {
+        let size = policy.initial_size.clamp(policy.min_size.max(1), policy.max_size.max(1));
+        Self {
+            policy,
+            size,
+            override_size: None,
+        }
+    }
+
+    /// Size of the next batch
+    pub fn next_size(&self) -> usize {
+        self.override_size.unwrap_or(self.size)
+    }
+
+    /// Pin the batch size, or hand control back to the controller with `None`
+    pub fn set_override(&mut self, size: Option<usize>) {
+        self.override_size = size;
+    }
+
+    pub fn override_size(&self) -> Option<usize> {
+        self.override_size
+    }
+
+    /// Feed back how long a batch of `batch_size` items took; returns the decision taken
+    pub fn record(&mut self, batch_size: usize, elapsed: Duration, memory_mb: Option<u64>) -> BatchDecision {
+        let latency_ms = elapsed.as_millis() as u64;
+        let target = self.policy.target_latency_ms.max(1) as f64;
+        let over_budget = matches!((memory_mb, self.policy.memory_budget_mb), (Some(used), Some(budget)) if used > budget);
+
+        let (proposed, reason) = if self.override_size.is_some() {
+            (self.size, "manual override".to_string())
+        } else if !self.policy.adaptive {
+            (self.size, "adaptive sizing disabled".to_string())
+        } else if over_budget {
+            (self.size / 2, format!("{} MB resident exceeds the memory budget", memory_mb.unwrap_or_default()))
+        } else if batch_size < self.size {
+            // A short final batch says little about how a full one would do
+            (self.size, "partial batch".to_string())
+        } else if latency_ms as f64 > target * SHRINK_ABOVE {
+            (
+                (self.size as f64 * target / latency_ms as f64) as usize,
+                format!("{} ms exceeds the {} ms target", latency_ms, self.policy.target_latency_ms),
+            )
+        } else if (latency_ms as f64) < target * GROW_BELOW {
+            (
+                (self.size as f64 * GROWTH).ceil() as usize,
+                format!("{} ms leaves headroom under the {} ms target", latency_ms, self.policy.target_latency_ms),
+            )
+        } else {
+            (self.size, "within target".to_string())
+        };
+        self.size = proposed.clamp(self.policy.min_size.max(1), self.policy.max_size.max(1));
+
+        BatchDecision {
+            batch_size,
+            latency_ms,
+            memory_mb,
+            next_size: self.next_size(),
+            reason,
+        }
+    }
+}
+
+/// Append a decision to a metrics history, dropping the oldest beyond `DECISION_HISTORY`
+pub fn remember(history: &mut Vec<BatchDecision>, decision: BatchDecision) {
+    if history.len() == DECISION_HISTORY {
+        history.remove(0);
+    }
+    history.push(decision);
+}
+
+/// Resident memory of this process in MB, where the platform exposes it
+pub fn resident_memory_mb() -> Option<u64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let resident_pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    // Linux pages are 4 KiB on every platform this runs on in practice
+    Some(resident_pages * 4 / 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_size_follows_latency_memory_and_override() {
+        let policy = BatchingPolicy {
+            initial_size: 100,
+            min_size: 10,
+            max_size: 400,
+            target_latency_ms: 100,
+            memory_budget_mb: Some(1_000),
+            ..BatchingPolicy::default()
+        };
+        let mut sizer = AdaptiveBatchSizer::new(policy);
+
+        assert_eq!(sizer.record(100, Duration::from_millis(20), None).next_size, 150);
+        assert_eq!(sizer.record(150, Duration::from_millis(300), None).next_size, 50);
+        assert_eq!(sizer.record(50, Duration::from_millis(90), None).next_size, 50);
+        assert_eq!(sizer.record(50, Duration::from_millis(10), Some(2_000)).next_size, 25);
+        for _ in 0..10 {
+            let size = sizer.next_size();
+            sizer.record(size, Duration::from_millis(1), None);
+        }
+        assert_eq!(sizer.next_size(), 400);
+
+        sizer.set_override(Some(64));
+        assert_eq!(sizer.record(64, Duration::from_secs(10), None).next_size, 64);
+        sizer.set_override(None);
+        assert_eq!(sizer.next_size(), 400);
+    }
+}
@@ -0,0 +1,167 @@
+use crate::config::LocalizationConfig;
+use crate::EpcisKgError;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+
+/// Language the messages in the code are written in
+pub const SOURCE_LANGUAGE: &str = "en";
+
+/// Catalogs shipped with the binary, so messages work from any working directory
+const BUNDLED_CATALOGS: &[(&str, &str)] = &[
+    ("en", include_str!("../../locales/en.toml")),
+    ("de", include_str!("../../locales/de.toml")),
+    ("es", include_str!("../../locales/es.toml")),
+];
+
+/// Languages of an `Accept-Language` header, most preferred first.
+///
+/// Tags are lowercased; `*` and ranges with `q=0` are left out. Ranges of equal
+/// weight keep their order in the header.
+pub fn accepted_languages(header: &str) -> Vec<String> {
+    let mut ranges: Vec<(String, f32)> = header
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';');
+            let tag = parts.next()?.trim().to_lowercase();
+            let weight = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (!tag.is_empty() && tag != "*" && weight > 0.0).then_some((tag, weight))
+        })
+        .collect();
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+    ranges.into_iter().map(|(tag, _)| tag).collect()
+}
+
+/// Whether a language tag falls under a range (`de` covers `de-CH`)
+pub fn language_matches(tag: &str, range: &str) -> bool {
+    let (tag, range) = (tag.to_lowercase(), range.to_lowercase());
+    tag == range || tag.strip_prefix(&range).is_some_and(|rest| rest.starts_with('-'))
+}
+
+/// User-facing messages in several languages.
+///
+/// Catalogs are TOML files of `[section]` tables of `name = "text"` entries,
+/// looked up as `section.name`. Text may hold `{placeholders}`. A message
+/// missing in a language falls back to the default language, then English,
+/// then the key itself.
+#[derive(Debug, Clone)]
+pub struct MessageCatalog {
+    default_language: String,
+    messages: HashMap<String, HashMap<String, String>>,
+}
+
+impl MessageCatalog {
+    /// Bundled catalogs with English as the default language
+    pub fn bundled() -> Self {
+        let mut catalog = Self {
+            default_language: SOURCE_LANGUAGE.to_string(),
+            messages: HashMap::new(),
+        };
+        for (language, text) in BUNDLED_CATALOGS {
+            catalog.add_catalog(language, text).expect("bundled message catalogs are valid TOML");
+        }
+        catalog
+    }
+
+    /// Bundled catalogs plus those in the configured `messages_dir`
+    pub fn from_config(config: &LocalizationConfig) -> Result<Self, EpcisKgError> {
+        let mut catalog = Self::bundled();
+        catalog.default_language = config.default_language.to_lowercase();
+        if let Some(dir) = config.messages_dir.as_deref().filter(|dir| Path::new(dir).is_dir()) {
+            for entry in std::fs::read_dir(dir)? {
+                let path = entry?.path();
+                if path.extension().and_then(|ext| ext.to_str()) != Some("toml") {
+                    continue;
+                }
+                let Some(language) = path.file_stem().and_then(|stem| stem.to_str()) else { continue };
+                catalog.add_catalog(language, &std::fs::read_to_string(&path)?)?;
+            }
+        }
+        Ok(catalog)
+    }
+
+    /// Merge a catalog into a language, replacing messages it redefines
+    pub fn add_catalog(&mut self, language: &str, text: &str) -> Result<(), EpcisKgError> {
+        let table: toml::Table = text.parse()?;
+        let messages = self.messages.entry(language.to_lowercase()).or_default();
+        for (section, entries) in table {
+            let Some(entries) = entries.as_table() else { continue };
+            for (name, value) in entries {
+                if let Some(text) = value.as_str() {
+                    messages.insert(format!("{}.{}", section, name), text.to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn default_language(&self) -> &str {
+        &self.default_language
+    }
+
+    /// Languages with a catalog, sorted
+    pub fn languages(&self) -> Vec<String> {
+        let mut languages: Vec<String> = self.messages.keys().cloned().collect();
+        languages.sort();
+        languages
+    }
+
+    /// First preferred language with a catalog (`de-CH` is served by `de`), else the default
+    pub fn negotiate(&self, preferred: &[String]) -> String {
+        preferred
+            .iter()
+            .find_map(|tag| {
+                let primary = tag.split('-').next().unwrap_or(tag);
+                [tag.as_str(), primary].into_iter().find(|language| self.messages.contains_key(*language))
+            })
+            .map(str::to_string)
+            .unwrap_or_else(|| self.default_language.clone())
+    }
+
+    /// Message `key` in `language` with its placeholders filled in
+    pub fn message(&self, language: &str, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut text = [language, self.default_language.as_str(), SOURCE_LANGUAGE]
+            .into_iter()
+            .find_map(|language| self.messages.get(language).and_then(|messages| messages.get(key)))
+            .cloned()
+            .unwrap_or_else(|| key.to_string());
+        for (name, value) in args {
+            text = text.replace(&format!("{{{}}}", name), &value.to_string());
+        }
+        text
+    }
+}
+
+impl Default for MessageCatalog {
+    fn default() -> Self {
+        Self::bundled()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_language_negotiation_and_fallback() {
+        assert_eq!(accepted_languages("fr-CH, fr;q=0.9, de;q=0.95, *;q=0.5, it;q=0"), vec!["fr-ch", "de", "fr"]);
+        assert!(language_matches("de-CH", "de"));
+        assert!(!language_matches("den", "de"));
+
+        let mut catalog = MessageCatalog::bundled();
+        assert_eq!(catalog.negotiate(&accepted_languages("fr-CH, de-AT;q=0.8")), "de");
+        assert_eq!(catalog.negotiate(&accepted_languages("ja")), "en");
+        assert_eq!(catalog.message("de", "problem.not_found", &[]), "Nicht gefunden");
+        assert_eq!(catalog.message("es", "init.initialized", &[("path", &"./data")]), "✓ Grafo de conocimiento inicializado en ./data");
+
+        // Languages added from a catalog directory fall back to English for missing messages
+        catalog.add_catalog("th", "[problem]\nnot_found = \"ไม่พบ\"\n").unwrap();
+        assert_eq!(catalog.negotiate(&["th".to_string()]), "th");
+        assert_eq!(catalog.message("th", "problem.not_found", &[]), "ไม่พบ");
+        assert_eq!(catalog.message("th", "problem.conflict", &[]), "Conflict");
+        assert_eq!(catalog.message("th", "no.such_key", &[]), "no.such_key");
+    }
+}
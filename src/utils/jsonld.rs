@@ -0,0 +1,480 @@
+use crate::config::JsonLdConfig;
+use crate::EpcisKgError;
+use oxrdf::{Subject, Term, Triple};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashSet};
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+
+/// Built-in frame returning one node per EPCIS event
+pub const EVENT_FRAME: &str = "event";
+/// Built-in frame returning one node per EPC with the events that reference it
+pub const EPC_FRAME: &str = "epc";
+
+/// Default context shared by the built-in frames
+pub fn default_context() -> Value {
+    json!({
+        "rdf": "http://www.w3.org/1999/02/22-rdf-syntax-ns#",
+        "rdfs": "http://www.w3.org/2000/01/rdf-schema#",
+        "xsd": "http://www.w3.org/2001/XMLSchema#",
+        "epcis": "urn:epcglobal:epcis:",
+        "cbv": "urn:epcglobal:cbv:",
+        "ex": "http://example.com/",
+        "eventID": "epcis:eventID",
+        "eventTime": "epcis:eventTime",
+        "recordTime": "epcis:recordTime",
+        "action": { "@id": "epcis:action", "@type": "@id" },
+        "epcList": { "@id": "epcis:epcList", "@type": "@id" },
+        "bizStep": { "@id": "epcis:bizStep", "@type": "@id" },
+        "disposition": { "@id": "epcis:disposition", "@type": "@id" },
+        "bizLocation": { "@id": "epcis:bizLocation", "@type": "@id" },
+        "readPoint": { "@id": "epcis:readPoint", "@type": "@id" }
+    })
+}
+
+/// Resolve a frame document by name.
+///
+/// Frames configured in `[jsonld.frames]` are read from disk and take
+/// precedence over the built-in `event` and `epc` frames.
+pub fn load_frame(name: &str, config: &JsonLdConfig) -> Result<Value, EpcisKgError> {
+    if let Some(path) = config.frames.get(name) {
+        let content = std::fs::read_to_string(path)?;
+        return Ok(serde_json::from_str(&content)?);
+    }
+
+    match name {
+        EVENT_FRAME => Ok(json!({
+            "@context": default_context(),
+            "@type": [
+                "epcis:ObjectEvent",
+                "epcis:AggregationEvent",
+                "epcis:QuantityEvent",
+                "epcis:TransactionEvent",
+                "epcis:TransformationEvent",
+                "epcis:Event"
+            ]
+        })),
+        EPC_FRAME => Ok(json!({
+            "@context": default_context(),
+            "@reverse": {
+                "epcList": { "@embed": "@always" }
+            }
+        })),
+        _ => Err(EpcisKgError::Validation(format!("Unknown JSON-LD frame: {}", name))),
+    }
+}
+
+/// Shapes RDF triples into compact, framed JSON-LD.
+///
+/// Supports the subset of JSON-LD 1.1 framing needed by the API: matching on
+/// `@type` and `@id`, nested property frames, `@reverse` embedding,
+/// `@explicit` and `@embed` (`@always` / `@never`). Cycles are broken by
+/// emitting a node reference instead of embedding the node again.
+pub struct JsonLdFramer {
+    context: Value,
+    prefixes: Vec<(String, String)>,
+    terms: BTreeMap<String, TermDefinition>,
+}
+
+#[derive(Debug, Clone)]
+struct TermDefinition {
+    iri: String,
+    id_valued: bool,
+}
+
+#[derive(Default)]
+struct NodeMap {
+    /// subject -> predicate -> objects
+    forward: BTreeMap<String, BTreeMap<String, Vec<Term>>>,
+    /// object -> predicate -> subjects
+    reverse: BTreeMap<String, BTreeMap<String, Vec<String>>>,
+}
+
+impl JsonLdFramer {
+    pub fn new(context: Value) -> Self {
+        let mut prefixes = Vec::new();
+        let mut raw_terms = Vec::new();
+
+        if let Value::Object(entries) = &context {
+            for (key, value) in entries {
+                match value {
+                    Value::String(iri) if iri.ends_with(['#', '/', ':']) => {
+                        prefixes.push((key.clone(), iri.clone()));
+                    }
+                    Value::String(iri) => raw_terms.push((key.clone(), iri.clone(), false)),
+                    Value::Object(definition) => {
+                        if let Some(iri) = definition.get("@id").and_then(|v| v.as_str()) {
+                            let id_valued = definition.get("@type").and_then(|v| v.as_str()) == Some("@id");
+                            raw_terms.push((key.clone(), iri.to_string(), id_valued));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // Longest namespace first so the most specific prefix wins
+        prefixes.sort_by(|a, b| b.1.len().cmp(&a.1.len()));
+
+        let mut framer = Self {
+            context,
+            prefixes,
+            terms: BTreeMap::new(),
+        };
+
+        for (term, iri, id_valued) in raw_terms {
+            let iri = framer.expand_iri(&iri);
+            framer.terms.insert(term, TermDefinition { iri, id_valued });
+        }
+
+        framer
+    }
+
+    /// Frame triples with a frame document, returning `{"@context", "@graph"}`
+    pub fn frame(triples: &[Triple], frame: &Value) -> Result<Value, EpcisKgError> {
+        let frame_object = frame
+            .as_object()
+            .ok_or_else(|| EpcisKgError::Validation("JSON-LD frame must be an object".to_string()))?;
+        let context = frame_object.get("@context").cloned().unwrap_or_else(default_context);
+        let framer = Self::new(context);
+        let nodes = NodeMap::from_triples(triples);
+
+        let graph: Vec<Value> = nodes
+            .subjects()
+            .into_iter()
+            .filter(|id| framer.matches(&nodes, id, frame_object))
+            .map(|id| framer.frame_node(&nodes, &id, frame_object, &mut Vec::new()))
+            .collect();
+
+        Ok(json!({
+            "@context": framer.context,
+            "@graph": graph
+        }))
+    }
+
+    /// Expand a compact IRI or term to an absolute IRI
+    pub fn expand_iri(&self, value: &str) -> String {
+        if let Some(definition) = self.terms.get(value) {
+            return definition.iri.clone();
+        }
+        if let Some((prefix, local)) = value.split_once(':') {
+            if let Some((_, namespace)) = self.prefixes.iter().find(|(p, _)| p == prefix) {
+                return format!("{}{}", namespace, local);
+            }
+        }
+        value.to_string()
+    }
+
+    /// Compact an absolute IRI using the context's terms and prefixes
+    pub fn compact_iri(&self, iri: &str) -> String {
+        if let Some((term, _)) = self.terms.iter().find(|(_, d)| d.iri == iri) {
+            return term.clone();
+        }
+        for (prefix, namespace) in &self.prefixes {
+            if let Some(local) = iri.strip_prefix(namespace.as_str()) {
+                if !local.is_empty() {
+                    return format!("{}:{}", prefix, local);
+                }
+            }
+        }
+        iri.to_string()
+    }
+
+    fn matches(&self, nodes: &NodeMap, id: &str, frame: &Map<String, Value>) -> bool {
+        if let Some(frame_ids) = frame.get("@id") {
+            let ids = string_list(frame_ids);
+            if !ids.is_empty() && !ids.iter().any(|candidate| self.expand_iri(candidate) == id) {
+                return false;
+            }
+        }
+
+        if let Some(frame_types) = frame.get("@type") {
+            let types: Vec<String> = string_list(frame_types).iter().map(|t| self.expand_iri(t)).collect();
+            if !types.is_empty() {
+                let node_types = nodes.objects(id, RDF_TYPE);
+                let has_type = node_types
+                    .iter()
+                    .any(|t| matches!(t, Term::NamedNode(n) if types.iter().any(|ty| ty == n.as_str())));
+                if !has_type {
+                    return false;
+                }
+            }
+        }
+
+        // Frames that only describe reverse properties select nodes that are referenced that way
+        if !frame.contains_key("@type") && !frame.contains_key("@id") {
+            if let Some(Value::Object(reverse)) = frame.get("@reverse") {
+                return reverse
+                    .keys()
+                    .any(|property| !nodes.referrers(id, &self.expand_iri(property)).is_empty());
+            }
+        }
+
+        true
+    }
+
+    fn frame_node(&self, nodes: &NodeMap, id: &str, frame: &Map<String, Value>, stack: &mut Vec<String>) -> Value {
+        let mut output = Map::new();
+        output.insert("@id".to_string(), Value::String(self.compact_id(id)));
+
+        if stack.iter().any(|s| s == id) {
+            return Value::Object(output);
+        }
+        stack.push(id.to_string());
+
+        let explicit = frame.get("@explicit").and_then(|v| v.as_bool()).unwrap_or(false);
+        let property_frames: BTreeMap<String, &Value> = frame
+            .iter()
+            .filter(|(key, _)| !key.starts_with('@'))
+            .map(|(key, value)| (self.expand_iri(key), value))
+            .collect();
+
+        if let Some(properties) = nodes.forward.get(id) {
+            for (predicate, objects) in properties {
+                let sub_frame = property_frames.get(predicate).and_then(|v| v.as_object());
+                if explicit && !property_frames.contains_key(predicate) {
+                    continue;
+                }
+
+                if predicate == RDF_TYPE {
+                    let types: Vec<Value> = objects
+                        .iter()
+                        .map(|t| Value::String(self.compact_id(&term_id(t))))
+                        .collect();
+                    output.insert("@type".to_string(), single_or_array(types));
+                    continue;
+                }
+
+                let key = self.compact_iri(predicate);
+                let id_valued = self.terms.get(&key).map(|d| d.id_valued).unwrap_or(false);
+                let values: Vec<Value> = objects
+                    .iter()
+                    .map(|object| self.frame_value(nodes, object, sub_frame, id_valued, stack))
+                    .collect();
+                output.insert(key, single_or_array(values));
+            }
+        }
+
+        if let Some(Value::Object(reverse_frames)) = frame.get("@reverse") {
+            let mut reverse_output = Map::new();
+            for (property, sub_frame) in reverse_frames {
+                let referrers = nodes.referrers(id, &self.expand_iri(property));
+                let sub_frame = sub_frame.as_object().cloned().unwrap_or_default();
+                let values: Vec<Value> = referrers
+                    .iter()
+                    .filter(|referrer| self.matches(nodes, referrer, &sub_frame))
+                    .map(|referrer| self.embed(nodes, referrer, &sub_frame, stack))
+                    .collect();
+                if !values.is_empty() {
+                    reverse_output.insert(property.clone(), single_or_array(values));
+                }
+            }
+            if !reverse_output.is_empty() {
+                output.insert("@reverse".to_string(), Value::Object(reverse_output));
+            }
+        }
+
+        stack.pop();
+        Value::Object(output)
+    }
+
+    fn frame_value(
+        &self,
+        nodes: &NodeMap,
+        object: &Term,
+        sub_frame: Option<&Map<String, Value>>,
+        id_valued: bool,
+        stack: &mut Vec<String>,
+    ) -> Value {
+        match object {
+            Term::Literal(literal) => {
+                let datatype = literal.datatype().as_str();
+                if let Some(language) = literal.language() {
+                    json!({ "@value": literal.value(), "@language": language })
+                } else if datatype == format!("{}string", XSD) {
+                    Value::String(literal.value().to_string())
+                } else if datatype == format!("{}boolean", XSD) {
+                    literal.value().parse::<bool>().map(Value::Bool).unwrap_or_else(|_| json!(literal.value()))
+                } else if datatype == format!("{}integer", XSD) {
+                    literal.value().parse::<i64>().map(|n| json!(n)).unwrap_or_else(|_| json!(literal.value()))
+                } else {
+                    json!({ "@value": literal.value(), "@type": self.compact_iri(datatype) })
+                }
+            }
+            _ => {
+                let id = term_id(object);
+                match sub_frame {
+                    Some(frame) if nodes.forward.contains_key(&id) => self.embed(nodes, &id, frame, stack),
+                    _ if id_valued => Value::String(self.compact_id(&id)),
+                    _ => json!({ "@id": self.compact_id(&id) }),
+                }
+            }
+        }
+    }
+
+    fn embed(&self, nodes: &NodeMap, id: &str, frame: &Map<String, Value>, stack: &mut Vec<String>) -> Value {
+        if frame.get("@embed").and_then(|v| v.as_str()) == Some("@never") {
+            return json!({ "@id": self.compact_id(id) });
+        }
+        self.frame_node(nodes, id, frame, stack)
+    }
+
+    fn compact_id(&self, id: &str) -> String {
+        if id.starts_with("_:") {
+            id.to_string()
+        } else {
+            self.compact_iri(id)
+        }
+    }
+}
+
+impl NodeMap {
+    fn from_triples(triples: &[Triple]) -> Self {
+        let mut nodes = NodeMap::default();
+
+        for triple in triples {
+            let subject = match &triple.subject {
+                Subject::NamedNode(node) => node.as_str().to_string(),
+                Subject::BlankNode(node) => format!("_:{}", node.as_str()),
+                // Statements about quoted triples have no JSON-LD 1.1 node representation
+                Subject::Triple(_) => continue,
+            };
+            let predicate = triple.predicate.as_str().to_string();
+
+            if !matches!(triple.object, Term::Literal(_)) {
+                nodes
+                    .reverse
+                    .entry(term_id(&triple.object))
+                    .or_default()
+                    .entry(predicate.clone())
+                    .or_default()
+                    .push(subject.clone());
+            }
+
+            nodes
+                .forward
+                .entry(subject)
+                .or_default()
+                .entry(predicate)
+                .or_default()
+                .push(triple.object.clone());
+        }
+
+        nodes
+    }
+
+    /// All node identifiers: subjects and referenced IRIs
+    fn subjects(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.forward.keys().cloned().collect();
+        let known: HashSet<&String> = self.forward.keys().collect();
+        ids.extend(self.reverse.keys().filter(|id| !known.contains(id)).cloned());
+        ids.sort();
+        ids
+    }
+
+    fn objects(&self, id: &str, predicate: &str) -> Vec<Term> {
+        self.forward
+            .get(id)
+            .and_then(|properties| properties.get(predicate))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn referrers(&self, id: &str, predicate: &str) -> Vec<String> {
+        self.reverse
+            .get(id)
+            .and_then(|properties| properties.get(predicate))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+fn term_id(term: &Term) -> String {
+    match term {
+        Term::NamedNode(node) => node.as_str().to_string(),
+        Term::BlankNode(node) => format!("_:{}", node.as_str()),
+        other => other.to_string(),
+    }
+}
+
+fn string_list(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(s) => vec![s.clone()],
+        Value::Array(items) => items.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn single_or_array(mut values: Vec<Value>) -> Value {
+    if values.len() == 1 {
+        values.remove(0)
+    } else {
+        Value::Array(values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxrdf::{Literal, NamedNode};
+
+    fn event_triples() -> Vec<Triple> {
+        let event = NamedNode::new_unchecked("urn:epc:event:evt-1");
+        vec![
+            Triple::new(
+                event.clone(),
+                NamedNode::new_unchecked(RDF_TYPE),
+                NamedNode::new_unchecked("urn:epcglobal:epcis:ObjectEvent"),
+            ),
+            Triple::new(
+                event.clone(),
+                NamedNode::new_unchecked("urn:epcglobal:epcis:eventID"),
+                Literal::new_simple_literal("evt-1"),
+            ),
+            Triple::new(
+                event.clone(),
+                NamedNode::new_unchecked("urn:epcglobal:epcis:epcList"),
+                NamedNode::new_unchecked("urn:epc:id:sgtin:0614141.107346.2017"),
+            ),
+            Triple::new(
+                event,
+                NamedNode::new_unchecked("urn:epcglobal:epcis:epcList"),
+                NamedNode::new_unchecked("urn:epc:id:sgtin:0614141.107346.2018"),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_event_frame() {
+        let frame = load_frame(EVENT_FRAME, &JsonLdConfig::default()).unwrap();
+        let framed = JsonLdFramer::frame(&event_triples(), &frame).unwrap();
+
+        let graph = framed["@graph"].as_array().unwrap();
+        assert_eq!(graph.len(), 1);
+        assert_eq!(graph[0]["@type"], "epcis:ObjectEvent");
+        assert_eq!(graph[0]["eventID"], "evt-1");
+        assert_eq!(graph[0]["epcList"].as_array().unwrap().len(), 2);
+        assert_eq!(graph[0]["epcList"][0], "urn:epc:id:sgtin:0614141.107346.2017");
+    }
+
+    #[test]
+    fn test_epc_frame_embeds_referencing_events() {
+        let frame = load_frame(EPC_FRAME, &JsonLdConfig::default()).unwrap();
+        let framed = JsonLdFramer::frame(&event_triples(), &frame).unwrap();
+
+        let graph = framed["@graph"].as_array().unwrap();
+        assert_eq!(graph.len(), 2);
+        assert_eq!(graph[0]["@id"], "urn:epc:id:sgtin:0614141.107346.2017");
+        assert_eq!(graph[0]["@reverse"]["epcList"]["eventID"], "evt-1");
+    }
+
+    #[test]
+    fn test_compact_and_expand_iri() {
+        let framer = JsonLdFramer::new(default_context());
+
+        assert_eq!(framer.compact_iri("urn:epcglobal:cbv:shipping"), "cbv:shipping");
+        assert_eq!(framer.compact_iri("urn:epcglobal:epcis:eventTime"), "eventTime");
+        assert_eq!(framer.expand_iri("bizStep"), "urn:epcglobal:epcis:bizStep");
+    }
+}
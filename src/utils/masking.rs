@@ -0,0 +1,163 @@
+use crate::config::{MaskingAction, PiiMaskingConfig};
+use crate::EpcisKgError;
+use hmac::{Hmac, Mac};
+use oxrdf::{Literal, Term, Triple};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Prefix of the graphs holding the unmasked values of an event.
+///
+/// Graph ACLs only grant these graphs through patterns that name them, so an
+/// API key allowed to read `*` still sees masked values only.
+pub const PII_GRAPH_PREFIX: &str = "urn:epcis:pii:";
+
+/// Value left in place of a redacted field
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Number of HMAC bytes kept in a masked value (rendered as hex)
+const MASK_BYTES: usize = 8;
+
+/// Access-restricted graph for the unmasked values of an event
+pub fn pii_graph_name(event_id: &str) -> String {
+    format!("{}{}", PII_GRAPH_PREFIX, event_id)
+}
+
+/// Event triples split by masking
+#[derive(Debug, Clone, Default)]
+pub struct MaskedTriples {
+    /// Triples for the event graph, with personal data masked
+    pub public: Vec<Triple>,
+    /// Original triples of every masked or dropped value, for the PII graph
+    pub restricted: Vec<Triple>,
+}
+
+/// Applies masking policies to personal data in event triples at capture.
+///
+/// The first policy whose predicate pattern (an IRI, or a prefix ending in
+/// `*`) matches a triple decides what the event graph keeps: a keyed hash, so
+/// equal values still join, a redaction marker, or nothing.
+pub struct PiiMasker {
+    key: Option<Vec<u8>>,
+    config: PiiMaskingConfig,
+}
+
+impl PiiMasker {
+    /// `None` when masking is disabled; the key is read from `key_env` when a policy hashes
+    pub fn from_config(config: &PiiMaskingConfig) -> Result<Option<Self>, EpcisKgError> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let key = if config.policies.iter().any(|policy| policy.action == MaskingAction::Hash) {
+            let key = std::env::var(&config.key_env).map_err(|_| {
+                EpcisKgError::Config(format!("PII masking key not found in environment variable {}", config.key_env))
+            })?;
+            if key.is_empty() {
+                return Err(EpcisKgError::Config("PII masking key cannot be empty".to_string()));
+            }
+            Some(key.into_bytes())
+        } else {
+            None
+        };
+        Ok(Some(Self { key, config: config.clone() }))
+    }
+
+    /// Policy action for a predicate, if any policy covers it
+    pub fn action_for(&self, predicate: &str) -> Option<MaskingAction> {
+        self.config
+            .policies
+            .iter()
+            .find(|policy| match policy.predicate.strip_suffix('*') {
+                Some(prefix) => predicate.starts_with(prefix),
+                None => policy.predicate == predicate,
+            })
+            .map(|policy| policy.action)
+    }
+
+    pub fn mask(&self, triples: Vec<Triple>) -> MaskedTriples {
+        let mut masked = MaskedTriples::default();
+        for triple in triples {
+            let Some(action) = self.action_for(triple.predicate.as_str()) else {
+                masked.public.push(triple);
+                continue;
+            };
+            let replacement = match action {
+                MaskingAction::Hash => Some(self.hash(&object_value(&triple.object))),
+                MaskingAction::Redact => Some(REDACTED.to_string()),
+                MaskingAction::Drop => None,
+            };
+            if let Some(value) = replacement {
+                masked.public.push(Triple::new(
+                    triple.subject.clone(),
+                    triple.predicate.clone(),
+                    Literal::new_simple_literal(value),
+                ));
+            }
+            masked.restricted.push(triple);
+        }
+        masked
+    }
+
+    fn hash(&self, value: &str) -> String {
+        let key = self.key.as_deref().unwrap_or_default();
+        let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+        mac.update(value.as_bytes());
+        let digest = mac.finalize().into_bytes();
+        let hex: String = digest[..MASK_BYTES].iter().map(|byte| format!("{:02x}", byte)).collect();
+        format!("masked:{}", hex)
+    }
+}
+
+fn object_value(object: &Term) -> String {
+    match object {
+        Term::NamedNode(node) => node.as_str().to_string(),
+        Term::Literal(literal) => literal.value().to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MaskingPolicy;
+    use oxrdf::NamedNode;
+
+    #[test]
+    fn test_policies_mask_and_keep_originals_apart() {
+        let config = PiiMaskingConfig {
+            enabled: true,
+            policies: vec![
+                MaskingPolicy { predicate: "https://ns.example.com/epcis/operatorName".to_string(), action: MaskingAction::Hash },
+                MaskingPolicy { predicate: "https://ns.example.com/epcis/customer*".to_string(), action: MaskingAction::Redact },
+                MaskingPolicy { predicate: "https://ns.example.com/epcis/badgeId".to_string(), action: MaskingAction::Drop },
+            ],
+            ..PiiMaskingConfig::default()
+        };
+        let masker = PiiMasker { key: Some(b"test-key".to_vec()), config };
+        let field = |name: &str, value: &str| {
+            Triple::new(
+                NamedNode::new_unchecked("urn:epc:event:1"),
+                NamedNode::new_unchecked(format!("https://ns.example.com/epcis/{}", name)),
+                Literal::new_simple_literal(value),
+            )
+        };
+        let triples = vec![
+            field("operatorName", "Jane Doe"),
+            field("customerEmail", "jane@example.com"),
+            field("badgeId", "B-1234"),
+            field("temperature", "4.5"),
+        ];
+
+        let masked = masker.mask(triples);
+        assert_eq!(masked.public.len(), 3);
+        assert_eq!(masked.restricted.len(), 3);
+        let values: Vec<String> = masked.public.iter().map(|triple| object_value(&triple.object)).collect();
+        assert!(values[0].starts_with("masked:"));
+        assert_eq!(values[0], masker.hash("Jane Doe"));
+        assert_eq!(values[1], REDACTED);
+        assert_eq!(values[2], "4.5");
+        assert!(!masked.public.iter().any(|triple| triple.to_string().contains("Jane Doe")));
+        assert!(masked.restricted.iter().any(|triple| triple.to_string().contains("Jane Doe")));
+        assert_eq!(pii_graph_name("1"), "urn:epcis:pii:1");
+    }
+}
@@ -1,2 +1,10 @@
+pub mod anonymization;
+pub mod batching;
 pub mod conversion;
+pub mod fuzzing;
+pub mod i18n;
+pub mod jsonld;
+pub mod masking;
+pub mod resilience;
+pub mod signing;
 pub mod validation;
\ No newline at end of file
@@ -0,0 +1,220 @@
+use crate::config::ResiliencePolicy;
+use crate::EpcisKgError;
+use parking_lot::Mutex;
+use std::future::Future;
+use std::io::Write;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Why one attempt at an external call failed
+#[derive(Debug, Clone, PartialEq)]
+pub enum Failure {
+    /// Worth retrying: timeouts, refused connections, 5xx answers, full queues
+    Transient(String),
+    /// Retrying cannot help, e.g. the remote side rejected the payload
+    Permanent(String),
+}
+
+#[derive(Debug)]
+enum BreakerState {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    /// The open period is over and trial calls decide whether to close again
+    HalfOpen,
+}
+
+/// Retries, circuit breaker and dead-letter file guarding one external
+/// integration (a webhook URL, a Kafka producer, ...).
+///
+/// Transient failures are retried with exponential backoff and jitter. A call
+/// that still fails counts towards the breaker; after `failure_threshold` such
+/// calls in a row the breaker opens and calls fail fast for `open_seconds`.
+/// Payloads of calls that fail for good, or are refused by an open breaker,
+/// are appended to the policy's dead-letter file.
+#[derive(Debug)]
+pub struct Resilience {
+    name: String,
+    policy: ResiliencePolicy,
+    state: Mutex<BreakerState>,
+}
+
+impl Resilience {
+    pub fn new(name: impl Into<String>, policy: ResiliencePolicy) -> Self {
+        Self {
+            name: name.into(),
+            policy,
+            state: Mutex::new(BreakerState::Closed { failures: 0 }),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether calls are currently rejected without being attempted
+    pub fn is_open(&self) -> bool {
+        matches!(*self.state.lock(), BreakerState::Open { until } if Instant::now() < until)
+    }
+
+    /// Delay before retry number `retry` (1 for the first retry)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(32) as i32;
+        let base = (self.policy.initial_backoff_ms as f64 * self.policy.backoff_multiplier.powi(exponent))
+            .min(self.policy.max_backoff_ms as f64);
+        let jitter = base * self.policy.jitter * (rand::random::<f64>() * 2.0 - 1.0);
+        Duration::from_millis((base + jitter).max(0.0) as u64)
+    }
+
+    /// Run an async operation under the policy; `payload` is what gets dead-lettered
+    pub async fn call<T, F, Fut>(&self, payload: &serde_json::Value, mut operation: F) -> Result<T, EpcisKgError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, Failure>>,
+    {
+        self.admit(payload)?;
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Ok(value) => return Ok(self.succeeded(value)),
+                Err(Failure::Transient(_)) if attempt < self.policy.max_attempts => {
+                    tokio::time::sleep(self.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                Err(failure) => return Err(self.give_up(payload, failure)),
+            }
+        }
+    }
+
+    /// As `call`, for synchronous operations; sleeps the calling thread between attempts
+    pub fn call_blocking<T>(
+        &self,
+        payload: &serde_json::Value,
+        mut operation: impl FnMut() -> Result<T, Failure>,
+    ) -> Result<T, EpcisKgError> {
+        self.admit(payload)?;
+        let mut attempt = 1;
+        loop {
+            match operation() {
+                Ok(value) => return Ok(self.succeeded(value)),
+                Err(Failure::Transient(_)) if attempt < self.policy.max_attempts => {
+                    std::thread::sleep(self.backoff(attempt));
+                    attempt += 1;
+                }
+                Err(failure) => return Err(self.give_up(payload, failure)),
+            }
+        }
+    }
+
+    /// Fail fast while the breaker is open
+    fn admit(&self, payload: &serde_json::Value) -> Result<(), EpcisKgError> {
+        let mut state = self.state.lock();
+        if let BreakerState::Open { until } = *state {
+            let now = Instant::now();
+            if now < until {
+                drop(state);
+                let message = format!("Circuit breaker for {} is open", self.name);
+                self.dead_letter(payload, &message);
+                return Err(EpcisKgError::Unavailable {
+                    message,
+                    retry_after_secs: Some(until.duration_since(now).as_secs().max(1)),
+                });
+            }
+            *state = BreakerState::HalfOpen;
+        }
+        Ok(())
+    }
+
+    fn succeeded<T>(&self, value: T) -> T {
+        *self.state.lock() = BreakerState::Closed { failures: 0 };
+        value
+    }
+
+    fn give_up(&self, payload: &serde_json::Value, failure: Failure) -> EpcisKgError {
+        let error = match failure {
+            // A rejected payload says nothing about the health of the remote side
+            Failure::Permanent(message) => EpcisKgError::Generic(format!("{} rejected the call: {}", self.name, message).into()),
+            Failure::Transient(message) => {
+                let mut state = self.state.lock();
+                let failures = match *state {
+                    BreakerState::Closed { failures } => failures + 1,
+                    _ => self.policy.failure_threshold,
+                };
+                *state = if failures >= self.policy.failure_threshold {
+                    warn!("Opening circuit breaker for {} after {} failed calls", self.name, failures);
+                    BreakerState::Open { until: Instant::now() + Duration::from_secs(self.policy.open_seconds) }
+                } else {
+                    BreakerState::Closed { failures }
+                };
+                EpcisKgError::Unavailable {
+                    message: format!("{} failed after {} attempts: {}", self.name, self.policy.max_attempts, message),
+                    retry_after_secs: None,
+                }
+            }
+        };
+        self.dead_letter(payload, &error.to_string());
+        error
+    }
+
+    /// Append an undeliverable payload to the dead-letter file as one JSON line
+    fn dead_letter(&self, payload: &serde_json::Value, error: &str) {
+        let Some(path) = &self.policy.dead_letter_path else { return };
+        let line = serde_json::json!({
+            "integration": self.name,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+            "error": error,
+            "payload": payload,
+        });
+        let written = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "{}", line));
+        if let Err(e) = written {
+            warn!("Failed to dead-letter a {} payload to {}: {}", self.name, path, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retries_open_the_breaker_and_dead_letter() {
+        let dir = tempfile::tempdir().unwrap();
+        let dead_letters = dir.path().join("dead_letters.jsonl");
+        let policy = ResiliencePolicy {
+            max_attempts: 3,
+            initial_backoff_ms: 1,
+            max_backoff_ms: 2,
+            failure_threshold: 2,
+            dead_letter_path: Some(dead_letters.to_string_lossy().into_owned()),
+            ..ResiliencePolicy::default()
+        };
+        let resilience = Resilience::new("test", policy);
+        let payload = serde_json::json!({ "event": "excursion" });
+
+        let mut attempts = 0;
+        let result = resilience.call_blocking(&payload, || {
+            attempts += 1;
+            if attempts < 3 { Err(Failure::Transient("timeout".to_string())) } else { Ok(attempts) }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        for _ in 0..2 {
+            let failed: Result<(), _> = resilience.call_blocking(&payload, || Err(Failure::Transient("refused".to_string())));
+            assert!(failed.unwrap_err().is_retryable());
+        }
+        assert!(resilience.is_open());
+        let mut called = false;
+        let rejected: Result<(), _> = resilience.call_blocking(&payload, || {
+            called = true;
+            Ok(())
+        });
+        assert!(matches!(rejected, Err(EpcisKgError::Unavailable { retry_after_secs: Some(_), .. })));
+        assert!(!called);
+
+        // Two exhausted calls and one refused by the open breaker
+        assert_eq!(std::fs::read_to_string(&dead_letters).unwrap().lines().count(), 3);
+    }
+}
@@ -0,0 +1,236 @@
+use crate::config::SigningConfig;
+use crate::storage::canonical::canonical_nquads;
+use crate::EpcisKgError;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use ed25519_dalek::{Signer, Verifier};
+use hmac::{Hmac, Mac};
+use oxrdf::Quad;
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Media type of a verifiable credential secured with JOSE
+pub const CREDENTIAL_MEDIA_TYPE: &str = "application/vc+jwt";
+
+enum SigningKey {
+    Ed25519(ed25519_dalek::SigningKey),
+    Hmac(Vec<u8>),
+}
+
+/// Signs documents sent to partners.
+///
+/// RDF data is signed over its canonical N-Quads (RDFC-1.0) as a detached
+/// JWS (RFC 7515, appendix F): the payload is left out of the compact form and
+/// the receiver recomputes it from the data, so the signature holds however
+/// the data was serialized in transit. Traces can also be issued as W3C
+/// Verifiable Credentials secured with JOSE (`vc+jwt`).
+pub struct DocumentSigner {
+    key: SigningKey,
+    config: SigningConfig,
+}
+
+impl DocumentSigner {
+    /// `key` is the 32-byte Ed25519 seed for `EdDSA` or the shared secret for `HS256`
+    pub fn new(key: &[u8], config: SigningConfig) -> Result<Self, EpcisKgError> {
+        let key = match config.algorithm.as_str() {
+            "EdDSA" => {
+                let seed: [u8; 32] = key
+                    .try_into()
+                    .map_err(|_| EpcisKgError::Config(format!("Ed25519 signing key must be 32 bytes, got {}", key.len())))?;
+                SigningKey::Ed25519(ed25519_dalek::SigningKey::from_bytes(&seed))
+            }
+            "HS256" if key.is_empty() => return Err(EpcisKgError::Config("Signing secret cannot be empty".to_string())),
+            "HS256" => SigningKey::Hmac(key.to_vec()),
+            other => return Err(EpcisKgError::Config(format!("Unsupported signing algorithm: {}", other))),
+        };
+
+        Ok(Self { key, config })
+    }
+
+    /// Create a signer with the key from the configured environment variable
+    /// (base64url for Ed25519 seeds)
+    pub fn from_config(config: SigningConfig) -> Result<Self, EpcisKgError> {
+        let value = std::env::var(&config.key_env)
+            .map_err(|_| EpcisKgError::Config(format!("Signing key not found in environment variable {}", config.key_env)))?;
+        let key = match config.algorithm.as_str() {
+            "EdDSA" => URL_SAFE_NO_PAD
+                .decode(value.trim().trim_end_matches('='))
+                .map_err(|e| EpcisKgError::Config(format!("Signing key in {} is not base64url: {}", config.key_env, e)))?,
+            _ => value.into_bytes(),
+        };
+        Self::new(&key, config)
+    }
+
+    /// Public key as a JWK for partners to verify with; `None` for shared secrets
+    pub fn public_jwk(&self) -> Option<Value> {
+        let SigningKey::Ed25519(key) = &self.key else { return None };
+        let mut jwk = json!({
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "alg": "EdDSA",
+            "use": "sig",
+            "x": URL_SAFE_NO_PAD.encode(key.verifying_key().as_bytes()),
+        });
+        if let Some(key_id) = &self.config.key_id {
+            jwk["kid"] = Value::from(key_id.as_str());
+        }
+        Some(jwk)
+    }
+
+    /// Detached JWS over the canonical N-Quads of a dataset
+    pub fn sign_dataset(&self, quads: &[Quad]) -> Result<String, EpcisKgError> {
+        Ok(self.sign_detached(canonical_nquads(quads)?.as_bytes()))
+    }
+
+    /// Check a detached JWS against the canonical N-Quads of a dataset
+    pub fn verify_dataset(&self, jws: &str, quads: &[Quad]) -> Result<(), EpcisKgError> {
+        self.verify_detached(jws, canonical_nquads(quads)?.as_bytes())
+    }
+
+    /// Compact JWS with the payload left out (`header..signature`)
+    pub fn sign_detached(&self, payload: &[u8]) -> String {
+        let header = self.encoded_header(json!({}));
+        let signature = self.signature(format!("{}.{}", header, URL_SAFE_NO_PAD.encode(payload)).as_bytes());
+        format!("{}..{}", header, signature)
+    }
+
+    pub fn verify_detached(&self, jws: &str, payload: &[u8]) -> Result<(), EpcisKgError> {
+        let (header, signature) = jws
+            .split_once("..")
+            .ok_or_else(|| EpcisKgError::Validation("Not a detached JWS".to_string()))?;
+        self.check_header(header)?;
+        self.check_signature(format!("{}.{}", header, URL_SAFE_NO_PAD.encode(payload)).as_bytes(), signature)
+    }
+
+    /// Wrap `subject` (e.g. a framed trace) in a verifiable credential, secured as a `vc+jwt`
+    pub fn issue_credential(&self, subject: Value, credential_type: &str) -> Result<String, EpcisKgError> {
+        if self.config.issuer.is_empty() {
+            return Err(EpcisKgError::Config("Issuing credentials needs [signing] issuer".to_string()));
+        }
+        let credential = json!({
+            "@context": ["https://www.w3.org/ns/credentials/v2"],
+            "id": format!("urn:uuid:{}", uuid::Uuid::new_v4()),
+            "type": ["VerifiableCredential", credential_type],
+            "issuer": self.config.issuer,
+            "validFrom": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            "credentialSubject": subject,
+        });
+
+        let header = self.encoded_header(json!({ "typ": "vc+jwt", "cty": "vc" }));
+        let input = format!("{}.{}", header, URL_SAFE_NO_PAD.encode(serde_json::to_vec(&credential)?));
+        let signature = self.signature(input.as_bytes());
+        Ok(format!("{}.{}", input, signature))
+    }
+
+    /// The credential in a `vc+jwt`, once its signature checks out
+    pub fn verify_credential(&self, jwt: &str) -> Result<Value, EpcisKgError> {
+        let mut parts = jwt.split('.');
+        let (Some(header), Some(payload), Some(signature), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return Err(EpcisKgError::Validation("Not a compact JWS".to_string()));
+        };
+        self.check_header(header)?;
+        self.check_signature(format!("{}.{}", header, payload).as_bytes(), signature)?;
+        Ok(serde_json::from_slice(&decode(payload)?)?)
+    }
+
+    fn algorithm(&self) -> &'static str {
+        match self.key {
+            SigningKey::Ed25519(_) => "EdDSA",
+            SigningKey::Hmac(_) => "HS256",
+        }
+    }
+
+    /// `alg` and `kid` merged into `extra`, base64url encoded
+    fn encoded_header(&self, mut extra: Value) -> String {
+        extra["alg"] = Value::from(self.algorithm());
+        if let Some(key_id) = &self.config.key_id {
+            extra["kid"] = Value::from(key_id.as_str());
+        }
+        URL_SAFE_NO_PAD.encode(extra.to_string())
+    }
+
+    /// Reject headers naming another algorithm, so a signature cannot be checked with the wrong primitive
+    fn check_header(&self, header: &str) -> Result<(), EpcisKgError> {
+        let header: Value = serde_json::from_slice(&decode(header)?)?;
+        match header["alg"].as_str() {
+            Some(alg) if alg == self.algorithm() => Ok(()),
+            other => Err(EpcisKgError::Validation(format!("Unexpected JWS algorithm: {:?}", other))),
+        }
+    }
+
+    fn signature(&self, input: &[u8]) -> String {
+        let bytes = match &self.key {
+            SigningKey::Ed25519(key) => key.sign(input).to_bytes().to_vec(),
+            SigningKey::Hmac(secret) => {
+                let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+                mac.update(input);
+                mac.finalize().into_bytes().to_vec()
+            }
+        };
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    fn check_signature(&self, input: &[u8], signature: &str) -> Result<(), EpcisKgError> {
+        let signature = decode(signature)?;
+        let valid = match &self.key {
+            SigningKey::Ed25519(key) => ed25519_dalek::Signature::from_slice(&signature)
+                .is_ok_and(|signature| key.verifying_key().verify(input, &signature).is_ok()),
+            SigningKey::Hmac(secret) => {
+                let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+                mac.update(input);
+                mac.verify_slice(&signature).is_ok()
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(EpcisKgError::Validation("JWS signature does not match".to_string()))
+        }
+    }
+}
+
+fn decode(part: &str) -> Result<Vec<u8>, EpcisKgError> {
+    URL_SAFE_NO_PAD
+        .decode(part)
+        .map_err(|e| EpcisKgError::Validation(format!("Invalid base64url in JWS: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxrdf::{GraphName, Literal, NamedNode};
+
+    #[test]
+    fn test_signatures_round_trip_and_detect_tampering() {
+        let quad = |value: &str| {
+            Quad::new(
+                NamedNode::new_unchecked("urn:epc:id:sgtin:0614141.107346.2017"),
+                NamedNode::new_unchecked("urn:epcglobal:cbv:mda:lotNumber"),
+                Literal::new_simple_literal(value),
+                GraphName::DefaultGraph,
+            )
+        };
+        let config = SigningConfig {
+            key_id: Some("did:web:example.com#key-1".to_string()),
+            issuer: "did:web:example.com".to_string(),
+            ..SigningConfig::default()
+        };
+        let signer = DocumentSigner::new(&[7; 32], config.clone()).unwrap();
+
+        let jws = signer.sign_dataset(&[quad("LOT1")]).unwrap();
+        assert!(jws.contains(".."));
+        assert!(signer.verify_dataset(&jws, &[quad("LOT1")]).is_ok());
+        assert!(signer.verify_dataset(&jws, &[quad("LOT2")]).is_err());
+        assert_eq!(signer.public_jwk().unwrap()["kid"], "did:web:example.com#key-1");
+
+        let shared = DocumentSigner::new(b"secret", SigningConfig { algorithm: "HS256".to_string(), ..config }).unwrap();
+        let credential = shared.issue_credential(json!({ "id": "urn:epc:id:sgtin:0614141.107346.2017" }), "EpcisTraceCredential").unwrap();
+        let verified = shared.verify_credential(&credential).unwrap();
+        assert_eq!(verified["issuer"], "did:web:example.com");
+        assert_eq!(verified["type"][1], "EpcisTraceCredential");
+        // An Ed25519 signer must not accept an HS256 token
+        assert!(signer.verify_credential(&credential).is_err());
+    }
+}
@@ -15,7 +15,8 @@ impl Validator {
             errors.push("Event ID cannot be empty".to_string());
         }
         
-        if event.epc_list.is_empty() {
+        // TransformationEvents may carry only input and output lists
+        if event.epc_list.is_empty() && event.input_epc_list.is_empty() && event.output_epc_list.is_empty() {
             errors.push("EPC list cannot be empty".to_string());
         }
         
@@ -60,6 +61,14 @@ mod tests {
             biz_step: Some("commissioning".to_string()),
             disposition: Some("active".to_string()),
             biz_location: Some("urn:epc:id:sgln:123456.789.0".to_string()),
+            source_list: Vec::new(),
+            destination_list: Vec::new(),
+            input_epc_list: Vec::new(),
+            output_epc_list: Vec::new(),
+            ilmd: None,
+            sensor_element_list: Vec::new(),
+            extensions: Default::default(),
+            event_time_zone_offset: None,
         };
 
         let result = validator.validate_epcis_event(&event);
@@ -122,6 +131,14 @@ mod tests {
             biz_step: None,
             disposition: None,
             biz_location: None,
+            source_list: Vec::new(),
+            destination_list: Vec::new(),
+            input_epc_list: Vec::new(),
+            output_epc_list: Vec::new(),
+            ilmd: None,
+            sensor_element_list: Vec::new(),
+            extensions: Default::default(),
+            event_time_zone_offset: None,
         };
 
         let result = validator.validate_epcis_event(&event);
@@ -0,0 +1,95 @@
+//! Golden-file conformance suite for EPCIS 2.0 documents.
+//!
+//! Each `tests/conformance/<name>.jsonld` document, modelled on the GS1 EPCIS
+//! 2.0 examples, is ingested through the event pipeline and its event graphs
+//! are compared with `<name>.nq` (canonical N-Quads). `queries.json` lists
+//! simple event queries run against all documents together, with the expected
+//! event IDs in event-time order.
+//!
+//! Set `EPCIS_KG_BLESS=1` to rewrite the `.nq` files after an intended
+//! mapping change, then review the diff.
+
+use epcis_knowledge_graph::models::document::parse_epcis_document;
+use epcis_knowledge_graph::models::epcis::EpcisEvent;
+use epcis_knowledge_graph::storage::canonical::canonical_nquads;
+use epcis_knowledge_graph::storage::epcis_query::EpcisQuery;
+use epcis_knowledge_graph::testing::TestHarness;
+use oxrdf::NamedNode;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const DOCUMENTS: &[&str] = &["object_events", "aggregation_event", "transformation_event", "sensor_event"];
+
+#[derive(Deserialize)]
+struct QueryCase {
+    name: String,
+    params: HashMap<String, String>,
+    expected: Vec<String>,
+}
+
+fn fixture_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("conformance")
+}
+
+fn document_events(name: &str) -> Vec<EpcisEvent> {
+    let path = fixture_dir().join(format!("{}.jsonld", name));
+    let document: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    parse_epcis_document(&document).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+async fn ingest(harness: &mut TestHarness, events: Vec<EpcisEvent>) {
+    for event in events {
+        let event_id = event.event_id.clone();
+        let result = harness.process(event).await.unwrap();
+        assert!(result.success, "event {} was rejected: {:?}", event_id, result);
+    }
+}
+
+/// Canonical N-Quads of the events' graphs
+fn event_quads(harness: &TestHarness, events: &[EpcisEvent]) -> String {
+    let mut quads = Vec::new();
+    for event in events {
+        let graph = format!("urn:epcis:event:{}", event.event_id);
+        let graph_name = NamedNode::new(graph.as_str()).unwrap();
+        for triple in harness.store.union_graph(std::slice::from_ref(&graph)).iter() {
+            quads.push(triple.into_owned().in_graph(graph_name.clone()));
+        }
+    }
+    canonical_nquads(&quads).unwrap()
+}
+
+#[tokio::test]
+async fn test_documents_match_golden_quads() {
+    let bless = std::env::var_os("EPCIS_KG_BLESS").is_some();
+
+    for name in DOCUMENTS {
+        let mut harness = TestHarness::new().await.unwrap();
+        let events = document_events(name);
+        ingest(&mut harness, events.clone()).await;
+
+        let actual = event_quads(&harness, &events);
+        let golden = fixture_dir().join(format!("{}.nq", name));
+        if bless {
+            std::fs::write(&golden, &actual).unwrap();
+            continue;
+        }
+        let expected = std::fs::read_to_string(&golden).unwrap();
+        assert_eq!(actual, expected, "{} differs from {}", name, golden.display());
+    }
+}
+
+#[tokio::test]
+async fn test_standard_queries() {
+    let mut harness = TestHarness::new().await.unwrap();
+    for name in DOCUMENTS {
+        ingest(&mut harness, document_events(name)).await;
+    }
+
+    let cases: Vec<QueryCase> =
+        serde_json::from_str(&std::fs::read_to_string(fixture_dir().join("queries.json")).unwrap()).unwrap();
+    for case in cases {
+        let query = EpcisQuery::from_params(&case.params).unwrap();
+        assert_eq!(harness.store.query_events(&query), case.expected, "query {}", case.name);
+    }
+}
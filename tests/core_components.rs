@@ -104,6 +104,14 @@ fn test_epcis_event_validation() {
         biz_step: Some("commissioning".to_string()),
         disposition: Some("active".to_string()),
         biz_location: Some("urn:epc:id:sgln:0614141.00777.0".to_string()),
+        source_list: Vec::new(),
+        destination_list: Vec::new(),
+        input_epc_list: Vec::new(),
+        output_epc_list: Vec::new(),
+        ilmd: None,
+        sensor_element_list: Vec::new(),
+        extensions: Default::default(),
+        event_time_zone_offset: None,
     };
     
     assert!(!valid_event.event_id.is_empty());
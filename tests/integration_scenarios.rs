@@ -0,0 +1,15 @@
+//! End-to-end scenarios: `cargo test --features integration --test integration_scenarios`.
+//! Set `EPCIS_KG_IT_URL` to run them against an already running server.
+#![cfg(feature = "integration")]
+
+use epcis_knowledge_graph::testing::integration::IntegrationServer;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_capture_query_trace_and_inference_scenarios() {
+    let server = IntegrationServer::start().await.expect("server under test");
+    let outcomes = server.run_scenarios().await;
+
+    assert_eq!(outcomes.len(), 4);
+    let failed: Vec<_> = outcomes.iter().filter(|outcome| !outcome.passed()).collect();
+    assert!(failed.is_empty(), "failed scenarios against {}: {:#?}", server.base_url, failed);
+}
@@ -0,0 +1,44 @@
+use epcis_knowledge_graph::models::epcis::EpcisEvent;
+use epcis_knowledge_graph::storage::oxigraph_store::OxigraphStore;
+use epcis_knowledge_graph::testing::strategies;
+use epcis_knowledge_graph::utils::fuzzing::{fuzz_sparql, fuzz_turtle};
+use epcis_knowledge_graph::utils::validation::Validator;
+use proptest::prelude::*;
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn turtle_parsers_never_panic(data in prop::collection::vec(any::<u8>(), 0..512)) {
+        let _ = fuzz_turtle(&data);
+    }
+
+    #[test]
+    fn turtle_text_never_panics(text in "(<|>|\"|@prefix|:|\\.| |\n|[a-z]|\\PC){0,80}") {
+        let _ = fuzz_turtle(text.as_bytes());
+    }
+
+    #[test]
+    fn sparql_front_end_never_panics(query in strategies::sparql_query()) {
+        let _ = fuzz_sparql(query.as_bytes());
+    }
+
+    #[test]
+    fn stored_triples_round_trip(triples in prop::collection::vec(strategies::triple(), 0..20)) {
+        let document: String = triples.iter().map(|triple| format!("{} .\n", triple)).collect();
+        let mut store = OxigraphStore::new_memory().unwrap();
+        store.store_ontology_turtle(&document, "urn:epcis:test").unwrap();
+
+        let stored = store.triples_in_graphs("urn:epcis:test");
+        for triple in &triples {
+            prop_assert!(stored.contains(triple), "missing {}", triple);
+        }
+    }
+
+    #[test]
+    fn generated_events_are_valid_and_serializable(event in strategies::epcis_event()) {
+        prop_assert!(Validator::new().validate_epcis_event(&event).is_ok());
+        let json = serde_json::to_string(&event).unwrap();
+        prop_assert_eq!(serde_json::from_str::<EpcisEvent>(&json).unwrap(), event);
+    }
+}
@@ -22,6 +22,14 @@ mod performance_benchmarks {
             biz_step: Some("commissioning".to_string()),
             disposition: Some("active".to_string()),
             biz_location: Some("urn:epc:id:sgln:123456.789.0".to_string()),
+            source_list: Vec::new(),
+            destination_list: Vec::new(),
+            input_epc_list: Vec::new(),
+            output_epc_list: Vec::new(),
+            ilmd: None,
+            sensor_element_list: Vec::new(),
+            extensions: Default::default(),
+            event_time_zone_offset: None,
         }
     }
 
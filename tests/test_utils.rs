@@ -1,24 +1,17 @@
 use serde::{Deserialize, Serialize};
 use epcis_knowledge_graph::models::epcis::EpcisEvent;
+use epcis_knowledge_graph::testing::EpcisEventBuilder;
 
 pub mod test_data {
     use super::*;
 
     pub fn sample_epcis_event() -> EpcisEvent {
-        EpcisEvent {
-            event_id: "test-event-001".to_string(),
-            event_type: "ObjectEvent".to_string(),
-            event_time: "2024-01-01T00:00:00Z".to_string(),
-            record_time: "2024-01-01T00:00:00Z".to_string(),
-            event_action: "ADD".to_string(),
-            epc_list: vec![
-                "urn:epc:id:sgtin:123456.789.100".to_string(),
-                "urn:epc:id:sgtin:123456.789.101".to_string(),
-            ],
-            biz_step: Some("commissioning".to_string()),
-            disposition: Some("active".to_string()),
-            biz_location: Some("urn:epc:id:sgln:123456.789.0".to_string()),
-        }
+        EpcisEventBuilder::object("test-event-001")
+            .epcs(["urn:epc:id:sgtin:123456.789.100", "urn:epc:id:sgtin:123456.789.101"])
+            .biz_step("commissioning")
+            .disposition("active")
+            .location("urn:epc:id:sgln:123456.789.0")
+            .build()
     }
 
     pub fn invalid_epcis_event_empty_id() -> EpcisEvent {